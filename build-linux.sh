@@ -45,8 +45,7 @@ serde_json = "1.0"
 serde_yaml = "0.9"
 anyhow = "1.0"
 thiserror = "1.0"
-log = "0.4"
-env_logger = "0.10"
+log = { version = "0.4", features = ["std", "kv"] }
 clap = { version = "4.0", features = ["derive"] }
 regex = "1.0"
 sysinfo = "0.30"
@@ -77,6 +76,7 @@ pub mod cache;
 pub mod cli;
 pub mod command_line;
 pub mod console_app;
+pub mod desktop_session;
 pub mod endpoint_monitor;
 pub mod file_monitor;
 pub mod orchestrator;
@@ -0,0 +1,182 @@
+//! `--adopt`: take servers that were started by hand into the orchestration state, so
+//! `--status` reports them and `--down` stops them like services `--up` started. Listening
+//! processes are matched against the services in the orchestration config and the ones
+//! `--detect` finds, by port, working directory and command line.
+
+use crate::service_state::ServiceState;
+use crate::types::ProcessInfo;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// A service a listening process may belong to
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub name: String,
+    pub port: Option<u16>,
+    pub dir: PathBuf,
+    pub command: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adoption {
+    pub service: String,
+    pub pid: i32,
+    pub port: u16,
+    /// What matched, e.g. "port, directory"
+    pub matched: Vec<&'static str>,
+}
+
+/// Words that start a command without saying what it runs
+const RUNNERS: [&str; 12] = [
+    "npm", "npx", "pnpm", "yarn", "bun", "run", "exec", "sh", "-c", "python", "python3", "node",
+];
+
+/// Pair each process with at most one candidate and the other way round. A process matches
+/// on its port, or on working directory and command line together when the candidate's
+/// port isn't known. Better matches are taken first.
+pub fn match_processes(candidates: &[Candidate], processes: &[ProcessInfo]) -> Vec<Adoption> {
+    let mut scored = Vec::new();
+    for candidate in candidates {
+        for process in processes {
+            let mut matched = Vec::new();
+            if candidate.port == Some(process.port) {
+                matched.push("port");
+            }
+            if process
+                .working_directory
+                .as_deref()
+                .is_some_and(|cwd| same_dir(Path::new(cwd), &candidate.dir))
+            {
+                matched.push("directory");
+            }
+            if process
+                .command_line
+                .as_deref()
+                .is_some_and(|command_line| command_matches(&candidate.command, command_line))
+            {
+                matched.push("command");
+            }
+            let port_known_elsewhere = candidate.port.is_some() && !matched.contains(&"port");
+            let enough = matched.contains(&"port")
+                || (!port_known_elsewhere && matched.contains(&"directory") && matched.contains(&"command"));
+            if enough {
+                scored.push((matched.len(), candidate, process, matched));
+            }
+        }
+    }
+    // Stable, so ties keep the candidates' order (config services before detected ones)
+    scored.sort_by_key(|(score, ..)| std::cmp::Reverse(*score));
+
+    let mut adoptions: Vec<Adoption> = Vec::new();
+    for (_, candidate, process, matched) in scored {
+        let taken = adoptions
+            .iter()
+            .any(|a| a.service == candidate.name || a.pid == process.pid);
+        if !taken {
+            adoptions.push(Adoption {
+                service: candidate.name.clone(),
+                pid: process.pid,
+                port: process.port,
+                matched,
+            });
+        }
+    }
+    adoptions
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Whether the words that say what `command` runs (past npm/run/node and the like) all
+/// appear in the process's command line
+fn command_matches(command: &str, command_line: &str) -> bool {
+    let words: Vec<String> = crate::command_line::parse_command_line(command)
+        .into_iter()
+        .filter(|word| !RUNNERS.contains(&word.as_str()) && !word.contains('='))
+        .collect();
+    !words.is_empty() && words.iter().all(|word| command_line.contains(word.as_str()))
+}
+
+/// Record each adoption in the orchestration state, started when the process was
+pub fn register(adoptions: &[Adoption], candidates: &[Candidate]) -> Result<()> {
+    let mut state = ServiceState::load();
+    let mut system = sysinfo::System::new();
+    for adoption in adoptions {
+        let pid = sysinfo::Pid::from_u32(adoption.pid as u32);
+        system.refresh_process(pid);
+        let started_at = system
+            .process(pid)
+            .and_then(|process| chrono::DateTime::from_timestamp(process.start_time() as i64, 0))
+            .unwrap_or_else(chrono::Utc::now);
+        let command = candidates
+            .iter()
+            .find(|c| c.name == adoption.service)
+            .map(|c| c.command.clone())
+            .unwrap_or_default();
+        state.record_adopted(&adoption.service, adoption.pid as u32, &command, started_at)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: i32, port: u16, cwd: &str, command_line: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            port,
+            command: "node".to_string(),
+            name: "node".to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: Some(command_line.to_string()),
+            working_directory: Some(cwd.to_string()),
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        }
+    }
+
+    #[test]
+    fn test_match_processes() {
+        let candidate = |name: &str, port: Option<u16>, dir: &str, command: &str| Candidate {
+            name: name.to_string(),
+            port,
+            dir: PathBuf::from(dir),
+            command: command.to_string(),
+        };
+        let candidates = vec![
+            candidate("web", Some(3000), "/srv/app/web", "npm run dev"),
+            candidate("api", None, "/srv/app/api", "node server.js"),
+            candidate("npm:dev", Some(3000), "/srv/app/web", "npm run dev"),
+            candidate("worker", Some(9000), "/srv/app/worker", "node worker.js"),
+        ];
+        let processes = vec![
+            process(10, 3000, "/srv/app/web", "node /srv/app/web/node_modules/.bin/next dev"),
+            process(20, 4123, "/srv/app/api", "node server.js"),
+            // Right directory and command, but not the port the worker is configured for
+            process(30, 9999, "/srv/app/worker", "node worker.js"),
+        ];
+
+        let adoptions = match_processes(&candidates, &processes);
+        assert_eq!(adoptions.len(), 2);
+        assert_eq!(adoptions[0].service, "web");
+        assert_eq!(adoptions[0].matched, vec!["port", "directory", "command"]);
+        assert_eq!(adoptions[1].service, "api");
+        assert_eq!((adoptions[1].pid, adoptions[1].port), (20, 4123));
+    }
+}
@@ -0,0 +1,410 @@
+//! `--serve`: the scanner over HTTP, for dashboards, editor plugins and remote machines.
+//! Every endpoint but `/metrics` returns JSON:
+//!
+//! | Endpoint | Returns |
+//! |----------|---------|
+//! | `GET /processes` | a scan (`output.snapshot`) |
+//! | `POST /kill/:port` | one `output.kill` result per process killed |
+//! | `GET /history` | kill history, oldest first (`?limit=N` for the last N) |
+//! | `GET /status` | version, uptime, monitored ports and the last scan's health |
+//! | `POST /restart/:port` | kills the process and starts its saved command again |
+//! | `GET /events` | Server-Sent Events: a `snapshot`, then `appeared`/`disappeared`/`killed` |
+//! | `GET /metrics` | port-kill's own memory, uptime and history size, in Prometheus text format |
+//!
+//! Errors come back as `{"error": "..."}` with a 4xx/5xx status. Every request needs the
+//! bearer token: `--serve-token`, or one generated and printed at startup. Requests from a
+//! web page on another origin are refused, and on localhost so are those for another host
+//! name, so a page the user visits can't reach the API through their browser.
+
+use crate::cli::Args;
+use crate::process_monitor::ProcessMonitor;
+use crate::types::{KillResult, PortEvent, ScanSnapshot};
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::Mutex;
+
+/// Address `--serve` binds to when none is given
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7777";
+
+/// Changes a slow `/events` client may fall behind by before it is told it missed some
+const EVENT_BUFFER: usize = 256;
+
+#[derive(Clone)]
+struct ApiState {
+    monitor: Arc<Mutex<ProcessMonitor>>,
+    port_description: String,
+    token: Arc<str>,
+    /// Listening on localhost only: requests must name it as their host
+    loopback: bool,
+    started_at: DateTime<Utc>,
+    events: broadcast::Sender<PortEvent>,
+    max_rss: Option<u64>,
+}
+
+/// `--serve` address: `PORT`, `HOST:PORT` or `[V6]:PORT`
+pub fn parse_addr(addr: &str) -> Result<SocketAddr> {
+    if let Ok(port) = addr.parse::<u16>() {
+        return Ok(SocketAddr::from(([127, 0, 0, 1], port)));
+    }
+    addr.parse()
+        .with_context(|| format!("Invalid --serve address '{}' (expected PORT or HOST:PORT)", addr))
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+    }
+}
+
+/// Whether `host` (a `Host` header or an origin's authority, port optional) is this machine
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Check a request's bearer token, and that it isn't a browser on another origin or, when
+/// serving on localhost, a rebound DNS name
+fn authorize(headers: &HeaderMap, token: &str, loopback: bool) -> Result<(), ApiError> {
+    let text = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let host = text(header::HOST);
+    if loopback && host.is_some_and(|host| !is_loopback_host(host)) {
+        return Err(ApiError(
+            StatusCode::FORBIDDEN,
+            "requests must be addressed to localhost".into(),
+        ));
+    }
+    if let Some(origin) = text(header::ORIGIN) {
+        let authority = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+        if !is_loopback_host(authority) && Some(authority) != host {
+            return Err(ApiError(
+                StatusCode::FORBIDDEN,
+                format!("requests from {} are not allowed", origin),
+            ));
+        }
+    }
+    let presented = text(header::AUTHORIZATION).and_then(|value| value.strip_prefix("Bearer "));
+    if presented != Some(token) {
+        return Err(ApiError(
+            StatusCode::UNAUTHORIZED,
+            "missing or wrong bearer token".into(),
+        ));
+    }
+    Ok(())
+}
+
+async fn require_token<B>(
+    State(state): State<ApiState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if let Err(e) = authorize(request.headers(), &state.token, state.loopback) {
+        return e.into_response();
+    }
+    next.run(request).await
+}
+
+async fn processes(State(state): State<ApiState>) -> Result<Json<ScanSnapshot>, ApiError> {
+    let processes = state.monitor.lock().await.scan_processes().await?;
+    Ok(Json(ScanSnapshot::new(
+        &processes,
+        crate::scan_health::last_scan(),
+    )))
+}
+
+async fn kill(
+    State(state): State<ApiState>,
+    Path(port): Path<u16>,
+) -> Result<(StatusCode, Json<Vec<KillResult>>), ApiError> {
+    let mut monitor = state.monitor.lock().await;
+    let processes = monitor.scan_processes().await?;
+    let Some(process) = processes.get(&port).cloned() else {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("No process is listening on port {}", port),
+        ));
+    };
+    let result = monitor.kill_process(process.pid).await;
+    let status = if result.is_ok() {
+        StatusCode::OK
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    let line = KillResult::new(process.pid, Some(port), Some(&process.name), &result);
+    // No subscribers is not an error
+    let _ = state.events.send(PortEvent::Killed {
+        result: line.clone(),
+    });
+    Ok((status, Json(vec![line])))
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+    limit: Option<usize>,
+}
+
+async fn history(
+    State(state): State<ApiState>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<Vec<crate::types::ProcessHistoryEntry>> {
+    let monitor = state.monitor.lock().await;
+    let entries = match query.limit {
+        Some(limit) => monitor.get_recent_history(limit),
+        None => monitor.get_history().get_entries(),
+    };
+    Json(entries.to_vec())
+}
+
+#[derive(Serialize)]
+struct ServerStatus {
+    version: &'static str,
+    started_at: DateTime<Utc>,
+    uptime_secs: u64,
+    monitoring: String,
+    demo: bool,
+    #[serde(flatten)]
+    last_scan: crate::scan_health::ScanStatusLine,
+}
+
+async fn status(State(state): State<ApiState>) -> Json<ServerStatus> {
+    Json(ServerStatus {
+        version: env!("CARGO_PKG_VERSION"),
+        started_at: state.started_at,
+        uptime_secs: (Utc::now() - state.started_at).num_seconds().max(0) as u64,
+        monitoring: state.port_description.clone(),
+        demo: crate::demo::is_enabled(),
+        last_scan: crate::scan_health::ScanStatusLine::from(&crate::scan_health::last_scan()),
+    })
+}
+
+/// `GET /metrics`: the server's own footprint, for scraping alongside what it watches
+async fn metrics(State(state): State<ApiState>) -> impl IntoResponse {
+    let history_entries = state.monitor.lock().await.get_history().get_entries().len();
+    let uptime = (Utc::now() - state.started_at).num_seconds().max(0);
+    let mut body = crate::memory::check(state.max_rss).to_prometheus();
+    for (name, help, value) in [
+        ("port_kill_uptime_seconds", "Seconds since the server started", uptime),
+        ("port_kill_history_entries", "Kill history entries held in memory", history_entries as i64),
+    ] {
+        body.push_str(&format!("# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"));
+    }
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+async fn restart(
+    State(state): State<ApiState>,
+    Path(port): Path<u16>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut monitor = state.monitor.lock().await;
+    if !monitor.get_restart_manager().can_restart(port) {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("No saved command to restart port {}; kill it through port-kill first", port),
+        ));
+    }
+    // Refresh so the process currently on the port is the one that gets killed
+    monitor.scan_processes().await?;
+    monitor.restart_process_on_port(port).await?;
+    Ok(Json(serde_json::json!({ "port": port, "status": "restarted" })))
+}
+
+/// `GET /events`: the current scan as a `snapshot` event, then every change as it is seen
+async fn events(
+    State(state): State<ApiState>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    // Subscribe first so nothing between the snapshot and the stream is lost
+    let receiver = state.events.subscribe();
+    let processes = state.monitor.lock().await.scan_processes().await?;
+    let snapshot = Event::default()
+        .event("snapshot")
+        .json_data(ScanSnapshot::new(&processes, crate::scan_health::last_scan()))
+        .map_err(anyhow::Error::new)?;
+
+    let changes = stream::unfold(receiver, |mut receiver| async move {
+        let event = match receiver.recv().await {
+            Ok(change) => Event::default()
+                .event(change.name())
+                .json_data(&change)
+                .unwrap_or_default(),
+            // The client should resync from /processes or by reconnecting
+            Err(RecvError::Lagged(missed)) => Event::default()
+                .event("lagged")
+                .data(format!("{{\"missed\":{}}}", missed)),
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok(event), receiver))
+    });
+    Ok(Sse::new(stream::once(async { Ok(snapshot) }).chain(changes)).keep_alive(KeepAlive::default()))
+}
+
+/// Scan every `interval` and publish what changed to `/events`
+async fn watch_changes(state: ApiState, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut previous = None;
+    loop {
+        ticker.tick().await;
+        let current = match state.monitor.lock().await.scan_processes().await {
+            Ok(current) => current,
+            Err(e) => {
+                log::debug!("Event scan failed: {:#}", e);
+                continue;
+            }
+        };
+        crate::timeline::record(&current);
+        if let Some(previous) = &previous {
+            for change in PortEvent::between(previous, &current) {
+                let _ = state.events.send(change);
+            }
+        }
+        previous = Some(current);
+    }
+}
+
+/// Warn when the server itself grows past `--max-rss`
+async fn watch_memory(max_rss: Option<u64>) {
+    let mut ticker = tokio::time::interval(crate::memory::CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        crate::memory::check(max_rss);
+    }
+}
+
+fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/processes", get(processes))
+        .route("/kill/:port", post(kill))
+        .route("/history", get(history))
+        .route("/status", get(status))
+        .route("/restart/:port", post(restart))
+        .route("/events", get(events))
+        .route("/metrics", get(metrics))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+/// Serve the API on `addr` until the process is stopped
+pub async fn serve(
+    addr: SocketAddr,
+    token: Option<String>,
+    monitor: Arc<Mutex<ProcessMonitor>>,
+    args: &Args,
+) -> Result<()> {
+    let generated = token.is_none();
+    let token = match token {
+        Some(token) => token,
+        None => crate::token::random_token()?,
+    };
+    let state = ApiState {
+        monitor,
+        port_description: args.get_port_description(),
+        token: token.as_str().into(),
+        loopback: addr.ip().is_loopback(),
+        started_at: Utc::now(),
+        events: broadcast::channel(EVENT_BUFFER).0,
+        max_rss: args.max_rss_bytes(),
+    };
+    tokio::spawn(watch_changes(
+        state.clone(),
+        Duration::from_secs(args.scan_interval.max(1)),
+    ));
+
+    tokio::spawn(watch_memory(state.max_rss));
+
+    let server = axum::Server::try_bind(&addr)
+        .with_context(|| format!("Could not listen on {}", addr))?
+        .serve(router(state).into_make_service());
+    println!("🌐 Serving the port-kill API on http://{}", server.local_addr());
+    if generated {
+        println!("   Token: {} (send Authorization: Bearer <token>, or set --serve-token)", token);
+    }
+    println!("   GET /processes · POST /kill/:port · GET /history · GET /status · POST /restart/:port · GET /events · GET /metrics");
+    server.await.context("API server stopped")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_serve_address() {
+        assert_eq!(parse_addr("8080").unwrap(), "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(parse_addr("0.0.0.0:7777").unwrap().port(), 7777);
+        assert!(parse_addr("[::1]:7777").unwrap().ip().is_loopback());
+        assert!(parse_addr("localhost").is_err());
+    }
+
+    #[test]
+    fn test_authorize() {
+        let headers = |pairs: &[(header::HeaderName, &str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in pairs {
+                map.insert(name.clone(), value.parse().unwrap());
+            }
+            map
+        };
+        let bearer = (header::AUTHORIZATION, "Bearer s3cret");
+        let local = (header::HOST, "127.0.0.1:7777");
+
+        assert!(authorize(&headers(&[bearer.clone(), local.clone()]), "s3cret", true).is_ok());
+        assert!(authorize(
+            &headers(&[(header::HOST, "localhost:7777"), bearer.clone()]),
+            "s3cret",
+            true
+        )
+        .is_ok());
+        assert!(authorize(
+            &headers(&[(header::HOST, "[::1]:7777"), bearer.clone()]),
+            "s3cret",
+            true
+        )
+        .is_ok());
+        // No token, or the wrong one
+        let no_token = authorize(&headers(std::slice::from_ref(&local)), "s3cret", true);
+        assert!(no_token.is_err_and(|e| e.0 == StatusCode::UNAUTHORIZED));
+        assert!(authorize(
+            &headers(&[local.clone(), (header::AUTHORIZATION, "Bearer nope")]),
+            "s3cret",
+            true
+        )
+        .is_err());
+        // DNS rebinding: evil.example resolving to 127.0.0.1
+        let rebound = headers(&[(header::HOST, "evil.example:7777"), bearer.clone()]);
+        assert!(authorize(&rebound, "s3cret", true).is_err_and(|e| e.0 == StatusCode::FORBIDDEN));
+        // ... which is fine when serving beyond localhost on purpose
+        assert!(authorize(&rebound, "s3cret", false).is_ok());
+        // A page on another origin, even with the token
+        let cross = headers(&[
+            local.clone(),
+            bearer.clone(),
+            (header::ORIGIN, "https://evil.example"),
+        ]);
+        assert!(authorize(&cross, "s3cret", true).is_err_and(|e| e.0 == StatusCode::FORBIDDEN));
+        let same = headers(&[local, bearer, (header::ORIGIN, "http://localhost:3000")]);
+        assert!(authorize(&same, "s3cret", true).is_ok());
+    }
+}
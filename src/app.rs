@@ -1,8 +1,14 @@
-use crate::{cli::Args, tray_menu::TrayMenu, types::{ProcessInfo, StatusBarInfo}};
+use crate::{
+    cli::Args,
+    hotkeys::HotkeyAction,
+    icon_theme::IconTheme,
+    tray_menu::{TrayAction, TrayMenu},
+    types::{ProcessInfo, StatusBarInfo},
+};
 use anyhow::Result;
 use crossbeam_channel::{bounded, Receiver};
 use log::{error, info, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
@@ -18,7 +24,8 @@ pub struct PortKillApp {
     tray_menu: TrayMenu,
     args: Args,
     current_processes: Arc<StdMutex<HashMap<u16, crate::types::ProcessInfo>>>,
-    menu_id_to_port: Arc<StdMutex<HashMap<String, u16>>>,
+    /// Process names added to the ignore list from the tray since it started
+    ignored_names: Arc<StdMutex<HashSet<String>>>,
 }
 
 #[cfg(target_os = "macos")]
@@ -28,7 +35,10 @@ impl PortKillApp {
         let (menu_sender, menu_event_receiver) = bounded(100);
 
         // Create tray menu
-        let tray_menu = TrayMenu::new(menu_sender)?;
+        let tray_menu = TrayMenu::new(
+            menu_sender,
+            IconTheme::configured(args.icon_theme.as_deref()),
+        )?;
 
         Ok(Self {
             tray_icon: Arc::new(StdMutex::new(None)),
@@ -36,7 +46,7 @@ impl PortKillApp {
             tray_menu,
             args,
             current_processes: Arc::new(StdMutex::new(HashMap::new())),
-            menu_id_to_port: Arc::new(StdMutex::new(HashMap::new())),
+            ignored_names: Arc::new(StdMutex::new(HashSet::new())),
         })
     }
 
@@ -49,10 +59,27 @@ impl PortKillApp {
         // Now create the tray icon after the event loop is created
         info!("Creating tray icon...");
         let initial_menu = self.tray_menu.get_current_menu()?;
+
+        // Global hotkeys are registered on the main thread, before the event loop runs
+        let hotkeys = match crate::hotkeys::Hotkeys::register() {
+            Ok(hotkeys) => Some(hotkeys),
+            Err(e) => {
+                warn!("{:#}", e);
+                None
+            }
+        };
+        let tooltip = match hotkeys
+            .as_ref()
+            .and_then(|hotkeys| hotkeys.accelerator_for(&HotkeyAction::ShowProcesses))
+        {
+            Some(accelerator) => format!("Port Kill - Development Port Monitor ({} lists processes)", accelerator),
+            None => "Port Kill - Development Port Monitor".to_string(),
+        };
         let tray_icon = TrayIconBuilder::new()
-            .with_tooltip("Port Kill - Development Port Monitor (Click or press Cmd+Shift+P)")
+            .with_tooltip(tooltip)
             .with_menu(Box::new(initial_menu))
             .with_icon(self.tray_menu.icon.clone())
+            .with_icon_as_template(self.tray_menu.theme.is_template())
             .build()?;
 
         info!("Tray icon created successfully!");
@@ -65,6 +92,7 @@ impl PortKillApp {
         // For now, let's manually check for processes every 5 seconds in the event loop
         let tray_icon = self.tray_icon.clone();
         let mut last_check = std::time::Instant::now();
+        let mut last_memory_check = std::time::Instant::now();
         let mut last_process_count = 0;
         let mut last_menu_update = std::time::Instant::now();
         let is_killing_processes = Arc::new(AtomicBool::new(false));
@@ -73,7 +101,7 @@ impl PortKillApp {
 
         // Give the tray icon time to appear
         info!("Waiting for tray icon to appear...");
-        println!("🔍 Look for a white square with red/green center in your status bar!");
+        println!("🔍 Look for a round badge with the process count in your status bar!");
         println!("   It should be in the top-right area of your screen.");
         println!(
             "💡 When in full-screen mode, use console mode: ./run.sh --console --ports 3000,8000"
@@ -82,8 +110,10 @@ impl PortKillApp {
         // Set up menu event handling
         let menu_event_receiver = self.menu_event_receiver.clone();
         let current_processes = self.current_processes.clone();
-        let menu_id_to_port = self.menu_id_to_port.clone();
+        let ignored_names = self.ignored_names.clone();
         let args = self.args.clone();
+        let icon_theme = self.tray_menu.theme;
+        let mut resource_sampler = crate::system_monitor::ResourceSampler::new();
 
         // Run the event loop
         event_loop.run(move |_event, _elwt| {
@@ -103,7 +133,7 @@ impl PortKillApp {
                     let current_processes_clone = current_processes.clone();
                     let is_killing_clone = is_killing_processes.clone();
                     let args_clone = args.clone();
-                    let menu_id_to_port_clone = menu_id_to_port.clone();
+                    let ignored_names_clone = ignored_names.clone();
                     
                     std::thread::spawn(move || {
                         // Add a delay to ensure the menu system is stable
@@ -117,36 +147,70 @@ impl PortKillApp {
                             let menu_id = event.id.0.clone();
                             info!("Menu ID: {}", menu_id);
                             
-                            // We need to determine which menu item was clicked based on the menu ID
-                            // Since the tray-icon crate uses internal IDs, we'll use a different approach
-                            // We'll check if this is a known special menu ID first
-                            
-                            if menu_id == "kill_all" {
-                                info!("Kill All Processes clicked, killing all processes...");
-                                let ports_to_kill = args_clone.get_ports_to_monitor();
-                                Self::kill_all_processes(&ports_to_kill, &args_clone)
-                            } else if menu_id == "quit" {
-                                info!("Quit clicked, exiting gracefully...");
-                                std::process::exit(0);
-                            } else {
-                                // For individual process clicks, use the menu ID mapping
-                                info!("Individual process clicked (ID: {}), looking up port...", menu_id);
-                                
-                                if let Ok(menu_id_guard) = menu_id_to_port_clone.lock() {
-                                    if let Some(&port) = menu_id_guard.get(&menu_id) {
-                                        if let Some(process_info) = processes.get(&port) {
-                                            info!("Killing specific process on port {} with PID {}", port, process_info.pid);
-                                            Self::kill_single_process(process_info, &args_clone)
-                                        } else {
-                                            error!("Process not found for port {}", port);
-                                            Ok(())
-                                        }
-                                    } else {
-                                        warn!("Menu ID {} not found in mapping, ignoring", menu_id);
-                                        Ok(())
+                            let action = TrayAction::parse(&menu_id);
+                            // Per-process actions apply to whatever is on the port now
+                            let process_on = |port: u16| {
+                                let process = processes.get(&port);
+                                if process.is_none() {
+                                    error!("Process not found for port {}", port);
+                                }
+                                process
+                            };
+                            match action {
+                                Some(TrayAction::KillAll) => {
+                                    info!("Kill All Processes clicked, killing all processes...");
+                                    let ports_to_kill = args_clone.get_ports_to_monitor();
+                                    Self::kill_all_processes(&ports_to_kill, &args_clone)
+                                }
+                                Some(TrayAction::KillGroup(group)) => {
+                                    info!("Kill all {} clicked", group);
+                                    crate::process_monitor::kill_group_processes(&group, &args_clone)
+                                }
+                                Some(TrayAction::KillDuplicates) => {
+                                    info!("Keep Newest, Kill Older clicked, killing older duplicate instances...");
+                                    Self::kill_older_duplicates(processes, &args_clone)
+                                }
+                                Some(TrayAction::Settings) => crate::settings::open(),
+                                Some(TrayAction::OpenAtLogin) => crate::autostart::toggle().map(|_| ()),
+                                Some(TrayAction::UndoLastKill) => crate::undo::undo_last_kill_from_tray(),
+                                Some(TrayAction::Quit) => {
+                                    info!("Quit clicked, exiting gracefully...");
+                                    crate::exit_code::exit(0);
+                                }
+                                Some(TrayAction::Kill(port)) => match process_on(port) {
+                                    Some(process_info) => {
+                                        info!("Killing specific process on port {} with PID {}", port, process_info.pid);
+                                        Self::kill_single_process(process_info, &args_clone)
                                     }
-                                } else {
-                                    error!("Failed to access menu ID mapping");
+                                    None => Ok(()),
+                                },
+                                Some(TrayAction::KillTree(port)) => match process_on(port) {
+                                    Some(process_info) => Self::kill_process_tree(process_info, &args_clone),
+                                    None => Ok(()),
+                                },
+                                Some(TrayAction::Restart(port)) => Self::restart_port(port, &args_clone),
+                                Some(TrayAction::Ignore(port)) => match process_on(port) {
+                                    Some(process_info) => Self::ignore_process(process_info, &ignored_names_clone),
+                                    None => Ok(()),
+                                },
+                                Some(TrayAction::CopyPid(port)) => match process_on(port) {
+                                    Some(process_info) => crate::desktop_session::copy_to_clipboard(&process_info.pid.to_string())
+                                        .map(|tool| info!("Copied PID {} with {}", process_info.pid, tool)),
+                                    None => Ok(()),
+                                },
+                                Some(TrayAction::OpenDirectory(port)) => match process_on(port) {
+                                    Some(process_info) => Self::open_working_directory(process_info),
+                                    None => Ok(()),
+                                },
+                                Some(TrayAction::Deprioritize(port)) => {
+                                    info!("Deprioritize clicked for port {}", port);
+                                    match process_on(port) {
+                                        Some(process_info) => Self::deprioritize_process(process_info, &args_clone),
+                                        None => Ok(()),
+                                    }
+                                }
+                                None => {
+                                    warn!("Unknown menu ID {}, ignoring", menu_id);
                                     Ok(())
                                 }
                             }
@@ -172,13 +236,31 @@ impl PortKillApp {
                     info!("Menu event received but already killing processes, ignoring");
                 }
             }
+
+            if let Some(action) = hotkeys.as_ref().and_then(|hotkeys| hotkeys.poll()) {
+                Self::run_hotkey(action, &current_processes, &args);
+            }
             
-            // Check for processes every 5 seconds (less frequent to avoid crashes)
-            if last_check.elapsed() >= std::time::Duration::from_secs(5) {
+            // Saved settings take effect by starting again
+            if crate::settings::take_saved() {
+                info!("Settings changed, restarting...");
+                if let Err(e) = crate::memory::restart_self() {
+                    error!("Failed to restart: {}", e);
+                }
+            }
+
+            // Keep an eye on the tray's own memory (warns over --max-rss)
+            if last_memory_check.elapsed() >= crate::memory::CHECK_INTERVAL {
+                last_memory_check = std::time::Instant::now();
+                crate::memory::check(args.max_rss_bytes());
+            }
+
+            // Check for processes every scan interval, but no more than every 5 seconds (less frequent to avoid crashes)
+            if last_check.elapsed() >= std::time::Duration::from_secs(args.scan_interval.max(5)) {
                 last_check = std::time::Instant::now();
                 
                 // Get detailed process information with crash-safe approach
-                let (process_count, processes) = if args.verbose {
+                let (_, mut processes) = if args.verbose {
                     // Use ProcessMonitor for verbose information
                     match std::panic::catch_unwind(|| {
                         Self::get_processes_on_ports_verbose(&args.get_ports_to_monitor(), &args)
@@ -202,8 +284,21 @@ impl PortKillApp {
                     }
                 };
                 
-                let status_info = StatusBarInfo::from_process_count(process_count);
+                // Names added to the ignore list from the tray since startup
+                if let Ok(ignored) = ignored_names.lock() {
+                    processes.retain(|_, process_info| !ignored.contains(&process_info.name));
+                }
+                let process_count = processes.len();
+                resource_sampler.sample(&mut processes);
+
+                let mut status_info = StatusBarInfo::from_process_count(process_count);
+                status_info.append_longest_running(&processes);
+                let scan_health = crate::scan_health::last_scan();
+                status_info.apply_scan_health(&scan_health);
                 println!("🔄 Port Status: {} - {}", status_info.text, status_info.tooltip);
+                // The tooltip doubles as the status detail view, refreshed every scan
+                status_info.set_top_consumers(&processes, &resource_sampler);
+                crate::timeline::record(&processes);
                 
                 // Update current processes
                 if let Ok(mut current_processes_guard) = current_processes.lock() {
@@ -255,6 +350,8 @@ impl PortKillApp {
                             }
                         }
                     }
+                } else if let Some(message) = scan_health.describe() {
+                    println!("{}", message);
                 } else {
                     println!("📋 No processes detected");
                 }
@@ -267,11 +364,12 @@ impl PortKillApp {
                             error!("Failed to update tooltip: {}", e);
                         }
                         
-                        // Update icon with new status
-                        if let Ok(new_icon) = TrayMenu::create_icon(&status_info.text) {
+                        // Update the icon's count badge
+                        if let Ok(new_icon) = TrayMenu::create_icon(process_count, icon_theme) {
                             if let Err(e) = icon.set_icon(Some(new_icon)) {
                                 error!("Failed to update icon: {}", e);
                             }
+                            icon.set_icon_as_template(icon_theme.is_template());
                         }
                         
                         // Only update menu if process count changed significantly and we're not killing processes
@@ -305,7 +403,7 @@ impl PortKillApp {
                                 match std::panic::catch_unwind(|| {
                                     TrayMenu::create_menu_with_verbose(&valid_processes, args.show_pid, args.verbose)
                                 }) {
-                                    Ok(Ok((new_menu, new_menu_id_to_port))) => {
+                                    Ok(Ok(new_menu)) => {
                                         // SAFETY FIX: Clear the old menu first and wait for macOS to finish cleanup
                                         // This prevents a use-after-free crash when macOS tries to unregister
                                         // key equivalents from menu items that are being deallocated.
@@ -320,11 +418,6 @@ impl PortKillApp {
                                         // Now set the new menu
                                         icon.set_menu(Some(Box::new(new_menu)));
                                         
-                                        // Store the menu ID to port mapping for event handling
-                                        if let Ok(mut menu_id_guard) = menu_id_to_port.lock() {
-                                            *menu_id_guard = new_menu_id_to_port;
-                                        }
-                                        
                                         last_process_count = valid_process_count;
                                         last_menu_update = std::time::Instant::now();
                                         info!("Menu updated successfully for {} processes", valid_process_count);
@@ -371,6 +464,7 @@ impl PortKillApp {
             None,
             true,
         ) {
+            process_monitor.set_user_filter(args.user.clone());
             // Use tokio runtime to run the async scan_processes method
             let rt = tokio::runtime::Runtime::new().unwrap();
             match rt.block_on(process_monitor.scan_processes()) {
@@ -389,6 +483,9 @@ impl PortKillApp {
         if ports.is_empty() {
             return (0, HashMap::new());
         }
+        if crate::demo::is_enabled() {
+            return crate::process_monitor::get_processes_on_ports(ports, args);
+        }
 
         #[cfg(target_os = "windows")]
         {
@@ -410,6 +507,7 @@ impl PortKillApp {
 
         const MAX_PORTS_PER_LSOF: usize = 100;
         const LARGE_RANGE_THRESHOLD: usize = 200;
+        crate::scan_health::begin_scan();
         
         let mut processes = HashMap::new();
         let ports_filter: HashSet<u16> = ports.iter().copied().collect();
@@ -428,6 +526,7 @@ impl PortKillApp {
             ];
 
             let output = std::process::Command::new("lsof").args(&lsof_args).output();
+            crate::scan_health::report(crate::scan_health::check_command("lsof", &output));
 
             match output {
                 Ok(output) => {
@@ -461,6 +560,7 @@ impl PortKillApp {
                 }
 
                 let output = std::process::Command::new("lsof").args(&lsof_args).output();
+                crate::scan_health::report(crate::scan_health::check_command("lsof", &output));
 
                 match output {
                     Ok(output) => {
@@ -492,6 +592,11 @@ impl PortKillApp {
             }
         }
 
+        if let Some(ref user) = args.user {
+            processes.retain(|_, p| p.is_owned_by(user));
+        }
+
+        crate::system_monitor::fill_process_origins(&mut processes);
         (processes.len(), processes)
     }
 
@@ -508,9 +613,11 @@ impl PortKillApp {
         let ignore_processes = args.get_ignore_processes_set();
 
         // On Windows, use netstat to find all listening TCP ports
+        crate::scan_health::begin_scan();
         let output = std::process::Command::new("netstat")
             .args(&["-ano", "-p", "TCP"])
             .output();
+        crate::scan_health::report(crate::scan_health::check_command("netstat", &output));
 
         match output {
             Ok(output) => {
@@ -530,6 +637,11 @@ impl PortKillApp {
             }
         }
 
+        if let Some(ref user) = args.user {
+            processes.retain(|_, p| p.is_owned_by(user));
+        }
+
+        crate::system_monitor::fill_process_origins(&mut processes);
         (processes.len(), processes)
     }
 
@@ -613,6 +725,13 @@ impl PortKillApp {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: None,
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             };
 
             process_info.process_group = process_info.determine_process_group();
@@ -715,6 +834,13 @@ impl PortKillApp {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: Some(parts[2].to_string()),
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             };
 
             process_info.process_group = process_info.determine_process_group();
@@ -756,7 +882,11 @@ impl PortKillApp {
         const LARGE_RANGE_THRESHOLD: usize = 200;
         
         let ports_filter: HashSet<u16> = ports.iter().copied().collect();
-        let ignore_ports = args.get_ignore_ports_set();
+        let mut ignore_ports = args.get_ignore_ports_set();
+        // Safe mode lists system ports but doesn't kill on them
+        if args.safe_mode {
+            ignore_ports.extend(1..crate::safe_mode::SYSTEM_PORTS_END);
+        }
         let ignore_processes = args.get_ignore_processes_set();
 
         let mut pids_to_kill = Vec::new();
@@ -830,8 +960,25 @@ impl PortKillApp {
         );
 
         for pid in pids_to_kill {
+            let owner = crate::process_monitor::get_process_owner(pid);
+            if let Some(ref user) = args.user {
+                if !owner.as_deref().map(|o| crate::types::same_user(o, user)).unwrap_or(false) {
+                    continue;
+                }
+            }
+            // The tray has no terminal to confirm on, so --sudo alone doesn't cover other users
+            if let Err(e) = crate::process_monitor::ensure_kill_allowed(pid, owner.as_deref(), args.force) {
+                warn!("Skipping PID {}: {}", pid, e);
+                continue;
+            }
+            if let Err(e) =
+                crate::process_monitor::ensure_not_protected(pid, None, &args.get_protected_processes())
+            {
+                info!("Skipping PID {}: {}", pid, e);
+                continue;
+            }
             info!("Attempting to kill process PID: {}", pid);
-            match Self::kill_process(pid) {
+            match crate::hooks::with_kill_hooks(pid, None, None, || Self::kill_process(pid)) {
                 Ok(_) => info!("Successfully killed process PID: {}", pid),
                 Err(e) => error!("Failed to kill process {}: {}", pid, e),
             }
@@ -846,7 +993,11 @@ impl PortKillApp {
         use std::collections::HashSet;
 
         let ports_filter: HashSet<u16> = ports.iter().copied().collect();
-        let ignore_ports = args.get_ignore_ports_set();
+        let mut ignore_ports = args.get_ignore_ports_set();
+        // Safe mode lists system ports but doesn't kill on them
+        if args.safe_mode {
+            ignore_ports.extend(1..crate::safe_mode::SYSTEM_PORTS_END);
+        }
         let ignore_processes = args.get_ignore_processes_set();
 
         let mut pids_to_kill = Vec::new();
@@ -883,8 +1034,24 @@ impl PortKillApp {
         );
 
         for pid in pids_to_kill {
+            let owner = crate::process_monitor::get_process_owner(pid);
+            if let Some(ref user) = args.user {
+                if !owner.as_deref().map(|o| crate::types::same_user(o, user)).unwrap_or(false) {
+                    continue;
+                }
+            }
+            if let Err(e) = crate::process_monitor::ensure_kill_allowed(pid, owner.as_deref(), args.force) {
+                warn!("Skipping PID {}: {}", pid, e);
+                continue;
+            }
+            if let Err(e) =
+                crate::process_monitor::ensure_not_protected(pid, None, &args.get_protected_processes())
+            {
+                info!("Skipping PID {}: {}", pid, e);
+                continue;
+            }
             info!("Attempting to kill process PID: {}", pid);
-            match Self::kill_process(pid) {
+            match crate::hooks::with_kill_hooks(pid, None, None, || Self::kill_process(pid)) {
                 Ok(_) => info!("Successfully killed process PID: {}", pid),
                 Err(e) => error!("Failed to kill process {}: {}", pid, e),
             }
@@ -1088,6 +1255,166 @@ impl PortKillApp {
         Ok(())
     }
 
+    /// Keep the newest instance of every project running more than once and kill the rest
+    fn kill_older_duplicates(processes: &HashMap<u16, ProcessInfo>, args: &Args) -> Result<()> {
+        let mut processes = processes.clone();
+        crate::system_monitor::fill_duplicate_candidates(&mut processes);
+        let mut failed = 0;
+        let mut total = 0;
+        for duplicate in crate::types::find_duplicate_instances(&processes) {
+            info!(
+                "Duplicate instance: {}, keeping PID {}",
+                duplicate.describe(),
+                duplicate.newest().pid
+            );
+            for process_info in duplicate.older() {
+                total += 1;
+                if let Err(e) = Self::kill_single_process(process_info, args) {
+                    error!("Failed to kill PID {}: {}", process_info.pid, e);
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(crate::exit_code::KillFailed { failed, total }.into());
+        }
+        Ok(())
+    }
+
+    /// A global hotkey was pressed; kills run off the event loop thread like menu actions
+    fn run_hotkey(
+        action: HotkeyAction,
+        current_processes: &Arc<StdMutex<HashMap<u16, ProcessInfo>>>,
+        args: &Args,
+    ) {
+        info!("Hotkey pressed: {}", action);
+        let processes = current_processes
+            .lock()
+            .map(|processes| processes.clone())
+            .unwrap_or_default();
+        let args = args.clone();
+        std::thread::spawn(move || {
+            let result = match &action {
+                HotkeyAction::KillAll => Self::kill_all_processes(&args.get_ports_to_monitor(), &args),
+                HotkeyAction::KillPort(port) => match processes.get(port) {
+                    Some(process_info) => Self::kill_single_process(process_info, &args),
+                    None => {
+                        info!("Nothing is listening on port {}", port);
+                        Ok(())
+                    }
+                },
+                HotkeyAction::KillGroup(group) => crate::process_monitor::kill_group_processes(group, &args),
+                HotkeyAction::ShowProcesses => {
+                    crate::notify::show(
+                        &format!("{} process(es) on monitored ports", processes.len()),
+                        &crate::hotkeys::processes_summary(&processes),
+                    );
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                error!("Hotkey {} failed: {}", action, e);
+            }
+        });
+    }
+
+    /// Tray "Kill Tree": the process's children first, then the process itself
+    fn kill_process_tree(process_info: &ProcessInfo, args: &Args) -> Result<()> {
+        let descendants = crate::system_monitor::descendants(process_info.pid);
+        info!(
+            "Killing process tree of {} (PID {}): {} descendant(s)",
+            process_info.name,
+            process_info.pid,
+            descendants.len()
+        );
+        // The root is checked against the ignore and protected lists before anything dies
+        if args.get_ignore_processes_set().contains(&process_info.name)
+            || args.get_ignore_ports_set().contains(&process_info.port)
+        {
+            info!("Ignoring process tree of {} (PID {}) - in ignore list", process_info.name, process_info.pid);
+            return Ok(());
+        }
+        crate::process_monitor::ensure_kill_allowed(
+            process_info.pid,
+            process_info.user.as_deref(),
+            args.force,
+        )?;
+        crate::process_monitor::ensure_not_protected(
+            process_info.pid,
+            Some(&process_info.name),
+            &args.get_protected_processes(),
+        )?;
+        crate::safe_mode::ensure_not_system_port(process_info.port, args.safe_mode)?;
+        for pid in descendants {
+            if let Err(e) = Self::kill_process(pid) {
+                warn!("Failed to kill child PID {}: {}", pid, e);
+            }
+        }
+        Self::kill_single_process(process_info, args)
+    }
+
+    /// Tray "Restart": kill what is on the port and start it again with its saved command
+    fn restart_port(port: u16, args: &Args) -> Result<()> {
+        let (update_sender, _update_receiver) = bounded(1);
+        let mut monitor = crate::process_monitor::ProcessMonitor::new(
+            update_sender,
+            vec![port],
+            args.docker,
+            args.verbose,
+        )?;
+        monitor.set_safe_mode(args.safe_mode);
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            // Refresh so the process currently on the port is the one that gets killed
+            monitor.scan_processes().await?;
+            monitor.restart_process_on_port(port).await
+        })?;
+        info!("Restarted port {}", port);
+        Ok(())
+    }
+
+    /// Tray "Add to Ignore List": hide the process now and in future runs
+    fn ignore_process(
+        process_info: &ProcessInfo,
+        ignored_names: &Arc<StdMutex<HashSet<String>>>,
+    ) -> Result<()> {
+        let path = crate::config::add_to_global_list("ignore_processes", &process_info.name)?;
+        if let Ok(mut ignored) = ignored_names.lock() {
+            ignored.insert(process_info.name.clone());
+        }
+        info!("Added {} to ignore_processes in {}", process_info.name, path.display());
+        Ok(())
+    }
+
+    /// Tray "Open Working Directory": reveal it in Finder
+    fn open_working_directory(process_info: &ProcessInfo) -> Result<()> {
+        let Some(dir) = &process_info.working_directory else {
+            return Ok(());
+        };
+        std::process::Command::new("open").arg(dir).spawn()?;
+        Ok(())
+    }
+
+    /// Tray "Deprioritize": renice by [`crate::priority::DEPRIORITIZE`] and keep it running
+    fn deprioritize_process(process_info: &ProcessInfo, args: &Args) -> Result<()> {
+        let (update_sender, _update_receiver) = bounded(1);
+        let mut monitor = crate::process_monitor::ProcessMonitor::new(
+            update_sender,
+            vec![process_info.port],
+            args.docker,
+            args.verbose,
+        )?;
+        let nice = monitor.renice_process(process_info, crate::priority::DEPRIORITIZE)?;
+        info!(
+            "Deprioritized {} (PID {}) on port {}{}",
+            process_info.name,
+            process_info.pid,
+            process_info.port,
+            nice.map(|n| format!(", now nice {}", n)).unwrap_or_default()
+        );
+        Ok(())
+    }
+
     pub fn kill_single_process(process_info: &ProcessInfo, args: &Args) -> Result<()> {
         info!("Killing single process PID: {}", process_info.pid);
 
@@ -1125,8 +1452,26 @@ impl PortKillApp {
             return Ok(());
         }
 
-        // Process is not ignored, proceed with killing
-        Self::kill_process(process_info.pid)
+        crate::process_monitor::ensure_kill_allowed(
+            process_info.pid,
+            process_info.user.as_deref(),
+            args.force,
+        )?;
+        crate::process_monitor::ensure_not_protected(
+            process_info.pid,
+            Some(&process_info.name),
+            &args.get_protected_processes(),
+        )?;
+        crate::safe_mode::ensure_not_system_port(process_info.port, args.safe_mode)?;
+
+        // Process is not ignored, proceed with killing (through any configured hooks)
+        crate::hooks::with_kill_hooks(
+            process_info.pid,
+            Some(process_info.port),
+            Some(&process_info.name),
+            || Self::kill_process(process_info.pid),
+        )
+        .map(|_| ())
     }
 
     /// Check if a process is still running by its PID
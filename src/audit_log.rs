@@ -0,0 +1,359 @@
+//! Append-only audit trail of destructive actions in `~/.port-kill/audit.log`: one JSON
+//! line per kill, restart, cache clean or guard conflict resolution, with who started it (CLI, tray, TUI, script, API
+//! or guard), the target and the outcome. Unlike the kill history, which feeds statistics
+//! and can be cleared, the audit log is only ever appended to. `--show-audit` reads it back.
+
+use crate::cli::Args;
+use crate::types::PortResolution;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Which front end started an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditInitiator {
+    Cli,
+    Tray,
+    Tui,
+    Script,
+    Api,
+    Guard,
+}
+
+impl AuditInitiator {
+    /// The initiator for a process started with `args`
+    pub fn for_args(args: &Args, tray: bool) -> Self {
+        if args.tui {
+            AuditInitiator::Tui
+        } else if args.script.is_some() || args.script_file.is_some() {
+            AuditInitiator::Script
+        } else if args.serve.is_some() {
+            AuditInitiator::Api
+        } else if args.guard_mode || args.guard.is_some() {
+            AuditInitiator::Guard
+        } else if tray && !args.console {
+            AuditInitiator::Tray
+        } else {
+            AuditInitiator::Cli
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    Kill,
+    Restart,
+    CacheClean,
+    Resolve,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Failed,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub initiator: AuditInitiator,
+    pub action: AuditAction,
+    /// OS user port-kill ran as
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i32>,
+    /// Process name, restart command or cache name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Deleted cache path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Bytes a cache clean freed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    /// How the guard resolved a conflict
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<PortResolution>,
+    pub outcome: AuditOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+static INITIATOR: Mutex<AuditInitiator> = Mutex::new(AuditInitiator::Cli);
+
+/// Set who the actions of this process are attributed to; each binary calls this at startup
+pub fn set_initiator(initiator: AuditInitiator) {
+    if let Ok(mut current) = INITIATOR.lock() {
+        *current = initiator;
+    }
+}
+
+/// Who the actions of this process are attributed to
+pub fn initiator() -> AuditInitiator {
+    INITIATOR.lock().map(|i| *i).unwrap_or(AuditInitiator::Cli)
+}
+
+impl AuditEntry {
+    pub fn new<T>(action: AuditAction, result: &Result<T>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            initiator: initiator(),
+            action,
+            user: std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok(),
+            port: None,
+            pid: None,
+            name: None,
+            path: None,
+            bytes: None,
+            resolution: None,
+            outcome: if result.is_ok() {
+                AuditOutcome::Success
+            } else {
+                AuditOutcome::Failed
+            },
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        }
+    }
+}
+
+pub fn log_file_path() -> PathBuf {
+    crate::uninstall::home_dir().join(".port-kill").join("audit.log")
+}
+
+/// Append an entry. Demo mode actions are not real and aren't recorded.
+pub fn record(entry: AuditEntry) {
+    if crate::demo::is_enabled() {
+        return;
+    }
+    let path = log_file_path();
+    let append = || -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    };
+    if let Err(e) = append() {
+        log::warn!("Failed to write audit log {}: {:#}", path.display(), e);
+    }
+}
+
+pub fn record_kill<T>(pid: i32, port: Option<u16>, name: Option<&str>, result: &Result<T>) {
+    record(AuditEntry {
+        pid: Some(pid),
+        port,
+        name: name.map(str::to_string),
+        ..AuditEntry::new(AuditAction::Kill, result)
+    });
+}
+
+pub fn record_restart(port: u16, command: &str, result: &Result<u32>) {
+    record(AuditEntry {
+        port: Some(port),
+        pid: result.as_ref().ok().map(|pid| *pid as i32),
+        name: Some(command.to_string()),
+        ..AuditEntry::new(AuditAction::Restart, result)
+    });
+}
+
+pub fn record_resolution<T>(
+    port: u16,
+    pid: i32,
+    name: &str,
+    resolution: PortResolution,
+    result: &Result<T>,
+) {
+    record(AuditEntry {
+        port: Some(port),
+        pid: Some(pid),
+        name: Some(name.to_string()),
+        resolution: Some(resolution),
+        ..AuditEntry::new(AuditAction::Resolve, result)
+    });
+}
+
+pub fn record_cache_clean(name: &str, path: &str, bytes: Option<u64>, result: &Result<()>) {
+    record(AuditEntry {
+        name: Some(name.to_string()),
+        path: Some(path.to_string()),
+        bytes,
+        ..AuditEntry::new(AuditAction::CacheClean, result)
+    });
+}
+
+/// `--show-audit` filters
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub action: Option<AuditAction>,
+    pub initiator: Option<AuditInitiator>,
+    pub port: Option<u16>,
+    pub failed_only: bool,
+}
+
+impl AuditFilter {
+    pub fn from_args(args: &Args) -> Result<Self> {
+        let since = args
+            .audit_since
+            .as_deref()
+            .map(|when| crate::timeline::parse_when(when, chrono::Local::now()))
+            .transpose()?;
+        Ok(Self {
+            since,
+            action: args.audit_action,
+            initiator: args.audit_initiator,
+            port: args.audit_port,
+            failed_only: args.audit_failed,
+        })
+    }
+
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.action.is_none_or(|action| entry.action == action)
+            && self.initiator.is_none_or(|initiator| entry.initiator == initiator)
+            && self.port.is_none_or(|port| entry.port == Some(port))
+            && (!self.failed_only || entry.outcome == AuditOutcome::Failed)
+    }
+}
+
+/// Matching entries, oldest first. Lines that don't parse are skipped.
+pub fn read(filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+    let path = log_file_path();
+    let file = match std::fs::File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<AuditEntry>(&line).ok())
+        .filter(|entry| filter.matches(entry))
+        .collect())
+}
+
+impl AuditEntry {
+    pub const CSV_HEADER: &'static str = "timestamp,initiator,action,user,port,pid,name,path,outcome,error";
+
+    pub fn to_csv_row(&self) -> String {
+        let field = |value: Option<String>| {
+            let value = value.unwrap_or_default();
+            if value.contains([',', '"', '\n']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value
+            }
+        };
+        [
+            self.timestamp.to_rfc3339(),
+            format!("{:?}", self.initiator).to_lowercase(),
+            serde_json::to_value(self.action)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            field(self.user.clone()),
+            field(self.port.map(|p| p.to_string())),
+            field(self.pid.map(|p| p.to_string())),
+            field(self.name.clone()),
+            field(self.path.clone()),
+            format!("{:?}", self.outcome).to_lowercase(),
+            field(self.error.clone()),
+        ]
+        .join(",")
+    }
+
+    /// One line for the table view
+    pub fn describe(&self) -> String {
+        let target = match (self.action, self.port, self.pid) {
+            (AuditAction::CacheClean, _, _) => {
+                self.path.clone().or(self.name.clone()).unwrap_or_default()
+            }
+            (_, Some(port), Some(pid)) => format!(
+                "{} (PID {}) on port {}",
+                self.name.as_deref().unwrap_or("process"),
+                pid,
+                port
+            ),
+            (_, Some(port), None) => format!("port {}", port),
+            (_, None, Some(pid)) => format!("{} (PID {})", self.name.as_deref().unwrap_or("process"), pid),
+            (_, None, None) => self.name.clone().unwrap_or_default(),
+        };
+        let action = match (self.action, self.resolution) {
+            (AuditAction::Kill, _) => "kill".to_string(),
+            (AuditAction::Restart, _) => "restart".to_string(),
+            (AuditAction::CacheClean, _) => "cache clean".to_string(),
+            (AuditAction::Resolve, Some(resolution)) => format!("resolve ({})", resolution.label()),
+            (AuditAction::Resolve, None) => "resolve".to_string(),
+        };
+        let outcome = match (&self.outcome, &self.error) {
+            (AuditOutcome::Success, _) => "✅".to_string(),
+            (AuditOutcome::Failed, Some(error)) => format!("❌ {}", error),
+            (AuditOutcome::Failed, None) => "❌".to_string(),
+        };
+        format!(
+            "{} [{}] {} {} {}",
+            self.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"),
+            format!("{:?}", self.initiator).to_lowercase(),
+            action,
+            target,
+            outcome
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_entry_round_trip_and_filter() {
+        let failed: Result<()> = Err(anyhow::anyhow!("permission denied"));
+        let entry = AuditEntry {
+            pid: Some(42),
+            port: Some(3000),
+            name: Some("node".into()),
+            ..AuditEntry::new(AuditAction::Kill, &failed)
+        };
+        let line = serde_json::to_string(&entry).unwrap();
+        assert!(line.contains("\"action\":\"kill\"") && line.contains("\"outcome\":\"failed\""));
+        assert!(!line.contains("\"path\""));
+        let entry: AuditEntry = serde_json::from_str(&line).unwrap();
+        assert_eq!(entry.error.as_deref(), Some("permission denied"));
+
+        let filter = AuditFilter {
+            port: Some(3000),
+            failed_only: true,
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry));
+        assert!(!AuditFilter { action: Some(AuditAction::Restart), ..Default::default() }.matches(&entry));
+        assert!(!AuditFilter { since: Some(Utc::now() + chrono::Duration::hours(1)), ..Default::default() }.matches(&entry));
+        assert!(entry.to_csv_row().ends_with(",node,,failed,permission denied"));
+
+        let resolved = AuditEntry {
+            port: Some(3000),
+            pid: Some(42),
+            name: Some("python3".into()),
+            resolution: Some(PortResolution::NotifyUser),
+            ..AuditEntry::new(AuditAction::Resolve, &Ok(()))
+        };
+        let line = serde_json::to_string(&resolved).unwrap();
+        assert!(line.contains("\"action\":\"resolve\"") && line.contains("\"resolution\":\"NotifyUser\""));
+        assert!(resolved.describe().contains("resolve (notify) python3 (PID 42) on port 3000"));
+    }
+}
@@ -0,0 +1,237 @@
+//! Open the tray at login: a LaunchAgent on macOS, an XDG autostart entry on Linux and a
+//! `Run` registry value on Windows. Each starts the `port-kill` tray binary without flags,
+//! so it runs with the settings in `~/.port-kill/config.toml`. Turned on and off with
+//! `--enable-autostart` / `--disable-autostart`, the tray's "Open at Login" item or the
+//! Settings window; `--uninstall` removes it along with everything else.
+
+use crate::daemon::{run, xml_escape};
+use crate::uninstall::{home_dir, LAUNCHD_LABEL, SERVICE_NAME};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// `HKCU` key holding the Windows login entry, a value named [`SERVICE_NAME`]
+pub const WINDOWS_RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// Where the login entry lives on this platform
+pub fn entry_path() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        home_dir()
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", LAUNCHD_LABEL))
+    } else if cfg!(target_os = "windows") {
+        PathBuf::from(format!(r"{}\{}", WINDOWS_RUN_KEY, SERVICE_NAME))
+    } else {
+        home_dir()
+            .join(".config/autostart")
+            .join(format!("{}.desktop", SERVICE_NAME))
+    }
+}
+
+pub fn launch_agent(exe: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>LimitLoadToSessionType</key>
+    <string>Aqua</string>
+</dict>
+</plist>
+"#,
+        LAUNCHD_LABEL,
+        xml_escape(&exe.to_string_lossy())
+    )
+}
+
+pub fn desktop_entry(exe: &Path) -> String {
+    let exe = exe.to_string_lossy();
+    let exec = if exe.contains(' ') {
+        format!("\"{}\"", exe)
+    } else {
+        exe.into_owned()
+    };
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Port Kill\n\
+         Comment=Monitor and free development ports\n\
+         Exec={}\n\
+         Icon=port-kill-green\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec
+    )
+}
+
+pub fn is_enabled() -> bool {
+    if cfg!(target_os = "windows") {
+        run("reg", &["query", WINDOWS_RUN_KEY, "/v", SERVICE_NAME]).is_ok()
+    } else {
+        entry_path().is_file()
+    }
+}
+
+/// The tray binary: this one, or `port-kill` next to it when run from `port-kill-console`
+pub fn tray_binary() -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to find the port-kill binary")?;
+    let is_console = exe
+        .file_stem()
+        .is_some_and(|stem| stem.to_string_lossy() == "port-kill-console");
+    if is_console {
+        let tray = exe.with_file_name(format!("port-kill{}", std::env::consts::EXE_SUFFIX));
+        if tray.is_file() {
+            return Ok(tray);
+        }
+    }
+    Ok(exe)
+}
+
+/// Start the tray binary at login. Returns where the entry was written.
+pub fn enable() -> Result<PathBuf> {
+    let exe = tray_binary()?;
+    let path = entry_path();
+    if cfg!(target_os = "windows") {
+        let command = format!("\"{}\"", exe.display());
+        run(
+            "reg",
+            &["add", WINDOWS_RUN_KEY, "/v", SERVICE_NAME, "/t", "REG_SZ", "/d", &command, "/f"],
+        )?;
+        return Ok(path);
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Not loaded now: that would start a second tray next to this one
+    let content = if cfg!(target_os = "macos") {
+        launch_agent(&exe)
+    } else {
+        desktop_entry(&exe)
+    };
+    std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Stop starting at login. Returns false when it wasn't enabled. A tray that is running
+/// keeps running.
+pub fn disable() -> Result<bool> {
+    if !is_enabled() {
+        return Ok(false);
+    }
+    if cfg!(target_os = "windows") {
+        run("reg", &["delete", WINDOWS_RUN_KEY, "/v", SERVICE_NAME, "/f"])?;
+    } else {
+        let path = entry_path();
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    Ok(true)
+}
+
+/// Turn the login item on or off, leaving it alone when it already is. The tray item,
+/// the Settings window and the flags all go through here.
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    if enabled == is_enabled() {
+        return Ok(());
+    }
+    if enabled {
+        enable()?;
+    } else {
+        disable()?;
+    }
+    log::info!("Open at Login {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/// Flip the login item for the tray's "Open at Login" item. Returns whether it is now on.
+pub fn toggle() -> Result<bool> {
+    let enabled = !is_enabled();
+    set_enabled(enabled)?;
+    Ok(enabled)
+}
+
+/// `--enable-autostart` / `--disable-autostart`
+pub fn set_from_flag(enable_autostart: bool) -> Result<()> {
+    if enable_autostart {
+        let path = enable()?;
+        println!("✅ Port Kill will open at login ({})", path.display());
+    } else if disable()? {
+        println!("✅ Port Kill will no longer open at login");
+    } else {
+        println!("Port Kill wasn't set to open at login");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_entries() {
+        let plist = launch_agent(Path::new("/Applications/Port Kill/port-kill"));
+        assert!(plist.contains("<string>com.treadie.port-kill</string>"));
+        assert!(plist.contains("<string>/Applications/Port Kill/port-kill</string>"));
+        assert!(!plist.contains("KeepAlive"));
+
+        let entry = desktop_entry(Path::new("/opt/port kill/port-kill"));
+        assert!(entry.contains("Exec=\"/opt/port kill/port-kill\"\n"));
+        assert!(entry_path().to_string_lossy().contains(SERVICE_NAME));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_enable_and_disable_write_the_entry() {
+        // The entry lives in the home directory, so this test runs itself again as a child
+        // process with a home of its own
+        let home = std::env::temp_dir().join(format!("port-kill-autostart-{}", std::process::id()));
+        if std::env::var_os("PORT_KILL_TEST_CHILD").is_none() {
+            let _ = std::fs::remove_dir_all(&home);
+            std::fs::create_dir_all(&home).unwrap();
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "--exact",
+                    "autostart::tests::test_enable_and_disable_write_the_entry",
+                ])
+                .env("PORT_KILL_TEST_CHILD", "1")
+                .env("HOME", &home)
+                .stdout(std::process::Stdio::null())
+                .status()
+                .unwrap();
+            let _ = std::fs::remove_dir_all(&home);
+            assert!(status.success());
+            return;
+        }
+
+        let path = entry_path();
+        assert!(path.starts_with(home_dir()));
+        assert!(!is_enabled());
+        assert!(!disable().unwrap());
+
+        assert_eq!(enable().unwrap(), path);
+        assert!(is_enabled());
+        let exe = std::env::current_exe().unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains(&*exe.to_string_lossy()));
+
+        // Already on: nothing changes
+        set_enabled(true).unwrap();
+        assert!(is_enabled());
+
+        assert!(!toggle().unwrap());
+        assert!(!path.exists());
+        assert!(toggle().unwrap());
+        assert!(is_enabled());
+
+        set_enabled(false).unwrap();
+        assert!(!is_enabled());
+        assert!(!disable().unwrap());
+    }
+}
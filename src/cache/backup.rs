@@ -1,8 +1,17 @@
-use super::types::CacheEntry;
+use super::output::{human_since, human_size};
+use super::rules::CacheRules;
+use super::types::{BackupInfo, BackupsResponse, CacheEntry};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Backups kept when neither `--keep-backups` nor `[cache] keep_backups` says otherwise
+pub const DEFAULT_KEEP_BACKUPS: usize = 10;
+
+/// Appended to [`backup_file_name`] for an entry backed up as a zstd-compressed tarball
+const ARCHIVE_EXTENSION: &str = ".tar.zst";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BackupManifest {
@@ -30,9 +39,110 @@ pub fn get_timestamped_dir() -> PathBuf {
     backup_dir.join(timestamp.to_string())
 }
 
+/// Name of an entry's copy inside a backup. Built from the entry ID to avoid collisions
+/// (e.g., multiple __pycache__ directories from different paths).
+pub fn backup_file_name(entry: &CacheEntry) -> String {
+    format!(
+        "{}-{}",
+        entry.id.replace([':', '/', '\\'], "-"),
+        Path::new(&entry.path)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+    )
+}
+
+/// Move `src` to `dst`: a rename, or a copy and delete when they are on different
+/// filesystems. `dst` must not exist.
+fn move_dir_or_file(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    match fs::rename(src, dst) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_dir_or_file(src, dst) {
+                let _ = remove_dir_or_file(dst);
+                return Err(e);
+            }
+            remove_dir_or_file(src).map_err(|e| {
+                std::io::Error::new(
+                    e.kind(),
+                    format!("copied to {} but not removed: {}", dst.display(), e),
+                )
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Stream `src` into the zstd-compressed tarball `dst`, under its own file name
+fn compress(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    let name = src.file_name().unwrap_or_default();
+    let encoder = zstd::Encoder::new(fs::File::create(dst)?, 0)?;
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    if fs::symlink_metadata(src)?.is_dir() {
+        builder.append_dir_all(name, src)?;
+    } else {
+        builder.append_path_with_name(src, name)?;
+    }
+    builder.into_inner()?.finish()?.sync_all()
+}
+
+/// Unpack the tarball [`compress`] made into `dst`'s directory, streaming it
+fn decompress(archive: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    if fs::symlink_metadata(dst).is_ok_and(|meta| meta.is_dir()) {
+        prepare_directory_destination(dst)?;
+    } else {
+        prepare_file_destination(dst)?;
+    }
+    let parent = dst.parent().unwrap_or(Path::new("."));
+    let decoder = zstd::Decoder::new(fs::File::open(archive)?)?;
+    let mut tarball = tar::Archive::new(decoder);
+    tarball.set_preserve_permissions(true);
+    tarball.set_preserve_mtime(true);
+    tarball.unpack(parent)
+}
+
+/// Back `src` up to `dst` (`dst` plus [`ARCHIVE_EXTENSION`] when compressing) and remove it
+fn back_up(src: &Path, dst: &Path, compressed: bool) -> Result<PathBuf, std::io::Error> {
+    if !compressed {
+        move_dir_or_file(src, dst)?;
+        return Ok(dst.to_path_buf());
+    }
+    let mut archive = dst.as_os_str().to_owned();
+    archive.push(ARCHIVE_EXTENSION);
+    let archive = PathBuf::from(archive);
+    if let Err(e) = compress(src, &archive) {
+        let _ = fs::remove_file(&archive);
+        return Err(e);
+    }
+    remove_dir_or_file(src).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("compressed to {} but not removed: {}", archive.display(), e),
+        )
+    })?;
+    Ok(archive)
+}
+
+static COMPRESS: OnceLock<bool> = OnceLock::new();
+
+/// Set `--compress-backups`, once
+pub fn configure_compression(compress: bool) {
+    let _ = COMPRESS.set(compress);
+}
+
+/// Whether `--safe-delete` backups are compressed: `--compress-backups` or
+/// `compress_backups` in the `[cache]` rules
+pub fn compression(rules: &CacheRules) -> bool {
+    COMPRESS.get().copied().unwrap_or(false) || rules.compress_backups == Some(true)
+}
+
+/// Delete `entries`, moving them into a new backup first with `safe_delete` (compressed
+/// with `compress`)
 pub async fn safe_delete_entries(
     entries: &[CacheEntry],
     safe_delete: bool,
+    compress: bool,
 ) -> Result<(Vec<CacheEntry>, Option<String>), std::io::Error> {
     let mut deleted = Vec::new();
     let mut backup_path = None;
@@ -42,28 +152,13 @@ pub async fn safe_delete_entries(
         fs::create_dir_all(&timestamped_dir)?;
         backup_path = Some(timestamped_dir.to_string_lossy().to_string());
 
-        // Move entries to backup
+        // Move entries to backup, or compress them into it
         for entry in entries {
             let src = Path::new(&entry.path);
             if src.exists() {
-                // Generate unique destination name using entry ID to avoid filename collisions
-                // (e.g., multiple __pycache__ directories from different paths)
-                let unique_name = format!(
-                    "{}-{}",
-                    entry.id.replace([':', '/', '\\'], "-"),
-                    src.file_name().unwrap_or_default().to_string_lossy()
-                );
-                let dst = timestamped_dir.join(&unique_name);
-                match copy_dir_or_file(src, &dst) {
-                    Ok(()) => match remove_dir_or_file(src) {
-                        Ok(()) => deleted.push(entry.clone()),
-                        Err(e) => {
-                            eprintln!(
-                                "Warning: Failed to remove original after backup {}: {}. Backup preserved at {}",
-                                entry.path, e, dst.display()
-                            );
-                        }
-                    },
+                let dst = timestamped_dir.join(backup_file_name(entry));
+                match back_up(src, &dst, compress) {
+                    Ok(_) => deleted.push(entry.clone()),
                     Err(e) => {
                         eprintln!("Warning: Failed to backup {}: {}", entry.path, e);
                     }
@@ -122,32 +217,208 @@ pub fn find_latest_backup() -> Result<Option<PathBuf>, std::io::Error> {
     Ok(entries.last().map(|e| e.path()))
 }
 
-pub async fn restore_from_backup(backup_path: &Path) -> Result<usize, std::io::Error> {
+/// Which backups survive a prune. The newest one is always kept, whatever the limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retention {
+    /// Number of backups to keep; 0 keeps them all
+    pub keep: Option<usize>,
+    /// Oldest backups go while all of them together are larger than this
+    pub max_total_bytes: Option<u64>,
+    pub max_age_days: Option<u32>,
+}
+
+static RETENTION: OnceLock<Retention> = OnceLock::new();
+
+/// Set the command line overrides (`--keep-backups`, ...), once
+pub fn configure_retention(overrides: Retention) {
+    let _ = RETENTION.set(overrides);
+}
+
+/// The retention in effect: command line flags, then the `[cache]` rules, then the default
+pub fn retention(rules: &CacheRules) -> Retention {
+    let overrides = RETENTION.get().copied().unwrap_or_default();
+    let config_size = rules.max_backup_size.as_deref().and_then(|size| {
+        super::watch::parse_size(size)
+            .map_err(|e| log::warn!("Ignoring [cache] max_backup_size: {:#}", e))
+            .ok()
+    });
+    Retention {
+        keep: overrides
+            .keep
+            .or(rules.keep_backups)
+            .or(Some(DEFAULT_KEEP_BACKUPS)),
+        max_total_bytes: overrides.max_total_bytes.or(config_size),
+        max_age_days: overrides.max_age_days.or(rules.max_backup_age_days),
+    }
+}
+
+/// Backups in the backup directory with their sizes, newest first
+pub fn list_backups() -> Vec<BackupInfo> {
+    let Ok(read) = fs::read_dir(get_backup_dir()) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = read
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let content = fs::read_to_string(path.join("manifest.json")).ok()?;
+            let manifest: BackupManifest = serde_json::from_str(&content).ok()?;
+            let (size_bytes, _) = super::size::measure(&path);
+            Some(BackupInfo {
+                id: entry.file_name().to_string_lossy().to_string(),
+                path: path.to_string_lossy().to_string(),
+                created_at: manifest.timestamp,
+                entry_count: manifest.entries.len(),
+                size_bytes,
+                entries: manifest.entries,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    backups
+}
+
+/// `cache --backups`
+pub fn list_backups_response() -> BackupsResponse {
+    let backups = list_backups();
+    super::size::save();
+    BackupsResponse {
+        total_size_bytes: backups.iter().map(|b| b.size_bytes).sum(),
+        backups,
+    }
+}
+
+pub fn print_backups_table(resp: &BackupsResponse) {
+    if resp.backups.is_empty() {
+        println!("No backups in {}", get_backup_dir().display());
+        return;
+    }
+    println!(
+        "{:<22} | {:<10} | {:<7} | {:<12}",
+        "BACKUP", "AGE", "ENTRIES", "SIZE"
+    );
+    println!("{}", "-".repeat(60));
+    for backup in &resp.backups {
+        println!(
+            "{:<22} | {:<10} | {:<7} | {:<12}",
+            backup.id,
+            human_since(Some(backup.created_at)),
+            backup.entry_count,
+            human_size(backup.size_bytes)
+        );
+        for entry in &backup.entries {
+            println!(
+                "    {:<10} {:>10}  {}",
+                entry.kind,
+                human_size(entry.size_bytes),
+                entry.path
+            );
+        }
+    }
+    println!();
+    println!(
+        "Total: {} backup(s), {} in {}",
+        resp.backups.len(),
+        human_size(resp.total_size_bytes),
+        get_backup_dir().display()
+    );
+    println!("Restore with: cache --restore --backup <BACKUP> [--only <path>]");
+}
+
+/// Indexes (into `backups`, newest first) of the backups `retention` doesn't keep
+fn select_pruned(backups: &[BackupInfo], retention: &Retention, now: DateTime<Utc>) -> Vec<usize> {
+    let mut pruned = Vec::new();
+    let mut kept_bytes = 0u64;
+    for (i, backup) in backups.iter().enumerate() {
+        let too_many = retention.keep.is_some_and(|keep| keep > 0 && i >= keep);
+        let too_old = retention
+            .max_age_days
+            .is_some_and(|days| now - backup.created_at > chrono::Duration::days(days as i64));
+        let too_big = retention
+            .max_total_bytes
+            .is_some_and(|max| kept_bytes + backup.size_bytes > max);
+        if i > 0 && (too_many || too_old || too_big) {
+            pruned.push(i);
+        } else {
+            kept_bytes += backup.size_bytes;
+        }
+    }
+    pruned
+}
+
+/// Remove the backups `retention` doesn't keep, returning them
+pub fn prune_backups(retention: &Retention) -> Vec<BackupInfo> {
+    let backups = list_backups();
+    let mut pruned = Vec::new();
+    for i in select_pruned(&backups, retention, Utc::now()) {
+        let backup = &backups[i];
+        match fs::remove_dir_all(&backup.path) {
+            Ok(()) => {
+                super::size::forget(Path::new(&backup.path));
+                pruned.push(backup.clone());
+            }
+            Err(e) => eprintln!(
+                "Warning: Failed to remove old backup {}: {}",
+                backup.path, e
+            ),
+        }
+    }
+    super::size::save();
+    pruned
+}
+
+/// The backup `id` (its directory name, as `--backups` lists it), or the newest for "last"
+pub fn find_backup(id: &str) -> Result<Option<PathBuf>, std::io::Error> {
+    if id == "last" {
+        return find_latest_backup();
+    }
+    let path = get_backup_dir().join(id);
+    Ok(path.join("manifest.json").exists().then_some(path))
+}
+
+/// Whether `wanted` (a `--only` value) is the entry's cache ID or its original path
+fn entry_matches(entry: &CacheEntry, wanted: &str) -> bool {
+    entry.id == wanted
+        || std::path::absolute(wanted).is_ok_and(|wanted| Path::new(&entry.path) == wanted)
+}
+
+/// Restore the backup's entries, or only those `only` names. Restored entries leave the
+/// backup; it is removed once nothing is left in it.
+pub async fn restore_from_backup(
+    backup_path: &Path,
+    only: &[String],
+) -> Result<usize, std::io::Error> {
     let manifest_path = backup_path.join("manifest.json");
     let manifest_content = fs::read_to_string(&manifest_path)?;
-    let manifest: BackupManifest = serde_json::from_str(&manifest_content)?;
+    let mut manifest: BackupManifest = serde_json::from_str(&manifest_content)?;
 
-    let mut restored_count = 0;
+    if let Some(missing) = only
+        .iter()
+        .find(|wanted| !manifest.entries.iter().any(|e| entry_matches(e, wanted)))
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Nothing in {} matches {}", backup_path.display(), missing),
+        ));
+    }
+
+    let mut restored_ids = Vec::new();
 
     for entry in &manifest.entries {
+        if !only.is_empty() && !only.iter().any(|wanted| entry_matches(entry, wanted)) {
+            continue;
+        }
         let original_path = Path::new(&entry.path);
-        // Use the same unique naming scheme as in safe_delete_entries
-        let unique_name = format!(
-            "{}-{}",
-            entry.id.replace([':', '/', '\\'], "-"),
-            original_path.file_name().unwrap_or_default().to_string_lossy()
-        );
-        let backup_file = backup_path.join(&unique_name);
+        let backup_file = backup_path.join(backup_file_name(entry));
+        let archive = backup_path.join(backup_file_name(entry) + ARCHIVE_EXTENSION);
 
-        if backup_file.exists() {
-            // Ensure parent directory exists
+        if archive.exists() {
             if let Some(parent) = original_path.parent() {
                 fs::create_dir_all(parent)?;
             }
-
-            match copy_dir_or_file(&backup_file, original_path) {
-                Ok(()) => match remove_dir_or_file(&backup_file) {
-                    Ok(()) => restored_count += 1,
+            match decompress(&archive, original_path) {
+                Ok(()) => match fs::remove_file(&archive) {
+                    Ok(()) => restored_ids.push(entry.id.clone()),
                     Err(e) => eprintln!(
                         "Warning: Failed to remove backup after restore {}: {}",
                         entry.path, e
@@ -155,18 +426,39 @@ pub async fn restore_from_backup(backup_path: &Path) -> Result<usize, std::io::E
                 },
                 Err(e) => eprintln!("Warning: Failed to restore {}: {}", entry.path, e),
             }
+        } else if fs::symlink_metadata(&backup_file).is_ok() {
+            // Ensure parent directory exists
+            if let Some(parent) = original_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let moved = if fs::symlink_metadata(&backup_file)?.is_dir() {
+                prepare_directory_destination(original_path)
+            } else {
+                prepare_file_destination(original_path)
+            }
+            .and_then(|()| move_dir_or_file(&backup_file, original_path));
+            match moved {
+                Ok(()) => restored_ids.push(entry.id.clone()),
+                Err(e) => eprintln!("Warning: Failed to restore {}: {}", entry.path, e),
+            }
         } else {
             eprintln!("Warning: Backup file not found for {}", entry.path);
         }
     }
 
-    // Clean up backup directory only after ALL files are successfully restored
-    // If any files failed to restore, keep the backup so users can retry
-    if restored_count > 0 && restored_count == manifest.entries.len() {
-        let _ = fs::remove_dir_all(backup_path);
+    // Keep what wasn't restored (not selected, or failed) so users can retry; the backup
+    // directory goes only once everything in it is back
+    if !restored_ids.is_empty() {
+        manifest.entries.retain(|e| !restored_ids.contains(&e.id));
+        if manifest.entries.is_empty() {
+            let _ = fs::remove_dir_all(backup_path);
+        } else {
+            fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        }
     }
 
-    Ok(restored_count)
+    Ok(restored_ids.len())
 }
 
 fn copy_dir_or_file(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
@@ -268,3 +560,93 @@ fn remove_dir_or_file(path: &Path) -> Result<(), std::io::Error> {
         fs::remove_file(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_pruned() {
+        let now = Utc::now();
+        let backups: Vec<BackupInfo> = (0..5)
+            .map(|i| BackupInfo {
+                id: i.to_string(),
+                path: format!("/backups/{}", i),
+                created_at: now - chrono::Duration::days(i * 10),
+                entry_count: 0,
+                size_bytes: 100,
+                entries: Vec::new(),
+            })
+            .collect();
+        let retention = |keep, max_total_bytes, max_age_days| Retention {
+            keep,
+            max_total_bytes,
+            max_age_days,
+        };
+
+        assert_eq!(
+            select_pruned(&backups, &retention(Some(3), None, None), now),
+            vec![3, 4]
+        );
+        assert!(select_pruned(&backups, &retention(Some(0), None, None), now).is_empty());
+        assert_eq!(
+            select_pruned(&backups, &retention(None, None, Some(15)), now),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            select_pruned(&backups, &retention(None, Some(250), None), now),
+            vec![2, 3, 4]
+        );
+        // The newest backup stays even when it alone is over the limits
+        assert_eq!(
+            select_pruned(&backups, &retention(Some(1), Some(50), Some(0)), now),
+            vec![1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_compressed_backup_round_trip() {
+        let root = std::env::temp_dir().join(format!("port-kill-backup-{}", std::process::id()));
+        let cache = root.join("project").join("node_modules");
+        fs::create_dir_all(cache.join("left-pad")).unwrap();
+        fs::write(
+            cache.join("left-pad").join("index.js"),
+            "module.exports = 1;",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("backup")).unwrap();
+
+        let archive = back_up(&cache, &root.join("backup").join("js-node_modules"), true).unwrap();
+        assert!(archive.ends_with("js-node_modules.tar.zst"));
+        assert!(!cache.exists());
+
+        decompress(&archive, &cache).unwrap();
+        assert_eq!(
+            fs::read_to_string(cache.join("left-pad").join("index.js")).unwrap(),
+            "module.exports = 1;"
+        );
+        // Restoring over a cache that came back in the meantime is refused
+        assert!(decompress(&archive, &cache).is_err());
+
+        let moved = back_up(&cache, &root.join("backup").join("plain"), false).unwrap();
+        assert!(moved.join("left-pad").join("index.js").exists() && !cache.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_entry_matches() {
+        let entry = CacheEntry {
+            id: "js:node_modules".to_string(),
+            kind: "js".to_string(),
+            name: "node_modules".to_string(),
+            path: "/srv/app/node_modules".to_string(),
+            size_bytes: 0,
+            last_used_at: None,
+            stale: false,
+            details: serde_json::json!({}),
+        };
+        assert!(entry_matches(&entry, "js:node_modules"));
+        assert!(entry_matches(&entry, "/srv/app/node_modules"));
+        assert!(!entry_matches(&entry, "/srv/other/node_modules"));
+    }
+}
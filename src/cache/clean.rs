@@ -1,79 +1,40 @@
-use super::backup::safe_delete_entries;
-use super::detect::{
-    detect_cloudflare_caches, detect_hf_caches, detect_java_caches, detect_js_caches,
-    detect_js_pm_caches, detect_npx_caches, detect_python_caches, detect_rust_caches,
-    detect_torch_caches, detect_vercel_caches,
-};
+use super::backup::{compression, prune_backups, retention, safe_delete_entries};
+use super::list::{detect_caches, CacheQuery};
 use super::types::{CleanResponse, CleanSummary};
 use std::path::Path;
 
-pub async fn clean_caches(
-    lang: &str,
-    include_npx: bool,
-    include_js_pm: bool,
-    safe_delete: bool,
-    _force: bool,
-    include_hf: bool,
-    include_torch: bool,
-    include_vercel: bool,
-    include_cloudflare: bool,
-    stale_days: Option<u32>,
-) -> CleanResponse {
-    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+pub async fn clean_caches(query: &CacheQuery) -> CleanResponse {
+    let mut entries = detect_caches(query);
 
-    let mut entries = Vec::new();
-    let has_specialized_flags = include_npx
-        || include_js_pm
-        || include_hf
-        || include_torch
-        || include_vercel
-        || include_cloudflare;
-
-    // Specialized integrations
-    if include_npx {
-        entries.extend(detect_npx_caches(stale_days));
-    }
-    if include_js_pm {
-        entries.extend(detect_js_pm_caches(stale_days));
-    }
-    if include_hf {
-        entries.extend(detect_hf_caches(stale_days));
-    }
-    if include_torch {
-        entries.extend(detect_torch_caches(stale_days));
-    }
-    if include_vercel {
-        entries.extend(detect_vercel_caches(stale_days));
-    }
-    if include_cloudflare {
-        entries.extend(detect_cloudflare_caches(stale_days));
-    }
-
-    // Language-based detection (always runs unless only specialized flags were given)
-    if !has_specialized_flags || lang != "auto" {
-        if lang == "auto" || lang == "rust" {
-            entries.extend(detect_rust_caches(Path::new(&cwd), stale_days));
-        }
-        if lang == "auto" || lang == "js" {
-            entries.extend(detect_js_caches(Path::new(&cwd), stale_days));
-        }
-        if lang == "auto" || lang == "py" {
-            entries.extend(detect_python_caches(stale_days));
-        }
-        if lang == "auto" || lang == "java" {
-            entries.extend(detect_java_caches(stale_days));
-        }
-    }
+    // Never delete what the config excludes or pins
+    let rules = super::rules::load();
+    rules.apply(&mut entries);
 
     // Only delete stale entries when stale_days filtering is requested
-    if stale_days.is_some() {
+    if query.stale_days.is_some() {
         entries.retain(|e| e.stale);
     }
 
-    match safe_delete_entries(&entries, safe_delete).await {
+    match safe_delete_entries(&entries, query.safe_delete, compression(&rules)).await {
         Ok((deleted, backup_path)) => {
+            for entry in &deleted {
+                crate::audit_log::record_cache_clean(
+                    &entry.name,
+                    &entry.path,
+                    Some(entry.size_bytes),
+                    &Ok(()),
+                );
+                super::size::forget(Path::new(&entry.path));
+            }
+            super::size::save();
             let freed_bytes: u64 = deleted.iter().map(|e| e.size_bytes).sum();
             let deleted_count = deleted.len();
+            // A new backup was made; drop the old ones the retention policy doesn't keep
+            let pruned_backups = if backup_path.is_some() {
+                prune_backups(&retention(&rules))
+            } else {
+                Vec::new()
+            };
             CleanResponse {
                 deleted,
                 backed_up_to: backup_path,
@@ -81,10 +42,15 @@ pub async fn clean_caches(
                     freed_bytes: freed_bytes,
                     deleted_count: deleted_count,
                 },
+                pruned_backups,
             }
         }
         Err(e) => {
             eprintln!("Error during cleanup: {}", e);
+            let failed = Err(anyhow::Error::from(e));
+            for entry in &entries {
+                crate::audit_log::record_cache_clean(&entry.name, &entry.path, None, &failed);
+            }
             CleanResponse {
                 deleted: vec![],
                 backed_up_to: None,
@@ -92,6 +58,7 @@ pub async fn clean_caches(
                     freed_bytes: 0,
                     deleted_count: 0,
                 },
+                pruned_backups: Vec::new(),
             }
         }
     }
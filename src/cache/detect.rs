@@ -7,7 +7,7 @@ use std::path::{Path, PathBuf};
 /// Returns true if the entry should be considered stale for --stale-days filtering.
 /// When stale_days is None, returns false (no filtering). When stale_days is Some(threshold),
 /// returns true if mtime is older than threshold days, or if mtime is unknown.
-fn is_stale(mtime: Option<DateTime<Utc>>, stale_days: Option<u32>) -> bool {
+pub(crate) fn is_stale(mtime: Option<DateTime<Utc>>, stale_days: Option<u32>) -> bool {
     match (mtime, stale_days) {
         (_, None) => false,
         (None, Some(_)) => true,
@@ -19,62 +19,110 @@ fn is_stale(mtime: Option<DateTime<Utc>>, stale_days: Option<u32>) -> bool {
     }
 }
 
-fn dir_size_and_mtime(path: &Path) -> (u64, Option<DateTime<Utc>>) {
-    let mut total: u64 = 0;
-    let mut newest: Option<DateTime<Utc>> = None;
-    let _ = walkdir::WalkDir::new(path).into_iter().for_each(|e| {
-        if let Ok(entry) = e {
-            if let Ok(md) = entry.metadata() {
-                if md.is_file() {
-                    total = total.saturating_add(md.len());
-                }
-                if let Ok(modified) = md.modified() {
-                    let dt: DateTime<Utc> = modified.into();
-                    newest = Some(match newest {
-                        Some(n) => n.max(dt),
-                        None => dt,
-                    });
-                }
-            }
-        }
-    });
-    (total, newest)
+pub(crate) fn dir_size_and_mtime(path: &Path) -> (u64, Option<DateTime<Utc>>) {
+    super::size::measure(path)
 }
 
-pub fn detect_rust_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry> {
+/// How far below each root `detect_rust_caches` looks for Cargo projects
+const RUST_ROOT_DEPTH: usize = 8;
+
+/// `target/` of every Cargo project under `roots`, plus the caches Cargo and sccache share
+/// between projects
+pub fn detect_rust_caches(roots: &[PathBuf], stale_days: Option<u32>) -> Vec<CacheEntry> {
     let mut entries = Vec::new();
 
-    // project target/
-    let target = root.join("target");
-    if target.exists() {
-        let (size, mtime) = dir_size_and_mtime(&target);
-        entries.push(CacheEntry {
-            id: "rust:project-target".to_string(),
-            kind: "rust".to_string(),
-            name: "Rust target".to_string(),
-            path: target.to_string_lossy().to_string(),
-            size_bytes: size,
-            last_used_at: mtime,
-            stale: is_stale(mtime, stale_days),
-            details: json!({}),
-        });
-    }
+    for root in roots {
+        let mut it = walkdir::WalkDir::new(root)
+            .max_depth(RUST_ROOT_DEPTH)
+            .into_iter();
+        while let Some(result) = it.next() {
+            let entry = match result {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+            let dir_name = entry.file_name().to_string_lossy();
+            if entry.depth() > 0 && (dir_name.starts_with('.') || dir_name == "node_modules") {
+                it.skip_current_dir();
+                continue;
+            }
+            if dir_name != "target" {
+                continue;
+            }
+            let Some(project) = entry.path().parent() else {
+                continue;
+            };
+            if !project.join("Cargo.toml").exists() {
+                continue;
+            }
 
-    // ~/.cargo/registry (downloaded crate sources and indices — safe to delete)
-    if let Ok(home) = std::env::var("HOME") {
-        let cargo_registry = PathBuf::from(home).join(".cargo/registry");
-        if cargo_registry.exists() {
-            let (size, mtime) = dir_size_and_mtime(&cargo_registry);
+            let (size, mtime) = dir_size_and_mtime(entry.path());
+            let profiles: Vec<String> = ["debug", "release"]
+                .iter()
+                .filter(|profile| entry.path().join(profile).is_dir())
+                .map(|profile| profile.to_string())
+                .collect();
             entries.push(CacheEntry {
-                id: "rust:cargo-registry".to_string(),
+                id: format!("rust:target:{}", entry.path().to_string_lossy()),
                 kind: "rust".to_string(),
-                name: "Cargo registry cache".to_string(),
-                path: cargo_registry.to_string_lossy().to_string(),
+                name: "Rust target".to_string(),
+                path: entry.path().to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
                 stale: is_stale(mtime, stale_days),
-                details: json!({}),
+                details: json!({
+                    "type": "project_target",
+                    "project": project.to_string_lossy(),
+                    "profiles": profiles,
+                }),
+            });
+            it.skip_current_dir();
+        }
+    }
+
+    // Downloaded crate sources and indices, and sccache's compiled objects — safe to delete
+    if let Ok(home) = std::env::var("HOME") {
+        let home_path = PathBuf::from(home);
+        let sccache_dir = std::env::var("SCCACHE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                if cfg!(target_os = "macos") {
+                    home_path.join("Library/Caches/Mozilla.sccache")
+                } else {
+                    home_path.join(".cache/sccache")
+                }
             });
+        let home_caches = [
+            (
+                "rust:cargo-registry",
+                home_path.join(".cargo/registry"),
+                "Cargo registry cache",
+                "cargo_registry",
+            ),
+            (
+                "rust:cargo-git",
+                home_path.join(".cargo/git"),
+                "Cargo git checkouts",
+                "cargo_git",
+            ),
+            ("rust:sccache", sccache_dir, "sccache cache", "sccache"),
+        ];
+        for (id, path, name, cache_type) in home_caches {
+            if path.exists() {
+                let (size, mtime) = dir_size_and_mtime(&path);
+                entries.push(CacheEntry {
+                    id: id.to_string(),
+                    kind: "rust".to_string(),
+                    name: name.to_string(),
+                    path: path.to_string_lossy().to_string(),
+                    size_bytes: size,
+                    last_used_at: mtime,
+                    stale: is_stale(mtime, stale_days),
+                    details: json!({ "type": cache_type }),
+                });
+            }
         }
     }
 
@@ -287,11 +335,31 @@ pub fn detect_js_pm_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
     entries
 }
 
-pub fn detect_python_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
-    let mut entries = Vec::new();
+/// Python cache categories `--python-categories` picks from
+pub const PYTHON_CATEGORIES: [&str; 8] = [
+    "pip", "poetry", "conda", "pycache", "pytest", "mypy", "ruff", "venv",
+];
+
+/// Project caches and virtualenvs under the current directory, and the pip, poetry and conda
+/// package caches in the home directory. `categories` limits it to some of
+/// [`PYTHON_CATEGORIES`]; empty means all of them.
+pub fn detect_python_caches(categories: &[String], stale_days: Option<u32>) -> Vec<CacheEntry> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let home = std::env::var("HOME").ok().map(PathBuf::from);
+    python_caches_under(&cwd, home.as_deref(), categories, stale_days)
+}
+
+/// Project caches under `root`, plus the package caches in `home`
+fn python_caches_under(
+    root: &Path,
+    home: Option<&Path>,
+    categories: &[String],
+    stale_days: Option<u32>,
+) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    let wanted = |category: &str| categories.is_empty() || categories.iter().any(|c| c == category);
 
-    let mut it = walkdir::WalkDir::new(&cwd).into_iter();
+    let mut it = walkdir::WalkDir::new(root).into_iter();
     while let Some(result) = it.next() {
         let entry = match result {
             Ok(e) => e,
@@ -302,38 +370,169 @@ pub fn detect_python_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
         }
 
         let dir_name = entry.file_name();
-        let (cache_type, cache_name) = if dir_name == "__pycache__" {
+        if dir_name == "node_modules" || dir_name == ".git" {
+            it.skip_current_dir();
+            continue;
+        }
+        // Any directory with a pyvenv.cfg is a virtualenv, whatever it's called
+        let (category, cache_name) = if dir_name == "__pycache__" {
             ("pycache", "__pycache__")
-        } else if dir_name == ".venv" {
+        } else if entry.path().join("pyvenv.cfg").is_file() {
             ("venv", "Python virtual environment")
         } else if dir_name == ".pytest_cache" {
-            ("pytest_cache", "pytest cache")
+            ("pytest", "pytest cache")
+        } else if dir_name == ".mypy_cache" {
+            ("mypy", "mypy cache")
+        } else if dir_name == ".ruff_cache" {
+            ("ruff", "Ruff cache")
         } else {
             continue;
         };
+        it.skip_current_dir();
+        if !wanted(category) {
+            continue;
+        }
 
         let (size, mtime) = dir_size_and_mtime(entry.path());
         entries.push(CacheEntry {
-            id: format!("python:{}:{}", cache_type, entry.path().to_string_lossy()),
+            id: format!("python:{}:{}", category, entry.path().to_string_lossy()),
             kind: "python".to_string(),
             name: cache_name.to_string(),
             path: entry.path().to_string_lossy().to_string(),
             size_bytes: size,
             last_used_at: mtime,
             stale: is_stale(mtime, stale_days),
-            details: json!({ "type": cache_type }),
+            details: json!({ "type": category }),
         });
-        it.skip_current_dir();
+    }
+
+    if let Some(home_path) = home {
+        let user_cache = if cfg!(target_os = "macos") {
+            home_path.join("Library/Caches")
+        } else {
+            std::env::var("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| home_path.join(".cache"))
+        };
+        let env_dir = |var: &str| std::env::var(var).ok().map(PathBuf::from);
+
+        let mut home_caches = vec![
+            (
+                "pip",
+                env_dir("PIP_CACHE_DIR").unwrap_or_else(|| user_cache.join("pip")),
+                "pip cache",
+            ),
+            (
+                "poetry",
+                env_dir("POETRY_CACHE_DIR").unwrap_or_else(|| user_cache.join("pypoetry")),
+                "Poetry cache",
+            ),
+        ];
+        // Downloaded packages of every conda install; CONDA_PKGS_DIRS lists them when set
+        let conda_pkgs: Vec<PathBuf> = match std::env::var("CONDA_PKGS_DIRS") {
+            Ok(dirs) => dirs
+                .split(',')
+                .map(|dir| PathBuf::from(dir.trim()))
+                .collect(),
+            Err(_) => [
+                "miniconda3",
+                "anaconda3",
+                "miniforge3",
+                "mambaforge",
+                ".conda",
+            ]
+            .iter()
+            .map(|install| home_path.join(install).join("pkgs"))
+            .collect(),
+        };
+        home_caches.extend(
+            conda_pkgs
+                .into_iter()
+                .map(|dir| ("conda", dir, "conda package cache")),
+        );
+
+        for (category, path, name) in home_caches {
+            if !wanted(category) || !path.exists() {
+                continue;
+            }
+            let (size, mtime) = dir_size_and_mtime(&path);
+            entries.push(CacheEntry {
+                id: format!("python:{}:{}", category, path.to_string_lossy()),
+                kind: "python".to_string(),
+                name: name.to_string(),
+                path: path.to_string_lossy().to_string(),
+                size_bytes: size,
+                last_used_at: mtime,
+                stale: is_stale(mtime, stale_days),
+                details: json!({ "type": category }),
+            });
+        }
     }
 
     entries
 }
 
+/// Caches shared by every project on the machine: downloaded dependencies and build tool
+/// distributions, all re-downloaded on the next build
+const JAVA_HOME_CACHES: [(&str, &str, &str, &str); 7] = [
+    (
+        "gradle:caches",
+        ".gradle/caches",
+        "Gradle dependency cache",
+        "gradle_caches",
+    ),
+    (
+        "gradle:wrapper",
+        ".gradle/wrapper/dists",
+        "Gradle wrapper distributions",
+        "gradle_wrapper",
+    ),
+    (
+        "maven:repository",
+        ".m2/repository",
+        "Maven repository cache",
+        "maven_repository",
+    ),
+    ("sbt:ivy2", ".ivy2/cache", "Ivy cache (sbt)", "ivy_cache"),
+    ("sbt:boot", ".sbt/boot", "sbt launcher cache", "sbt_boot"),
+    (
+        "sbt:coursier",
+        ".cache/coursier",
+        "Coursier cache (sbt)",
+        "coursier_cache",
+    ),
+    (
+        "sbt:coursier",
+        "Library/Caches/Coursier",
+        "Coursier cache (sbt)",
+        "coursier_cache",
+    ),
+];
+
 pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
-    let mut entries = Vec::new();
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let home = std::env::var("HOME").ok().map(PathBuf::from);
+    java_caches_under(&cwd, home.as_deref(), stale_days)
+}
 
-    let mut it = walkdir::WalkDir::new(&cwd).into_iter();
+/// Project caches under `root`, plus the shared caches in `home`
+fn java_caches_under(root: &Path, home: Option<&Path>, stale_days: Option<u32>) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    // Run from the home directory, the walk would otherwise take ~/.gradle for a project's
+    // .gradle and count the shared caches inside it twice
+    let shared_roots: Vec<PathBuf> = home
+        .map(|home| {
+            std::iter::once(home.join(".gradle"))
+                .chain(
+                    JAVA_HOME_CACHES
+                        .iter()
+                        .map(|(_, relative, _, _)| home.join(relative)),
+                )
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut it = walkdir::WalkDir::new(root).into_iter();
     while let Some(result) = it.next() {
         let entry = match result {
             Ok(e) => e,
@@ -344,6 +543,13 @@ pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
         }
 
         let dir_name = entry.file_name();
+        if dir_name == "node_modules"
+            || dir_name == ".git"
+            || shared_roots.iter().any(|shared| shared == entry.path())
+        {
+            it.skip_current_dir();
+            continue;
+        }
 
         if dir_name == ".gradle" {
             let (size, mtime) = dir_size_and_mtime(entry.path());
@@ -385,27 +591,171 @@ pub fn detect_java_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
                 });
                 it.skip_current_dir();
             }
-        }
-    }
-
-    // Maven local repository (~/.m2/repository) — downloaded artifacts, safe to delete
-    if let Ok(home) = std::env::var("HOME") {
-        let maven_repo = PathBuf::from(home).join(".m2/repository");
-        if maven_repo.exists() {
-            let (size, mtime) = dir_size_and_mtime(&maven_repo);
+        } else if dir_name == "target" {
+            // Only JVM build output: a Rust target/ has neither file next to it
+            let Some(project) = entry.path().parent() else {
+                continue;
+            };
+            let (cache_type, cache_name) = if is_sbt_project(project) {
+                ("sbt_target", "sbt target")
+            } else if project.join("pom.xml").exists() {
+                ("maven_target", "Maven target")
+            } else {
+                continue;
+            };
+            let (size, mtime) = dir_size_and_mtime(entry.path());
             entries.push(CacheEntry {
-                id: "maven:repository".to_string(),
+                id: format!("java:{}:{}", cache_type, entry.path().to_string_lossy()),
                 kind: "java".to_string(),
-                name: "Maven repository cache".to_string(),
-                path: maven_repo.to_string_lossy().to_string(),
+                name: cache_name.to_string(),
+                path: entry.path().to_string_lossy().to_string(),
                 size_bytes: size,
                 last_used_at: mtime,
                 stale: is_stale(mtime, stale_days),
-                details: json!({ "type": "maven_repository" }),
+                details: json!({ "type": cache_type }),
             });
+            it.skip_current_dir();
+        }
+    }
+
+    if let Some(home) = home {
+        for (id, relative, name, cache_type) in &JAVA_HOME_CACHES {
+            let path = home.join(relative);
+            if path.exists() {
+                let (size, mtime) = dir_size_and_mtime(&path);
+                entries.push(CacheEntry {
+                    id: id.to_string(),
+                    kind: "java".to_string(),
+                    name: name.to_string(),
+                    path: path.to_string_lossy().to_string(),
+                    size_bytes: size,
+                    last_used_at: mtime,
+                    stale: is_stale(mtime, stale_days),
+                    details: json!({ "type": cache_type }),
+                });
+            }
+        }
+    }
+
+    drop_nested(&mut entries);
+    entries
+}
+
+/// Drop entries inside another entry, so nothing is counted or cleaned twice (e.g. when
+/// run from a directory that is itself under one of the shared caches)
+fn drop_nested(entries: &mut Vec<CacheEntry>) {
+    let paths: Vec<PathBuf> = entries.iter().map(|e| PathBuf::from(&e.path)).collect();
+    entries.retain(|entry| {
+        let path = Path::new(&entry.path);
+        !paths
+            .iter()
+            .any(|other| other != path && path.starts_with(other))
+    });
+}
+
+/// A directory with a build.sbt, or the `project/` directory of one (its target/ holds the
+/// compiled build definition)
+fn is_sbt_project(dir: &Path) -> bool {
+    dir.join("build.sbt").exists()
+        || (dir.file_name().is_some_and(|name| name == "project")
+            && dir
+                .parent()
+                .is_some_and(|parent| parent.join("build.sbt").exists()))
+}
+
+/// Xcode, CocoaPods and Homebrew caches under ~/Library. Xcode's are listed per project
+/// (DerivedData) and per OS version (device support), so `--stale-days` drops the ones
+/// nothing has used in a while rather than all of them.
+pub fn detect_macos_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+    let Ok(home) = std::env::var("HOME") else {
+        return Vec::new();
+    };
+    let homebrew = std::env::var("HOMEBREW_CACHE").ok().map(PathBuf::from);
+    macos_caches_in(Path::new(&home), homebrew, stale_days)
+}
+
+/// The caches under `home`/Library, with Homebrew's at `homebrew` when it was moved
+fn macos_caches_in(
+    home: &Path,
+    homebrew: Option<PathBuf>,
+    stale_days: Option<u32>,
+) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    let library = home.join("Library");
+    let xcode = library.join("Developer/Xcode");
+
+    let mut push = |kind: &str, id: String, name: String, path: &Path, cache_type: &str| {
+        let (size, mtime) = dir_size_and_mtime(path);
+        entries.push(CacheEntry {
+            id,
+            kind: kind.to_string(),
+            name,
+            path: path.to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: mtime,
+            stale: is_stale(mtime, stale_days),
+            details: json!({ "type": cache_type }),
+        });
+    };
+
+    // DerivedData/<Project>-<hash>: rebuilt on the next build of that project
+    if let Ok(projects) = fs::read_dir(xcode.join("DerivedData")) {
+        for project in projects.flatten().filter(|e| e.path().is_dir()) {
+            let dir_name = project.file_name().to_string_lossy().to_string();
+            let name = dir_name
+                .rsplit_once('-')
+                .map_or(dir_name.as_str(), |(name, _)| name);
+            push(
+                "xcode",
+                format!("xcode:derived-data:{}", dir_name),
+                format!("DerivedData: {}", name),
+                &project.path(),
+                "derived_data",
+            );
         }
     }
 
+    // Debug symbols copied from every device and OS version ever connected
+    for platform in ["iOS", "watchOS", "tvOS", "visionOS"] {
+        let support = xcode.join(format!("{} DeviceSupport", platform));
+        if let Ok(versions) = fs::read_dir(&support) {
+            for version in versions.flatten().filter(|e| e.path().is_dir()) {
+                let version_name = version.file_name().to_string_lossy().to_string();
+                push(
+                    "xcode",
+                    format!("xcode:device-support:{}:{}", platform, version_name),
+                    format!("{} DeviceSupport {}", platform, version_name),
+                    &version.path(),
+                    "device_support",
+                );
+            }
+        }
+    }
+
+    let cocoapods = library.join("Caches/CocoaPods");
+    if cocoapods.exists() {
+        push(
+            "cocoapods",
+            "cocoapods:cache".to_string(),
+            "CocoaPods cache".to_string(),
+            &cocoapods,
+            "cocoapods_cache",
+        );
+    }
+
+    // Downloaded bottles and sources; installed formulae in the Cellar are left to
+    // `brew cleanup`
+    let homebrew = homebrew.unwrap_or_else(|| library.join("Caches/Homebrew"));
+    if homebrew.exists() {
+        push(
+            "homebrew",
+            "homebrew:cache".to_string(),
+            "Homebrew download cache".to_string(),
+            &homebrew,
+            "homebrew_cache",
+        );
+    }
+
     entries
 }
 
@@ -514,3 +864,211 @@ pub fn detect_cloudflare_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
 
     entries
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn make_dirs(root: &Path, dirs: &[&str]) {
+        for dir in dirs {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rust_targets_under_roots() {
+        let root =
+            std::env::temp_dir().join(format!("port-kill-rust-roots-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        make_dirs(
+            &root,
+            &[
+                "app/target/debug",
+                "app/target/release",
+                "app/target/package/app-0.1.0/target",
+                "tools/cli/target/debug",
+                "notes/target",
+                ".hidden/crate/target/debug",
+                "web/node_modules/pkg/target",
+            ],
+        );
+        for project in [
+            "app",
+            "app/target/package/app-0.1.0",
+            "tools/cli",
+            ".hidden/crate",
+            "web/node_modules/pkg",
+        ] {
+            fs::write(root.join(project).join("Cargo.toml"), b"[package]").unwrap();
+        }
+
+        let entries = detect_rust_caches(&[root.join("app"), root.join("tools")], None);
+        let targets: Vec<_> = entries
+            .iter()
+            .filter(|e| e.id.starts_with("rust:target:"))
+            .collect();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].path, root.join("app/target").to_string_lossy());
+        assert_eq!(targets[0].details["profiles"], json!(["debug", "release"]));
+        assert_eq!(
+            targets[1].path,
+            root.join("tools/cli/target").to_string_lossy()
+        );
+
+        // Hidden directories, node_modules and target/ without a Cargo.toml are left out
+        let entries = detect_rust_caches(std::slice::from_ref(&root), None);
+        let targets = entries
+            .iter()
+            .filter(|e| e.id.starts_with("rust:target:"))
+            .count();
+        assert_eq!(targets, 2);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_python_categories() {
+        let base = std::env::temp_dir().join(format!("port-kill-py-caches-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let (root, home) = (base.join("proj"), base.join("home"));
+        make_dirs(
+            &root,
+            &[
+                "__pycache__",
+                ".venv/lib/site-packages/__pycache__",
+                ".pytest_cache",
+                ".mypy_cache",
+                ".ruff_cache",
+                "node_modules/pkg/__pycache__",
+            ],
+        );
+        fs::write(root.join(".venv/pyvenv.cfg"), b"home = /usr/bin").unwrap();
+        make_dirs(
+            &home,
+            &[".cache/pip", "Library/Caches/pip", "miniconda3/pkgs"],
+        );
+
+        let types = |entries: &[CacheEntry]| {
+            let mut types: Vec<_> = entries
+                .iter()
+                .map(|e| e.details["type"].as_str().unwrap().to_string())
+                .collect();
+            types.sort();
+            types
+        };
+        let all = python_caches_under(&root, None, &[], None);
+        assert_eq!(types(&all), ["mypy", "pycache", "pytest", "ruff", "venv"]);
+
+        // --python-categories as the command line gives it
+        let categories = |value: &str| {
+            let args = crate::cli::Args::try_parse_from([
+                "port-kill",
+                "cache",
+                "--python-categories",
+                value,
+            ])?;
+            let cache = args.cache.as_ref().and_then(|c| c.args()).unwrap();
+            Ok::<_, clap::Error>(crate::cache::list::CacheQuery::from(cache).python_categories)
+        };
+        let project_only = categories("pycache,venv").unwrap();
+        assert_eq!(project_only, ["pycache", "venv"]);
+        let entries = python_caches_under(&root, Some(&home), &project_only, None);
+        assert_eq!(types(&entries), ["pycache", "venv"]);
+
+        let pip = python_caches_under(&root, Some(&home), &categories("pip").unwrap(), None);
+        assert!(pip.iter().all(|e| e.details["type"] == "pip"));
+        assert!(categories("wheel").is_err());
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_macos_caches() {
+        let home =
+            std::env::temp_dir().join(format!("port-kill-macos-caches-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        make_dirs(
+            &home,
+            &[
+                "Library/Developer/Xcode/DerivedData/MyApp-abcdefghij",
+                "Library/Developer/Xcode/DerivedData/My-Other-App-klmnopqrst",
+                "Library/Developer/Xcode/iOS DeviceSupport/17.4 (21E219)",
+                "Library/Developer/Xcode/watchOS DeviceSupport/10.4",
+                "Library/Caches/CocoaPods",
+                "Library/Caches/Homebrew",
+            ],
+        );
+        fs::write(
+            home.join("Library/Developer/Xcode/DerivedData/info.plist"),
+            b"not a project",
+        )
+        .unwrap();
+
+        let entries = macos_caches_in(&home, None, None);
+        let mut ids: Vec<_> = entries.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            [
+                "cocoapods:cache",
+                "homebrew:cache",
+                "xcode:derived-data:My-Other-App-klmnopqrst",
+                "xcode:derived-data:MyApp-abcdefghij",
+                "xcode:device-support:iOS:17.4 (21E219)",
+                "xcode:device-support:watchOS:10.4",
+            ]
+        );
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert!(names.contains(&"DerivedData: My-Other-App"));
+        assert!(names.contains(&"iOS DeviceSupport 17.4 (21E219)"));
+
+        // HOMEBREW_CACHE moves the Homebrew cache; an empty Library has nothing
+        let moved = macos_caches_in(&home, Some(home.join("brew")), None);
+        assert!(moved.iter().all(|e| e.kind != "homebrew"));
+        assert!(macos_caches_in(&home.join("nowhere"), None, None).is_empty());
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn test_java_caches_from_home_are_not_counted_twice() {
+        let home =
+            std::env::temp_dir().join(format!("port-kill-java-caches-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        make_dirs(
+            &home,
+            &[
+                ".gradle/caches/modules-2",
+                ".gradle/wrapper/dists/gradle-8.5-bin",
+                ".gradle/daemon",
+                ".m2/repository/org/build",
+                "code/app/.gradle/8.5",
+            ],
+        );
+        fs::write(home.join(".m2/repository/org/build/lib.jar"), b"jar").unwrap();
+
+        let entries = java_caches_under(&home, Some(&home), None);
+        let mut paths: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                Path::new(&e.path)
+                    .strip_prefix(&home)
+                    .unwrap()
+                    .to_path_buf()
+            })
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                Path::new(".gradle/caches"),
+                Path::new(".gradle/wrapper/dists"),
+                Path::new(".m2/repository"),
+                Path::new("code/app/.gradle"),
+            ]
+        );
+
+        // Run from inside a shared cache, the walk's finds are part of it
+        let from_cache = java_caches_under(&home.join(".m2/repository"), Some(&home), None);
+        assert!(from_cache.iter().all(|e| !e.path.ends_with("org/build")));
+        let _ = fs::remove_dir_all(&home);
+    }
+}
@@ -1,16 +1,96 @@
+use super::backup::{backup_file_name, get_backup_dir, BackupManifest};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize)]
+/// Result of `cache --doctor`
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct DoctorReport {
     pub ok: bool,
     pub notes: Vec<String>,
     pub warnings: Vec<String>,
     pub errors: Vec<String>,
+    /// Checks of port-kill's own state, each with a stable code and what `--fix` does
+    pub findings: Vec<DoctorFinding>,
 }
 
-pub async fn doctor() -> DoctorReport {
+impl DoctorReport {
+    /// The most serious problem left, counting the plain warnings and errors
+    pub fn worst_severity(&self) -> Option<Severity> {
+        if !self.errors.is_empty() {
+            return Some(Severity::Error);
+        }
+        let worst = self
+            .findings
+            .iter()
+            .filter(|finding| !finding.fixed)
+            .map(|finding| finding.severity)
+            .max();
+        if !self.warnings.is_empty() {
+            return worst.max(Some(Severity::Warning));
+        }
+        worst
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DoctorFinding {
+    /// Stable identifier of the check, e.g. `backup.stale-manifest`
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// What `--fix` does about it, when there is a fix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+    /// `--fix` applied the fix in this run
+    pub fixed: bool,
+}
+
+impl DoctorFinding {
+    fn new(code: &str, severity: Severity, message: String, path: &Path) -> Self {
+        Self {
+            code: code.to_string(),
+            severity,
+            message,
+            path: Some(path.to_string_lossy().to_string()),
+            fix: None,
+            fixed: false,
+        }
+    }
+
+    /// Describe the fix, and apply it when `apply` is set
+    fn fix(
+        mut self,
+        description: &str,
+        apply: bool,
+        action: impl FnOnce() -> std::io::Result<()>,
+    ) -> Self {
+        self.fix = Some(description.to_string());
+        if apply {
+            match action() {
+                Ok(()) => self.fixed = true,
+                Err(e) => self.message.push_str(&format!(" (fix failed: {})", e)),
+            }
+        }
+        self
+    }
+}
+
+pub async fn doctor(fix: bool) -> DoctorReport {
     let mut notes = Vec::new();
     let mut warnings = Vec::new();
     let mut errors = Vec::new();
@@ -30,6 +110,13 @@ pub async fn doctor() -> DoctorReport {
             (".vercel", "Vercel cache"),
             (".cloudflare", "Cloudflare cache"),
             (".m2", "Maven cache"),
+            ("Library/Developer/Xcode/DerivedData", "Xcode DerivedData"),
+            (
+                "Library/Developer/Xcode/iOS DeviceSupport",
+                "Xcode iOS device support",
+            ),
+            ("Library/Caches/CocoaPods", "CocoaPods cache"),
+            ("Library/Caches/Homebrew", "Homebrew download cache"),
         ];
 
         for (dir, description) in &cache_dirs {
@@ -69,22 +156,25 @@ pub async fn doctor() -> DoctorReport {
 
         // Check for large cache directories
         let large_caches = [
-            (".cargo", 1_000_000_000),      // 1GB
-            (".npm", 500_000_000),          // 500MB
-            (".pnpm-store", 1_000_000_000), // 1GB
+            (".cargo", 1_000_000_000),                                     // 1GB
+            (".npm", 500_000_000),                                         // 500MB
+            (".pnpm-store", 1_000_000_000),                                // 1GB
+            ("Library/Developer/Xcode/DerivedData", 10_000_000_000),       // 10GB
+            ("Library/Developer/Xcode/iOS DeviceSupport", 10_000_000_000), // 10GB
+            ("Library/Caches/CocoaPods", 2_000_000_000),                   // 2GB
+            ("Library/Caches/Homebrew", 2_000_000_000),                    // 2GB
         ];
 
         for (dir, threshold) in &large_caches {
             let path = PathBuf::from(&home).join(dir);
             if path.exists() {
-                if let Ok(size) = get_dir_size(&path) {
-                    if size > *threshold {
-                        warnings.push(format!(
-                            "Large cache detected: {} ({:.1} MB)",
-                            dir,
-                            size as f64 / 1_000_000.0
-                        ));
-                    }
+                let (size, _) = super::size::measure(&path);
+                if size > *threshold {
+                    warnings.push(format!(
+                        "Large cache detected: {} ({:.1} MB)",
+                        dir,
+                        size as f64 / 1_000_000.0
+                    ));
                 }
             }
         }
@@ -92,16 +182,16 @@ pub async fn doctor() -> DoctorReport {
         errors.push("HOME environment variable not set".to_string());
     }
 
-    // Check for backup directories
-    let backup_dir = PathBuf::from(".cachekill-backup");
-    if backup_dir.exists() {
-        if let Ok(entries) = fs::read_dir(&backup_dir) {
-            let count = entries.count();
-            notes.push(format!(
-                "Found {} backup(s) from previous clean operations",
-                count
-            ));
-        }
+    // Check for backups from previous safe-delete cleans
+    let backups = super::backup::list_backups();
+    if !backups.is_empty() {
+        let total: u64 = backups.iter().map(|b| b.size_bytes).sum();
+        notes.push(format!(
+            "Found {} backup(s) from previous clean operations, {} in {} (see cache --backups)",
+            backups.len(),
+            super::output::human_size(total),
+            super::backup::get_backup_dir().display()
+        ));
     }
 
     // Check current working directory for cache files
@@ -115,25 +205,185 @@ pub async fn doctor() -> DoctorReport {
         }
     }
 
+    // Check that port scanning works, so "no processes" can be trusted
+    crate::scan_health::diagnose(&mut notes, &mut warnings, &mut errors);
+    notes.extend(crate::offline::doctor_notes());
+
+    // port-kill's own directories and backups, which --fix can repair
+    let findings = check_state(fix);
+    for finding in &findings {
+        if finding.fixed {
+            notes.push(format!("Fixed: {}", finding.message));
+            continue;
+        }
+        match finding.severity {
+            Severity::Info => notes.push(finding.message.clone()),
+            Severity::Warning => warnings.push(finding.message.clone()),
+            Severity::Error => errors.push(finding.message.clone()),
+        }
+    }
+
+    super::size::save();
+
     let ok = errors.is_empty();
     DoctorReport {
         ok,
         notes,
         warnings,
         errors,
+        findings,
     }
 }
 
-fn get_dir_size(path: &std::path::Path) -> Result<u64, std::io::Error> {
-    let mut total = 0u64;
-    for entry in walkdir::WalkDir::new(path) {
-        if let Ok(entry) = entry {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    total += metadata.len();
+fn check_state(fix: bool) -> Vec<DoctorFinding> {
+    let mut findings = Vec::new();
+
+    let state_dir = crate::uninstall::home_dir().join(".port-kill");
+    let backup_dir = get_backup_dir();
+    for (dir, what) in [(&state_dir, "State"), (&backup_dir, "Backup")] {
+        if !dir.exists() {
+            findings.push(
+                DoctorFinding::new(
+                    "dir.missing",
+                    Severity::Info,
+                    format!("{} directory {} doesn't exist", what, dir.display()),
+                    dir,
+                )
+                .fix("create it", fix, || fs::create_dir_all(dir)),
+            );
+        }
+    }
+
+    if let Ok(read) = fs::read_dir(&backup_dir) {
+        let mut dirs: Vec<PathBuf> = read
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        dirs.sort();
+        findings.extend(dirs.iter().filter_map(|dir| check_backup(dir, fix)));
+    }
+
+    findings
+}
+
+fn check_backup(dir: &Path, fix: bool) -> Option<DoctorFinding> {
+    let manifest_path = dir.join("manifest.json");
+    let manifest: Option<BackupManifest> = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    let Some(mut manifest) = manifest else {
+        // A clean interrupted before the manifest was written, or a damaged manifest
+        let finding = DoctorFinding::new(
+            "backup.orphaned",
+            Severity::Warning,
+            format!(
+                "Backup {} has no readable manifest, so --backups doesn't list it and it is never pruned",
+                dir.display()
+            ),
+            dir,
+        );
+        return Some(finding.fix(
+            "write an empty manifest so it is listed and pruned; its files stay for recovery by hand",
+            fix,
+            || {
+                if manifest_path.exists() {
+                    fs::rename(&manifest_path, dir.join("manifest.json.broken"))?;
                 }
+                let manifest = BackupManifest {
+                    timestamp: backup_time(dir),
+                    entries: Vec::new(),
+                    backup_dir: dir.to_string_lossy().to_string(),
+                };
+                fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+            },
+        ));
+    };
+
+    let missing: Vec<String> = manifest
+        .entries
+        .iter()
+        .filter(|entry| !dir.join(backup_file_name(entry)).exists())
+        .map(|entry| entry.path.clone())
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+    let finding = DoctorFinding::new(
+        "backup.stale-manifest",
+        Severity::Warning,
+        format!(
+            "Backup {} lists {} entr{} with no copy left to restore: {}",
+            dir.display(),
+            missing.len(),
+            if missing.len() == 1 { "y" } else { "ies" },
+            missing.join(", ")
+        ),
+        dir,
+    );
+    Some(finding.fix(
+        "drop those entries from the manifest, and the backup once nothing is left in it",
+        fix,
+        || {
+            manifest
+                .entries
+                .retain(|entry| !missing.contains(&entry.path));
+            let only_manifest = fs::read_dir(dir)?.count() == 1;
+            if manifest.entries.is_empty() && only_manifest {
+                fs::remove_dir_all(dir)
+            } else {
+                fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
             }
-        }
+        },
+    ))
+}
+
+/// When a backup was made: from its directory name, else its modification time
+fn backup_time(dir: &Path) -> DateTime<Utc> {
+    dir.file_name()
+        .and_then(|name| {
+            NaiveDateTime::parse_from_str(&name.to_string_lossy(), "%Y-%m-%dT%H-%M-%SZ").ok()
+        })
+        .map(|time| time.and_utc())
+        .or_else(|| {
+            fs::metadata(dir)
+                .and_then(|md| md.modified())
+                .ok()
+                .map(DateTime::from)
+        })
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worst_severity() {
+        let finding = |severity, fixed| DoctorFinding {
+            code: "test".to_string(),
+            severity,
+            message: String::new(),
+            path: None,
+            fix: None,
+            fixed,
+        };
+        let mut report = DoctorReport {
+            ok: true,
+            notes: vec!["note".to_string()],
+            warnings: Vec::new(),
+            errors: Vec::new(),
+            findings: vec![
+                finding(Severity::Info, false),
+                finding(Severity::Error, true),
+            ],
+        };
+        assert_eq!(report.worst_severity(), Some(Severity::Info));
+        report.warnings.push("warning".to_string());
+        assert_eq!(report.worst_severity(), Some(Severity::Warning));
+        report.errors.push("error".to_string());
+        assert_eq!(report.worst_severity(), Some(Severity::Error));
+        assert!(Severity::Warning >= Severity::Info);
     }
-    Ok(total)
 }
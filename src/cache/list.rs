@@ -1,67 +1,151 @@
 use super::detect::{
     detect_cloudflare_caches, detect_hf_caches, detect_java_caches, detect_js_caches,
-    detect_js_pm_caches, detect_npx_caches, detect_python_caches, detect_rust_caches,
-    detect_torch_caches, detect_vercel_caches,
+    detect_js_pm_caches, detect_macos_caches, detect_npx_caches, detect_python_caches,
+    detect_rust_caches, detect_torch_caches, detect_vercel_caches,
 };
 use super::output::{human_since, human_size, print_cache_summary, print_table};
-use super::types::{ListResponse, ListSummary};
-use std::path::Path;
-
-pub async fn list_caches(
-    lang: &str,
-    include_npx: bool,
-    include_js_pm: bool,
-    include_hf: bool,
-    include_torch: bool,
-    include_vercel: bool,
-    include_cloudflare: bool,
-    stale_days: Option<u32>,
-) -> ListResponse {
+use super::project::{detect_project_artifacts, project_root};
+use super::types::{CacheEntry, ListResponse, ListSummary};
+use crate::cli::CacheArgs;
+use std::path::{Path, PathBuf};
+
+/// Which caches `list_caches` and `clean_caches` cover, as the `cache` flags describe them
+#[derive(Debug, Clone)]
+pub struct CacheQuery {
+    /// `auto`, `rust`, `js`, `py`, `java` or `macos`
+    pub lang: String,
+    pub npx: bool,
+    pub js_pm: bool,
+    pub hf: bool,
+    pub torch: bool,
+    pub vercel: bool,
+    pub cloudflare: bool,
+    /// Mark entries unused for this many days as stale; a clean then only deletes those
+    pub stale_days: Option<u32>,
+    /// Directories searched for Cargo projects (default: the current directory)
+    pub roots: Vec<PathBuf>,
+    /// Some of [`PYTHON_CATEGORIES`](super::detect::PYTHON_CATEGORIES); empty means all
+    pub python_categories: Vec<String>,
+    /// Only the build artifacts of this project (`""`: the current git repository)
+    pub project: Option<String>,
+    /// Back entries up before a clean deletes them
+    pub safe_delete: bool,
+}
+
+impl Default for CacheQuery {
+    fn default() -> Self {
+        Self {
+            lang: "auto".to_string(),
+            npx: false,
+            js_pm: false,
+            hf: false,
+            torch: false,
+            vercel: false,
+            cloudflare: false,
+            stale_days: None,
+            roots: Vec::new(),
+            python_categories: Vec::new(),
+            project: None,
+            safe_delete: true,
+        }
+    }
+}
+
+impl From<&CacheArgs> for CacheQuery {
+    fn from(args: &CacheArgs) -> Self {
+        Self {
+            lang: args.lang.clone(),
+            npx: args.npx,
+            js_pm: args.js_pm,
+            hf: args.hf,
+            torch: args.torch,
+            vercel: args.vercel,
+            cloudflare: args.cloudflare,
+            stale_days: args.stale_days,
+            roots: args.roots.clone(),
+            python_categories: args.python_categories.clone(),
+            project: args.project.clone(),
+            safe_delete: args.safe_delete,
+        }
+    }
+}
+
+impl CacheQuery {
+    fn wants_lang(&self, langs: &[&str]) -> bool {
+        self.lang == "auto" || langs.contains(&self.lang.as_str())
+    }
+}
+
+/// Every cache `query` covers, before config exclusions
+pub(crate) fn detect_caches(query: &CacheQuery) -> Vec<CacheEntry> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let stale_days = query.stale_days;
 
     let mut entries = Vec::new();
-    let has_specialized_flags = include_npx
-        || include_js_pm
-        || include_hf
-        || include_torch
-        || include_vercel
-        || include_cloudflare;
+    let has_specialized_flags =
+        query.npx || query.js_pm || query.hf || query.torch || query.vercel || query.cloudflare;
 
     // Specialized integrations
-    if include_npx {
+    if query.npx {
         entries.extend(detect_npx_caches(stale_days));
     }
-    if include_js_pm {
+    if query.js_pm {
         entries.extend(detect_js_pm_caches(stale_days));
     }
-    if include_hf {
+    if query.hf {
         entries.extend(detect_hf_caches(stale_days));
     }
-    if include_torch {
+    if query.torch {
         entries.extend(detect_torch_caches(stale_days));
     }
-    if include_vercel {
+    if query.vercel {
         entries.extend(detect_vercel_caches(stale_days));
     }
-    if include_cloudflare {
+    if query.cloudflare {
         entries.extend(detect_cloudflare_caches(stale_days));
     }
 
-    // Language-based detection (always runs unless only specialized flags were given)
-    if !has_specialized_flags || lang != "auto" {
-        if lang == "auto" || lang == "rust" {
-            entries.extend(detect_rust_caches(Path::new(&cwd), stale_days));
+    // --project: only the build artifacts of one repository
+    if let Some(project) = &query.project {
+        entries.extend(detect_project_artifacts(&project_root(project), stale_days));
+    }
+
+    // Language-based detection (always runs unless only specialized flags or --project were given)
+    if query.project.is_none() && (!has_specialized_flags || query.lang != "auto") {
+        if query.wants_lang(&["rust"]) {
+            let roots = if query.roots.is_empty() {
+                vec![cwd.clone()]
+            } else {
+                query.roots.clone()
+            };
+            entries.extend(detect_rust_caches(&roots, stale_days));
         }
-        if lang == "auto" || lang == "js" {
+        if query.wants_lang(&["js"]) {
             entries.extend(detect_js_caches(Path::new(&cwd), stale_days));
         }
-        if lang == "auto" || lang == "py" {
-            entries.extend(detect_python_caches(stale_days));
+        if query.wants_lang(&["py", "python"]) {
+            entries.extend(detect_python_caches(&query.python_categories, stale_days));
         }
-        if lang == "auto" || lang == "java" {
+        if query.wants_lang(&["java"]) {
             entries.extend(detect_java_caches(stale_days));
         }
+        if query.wants_lang(&["macos"]) {
+            entries.extend(detect_macos_caches(stale_days));
+        }
     }
+    entries
+}
+
+pub async fn list_caches(query: &CacheQuery) -> ListResponse {
+    let mut entries = detect_caches(query);
+
+    super::size::save();
+
+    // Config exclusions and pinned paths
+    let excluded = super::rules::load().apply(&mut entries);
+
+    // Stable order for --json consumers (directory listings come back in any order)
+    entries.sort_by(|a, b| (&a.kind, &a.name, &a.path).cmp(&(&b.kind, &b.name, &b.path)));
 
     // summary
     let mut total = 0u64;
@@ -79,6 +163,7 @@ pub async fn list_caches(
             total_size_bytes: total,
             count,
             stale_count: stale,
+            excluded_count: excluded,
         },
     };
     resp
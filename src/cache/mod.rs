@@ -6,5 +6,10 @@ pub mod js_pm;
 pub mod list;
 pub mod npx;
 pub mod output;
+pub mod project;
 pub mod restore;
+pub mod rules;
+pub mod schedule;
+pub mod size;
 pub mod types;
+pub mod watch;
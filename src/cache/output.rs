@@ -52,7 +52,7 @@ pub fn print_table(rows: &[(String, String, String, String, String)]) {
     // Columns: PACKAGE | KIND | SIZE | LAST USED | STALE?
     // Fixed column widths for proper alignment
     let package_width = 50;
-    let kind_width = 8;
+    let kind_width = 10;
     let size_width = 12;
     let last_used_width = 16;
     let stale_width = 6;
@@ -88,6 +88,12 @@ pub fn print_cache_summary(resp: &super::types::ListResponse) {
     println!("Total size: {}", human_size(resp.summary.total_size_bytes));
     println!("Total entries: {}", resp.summary.count);
     println!("Stale entries: {}", resp.summary.stale_count);
+    if resp.summary.excluded_count > 0 {
+        println!(
+            "Excluded or pinned: {} (see [cache] in the config)",
+            resp.summary.excluded_count
+        );
+    }
 
     // Size by kind breakdown
     let mut kind_sizes: HashMap<String, u64> = HashMap::new();
@@ -0,0 +1,223 @@
+use super::detect::{dir_size_and_mtime, is_stale};
+use super::types::CacheEntry;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const JS_MANIFESTS: &[&str] = &["package.json"];
+const PYTHON_MANIFESTS: &[&str] = &[
+    "pyproject.toml",
+    "setup.py",
+    "setup.cfg",
+    "tox.ini",
+    "pytest.ini",
+    "requirements.txt",
+];
+
+/// Build output directories `--project` cleans, each with the files one of which must sit
+/// next to it: the manifest of what builds it. `__pycache__` goes next to Python sources
+/// instead, which [`is_build_output`] checks.
+const ARTIFACT_DIRS: [(&str, &[&str]); 11] = [
+    (".next", JS_MANIFESTS),
+    (".nuxt", JS_MANIFESTS),
+    (".svelte-kit", JS_MANIFESTS),
+    (".turbo", JS_MANIFESTS),
+    ("dist", &["package.json", "pyproject.toml", "setup.py"]),
+    ("coverage", JS_MANIFESTS),
+    ("__pycache__", &[]),
+    (".pytest_cache", PYTHON_MANIFESTS),
+    (".mypy_cache", PYTHON_MANIFESTS),
+    (".ruff_cache", PYTHON_MANIFESTS),
+    ("target", &["Cargo.toml", "pom.xml", "build.sbt"]),
+];
+
+/// The directory `--project` works on: `path`, or the git repository the current directory
+/// is in when `path` is empty (`--project` without a value)
+pub fn project_root(path: &str) -> PathBuf {
+    if !path.is_empty() {
+        return Path::new(path)
+            .canonicalize()
+            .unwrap_or_else(|_| PathBuf::from(path));
+    }
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    cwd.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(&cwd)
+        .to_path_buf()
+}
+
+/// Build artifacts under `root`: the directories in [`ARTIFACT_DIRS`] next to the manifest
+/// that builds them, and `node_modules/.cache`. Hidden directories and virtualenvs are not
+/// searched, so installed packages' own `dist` or `coverage` are never mistaken for the
+/// project's. In a git repository, a directory with tracked files in it is source, not
+/// output, and is left alone.
+pub fn detect_project_artifacts(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    let tracked = tracked_dirs(root);
+
+    let mut it = walkdir::WalkDir::new(root).into_iter();
+    while let Some(result) = it.next() {
+        let entry = match result {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        if !entry.file_type().is_dir() || entry.depth() == 0 {
+            continue;
+        }
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        let parent = entry.path().parent().unwrap_or(root);
+
+        let path = if dir_name == "node_modules" {
+            // Only its bundler caches; the dependencies themselves belong to `--lang js`
+            it.skip_current_dir();
+            entry.path().join(".cache")
+        } else if is_build_output(&dir_name, parent) {
+            it.skip_current_dir();
+            entry.path().to_path_buf()
+        } else {
+            if dir_name.starts_with('.') || entry.path().join("pyvenv.cfg").exists() {
+                it.skip_current_dir();
+            }
+            continue;
+        };
+        if !path.is_dir() {
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        if tracked.contains(relative) {
+            continue;
+        }
+
+        let (size, mtime) = dir_size_and_mtime(&path);
+        entries.push(CacheEntry {
+            id: format!("project:{}", path.to_string_lossy()),
+            kind: "project".to_string(),
+            name: relative.to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: mtime,
+            stale: is_stale(mtime, stale_days),
+            details: json!({
+                "type": "build_artifact",
+                "project": root.to_string_lossy(),
+            }),
+        });
+    }
+
+    entries
+}
+
+/// Whether `dir_name` in `parent` is one of [`ARTIFACT_DIRS`] where its build puts it
+fn is_build_output(dir_name: &str, parent: &Path) -> bool {
+    let Some((_, manifests)) = ARTIFACT_DIRS.iter().find(|(name, _)| *name == dir_name) else {
+        return false;
+    };
+    if dir_name == "__pycache__" {
+        return fs::read_dir(parent).is_ok_and(|files| {
+            files
+                .flatten()
+                .any(|file| file.path().extension().is_some_and(|ext| ext == "py"))
+        });
+    }
+    manifests.iter().any(|file| parent.join(file).exists())
+}
+
+/// Directories under `root`, relative to it, that git tracks files in. Empty outside a
+/// repository or without git.
+fn tracked_dirs(root: &Path) -> HashSet<PathBuf> {
+    let Ok(output) = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["ls-files", "-z"])
+        .output()
+    else {
+        return HashSet::new();
+    };
+    if !output.status.success() {
+        return HashSet::new();
+    }
+    let mut dirs = HashSet::new();
+    for file in output.stdout.split(|&b| b == 0).filter(|f| !f.is_empty()) {
+        let file = PathBuf::from(String::from_utf8_lossy(file).as_ref());
+        for dir in file.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() || !dirs.insert(dir.to_path_buf()) {
+                break;
+            }
+        }
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: PathBuf) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+
+    #[test]
+    fn test_detect_project_artifacts() {
+        let root = std::env::temp_dir().join(format!("port-kill-project-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        touch(root.join("package.json"));
+        touch(root.join("dist/app.js"));
+        touch(root.join("coverage/lcov.info"));
+        touch(root.join("node_modules/.cache/babel/x.json"));
+        touch(root.join("node_modules/left-pad/dist/index.js"));
+        touch(root.join("app/main.py"));
+        touch(root.join("app/__pycache__/main.cpython-312.pyc"));
+        touch(root.join("engine/Cargo.toml"));
+        touch(root.join("engine/target/debug/engine"));
+        touch(root.join("tools/pyproject.toml"));
+        touch(root.join("tools/.pytest_cache/README.md"));
+        // Not build output: nothing builds it there, or it belongs to a virtualenv
+        touch(root.join("docs/target/page.html"));
+        touch(root.join("vendor/chart/dist/chart.js"));
+        touch(root.join(".venv/pyvenv.cfg"));
+        touch(root.join(".venv/lib/site-packages/pkg/__init__.py"));
+        touch(root.join(".venv/lib/site-packages/pkg/__pycache__/x.pyc"));
+        touch(root.join("venv/pyvenv.cfg"));
+        touch(root.join("venv/lib/site-packages/coverage/__init__.py"));
+        touch(root.join("venv/lib/site-packages/package.json"));
+        touch(root.join(".github/package.json"));
+        touch(root.join(".github/dist/action.js"));
+
+        let found = |root: &Path| {
+            let mut names: Vec<String> = detect_project_artifacts(root, None)
+                .into_iter()
+                .map(|e| e.name)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            found(&root),
+            [
+                "app/__pycache__",
+                "coverage",
+                "dist",
+                "engine/target",
+                "node_modules/.cache",
+                "tools/.pytest_cache",
+            ]
+        );
+
+        // A directory with files git tracks in it is source
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(args)
+                .output()
+                .is_ok_and(|o| o.status.success())
+        };
+        if git(&["init", "-q"]) && git(&["add", "-f", "dist/app.js"]) {
+            assert!(!found(&root).contains(&"dist".to_string()));
+        }
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,10 +1,14 @@
-use super::backup::{find_latest_backup, restore_from_backup};
+use super::backup::{find_backup, prune_backups, restore_from_backup, retention};
 use super::types::RestoreResponse;
 
-pub async fn restore_last_backup() -> RestoreResponse {
-    match find_latest_backup() {
-        Ok(Some(backup_path)) => match restore_from_backup(&backup_path).await {
+/// `cache --restore [--backup <id>] [--only <path|id>]`: restore the backup `id` ("last"
+/// for the newest), or only the entries of it that `only` names
+pub async fn restore_backup(id: &str, only: &[String]) -> RestoreResponse {
+    match find_backup(id) {
+        Ok(Some(backup_path)) => match restore_from_backup(&backup_path, only).await {
             Ok(count) => {
+                // What is left of the backup, if anything, has a different size now
+                super::size::forget(&backup_path);
                 let restored_from = backup_path.to_string_lossy().to_string();
                 if count == 0 {
                     eprintln!("No entries were restored from backup (destinations may be non-empty or backup files missing)");
@@ -12,12 +16,14 @@ pub async fn restore_last_backup() -> RestoreResponse {
                         restored_from,
                         restored_count: 0,
                         error: Some("No entries were restored from backup".to_string()),
+                        pruned_backups: Vec::new(),
                     }
                 } else {
                     RestoreResponse {
                         restored_from,
                         restored_count: count,
                         error: None,
+                        pruned_backups: prune_backups(&retention(&super::rules::load())),
                     }
                 }
             }
@@ -27,15 +33,22 @@ pub async fn restore_last_backup() -> RestoreResponse {
                     restored_from: backup_path.to_string_lossy().to_string(),
                     restored_count: 0,
                     error: Some(format!("Failed to restore from backup: {}", e)),
+                    pruned_backups: Vec::new(),
                 }
             }
         },
         Ok(None) => {
-            eprintln!("No backup found to restore");
+            let error = if id == "last" {
+                "No backup found to restore".to_string()
+            } else {
+                format!("No backup {} (see cache --backups)", id)
+            };
+            eprintln!("{}", error);
             RestoreResponse {
                 restored_from: String::new(),
                 restored_count: 0,
-                error: Some("No backup found to restore".to_string()),
+                error: Some(error),
+                pruned_backups: Vec::new(),
             }
         }
         Err(e) => {
@@ -44,6 +57,7 @@ pub async fn restore_last_backup() -> RestoreResponse {
                 restored_from: String::new(),
                 restored_count: 0,
                 error: Some(format!("Failed to find backup: {}", e)),
+                pruned_backups: Vec::new(),
             }
         }
     }
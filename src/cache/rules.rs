@@ -0,0 +1,201 @@
+//! Caches `cache --list` and `--clean` never touch, from the `[cache]` table of the config
+//! files:
+//!
+//! ```toml
+//! [cache]
+//! exclude = ["**/models/**", "~/work/legacy/node_modules"]  # globs, `~` is home
+//! min_age_days = 7       # leave anything used in the last week alone
+//! pinned = ["/Users/me/.cache/huggingface/hub/models--meta-llama--Llama-3-8B"]
+//! ```
+//!
+//! `pinned` is what `cache --pin <path>` adds to. A cache that has a pinned path inside it
+//! is skipped as a whole, since cleaning it would remove the pinned one too.
+//!
+//! The same table sets how many `--safe-delete` backups are kept (see
+//! [`super::backup::retention`]): `keep_backups`, `max_backup_size` (e.g. `"20GB"`) and
+//! `max_backup_age_days`; and `compress_backups = true` stores them as zstd-compressed
+//! tarballs, like `--compress-backups`.
+
+use super::types::CacheEntry;
+use anyhow::Result;
+use chrono::Utc;
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CacheRules {
+    /// Glob patterns matched against each cache's full path
+    pub exclude: Vec<String>,
+    /// Caches used more recently than this many days ago are skipped
+    pub min_age_days: Option<u32>,
+    pub pinned: Vec<String>,
+    pub keep_backups: Option<usize>,
+    pub max_backup_size: Option<String>,
+    pub max_backup_age_days: Option<u32>,
+    pub compress_backups: Option<bool>,
+}
+
+impl CacheRules {
+    /// Overlay the project file's rules: lists are combined, the other settings replaced
+    pub fn merge(mut self, overlay: CacheRules) -> CacheRules {
+        for pattern in overlay.exclude {
+            if !self.exclude.contains(&pattern) {
+                self.exclude.push(pattern);
+            }
+        }
+        for path in overlay.pinned {
+            if !self.pinned.contains(&path) {
+                self.pinned.push(path);
+            }
+        }
+        self.min_age_days = overlay.min_age_days.or(self.min_age_days);
+        self.keep_backups = overlay.keep_backups.or(self.keep_backups);
+        self.max_backup_size = overlay.max_backup_size.or(self.max_backup_size);
+        self.max_backup_age_days = overlay.max_backup_age_days.or(self.max_backup_age_days);
+        self.compress_backups = overlay.compress_backups.or(self.compress_backups);
+        self
+    }
+
+    /// Why `entry` is skipped, if it is
+    pub fn skip_reason(&self, entry: &CacheEntry) -> Option<String> {
+        let path = Path::new(&entry.path);
+        for pinned in &self.pinned {
+            let pinned = expand_home(pinned);
+            if path.starts_with(&pinned) || pinned.starts_with(path) {
+                return Some(format!("pinned {}", pinned.display()));
+            }
+        }
+        let globs: Vec<(&String, Regex)> = self
+            .exclude
+            .iter()
+            .filter_map(|pattern| {
+                let expanded = expand_home(pattern).to_string_lossy().to_string();
+                Some((pattern, crate::workspaces::glob_regex(&expanded)?))
+            })
+            .collect();
+        let slashed = entry.path.replace('\\', "/");
+        if let Some((pattern, _)) = globs.iter().find(|(_, re)| re.is_match(&slashed)) {
+            return Some(format!("excluded by {}", pattern));
+        }
+        if let (Some(min_age), Some(last_used)) = (self.min_age_days, entry.last_used_at) {
+            if (Utc::now() - last_used).num_days() < min_age as i64 {
+                return Some(format!("used within {} days", min_age));
+            }
+        }
+        None
+    }
+
+    /// Drop the entries the rules skip, returning how many were dropped
+    pub fn apply(&self, entries: &mut Vec<CacheEntry>) -> usize {
+        let before = entries.len();
+        entries.retain(|entry| match self.skip_reason(entry) {
+            Some(reason) => {
+                log::debug!("Skipping cache {}: {}", entry.path, reason);
+                false
+            }
+            None => true,
+        });
+        before - entries.len()
+    }
+}
+
+/// The `[cache]` rules of the global and project config files
+pub fn load() -> CacheRules {
+    crate::config::load_or_default()
+        .config
+        .cache
+        .unwrap_or_default()
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => crate::uninstall::home_dir().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// `cache --pin <path>` / `--unpin <path>`: add the path to (or remove it from) `pinned` in
+/// the global config file. Returns the absolute path.
+pub fn set_pinned(path: &str, pinned: bool) -> Result<PathBuf> {
+    let expanded = expand_home(path);
+    let absolute = expanded.canonicalize().unwrap_or(expanded);
+    let value = absolute.to_string_lossy();
+    if pinned {
+        crate::config::add_to_global_section_list("cache", "pinned", &value)?;
+    } else {
+        crate::config::remove_from_global_section_list("cache", "pinned", &value)?;
+    }
+    Ok(absolute)
+}
+
+/// Handle `--pin` / `--unpin`
+pub fn pin_command(args: &crate::cli::CacheArgs) -> Result<()> {
+    if let Some(path) = &args.pin {
+        let pinned = set_pinned(path, true)?;
+        println!(
+            "📌 Pinned {}: cache --list and --clean will leave it alone",
+            pinned.display()
+        );
+    }
+    if let Some(path) = &args.unpin {
+        let unpinned = set_pinned(path, false)?;
+        println!("Unpinned {}", unpinned.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_reason() {
+        let entry = |path: &str, days_ago: i64| CacheEntry {
+            id: path.to_string(),
+            kind: "js".to_string(),
+            name: path.to_string(),
+            path: path.to_string(),
+            size_bytes: 0,
+            last_used_at: Some(Utc::now() - chrono::Duration::days(days_ago)),
+            stale: false,
+            details: serde_json::json!({}),
+        };
+        let rules = CacheRules {
+            exclude: vec!["**/models/**".to_string(), "/srv/keep/*".to_string()],
+            min_age_days: Some(7),
+            pinned: vec!["/home/me/.cache/huggingface/hub/llama".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            rules.skip_reason(&entry("/home/me/app/node_modules", 30)),
+            None
+        );
+        assert!(rules
+            .skip_reason(&entry("/home/me/app/models/big", 30))
+            .unwrap()
+            .starts_with("excluded"));
+        assert!(rules.skip_reason(&entry("/srv/keep/target", 30)).is_some());
+        assert!(rules
+            .skip_reason(&entry("/srv/keep/a/target", 30))
+            .is_none());
+        assert!(rules
+            .skip_reason(&entry("/home/me/app/dist", 2))
+            .unwrap()
+            .starts_with("used within"));
+        // The pinned path, and a cache the pinned path is in
+        assert!(rules
+            .skip_reason(&entry("/home/me/.cache/huggingface/hub/llama", 30))
+            .is_some());
+        assert!(rules
+            .skip_reason(&entry("/home/me/.cache/huggingface", 30))
+            .unwrap()
+            .starts_with("pinned"));
+
+        let mut entries = vec![entry("/a/models/x", 30), entry("/a/dist", 30)];
+        assert_eq!(rules.apply(&mut entries), 1);
+        assert_eq!(entries[0].path, "/a/dist");
+    }
+}
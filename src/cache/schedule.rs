@@ -0,0 +1,499 @@
+//! `cache --schedule daily|weekly`: clean caches on a schedule, with the other `cache` flags
+//! (`--lang`, `--stale-days`, `--npx`, `--safe-delete`, ...) as the policy. On its own it
+//! keeps running and cleans whenever a period has passed since the last scheduled clean.
+//! `--install-schedule` registers it with the system instead, at 03:00: a systemd user
+//! timer (a crontab line where there is no systemd), a LaunchAgent or a scheduled task,
+//! each running `cache --schedule <frequency> --once` with the same flags. Every scheduled
+//! clean is appended to `~/.port-kill/cache-schedule.log`, which `--schedule-log` prints.
+
+use super::clean::clean_caches;
+use super::list::CacheQuery;
+use super::output::human_size;
+use crate::cli::CacheArgs;
+use crate::daemon::{run, systemd_quote, windows_command, xml_escape};
+use crate::uninstall::{home_dir, LAUNCHD_LABEL, SERVICE_NAME};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Ends the crontab line `--install-schedule` writes, so it can be found again
+const CRON_MARKER: &str = "# port-kill cache schedule";
+
+/// One clean run by the schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledClean {
+    pub at: DateTime<Utc>,
+    /// "timer" for a running `--schedule`, "system" for an installed entry
+    pub trigger: String,
+    pub deleted_count: usize,
+    pub freed_bytes: u64,
+    pub backed_up_to: Option<String>,
+}
+
+/// Unit, task and plist name of the installed schedule
+pub fn schedule_name() -> String {
+    format!("{}-cache-clean", SERVICE_NAME)
+}
+
+pub fn launchd_label() -> String {
+    format!("{}.cache-clean", LAUNCHD_LABEL)
+}
+
+pub fn log_path() -> PathBuf {
+    home_dir().join(".port-kill").join("cache-schedule.log")
+}
+
+fn period(frequency: &str) -> chrono::Duration {
+    if frequency == "weekly" {
+        chrono::Duration::weeks(1)
+    } else {
+        chrono::Duration::days(1)
+    }
+}
+
+/// Scheduled cleans so far, oldest first
+pub fn read_log() -> Vec<ScheduledClean> {
+    fs::read_to_string(log_path())
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn append_log(entry: &ScheduledClean) -> Result<()> {
+    let path = log_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Run the clean the flags describe and log it
+pub async fn clean_now(args: &CacheArgs, trigger: &str) -> ScheduledClean {
+    let resp = clean_caches(&CacheQuery::from(args)).await;
+    let entry = ScheduledClean {
+        at: Utc::now(),
+        trigger: trigger.to_string(),
+        deleted_count: resp.summary.deleted_count,
+        freed_bytes: resp.summary.freed_bytes,
+        backed_up_to: resp.backed_up_to,
+    };
+    if let Err(e) = append_log(&entry) {
+        eprintln!("Warning: Failed to log the scheduled clean: {:#}", e);
+    }
+    entry
+}
+
+fn describe(entry: &ScheduledClean) -> String {
+    let mut text = format!(
+        "cleaned {} cache(s), freeing {}",
+        entry.deleted_count,
+        human_size(entry.freed_bytes)
+    );
+    if let Some(backup) = &entry.backed_up_to {
+        text.push_str(&format!(" (backed up to {})", backup));
+    }
+    text
+}
+
+pub fn print_log() {
+    let log = read_log();
+    if log.is_empty() {
+        println!("No scheduled cleans yet ({})", log_path().display());
+        return;
+    }
+    println!("{:<20} | {:<7} | DETAILS", "WHEN", "TRIGGER");
+    println!("{}", "-".repeat(80));
+    for entry in &log {
+        println!(
+            "{:<20} | {:<7} | {}",
+            entry.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            entry.trigger,
+            describe(entry)
+        );
+    }
+}
+
+/// Keep running, cleaning once a period has passed since the last scheduled clean
+async fn run_timer(args: &CacheArgs, frequency: &str) {
+    let period = period(frequency);
+    let mut last = read_log().last().map(|entry| entry.at);
+    println!(
+        "🗓️  Cleaning caches {} (log: {})",
+        frequency,
+        log_path().display()
+    );
+    loop {
+        let due = last.map_or_else(Utc::now, |at| at + period);
+        if let Ok(wait) = (due - Utc::now()).to_std() {
+            println!(
+                "Next clean at {}",
+                due.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            );
+            tokio::time::sleep(wait).await;
+        }
+        let entry = clean_now(args, "timer").await;
+        println!("🧹 Scheduled clean: {}", describe(&entry));
+        last = Some(entry.at);
+    }
+}
+
+/// The arguments an installed entry runs with: the `cache` flags of `command_line`, without
+/// `--install-schedule`, and `--once`
+pub fn scheduled_args(command_line: &[String]) -> Vec<String> {
+    let mut args = vec!["cache".to_string()];
+    args.extend(
+        command_line
+            .iter()
+            .skip_while(|arg| !matches!(arg.as_str(), "cache" | "c"))
+            .skip(1)
+            .filter(|arg| !matches!(arg.as_str(), "--install-schedule" | "--once" | "--json"))
+            .cloned(),
+    );
+    args.push("--once".to_string());
+    args
+}
+
+/// Shell quoting for the crontab line
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./,=:@%+".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+pub fn cron_line(exe: &Path, args: &[String], frequency: &str) -> String {
+    let command = std::iter::once(exe.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| shell_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let when = if frequency == "weekly" {
+        "0 3 * * 0"
+    } else {
+        "0 3 * * *"
+    };
+    format!("{} {} {}", when, command, CRON_MARKER)
+}
+
+/// The `.service` and `.timer` of the systemd schedule
+pub fn systemd_units(exe: &Path, args: &[String], frequency: &str) -> (String, String) {
+    let exec = std::iter::once(exe.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let service = format!(
+        "[Unit]
+Description=port-kill scheduled cache clean
+
+[Service]
+Type=oneshot
+ExecStart={}
+",
+        exec
+    );
+    let calendar = if frequency == "weekly" {
+        "Sun *-*-* 03:00:00"
+    } else {
+        "*-*-* 03:00:00"
+    };
+    let timer = format!(
+        "[Unit]
+Description=port-kill scheduled cache clean ({})
+
+[Timer]
+OnCalendar={}
+Persistent=true
+
+[Install]
+WantedBy=timers.target
+",
+        frequency, calendar
+    );
+    (service, timer)
+}
+
+pub fn launchd_plist(exe: &Path, args: &[String], frequency: &str) -> String {
+    let arguments: String = std::iter::once(exe.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+        .collect();
+    let weekday = if frequency == "weekly" {
+        "        <key>Weekday</key>\n        <integer>0</integer>\n"
+    } else {
+        ""
+    };
+    let log = xml_escape(&crate::daemon::log_file_path().to_string_lossy());
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>StartCalendarInterval</key>
+    <dict>
+{}        <key>Hour</key>
+        <integer>3</integer>
+        <key>Minute</key>
+        <integer>0</integer>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{}</string>
+    <key>StandardErrorPath</key>
+    <string>{}</string>
+</dict>
+</plist>
+"#,
+        launchd_label(),
+        arguments,
+        weekday,
+        log,
+        log
+    )
+}
+
+fn systemd_dir() -> PathBuf {
+    home_dir().join(".config/systemd/user")
+}
+
+fn plist_path() -> PathBuf {
+    home_dir()
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", launchd_label()))
+}
+
+fn has_systemd() -> bool {
+    run("systemctl", &["--user", "show-environment"]).is_ok()
+}
+
+/// The current crontab without the line `--install-schedule` wrote
+fn crontab_without_schedule() -> Vec<String> {
+    Command::new("crontab")
+        .arg("-l")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| !line.ends_with(CRON_MARKER))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn write_crontab(lines: &[String]) -> Result<()> {
+    let mut child = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run crontab")?;
+    let mut content = lines.join("\n");
+    content.push('\n');
+    child
+        .stdin
+        .take()
+        .context("Failed to write to crontab")?
+        .write_all(content.as_bytes())?;
+    if !child.wait()?.success() {
+        anyhow::bail!("crontab - failed");
+    }
+    Ok(())
+}
+
+/// Register the schedule with the system, replacing an installed one. Returns where.
+pub fn install(args: &[String], frequency: &str) -> Result<String> {
+    let exe = std::env::current_exe().context("Failed to find the port-kill binary")?;
+    let _ = uninstall();
+
+    if cfg!(target_os = "windows") {
+        let name = schedule_name();
+        let command = windows_command(&exe, args);
+        let mut schtasks = vec!["/Create", "/TN", name.as_str(), "/TR", command.as_str()];
+        if frequency == "weekly" {
+            schtasks.extend(["/SC", "WEEKLY", "/D", "SUN"]);
+        } else {
+            schtasks.extend(["/SC", "DAILY"]);
+        }
+        schtasks.extend(["/ST", "03:00", "/F"]);
+        run("schtasks", &schtasks)?;
+        return Ok(format!("Task Scheduler: {}", name));
+    }
+
+    if cfg!(target_os = "macos") {
+        let path = plist_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, launchd_plist(&exe, args, frequency))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        run("launchctl", &["load", "-w", &path.to_string_lossy()])?;
+        return Ok(path.display().to_string());
+    }
+
+    if has_systemd() {
+        let dir = systemd_dir();
+        fs::create_dir_all(&dir)?;
+        let (service, timer) = systemd_units(&exe, args, frequency);
+        let name = schedule_name();
+        fs::write(dir.join(format!("{}.service", name)), service)?;
+        let timer_path = dir.join(format!("{}.timer", name));
+        fs::write(&timer_path, timer)?;
+        run("systemctl", &["--user", "daemon-reload"])?;
+        run(
+            "systemctl",
+            &["--user", "enable", "--now", &format!("{}.timer", name)],
+        )?;
+        return Ok(timer_path.display().to_string());
+    }
+
+    let mut crontab = crontab_without_schedule();
+    crontab.push(cron_line(&exe, args, frequency));
+    write_crontab(&crontab).context(
+        "No systemd user session and no usable crontab; run without --install-schedule to keep the schedule running in the foreground",
+    )?;
+    Ok("crontab".to_string())
+}
+
+/// Remove the installed schedule. Returns false when none was installed.
+pub fn uninstall() -> Result<bool> {
+    if cfg!(target_os = "windows") {
+        return Ok(run("schtasks", &["/Delete", "/TN", &schedule_name(), "/F"]).is_ok());
+    }
+    if cfg!(target_os = "macos") {
+        let path = plist_path();
+        if !path.exists() {
+            return Ok(false);
+        }
+        crate::uninstall::stop_service(&path);
+        fs::remove_file(&path)?;
+        return Ok(true);
+    }
+
+    let mut removed = false;
+    let timer = systemd_dir().join(format!("{}.timer", schedule_name()));
+    if timer.exists() {
+        crate::uninstall::stop_service(&timer);
+        fs::remove_file(&timer)?;
+        let _ = fs::remove_file(timer.with_extension("service"));
+        let _ = run("systemctl", &["--user", "daemon-reload"]);
+        removed = true;
+    }
+    let crontab = crontab_without_schedule();
+    let installed = Command::new("crontab")
+        .arg("-l")
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(CRON_MARKER));
+    if installed {
+        write_crontab(&crontab)?;
+        removed = true;
+    }
+    Ok(removed)
+}
+
+/// Handle `--schedule`, `--install-schedule`, `--uninstall-schedule` and `--schedule-log`
+pub async fn schedule_command(args: &CacheArgs) -> Result<()> {
+    if args.schedule_log {
+        print_log();
+        return Ok(());
+    }
+    if args.uninstall_schedule {
+        if uninstall()? {
+            println!("🗑️  Removed the scheduled cache clean");
+        } else {
+            println!("No scheduled cache clean is installed.");
+        }
+        return Ok(());
+    }
+    let frequency = args
+        .schedule
+        .as_deref()
+        .context("Give a frequency, e.g. --schedule daily")?;
+    if args.install_schedule {
+        let command_line: Vec<String> = std::env::args().collect();
+        let scheduled = scheduled_args(&command_line);
+        let installed = install(&scheduled, frequency)?;
+        println!(
+            "✅ Scheduled a {} cache clean at 03:00: {}",
+            frequency, installed
+        );
+        println!("   Runs: port-kill {}", scheduled.join(" "));
+        println!("   Log:  {}", log_path().display());
+    } else if args.once {
+        let entry = clean_now(args, "system").await;
+        println!("🧹 Scheduled clean: {}", describe(&entry));
+    } else {
+        run_timer(args, frequency).await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_entries() {
+        let command_line: Vec<String> = [
+            "port-kill-console",
+            "cache",
+            "--schedule",
+            "weekly",
+            "--lang",
+            "js",
+            "--stale-days",
+            "30",
+            "--install-schedule",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let args = scheduled_args(&command_line);
+        assert_eq!(
+            args,
+            vec![
+                "cache",
+                "--schedule",
+                "weekly",
+                "--lang",
+                "js",
+                "--stale-days",
+                "30",
+                "--once"
+            ]
+        );
+
+        let exe = Path::new("/opt/port kill/port-kill-console");
+        assert_eq!(
+            cron_line(exe, &args, "weekly"),
+            "0 3 * * 0 '/opt/port kill/port-kill-console' cache --schedule weekly --lang js --stale-days 30 --once # port-kill cache schedule"
+        );
+        let (service, timer) = systemd_units(exe, &args, "daily");
+        assert!(service.contains("ExecStart=\"/opt/port kill/port-kill-console\" cache --schedule"));
+        assert!(timer.contains("OnCalendar=*-*-* 03:00:00\n"));
+        let plist = launchd_plist(exe, &args, "weekly");
+        assert!(plist.contains("<key>Weekday</key>"));
+        assert!(plist.contains("        <string>--once</string>\n"));
+    }
+}
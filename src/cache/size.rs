@@ -0,0 +1,265 @@
+//! Directory sizes for the cache detectors. Subdirectories are walked in parallel, a spinner
+//! on stderr shows how far the walk has got, `--max-depth` / `--scan-timeout` bound it, and
+//! results are kept in `~/.port-kill/cache-sizes.json` so listing again within
+//! `--size-cache-ttl` seconds doesn't walk the same directories again.
+
+use chrono::{DateTime, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a measured size is reused, unless `--size-cache-ttl` says otherwise
+pub const DEFAULT_TTL_SECS: u64 = 600;
+
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Directories deeper than this below the measured one aren't counted
+    pub max_depth: Option<usize>,
+    /// Per directory measured; what was counted by then is reported
+    pub timeout: Option<Duration>,
+    /// Zero measures every time
+    pub ttl: Duration,
+    pub progress: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            timeout: None,
+            ttl: Duration::from_secs(DEFAULT_TTL_SECS),
+            progress: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSize {
+    size_bytes: u64,
+    last_used_at: Option<DateTime<Utc>>,
+    measured_at: DateTime<Utc>,
+    /// Cut short by the depth limit or timeout; not reused
+    #[serde(default)]
+    partial: bool,
+}
+
+static OPTIONS: OnceLock<ScanOptions> = OnceLock::new();
+static SIZES: OnceLock<Mutex<HashMap<String, CachedSize>>> = OnceLock::new();
+
+/// Set once, before the first `cache` listing or clean
+pub fn configure(options: ScanOptions) {
+    let _ = OPTIONS.set(options);
+}
+
+fn options() -> &'static ScanOptions {
+    OPTIONS.get_or_init(ScanOptions::default)
+}
+
+fn sizes_path() -> PathBuf {
+    crate::uninstall::home_dir()
+        .join(".port-kill")
+        .join("cache-sizes.json")
+}
+
+fn sizes() -> &'static Mutex<HashMap<String, CachedSize>> {
+    SIZES.get_or_init(|| {
+        let sizes = fs::read_to_string(sizes_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Mutex::new(sizes)
+    })
+}
+
+/// Total size of the files under `path` and the newest modification time in it
+pub fn measure(path: &Path) -> (u64, Option<DateTime<Utc>>) {
+    let options = options();
+    let key = path.to_string_lossy().to_string();
+    if !options.ttl.is_zero() {
+        if let Some(cached) = sizes().lock().unwrap().get(&key) {
+            let age = (Utc::now() - cached.measured_at)
+                .to_std()
+                .unwrap_or(Duration::MAX);
+            if !cached.partial && age < options.ttl {
+                return (cached.size_bytes, cached.last_used_at);
+            }
+        }
+    }
+
+    let progress = options.progress.then(|| {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(ProgressStyle::with_template("{spinner} {msg}").unwrap());
+        bar.enable_steady_tick(Duration::from_millis(100));
+        bar
+    });
+    let walk = Walk {
+        deadline: options.timeout.map(|timeout| Instant::now() + timeout),
+        max_depth: options.max_depth,
+        bytes: AtomicU64::new(0),
+        files: AtomicU64::new(0),
+        newest: Mutex::new(None),
+        partial: AtomicBool::new(false),
+        progress: progress.as_ref(),
+        label: key.clone(),
+    };
+    walk.dir(path, 0);
+    if let Some(bar) = &progress {
+        bar.finish_and_clear();
+    }
+
+    let size_bytes = walk.bytes.load(Ordering::Relaxed);
+    let last_used_at = *walk.newest.lock().unwrap();
+    let partial = walk.partial.load(Ordering::Relaxed);
+    if partial && options.progress {
+        eprintln!(
+            "⚠️  {} was only partly measured (--max-depth / --scan-timeout); its size is a lower bound",
+            key
+        );
+    }
+    sizes().lock().unwrap().insert(
+        key,
+        CachedSize {
+            size_bytes,
+            last_used_at,
+            measured_at: Utc::now(),
+            partial,
+        },
+    );
+    (size_bytes, last_used_at)
+}
+
+/// Write the sizes measured so far, for the next run to reuse
+pub fn save() {
+    let Some(sizes) = SIZES.get() else {
+        return;
+    };
+    let sizes = sizes.lock().unwrap();
+    let path = sizes_path();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(content) = serde_json::to_string(&*sizes) {
+        let _ = fs::write(path, content);
+    }
+}
+
+/// Forget a directory's size, e.g. once it was deleted
+pub fn forget(path: &Path) {
+    sizes()
+        .lock()
+        .unwrap()
+        .remove(path.to_string_lossy().as_ref());
+}
+
+struct Walk<'a> {
+    deadline: Option<Instant>,
+    max_depth: Option<usize>,
+    bytes: AtomicU64,
+    files: AtomicU64,
+    newest: Mutex<Option<DateTime<Utc>>>,
+    partial: AtomicBool,
+    progress: Option<&'a ProgressBar>,
+    label: String,
+}
+
+impl Walk<'_> {
+    fn dir(&self, dir: &Path, depth: usize) {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+            || self.max_depth.is_some_and(|max| depth > max)
+        {
+            self.partial.store(true, Ordering::Relaxed);
+            return;
+        }
+        let Ok(read) = fs::read_dir(dir) else {
+            return;
+        };
+
+        let mut subdirs = Vec::new();
+        let mut newest: Option<DateTime<Utc>> = None;
+        if let Ok(modified) = fs::symlink_metadata(dir).and_then(|md| md.modified()) {
+            newest = Some(modified.into());
+        }
+        for entry in read.flatten() {
+            // Not following symlinks, like walkdir
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if let Ok(modified) = metadata.modified() {
+                let modified: DateTime<Utc> = modified.into();
+                newest = Some(newest.map_or(modified, |n| n.max(modified)));
+            }
+            if metadata.is_dir() {
+                subdirs.push(entry.path());
+            } else if metadata.is_file() {
+                self.bytes.fetch_add(metadata.len(), Ordering::Relaxed);
+                self.files.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        if let Some(newest) = newest {
+            let mut overall = self.newest.lock().unwrap();
+            *overall = Some(overall.map_or(newest, |n| n.max(newest)));
+        }
+        if let Some(bar) = self.progress {
+            bar.set_message(format!(
+                "Measuring {}: {} files, {}",
+                self.label,
+                self.files.load(Ordering::Relaxed),
+                super::output::human_size(self.bytes.load(Ordering::Relaxed))
+            ));
+        }
+
+        subdirs
+            .par_iter()
+            .for_each(|subdir| self.dir(subdir, depth + 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let root = std::env::temp_dir().join(format!("port-kill-size-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::write(root.join("top.bin"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("a/mid.bin"), vec![0u8; 20]).unwrap();
+        fs::write(root.join("a/b/c/deep.bin"), vec![0u8; 3]).unwrap();
+
+        let walk = |max_depth| {
+            let walk = Walk {
+                deadline: None,
+                max_depth,
+                bytes: AtomicU64::new(0),
+                files: AtomicU64::new(0),
+                newest: Mutex::new(None),
+                partial: AtomicBool::new(false),
+                progress: None,
+                label: String::new(),
+            };
+            walk.dir(&root, 0);
+            (
+                walk.bytes.into_inner(),
+                walk.files.into_inner(),
+                walk.partial.into_inner(),
+            )
+        };
+        assert_eq!(walk(None), (123, 3, false));
+        // a/ is depth 1: its files count, a/b and below don't
+        assert_eq!(walk(Some(1)), (120, 2, true));
+
+        let (size, newest) = measure(&root);
+        assert_eq!(size, 123);
+        assert!(newest.is_some());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
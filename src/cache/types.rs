@@ -1,7 +1,8 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CacheEntry {
     pub id: String,
@@ -14,40 +15,74 @@ pub struct CacheEntry {
     pub details: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ListSummary {
     pub total_size_bytes: u64,
     pub count: usize,
     pub stale_count: usize,
+    /// Caches left out by the `[cache]` exclusions and pinned paths
+    #[serde(default)]
+    pub excluded_count: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Result of `cache --list`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ListResponse {
     pub entries: Vec<CacheEntry>,
     pub summary: ListSummary,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CleanSummary {
     pub freed_bytes: u64,
     pub deleted_count: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Result of `cache --clean`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CleanResponse {
     pub deleted: Vec<CacheEntry>,
     pub backed_up_to: Option<String>,
     pub summary: CleanSummary,
+    /// Old backups removed by the retention policy after this clean
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pruned_backups: Vec<BackupInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Result of `cache --restore` / `--restore-last`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RestoreResponse {
     pub restored_from: String,
     pub restored_count: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Old backups removed by the retention policy after this restore
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pruned_backups: Vec<BackupInfo>,
+}
+
+/// A backup made by `cache --clean --safe-delete`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// Directory name, what `cache --restore --backup` takes
+    pub id: String,
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub entry_count: usize,
+    pub size_bytes: u64,
+    /// The caches in the backup, as they were when cleaned
+    pub entries: Vec<CacheEntry>,
+}
+
+/// Result of `cache --backups`, newest backup first
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupsResponse {
+    pub backups: Vec<BackupInfo>,
+    pub total_size_bytes: u64,
 }
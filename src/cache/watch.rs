@@ -0,0 +1,121 @@
+//! `cache --watch --free-below 10GB`: check the free space of the disk the home directory
+//! is on, and when it drops below the threshold, notify or run a clean with the other
+//! `cache` flags (`--lang`, `--stale-days`, `--npx`, ...) as the policy. It fires once per
+//! drop: space has to recover above the threshold before it fires again, so a clean that
+//! can't free enough doesn't run over and over. Cleaned caches are backed up like any
+//! `--clean --safe-delete`, which frees nothing when the backup is on the same disk, so
+//! `--force` deletes them outright instead.
+
+use super::clean::clean_caches;
+use super::list::CacheQuery;
+use super::output::human_size;
+use crate::cli::CacheArgs;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::time::Duration;
+
+/// Bytes in a size like `10GB`, `500M`, `1.5TB` or `4096`. Units are powers of 1024, like
+/// the sizes `cache --list` prints.
+pub fn parse_size(size: &str) -> Result<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid size '{}', expected e.g. 10GB", size))?;
+    let power = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 1,
+        "M" | "MB" | "MIB" => 2,
+        "G" | "GB" | "GIB" => 3,
+        "T" | "TB" | "TIB" => 4,
+        other => bail!("Unknown size unit '{}' in '{}'", other, size),
+    };
+    Ok((number * 1024f64.powi(power)) as u64)
+}
+
+/// Available space on the disk `path` is on (the mount point that is its longest prefix)
+pub fn free_space(path: &Path) -> Option<u64> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+pub async fn watch(args: &CacheArgs) -> Result<()> {
+    let threshold = parse_size(
+        args.free_below
+            .as_deref()
+            .context("--watch needs a threshold, e.g. --free-below 10GB")?,
+    )?;
+    let home = crate::uninstall::home_dir();
+    let clean = args.on_low_space == "clean";
+    free_space(&home)
+        .with_context(|| format!("Can't tell the free space of {}", home.display()))?;
+
+    println!(
+        "👀 Watching free space on {}: below {} will {}",
+        home.display(),
+        human_size(threshold),
+        if clean { "clean caches" } else { "notify" }
+    );
+    let mut low = false;
+    loop {
+        let free = free_space(&home).unwrap_or(u64::MAX);
+        if free >= threshold {
+            low = false;
+        } else if !low {
+            low = true;
+            on_low_space(args, free, threshold, clean).await;
+        }
+        tokio::time::sleep(Duration::from_secs(args.watch_interval.max(1))).await;
+    }
+}
+
+async fn on_low_space(args: &CacheArgs, free: u64, threshold: u64, clean: bool) {
+    let title = "Low disk space";
+    let mut body = format!("{} free, below {}", human_size(free), human_size(threshold));
+    if clean {
+        let resp = clean_caches(&CacheQuery {
+            // A backup on the same disk frees nothing; --force deletes outright
+            safe_delete: args.safe_delete && !args.force,
+            ..CacheQuery::from(args)
+        })
+        .await;
+        body.push_str(&format!(
+            ": cleaned {} cache(s), freeing {}",
+            resp.summary.deleted_count,
+            human_size(resp.summary.freed_bytes)
+        ));
+        if let Some(backup) = &resp.backed_up_to {
+            body.push_str(&format!(" (moved to {}; --force deletes instead)", backup));
+        }
+    } else {
+        body.push_str(": run `port-kill cache --list` to see what can go");
+    }
+    println!("⚠️  {}: {}", title, body);
+    crate::notify::show(title, &body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("10GB").unwrap(), 10 * 1024 * 1024 * 1024);
+        assert_eq!(parse_size("500m").unwrap(), 500 * 1024 * 1024);
+        assert_eq!(
+            parse_size("1.5 TB").unwrap(),
+            1024 * 1024 * 1024 * 1024 * 3 / 2
+        );
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert!(parse_size("10XB").is_err());
+        assert!(parse_size("GB").is_err());
+    }
+}
@@ -1,5 +1,6 @@
+use crate::port_spec::{self, PortSpec};
 use crate::preset_manager::{PortPreset, PresetManager};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use clap::{Args as ClapArgs, Subcommand};
 use std::collections::HashSet;
 
@@ -15,6 +16,29 @@ pub enum LogLevel {
     None,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// `[time LEVEL module] message key=value`
+    Text,
+    /// One JSON object per line with timestamp, level, target, message and fields
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    Table,
+    /// One JSON snapshot object per scan
+    Json,
+    /// Comma-separated rows with a header
+    Csv,
+    /// One JSON object per line (per process, history entry or statistic)
+    Ndjson,
+}
+
+/// Prefix of the environment variables that stand in for flags, e.g. `PORT_KILL_PORTS`
+pub const ENV_PREFIX: &str = "PORT_KILL_";
+
 #[derive(Parser, Debug, Clone)]
 #[command(
     name = "port-kill",
@@ -47,10 +71,27 @@ pub struct Args {
     #[arg(long, value_delimiter = ',')]
     pub ignore_patterns: Option<Vec<String>>,
 
+    /// Regular expression to ignore, matched anywhere in the process name, command or full
+    /// command line (repeatable, e.g. --ignore-regex 'node .*--inspect')
+    #[arg(long, value_name = "REGEX")]
+    pub ignore_regex: Option<Vec<String>>,
+
+    /// Match --ignore-patterns, --ignore-regex and --only-processes case-insensitively
+    #[arg(long)]
+    pub ignore_case: bool,
+
     /// Process groups to ignore (e.g., Database,Web Server)
     #[arg(long, value_delimiter = ',')]
     pub ignore_groups: Option<Vec<String>>,
 
+    /// Process names that are shown but never killed (e.g., postgres,redis-server)
+    #[arg(long, value_delimiter = ',')]
+    pub protect: Option<Vec<String>>,
+
+    /// Don't read ~/.port-kill/config.toml or the project's .portkill.toml
+    #[arg(long)]
+    pub no_config: bool,
+
     /// Enable smart filtering (auto-detect and ignore system processes)
     #[arg(long)]
     pub smart_filter: bool,
@@ -59,6 +100,22 @@ pub struct Args {
     #[arg(long, value_delimiter = ',')]
     pub only_groups: Option<Vec<String>>,
 
+    /// Allowlist mode: only show these processes (names or wildcard patterns, e.g. node,bun*)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub only_processes: Option<Vec<String>>,
+
+    /// Allowlist mode: only show processes on these ports (supports ranges like 3000-3010)
+    #[arg(long, value_delimiter = ',', value_name = "PORTS")]
+    pub only_ports: Option<Vec<String>>,
+
+    /// Per-port ignores and allowlists from the config's `[port_rules]`
+    #[arg(skip)]
+    pub port_rules: std::collections::HashMap<String, crate::smart_filter::PortRule>,
+
+    /// Filters from the config's `[[conditional_filters]]`
+    #[arg(skip)]
+    pub conditional_filters: Vec<crate::smart_filter::ConditionalFilter>,
+
     /// Run in console mode instead of status bar mode
     #[arg(short, long)]
     pub console: bool,
@@ -79,6 +136,18 @@ pub struct Args {
     #[arg(long, default_value = "info", value_enum)]
     pub log_level: LogLevel,
 
+    /// Log line format (text, json)
+    #[arg(long, default_value = "text", value_enum)]
+    pub log_format: LogFormat,
+
+    /// Write logs to this file instead of stderr, rotating it as it grows
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<String>,
+
+    /// Size in MB at which --log-file is rotated (the last 5 files are kept)
+    #[arg(long, default_value = "10", value_name = "MB")]
+    pub log_max_size: u64,
+
     /// Show process kill history
     #[arg(long)]
     pub show_history: bool,
@@ -87,6 +156,16 @@ pub struct Args {
     #[arg(long)]
     pub clear_history: bool,
 
+    /// Add the entries of a JSON history file (like the old ~/.port-kill-history.json) to
+    /// the history store
+    #[arg(long, value_name = "FILE")]
+    pub import_history: Option<String>,
+
+    /// Maintain the kill history: `prune` applies the [history] retention of the config
+    /// files now and compacts the database
+    #[arg(long = "history", value_name = "ACTION", value_parser = ["prune"])]
+    pub history_action: Option<String>,
+
     /// Show filter configuration
     #[arg(long)]
     pub show_filters: bool,
@@ -111,9 +190,27 @@ pub struct Args {
     #[arg(long, value_delimiter = ',')]
     pub kill_project: Option<Vec<String>>,
 
+    /// Kill listeners on the monitored ports that hold GPU memory (NVIDIA, via nvidia-smi)
+    #[arg(long)]
+    pub kill_gpu_hogs: bool,
+
+    /// For projects running more than once (same directory and command on different
+    /// ports), keep the newest instance and kill the older ones
+    #[arg(long)]
+    pub kill_duplicates: bool,
+
     /// Restart processes on specific port (kill and restart with saved command)
     #[arg(long)]
     pub restart: Option<u16>,
+
+    /// With --restart: keep watching the restarted process and start it again, with
+    /// backoff, each time it dies
+    #[arg(long, requires = "restart")]
+    pub supervise: bool,
+
+    /// How many times --supervise restarts a process before giving up
+    #[arg(long, default_value = "5", value_name = "N", requires = "supervise")]
+    pub supervise_max_restarts: u32,
     
     /// Show restart history (list ports that can be restarted)
     #[arg(long)]
@@ -123,6 +220,11 @@ pub struct Args {
     #[arg(long)]
     pub clear_restart: Option<u16>,
 
+    /// Start the most recently killed process again with its recorded command, in its
+    /// original directory (asks first)
+    #[arg(long)]
+    pub undo_last_kill: bool,
+
     /// Show process tree (parent-child relationships)
     #[arg(long)]
     pub show_tree: bool,
@@ -131,10 +233,19 @@ pub struct Args {
     #[arg(long)]
     pub json: bool,
 
+    /// Output format for --list and the monitoring loop (table, json, csv)
+    #[arg(long, default_value = "table", value_enum)]
+    pub output: OutputFormat,
+
     /// Reset common development ports (3000, 5000, 8000, 5432, 3306, 6379, 27017, 8080, 9000)
     #[arg(long)]
     pub reset: bool,
 
+    /// With --reset: start the current project fresh instead. Kills the dev servers running
+    /// from it and deletes its build caches (.next, dist, target, ...) after one confirmation
+    #[arg(long, requires = "reset")]
+    pub deep: bool,
+
     /// Show frequent offenders (processes killed multiple times)
     #[arg(long)]
     pub show_offenders: bool,
@@ -147,14 +258,75 @@ pub struct Args {
     #[arg(long)]
     pub show_suggestions: bool,
 
+    /// Add the --show-suggestions ignores to ~/.port-kill/config.toml, after asking
+    #[arg(long)]
+    pub apply_suggestions: bool,
+
+    /// Manage the ignore lists in ~/.port-kill/config.toml: `add <kind> <values>`,
+    /// `remove <kind> <values>` or `list`, where kind is port, process, pattern, regex or group
+    #[arg(long, num_args = 1.., value_names = ["ACTION", "KIND", "VALUE"])]
+    pub ignore: Option<Vec<String>>,
+
     /// Show detailed history statistics
     #[arg(long)]
     pub show_stats: bool,
 
+    /// Only history since WHEN, e.g. "2h ago", "yesterday 9am" or "2025-06-01"
+    /// (--show-history, --show-stats, --show-offenders, --show-patterns, --show-suggestions)
+    #[arg(long = "since", value_name = "WHEN")]
+    pub history_since: Option<String>,
+
+    /// Only history before WHEN
+    #[arg(long = "until", value_name = "WHEN")]
+    pub history_until: Option<String>,
+
+    /// Only history on this port
+    #[arg(long = "port", value_name = "PORT")]
+    pub history_port: Option<u16>,
+
+    /// Only history of this project
+    #[arg(long = "project", value_name = "NAME")]
+    pub history_project: Option<String>,
+
+    /// Only history of this process group, e.g. Node.js
+    #[arg(long = "group", value_name = "GROUP")]
+    pub history_group: Option<String>,
+
+    /// Only the newest N history entries (for --show-history, how many are listed)
+    #[arg(long = "limit", value_name = "N")]
+    pub history_limit: Option<usize>,
+
     /// Show smart root cause analysis
-    #[arg(long)]
+    #[arg(long, alias = "analyze")]
     pub show_root_cause: bool,
 
+    /// With --show-root-cause, offer to carry out its recommendations one at a time: ignore
+    /// offenders, save colliding ports as a preset, reserve ports the guard keeps fighting over
+    #[arg(long, requires = "show_root_cause")]
+    pub apply: bool,
+
+    /// Report kill counts, offenders and conflicts for the monitored ports (combine with --preset).
+    /// `--report week` or `--report month` prints a digest of the whole history instead
+    #[arg(long, num_args = 0..=1, value_name = "PERIOD", value_enum)]
+    pub report: Option<Option<crate::digest::ReportPeriod>>,
+
+    /// How `--report week|month` is printed
+    #[arg(long, value_enum, default_value = "text", value_name = "FORMAT")]
+    pub report_format: crate::digest::ReportFormat,
+
+    /// Show the port map at a past moment from the recorded timeline and compare it with now,
+    /// e.g. "yesterday 15:00", "3pm", "2h ago" or "2025-06-01 09:30"
+    #[arg(long, value_name = "WHEN")]
+    pub at: Option<String>,
+
+    /// Chart when PORT was occupied and by what, from the recorded timeline
+    #[arg(long, value_name = "PORT")]
+    pub timeline: Option<u16>,
+
+    /// How many hours back --timeline goes (the timeline keeps 7 days)
+    #[arg(long, value_name = "N", default_value_t = 24, requires = "timeline")]
+    pub hours: u32,
+
     /// Enable Port Guard Mode - proactive port conflict prevention
     #[arg(long)]
     pub guard_mode: bool,
@@ -167,6 +339,88 @@ pub struct Args {
     #[arg(long)]
     pub auto_resolve: bool,
 
+    /// What the guard does about conflicts: kill, notify, reassign (suggest a free port) or
+    /// block (log only). With --reserve, for that reservation; otherwise for the ports
+    /// without a policy of their own (default: kill with --auto-resolve, notify without)
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub on_conflict: Option<crate::types::PortResolution>,
+
+    /// Simulate guard mode: log what the guard would do on each conflict without killing anything
+    #[arg(long)]
+    pub simulate: bool,
+
+    /// Also scan listeners inside running WSL2 distros (Windows only)
+    #[arg(long)]
+    pub wsl: bool,
+
+    /// Show ESTABLISHED connections (count and peers) for each monitored port and exit
+    #[arg(long)]
+    pub connections: bool,
+
+    /// Show rough throughput and new-connection rate for each monitored port and exit
+    #[arg(long)]
+    pub traffic: bool,
+
+    /// Seconds of traffic to sample for --traffic and --only-active
+    #[arg(long, default_value = "2", value_name = "SECS")]
+    pub traffic_window: u64,
+
+    /// Refuse to kill a listener while clients are still connected to its port
+    #[arg(long)]
+    pub no_kill_if_active: bool,
+
+    /// Only kill listeners that are actually being hit: traffic or new connections during a --traffic-window sample
+    #[arg(long, conflicts_with = "no_kill_if_active")]
+    pub only_active: bool,
+
+    /// Interactive terminal UI: live sortable/filterable table with kill, restart and ignore keys
+    #[arg(long)]
+    pub tui: bool,
+
+    /// Keep the terminal title updated with the process count and status while monitoring
+    #[arg(long)]
+    pub set_terminal_title: bool,
+
+    /// Write a self-contained HTML status page (ports, processes, projects, cache stats, last audit).
+    /// Exits after writing unless --console is given, in which case it is rewritten on every scan
+    #[arg(long, value_name = "FILE")]
+    pub status_page: Option<String>,
+
+    /// Serve the scanner as a JSON HTTP API (processes, kill, history, status, restart).
+    /// ADDR is PORT or HOST:PORT; defaults to 127.0.0.1:7777
+    #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = crate::api_server::DEFAULT_ADDR)]
+    pub serve: Option<String>,
+
+    /// Bearer token `--serve` requires on every request; one is generated and printed when
+    /// this isn't given
+    #[arg(long, value_name = "TOKEN", env = "PORT_KILL_API_TOKEN", hide_env_values = true)]
+    pub serve_token: Option<String>,
+
+    /// Per-port cooldown in seconds between automated kills (0 disables)
+    #[arg(long, default_value = "30")]
+    pub auto_kill_cooldown: u64,
+
+    /// Max automated kills per window before switching to notify-only (0 disables)
+    #[arg(long, default_value = "10")]
+    pub max_auto_kills: usize,
+
+    /// Window in seconds for --max-auto-kills
+    #[arg(long, default_value = "600")]
+    pub auto_kill_window: u64,
+
+    /// Only show and kill processes owned by this user
+    #[arg(long)]
+    pub user: Option<String>,
+
+    /// Allow killing processes owned by other users
+    #[arg(long)]
+    pub force: bool,
+
+    /// Retry kills that fail with a permission error via sudo (Unix) or UAC elevation (Windows)
+    /// (other users' processes are still confirmed unless --force is given)
+    #[arg(long)]
+    pub sudo: bool,
+
     /// Port reservation file path for persistent port assignments
     #[arg(long, default_value = "~/.port-kill/reservations.json")]
     pub reservation_file: String,
@@ -187,6 +441,27 @@ pub struct Args {
     #[arg(long)]
     pub process_name: Option<String>,
 
+    /// Reserve PORT for --process-name (and --project-name) in the reservation file, for the
+    /// guard to enforce
+    #[arg(long, value_name = "PORT", requires = "process_name")]
+    pub reserve: Option<u16>,
+
+    /// How long a --reserve reservation lasts, e.g. 12h or 7d, or never (default 24h)
+    #[arg(long, value_name = "DURATION", requires = "reserve")]
+    pub expires_in: Option<String>,
+
+    /// Let a --reserve reservation lapse when it expires instead of the guard renewing it
+    #[arg(long, requires = "reserve")]
+    pub no_renew: bool,
+
+    /// Release a port reservation
+    #[arg(long, value_name = "PORT")]
+    pub unreserve: Option<u16>,
+
+    /// List the port reservations
+    #[arg(long)]
+    pub reservations: bool,
+
     /// Enable Security Audit Mode - comprehensive security analysis
     #[arg(long)]
     pub audit: bool,
@@ -211,6 +486,46 @@ pub struct Args {
     #[arg(long)]
     pub remote: Option<String>,
 
+    /// Also stop the other end of a tunnel: killing a local ssh -L or kubectl port-forward
+    /// stops the process it forwards to, and killing ports with --remote closes local ssh
+    /// tunnels to them
+    #[arg(long)]
+    pub linked: bool,
+
+    /// Renice the process on PORT instead of killing it, e.g. `--nice 3000 +10` keeps a
+    /// heavy dev server running at a lower priority (negative values usually need root)
+    #[arg(long, num_args = 2, value_names = ["PORT", "ADJUSTMENT"], allow_hyphen_values = true)]
+    pub nice: Option<Vec<String>>,
+
+    /// Guard the named profiles from the config file side by side, each with its own
+    /// ports, filters and policy (use with --guard-mode; `all` runs every profile)
+    #[arg(long, value_delimiter = ',', value_name = "NAME")]
+    pub profile: Option<Vec<String>>,
+
+    /// Show the audit trail of kills, restarts and cache cleans (~/.port-kill/audit.log)
+    #[arg(long)]
+    pub show_audit: bool,
+
+    /// Only audit entries since WHEN, e.g. "2h ago", "yesterday 9am" or "2025-06-01 09:30"
+    #[arg(long, value_name = "WHEN")]
+    pub audit_since: Option<String>,
+
+    /// Only audit entries for this action (kill, restart, cache-clean)
+    #[arg(long, value_enum)]
+    pub audit_action: Option<crate::audit_log::AuditAction>,
+
+    /// Only audit entries started from here (cli, tray, tui, script, api, guard)
+    #[arg(long, value_enum)]
+    pub audit_initiator: Option<crate::audit_log::AuditInitiator>,
+
+    /// Only audit entries for this port
+    #[arg(long, value_name = "PORT")]
+    pub audit_port: Option<u16>,
+
+    /// Only failed actions in the audit trail
+    #[arg(long)]
+    pub audit_failed: bool,
+
     /// Endpoint monitoring: send data to external endpoint for monitoring/alerting
     #[arg(long)]
     pub monitor_endpoint: Option<String>,
@@ -288,15 +603,44 @@ pub struct Args {
     #[arg(long)]
     pub list: bool,
 
+    /// Scan once, print the results (honours filters, --json and --output) and exit; exit code 2 if anything was found
+    #[arg(long)]
+    pub once: bool,
+
+    /// Block until these ports are free (comma-separated, supports ranges); combine with positional ports to kill then wait
+    #[arg(long, value_delimiter = ',', value_name = "PORTS")]
+    pub wait_free: Option<Vec<String>>,
+
+    /// Block until these ports accept connections (comma-separated, supports ranges), e.g. before starting dependents
+    #[arg(long, value_delimiter = ',', value_name = "PORTS")]
+    pub wait_open: Option<Vec<String>>,
+
+    /// Give up --wait-free / --wait-open after this many seconds (exit code 5); waits indefinitely by default
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+
+    /// Exit with code 2 if any of these ports is in use, 0 if all are free (comma-separated, supports ranges; for CI and Makefiles)
+    #[arg(long, value_delimiter = ',', value_name = "PORTS")]
+    pub fail_if_occupied: Option<Vec<String>>,
+
     /// Ask for confirmation before killing
     #[arg(long)]
     pub safe: bool,
 
+    /// Turn off safe mode for good: no prompts on multi-kills, system ports and processes
+    /// become killable, and --kill-all over wide ranges really kills
+    #[arg(long)]
+    pub trust_me: bool,
+
+    /// Whether safe mode is on for this run, worked out from `--trust-me` and the config
+    #[arg(skip)]
+    pub safe_mode: bool,
+
     /// Positional ports imply clearPort on each (e.g., `port-kill 3000 5000`)
     #[arg(value_parser, value_name = "PORTS")]
     pub positional_ports: Vec<u16>,
 
-    /// Use a preset configuration (e.g., 'dev', 'system', 'database', 'web', 'react', 'node', 'python', 'full', 'minimal')
+    /// Use a preset configuration (e.g., 'dev', 'system', 'database', 'web', 'react', 'node', 'python', 'full', 'minimal'), or 'auto' for one matching the project in the current directory
     #[arg(long, value_name = "PRESET")]
     pub preset: Option<String>,
 
@@ -316,17 +660,101 @@ pub struct Args {
     #[arg(long, value_name = "NAME")]
     pub delete_preset: Option<String>,
 
+    /// Write a preset to FILE (TOML when it ends in .toml, JSON otherwise), or print it as
+    /// JSON, for sharing through a repo or a gist
+    #[arg(long, num_args = 1..=2, value_names = ["NAME", "FILE"])]
+    pub export_preset: Option<Vec<String>>,
+
+    /// Add the presets in a file or URL written by --export-preset (or a presets.json)
+    #[arg(long, value_name = "FILE|URL")]
+    pub import_preset: Option<String>,
+
+    /// Import the preset under this name instead of the one in the file
+    #[arg(long, value_name = "NAME", requires = "import_preset")]
+    pub import_as: Option<String>,
+
+    /// Let --import-preset replace presets with the same name
+    #[arg(long, requires = "import_preset")]
+    pub overwrite: bool,
+
+    /// Print a preset's full definition as JSON
+    #[arg(long, value_name = "NAME")]
+    pub show_preset: Option<String>,
+
+    /// Open a preset in $EDITOR as JSON; it is checked and saved when the editor exits
+    #[arg(long, value_name = "NAME")]
+    pub edit_preset: Option<String>,
+
+    /// Scan once and report which running processes the preset would show, ignore, or allow killing
+    #[arg(long)]
+    pub what_if: bool,
+
     /// Check for updates and show notification if available
     #[arg(long)]
     pub check_updates: bool,
 
+    /// Skip the automatic update check on startup (e.g. in CI containers)
+    #[arg(long)]
+    pub no_update_check: bool,
+
+    /// Show desktop notifications for kills, failed kills and port guard conflicts
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Show realistic fake processes instead of scanning the system; kills only pretend.
+    /// For screenshots, demos and UI work
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Send a test event to every webhook in `[[notifications.webhooks]]` and report the result
+    #[arg(long)]
+    pub test_webhooks: bool,
+
+    /// Remove installed services, login items and shell completions, printing each path
+    #[arg(long)]
+    pub uninstall: bool,
+
+    /// Open the tray at login (LaunchAgent on macOS, autostart entry on Linux, Run key on Windows)
+    #[arg(long, conflicts_with = "disable_autostart")]
+    pub enable_autostart: bool,
+
+    /// Stop opening the tray at login
+    #[arg(long)]
+    pub disable_autostart: bool,
+
+    /// With --uninstall, also delete all state: config, presets, history, reservations and cache backups
+    #[arg(long)]
+    pub purge: bool,
+
     /// Automatically update to the latest version
     #[arg(long)]
     pub self_update: bool,
 
-    /// Cache management subcommand
+    /// Never touch the network: no update checks, webhooks, --monitor-endpoint, --remote or
+    /// --linked tunnel endpoints (`cache --doctor` lists what is blocked)
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Resident memory port-kill itself may use while running long (tray, daemon, guard,
+    /// --serve), e.g. 200M. Over it, port-kill warns; the daemon restarts itself.
+    #[arg(long, value_name = "SIZE")]
+    pub max_rss: Option<String>,
+
+    /// Tray icon theme: auto, color, template (follows the macOS menu bar), light or dark
+    #[arg(long, value_name = "THEME")]
+    pub icon_theme: Option<String>,
+
+    /// Cache management and shell completion subcommands
     #[command(subcommand)]
     pub cache: Option<CacheSubcommand>,
+
+    /// Print completion candidates (used by the generated completion scripts)
+    #[arg(long, hide = true, value_enum, value_name = "KIND")]
+    pub complete_values: Option<crate::completions::CompletionValues>,
+
+    /// Print the JSON Schema of a `--json` output (e.g. output.list, output.cache.list)
+    #[arg(long, value_enum, value_name = "OUTPUT")]
+    pub schema: Option<crate::output_schema::OutputSchema>,
     
     /// Detect available services (npm scripts, docker-compose, etc.)
     #[arg(long)]
@@ -355,6 +783,23 @@ pub struct Args {
     /// Show status of all configured services
     #[arg(long)]
     pub status: bool,
+
+    /// Start the configured services that crashed again, leaving stopped ones alone
+    #[arg(long)]
+    pub restart_failed: bool,
+
+    /// Take servers started by hand into the orchestration state (matched by port,
+    /// directory and command), so --status reports them and --down stops them
+    #[arg(long)]
+    pub adopt: bool,
+
+    /// Print the end of a service's captured output (~/.port-kill/logs/<service>.log)
+    #[arg(long, value_name = "SERVICE")]
+    pub logs: Option<String>,
+
+    /// With --logs: keep printing what the service writes
+    #[arg(long, requires = "logs")]
+    pub follow: bool,
     
     /// Path to orchestration config file
     #[arg(long, default_value = ".port-kill.yaml")]
@@ -369,20 +814,75 @@ pub struct Args {
 pub enum CacheSubcommand {
     /// Cache operations: list, clean, dry-run, restore, doctor, NPX, JS PM
     #[command(name = "cache", visible_alias = "c")]
-    Op(CacheArgs),
+    Op(Box<CacheArgs>),
+    /// Print a shell completion script (bash, zsh, fish, powershell, elvish)
+    Completions(CompletionsArgs),
+    /// Filter listeners (or history) with an expression, e.g. 'group=Node.js and cpu>50'
+    Query(QueryArgs),
+    /// Monitor (or guard, with --guard-mode) with no terminal attached
+    Daemon(DaemonArgs),
 }
 
 // For backward compatibility - keep CacheCommand as an alias
 pub type CacheCommand = CacheSubcommand;
 
 impl CacheSubcommand {
-    pub fn args(&self) -> &CacheArgs {
+    /// Arguments of the `cache` subcommand, if that is the one given
+    pub fn args(&self) -> Option<&CacheArgs> {
         match self {
-            CacheSubcommand::Op(args) => args,
+            CacheSubcommand::Op(args) => Some(args),
+            CacheSubcommand::Completions(_)
+            | CacheSubcommand::Query(_)
+            | CacheSubcommand::Daemon(_) => None,
         }
     }
 }
 
+#[derive(ClapArgs, Debug, Clone)]
+pub struct CompletionsArgs {
+    /// Shell to generate the script for
+    #[arg(value_enum)]
+    pub shell: clap_complete::Shell,
+
+    /// Write the script to the shell's per-user completion directory instead of stdout
+    #[arg(long)]
+    pub install: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct DaemonArgs {
+    /// Register a service (systemd user unit, LaunchAgent or logon task) that runs the daemon
+    /// with the flags given before `daemon`, starting at login
+    #[arg(long, conflicts_with = "uninstall_service")]
+    pub install_service: bool,
+
+    /// Stop and remove the service installed with --install-service
+    #[arg(long)]
+    pub uninstall_service: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct QueryArgs {
+    /// Filter expression, e.g. "group=Node.js and cpu>50 and project!=infra"
+    pub expression: String,
+
+    /// Match the kill history instead of the current listeners
+    #[arg(long, conflicts_with_all = ["kill", "restart"])]
+    pub history: bool,
+
+    /// Kill every matching process
+    #[arg(long, conflicts_with = "restart")]
+    pub kill: bool,
+
+    /// Restart every matching process with its saved restart command
+    #[arg(long)]
+    pub restart: bool,
+
+    /// JSON output, one object per line
+    #[arg(long)]
+    pub json: bool,
+}
+
 #[derive(ClapArgs, Debug, Clone)]
 pub struct CacheArgs {
     /// Operation mode: list, clean, dry-run, restore-last, doctor
@@ -394,8 +894,23 @@ pub struct CacheArgs {
     pub dry_run: bool,
     #[arg(long)]
     pub restore_last: bool,
+    /// Restore from a backup: the newest, or the one --backup names
+    #[arg(long)]
+    pub restore: bool,
+    /// Backup for --restore, as --backups lists it (default: the newest)
+    #[arg(long, value_name = "BACKUP")]
+    pub backup: Option<String>,
+    /// With --restore: only these entries, by original path or cache ID
+    #[arg(long, value_name = "PATH|ID", value_delimiter = ',')]
+    pub only: Vec<String>,
     #[arg(long)]
     pub doctor: bool,
+    /// With --doctor: apply the fixes it offers (create missing directories, repair backups)
+    #[arg(long, requires = "doctor")]
+    pub fix: bool,
+    /// With --doctor: exit with 1 when anything at this severity or worse is left
+    #[arg(long, value_name = "SEVERITY", value_enum, requires = "doctor")]
+    pub fail_on: Option<crate::cache::doctor::Severity>,
 
     /// JSON output
     #[arg(long)]
@@ -432,45 +947,210 @@ pub struct CacheArgs {
     /// NPX stale days
     #[arg(long)]
     pub stale_days: Option<u32>,
+
+    /// Directories searched for Cargo projects' target/ (default: the current directory)
+    #[arg(long, value_delimiter = ',')]
+    pub roots: Vec<std::path::PathBuf>,
+
+    /// Python caches to include: pip, poetry, conda, pycache, pytest, mypy, ruff, venv
+    /// (default: all)
+    #[arg(long, value_delimiter = ',', value_parser = crate::cache::detect::PYTHON_CATEGORIES)]
+    pub python_categories: Vec<String>,
+
+    /// Only the build artifacts of one project (node_modules/.cache, target/, .next, dist,
+    /// __pycache__, coverage, ...). Without a path, the git repository of the current directory
+    #[arg(long, num_args = 0..=1, default_missing_value = "")]
+    pub project: Option<String>,
+
+    /// Don't count directories deeper than this below each cache when measuring sizes
+    #[arg(long)]
+    pub max_depth: Option<usize>,
+
+    /// Stop measuring a cache after this many seconds and report what was counted
+    #[arg(long)]
+    pub scan_timeout: Option<u64>,
+
+    /// Reuse sizes measured in the last N seconds (0 measures every time)
+    #[arg(long, default_value_t = crate::cache::size::DEFAULT_TTL_SECS)]
+    pub size_cache_ttl: u64,
+
+    /// Never list or clean this path, or a cache that contains it (saved in the config)
+    #[arg(long, value_name = "PATH")]
+    pub pin: Option<String>,
+
+    /// Remove a path added with --pin
+    #[arg(long, value_name = "PATH")]
+    pub unpin: Option<String>,
+
+    /// Keep checking free disk space and act when it drops below --free-below
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Free space threshold for --watch, e.g. 10GB or 500MB
+    #[arg(long, value_name = "SIZE")]
+    pub free_below: Option<String>,
+
+    /// What --watch does when space runs low: notify, or clean with the other cache flags
+    #[arg(long, default_value = "notify", value_parser = ["notify", "clean"])]
+    pub on_low_space: String,
+
+    /// Seconds between --watch checks
+    #[arg(long, default_value_t = 60)]
+    pub watch_interval: u64,
+
+    /// Clean caches daily or weekly, with the other cache flags as the policy
+    #[arg(long, value_name = "FREQUENCY", value_parser = ["daily", "weekly"])]
+    pub schedule: Option<String>,
+
+    /// Register --schedule with systemd (or cron), launchd or Task Scheduler instead of running it
+    #[arg(long, requires = "schedule")]
+    pub install_schedule: bool,
+
+    /// Remove the schedule --install-schedule registered
+    #[arg(long)]
+    pub uninstall_schedule: bool,
+
+    /// With --schedule: clean once now and log it, as the registered schedule does
+    #[arg(long, requires = "schedule")]
+    pub once: bool,
+
+    /// Show the log of scheduled cleans
+    #[arg(long)]
+    pub schedule_log: bool,
+
+    /// List the backups --clean --safe-delete made, with their sizes and contents
+    #[arg(long)]
+    pub backups: bool,
+
+    /// Backups to keep after a clean or restore removes old ones (default 10, 0 keeps all)
+    #[arg(long, value_name = "N")]
+    pub keep_backups: Option<usize>,
+
+    /// Remove the oldest backups while all of them together are larger than this, e.g. 20GB
+    #[arg(long, value_name = "SIZE", value_parser = crate::cache::watch::parse_size)]
+    pub max_backup_size: Option<u64>,
+
+    /// Remove backups older than this many days
+    #[arg(long, value_name = "DAYS")]
+    pub max_backup_age: Option<u32>,
+
+    /// Store --safe-delete backups as zstd-compressed tarballs instead of moving the caches
+    #[arg(long)]
+    pub compress_backups: bool,
+}
+
+impl CacheArgs {
+    /// How cache sizes are measured for these arguments
+    pub fn scan_options(&self) -> crate::cache::size::ScanOptions {
+        crate::cache::size::ScanOptions {
+            max_depth: self.max_depth,
+            timeout: self.scan_timeout.map(std::time::Duration::from_secs),
+            ttl: std::time::Duration::from_secs(self.size_cache_ttl),
+            // Keep stderr quiet for scripts reading --json
+            progress: !self.json,
+        }
+    }
+
+    /// Backup retention flags; unset ones fall back to the `[cache]` config
+    pub fn retention(&self) -> crate::cache::backup::Retention {
+        crate::cache::backup::Retention {
+            keep: self.keep_backups,
+            max_total_bytes: self.max_backup_size,
+            max_age_days: self.max_backup_age,
+        }
+    }
+}
+
+/// Attach `<prefix><FLAG>` environment variables to `command`'s flags, and recurse into
+/// its subcommands
+fn with_env(command: clap::Command, prefix: &str) -> clap::Command {
+    let command = command.mut_args(|arg| {
+        if arg.is_positional() || arg.get_env().is_some() {
+            return arg;
+        }
+        let var = format!("{}{}", prefix, arg.get_id().as_str().to_uppercase());
+        if matches!(arg.get_action(), clap::ArgAction::SetTrue) {
+            // Accept 1/0, yes/no, on/off as well as true/false
+            arg.env(var).value_parser(clap::builder::FalseyValueParser::new())
+        } else {
+            arg.env(var)
+        }
+    });
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    subcommands.into_iter().fold(command, |command, name| {
+        let prefix = format!("{}{}_", prefix, name.to_uppercase().replace('-', "_"));
+        command.mut_subcommand(&name, |sub| with_env(sub, &prefix))
+    })
 }
 
 impl Args {
+    /// Parse the command line. Flags that aren't given fall back to `PORT_KILL_<FLAG>`
+    /// environment variables, e.g. `PORT_KILL_PORTS=3000,8080` or `PORT_KILL_NO_CONFIG=1`.
+    pub fn parse_with_env() -> Self {
+        let matches = Self::command_with_env().get_matches();
+        Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+    }
+
+    /// The clap command with an environment variable attached to every flag that doesn't
+    /// name its own: `PORT_KILL_<FLAG>`, and `PORT_KILL_<SUBCOMMAND>_<FLAG>` for subcommands
+    pub fn command_with_env() -> clap::Command {
+        with_env(Self::command(), ENV_PREFIX)
+    }
+
     /// Get the list of ports to monitor
     pub fn get_ports_to_monitor(&self) -> Vec<u16> {
         if let Some(ref specific_ports) = self.ports {
-            // Parse port strings (supports individual ports and ranges like "3000-3010")
-            let mut ports = Vec::new();
-            for port_str in specific_ports {
-                if let Some(range_ports) = self.parse_port_range(port_str) {
-                    ports.extend(range_ports);
-                }
+            // Port specs: single ports, ranges like "3000-3010", wildcards like "30**" and
+            // exclusions like "!3005". Exclusions alone apply to the port range.
+            if specific_ports
+                .iter()
+                .all(|spec| port_spec::is_exclusion(spec))
+            {
+                let mut specs = vec![format!("{}-{}", self.start_port, self.end_port)];
+                specs.extend(specific_ports.iter().cloned());
+                return port_spec::expand(&specs);
             }
-            ports
+            port_spec::expand(specific_ports)
         } else {
             // Use port range
             (self.start_port..=self.end_port).collect()
         }
     }
 
-    /// Parse a port string that can be either a single port or a range (e.g., "3000" or "3000-3010")
+    /// Ports passed to --fail-if-occupied, if any
+    pub fn get_fail_if_occupied_ports(&self) -> Option<Vec<u16>> {
+        self.fail_if_occupied
+            .as_ref()
+            .map(|specs| self.parse_port_specs(specs))
+    }
+
+    /// Ports passed to --wait-free, if any
+    pub fn get_wait_free_ports(&self) -> Option<Vec<u16>> {
+        self.wait_free.as_ref().map(|specs| self.parse_port_specs(specs))
+    }
+
+    /// Ports passed to --wait-open, if any
+    pub fn get_wait_open_ports(&self) -> Option<Vec<u16>> {
+        self.wait_open.as_ref().map(|specs| self.parse_port_specs(specs))
+    }
+
+    fn parse_port_specs(&self, specs: &[String]) -> Vec<u16> {
+        specs
+            .iter()
+            .filter_map(|spec| self.parse_port_range(spec))
+            .flatten()
+            .collect()
+    }
+
+    /// Parse a port string that can be a single port, a range or a wildcard (e.g., "3000",
+    /// "3000-3010" or "30**"); exclusions aren't ports
     fn parse_port_range(&self, port_str: &str) -> Option<Vec<u16>> {
-        let port_str = port_str.trim();
-
-        if port_str.contains('-') {
-            // Handle port range (e.g., "3000-3010" or "3000 - 3010")
-            let parts: Vec<&str> = port_str.split('-').collect();
-            if parts.len() == 2 {
-                // Trim each part to handle ranges with spaces like "3000 - 3010"
-                if let (Ok(start), Ok(end)) = (parts[0].trim().parse::<u16>(), parts[1].trim().parse::<u16>()) {
-                    if start <= end {
-                        return Some((start..=end).collect());
-                    }
-                }
-            }
-            None
-        } else {
-            // Handle single port
-            port_str.parse::<u16>().ok().map(|port| vec![port])
+        match port_spec::parse(port_str) {
+            Some((false, range)) => Some(range.collect()),
+            _ => None,
         }
     }
 
@@ -513,12 +1193,23 @@ impl Args {
             .map(|groups| groups.iter().cloned().collect())
     }
 
+    /// Ports passed to --only-ports, if any
+    pub fn get_only_ports_set(&self) -> Option<HashSet<u16>> {
+        self.only_ports
+            .as_ref()
+            .map(|specs| port_spec::expand(specs).into_iter().collect())
+    }
+
     /// Get smart filter defaults
     pub fn get_smart_filter_defaults(&self) -> (HashSet<u16>, HashSet<String>, HashSet<String>) {
         if !self.smart_filter {
             return (HashSet::new(), HashSet::new(), HashSet::new());
         }
+        Self::smart_filter_defaults()
+    }
 
+    /// What `--smart-filter` hides: common system ports, processes and process groups
+    pub fn smart_filter_defaults() -> (HashSet<u16>, HashSet<String>, HashSet<String>) {
         // Smart port ignores (common system ports)
         let smart_ignore_ports: HashSet<u16> = [
             22,    // SSH
@@ -651,14 +1342,29 @@ impl Args {
             }
 
             for port_str in specific_ports {
-                if let Some(ports) = self.parse_port_range(port_str) {
-                    for port in ports {
-                        if port == 0 {
-                            return Err("Port 0 is not valid".to_string());
-                        }
+                match port_spec::parse(port_str) {
+                    Some((false, range)) if *range.start() == 0 => {
+                        return Err("Port 0 is not valid".to_string());
                     }
-                } else {
-                    return Err(format!("Invalid port specification: '{}'", port_str));
+                    Some(_) => {}
+                    None => return Err(format!("Invalid port specification: '{}'", port_str)),
+                }
+            }
+            if self.get_ports_to_monitor().is_empty() {
+                return Err("The port exclusions leave no ports to monitor".to_string());
+            }
+        }
+
+        for (flag, specs) in [
+            ("--only-ports", &self.only_ports),
+            ("--fail-if-occupied", &self.fail_if_occupied),
+            ("--wait-free", &self.wait_free),
+            ("--wait-open", &self.wait_open),
+        ] {
+            for port_str in specs.iter().flatten() {
+                match self.parse_port_range(port_str) {
+                    Some(ports) if !ports.contains(&0) => {}
+                    _ => return Err(format!("Invalid {} port: '{}'", flag, port_str)),
                 }
             }
         }
@@ -681,6 +1387,105 @@ impl Args {
             }
         }
 
+        for pattern in self.ignore_regex.iter().flatten() {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(format!("Invalid --ignore-regex '{}': {}", pattern, e));
+            }
+        }
+
+        if self.simulate && !self.guard_mode && self.guard.is_none() {
+            return Err("--simulate requires --guard-mode or --guard".to_string());
+        }
+
+        if self.on_conflict.is_some()
+            && !self.guard_mode
+            && self.guard.is_none()
+            && self.reserve.is_none()
+        {
+            return Err("--on-conflict requires --guard-mode, --guard or --reserve".to_string());
+        }
+
+        if self.profile.is_some() && !self.guard_mode {
+            return Err("--profile requires --guard-mode".to_string());
+        }
+
+        if let Some(theme) = &self.icon_theme {
+            if let Err(e) = theme.parse::<crate::icon_theme::IconTheme>() {
+                return Err(e.to_string());
+            }
+        }
+
+        if let Some(size) = &self.max_rss {
+            if let Err(e) = crate::service_group::parse_size(size) {
+                return Err(format!("Invalid --max-rss: {}", e));
+            }
+        }
+
+        if self.purge && !self.uninstall {
+            return Err("--purge requires --uninstall".to_string());
+        }
+
+        let audit_filtered = self.audit_since.is_some()
+            || self.audit_action.is_some()
+            || self.audit_initiator.is_some()
+            || self.audit_port.is_some()
+            || self.audit_failed;
+        if audit_filtered && !self.show_audit {
+            return Err("--audit-since, --audit-action, --audit-initiator, --audit-port and --audit-failed require --show-audit".to_string());
+        }
+
+        if self.hours == 0 || i64::from(self.hours) > crate::timeline::RETENTION_DAYS * 24 {
+            return Err(format!(
+                "--hours must be between 1 and {}, the hours the timeline keeps",
+                crate::timeline::RETENTION_DAYS * 24
+            ));
+        }
+
+        if let Some(expires_in) = &self.expires_in {
+            if let Err(e) = crate::reservations::parse_term(expires_in) {
+                return Err(e.to_string());
+            }
+        }
+
+        if self.apply && self.json {
+            return Err("--apply asks before each change and can't be used with --json".to_string());
+        }
+
+        if self.report_format != crate::digest::ReportFormat::Text
+            && !matches!(self.report, Some(Some(_)))
+        {
+            return Err("--report-format requires --report week or --report month".to_string());
+        }
+
+        let history_filtered = self.history_since.is_some()
+            || self.history_until.is_some()
+            || self.history_port.is_some()
+            || self.history_project.is_some()
+            || self.history_group.is_some()
+            || self.history_limit.is_some();
+        let history_shown = self.show_history
+            || self.show_stats
+            || self.show_offenders
+            || self.show_patterns
+            || self.show_suggestions;
+        if history_filtered && !history_shown {
+            return Err("--since, --until, --port, --project, --group and --limit require --show-history, --show-stats, --show-offenders, --show-patterns or --show-suggestions".to_string());
+        }
+        for (flag, when) in [
+            ("--since", &self.history_since),
+            ("--until", &self.history_until),
+        ] {
+            if let Some(when) = when {
+                if let Err(e) = crate::timeline::parse_when(when, chrono::Local::now()) {
+                    return Err(format!("Invalid {}: {}", flag, e));
+                }
+            }
+        }
+
+        if self.what_if && self.preset.is_none() {
+            return Err("--what-if requires --preset".to_string());
+        }
+
         Ok(())
     }
 
@@ -746,10 +1551,112 @@ impl Args {
         self.remote.clone()
     }
 
+    /// Merge ~/.port-kill/config.toml and the project's .portkill.toml (unless `--no-config`)
+    pub fn apply_layered_config(&mut self) -> Result<(), String> {
+        if self.no_config {
+            self.apply_safe_mode(None);
+            return Ok(());
+        }
+        let layered = crate::config::load().map_err(|e| format!("{:#}", e))?;
+        self.apply_config(&layered);
+        Ok(())
+    }
+
+    /// Fill in settings from the config files. Ports (or the config's preset) only apply
+    /// when none were given on the command line; ignore and protected lists are combined.
+    pub fn apply_config(&mut self, layered: &crate::config::LayeredConfig) {
+        let config = &layered.config;
+        let range_given = self.start_port != 2000 || self.end_port != 9000;
+        if self.ports.is_none() && self.preset.is_none() && !range_given {
+            self.preset = config.preset.clone();
+            if self.preset.is_none() {
+                self.ports = config.ports.clone();
+            }
+        }
+
+        fn combine<T: Clone + PartialEq>(args: &mut Option<Vec<T>>, config: &Option<Vec<T>>) {
+            if let Some(config) = config {
+                let list = args.get_or_insert_with(Vec::new);
+                for item in config {
+                    if !list.contains(item) {
+                        list.push(item.clone());
+                    }
+                }
+            }
+        }
+        combine(&mut self.ignore_ports, &config.ignore_ports);
+        combine(&mut self.ignore_processes, &config.ignore_processes);
+        combine(&mut self.ignore_patterns, &config.ignore_patterns);
+        combine(&mut self.ignore_regex, &config.ignore_regex);
+        combine(&mut self.ignore_groups, &config.ignore_groups);
+        combine(&mut self.protect, &config.protected);
+        self.docker |= config.docker.unwrap_or(false);
+        self.ignore_case |= config.ignore_case.unwrap_or(false);
+        self.port_rules = config.port_rules.clone();
+        self.conditional_filters = config.conditional_filters.clone();
+        if self.on_conflict.is_none() && self.reserve.is_none() {
+            self.on_conflict = config.on_conflict;
+        }
+        if self.scan_interval == 2 {
+            if let Some(interval) = config.scan_interval {
+                self.scan_interval = interval.max(1);
+            }
+        }
+        self.apply_safe_mode(config.safe_mode);
+
+        // Services in .portkill.toml stand in for a missing .port-kill.yaml
+        if let Some(ref project) = layered.project_path {
+            if !config.services.is_empty()
+                && self.config_file == ".port-kill.yaml"
+                && !std::path::Path::new(&self.config_file).exists()
+            {
+                self.config_file = project.to_string_lossy().to_string();
+            }
+        }
+    }
+
+    /// Turn safe mode on unless the user trusted port-kill (or the config says otherwise),
+    /// protecting system processes. Kills check system ports with
+    /// [`crate::safe_mode::ensure_not_system_port`].
+    pub fn apply_safe_mode(&mut self, config_setting: Option<bool>) {
+        self.safe_mode = !self.trust_me && crate::safe_mode::is_active(config_setting);
+        if self.safe_mode {
+            let protect = self.protect.get_or_insert_with(Vec::new);
+            for name in crate::safe_mode::SYSTEM_PROCESSES {
+                if !protect.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+                    protect.push(name.to_string());
+                }
+            }
+        }
+    }
+
+    /// Whether killing `count` processes needs a yes first (`--safe`, or safe mode and more than one)
+    pub fn needs_kill_confirmation(&self, count: usize) -> bool {
+        count > 0 && (self.safe || (self.safe_mode && count > 1))
+    }
+
+    /// Traffic sampling window when --only-active is set
+    pub fn only_active_window(&self) -> Option<std::time::Duration> {
+        self.only_active
+            .then(|| std::time::Duration::from_secs(self.traffic_window.max(1)))
+    }
+
+    /// `--max-rss` in bytes (checked in `validate`)
+    pub fn max_rss_bytes(&self) -> Option<u64> {
+        self.max_rss
+            .as_deref()
+            .and_then(|size| crate::service_group::parse_size(size).ok())
+    }
+
+    /// Process names that must never be killed
+    pub fn get_protected_processes(&self) -> Vec<String> {
+        self.protect.clone().unwrap_or_default()
+    }
+
     /// Apply preset configuration to these args
     pub fn apply_preset(&mut self, preset: &PortPreset) {
         // Override ports with preset ports
-        self.ports = Some(preset.ports.iter().map(|p| p.to_string()).collect());
+        self.ports = Some(preset.ports.iter().map(|spec| spec.to_string()).collect());
 
         // Apply ignore settings from preset
         if let Some(ref ignore_ports) = preset.ignore_ports {
@@ -782,20 +1689,74 @@ impl Args {
 
     /// Load and apply preset by name
     pub fn load_preset(&mut self, preset_name: &str) -> Result<(), String> {
+        let manager = self.presets_for(preset_name)?;
+        let preset = manager
+            .resolve_preset(preset_name)
+            .map_err(|e| e.to_string())?;
+        self.apply_preset(&preset);
+        Ok(())
+    }
+
+    /// `--show-preset`: the preset's definition as pretty JSON, and the ports it comes to
+    /// when it extends others
+    pub fn show_preset(&self, preset_name: &str) -> Result<(String, Option<Vec<u16>>), String> {
+        let manager = self.presets_for(preset_name)?;
+        let preset = manager
+            .get_preset(preset_name)
+            .expect("presets_for checks the preset exists");
+        let definition = serde_json::to_string_pretty(preset).map_err(|e| e.to_string())?;
+        let resolved = if preset.extends.is_empty() {
+            None
+        } else {
+            let resolved = manager
+                .resolve_preset(preset_name)
+                .map_err(|e| e.to_string())?;
+            Some(resolved.port_numbers())
+        };
+        Ok((definition, resolved))
+    }
+
+    /// The presets with `preset_name` among them: saved and built-in ones, those in the
+    /// config files and, for `auto`, the one detected for the project
+    fn presets_for(&self, preset_name: &str) -> Result<PresetManager, String> {
         let mut manager = PresetManager::new();
         manager
             .load_presets()
             .map_err(|e| format!("Failed to load presets: {}", e))?;
+        if !self.no_config {
+            Self::add_config_presets(&mut manager);
+        }
 
-        if let Some(preset) = manager.get_preset(preset_name) {
-            self.apply_preset(preset);
-            Ok(())
-        } else {
-            Err(format!(
+        // A preset of your own called "auto" wins over detection
+        if preset_name == crate::preset_manager::AUTO_PRESET
+            && manager.get_preset(preset_name).is_none()
+        {
+            // The current directory, else the repository it is in
+            let cwd = std::env::current_dir().unwrap_or_else(|_| ".".into());
+            let root = crate::cache::project::project_root("");
+            let preset = crate::preset_manager::detect_project_preset(&cwd)
+                .or_else(|| {
+                    (root != cwd)
+                        .then(|| crate::preset_manager::detect_project_preset(&root))
+                        .flatten()
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "--preset auto found no project in {}; pass --preset <name> or --ports",
+                        cwd.display()
+                    )
+                })?;
+            log::info!("--preset auto: {}", preset.description);
+            manager.add_preset(preset);
+        }
+
+        if manager.get_preset(preset_name).is_none() {
+            return Err(format!(
                 "Preset '{}' not found. Use --list-presets to see available presets.",
                 preset_name
-            ))
+            ));
         }
+        Ok(manager)
     }
 
     /// List available presets
@@ -804,15 +1765,28 @@ impl Args {
         manager
             .load_presets()
             .map_err(|e| format!("Failed to load presets: {}", e))?;
+        Self::add_config_presets(&mut manager);
         Ok(manager.list_presets())
     }
 
+    /// Presets defined in the config files (they are never written to presets.json)
+    fn add_config_presets(manager: &mut PresetManager) {
+        for (name, preset) in &crate::config::load_or_default().config.presets {
+            manager.add_preset(preset.to_preset(name));
+        }
+    }
+
     /// Build a PortPreset from current arguments
     pub fn build_preset_from_args(&self, name: String, description: String) -> PortPreset {
         PortPreset {
             name,
             description,
-            ports: self.get_ports_to_monitor(),
+            ports: match &self.ports {
+                Some(specs) => specs.iter().map(|spec| PortSpec::from(spec.as_str())).collect(),
+                None => vec![PortSpec::from(
+                    format!("{}-{}", self.start_port, self.end_port).as_str(),
+                )],
+            },
             ignore_ports: self.ignore_ports.clone(),
             ignore_processes: self.ignore_processes.clone(),
             ignore_patterns: self.ignore_patterns.clone(),
@@ -823,6 +1797,20 @@ impl Args {
             show_pid: self.show_pid,
             performance: self.performance,
             show_context: self.show_context,
+            extends: Vec::new(),
+            provenance: None,
+            meta: Default::default(),
+        }
+    }
+}
+
+impl Args {
+    /// Format for process listings; the older `--json` flag implies json
+    pub fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            self.output
         }
     }
 }
@@ -867,16 +1855,27 @@ mod tests {
             ignore_ports: None,
             ignore_processes: None,
             ignore_patterns: None,
+            ignore_regex: None,
+            ignore_case: false,
             ignore_groups: None,
             smart_filter: false,
             only_groups: None,
+            only_processes: None,
+            only_ports: None,
+            port_rules: Default::default(),
+            conditional_filters: Vec::new(),
             console: false,
             verbose: false,
             docker: false,
             show_pid: false,
             log_level: LogLevel::Info,
+            log_format: LogFormat::Text,
+            log_file: None,
+            log_max_size: 10,
             show_history: false,
             clear_history: false,
+            import_history: None,
+            history_action: None,
             show_filters: false,
             performance: false,
             show_context: false,
@@ -889,25 +1888,50 @@ mod tests {
             show_tree: false,
             json: false,
             reset: false,
+            deep: false,
             show_offenders: false,
             show_patterns: false,
             show_suggestions: false,
+            apply_suggestions: false,
+            ignore: None,
             show_stats: false,
+            history_since: None,
+            history_until: None,
+            history_port: None,
+            history_project: None,
+            history_group: None,
+            history_limit: None,
             show_root_cause: false,
+            apply: false,
             guard_mode: false,
             guard_ports: "3000,3001,3002,8000,8080,9000".to_string(),
             auto_resolve: false,
+            on_conflict: None,
             reservation_file: "~/.port-kill/reservations.json".to_string(),
             intercept_commands: false,
             reserve_port: None,
             project_name: None,
             process_name: None,
+            reserve: None,
+            expires_in: None,
+            no_renew: false,
+            unreserve: None,
+            reservations: false,
             audit: false,
             security_mode: false,
             suspicious_ports: "8444,4444,9999,14444,5555,6666,7777".to_string(),
             baseline_file: None,
             suspicious_only: false,
             remote: None,
+            linked: false,
+            nice: None,
+            profile: None,
+            show_audit: false,
+            audit_since: None,
+            audit_action: None,
+            audit_initiator: None,
+            audit_port: None,
+            audit_failed: false,
             monitor_endpoint: None,
             send_interval: 30,
             scan_interval: 2,
@@ -934,9 +1958,37 @@ mod tests {
             save_preset: None,
             preset_desc: None,
             delete_preset: None,
+            export_preset: None,
+            import_preset: None,
+            import_as: None,
+            overwrite: false,
+            show_preset: None,
+            edit_preset: None,
             check_updates: false,
+            no_update_check: false,
+            demo: false,
+            test_webhooks: false,
+            adopt: false,
+            restart_failed: false,
+            logs: None,
+            follow: false,
+            supervise: false,
+            supervise_max_restarts: 5,
+            undo_last_kill: false,
+            enable_autostart: false,
+            disable_autostart: false,
+            icon_theme: None,
+            max_rss: None,
+            offline: false,
+            trust_me: false,
+            safe_mode: false,
+            notify: false,
+            uninstall: false,
+            purge: false,
             self_update: false,
             cache: None,
+            complete_values: None,
+            schema: None,
             detect: false,
             start: None,
             guard_auto_restart: false,
@@ -946,6 +1998,40 @@ mod tests {
             status: false,
             config_file: ".port-kill.yaml".to_string(),
             init_config: false,
+            simulate: false,
+            wsl: false,
+            auto_kill_cooldown: 30,
+            max_auto_kills: 10,
+            auto_kill_window: 600,
+            user: None,
+            force: false,
+            sudo: false,
+            what_if: false,
+            report: None,
+            report_format: crate::digest::ReportFormat::Text,
+            at: None,
+            timeline: None,
+            hours: 24,
+            connections: false,
+            traffic: false,
+            traffic_window: 2,
+            no_kill_if_active: false,
+            only_active: false,
+            tui: false,
+            set_terminal_title: false,
+            status_page: None,
+            serve: None,
+            serve_token: None,
+            output: OutputFormat::Table,
+            once: false,
+            fail_if_occupied: None,
+            kill_gpu_hogs: false,
+            wait_free: None,
+            wait_open: None,
+            timeout: None,
+            kill_duplicates: false,
+            protect: None,
+            no_config: false,
         }
     }
 
@@ -985,6 +2071,22 @@ mod tests {
         assert_eq!(ports, vec![3000, 3001, 3002, 8000, 8080, 8081]);
     }
 
+    #[test]
+    fn test_get_ports_to_monitor_with_exclusions() {
+        let mut args = create_test_args();
+        args.ports = Some(vec!["300*".to_string(), "!3001-3008".to_string()]);
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 3009]);
+
+        // Exclusions alone apply to the port range
+        args.ports = Some(vec!["!3001".to_string()]);
+        args.start_port = 3000;
+        args.end_port = 3002;
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 3002]);
+
+        args.ports = Some(vec!["3000".to_string(), "!3000".to_string()]);
+        assert!(args.validate().is_err());
+    }
+
     #[test]
     fn test_get_ignore_ports_set() {
         let mut args = create_test_args();
@@ -1080,4 +2182,93 @@ mod tests {
 
         assert!(args.validate().is_err());
     }
+
+    #[test]
+    fn test_validation_simulate_requires_guard() {
+        let mut args = create_test_args();
+        args.simulate = true;
+        assert!(args.validate().is_err());
+
+        args.guard_mode = true;
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validation_what_if_requires_preset() {
+        let mut args = create_test_args();
+        args.what_if = true;
+        assert!(args.validate().is_err());
+
+        args.preset = Some("dev".to_string());
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_flags_fall_back_to_environment() {
+        // Setting variables here would race with tests running alongside, so this test
+        // runs itself again as a child process with them set
+        if std::env::var_os("PORT_KILL_TEST_CHILD").is_none() {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "cli::tests::test_flags_fall_back_to_environment"])
+                .env("PORT_KILL_TEST_CHILD", "1")
+                .env("PORT_KILL_IGNORE_PROCESSES", "Chrome,rapportd")
+                .env("PORT_KILL_NO_UPDATE_CHECK", "1")
+                .env("PORT_KILL_SCAN_INTERVAL", "7")
+                .env("PORT_KILL_SAFE", "false")
+                .env("PORT_KILL_API_TOKEN", "s3cret")
+                .env("PORT_KILL_CACHE_LANG", "rust")
+                .stdout(std::process::Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success());
+            return;
+        }
+
+        let matches = Args::command_with_env()
+            .try_get_matches_from(["port-kill", "--scan-interval", "5"])
+            .unwrap();
+        let args = Args::from_arg_matches(&matches).unwrap();
+        assert_eq!(
+            args.ignore_processes,
+            Some(vec!["Chrome".to_string(), "rapportd".to_string()])
+        );
+        assert!(args.no_update_check);
+        // The command line wins over the environment
+        assert_eq!(args.scan_interval, 5);
+        assert!(!args.safe);
+        // A flag's own variable is kept
+        assert_eq!(args.serve_token.as_deref(), Some("s3cret"));
+
+        let matches = Args::command_with_env()
+            .try_get_matches_from(["port-kill", "cache", "--list"])
+            .unwrap();
+        let args = Args::from_arg_matches(&matches).unwrap();
+        assert_eq!(
+            args.cache.as_ref().and_then(|c| c.args()).unwrap().lang,
+            "rust"
+        );
+    }
+
+    #[test]
+    fn test_safe_mode_protects_system_ports_and_processes() {
+        let mut args = create_test_args();
+        args.ignore_ports = Some(vec![3000]);
+        args.apply_safe_mode(Some(true));
+        assert!(args.safe_mode);
+        // System ports are still scanned, just not killed
+        let ignored = args.get_ignore_ports_set();
+        assert!(!ignored.contains(&22) && ignored.contains(&3000) && !ignored.contains(&5173));
+        assert!(crate::safe_mode::ensure_not_system_port(22, args.safe_mode).is_err());
+        assert!(crate::safe_mode::ensure_not_system_port(5173, args.safe_mode).is_ok());
+        assert!(args.get_protected_processes().contains(&"launchd".to_string()));
+        assert!(!args.needs_kill_confirmation(1));
+        assert!(args.needs_kill_confirmation(2));
+
+        let mut args = create_test_args();
+        args.trust_me = true;
+        args.apply_safe_mode(Some(true));
+        assert!(!args.safe_mode);
+        assert!(crate::safe_mode::ensure_not_system_port(22, args.safe_mode).is_ok());
+        assert!(!args.needs_kill_confirmation(5));
+    }
 }
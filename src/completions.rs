@@ -0,0 +1,235 @@
+//! `port-kill completions <shell>`: shell completion scripts generated with clap_complete.
+//!
+//! Flags whose values depend on the machine — preset names, detected and configured
+//! services, restartable ports — are completed at tab time: the bash, zsh and fish scripts
+//! call back into `port-kill --complete-values <kind>`. PowerShell and elvish get the values
+//! known when the script was generated.
+
+use crate::cli::Args;
+use anyhow::{Context, Result};
+use clap::builder::PossibleValuesParser;
+use clap_complete::Shell;
+use std::path::PathBuf;
+
+/// Values completed at tab time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CompletionValues {
+    /// Built-in, user and config-file presets
+    Presets,
+    /// Services found by --detect, for --start
+    Services,
+    /// Services from .port-kill.yaml / .portkill.toml, for --restart-service
+    ConfigServices,
+    /// Ports with saved restart info, for --restart / --clear-restart
+    RestartPorts,
+}
+
+impl CompletionValues {
+    fn id(self) -> &'static str {
+        match self {
+            CompletionValues::Presets => "presets",
+            CompletionValues::Services => "services",
+            CompletionValues::ConfigServices => "config-services",
+            CompletionValues::RestartPorts => "restart-ports",
+        }
+    }
+
+    /// Placeholder put in the generated script where the values are looked up
+    fn sentinel(self) -> String {
+        format!("__port_kill_complete_{}__", self.id().replace('-', "_"))
+    }
+}
+
+/// Flags (by clap id) whose values are completed dynamically
+const DYNAMIC_ARGS: [(&str, CompletionValues); 8] = [
+    ("preset", CompletionValues::Presets),
+    ("delete_preset", CompletionValues::Presets),
+    ("show_preset", CompletionValues::Presets),
+    ("edit_preset", CompletionValues::Presets),
+    ("start", CompletionValues::Services),
+    ("restart_service", CompletionValues::ConfigServices),
+    ("restart", CompletionValues::RestartPorts),
+    ("clear_restart", CompletionValues::RestartPorts),
+];
+
+/// Current candidates for `kind`, sorted and deduplicated. Lookups that fail yield nothing.
+pub fn values(kind: CompletionValues) -> Vec<String> {
+    let mut values: Vec<String> = match kind {
+        CompletionValues::Presets => {
+            let mut manager = crate::preset_manager::PresetManager::new();
+            let _ = manager.load_presets();
+            let mut names = manager.get_preset_names();
+            names.extend(crate::config::load_or_default().config.presets.into_keys());
+            names.push(crate::preset_manager::AUTO_PRESET.to_string());
+            names
+        }
+        CompletionValues::Services => crate::service_detector::ServiceDetector::new()
+            .discover_services()
+            .map(|services| services.into_iter().map(|s| s.name).collect())
+            .unwrap_or_default(),
+        CompletionValues::ConfigServices => {
+            let orchestrator = crate::orchestrator::Orchestrator::load_from_default().ok();
+            match orchestrator {
+                Some(o) => o.config().services.keys().cloned().collect(),
+                None => crate::config::load_or_default()
+                    .config
+                    .services
+                    .into_keys()
+                    .collect(),
+            }
+        }
+        CompletionValues::RestartPorts => crate::restart_manager::RestartManager::new()
+            .map(|m| {
+                m.list_restartable_ports()
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    values.sort();
+    values.dedup();
+    values
+}
+
+/// Shell code that prints the values of `kind` when run at completion time, for shells
+/// whose scripts can run commands
+fn lookup(shell: Shell, bin_name: &str, kind: CompletionValues) -> Option<String> {
+    let command = format!("{} --complete-values {} 2>/dev/null", bin_name, kind.id());
+    match shell {
+        Shell::Bash => Some(format!("$({})", command)),
+        Shell::Zsh => Some(format!("{{compadd -- $({})}}", command)),
+        // fish expands `-a` arguments when completing
+        Shell::Fish => Some(format!("({})", command)),
+        _ => None,
+    }
+}
+
+/// The completion script for `shell`
+pub fn generate(shell: Shell, bin_name: &str) -> String {
+    let mut command = Args::command_with_env().name(bin_name.to_string());
+    let mut replacements = Vec::new();
+    for (id, kind) in DYNAMIC_ARGS {
+        let candidates = match lookup(shell, bin_name, kind) {
+            Some(replacement) => {
+                replacements.push((kind.sentinel(), replacement));
+                vec![kind.sentinel()]
+            }
+            None => values(kind),
+        };
+        if !candidates.is_empty() {
+            command = command.mut_arg(id, |arg| {
+                arg.value_parser(PossibleValuesParser::new(candidates))
+            });
+        }
+    }
+
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, bin_name, &mut script);
+    let mut script = String::from_utf8_lossy(&script).to_string();
+
+    for (sentinel, replacement) in replacements {
+        // zsh lists possible values as `(a b c)`, an action that isn't evaluated
+        if shell == Shell::Zsh {
+            script = script.replace(&format!("({})", sentinel), &replacement);
+        }
+        script = script.replace(&sentinel, &replacement);
+    }
+    script
+}
+
+/// Where `--install` writes the script for `shell`, if it has a standard per-user location
+pub fn install_path(shell: Shell, bin_name: &str) -> Option<PathBuf> {
+    let home = crate::uninstall::home_dir();
+    crate::uninstall::completion_paths(&home)
+        .into_iter()
+        .find(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            match shell {
+                Shell::Bash => name == bin_name,
+                Shell::Zsh => name == format!("_{}", bin_name),
+                Shell::Fish => name == format!("{}.fish", bin_name),
+                _ => false,
+            }
+        })
+}
+
+/// Write the script to its standard location and return the path
+pub fn install(shell: Shell, bin_name: &str) -> Result<PathBuf> {
+    let path = install_path(shell, bin_name).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No standard completion directory for {}; redirect `{} completions {}` into your profile instead",
+            shell,
+            bin_name,
+            shell
+        )
+    })?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&path, generate(shell, bin_name))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Name of the running binary (`port-kill` or `port-kill-console`)
+pub fn bin_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "port-kill".to_string())
+}
+
+/// Print the script for `shell`, or install it with `install`
+pub fn run(shell: Shell, install: bool) -> Result<()> {
+    let bin_name = bin_name();
+    if !install {
+        print!("{}", generate(shell, &bin_name));
+        return Ok(());
+    }
+    let path = self::install(shell, &bin_name)?;
+    println!("✅ Installed {} completions: {}", shell, path.display());
+    if let Some(hint) = install_hint(shell) {
+        println!("   {}", hint);
+    }
+    Ok(())
+}
+
+/// Post-install instructions for shells that don't pick the file up on their own
+pub fn install_hint(shell: Shell) -> Option<&'static str> {
+    match shell {
+        Shell::Zsh => Some("Make sure ~/.zfunc is on your fpath: add `fpath=(~/.zfunc $fpath)` before `compinit` in ~/.zshrc"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_call_back_for_dynamic_values() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = generate(shell, "port-kill");
+            assert!(!script.contains("__port_kill_complete_"), "{}", shell);
+            assert!(
+                script.contains("port-kill --complete-values presets"),
+                "{}",
+                shell
+            );
+            assert!(script.contains("--complete-values restart-ports"), "{}", shell);
+        }
+        assert!(generate(Shell::Zsh, "port-kill")
+            .contains("{compadd -- $(port-kill --complete-values services 2>/dev/null)}"));
+    }
+
+    #[test]
+    fn test_install_paths() {
+        let bash = install_path(Shell::Bash, "port-kill").unwrap();
+        assert!(bash.ends_with(".local/share/bash-completion/completions/port-kill"));
+        let zsh = install_path(Shell::Zsh, "port-kill-console").unwrap();
+        assert!(zsh.ends_with(".zfunc/_port-kill-console"));
+        assert!(install_path(Shell::PowerShell, "port-kill").is_none());
+    }
+}
@@ -0,0 +1,167 @@
+//! docker-compose files, read with a YAML parser: each service with the host ports it
+//! publishes, the profiles it belongs to and what it `depends_on`. `--detect` lists the
+//! services from it and `--init-config` turns them into an orchestration config.
+
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+
+/// Compose file names, in the order docker compose looks for them
+pub const FILE_NAMES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yml",
+    "docker-compose.yaml",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComposeService {
+    pub name: String,
+    /// Published host ports, in the order declared. A bare container port is published on
+    /// a random host port and left out.
+    pub published_ports: Vec<u16>,
+    /// Only started when one of these profiles is enabled; empty means always
+    pub profiles: Vec<String>,
+    pub depends_on: Vec<String>,
+}
+
+/// Compose files in `dir`
+pub fn find(dir: &Path) -> Vec<PathBuf> {
+    FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+pub fn load(path: &Path) -> Result<Vec<ComposeService>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("Invalid compose file {}", path.display()))
+}
+
+/// Services in the order the file lists them
+pub fn parse(content: &str) -> Result<Vec<ComposeService>> {
+    let mut compose: Value = serde_yaml::from_str(content)?;
+    // `<<: *defaults` merges shared settings, ports included
+    compose.apply_merge()?;
+    let Some(services) = compose.get("services") else {
+        return Ok(Vec::new());
+    };
+    let services = services
+        .as_mapping()
+        .context("services must be a mapping of service names")?;
+
+    let mut parsed = Vec::new();
+    for (name, service) in services {
+        let name = name.as_str().context("service names must be strings")?.to_string();
+        let strings = |key: &str| -> Vec<String> {
+            service
+                .get(key)
+                .and_then(Value::as_sequence)
+                .map(|items| items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect())
+                .unwrap_or_default()
+        };
+        // depends_on is a list of names or a mapping of name -> condition
+        let depends_on = match service.get("depends_on") {
+            Some(Value::Mapping(map)) => map.keys().filter_map(|k| k.as_str().map(str::to_string)).collect(),
+            _ => strings("depends_on"),
+        };
+        let published_ports = service
+            .get("ports")
+            .and_then(Value::as_sequence)
+            .map(|ports| ports.iter().filter_map(published_port).collect())
+            .unwrap_or_default();
+        parsed.push(ComposeService {
+            name,
+            published_ports,
+            profiles: strings("profiles"),
+            depends_on,
+        });
+    }
+    Ok(parsed)
+}
+
+/// The host port of one `ports:` entry: `"8080:80"`, `"127.0.0.1:8080:80/tcp"`,
+/// `"${WEB_PORT:-8080}:80"`, `"8000-8010:8000-8010"` (its first port) or the long syntax's
+/// `published`
+fn published_port(entry: &Value) -> Option<u16> {
+    match entry {
+        Value::String(mapping) => {
+            let mapping = mapping.split('/').next()?;
+            let parts: Vec<&str> = mapping.rsplitn(2, ':').collect();
+            let host = parts.get(1)?;
+            // The host part may still carry an IP: 127.0.0.1:8080
+            let host = if host.starts_with("${") { host } else { host.rsplit(':').next()? };
+            expand_default(host)?.split('-').next()?.parse().ok()
+        }
+        Value::Mapping(_) => match entry.get("published")? {
+            Value::Number(n) => u16::try_from(n.as_u64()?).ok(),
+            Value::String(s) => expand_default(s)?.split('-').next()?.parse().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `${VAR:-8080}` / `${VAR-8080}` becomes VAR from the environment or 8080
+fn expand_default(value: &str) -> Option<String> {
+    let Some(inner) = value.strip_prefix("${").and_then(|v| v.strip_suffix('}')) else {
+        return Some(value.to_string());
+    };
+    let (name, default) = match inner.split_once(":-").or_else(|| inner.split_once('-')) {
+        Some((name, default)) => (name, Some(default)),
+        None => (inner, None),
+    };
+    std::env::var(name).ok().or_else(|| default.map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compose() {
+        let services = parse(
+            r#"
+version: "3.8"
+x-common: &common
+  restart: unless-stopped
+services:
+  web:
+    <<: *common
+    ports:
+      - "127.0.0.1:8080:80/tcp"
+      - 9229
+    depends_on:
+      db:
+        condition: service_healthy
+      cache:
+        condition: service_started
+  db:
+    ports:
+      - "${PORT_KILL_TEST_UNSET_DB_PORT:-5433}:5432"
+  cache:
+    ports:
+      - target: 6379
+        published: 6380
+  debug:
+    profiles: [debug]
+    depends_on: [web]
+volumes:
+  data:
+"#,
+        )
+        .unwrap();
+        let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["web", "db", "cache", "debug"]);
+        assert_eq!(services[0].published_ports, vec![8080]);
+        assert_eq!(services[0].depends_on, vec!["db", "cache"]);
+        assert_eq!(services[1].published_ports, vec![5433]);
+        assert_eq!(services[2].published_ports, vec![6380]);
+        assert_eq!(services[3].profiles, vec!["debug"]);
+        assert_eq!(services[3].depends_on, vec!["web"]);
+
+        assert!(parse("services: [web]").is_err());
+    }
+}
@@ -0,0 +1,559 @@
+//! Layered configuration. The global `~/.port-kill/config.toml` is overlaid by a
+//! per-repository `.portkill.toml` (or `.portkill.json`), found by walking up from the
+//! current directory, so a project can carry its own ports, ignores, presets, protected
+//! processes, guard profiles and services:
+//!
+//! ```toml
+//! ports = ["3000", "5173", "8000-8010"]
+//! ignore_processes = ["Code Helper"]
+//! ignore_regex = ["--inspect(=\\d+)?$"]   # matched against the full command line too
+//! protected = ["postgres"]            # never killed, even by --kill-all
+//! preset = "web"                      # applied when no ports are given on the command line
+//! docker = true                       # like --docker
+//! scan_interval = 5                   # seconds, unless --scan-interval is given
+//! on_conflict = "notify"              # the guard's policy, unless --on-conflict is given
+//!
+//! [presets.web]
+//! description = "Frontend + API"
+//! ports = [3000, 5173, 8080]
+//! extends = ["database"]              # adds the database preset's ports and ignores
+//!
+//! [port_rules."8080"]                # hide nginx on 8080 only
+//! ignore_processes = ["nginx"]
+//!
+//! [[conditional_filters]]            # hide chat apps during work hours
+//! hours = "09:00-18:00"
+//! ignore_processes = ["Slack*"]
+//!
+//! [services.api]
+//! command = "npm run dev"
+//! port = 3000
+//! ```
+//!
+//! Command-line flags win over both files; the project file wins over the global one.
+//! Lists (ignores, protected processes) are combined rather than replaced. A project file
+//! comes with whatever repository was cloned, so the settings that run commands, reach
+//! the network or turn safe mode off ([`GLOBAL_ONLY_KEYS`]) are only read from the global
+//! file.
+
+use crate::cache::rules::CacheRules;
+use crate::graceful_http::GracefulHttp;
+use crate::history_store::HistoryRetention;
+use crate::hooks::KillHook;
+use crate::notify::NotifySettings;
+use crate::orchestrator::ServiceConfig;
+use crate::port_spec::PortSpec;
+use crate::preset_manager::PortPreset;
+use crate::profiles::ConfigProfile;
+use crate::smart_filter::{ConditionalFilter, PortRule};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File names looked for in each directory, in order of preference
+pub const PROJECT_CONFIG_FILES: [&str; 2] = [".portkill.toml", ".portkill.json"];
+
+/// Keys only the global file may set: hooks and graceful HTTP rules run commands and send
+/// requests on every kill, webhooks post to any URL and `safe_mode` turns the kill
+/// confirmations off
+pub const GLOBAL_ONLY_KEYS: [&str; 4] = ["hooks", "graceful_http", "notifications", "safe_mode"];
+
+/// A preset defined in a config file; its name is the table key
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ConfigPreset {
+    pub description: Option<String>,
+    pub ports: Vec<PortSpec>,
+    pub ignore_ports: Option<Vec<u16>>,
+    pub ignore_processes: Option<Vec<String>>,
+    pub ignore_patterns: Option<Vec<String>>,
+    pub ignore_groups: Option<Vec<String>>,
+    pub only_groups: Option<Vec<String>>,
+    pub smart_filter: bool,
+    pub docker: bool,
+    pub show_pid: bool,
+    pub performance: bool,
+    pub show_context: bool,
+    /// Presets this one adds to, built-in, saved or from the config files
+    pub extends: Vec<String>,
+}
+
+impl ConfigPreset {
+    pub fn to_preset(&self, name: &str) -> PortPreset {
+        PortPreset {
+            name: name.to_string(),
+            description: self
+                .description
+                .clone()
+                .unwrap_or_else(|| "Preset from config file".to_string()),
+            ports: self.ports.clone(),
+            ignore_ports: self.ignore_ports.clone(),
+            ignore_processes: self.ignore_processes.clone(),
+            ignore_patterns: self.ignore_patterns.clone(),
+            ignore_groups: self.ignore_groups.clone(),
+            only_groups: self.only_groups.clone(),
+            smart_filter: self.smart_filter,
+            docker: self.docker,
+            show_pid: self.show_pid,
+            performance: self.performance,
+            show_context: self.show_context,
+            extends: self.extends.clone(),
+            provenance: None,
+            meta: Default::default(),
+        }
+    }
+}
+
+/// The settings one config file can hold
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ConfigLayer {
+    /// Ports and ranges to monitor, e.g. `"3000"` or `"8000-8010"`
+    pub ports: Option<Vec<String>>,
+    pub ignore_ports: Option<Vec<u16>>,
+    pub ignore_processes: Option<Vec<String>>,
+    pub ignore_patterns: Option<Vec<String>>,
+    /// Regular expressions, like `--ignore-regex`
+    pub ignore_regex: Option<Vec<String>>,
+    /// Like `--ignore-case`
+    pub ignore_case: Option<bool>,
+    pub ignore_groups: Option<Vec<String>>,
+    /// Process names that are shown but never killed
+    pub protected: Option<Vec<String>>,
+    /// Preset to apply when no ports are given on the command line
+    pub preset: Option<String>,
+    pub presets: HashMap<String, ConfigPreset>,
+    /// Named guard profiles for `--guard-mode --profile` (`[profiles.<name>]`)
+    pub profiles: HashMap<String, ConfigProfile>,
+    pub hooks: Vec<KillHook>,
+    /// Shutdown endpoints to try on HTTP listeners before they are signalled
+    pub graceful_http: Vec<GracefulHttp>,
+    /// Desktop notification and webhook settings (`[notifications]`)
+    pub notifications: Option<NotifySettings>,
+    /// Orchestration services (project file only), used by `--up`/`--down`/`--status`
+    /// when there is no `.port-kill.yaml`
+    pub services: HashMap<String, ServiceConfig>,
+    /// Environment variables for every service
+    pub env: Option<HashMap<String, String>>,
+    /// Force safe mode on or off; unset means on until `--trust-me`
+    pub safe_mode: Option<bool>,
+    /// Tray hotkeys, accelerator to action (`[hotkeys]`, see [`crate::hotkeys`])
+    pub hotkeys: Option<HashMap<String, String>>,
+    /// Tray icon theme (see [`crate::icon_theme`]); `--icon-theme` overrides it
+    pub icon_theme: Option<String>,
+    /// Include Docker containers, like `--docker`
+    pub docker: Option<bool>,
+    /// Seconds between scans when `--scan-interval` isn't given
+    pub scan_interval: Option<u64>,
+    /// What the guard does about conflicts on ports without a policy of their own, unless
+    /// `--on-conflict` is given
+    pub on_conflict: Option<crate::types::PortResolution>,
+    /// Caches `cache --list` / `--clean` skip (`[cache]`, see [`crate::cache::rules`])
+    pub cache: Option<CacheRules>,
+    /// Ignores and allowlists for some ports only, keyed by port spec (`[port_rules."8080"]`,
+    /// see [`crate::smart_filter::PortRule`])
+    pub port_rules: HashMap<String, PortRule>,
+    /// Filters that apply only at some times or in some situations (`[[conditional_filters]]`,
+    /// see [`crate::smart_filter::ConditionalFilter`])
+    pub conditional_filters: Vec<ConditionalFilter>,
+    /// How much kill history is kept (`[history]`, see [`crate::history_store`])
+    pub history: Option<HistoryRetention>,
+}
+
+fn merge_list<T: Clone + PartialEq>(
+    base: Option<Vec<T>>,
+    overlay: Option<Vec<T>>,
+) -> Option<Vec<T>> {
+    match (base, overlay) {
+        (Some(mut base), Some(overlay)) => {
+            for item in overlay {
+                if !base.contains(&item) {
+                    base.push(item);
+                }
+            }
+            Some(base)
+        }
+        (base, overlay) => base.or(overlay),
+    }
+}
+
+impl ConfigLayer {
+    /// Parse a `.toml` or `.json` config file, chosen by extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))
+        } else {
+            toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+        }
+    }
+
+    /// Parse a project file, dropping the [`GLOBAL_ONLY_KEYS`] with a warning for each
+    pub fn from_project_file(path: &Path) -> Result<Self> {
+        let mut layer = Self::from_file(path)?;
+        let set = [
+            !layer.hooks.is_empty(),
+            !layer.graceful_http.is_empty(),
+            layer.notifications.is_some(),
+            layer.safe_mode.is_some(),
+        ];
+        for (key, _) in GLOBAL_ONLY_KEYS.iter().zip(set).filter(|(_, set)| *set) {
+            log::warn!(
+                "Ignoring `{}` in {}: only {} may set it",
+                key,
+                path.display(),
+                global_config_path().display()
+            );
+        }
+        layer.hooks.clear();
+        layer.graceful_http.clear();
+        layer.notifications = None;
+        layer.safe_mode = None;
+        Ok(layer)
+    }
+
+    /// Overlay `overlay` (the more specific file) onto this layer
+    pub fn merge(self, overlay: ConfigLayer) -> ConfigLayer {
+        let mut presets = self.presets;
+        presets.extend(overlay.presets);
+        let mut profiles = self.profiles;
+        profiles.extend(overlay.profiles);
+        let mut port_rules = self.port_rules;
+        port_rules.extend(overlay.port_rules);
+        let mut conditional_filters = self.conditional_filters;
+        conditional_filters.extend(overlay.conditional_filters);
+        let mut hooks = overlay.hooks;
+        hooks.extend(self.hooks);
+        let mut graceful_http = overlay.graceful_http;
+        graceful_http.extend(self.graceful_http);
+
+        ConfigLayer {
+            ports: overlay.ports.or(self.ports),
+            ignore_ports: merge_list(self.ignore_ports, overlay.ignore_ports),
+            ignore_processes: merge_list(self.ignore_processes, overlay.ignore_processes),
+            ignore_patterns: merge_list(self.ignore_patterns, overlay.ignore_patterns),
+            ignore_regex: merge_list(self.ignore_regex, overlay.ignore_regex),
+            ignore_case: overlay.ignore_case.or(self.ignore_case),
+            ignore_groups: merge_list(self.ignore_groups, overlay.ignore_groups),
+            protected: merge_list(self.protected, overlay.protected),
+            preset: overlay.preset.or(self.preset),
+            presets,
+            profiles,
+            hooks,
+            graceful_http,
+            notifications: overlay.notifications.or(self.notifications),
+            // Services belong to the project: their dirs are relative to its config file
+            services: overlay.services,
+            env: overlay.env,
+            safe_mode: overlay.safe_mode.or(self.safe_mode),
+            hotkeys: match (self.hotkeys, overlay.hotkeys) {
+                (Some(mut base), Some(overlay)) => {
+                    base.extend(overlay);
+                    Some(base)
+                }
+                (base, overlay) => overlay.or(base),
+            },
+            icon_theme: overlay.icon_theme.or(self.icon_theme),
+            docker: overlay.docker.or(self.docker),
+            scan_interval: overlay.scan_interval.or(self.scan_interval),
+            on_conflict: overlay.on_conflict.or(self.on_conflict),
+            cache: match (self.cache, overlay.cache) {
+                (Some(base), Some(overlay)) => Some(base.merge(overlay)),
+                (base, overlay) => overlay.or(base),
+            },
+            port_rules,
+            conditional_filters,
+            history: match (self.history, overlay.history) {
+                (Some(base), Some(overlay)) => Some(base.merge(overlay)),
+                (base, overlay) => overlay.or(base),
+            },
+        }
+    }
+}
+
+/// The merged configuration together with the files it came from
+#[derive(Debug, Clone, Default)]
+pub struct LayeredConfig {
+    pub global_path: Option<PathBuf>,
+    pub project_path: Option<PathBuf>,
+    pub config: ConfigLayer,
+}
+
+pub fn global_config_path() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".port-kill").join("config.toml")
+}
+
+/// The nearest project config file in `start` or one of its parents
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find_map(|dir| {
+        PROJECT_CONFIG_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Load and merge the global and project config files. Missing files are fine.
+pub fn load() -> Result<LayeredConfig> {
+    let mut layered = LayeredConfig::default();
+
+    let global = global_config_path();
+    if global.is_file() {
+        layered.config = ConfigLayer::from_file(&global)?;
+        // Services belong to a project, so the global file can't define any
+        layered.config.services.clear();
+        layered.config.env = None;
+        layered.global_path = Some(global);
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    if let Some(project) = find_project_config(&cwd) {
+        let overlay = ConfigLayer::from_project_file(&project)?;
+        layered.config = std::mem::take(&mut layered.config).merge(overlay);
+        layered.project_path = Some(project);
+    }
+
+    Ok(layered)
+}
+
+/// Add `value` to the list `key` (e.g. `ignore_processes`) in the global config file,
+/// creating either if needed. The file is rewritten, so comments in it are lost.
+pub fn add_to_global_list(key: &str, value: &str) -> Result<PathBuf> {
+    let path = global_config_path();
+    add_to_list(&path, key, value)?;
+    Ok(path)
+}
+
+/// Change the top-level list `key` of the global config file with `update`, creating
+/// either if needed. Like [`add_to_global_list`], this drops comments in the file.
+pub fn update_global_list(
+    key: &str,
+    update: impl FnOnce(&mut Vec<toml::Value>),
+) -> Result<PathBuf> {
+    let path = global_config_path();
+    let mut table = read_table(&path)?;
+    let list = table
+        .entry(key.to_string())
+        .or_insert_with(|| toml::Value::Array(Vec::new()))
+        .as_array_mut()
+        .with_context(|| format!("`{}` in {} is not a list", key, path.display()))?;
+    update(list);
+    write_table(&path, &table)?;
+    Ok(path)
+}
+
+/// Set (or with `None`, remove) top-level keys of the global config file, creating it if
+/// needed. Like [`add_to_global_list`], this drops comments in the file.
+pub fn set_global_values(values: Vec<(&str, Option<toml::Value>)>) -> Result<PathBuf> {
+    let path = global_config_path();
+    let mut table = read_table(&path)?;
+    for (key, value) in values {
+        match value {
+            Some(value) => table.insert(key.to_string(), value),
+            None => table.remove(key),
+        };
+    }
+    write_table(&path, &table)?;
+    Ok(path)
+}
+
+/// Add `value` to the list `key` of the table `section` (e.g. `[cache]` `pinned`) in the
+/// global config file. Like [`add_to_global_list`], this drops comments in the file.
+pub fn add_to_global_section_list(section: &str, key: &str, value: &str) -> Result<PathBuf> {
+    update_global_section_list(section, key, |list| {
+        if !list.iter().any(|item| item.as_str() == Some(value)) {
+            list.push(toml::Value::String(value.to_string()));
+        }
+    })
+}
+
+/// Remove `value` from the list `key` of the table `section` in the global config file
+pub fn remove_from_global_section_list(section: &str, key: &str, value: &str) -> Result<PathBuf> {
+    update_global_section_list(section, key, |list| {
+        list.retain(|item| item.as_str() != Some(value))
+    })
+}
+
+fn update_global_section_list(
+    section: &str,
+    key: &str,
+    update: impl FnOnce(&mut Vec<toml::Value>),
+) -> Result<PathBuf> {
+    let path = global_config_path();
+    let mut table = read_table(&path)?;
+    let section_table = table
+        .entry(section.to_string())
+        .or_insert_with(|| toml::Value::Table(toml::value::Table::new()))
+        .as_table_mut()
+        .with_context(|| format!("`{}` in {} is not a table", section, path.display()))?;
+    let list = section_table
+        .entry(key.to_string())
+        .or_insert_with(|| toml::Value::Array(Vec::new()))
+        .as_array_mut()
+        .with_context(|| format!("`{}.{}` in {} is not a list", section, key, path.display()))?;
+    update(list);
+    write_table(&path, &table)?;
+    Ok(path)
+}
+
+fn read_table(path: &Path) -> Result<toml::value::Table> {
+    if !path.is_file() {
+        return Ok(toml::value::Table::new());
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn write_table(path: &Path, table: &toml::value::Table) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, toml::to_string(table)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn add_to_list(path: &Path, key: &str, value: &str) -> Result<()> {
+    let mut table = read_table(path)?;
+    let list = table
+        .entry(key.to_string())
+        .or_insert_with(|| toml::Value::Array(Vec::new()))
+        .as_array_mut()
+        .with_context(|| format!("`{}` in {} is not a list", key, path.display()))?;
+    if !list.iter().any(|item| item.as_str() == Some(value)) {
+        list.push(toml::Value::String(value.to_string()));
+    }
+    write_table(path, &table)
+}
+
+/// Like [`load`], but a broken file is logged and treated as missing, for callers that
+/// can't stop to report it (kill paths, background loops)
+pub fn load_or_default() -> LayeredConfig {
+    load().unwrap_or_else(|e| {
+        log::warn!("Ignoring config: {:#}", e);
+        LayeredConfig::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_config_discovery_and_merge() {
+        let root = std::env::temp_dir().join("port-kill-test-layered-config");
+        let _ = fs::remove_dir_all(&root);
+        let nested = root.join("packages").join("web");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            root.join(".portkill.json"),
+            r#"{"ports": ["3000", "5173"], "ignore_processes": ["node"], "protected": ["postgres"],
+                "presets": {"web": {"ports": [3000]}},
+                "services": {"api": {"command": "npm run dev", "port": 3000}}}"#,
+        )
+        .unwrap();
+
+        let found = find_project_config(&nested).unwrap();
+        assert_eq!(found, root.join(".portkill.json"));
+        let project = ConfigLayer::from_file(&found).unwrap();
+
+        let global: ConfigLayer = toml::from_str(
+            r#"
+            ports = ["8080"]
+            ignore_processes = ["Code Helper", "node"]
+            preset = "dev"
+            "#,
+        )
+        .unwrap();
+        let merged = global.merge(project);
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(
+            merged.ports,
+            Some(vec!["3000".to_string(), "5173".to_string()])
+        );
+        assert_eq!(
+            merged.ignore_processes,
+            Some(vec!["Code Helper".to_string(), "node".to_string()])
+        );
+        assert_eq!(merged.protected, Some(vec!["postgres".to_string()]));
+        assert_eq!(merged.preset.as_deref(), Some("dev"));
+        assert_eq!(
+            merged.presets["web"].to_preset("web").port_numbers(),
+            vec![3000]
+        );
+        assert_eq!(merged.services["api"].port, Some(3000));
+    }
+
+    #[test]
+    fn test_add_to_list_keeps_other_settings() {
+        let path = std::env::temp_dir().join("port-kill-test-add-to-list.toml");
+        fs::write(
+            &path,
+            "ignore_processes = [\"Code Helper\"]\n\n[presets.web]\nports = [3000]\n",
+        )
+        .unwrap();
+        add_to_list(&path, "ignore_processes", "rapportd").unwrap();
+        add_to_list(&path, "ignore_processes", "rapportd").unwrap();
+        let layer = ConfigLayer::from_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            layer.ignore_processes,
+            Some(vec!["Code Helper".to_string(), "rapportd".to_string()])
+        );
+        assert_eq!(layer.presets["web"].ports, vec![PortSpec::Port(3000)]);
+    }
+
+    #[test]
+    fn test_project_file_cannot_set_global_only_keys() {
+        let path = std::env::temp_dir().join(format!(
+            "port-kill-test-project-{}.portkill.toml",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            r#"
+            ports = ["3457"]
+            safe_mode = false
+
+            [[hooks]]
+            pre_kill = "touch PWNED"
+
+            [[graceful_http]]
+            ports = [3000]
+
+            [notifications]
+            enabled = true
+
+            [[notifications.webhooks]]
+            url = "https://example.com/hook"
+            "#,
+        )
+        .unwrap();
+        let everything = ConfigLayer::from_file(&path).unwrap();
+        let project = ConfigLayer::from_project_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        // The file itself parses with all of them
+        assert_eq!(everything.hooks.len(), 1);
+        assert_eq!(everything.safe_mode, Some(false));
+
+        assert_eq!(project.ports, Some(vec!["3457".to_string()]));
+        assert!(project.hooks.is_empty());
+        assert!(project.graceful_http.is_empty());
+        assert!(project.notifications.is_none());
+        assert!(project.safe_mode.is_none());
+
+        // ... and merging keeps the global file's settings
+        let global: ConfigLayer = toml::from_str("safe_mode = true").unwrap();
+        assert_eq!(global.merge(project).safe_mode, Some(true));
+    }
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::process::Command;
+
+/// ESTABLISHED connections accepted on one monitored port
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PortConnections {
+    pub port: u16,
+    pub count: usize,
+    /// Remote addresses, deduplicated and sorted
+    pub peers: Vec<String>,
+}
+
+impl PortConnections {
+    pub fn is_active(&self) -> bool {
+        self.count > 0
+    }
+
+    /// "3 connections from 127.0.0.1:54321, 10.0.0.2:40110"
+    pub fn summary(&self) -> String {
+        if self.count == 0 {
+            return "no active connections".to_string();
+        }
+        let noun = if self.count == 1 {
+            "connection"
+        } else {
+            "connections"
+        };
+        const MAX_PEERS: usize = 5;
+        let mut peers = self
+            .peers
+            .iter()
+            .take(MAX_PEERS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.peers.len() > MAX_PEERS {
+            peers.push_str(&format!(" (+{} more)", self.peers.len() - MAX_PEERS));
+        }
+        format!("{} {} from {}", self.count, noun, peers)
+    }
+}
+
+/// Split "host:port" (IPv4, bracketed IPv6 or bare IPv6) into its port
+fn port_of(address: &str) -> Option<u16> {
+    address.rsplit(':').next()?.parse().ok()
+}
+
+fn record(
+    connections: &mut BTreeMap<u16, PortConnections>,
+    ports: &HashSet<u16>,
+    local: &str,
+    peer: &str,
+) {
+    let port = match port_of(local) {
+        Some(p) if ports.contains(&p) => p,
+        _ => return,
+    };
+    let entry = connections.entry(port).or_insert_with(|| PortConnections {
+        port,
+        ..Default::default()
+    });
+    entry.count += 1;
+    if !entry.peers.iter().any(|p| p == peer) {
+        entry.peers.push(peer.to_string());
+        entry.peers.sort();
+    }
+}
+
+/// Parse `lsof -nP -iTCP -sTCP:ESTABLISHED` output, keeping connections whose local side is a monitored port
+pub fn parse_lsof_established(stdout: &str, ports: &HashSet<u16>) -> BTreeMap<u16, PortConnections> {
+    let mut connections = BTreeMap::new();
+    for line in stdout.lines().skip(1) {
+        // node 1234 user 22u IPv4 0x1234 0t0 TCP 127.0.0.1:3000->127.0.0.1:54321 (ESTABLISHED)
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 9 {
+            continue;
+        }
+        if let Some((local, peer)) = parts[8].split_once("->") {
+            record(&mut connections, ports, local, peer);
+        }
+    }
+    connections
+}
+
+/// Parse `netstat -ano -p TCP` output, keeping ESTABLISHED connections on monitored ports
+pub fn parse_netstat_established(
+    stdout: &str,
+    ports: &HashSet<u16>,
+) -> BTreeMap<u16, PortConnections> {
+    let mut connections = BTreeMap::new();
+    for line in stdout.lines() {
+        // TCP 127.0.0.1:3000 127.0.0.1:54321 ESTABLISHED 1234
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 || parts[0] != "TCP" || parts[3] != "ESTABLISHED" {
+            continue;
+        }
+        record(&mut connections, ports, parts[1], parts[2]);
+    }
+    connections
+}
+
+/// ESTABLISHED connections for each monitored port that has at least one
+pub fn get_established_connections(ports: &[u16]) -> BTreeMap<u16, PortConnections> {
+    let ports: HashSet<u16> = ports.iter().copied().collect();
+    if ports.is_empty() {
+        return BTreeMap::new();
+    }
+
+    let output = if cfg!(target_os = "windows") {
+        Command::new("netstat").args(["-ano", "-p", "TCP"]).output()
+    } else {
+        Command::new("lsof")
+            .args(["-nP", "-iTCP", "-sTCP:ESTABLISHED"])
+            .output()
+    };
+
+    match output {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if cfg!(target_os = "windows") {
+                parse_netstat_established(&stdout, &ports)
+            } else {
+                parse_lsof_established(&stdout, &ports)
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to list established connections: {}", e);
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Active connections on `port`, if any (used by --no-kill-if-active)
+pub fn active_connections_on(port: u16) -> Option<PortConnections> {
+    get_established_connections(&[port])
+        .remove(&port)
+        .filter(|c| c.is_active())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lsof_established() {
+        let stdout = "COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   22u  IPv4 0x1234      0t0  TCP 127.0.0.1:3000->127.0.0.1:54321 (ESTABLISHED)
+node     1234 user   23u  IPv4 0x1235      0t0  TCP 127.0.0.1:3000->127.0.0.1:54322 (ESTABLISHED)
+curl     5678 user   5u   IPv4 0x1236      0t0  TCP 127.0.0.1:54321->127.0.0.1:3000 (ESTABLISHED)
+python   9999 user   7u   IPv6 0x1237      0t0  TCP [::1]:8000->[::1]:60000 (ESTABLISHED)";
+        let ports: HashSet<u16> = [3000, 8000].into_iter().collect();
+        let connections = parse_lsof_established(stdout, &ports);
+
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[&3000].count, 2);
+        assert_eq!(
+            connections[&3000].peers,
+            vec!["127.0.0.1:54321".to_string(), "127.0.0.1:54322".to_string()]
+        );
+        assert_eq!(connections[&8000].peers, vec!["[::1]:60000".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_netstat_established() {
+        let stdout = "
+  Proto  Local Address          Foreign Address        State           PID
+  TCP    0.0.0.0:3000           0.0.0.0:0              LISTENING       1234
+  TCP    127.0.0.1:3000         127.0.0.1:50000        ESTABLISHED     1234
+  TCP    127.0.0.1:50000        127.0.0.1:3000         ESTABLISHED     5678
+  TCP    127.0.0.1:3000         127.0.0.1:50001        TIME_WAIT       0";
+        let ports: HashSet<u16> = [3000].into_iter().collect();
+        let connections = parse_netstat_established(stdout, &ports);
+
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[&3000].count, 1);
+        assert_eq!(connections[&3000].summary(), "1 connection from 127.0.0.1:50000");
+    }
+}
@@ -1,15 +1,25 @@
 use crate::{
-    cli::Args,
+    cache::{
+        list::{list_caches, CacheQuery},
+        types::ListSummary,
+    },
+    cli::{Args, OutputFormat},
     endpoint_monitor::EndpointMonitor,
+    exit_code::{self, exit_on_kill_failure},
+    hooks::KillOutcome,
     port_guard::PortGuardDaemon,
     process_monitor::ProcessMonitor,
+    rate_limiter::KillRateLimiter,
     security_audit::SecurityAuditor,
     smart_filter::SmartFilter,
-    types::{GuardStatus, ProcessUpdate, SecurityAuditResult, StatusBarInfo},
+    status_page::StatusPage,
+    types::{
+        format_uptime, GuardStatus, ProcessUpdate, ScanSnapshot, SecurityAuditResult, StatusBarInfo,
+    },
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossbeam_channel::{bounded, Receiver};
-use log::{error, info};
+use log::{error, info, warn};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -19,6 +29,7 @@ pub struct ConsolePortKillApp {
     update_receiver: Receiver<ProcessUpdate>,
     args: Args,
     port_guard: Option<Arc<PortGuardDaemon>>,
+    cache_summary: Option<ListSummary>,
 }
 
 impl ConsolePortKillApp {
@@ -40,29 +51,26 @@ impl ConsolePortKillApp {
         let smart_filter = Self::create_smart_filter(&args)?;
 
         // Create process monitor with configurable ports
-        println!(
-            "DEBUG: Creating ProcessMonitor with verbose={}, performance={}",
+        log::debug!(
+            "Creating ProcessMonitor with verbose={}, performance={}",
             args.verbose, args.performance
         );
-        let process_monitor = if let Some(filter) = smart_filter {
-            Arc::new(Mutex::new(ProcessMonitor::new_with_performance(
-                update_sender,
-                args.get_ports_to_monitor(),
-                args.docker,
-                args.verbose,
-                Some(filter),
-                args.performance,
-            )?))
-        } else {
-            Arc::new(Mutex::new(ProcessMonitor::new_with_performance(
-                update_sender,
-                args.get_ports_to_monitor(),
-                args.docker,
-                args.verbose,
-                None,
-                args.performance,
-            )?))
-        };
+        let mut monitor = ProcessMonitor::new_with_performance(
+            update_sender,
+            args.get_ports_to_monitor(),
+            args.docker,
+            args.verbose,
+            smart_filter,
+            args.performance,
+        )?;
+        monitor.set_user_filter(args.user.clone());
+        monitor.set_allow_other_users(args.force);
+        monitor.set_elevate(args.sudo);
+        monitor.set_no_kill_if_active(args.no_kill_if_active);
+        monitor.set_only_active(args.only_active_window());
+        monitor.set_protected(args.get_protected_processes());
+        monitor.set_safe_mode(args.safe_mode);
+        let process_monitor = Arc::new(Mutex::new(monitor));
 
         // Initialize Port Guard if enabled
         let port_guard = if args.guard_mode {
@@ -76,6 +84,12 @@ impl ConsolePortKillApp {
             );
             daemon.set_process_interception(args.intercept_commands);
             daemon.set_auto_restart(args.guard_auto_restart);
+            if let Some(policy) = args.on_conflict {
+                daemon.set_default_policy(policy);
+            }
+            daemon.set_simulate(args.simulate);
+            daemon.set_kill_limiter(KillRateLimiter::from_args(&args));
+            daemon.set_no_kill_if_active(args.no_kill_if_active);
             Some(Arc::new(daemon))
         } else {
             None
@@ -86,6 +100,7 @@ impl ConsolePortKillApp {
             update_receiver,
             args,
             port_guard,
+            cache_summary: None,
         })
     }
 
@@ -99,25 +114,23 @@ impl ConsolePortKillApp {
         let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
         let smart_filter = Self::create_smart_filter(&self.args)?;
 
-        if let Some(filter) = smart_filter {
-            ProcessMonitor::new_with_performance(
-                update_sender,
-                ports_to_scan,
-                self.args.docker,
-                self.args.verbose,
-                Some(filter),
-                self.args.performance,
-            )
-        } else {
-            ProcessMonitor::new_with_performance(
-                update_sender,
-                ports_to_scan,
-                self.args.docker,
-                self.args.verbose,
-                None,
-                self.args.performance,
-            )
-        }
+        let mut monitor = ProcessMonitor::new_with_performance(
+            update_sender,
+            ports_to_scan,
+            self.args.docker,
+            self.args.verbose,
+            smart_filter,
+            self.args.performance,
+        )?;
+        monitor.set_user_filter(self.args.user.clone());
+        monitor.set_allow_other_users(self.args.force);
+        monitor.set_elevate(self.args.sudo);
+        monitor.set_confirm_on_terminal(true);
+        monitor.set_no_kill_if_active(self.args.no_kill_if_active);
+        monitor.set_only_active(self.args.only_active_window());
+        monitor.set_protected(self.args.get_protected_processes());
+        monitor.set_safe_mode(self.args.safe_mode);
+        Ok(monitor)
     }
 
     fn create_smart_filter(args: &Args) -> Result<Option<SmartFilter>> {
@@ -139,8 +152,13 @@ impl ConsolePortKillApp {
         if ignore_ports.is_empty()
             && ignore_processes.is_empty()
             && args.ignore_patterns.is_none()
+            && args.ignore_regex.is_none()
             && ignore_groups.is_empty()
             && args.only_groups.is_none()
+            && args.only_processes.is_none()
+            && args.only_ports.is_none()
+            && args.port_rules.is_empty()
+            && args.conditional_filters.is_empty()
         {
             return Ok(None);
         }
@@ -151,11 +169,217 @@ impl ConsolePortKillApp {
             args.ignore_patterns.clone(),
             ignore_groups,
             args.get_only_groups_set(),
-        )?;
+        )?
+        .with_ignore_case(args.ignore_case)?
+        .with_ignore_regex(args.ignore_regex.clone())?
+        .with_allowlist(args.only_processes.clone(), args.get_only_ports_set())?
+        .with_port_rules(&args.port_rules)?
+        .with_conditional_filters(&args.conditional_filters, Args::smart_filter_defaults())?;
 
         Ok(Some(filter))
     }
 
+    /// Listeners on the monitored ports with the clients connected to them (--connections)
+    pub async fn show_connections(&self) -> Result<()> {
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        let processes = temp_monitor.scan_processes().await?;
+
+        let mut listening: Vec<u16> = processes.keys().copied().collect();
+        listening.sort();
+        let connections = crate::connections::get_established_connections(&listening);
+
+        if self.args.json {
+            for port in &listening {
+                let process_info = &processes[port];
+                let json = serde_json::json!({
+                    "port": port,
+                    "pid": process_info.pid,
+                    "name": process_info.name,
+                    "connections": connections.get(port).cloned().unwrap_or_default(),
+                });
+                println!("{}", json);
+            }
+            return Ok(());
+        }
+
+        if listening.is_empty() {
+            Self::print_no_processes();
+            return Ok(());
+        }
+
+        println!("🔗 Connections on monitored ports:");
+        for port in &listening {
+            let process_info = &processes[port];
+            let activity = match connections.get(port) {
+                Some(active) => active.summary(),
+                None => "idle".to_string(),
+            };
+            println!(
+                "  • Port {}: {} (PID {}) — {}",
+                port,
+                process_info.get_display_name(),
+                process_info.pid,
+                activity
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Listeners on the monitored ports with their throughput and new-connection rate over
+    /// a `--traffic-window` sample (--traffic)
+    pub async fn show_traffic(&self) -> Result<()> {
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        let processes = temp_monitor.scan_processes().await?;
+
+        let mut listening: Vec<u16> = processes.keys().copied().collect();
+        listening.sort();
+        if listening.is_empty() {
+            if !self.args.json {
+                Self::print_no_processes();
+            }
+            return Ok(());
+        }
+        let window = std::time::Duration::from_secs(self.args.traffic_window.max(1));
+        if !self.args.json {
+            println!("⏱️  Sampling traffic for {}s...", window.as_secs());
+        }
+        let traffic = crate::traffic::measure(&listening, window);
+
+        if self.args.json {
+            for port in &listening {
+                let process_info = &processes[port];
+                let json = serde_json::json!({
+                    "port": port,
+                    "pid": process_info.pid,
+                    "name": process_info.name,
+                    "traffic": traffic.get(port).cloned().unwrap_or_default(),
+                });
+                println!("{}", json);
+            }
+            return Ok(());
+        }
+
+        println!("📶 Traffic on monitored ports:");
+        // Busiest first
+        let mut rows: Vec<_> = listening
+            .iter()
+            .map(|port| (&processes[port], traffic.get(port).cloned().unwrap_or_default()))
+            .collect();
+        rows.sort_by(|(_, a), (_, b)| {
+            let load = |t: &crate::traffic::PortTraffic| {
+                t.bytes_in_per_sec.unwrap_or(0.0) + t.bytes_out_per_sec.unwrap_or(0.0)
+            };
+            load(b)
+                .total_cmp(&load(a))
+                .then(b.new_connections_per_sec.total_cmp(&a.new_connections_per_sec))
+        });
+        for (process_info, port_traffic) in &rows {
+            let marker = if port_traffic.is_active() { "🟢" } else { "⚪" };
+            println!(
+                "  {} Port {}: {} (PID {}) — {}",
+                marker,
+                process_info.port,
+                process_info.get_display_name(),
+                process_info.pid,
+                port_traffic.summary()
+            );
+        }
+        Ok(())
+    }
+
+    /// Scan once without any filters, then sort every listener into what the active
+    /// preset would show and let you kill, show but refuse to kill, or hide (--what-if)
+    pub async fn preset_what_if(&self) -> Result<()> {
+        let (update_sender, _update_receiver) = bounded(100);
+        let mut monitor = ProcessMonitor::new_with_performance(
+            update_sender,
+            self.args.get_ports_to_monitor(),
+            self.args.docker,
+            false,
+            None,
+            false,
+        )?;
+        let mut processes: Vec<_> = monitor.scan_processes().await?.into_values().collect();
+        processes.sort_by_key(|p| (p.port, p.pid));
+
+        let filter = Self::create_smart_filter(&self.args)?;
+        let allow_other_users = self.args.force;
+
+        let mut killable = Vec::new();
+        let mut protected = Vec::new();
+        let mut ignored = Vec::new();
+
+        let process_count = processes.len();
+        for process in processes {
+            let reason = filter
+                .as_ref()
+                .and_then(|f| f.ignore_reason_in_scan(&process, process_count))
+                .or_else(|| match (&self.args.user, &process.user) {
+                    (Some(user), Some(owner)) if !process.is_owned_by(user) => {
+                        Some(format!("owned by '{}', not --user {}", owner, user))
+                    }
+                    _ => None,
+                });
+            if let Some(reason) = reason {
+                ignored.push((process, reason));
+                continue;
+            }
+
+            match crate::process_monitor::ensure_kill_allowed(
+                process.pid,
+                process.user.as_deref(),
+                allow_other_users,
+            )
+            .and_then(|()| {
+                crate::process_monitor::ensure_not_protected(
+                    process.pid,
+                    Some(&process.name),
+                    &self.args.get_protected_processes(),
+                )
+            })
+            .and_then(|()| {
+                crate::safe_mode::ensure_not_system_port(process.port, self.args.safe_mode)
+            }) {
+                Ok(()) => killable.push(process),
+                Err(e) => protected.push((process, e.to_string())),
+            }
+        }
+
+        let describe = |p: &crate::types::ProcessInfo| {
+            let owner = p
+                .describe_owner()
+                .map(|u| format!(", user {}", u))
+                .unwrap_or_default();
+            format!("Port {}: {} (PID {}{})", p.port, p.get_display_name(), p.pid, owner)
+        };
+
+        println!(
+            "🧪 What-if for preset '{}' on {}",
+            self.args.preset.as_deref().unwrap_or("none"),
+            self.args.get_port_description()
+        );
+
+        println!("\n✅ Visible and killable ({}):", killable.len());
+        for p in &killable {
+            println!("  • {}", describe(p));
+        }
+
+        println!("\n🔒 Visible but not killable ({}):", protected.len());
+        for (p, reason) in &protected {
+            println!("  • {} — {}", describe(p), reason);
+        }
+
+        println!("\n🙈 Ignored ({}):", ignored.len());
+        for (p, reason) in &ignored {
+            println!("  • {} — {}", describe(p), reason);
+        }
+
+        Ok(())
+    }
+
     pub async fn run(mut self) -> Result<()> {
         info!("Starting Console Port Kill application...");
 
@@ -164,74 +388,111 @@ impl ConsolePortKillApp {
             let ports_to_scan = Self::get_ports_to_scan(&self.args);
             let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
             let processes = temp_monitor.scan_processes().await?;
+            if self.args.output_format() != OutputFormat::Table {
+                return Self::print_scan_snapshot(&processes, self.args.output_format(), true);
+            }
             if processes.is_empty() {
-                println!("ℹ️  No processes detected");
+                Self::print_no_processes();
             } else {
                 println!("📋 Ports in use (one-time snapshot):");
+                let jupyter_servers = crate::jupyter::list_servers();
                 for (port, p) in &processes {
+                    let owner = p
+                        .describe_owner()
+                        .map(|u| format!(", user {}", u))
+                        .unwrap_or_default();
                     println!(
-                        "  • Port {}: {} (PID {})",
+                        "  • Port {}: {} (PID {}{})",
                         port,
                         p.get_display_name(),
-                        p.pid
+                        p.pid,
+                        owner
                     );
+                    if let Some(server) = jupyter_servers
+                        .iter()
+                        .find(|s| s.port == *port && s.pid == p.pid)
+                    {
+                        println!("    🔗 {}", server.browser_url());
+                    }
                 }
+                Self::print_duplicate_warnings(&processes);
             }
             return Ok(());
         }
 
+        // One-shot: listeners with their ESTABLISHED connections
+        if self.args.connections {
+            return self.show_connections().await;
+        }
+
+        // One-shot: throughput and connection rate per listener
+        if self.args.traffic {
+            return self.show_traffic().await;
+        }
+
+        // One-shot: preview the active preset against the live system
+        if self.args.what_if {
+            return self.preset_what_if().await;
+        }
+
+        // One-shot: write the status page and exit (with --console it is rewritten on every scan instead)
+        if let Some(path) = self.args.status_page.clone() {
+            if !self.args.console {
+                let ports_to_scan = Self::get_ports_to_scan(&self.args);
+                let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+                let processes = temp_monitor.scan_processes().await?;
+                let processes = self.filter_ignored_processes(&processes);
+                self.write_status_page(&path, &processes, None).await?;
+                println!("📄 Status page written to {}", path);
+                return Ok(());
+            }
+        }
+
         // One-shot: clear specific port(s) provided as positional ports
         if !self.args.positional_ports.is_empty() {
             use crate::process_monitor::kill_all_processes as kill_on_ports;
-            if self.args.safe {
-                let ports_str = self
-                    .args
-                    .positional_ports
-                    .iter()
-                    .map(|p| p.to_string())
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                println!("Confirm kill on ports [{}]? y/N", ports_str);
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-                if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
-                    println!("Cancelled.");
-                    return Ok(());
-                }
+            let ports_str = self
+                .args
+                .positional_ports
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let prompt = format!("Confirm kill on ports [{}]?", ports_str);
+            if !self.confirm_kill(self.args.positional_ports.len(), &prompt)? {
+                return Ok(());
             }
-            kill_on_ports(&self.args.positional_ports, &self.args)?;
-            return Ok(());
+            let tunnels = self.linked_tunnels(&self.args.positional_ports);
+            exit_on_kill_failure(kill_on_ports(&self.args.positional_ports, &self.args))?;
+            self.stop_linked_endpoints(&tunnels);
+            return self.wait_for_ports().await;
         }
 
         // One-shot: --clear
         if let Some(port) = self.args.clear {
-            if self.args.safe {
-                println!("Confirm kill on port {}? y/N", port);
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-                if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
-                    println!("Cancelled.");
-                    return Ok(());
-                }
+            if !self.confirm_kill(1, &format!("Confirm kill on port {}?", port))? {
+                return Ok(());
             }
             use crate::process_monitor::kill_all_processes as kill_on_ports;
-            kill_on_ports(&[port], &self.args)?;
-            return Ok(());
+            let tunnels = self.linked_tunnels(&[port]);
+            exit_on_kill_failure(kill_on_ports(&[port], &self.args))?;
+            self.stop_linked_endpoints(&tunnels);
+            return self.wait_for_ports().await;
         }
 
         // One-shot: --kill (by PID)
         if let Some(pid) = self.args.kill {
-            if self.args.safe {
-                println!("Confirm kill PID {}? y/N", pid);
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-                if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
-                    println!("Cancelled.");
-                    return Ok(());
-                }
+            if !self.confirm_kill(1, &format!("Confirm kill PID {}?", pid))? {
+                return Ok(());
             }
             use crate::process_monitor::kill_single_process;
-            kill_single_process(pid, &self.args)?;
+            let tunnels = if self.args.linked {
+                crate::tunnel::tunnels_of_pid(pid)
+            } else {
+                Vec::new()
+            };
+            exit_on_kill_failure(kill_single_process(pid, &self.args))?;
+            self.stop_linked_endpoints(&tunnels);
             return Ok(());
         }
 
@@ -243,18 +504,9 @@ impl ConsolePortKillApp {
                 println!("ℹ️  No processes found holding {}", path);
                 return Ok(());
             }
-            if self.args.safe {
-                println!(
-                    "Confirm kill {} process(es) holding {}? y/N",
-                    procs.len(),
-                    path
-                );
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-                if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
-                    println!("Cancelled.");
-                    return Ok(());
-                }
+            let prompt = format!("Confirm kill {} process(es) holding {}?", procs.len(), path);
+            if !self.confirm_kill(procs.len(), &prompt)? {
+                return Ok(());
             }
             // Kill each PID
             let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
@@ -266,8 +518,17 @@ impl ConsolePortKillApp {
                 None,
                 self.args.performance,
             )?;
+            temp_monitor.set_allow_other_users(self.args.force);
+            temp_monitor.set_elevate(self.args.sudo);
+            temp_monitor.set_confirm_on_terminal(true);
+            temp_monitor.set_no_kill_if_active(self.args.no_kill_if_active);
+            temp_monitor.set_only_active(self.args.only_active_window());
+            temp_monitor.set_protected(self.args.get_protected_processes());
+            temp_monitor.set_safe_mode(self.args.safe_mode);
             for p in procs {
-                let _ = temp_monitor.kill_process(p.pid).await;
+                if let Err(e) = temp_monitor.kill_process(p.pid).await {
+                    println!("❌ Failed to kill {} (PID {}): {}", p.name, p.pid, e);
+                }
             }
             return Ok(());
         }
@@ -279,18 +540,9 @@ impl ConsolePortKillApp {
                 println!("ℹ️  No processes found holding files with '{}'", ext);
                 return Ok(());
             }
-            if self.args.safe {
-                println!(
-                    "Confirm kill {} process(es) with files '{}'? y/N",
-                    procs.len(),
-                    ext
-                );
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-                if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
-                    println!("Cancelled.");
-                    return Ok(());
-                }
+            let prompt = format!("Confirm kill {} process(es) with files '{}'?", procs.len(), ext);
+            if !self.confirm_kill(procs.len(), &prompt)? {
+                return Ok(());
             }
             let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
             let mut temp_monitor = ProcessMonitor::new_with_performance(
@@ -301,8 +553,17 @@ impl ConsolePortKillApp {
                 None,
                 self.args.performance,
             )?;
+            temp_monitor.set_allow_other_users(self.args.force);
+            temp_monitor.set_elevate(self.args.sudo);
+            temp_monitor.set_confirm_on_terminal(true);
+            temp_monitor.set_no_kill_if_active(self.args.no_kill_if_active);
+            temp_monitor.set_only_active(self.args.only_active_window());
+            temp_monitor.set_protected(self.args.get_protected_processes());
+            temp_monitor.set_safe_mode(self.args.safe_mode);
             for p in procs {
-                let _ = temp_monitor.kill_process(p.pid).await;
+                if let Err(e) = temp_monitor.kill_process(p.pid).await {
+                    println!("❌ Failed to kill {} (PID {}): {}", p.name, p.pid, e);
+                }
             }
             return Ok(());
         }
@@ -335,16 +596,43 @@ impl ConsolePortKillApp {
             if let Some(name) = &self.args.allow {
                 daemon.set_allowed_process_name(name.clone());
             }
+            if let Some(policy) = self.args.on_conflict {
+                daemon.set_default_policy(policy);
+            }
             daemon.set_process_interception(self.args.intercept_commands);
+            daemon.set_simulate(self.args.simulate);
+            daemon.set_kill_limiter(KillRateLimiter::from_args(&self.args));
+            daemon.set_no_kill_if_active(self.args.no_kill_if_active);
             let guard = Arc::new(daemon);
             self.port_guard = Some(guard.clone());
             println!("🛡️  Guarding port {}. Press Ctrl+C to stop.", port);
+            if self.args.simulate {
+                println!("🧪 Simulation mode: conflicts will be logged, nothing will be killed");
+            }
             // Fall through to normal loop so the app stays running; guard is started in background below
         }
 
+        // One-shot: block until ports are free / open
+        if self.args.wait_free.is_some() || self.args.wait_open.is_some() {
+            return self.wait_for_ports().await;
+        }
+
+        // One-shot CI gate: exit 2 if any of the given ports is taken
+        if let Some(ports) = self.args.get_fail_if_occupied_ports() {
+            return self.fail_if_occupied(ports).await;
+        }
+
+        // One-shot: a single scan shown like a monitoring update; exit 2 if anything was found
+        if self.args.once {
+            let found = self.run_once().await?;
+            Self::exit_for_scan(found);
+            return Ok(());
+        }
+
         if self.args.json {
             // JSON mode - output processes once and exit
-            return self.output_processes_json().await;
+            self.output_processes_json().await?;
+            return Ok(());
         }
 
         // Check if endpoint monitoring is enabled
@@ -352,21 +640,24 @@ impl ConsolePortKillApp {
             return self.run_endpoint_monitoring().await;
         }
 
-        println!("🚀 Port Kill Console Monitor Started!");
-        println!(
-            "📡 Monitoring {} every 2 seconds...",
-            self.args.get_port_description()
-        );
+        // Machine-readable output keeps stdout free of banners
+        if self.args.output_format() == OutputFormat::Table {
+            println!("🚀 Port Kill Console Monitor Started!");
+            println!(
+                "📡 Monitoring {} every 2 seconds...",
+                self.args.get_port_description()
+            );
 
-        // Show filter information if filtering is enabled
-        if let Ok(monitor) = self.process_monitor.try_lock() {
-            if let Some(filter_stats) = monitor.get_filter_stats() {
-                println!("🔍 {}", filter_stats.get_description());
+            // Show filter information if filtering is enabled
+            if let Ok(monitor) = self.process_monitor.try_lock() {
+                if let Some(filter_stats) = monitor.get_filter_stats() {
+                    println!("🔍 {}", filter_stats.get_description());
+                }
             }
-        }
 
-        println!("💡 Press Ctrl+C to quit");
-        println!("");
+            println!("💡 Press Ctrl+C to quit");
+            println!();
+        }
 
         // Start Port Guard in background (--guard or --guard-mode)
         if let Some(guard) = &self.port_guard {
@@ -422,7 +713,7 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
-    async fn output_processes_json(&mut self) -> Result<()> {
+    async fn output_processes_json(&mut self) -> Result<usize> {
         // Use smart port selection to avoid hanging on large port ranges
         let ports_to_scan = Self::get_ports_to_scan(&self.args);
         let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
@@ -431,180 +722,418 @@ impl ConsolePortKillApp {
         // Filter out ignored processes
         let filtered_processes = self.filter_ignored_processes(&processes);
 
-        // Output each process as JSON
-        for (_port, process_info) in &filtered_processes {
+        // Output each process as JSON, in port order
+        let mut sorted: Vec<&crate::types::ProcessInfo> = filtered_processes.values().collect();
+        sorted.sort_by_key(|p| (p.port, p.pid));
+        for process_info in sorted {
             let json = serde_json::to_string(process_info)?;
             println!("{}", json);
         }
 
-        Ok(())
-    }
+        // A trailing status line (no pid/port) lets consumers tell "scan failed" from "nothing listening"
+        let line = crate::scan_health::ScanStatusLine::from(&crate::scan_health::last_scan());
+        println!("{}", serde_json::to_string(&line)?);
 
-    async fn handle_console_updates(&mut self) {
-        info!("Starting console update handler...");
+        Ok(filtered_processes.len())
+    }
 
-        loop {
-            // Check for process updates
-            if let Ok(update) = self.update_receiver.try_recv() {
-                // Filter out ignored processes
-                let filtered_processes = self.filter_ignored_processes(&update.processes);
-                let filtered_count = filtered_processes.len();
+    /// Exit with the documented code for a one-shot scan: 4 if the scan failed, 2 if anything was found
+    fn exit_for_scan(found: usize) {
+        if crate::scan_health::last_scan().status == crate::scan_health::ScanStatus::Failed {
+            exit_code::exit(exit_code::SCAN_FAILED);
+        }
+        if found > 0 {
+            exit_code::exit(exit_code::PROCESSES_FOUND);
+        }
+    }
 
-                // Update status
-                let status_info = StatusBarInfo::from_processes_with_status(&filtered_processes);
+    /// `--wait-free` / `--wait-open`: block until the ports reach the requested state,
+    /// exiting with code 5 on --timeout. Does nothing when neither flag is set.
+    async fn wait_for_ports(&self) -> Result<()> {
+        use crate::port_wait::{wait_for_ports, WaitCondition};
+
+        let timeout = self.args.timeout.map(std::time::Duration::from_secs);
+        let started = std::time::Instant::now();
+        let waits = [
+            (self.args.get_wait_free_ports(), WaitCondition::Free),
+            (self.args.get_wait_open_ports(), WaitCondition::Open),
+        ];
+
+        for (ports, condition) in waits {
+            let Some(ports) = ports else { continue };
+            let port_list = ports
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!(
+                "⏳ Waiting for port(s) {} to be {}...",
+                port_list,
+                condition.describe()
+            );
 
-                // Print status to console
+            // Both waits share one deadline
+            let remaining = timeout.map(|t| t.saturating_sub(started.elapsed()));
+            let pending = wait_for_ports(&ports, condition, remaining).await;
+            if !pending.is_empty() {
+                let pending = pending
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
                 println!(
-                    "🔄 Port Status: {} - {}",
-                    status_info.text, status_info.tooltip
+                    "⌛ Timed out after {}s: port(s) {} not {}",
+                    self.args.timeout.unwrap_or_default(),
+                    pending,
+                    condition.describe()
                 );
+                exit_code::exit(exit_code::TIMED_OUT);
+            }
+            println!(
+                "✅ Port(s) {} {} (after {:.1}s)",
+                port_list,
+                condition.describe(),
+                started.elapsed().as_secs_f64()
+            );
+        }
+        Ok(())
+    }
+
+    /// `--fail-if-occupied`: check that the given ports are free, for CI and Makefiles
+    async fn fail_if_occupied(&self, ports: Vec<u16>) -> Result<()> {
+        let port_list = ports
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut temp_monitor = self.create_temp_monitor(ports).await?;
+        let processes = temp_monitor.scan_processes().await?;
+
+        match self.args.output_format() {
+            OutputFormat::Table => {
+                if let Some(message) = crate::scan_health::last_scan().describe() {
+                    println!("{}", message);
+                } else if processes.is_empty() {
+                    println!("✅ Port(s) {} free", port_list);
+                } else {
+                    let mut occupied: Vec<_> = processes.values().collect();
+                    occupied.sort_by_key(|p| p.port);
+                    for process_info in &occupied {
+                        println!(
+                            "❌ Port {} is in use by {} (PID {})",
+                            process_info.port,
+                            process_info.get_short_name(),
+                            process_info.pid
+                        );
+                    }
+                    let busy: Vec<String> = occupied.iter().map(|p| p.port.to_string()).collect();
+                    println!("💡 Free them with: port-kill-console {}", busy.join(" "));
+                }
+            }
+            format => Self::print_scan_snapshot(&processes, format, true)?,
+        }
+
+        Self::exit_for_scan(processes.len());
+        Ok(())
+    }
+
+    /// `--once`: scan, print in the selected format and return how many processes were found
+    async fn run_once(&mut self) -> Result<usize> {
+        if self.args.json {
+            return self.output_processes_json().await;
+        }
+
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        let processes = temp_monitor.scan_processes().await?;
+        let filtered_processes = self.filter_ignored_processes(&processes);
+
+        match self.args.output_format() {
+            OutputFormat::Table => self.print_update(&processes, &filtered_processes),
+            format => Self::print_scan_snapshot(&filtered_processes, format, true)?,
+        }
+        Ok(filtered_processes.len())
+    }
+
+    /// Print one scan as a JSON snapshot line or CSV rows (header only when asked)
+    fn print_scan_snapshot(
+        processes: &HashMap<u16, crate::types::ProcessInfo>,
+        format: OutputFormat,
+        csv_header: bool,
+    ) -> Result<()> {
+        let snapshot = ScanSnapshot::new(processes, crate::scan_health::last_scan());
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(&snapshot)?),
+            OutputFormat::Ndjson => {
+                for line in snapshot.ndjson_lines()? {
+                    println!("{}", line);
+                }
+            }
+            OutputFormat::Csv => {
+                if csv_header {
+                    println!("{}", crate::types::ProcessInfo::CSV_HEADER);
+                }
+                for process_info in &snapshot.processes {
+                    println!("{}", process_info.to_csv_row(&snapshot.timestamp));
+                }
+            }
+            OutputFormat::Table => {}
+        }
+        Ok(())
+    }
 
-                if filtered_count > 0 {
-                    println!("📋 Detected Processes (after filtering ignored):");
+    /// Write the `--status-page` HTML for a scan. Cache stats are slow to collect, so
+    /// they are gathered once and reused on later rewrites.
+    async fn write_status_page(
+        &mut self,
+        path: &str,
+        processes: &HashMap<u16, crate::types::ProcessInfo>,
+        auto_refresh: Option<u64>,
+    ) -> Result<()> {
+        if self.cache_summary.is_none() {
+            let caches = list_caches(&CacheQuery::default()).await;
+            self.cache_summary = Some(caches.summary);
+        }
+
+        let page = StatusPage {
+            generated_at: chrono::Utc::now(),
+            port_description: self.args.get_port_description(),
+            processes: processes.values().cloned().collect(),
+            scan_health: crate::scan_health::last_scan(),
+            cache_summary: self.cache_summary.clone(),
+            last_audit: SecurityAuditor::load_last_audit(),
+            auto_refresh,
+        };
+        page.write_to(std::path::Path::new(path))
+    }
 
-                    // Show process summary
-                    let mut group_counts: std::collections::HashMap<String, usize> =
-                        std::collections::HashMap::new();
-                    let mut project_counts: std::collections::HashMap<String, usize> =
-                        std::collections::HashMap::new();
+    /// Empty scan result: explain a failed scan instead of claiming nothing is running
+    fn print_no_processes() {
+        match crate::scan_health::last_scan().describe() {
+            Some(message) => println!("{}", message),
+            None => println!("ℹ️  No processes detected"),
+        }
+    }
 
-                    for (_, process_info) in &filtered_processes {
-                        if let Some(ref group) = process_info.process_group {
-                            *group_counts.entry(group.clone()).or_insert(0) += 1;
+    /// Print one scan the way the monitoring loop shows it (also used by --once)
+    fn print_update(
+        &self,
+        processes: &HashMap<u16, crate::types::ProcessInfo>,
+        filtered_processes: &HashMap<u16, crate::types::ProcessInfo>,
+    ) {
+        let filtered_count = filtered_processes.len();
+
+        // Update status
+        let mut status_info =
+            StatusBarInfo::from_processes_with_status(filtered_processes);
+        status_info.apply_scan_health(&crate::scan_health::last_scan());
+
+        if self.args.set_terminal_title {
+            use std::io::Write;
+            print!("{}", status_info.terminal_title_sequence());
+            let _ = std::io::stdout().flush();
+        }
+
+        // Print status to console
+        println!(
+            "🔄 Port Status: {} - {}",
+            status_info.text, status_info.tooltip
+        );
+
+        if filtered_count > 0 {
+            println!("📋 Detected Processes (after filtering ignored):");
+
+            // Show process summary
+            let mut group_counts: std::collections::HashMap<String, usize> =
+                std::collections::HashMap::new();
+            let mut project_counts: std::collections::HashMap<String, usize> =
+                std::collections::HashMap::new();
+
+            for (_, process_info) in filtered_processes {
+                if let Some(ref group) = process_info.process_group {
+                    *group_counts.entry(group.clone()).or_insert(0) += 1;
+                }
+                if let Some(ref project) = process_info.project_name {
+                    *project_counts.entry(project.clone()).or_insert(0) += 1;
+                }
+            }
+
+            if !group_counts.is_empty() {
+                let group_summary: Vec<String> = group_counts
+                    .iter()
+                    .map(|(group, count)| format!("{}: {}", group, count))
+                    .collect();
+                println!("   📊 Groups: {}", group_summary.join(", "));
+            }
+
+            if !project_counts.is_empty() {
+                let project_summary: Vec<String> = project_counts
+                    .iter()
+                    .map(|(project, count)| format!("{}: {}", project, count))
+                    .collect();
+                println!("   📁 Projects: {}", project_summary.join(", "));
+            }
+
+            Self::print_duplicate_warnings(filtered_processes);
+
+            println!();
+
+            for (_port, process_info) in filtered_processes {
+                if self.args.verbose {
+                    // Verbose mode: show detailed description
+                    let mut parts =
+                        vec![format!("   • {}", process_info.get_detailed_description())];
+
+                    // Add project context if requested
+                    if self.args.show_context {
+                        parts.push(format!(
+                            "[Context: {}]",
+                            process_info.get_project_description()
+                        ));
+                    }
+
+                    // Add performance metrics if available
+                    if self.args.performance {
+                        if let Some(cpu) = process_info.cpu_usage {
+                            let cpu_indicator = if cpu > 50.0 {
+                                "🔥"
+                            } else if cpu > 20.0 {
+                                "⚠️"
+                            } else {
+                                "✅"
+                            };
+                            parts.push(format!("CPU: {:.1}%{}", cpu, cpu_indicator));
                         }
-                        if let Some(ref project) = process_info.project_name {
-                            *project_counts.entry(project.clone()).or_insert(0) += 1;
+                        if let Some(memory) = process_info.memory_usage {
+                            let memory_mb = memory as f64 / 1024.0 / 1024.0;
+                            let memory_indicator = if memory_mb > 500.0 {
+                                "🔥"
+                            } else if memory_mb > 100.0 {
+                                "⚠️"
+                            } else {
+                                "✅"
+                            };
+                            parts.push(format!(
+                                "RAM: {:.1}MB{}",
+                                memory_mb, memory_indicator
+                            ));
                         }
                     }
+                    if let Some(gpu) = process_info.gpu_memory {
+                        parts.push(format!("GPU: {}", crate::gpu::format_gpu_memory(gpu)));
+                    }
 
-                    if !group_counts.is_empty() {
-                        let group_summary: Vec<String> = group_counts
-                            .iter()
-                            .map(|(group, count)| format!("{}: {}", group, count))
-                            .collect();
-                        println!("   📊 Groups: {}", group_summary.join(", "));
+                    if let Some(secs) = process_info.uptime_secs {
+                        parts.push(format!("[up {}]", format_uptime(secs)));
+                    }
+                    if let Some(ppid) = process_info.ppid {
+                        parts.push(format!("[parent PID {}]", ppid));
                     }
 
-                    if !project_counts.is_empty() {
-                        let project_summary: Vec<String> = project_counts
-                            .iter()
-                            .map(|(project, count)| format!("{}: {}", project, count))
-                            .collect();
-                        println!("   📁 Projects: {}", project_summary.join(", "));
+                    if self.args.show_pid {
+                        parts.push(format!("(PID {})", process_info.pid));
                     }
 
-                    println!();
+                    println!("{}", parts.join(" "));
+                } else {
+                    // Normal mode: show enhanced display name
+                    let mut parts = vec![format!(
+                        "   • Port {}: {}",
+                        _port,
+                        process_info.get_display_name()
+                    )];
+
+                    // Add project context if requested
+                    if self.args.show_context {
+                        parts.push(format!(
+                            "[Context: {}]",
+                            process_info.get_project_description()
+                        ));
+                    }
 
-                    for (_port, process_info) in &filtered_processes {
-                        if self.args.verbose {
-                            // Verbose mode: show detailed description
-                            let mut parts =
-                                vec![format!("   • {}", process_info.get_detailed_description())];
-
-                            // Add project context if requested
-                            if self.args.show_context {
-                                parts.push(format!(
-                                    "[Context: {}]",
-                                    process_info.get_project_description()
-                                ));
-                            }
+                    // Add performance metrics if available
+                    if self.args.performance {
+                        if let Some(cpu) = process_info.cpu_usage {
+                            let cpu_indicator = if cpu > 50.0 {
+                                "🔥"
+                            } else if cpu > 20.0 {
+                                "⚠️"
+                            } else {
+                                "✅"
+                            };
+                            parts.push(format!("CPU: {:.1}%{}", cpu, cpu_indicator));
+                        }
+                        if let Some(memory) = process_info.memory_usage {
+                            let memory_mb = memory as f64 / 1024.0 / 1024.0;
+                            let memory_indicator = if memory_mb > 500.0 {
+                                "🔥"
+                            } else if memory_mb > 100.0 {
+                                "⚠️"
+                            } else {
+                                "✅"
+                            };
+                            parts.push(format!(
+                                "RAM: {:.1}MB{}",
+                                memory_mb, memory_indicator
+                            ));
+                        }
+                    }
+                    if let Some(gpu) = process_info.gpu_memory {
+                        parts.push(format!("GPU: {}", crate::gpu::format_gpu_memory(gpu)));
+                    }
 
-                            // Add performance metrics if available
-                            if self.args.performance {
-                                if let Some(cpu) = process_info.cpu_usage {
-                                    let cpu_indicator = if cpu > 50.0 {
-                                        "🔥"
-                                    } else if cpu > 20.0 {
-                                        "⚠️"
-                                    } else {
-                                        "✅"
-                                    };
-                                    parts.push(format!("CPU: {:.1}%{}", cpu, cpu_indicator));
-                                }
-                                if let Some(memory) = process_info.memory_usage {
-                                    let memory_mb = memory as f64 / 1024.0 / 1024.0;
-                                    let memory_indicator = if memory_mb > 500.0 {
-                                        "🔥"
-                                    } else if memory_mb > 100.0 {
-                                        "⚠️"
-                                    } else {
-                                        "✅"
-                                    };
-                                    parts.push(format!(
-                                        "RAM: {:.1}MB{}",
-                                        memory_mb, memory_indicator
-                                    ));
-                                }
-                            }
+                    if self.args.show_pid {
+                        parts.push(format!("(PID {})", process_info.pid));
+                    }
 
-                            if self.args.show_pid {
-                                parts.push(format!("(PID {})", process_info.pid));
-                            }
+                    println!("{}", parts.join(" "));
+                }
+            }
+        }
 
-                            println!("{}", parts.join(" "));
-                        } else {
-                            // Normal mode: show enhanced display name
-                            let mut parts = vec![format!(
-                                "   • Port {}: {}",
-                                _port,
-                                process_info.get_display_name()
-                            )];
-
-                            // Add project context if requested
-                            if self.args.show_context {
-                                parts.push(format!(
-                                    "[Context: {}]",
-                                    process_info.get_project_description()
-                                ));
-                            }
+        // Show ignored processes if any
+        let ignored_count = processes.len() - filtered_count;
+        if ignored_count > 0 {
+            println!(
+                "🚫 Ignored {} process(es) based on user configuration",
+                ignored_count
+            );
+        }
 
-                            // Add performance metrics if available
-                            if self.args.performance {
-                                if let Some(cpu) = process_info.cpu_usage {
-                                    let cpu_indicator = if cpu > 50.0 {
-                                        "🔥"
-                                    } else if cpu > 20.0 {
-                                        "⚠️"
-                                    } else {
-                                        "✅"
-                                    };
-                                    parts.push(format!("CPU: {:.1}%{}", cpu, cpu_indicator));
-                                }
-                                if let Some(memory) = process_info.memory_usage {
-                                    let memory_mb = memory as f64 / 1024.0 / 1024.0;
-                                    let memory_indicator = if memory_mb > 500.0 {
-                                        "🔥"
-                                    } else if memory_mb > 100.0 {
-                                        "⚠️"
-                                    } else {
-                                        "✅"
-                                    };
-                                    parts.push(format!(
-                                        "RAM: {:.1}MB{}",
-                                        memory_mb, memory_indicator
-                                    ));
-                                }
-                            }
+        println!("");
+    }
 
-                            if self.args.show_pid {
-                                parts.push(format!("(PID {})", process_info.pid));
-                            }
+    async fn handle_console_updates(&mut self) {
+        info!("Starting console update handler...");
+        let output_format = self.args.output_format();
+        let mut csv_header = true;
 
-                            println!("{}", parts.join(" "));
-                        }
+        loop {
+            // Check for process updates
+            if let Ok(update) = self.update_receiver.try_recv() {
+                // Filter out ignored processes
+                let filtered_processes = self.filter_ignored_processes(&update.processes);
+
+                if let Some(path) = self.args.status_page.clone() {
+                    if let Err(e) = self
+                        .write_status_page(&path, &filtered_processes, Some(5))
+                        .await
+                    {
+                        error!("Failed to update status page: {}", e);
                     }
                 }
 
-                // Show ignored processes if any
-                let ignored_count = update.processes.len() - filtered_count;
-                if ignored_count > 0 {
-                    println!(
-                        "🚫 Ignored {} process(es) based on user configuration",
-                        ignored_count
-                    );
+                if output_format != OutputFormat::Table {
+                    if let Err(e) =
+                        Self::print_scan_snapshot(&filtered_processes, output_format, csv_header)
+                    {
+                        error!("Failed to print scan snapshot: {}", e);
+                    }
+                    csv_header = false;
+                    continue;
                 }
 
-                println!("");
+                self.print_update(&update.processes, &filtered_processes);
             }
 
             // Sleep briefly to avoid busy waiting
@@ -612,6 +1141,20 @@ impl ConsolePortKillApp {
         }
     }
 
+    /// Ask before killing `count` processes when `--safe` or safe mode calls for it.
+    /// Returns false (after saying so) when the user says no.
+    fn confirm_kill(&self, count: usize, prompt: &str) -> Result<bool> {
+        if !self.args.needs_kill_confirmation(count) || crate::safe_mode::confirm(prompt)? {
+            return Ok(true);
+        }
+        if self.args.safe {
+            println!("Cancelled.");
+        } else {
+            crate::safe_mode::print_cancelled();
+        }
+        Ok(false)
+    }
+
     fn filter_ignored_processes(
         &self,
         processes: &HashMap<u16, crate::types::ProcessInfo>,
@@ -640,11 +1183,35 @@ impl ConsolePortKillApp {
 
     pub async fn display_history(&self) -> Result<()> {
         let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let filter = crate::history_store::HistoryFilter::from_args(&self.args)?;
+        let history = monitor.get_history().filtered(&filter);
+
+        // Exports cover the whole (filtered) history, oldest first
+        if !self.args.json && self.args.output != OutputFormat::Table {
+            let entries = history.get_entries();
+            match self.args.output {
+                OutputFormat::Json => println!("{}", serde_json::to_string(entries)?),
+                OutputFormat::Ndjson => {
+                    for entry in entries {
+                        println!("{}", serde_json::to_string(entry)?);
+                    }
+                }
+                OutputFormat::Csv => {
+                    println!("{}", crate::types::ProcessHistoryEntry::CSV_HEADER);
+                    for entry in entries {
+                        println!("{}", entry.to_csv_row());
+                    }
+                }
+                OutputFormat::Table => {}
+            }
+            return Ok(());
+        }
 
         if history.is_empty() {
             if self.args.json {
                 println!("[]");
+            } else if !filter.is_empty() {
+                println!("📋 No kill history matches the filters");
             } else {
                 println!("📋 No process kill history found");
             }
@@ -653,7 +1220,8 @@ impl ConsolePortKillApp {
 
         if self.args.json {
             // Output history as JSON
-            let recent_entries = history.get_recent_entries(50); // Show last 50 entries for API
+            // Show the last 50 entries (or --limit) for API
+            let recent_entries = history.get_recent_entries(self.args.history_limit.unwrap_or(50));
 
             for entry in recent_entries.iter() {
                 let json = serde_json::to_string(entry)?;
@@ -664,7 +1232,8 @@ impl ConsolePortKillApp {
             println!("📋 Process Kill History ({} entries):", history.len());
             println!("{}", "─".repeat(80));
 
-            let recent_entries = history.get_recent_entries(20); // Show last 20 entries
+            // Show the last 20 entries, or --limit
+            let recent_entries = history.get_recent_entries(self.args.history_limit.unwrap_or(20));
 
             for (i, entry) in recent_entries.iter().enumerate() {
                 let display_name = entry.get_display_name();
@@ -687,7 +1256,15 @@ impl ConsolePortKillApp {
                     println!("    Directory: {}", work_dir);
                 }
 
-                println!("    Killed by: {}", entry.killed_by);
+                match entry.action {
+                    crate::types::HistoryAction::Kill => {
+                        println!("    Killed by: {}", entry.describe_initiator())
+                    }
+                    crate::types::HistoryAction::Restart => {
+                        println!("    Restarted by: {}", entry.describe_initiator())
+                    }
+                    action => println!("    Action: {} (left running)", action.describe()),
+                }
                 println!();
             }
         }
@@ -695,6 +1272,163 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// Print the audit trail (`--show-audit`), oldest first, narrowed by the `--audit-*` filters
+    pub async fn show_audit(&self) -> Result<()> {
+        let filter = crate::audit_log::AuditFilter::from_args(&self.args)?;
+        let entries = crate::audit_log::read(&filter)?;
+
+        match (self.args.json, self.args.output) {
+            (true, _) | (false, OutputFormat::Ndjson) => {
+                for entry in &entries {
+                    println!("{}", serde_json::to_string(entry)?);
+                }
+            }
+            (false, OutputFormat::Json) => println!("{}", serde_json::to_string(&entries)?),
+            (false, OutputFormat::Csv) => {
+                println!("{}", crate::audit_log::AuditEntry::CSV_HEADER);
+                for entry in &entries {
+                    println!("{}", entry.to_csv_row());
+                }
+            }
+            (false, OutputFormat::Table) => {
+                if entries.is_empty() {
+                    println!("📋 No audited actions found");
+                    return Ok(());
+                }
+                println!("📋 Audit trail ({} entries):", entries.len());
+                println!("{}", "─".repeat(80));
+                for entry in &entries {
+                    println!("{}", entry.describe());
+                }
+                println!("{}", "─".repeat(80));
+                println!("📁 {}", crate::audit_log::log_file_path().display());
+            }
+        }
+        Ok(())
+    }
+
+    /// `port-kill query <expression>`: print the listeners (or `--history` entries) the
+    /// expression matches, and with `--kill`/`--restart` act on them
+    pub async fn run_query(&self, query_args: &crate::cli::QueryArgs) -> Result<()> {
+        let query = crate::query::Query::parse(&query_args.expression)?;
+        let json = query_args.json || self.args.json;
+
+        if query_args.history {
+            let monitor = self.process_monitor.lock().await;
+            let matches: Vec<_> = monitor
+                .get_history()
+                .get_entries()
+                .iter()
+                .filter(|entry| query.matches(*entry))
+                .collect();
+            if json {
+                for entry in &matches {
+                    println!("{}", serde_json::to_string(entry)?);
+                }
+            } else if matches.is_empty() {
+                println!("ℹ️  No history entries match '{}'", query_args.expression);
+            } else {
+                println!("📋 {} history entries match '{}':", matches.len(), query_args.expression);
+                for entry in &matches {
+                    println!(
+                        "  • {} {} (PID {}) on port {} [{}]",
+                        entry.killed_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"),
+                        entry.process_name,
+                        entry.pid,
+                        entry.port,
+                        entry.describe_initiator()
+                    );
+                }
+            }
+            return Ok(());
+        }
+
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        temp_monitor.set_performance(self.args.performance || query.needs_metrics());
+        let processes = temp_monitor.scan_processes().await?;
+        let mut processes = self.filter_ignored_processes(&processes);
+        crate::system_monitor::fill_working_directories(&mut processes);
+
+        let mut matches: Vec<_> = processes.into_values().filter(|p| query.matches(p)).collect();
+        matches.sort_by_key(|p| p.port);
+
+        if json {
+            for process_info in &matches {
+                println!("{}", serde_json::to_string(process_info)?);
+            }
+        } else if matches.is_empty() {
+            println!("ℹ️  No processes match '{}'", query_args.expression);
+        } else {
+            println!("🔍 {} processes match '{}':", matches.len(), query_args.expression);
+            for process_info in &matches {
+                let mut line = format!(
+                    "  • Port {}: {} (PID {})",
+                    process_info.port,
+                    process_info.get_short_name(),
+                    process_info.pid
+                );
+                if let Some(group) = &process_info.process_group {
+                    line.push_str(&format!(" [{}]", group));
+                }
+                if let Some(project) = &process_info.project_name {
+                    line.push_str(&format!(" 📁 {}", project));
+                }
+                if let Some(cpu) = process_info.cpu_usage {
+                    line.push_str(&format!(" CPU {:.1}%", cpu));
+                }
+                if let Some(memory) = process_info.memory_usage {
+                    line.push_str(&format!(" RAM {:.1} MB", memory as f64 / 1024.0 / 1024.0));
+                }
+                println!("{}", line);
+            }
+        }
+
+        if query_args.kill {
+            let prompt = format!("Confirm kill {} matching process(es)?", matches.len());
+            if !self.confirm_kill(matches.len(), &prompt)? {
+                return Ok(());
+            }
+        }
+        for process_info in &matches {
+            let result = if query_args.kill {
+                temp_monitor
+                    .kill_process(process_info.pid)
+                    .await
+                    .map(|outcome| match outcome {
+                        KillOutcome::Killed => "Killed",
+                        KillOutcome::Exited => "Gracefully stopped",
+                    })
+            } else if query_args.restart {
+                temp_monitor
+                    .restart_process_on_port(process_info.port)
+                    .await
+                    .map(|()| "Restarted")
+            } else {
+                continue;
+            };
+            let verb = if query_args.kill { "kill" } else { "restart" };
+            match result {
+                Ok(done) if !json => println!(
+                    "✅ {} {} (PID {}) on port {}",
+                    done,
+                    process_info.get_short_name(),
+                    process_info.pid,
+                    process_info.port
+                ),
+                Ok(_) => {}
+                Err(e) => eprintln!(
+                    "❌ Failed to {} {} (PID {}): {}",
+                    verb,
+                    process_info.get_short_name(),
+                    process_info.pid,
+                    e
+                ),
+            }
+        }
+        Ok(())
+    }
+
     pub async fn clear_history(&self) -> Result<()> {
         let mut monitor = self.process_monitor.lock().await;
         monitor.clear_history();
@@ -728,16 +1462,37 @@ impl ConsolePortKillApp {
                     filter_stats.ignore_patterns_count
                 );
             }
+            if filter_stats.ignore_regex_count > 0 {
+                println!("  • Using {} regex filters", filter_stats.ignore_regex_count);
+            }
             if filter_stats.ignore_groups_count > 0 {
                 println!(
                     "  • Ignoring {} process groups",
                     filter_stats.ignore_groups_count
                 );
             }
-            if filter_stats.only_groups_count > 0 {
+            if filter_stats.only_groups_count > 0 {
+                println!(
+                    "  • Showing only {} process groups",
+                    filter_stats.only_groups_count
+                );
+            }
+            if filter_stats.only_processes_count > 0 {
+                println!(
+                    "  • Showing only {} processes or patterns",
+                    filter_stats.only_processes_count
+                );
+            }
+            if filter_stats.only_ports_count > 0 {
+                println!("  • Showing only {} ports", filter_stats.only_ports_count);
+            }
+            if filter_stats.port_rules_count > 0 {
+                println!("  • {} per-port rules", filter_stats.port_rules_count);
+            }
+            if filter_stats.conditional_filters_count > 0 {
                 println!(
-                    "  • Showing only {} process groups",
-                    filter_stats.only_groups_count
+                    "  • {} conditional filters",
+                    filter_stats.conditional_filters_count
                 );
             }
         } else {
@@ -753,32 +1508,33 @@ impl ConsolePortKillApp {
         let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
         let processes = temp_monitor.scan_processes().await?;
 
+        let members = crate::process_monitor::processes_in_groups(&processes, groups);
+        let prompt = format!("Confirm kill {} process(es) in groups: {}?", members.len(), groups.join(", "));
+        if !self.confirm_kill(members.len(), &prompt)? {
+            return Ok(());
+        }
+
         let mut killed_count = 0;
-        let mut total_count = 0;
+        let total_count = members.len();
 
-        for (port, process_info) in &processes {
-            if let Some(ref group) = process_info.process_group {
-                if groups.contains(group) {
-                    total_count += 1;
-                    println!(
-                        "🔪 Killing {} (PID {}) on port {} - Group: {}",
-                        process_info.get_short_name(),
-                        process_info.pid,
-                        port,
-                        group
-                    );
+        for process_info in members {
+            println!(
+                "🔪 Killing {} (PID {}) on port {} - Group: {}",
+                process_info.get_short_name(),
+                process_info.pid,
+                process_info.port,
+                process_info.process_group.as_deref().unwrap_or_default()
+            );
 
-                    if let Err(e) = temp_monitor.kill_process(process_info.pid).await {
-                        println!(
-                            "❌ Failed to kill {} (PID {}): {}",
-                            process_info.get_short_name(),
-                            process_info.pid,
-                            e
-                        );
-                    } else {
-                        killed_count += 1;
-                    }
-                }
+            if let Err(e) = temp_monitor.kill_process(process_info.pid).await {
+                println!(
+                    "❌ Failed to kill {} (PID {}): {}",
+                    process_info.get_short_name(),
+                    process_info.pid,
+                    e
+                );
+            } else {
+                killed_count += 1;
             }
         }
 
@@ -791,8 +1547,150 @@ impl ConsolePortKillApp {
                 total_count,
                 groups.join(", ")
             );
+            if killed_count < total_count {
+                exit_code::exit(exit_code::KILL_FAILED);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `--kill-gpu-hogs`: kill listeners on the monitored ports that hold GPU memory
+    pub async fn kill_gpu_hogs(&self) -> Result<()> {
+        if !crate::gpu::is_available() {
+            anyhow::bail!("GPU usage needs an NVIDIA GPU with `nvidia-smi` on PATH");
+        }
+
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        let processes = temp_monitor.scan_processes().await?;
+        let gpu_usage = crate::gpu::gpu_memory_by_pid();
+
+        let mut hogs: Vec<_> = self
+            .filter_ignored_processes(&processes)
+            .into_values()
+            .filter_map(|p| gpu_usage.get(&p.pid).map(|&bytes| (p, bytes)))
+            .collect();
+        hogs.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+
+        if hogs.is_empty() {
+            println!("ℹ️  No listeners on the monitored ports are holding GPU memory");
+            return Ok(());
+        }
+
+        let total_count = hogs.len();
+        let prompt = format!("Confirm kill {} process(es) holding GPU memory?", total_count);
+        if !self.confirm_kill(total_count, &prompt)? {
+            return Ok(());
+        }
+        let mut killed_count = 0;
+        for (process_info, bytes) in &hogs {
+            println!(
+                "🔪 Killing {} (PID {}) on port {} - GPU: {}",
+                process_info.get_short_name(),
+                process_info.pid,
+                process_info.port,
+                crate::gpu::format_gpu_memory(*bytes)
+            );
+            if let Err(e) = temp_monitor.kill_process(process_info.pid).await {
+                println!(
+                    "❌ Failed to kill {} (PID {}): {}",
+                    process_info.get_short_name(),
+                    process_info.pid,
+                    e
+                );
+            } else {
+                killed_count += 1;
+            }
+        }
+
+        let freed: u64 = hogs.iter().map(|(_, bytes)| bytes).sum();
+        println!(
+            "✅ Killed {}/{} GPU processes, freeing up to {} of GPU memory",
+            killed_count,
+            total_count,
+            crate::gpu::format_gpu_memory(freed)
+        );
+        if killed_count < total_count {
+            exit_code::exit(exit_code::KILL_FAILED);
+        }
+        Ok(())
+    }
+
+    /// Warn about projects running more than once (see `--kill-duplicates`)
+    fn print_duplicate_warnings(processes: &HashMap<u16, crate::types::ProcessInfo>) {
+        let mut processes = processes.clone();
+        crate::system_monitor::fill_duplicate_candidates(&mut processes);
+        for duplicate in crate::types::find_duplicate_instances(&processes) {
+            println!(
+                "   ⚠️  Duplicate instance: {} (newest PID {}; --kill-duplicates keeps it and kills the rest)",
+                duplicate.describe(),
+                duplicate.newest().pid
+            );
+        }
+    }
+
+    /// `--kill-duplicates`: for every project running more than once, keep the newest
+    /// instance and kill the older ones
+    pub async fn kill_duplicates(&self) -> Result<()> {
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        let processes = temp_monitor.scan_processes().await?;
+        let mut processes = self.filter_ignored_processes(&processes);
+        crate::system_monitor::fill_duplicate_candidates(&mut processes);
+
+        let duplicates = crate::types::find_duplicate_instances(&processes);
+        if duplicates.is_empty() {
+            println!("ℹ️  No duplicate instances found");
+            return Ok(());
+        }
+
+        let older: usize = duplicates.iter().map(|d| d.older().len()).sum();
+        let prompt = format!("Confirm kill {} older duplicate instance(s)?", older);
+        if !self.confirm_kill(older, &prompt)? {
+            return Ok(());
+        }
+
+        let mut killed_count = 0;
+        let mut total_count = 0;
+        for duplicate in &duplicates {
+            let newest = duplicate.newest();
+            println!(
+                "⚠️  Duplicate instance: {} - keeping PID {} on port {}",
+                duplicate.describe(),
+                newest.pid,
+                newest.port
+            );
+            for process_info in duplicate.older() {
+                total_count += 1;
+                println!(
+                    "🔪 Killing older {} (PID {}) on port {}",
+                    process_info.get_short_name(),
+                    process_info.pid,
+                    process_info.port
+                );
+                if let Err(e) = temp_monitor.kill_process(process_info.pid).await {
+                    println!(
+                        "❌ Failed to kill {} (PID {}): {}",
+                        process_info.get_short_name(),
+                        process_info.pid,
+                        e
+                    );
+                } else {
+                    killed_count += 1;
+                }
+            }
         }
 
+        println!(
+            "✅ Killed {}/{} older instance(s) across {} project(s)",
+            killed_count,
+            total_count,
+            duplicates.len()
+        );
+        if killed_count < total_count {
+            exit_code::exit(exit_code::KILL_FAILED);
+        }
         Ok(())
     }
 
@@ -802,6 +1700,15 @@ impl ConsolePortKillApp {
         let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
         let processes = temp_monitor.scan_processes().await?;
 
+        let matching = processes
+            .values()
+            .filter(|p| p.project_name.as_ref().is_some_and(|name| projects.contains(name)))
+            .count();
+        let prompt = format!("Confirm kill {} process(es) in projects: {}?", matching, projects.join(", "));
+        if !self.confirm_kill(matching, &prompt)? {
+            return Ok(());
+        }
+
         let mut killed_count = 0;
         let mut total_count = 0;
 
@@ -843,6 +1750,9 @@ impl ConsolePortKillApp {
                 total_count,
                 projects.join(", ")
             );
+            if killed_count < total_count {
+                exit_code::exit(exit_code::KILL_FAILED);
+            }
         }
 
         Ok(())
@@ -851,6 +1761,7 @@ impl ConsolePortKillApp {
     pub async fn kill_all_processes(&self) -> Result<()> {
         // Use smart port selection to avoid hanging on large port ranges
         let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let port_count = ports_to_scan.len();
         let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
         let processes = temp_monitor.scan_processes().await?;
 
@@ -859,11 +1770,40 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
-        let total_count = processes.len();
+        let protected = self.args.get_protected_processes();
+        let is_protected = |p: &crate::types::ProcessInfo| {
+            crate::process_monitor::ensure_not_protected(p.pid, Some(&p.name), &protected)
+                .and_then(|()| {
+                    crate::safe_mode::ensure_not_system_port(p.port, self.args.safe_mode)
+                })
+                .is_err()
+        };
+        let protected_count = processes.values().filter(|p| is_protected(p)).count();
+        if protected_count > 0 {
+            println!("🔒 Skipping {} protected process(es)", protected_count);
+        }
+
+        let total_count = processes.len() - protected_count;
+
+        // Safe mode: a wide --kill-all only shows what it would do
+        if self.args.safe_mode && port_count > crate::safe_mode::WIDE_RANGE_PORTS {
+            let mut would_kill: Vec<_> = processes.values().filter(|p| !is_protected(p)).collect();
+            would_kill.sort_by_key(|p| p.port);
+            crate::safe_mode::print_wide_range_banner(port_count);
+            println!("Would kill {} process(es):", total_count);
+            for p in would_kill {
+                println!("  • Port {}: {} (PID {})", p.port, p.get_display_name(), p.pid);
+            }
+            return Ok(());
+        }
+        if !self.confirm_kill(total_count, &format!("Confirm kill all {} processes?", total_count))? {
+            return Ok(());
+        }
+
         println!("🔪 Killing all {} processes...", total_count);
 
         // Use the ProcessMonitor's kill_all_processes method which handles history properly
-        temp_monitor.kill_all_processes().await?;
+        exit_on_kill_failure(temp_monitor.kill_all_processes().await)?;
 
         println!("✅ Killed all {} processes", total_count);
 
@@ -926,7 +1866,7 @@ impl ConsolePortKillApp {
         let processes = temp_monitor.scan_processes().await?;
 
         if processes.is_empty() {
-            println!("ℹ️  No processes detected");
+            Self::print_no_processes();
             return Ok(());
         }
 
@@ -975,6 +1915,10 @@ impl ConsolePortKillApp {
                 {
                     println!("  │  └─ Docker Container: {}", container_name);
                 }
+
+                if let Some(ref distro) = process_info.wsl_distro {
+                    println!("  │  └─ WSL Distro: {}", distro);
+                }
             }
             println!();
         }
@@ -999,16 +1943,114 @@ impl ConsolePortKillApp {
 
         // Use the existing kill_all_processes function directly with reset ports
         use crate::process_monitor::kill_all_processes;
-        kill_all_processes(&reset_ports, &self.args)?;
+        exit_on_kill_failure(kill_all_processes(&reset_ports, &self.args))?;
 
         println!("✅ Reset complete! Development ports are now free and ready for use!");
 
         Ok(())
     }
 
+    /// `--reset --deep`: the "start fresh" button for the current project (the git
+    /// repository the current directory is in). Kills the dev servers running from it and
+    /// deletes its build caches, after one confirmation. The ports scanned are the monitored
+    /// ones, the `--reset` ones and those of the services `--detect` finds in the project.
+    pub async fn reset_project(&self) -> Result<()> {
+        let root = crate::cache::project::project_root("");
+        let services = crate::service_detector::ServiceDetector::with_paths(vec![root.clone()])
+            .discover_services()
+            .unwrap_or_default();
+
+        let mut ports = self.args.get_ports_to_monitor();
+        ports.extend(self.args.get_reset_ports());
+        ports.extend(services.iter().filter_map(|s| s.inferred_port));
+        ports.sort_unstable();
+        ports.dedup();
+        let (_, mut listeners) = crate::process_monitor::get_processes_on_ports(&ports, &self.args);
+        crate::system_monitor::fill_working_directories(&mut listeners);
+        let mut processes: Vec<crate::types::ProcessInfo> = listeners
+            .into_values()
+            .filter(|process| Self::runs_from(process, &root))
+            .collect();
+        processes.sort_by_key(|p| (p.port, p.pid));
+
+        let mut caches = crate::cache::project::detect_project_artifacts(&root, None);
+        crate::cache::rules::load().apply(&mut caches);
+        let cache_bytes: u64 = caches.iter().map(|c| c.size_bytes).sum();
+
+        println!("🧨 Starting {} fresh", root.display());
+        if !services.is_empty() {
+            let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+            println!("   Services detected: {}", names.join(", "));
+        }
+        if processes.is_empty() && caches.is_empty() {
+            println!("✨ Nothing to reset: no dev server runs from here and there are no build caches");
+            return Ok(());
+        }
+        for process in &processes {
+            println!("   • Kill {} (PID {}) on port {}", process.name, process.pid, process.port);
+        }
+        for cache in &caches {
+            println!(
+                "   • Delete {} ({})",
+                cache.name,
+                crate::cache::output::human_size(cache.size_bytes)
+            );
+        }
+        let prompt = format!(
+            "Kill {} process(es) and delete {} build cache(s) ({})?",
+            processes.len(),
+            caches.len(),
+            crate::cache::output::human_size(cache_bytes)
+        );
+        if !crate::safe_mode::confirm(&prompt)? {
+            println!("Cancelled.");
+            return Ok(());
+        }
+
+        let failed = processes
+            .iter()
+            .filter(|p| crate::process_monitor::kill_single_process(p.pid, &self.args).is_err())
+            .count();
+        if !caches.is_empty() {
+            // Build output is regenerated by the next build, so no backup
+            let root = root.to_string_lossy();
+            let resp = crate::cache::clean::clean_caches(&CacheQuery {
+                safe_delete: false,
+                project: Some(root.to_string()),
+                ..CacheQuery::default()
+            })
+            .await;
+            println!(
+                "🧹 Deleted {} build cache(s), freeing {}",
+                resp.summary.deleted_count,
+                crate::cache::output::human_size(resp.summary.freed_bytes)
+            );
+        }
+        exit_on_kill_failure(crate::process_monitor::kill_outcome(failed, processes.len()))?;
+
+        println!("✅ Fresh start ready: run your dev server again");
+        Ok(())
+    }
+
+    /// Whether `process` was started from inside `root`: its working directory is there, or
+    /// its command line names a path in it
+    fn runs_from(process: &crate::types::ProcessInfo, root: &std::path::Path) -> bool {
+        let in_root = process
+            .working_directory
+            .as_deref()
+            .map(|cwd| std::path::Path::new(cwd).canonicalize().unwrap_or_else(|_| cwd.into()))
+            .is_some_and(|cwd| cwd.starts_with(root));
+        in_root
+            || process
+                .command_line
+                .as_deref()
+                .is_some_and(|command_line| command_line.contains(&*root.to_string_lossy()))
+    }
+
     pub async fn show_frequent_offenders(&self) -> Result<()> {
         let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let filter = crate::history_store::HistoryFilter::from_args(&self.args)?;
+        let history = monitor.get_history().filtered(&filter);
 
         if history.is_empty() {
             if self.args.json {
@@ -1082,7 +2124,8 @@ impl ConsolePortKillApp {
 
     pub async fn show_time_patterns(&self) -> Result<()> {
         let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let filter = crate::history_store::HistoryFilter::from_args(&self.args)?;
+        let history = monitor.get_history().filtered(&filter);
 
         if history.is_empty() {
             println!(
@@ -1131,7 +2174,8 @@ impl ConsolePortKillApp {
 
     pub async fn show_ignore_suggestions(&self) -> Result<()> {
         let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let filter = crate::history_store::HistoryFilter::from_args(&self.args)?;
+        let history = monitor.get_history().filtered(&filter);
 
         if history.is_empty() {
             if self.args.json {
@@ -1218,7 +2262,8 @@ impl ConsolePortKillApp {
 
     pub async fn show_history_statistics(&self) -> Result<()> {
         let monitor = self.process_monitor.lock().await;
-        let history = monitor.get_history();
+        let filter = crate::history_store::HistoryFilter::from_args(&self.args)?;
+        let history = monitor.get_history().filtered(&filter);
 
         if history.is_empty() {
             if self.args.json {
@@ -1233,40 +2278,264 @@ impl ConsolePortKillApp {
 
         let stats = history.get_statistics();
 
+        match self.args.output_format() {
+            OutputFormat::Json => {
+                // Output JSON for API consumption
+                println!("{}", serde_json::to_string(&stats)?);
+                return Ok(());
+            }
+            OutputFormat::Ndjson => {
+                for row in stats.rows() {
+                    println!("{}", serde_json::to_string(&row)?);
+                }
+                return Ok(());
+            }
+            OutputFormat::Csv => {
+                println!("{}", crate::types::StatisticRow::CSV_HEADER);
+                for row in stats.rows() {
+                    println!("{}", row.to_csv_row());
+                }
+                return Ok(());
+            }
+            OutputFormat::Table => {}
+        }
+
+        println!("📊 History Statistics:");
+        println!("{}", "─".repeat(50));
+        println!("Total kills: {}", stats.total_kills);
+        println!("Unique processes: {}", stats.unique_processes);
+        println!("Unique ports: {}", stats.unique_ports);
+        println!("Unique projects: {}", stats.unique_projects);
+        println!("Average kills per day: {:.1}", stats.average_kills_per_day);
+
+        if let Some((name, count)) = &stats.most_killed_process {
+            println!("Most killed process: {} ({} times)", name, count);
+        }
+
+        if let Some((port, count)) = &stats.most_killed_port {
+            println!("Most killed port: {} ({} times)", port, count);
+        }
+
+        if let Some((project, count)) = &stats.most_killed_project {
+            println!("Most killed project: {} ({} times)", project, count);
+        }
+
+        if let Some(oldest) = &stats.oldest_kill {
+            println!("Oldest kill: {}", oldest.format("%Y-%m-%d %H:%M"));
+        }
+
+        if let Some(newest) = &stats.newest_kill {
+            println!("Newest kill: {}", newest.format("%Y-%m-%d %H:%M"));
+        }
+
+        Ok(())
+    }
+
+    /// Kill history restricted to the monitored ports, e.g. `--report --preset react`
+    pub async fn show_port_report(&self) -> Result<()> {
+        let monitor = self.process_monitor.lock().await;
+        if let Some(Some(period)) = self.args.report {
+            let format = if self.args.json {
+                crate::digest::ReportFormat::Json
+            } else {
+                self.args.report_format
+            };
+            return crate::digest::command(monitor.get_history(), period, format);
+        }
+        let report = monitor
+            .get_history()
+            .get_port_heat_report(&self.args.get_ports_to_monitor(), self.args.preset.clone());
+
         if self.args.json {
             // Output JSON for API consumption
-            println!("{}", serde_json::to_string(&stats)?);
+            println!("{}", serde_json::to_string(&report)?);
+            return Ok(());
+        }
+
+        let scope = match report.preset {
+            Some(ref name) => format!("preset '{}'", name),
+            None => "monitored ports".to_string(),
+        };
+        println!("🔥 Port History Report ({}):", scope);
+        println!("{}", "─".repeat(60));
+        println!(
+            "{} kills on {} watched ports, {} on ports outside this set",
+            report.total_kills, report.ports_watched, report.kills_outside_ports
+        );
+        println!();
+
+        if !report.port_kills.is_empty() {
+            println!("🌡️  Port heat:");
+            let hottest = report.port_kills[0].1;
+            for (port, kills) in &report.port_kills {
+                let width = (kills * 30).div_ceil(hottest);
+                println!("  {:>5} {:<30} {}", port, "█".repeat(width), kills);
+            }
+            println!();
+        }
+
+        if !report.offenders.is_empty() {
+            println!("🚨 Repeat offenders:");
+            for offender in &report.offenders {
+                println!(
+                    "  • {} on port {} (killed {} times, last {})",
+                    offender.process_name,
+                    offender.port,
+                    offender.kill_count,
+                    offender.last_killed.format("%Y-%m-%d %H:%M")
+                );
+            }
+            println!();
+        }
+
+        if !report.conflicts.is_empty() {
+            println!("⚠️  Conflicts:");
+            for conflict in &report.conflicts {
+                println!(
+                    "  • Port {}: {} ({:?})",
+                    conflict.port,
+                    conflict.conflicting_processes.join(", "),
+                    conflict.severity
+                );
+            }
+            println!();
+        }
+
+        if !report.cold_ports.is_empty() {
+            if report.cold_ports.len() <= 20 {
+                let ports: Vec<String> = report.cold_ports.iter().map(|p| p.to_string()).collect();
+                println!("🧊 Never killed: {}", ports.join(", "));
+            } else {
+                println!("🧊 {} watched ports were never killed", report.cold_ports.len());
+            }
+        }
+
+        if report.kills_outside_ports > report.total_kills {
+            println!("💡 Most kills happen outside this port set; the preset's ranges may be out of date.");
+        } else if !report.offenders.is_empty() {
+            println!("💡 Repeat offenders are candidates for the preset's ignore lists.");
+        }
+
+        Ok(())
+    }
+
+    /// `--at`: the port map at a past moment from the timeline, compared with now
+    pub async fn show_port_map_at(&self, when: &str) -> Result<()> {
+        use crate::timeline::{self, Listener, TimeTravelReport};
+
+        let at = timeline::parse_when(when, chrono::Local::now())?;
+        let path = timeline::timeline_path();
+        let records = if path.exists() { timeline::load(&path)? } else { Vec::new() };
+        let Some(past) = timeline::port_map_at(&records, at) else {
+            return Err(anyhow::anyhow!(
+                "Nothing was recorded at or before {}. The timeline is written while the tray, \
+                 console monitoring or the TUI is running",
+                at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            ));
+        };
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        let now = temp_monitor.scan_processes().await?;
+        let report = TimeTravelReport::new(at, past, &now);
+
+        if self.args.json {
+            println!("{}", serde_json::to_string(&report)?);
             return Ok(());
         }
 
-        println!("📊 History Statistics:");
-        println!("{}", "─".repeat(50));
-        println!("Total kills: {}", stats.total_kills);
-        println!("Unique processes: {}", stats.unique_processes);
-        println!("Unique ports: {}", stats.unique_ports);
-        println!("Unique projects: {}", stats.unique_projects);
-        println!("Average kills per day: {:.1}", stats.average_kills_per_day);
+        let local = |t: chrono::DateTime<chrono::Utc>| {
+            t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()
+        };
+        let describe = |l: &Listener| {
+            let mut line = format!("Port {}: {} (PID {})", l.port, l.name, l.pid);
+            if let Some(project) = &l.project_name {
+                line.push_str(&format!(" [{}]", project));
+            }
+            if let Some(container) = &l.container_name {
+                line.push_str(&format!(" 🐳 {}", container));
+            }
+            line
+        };
+
+        println!("🕰️  Ports at {}:", local(report.at));
+        println!("{}", "─".repeat(60));
+        if !report.covered {
+            println!(
+                "⚠️  port-kill wasn't recording then; showing the last port map recorded, at {}",
+                local(report.recorded_at)
+            );
+        }
+        if report.then.is_empty() {
+            println!("ℹ️  Nothing was listening on the monitored ports");
+        }
+        for listener in &report.then {
+            let marker = if report.gone.contains(listener) {
+                "✗ gone now"
+            } else {
+                "✓ still running"
+            };
+            println!("  • {}  {}", describe(listener), marker);
+            if let Some(command_line) = &listener.command_line {
+                println!("      {}", command_line);
+            }
+        }
+        if !report.new.is_empty() {
+            println!();
+            println!("🆕 Listening now, not then:");
+            for listener in &report.new {
+                println!("  • {}", describe(listener));
+            }
+        }
+        Ok(())
+    }
+
+    /// `--timeline PORT`: when the port was occupied and by what over the last `--hours`
+    pub async fn show_port_timeline(&self, port: u16) -> Result<()> {
+        use crate::timeline::{self, Occupancy};
 
-        if let Some((name, count)) = &stats.most_killed_process {
-            println!("Most killed process: {} ({} times)", name, count);
-        }
+        let port_timeline = timeline::recent_port_timeline(port, self.args.hours)?;
 
-        if let Some((port, count)) = &stats.most_killed_port {
-            println!("Most killed port: {} ({} times)", port, count);
+        if self.args.json {
+            println!("{}", serde_json::to_string(&port_timeline)?);
+            return Ok(());
         }
 
-        if let Some((project, count)) = &stats.most_killed_project {
-            println!("Most killed project: {} ({} times)", project, count);
+        let columns: u64 = 48;
+        let step = (port_timeline.until - port_timeline.since).num_seconds() as u64 / columns;
+        println!(
+            "📈 Port {} over the last {}h (one column is {})",
+            port,
+            self.args.hours,
+            format_uptime(step)
+        );
+        println!("{}", "─".repeat(60));
+        if port_timeline
+            .spans
+            .iter()
+            .all(|span| span.occupancy == Occupancy::Unknown)
+        {
+            println!(
+                "ℹ️  Nothing was recorded in that time. The timeline is written while the tray, \
+                 console monitoring or the TUI is running"
+            );
+            return Ok(());
         }
-
-        if let Some(oldest) = &stats.oldest_kill {
-            println!("Oldest kill: {}", oldest.format("%Y-%m-%d %H:%M"));
+        for line in port_timeline.chart(columns as usize) {
+            println!("  {}", line);
         }
-
-        if let Some(newest) = &stats.newest_kill {
-            println!("Newest kill: {}", newest.format("%Y-%m-%d %H:%M"));
+        println!();
+        let local = |t: chrono::DateTime<chrono::Utc>| {
+            t.with_timezone(&chrono::Local).format("%m-%d %H:%M").to_string()
+        };
+        for span in &port_timeline.spans {
+            println!(
+                "  {} – {}  {:<9} {}",
+                local(span.from),
+                local(span.to),
+                format_uptime((span.to - span.from).num_seconds().max(0) as u64),
+                span.describe()
+            );
         }
-
         Ok(())
     }
 
@@ -1353,6 +2622,11 @@ impl ConsolePortKillApp {
             println!("✅ No issues detected! Your development workflow is running smoothly.");
         }
 
+        if self.args.apply {
+            println!();
+            crate::remediation::command(history, &self.args.get_reservation_file_path())?;
+        }
+
         Ok(())
     }
 
@@ -1387,6 +2661,194 @@ impl ConsolePortKillApp {
         }
     }
 
+    /// Guard several config profiles side by side (`--guard-mode --profile work,infra`),
+    /// each with its own ports, filters and policy, until Ctrl+C
+    pub async fn run_guard_profiles(args: Args, names: &[String]) -> Result<()> {
+        let config = crate::config::load()?.config;
+        let profiles = crate::profiles::select(&config, names)?;
+
+        // Each guard runs its monitoring loop until stopped
+        let mut running = Vec::new();
+        let mut guards = Vec::new();
+        for profile in profiles {
+            let app = Arc::new(Self::new(profile.args(&args))?);
+            let (name, guard_app) = (profile.name.clone(), app.clone());
+            guards.push(tokio::spawn(async move {
+                if let Err(e) = guard_app.start_port_guard().await {
+                    error!("Profile '{}': {:#}", name, e);
+                }
+            }));
+            println!(
+                "🛡️  Profile '{}' ({}): ports {}",
+                profile.name,
+                profile.config.policy.label(),
+                profile.describe_ports()
+            );
+            running.push((profile, app));
+        }
+
+        // Each profile reports its guard and listeners for --status and the tray
+        let reporters: Vec<_> = running
+            .iter()
+            .map(|(profile, app)| {
+                let (profile, app) = (profile.clone(), app.clone());
+                tokio::spawn(async move {
+                    loop {
+                        if let Ok(guard) = app.get_port_guard_status().await {
+                            let processes = app
+                                .process_monitor
+                                .lock()
+                                .await
+                                .scan_processes()
+                                .await
+                                .unwrap_or_default();
+                            let status = crate::profiles::ProfileStatus::new(&profile, guard, &processes);
+                            if let Err(e) = crate::profiles::write_status(&status) {
+                                log::warn!("{:#}", e);
+                            }
+                        }
+                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    }
+                })
+            })
+            .collect();
+
+        info!("🛡️  Port Guard is running {} profile(s). Press Ctrl+C to stop.", running.len());
+        tokio::signal::ctrl_c().await?;
+
+        for reporter in reporters {
+            reporter.abort();
+        }
+        for (profile, app) in &running {
+            app.stop_port_guard().await?;
+            crate::profiles::remove_status(&profile.name);
+        }
+        for guard in guards {
+            let _ = guard.await;
+        }
+        Ok(())
+    }
+
+    /// Print the guard profiles that are running, from their status files. Returns whether
+    /// any were shown.
+    fn show_profile_status() -> bool {
+        let statuses: Vec<_> = crate::profiles::read_statuses()
+            .into_iter()
+            .filter(|status| status.is_live())
+            .collect();
+        if statuses.is_empty() {
+            return false;
+        }
+
+        println!("🛡️  GUARD PROFILES");
+        println!();
+        for status in &statuses {
+            println!(
+                "   ✅ {} - {} (PID {})",
+                status.name,
+                status.policy.label(),
+                status.pid
+            );
+            println!("      Ports: {}", status.ports);
+            if status.listeners.is_empty() {
+                println!("      Listeners: none");
+            }
+            for (port, pid, name) in &status.listeners {
+                println!("      Port {}: {} (PID {})", port, name, pid);
+            }
+            if status.guard.conflicts_resolved > 0 {
+                println!("      Conflicts resolved: {}", status.guard.conflicts_resolved);
+            }
+            if status.guard.rate_limited {
+                println!("      ⚠️  Kill rate limit reached; kills are paused");
+            }
+        }
+        println!();
+        true
+    }
+
+    /// `--status` for a running daemon: its PID and memory. False when none is running.
+    fn show_daemon_status() -> bool {
+        let Some(pid) = crate::daemon::running_pid() else {
+            return false;
+        };
+        println!("👻 DAEMON");
+        println!();
+        println!("   ✅ Running (PID {})", pid);
+        match crate::memory::read_daemon_stats() {
+            Some(stats) => {
+                println!("      Memory: {}", stats.describe());
+                if stats.over_limit > 0 {
+                    println!("      ⚠️  Went over --max-rss {} time(s)", stats.over_limit);
+                }
+            }
+            None => println!("      Memory: not reported yet"),
+        }
+        println!();
+        true
+    }
+
+    /// `port-kill daemon`: guard (with `--guard-mode`) or monitor in the background until
+    /// Ctrl+C or SIGTERM, with a pid file so a second daemon refuses to start. Over
+    /// `--max-rss` it stops and starts itself again.
+    pub async fn run_daemon(mut args: Args) -> Result<()> {
+        if let Some(pid) = crate::daemon::running_pid() {
+            return Err(anyhow::anyhow!(
+                "A port-kill daemon is already running (PID {})",
+                pid
+            ));
+        }
+        crate::daemon::write_pid_file()?;
+        info!("👻 port-kill daemon started (PID {})", std::process::id());
+
+        let max_rss = args.max_rss_bytes();
+        let mut over_memory = false;
+        let result = if args.guard_mode {
+            match args.profile.clone() {
+                Some(names) => tokio::select! {
+                    result = Self::run_guard_profiles(args, &names) => result,
+                    _ = crate::daemon::shutdown_signal() => Ok(()),
+                    _ = crate::daemon::watch_memory(max_rss) => {
+                        over_memory = true;
+                        Ok(())
+                    }
+                },
+                None => {
+                    // The guard's monitoring loop runs until the daemon is stopped
+                    let app = Self::new(args)?;
+                    tokio::select! {
+                        result = app.start_port_guard() => result,
+                        _ = crate::daemon::shutdown_signal() => app.stop_port_guard().await,
+                        _ = crate::daemon::watch_memory(max_rss) => {
+                            over_memory = true;
+                            app.stop_port_guard().await
+                        }
+                    }
+                }
+            }
+        } else {
+            args.console = true;
+            let app = Self::new(args)?;
+            tokio::select! {
+                result = app.run() => result,
+                _ = crate::daemon::shutdown_signal() => Ok(()),
+                _ = crate::daemon::watch_memory(max_rss) => {
+                    over_memory = true;
+                    Ok(())
+                }
+            }
+        };
+
+        crate::daemon::remove_pid_file();
+        let _ = std::fs::remove_file(crate::memory::daemon_stats_path());
+        if over_memory {
+            warn!("👻 port-kill daemon is over --max-rss; restarting");
+            crate::memory::restart_self()?;
+        }
+        info!("👻 port-kill daemon stopped");
+        result
+    }
+
     /// Reserve a port
     pub async fn reserve_port(
         &self,
@@ -1459,6 +2921,18 @@ impl ConsolePortKillApp {
         );
 
         let audit_result = auditor.perform_audit(processes).await?;
+        if let Err(e) = SecurityAuditor::save_last_audit(&audit_result) {
+            log::warn!("Failed to save audit result: {}", e);
+        }
+        for finding in &audit_result.suspicious_processes {
+            crate::notify::send(crate::notify::Message::audit_finding(
+                finding.port,
+                finding.process_info.pid,
+                &finding.process_info.name,
+                &format!("{:?}", finding.risk_level),
+                &format!("{:?}", finding.suspicion_reason),
+            ));
+        }
 
         if self.args.json {
             // Output JSON for API consumption
@@ -1473,6 +2947,7 @@ impl ConsolePortKillApp {
 
     /// Execute command on remote host via SSH
     pub async fn execute_remote_command(&self, command: &str) -> Result<String> {
+        crate::offline::ensure_allowed(crate::offline::Channel::Remote)?;
         if let Some(remote_host) = &self.args.get_remote_host() {
             let ssh_command = format!("ssh {} '{}'", remote_host, command);
 
@@ -1494,10 +2969,77 @@ impl ConsolePortKillApp {
         }
     }
 
+    /// `--serve`: expose the scanner over HTTP until stopped
+    pub async fn serve_api(&self, addr: &str) -> Result<()> {
+        let addr = crate::api_server::parse_addr(addr)?;
+        crate::api_server::serve(
+            addr,
+            self.args.serve_token.clone(),
+            self.process_monitor(),
+            &self.args,
+        )
+        .await
+    }
+
+    /// `--linked`: the tunnels among the listeners about to be killed on `ports`
+    fn linked_tunnels(&self, ports: &[u16]) -> Vec<crate::tunnel::Tunnel> {
+        if !self.args.linked {
+            return Vec::new();
+        }
+        crate::process_monitor::get_processes_on_ports_multi(ports, &self.args)
+            .values()
+            .flatten()
+            .filter_map(crate::tunnel::tunnel_for)
+            .collect()
+    }
+
+    /// Stop the remote end of each tunnel that was just killed
+    fn stop_linked_endpoints(&self, tunnels: &[crate::tunnel::Tunnel]) {
+        for tunnel in tunnels {
+            // Keep stdout parseable for --json
+            let report = |line: String| {
+                if self.args.json {
+                    eprintln!("{}", line);
+                } else {
+                    println!("{}", line);
+                }
+            };
+            if let Some(workload) = tunnel.scaled_workload() {
+                let prompt = format!(
+                    "Port {}: scale {} to 0 replicas? This stops every replica, not only the forwarded pod.",
+                    tunnel.local_port, workload
+                );
+                if !crate::safe_mode::confirm(&prompt).unwrap_or(false) {
+                    report(format!("⏭️  Port {}: left {} running", tunnel.local_port, workload));
+                    continue;
+                }
+            }
+            match tunnel.stop_remote() {
+                Ok(()) => report(format!(
+                    "🔗 Port {}: stopped the remote end ({})",
+                    tunnel.local_port,
+                    tunnel.describe()
+                )),
+                Err(e) => report(format!(
+                    "⚠️  Port {}: could not stop the remote end ({}): {:#}",
+                    tunnel.local_port,
+                    tunnel.describe(),
+                    e
+                )),
+            }
+        }
+    }
+
     /// Run in remote mode - execute commands on remote host
     pub async fn run_remote_mode(&self, remote_host: &str) -> Result<()> {
+        crate::offline::ensure_allowed(crate::offline::Channel::Remote)?;
         println!("🌐 Remote Mode: Connecting to {}", remote_host);
 
+        // Ports given on their own are killed on the remote host, like locally
+        if !self.args.positional_ports.is_empty() {
+            return self.kill_remote_ports(remote_host).await;
+        }
+
         // Build the remote command
         let mut remote_command = String::from("./port-kill-console");
 
@@ -1541,6 +3083,48 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// `--remote HOST PORT...`: kill on the remote host, and with `--linked` close the
+    /// local ssh tunnels forwarding to those ports
+    async fn kill_remote_ports(&self, remote_host: &str) -> Result<()> {
+        let ports = &self.args.positional_ports;
+        let mut remote_command = String::from("./port-kill-console");
+        for port in ports {
+            remote_command.push_str(&format!(" {}", port));
+        }
+        if self.args.json {
+            remote_command.push_str(" --json");
+        }
+        println!("📡 Executing: {}", remote_command);
+        let output = self.execute_remote_command(&remote_command).await?;
+        print!("{}", output);
+
+        if !self.args.linked {
+            return Ok(());
+        }
+        let mut closed = std::collections::HashSet::new();
+        for &port in ports {
+            for tunnel in crate::tunnel::local_tunnels_to(remote_host, port) {
+                // One ssh process may carry several forwards
+                if !closed.insert(tunnel.pid) {
+                    continue;
+                }
+                match crate::process_monitor::kill_single_process(tunnel.pid, &self.args) {
+                    Ok(()) => println!(
+                        "🔗 Closed local tunnel on port {} (PID {}, {})",
+                        tunnel.local_port,
+                        tunnel.pid,
+                        tunnel.describe()
+                    ),
+                    Err(e) => println!(
+                        "⚠️  Could not close local tunnel on port {} (PID {}): {:#}",
+                        tunnel.local_port, tunnel.pid, e
+                    ),
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Display security audit results
     async fn display_audit_results(&self, result: &SecurityAuditResult) -> Result<()> {
         println!("🔒 SECURITY AUDIT RESULTS");
@@ -1631,6 +3215,22 @@ impl ConsolePortKillApp {
             println!("   Working Directory: {}", restart_info.working_directory);
         }
 
+        if self.args.supervise {
+            monitor.free_port_for_restart(port).await?;
+            drop(monitor);
+            let policy = crate::restart_manager::SupervisePolicy {
+                max_restarts: self.args.supervise_max_restarts,
+                ..Default::default()
+            };
+            println!(
+                "👀 Supervising port {}: restarting it up to {} time(s) if it dies (Ctrl+C to stop)",
+                port, policy.max_restarts
+            );
+            let mut restart_manager = crate::restart_manager::RestartManager::new()?;
+            return tokio::task::spawn_blocking(move || restart_manager.supervise(port, &policy))
+                .await?;
+        }
+
         // Perform the restart
         match monitor.restart_process_on_port(port).await {
             Ok(()) => {
@@ -1666,6 +3266,9 @@ impl ConsolePortKillApp {
                 println!("      Command: {}", restart_info.command.join(" "));
                 println!("      Working Dir: {}", restart_info.working_directory);
                 println!("      Last Restarted: {}", format_time_ago(restart_info.last_restarted));
+                for event in restart_info.events.iter().rev().take(5) {
+                    println!("      {} ago: {}", format_time_ago(event.at), event.kind);
+                }
                 println!();
             }
         }
@@ -1675,6 +3278,38 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// `--nice PORT ADJUSTMENT`: renice the processes on a port instead of killing them
+    pub async fn renice_port(&self, spec: &[String]) -> Result<()> {
+        let [port, adjustment] = spec else {
+            anyhow::bail!("--nice takes a port and an adjustment, e.g. --nice 3000 +10");
+        };
+        let port: u16 = port
+            .parse()
+            .with_context(|| format!("Invalid port '{}'", port))?;
+        let adjustment = crate::priority::parse_adjustment(adjustment)?;
+
+        let processes = crate::process_monitor::get_processes_on_ports_multi(&[port], &self.args)
+            .remove(&port)
+            .unwrap_or_default();
+        if processes.is_empty() {
+            println!("ℹ️  No process is listening on port {}", port);
+            return Ok(());
+        }
+        let mut monitor = self.process_monitor.lock().await;
+        for process in &processes {
+            let nice = monitor.renice_process(process, adjustment)?;
+            println!(
+                "🐢 Reniced {} (PID {}) on port {} by {}{}",
+                process.name,
+                process.pid,
+                port,
+                crate::priority::format_adjustment(adjustment),
+                nice.map(|n| format!(", now nice {}", n)).unwrap_or_default()
+            );
+        }
+        Ok(())
+    }
+
     /// Clear restart history for a specific port
     pub async fn clear_restart_history(&self, port: u16) -> Result<()> {
         let mut monitor = self.process_monitor.lock().await;
@@ -1702,6 +3337,20 @@ impl ConsolePortKillApp {
         let detector = ServiceDetector::new();
         let services = detector.discover_services()?;
 
+        let jupyter_servers = crate::jupyter::list_servers();
+        if !jupyter_servers.is_empty() {
+            println!("🪐 RUNNING JUPYTER SERVERS");
+            println!();
+            for server in &jupyter_servers {
+                println!("   Port {} (PID {})", server.port, server.pid);
+                println!("      URL: {}", server.browser_url());
+                if let Some(ref root_dir) = server.root_dir {
+                    println!("      Root Dir: {}", root_dir);
+                }
+                println!();
+            }
+        }
+
         if services.is_empty() {
             println!("ℹ️  No services detected in current directory");
             println!("💡 Tip: Run this command from a project directory containing:");
@@ -1709,6 +3358,10 @@ impl ConsolePortKillApp {
             println!("   • docker-compose.yml (Docker services)");
             println!("   • Procfile (Procfile processes)");
             println!("   • app.py/manage.py (Python apps)");
+            println!("   • go.mod, Cargo.toml (Go and Rust binaries)");
+            println!("   • build.gradle/pom.xml (Spring Boot), bin/rails (Rails), artisan (Laravel)");
+            println!("   • project.json (Nx targets)");
+            println!("   • a workspace root (npm/yarn/pnpm, Cargo, Nx or Turborepo)");
             return Ok(());
         }
 
@@ -1720,6 +3373,17 @@ impl ConsolePortKillApp {
             println!("   {} - {}", service.name, service.description);
             if let Some(port) = service.inferred_port {
                 println!("      Inferred Port: {}", port);
+                let sharing: Vec<&str> = services
+                    .iter()
+                    .filter(|other| other.name != service.name && other.inferred_port == Some(port))
+                    .map(|other| other.name.as_str())
+                    .collect();
+                if !sharing.is_empty() {
+                    println!("      ⚠️  Conflicts with: {}", sharing.join(", "));
+                }
+                if std::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, port)).is_err() {
+                    println!("      ⚠️  Port {} is already in use", port);
+                }
             }
             println!("      Working Dir: {}", service.working_directory.display());
             println!();
@@ -1736,7 +3400,16 @@ impl ConsolePortKillApp {
 
         println!("🚀 Starting service: {}...", service_name);
 
-        let detector = ServiceDetector::new();
+        // A service of the same name in the orchestration config lends its env and env_file
+        let config_path = std::path::Path::new(&self.args.config_file);
+        let env = if config_path.exists() {
+            crate::orchestrator::Orchestrator::load(config_path)?
+                .service_environment(service_name)?
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+        let detector = ServiceDetector::new().with_env(env);
         let services = detector.discover_services()?;
 
         // Find the service
@@ -1753,6 +3426,9 @@ impl ConsolePortKillApp {
         println!("   Starting: {}", service.description);
         if let Some(port) = service.inferred_port {
             println!("   Expected Port: {}", port);
+            if std::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, port)).is_err() {
+                println!("   ⚠️  Port {} is already in use; the service may fail to bind", port);
+            }
         }
         println!();
 
@@ -1782,8 +3458,23 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
-        create_sample_config(config_path)?;
-        println!("✅ Created sample configuration file: {}", config_path.display());
+        // A compose file next to the config: generate the services from it
+        let config_dir = config_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        if let Some(compose_path) = crate::compose::find(config_dir).into_iter().next() {
+            let count = crate::orchestrator::create_config_from_compose(config_path, &compose_path)?;
+            println!(
+                "✅ Created {} with {} service(s) from {}",
+                config_path.display(),
+                count,
+                compose_path.display()
+            );
+        } else {
+            create_sample_config(config_path)?;
+            println!("✅ Created sample configuration file: {}", config_path.display());
+        }
         println!();
         println!("📝 Edit the file to configure your services, then run:");
         println!("   port-kill --up     # Start all services");
@@ -1799,8 +3490,12 @@ impl ConsolePortKillApp {
         use std::path::Path;
 
         let config_path = Path::new(&self.args.config_file);
+        let showed_profiles = Self::show_profile_status();
 
         if !config_path.exists() {
+            if showed_profiles {
+                return Ok(());
+            }
             println!("❌ Configuration file not found: {}", config_path.display());
             println!("💡 Create one with: port-kill --init-config");
             return Ok(());
@@ -1817,6 +3512,10 @@ impl ConsolePortKillApp {
                 println!("✅ All services started successfully!");
                 println!();
                 self.show_orchestrator_status(&orchestrator).await?;
+
+                if orchestrator.wants_oom_supervision() {
+                    self.supervise_oom_restarts(&mut orchestrator).await?;
+                }
             }
             Err(e) => {
                 println!("❌ Failed to start services: {}", e);
@@ -1828,14 +3527,43 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// Keep `--up` in the foreground, restarting `restart_on_oom` services after an OOM kill
+    async fn supervise_oom_restarts(&self, orchestrator: &mut crate::orchestrator::Orchestrator) -> Result<()> {
+        println!("👀 Watching for OOM kills (restart_on_oom). Press Ctrl+C to stop watching; services keep running.");
+
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    println!();
+                    println!("👋 Stopped watching. Use --down to stop the services.");
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(std::time::Duration::from_secs(2)) => {
+                    match orchestrator.restart_oom_killed().await {
+                        Ok(restarted) => {
+                            for name in restarted {
+                                println!("💥 Service '{}' exceeded its memory limit and was restarted", name);
+                            }
+                        }
+                        Err(e) => println!("❌ Failed to restart service after OOM kill: {}", e),
+                    }
+                }
+            }
+        }
+    }
+
     /// Stop all services from config
     pub async fn orchestrate_down(&self) -> Result<()> {
         use crate::orchestrator::Orchestrator;
         use std::path::Path;
 
         let config_path = Path::new(&self.args.config_file);
+        let showed_profiles = Self::show_profile_status();
 
         if !config_path.exists() {
+            if showed_profiles {
+                return Ok(());
+            }
             println!("❌ Configuration file not found: {}", config_path.display());
             return Ok(());
         }
@@ -1863,8 +3591,12 @@ impl ConsolePortKillApp {
         use std::path::Path;
 
         let config_path = Path::new(&self.args.config_file);
+        let showed_profiles = Self::show_profile_status();
 
         if !config_path.exists() {
+            if showed_profiles {
+                return Ok(());
+            }
             println!("❌ Configuration file not found: {}", config_path.display());
             return Ok(());
         }
@@ -1885,14 +3617,41 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// `--logs <service> [--follow]`: the end of a service's captured output
+    pub async fn show_service_logs(&self, service_name: &str) -> Result<()> {
+        let path = crate::service_logs::log_path(service_name);
+        if !path.exists() {
+            println!("ℹ️  No logs for service '{}' yet ({})", service_name, path.display());
+            println!("💡 Output is captured for services started with --up or --restart-service");
+            return Ok(());
+        }
+
+        for line in crate::service_logs::last_lines(&path, crate::service_logs::TAIL_LINES)? {
+            println!("{}", line);
+        }
+        if self.args.follow {
+            tokio::task::spawn_blocking(move || {
+                crate::service_logs::follow(&path, &mut std::io::stdout())
+            })
+            .await??;
+        }
+
+        Ok(())
+    }
+
     /// Show status of all configured services
     pub async fn orchestrate_status(&self) -> Result<()> {
         use crate::orchestrator::Orchestrator;
         use std::path::Path;
 
         let config_path = Path::new(&self.args.config_file);
+        let showed_daemon = Self::show_daemon_status();
+        let showed_profiles = Self::show_profile_status();
 
         if !config_path.exists() {
+            if showed_daemon || showed_profiles {
+                return Ok(());
+            }
             println!("❌ Configuration file not found: {}", config_path.display());
             println!("💡 Create one with: port-kill --init-config");
             return Ok(());
@@ -1917,24 +3676,199 @@ impl ConsolePortKillApp {
         println!("📋 SERVICE STATUS");
         println!();
 
+        let mut crashed = 0;
         for status in statuses {
-            let status_icon = if status.running { "✅" } else { "⭕" };
-            let status_text = if status.running { "RUNNING" } else { "STOPPED" };
+            use crate::service_state::RunState;
+            let status_icon = match status.state {
+                RunState::Running => "✅",
+                RunState::Crashed => "💥",
+                RunState::Stopped | RunState::NotStarted => "⭕",
+            };
+            if status.state == RunState::Crashed {
+                crashed += 1;
+            }
 
-            println!("   {} {} - {}", status_icon, status.name, status_text);
+            println!("   {} {} - {}", status_icon, status.name, status.state);
             println!("      Command: {}", status.command);
 
             if let Some(port) = status.port {
-                println!("      Port: {}", port);
+                match status.port_bound {
+                    Some(true) => println!("      Port: {} (listening)", port),
+                    _ if status.running => println!("      Port: {} (⚠️  nothing listening)", port),
+                    _ => println!("      Port: {}", port),
+                }
             }
 
             if let Some(pid) = status.pid {
                 println!("      PID: {}", pid);
             }
 
+            if let Some(started_at) = status.started_at {
+                println!("      Uptime: {}", format_time_ago(started_at));
+            }
+
+            if status.state == RunState::Crashed {
+                println!("      💡 See why with: port-kill --logs {}", status.name);
+            }
+
+            if let Some(ref group) = status.group {
+                println!("      Processes: {} ({})", status.processes, group);
+            }
+
+            if status.mem_limit.is_some() || status.cpus.is_some() {
+                let mut limits = Vec::new();
+                if let Some(ref mem) = status.mem_limit {
+                    limits.push(format!("memory {}", mem));
+                }
+                if let Some(cpus) = status.cpus {
+                    limits.push(format!("{} CPU", cpus));
+                }
+                let in_process_group = status.group.as_deref().is_none_or(|g| g.starts_with("process group"));
+                let enforced = if status.running && in_process_group {
+                    " (not enforced - needs cgroup v2 or a Job Object)"
+                } else {
+                    ""
+                };
+                println!("      Limits: {}{}", limits.join(", "), enforced);
+            }
+
+            if let Some(ref usage) = status.usage {
+                use crate::cache::output::human_size;
+                if let Some(current) = usage.memory_bytes {
+                    match usage.memory_limit {
+                        Some(limit) => println!("      Memory: {} / {}", human_size(current), human_size(limit)),
+                        None => println!("      Memory: {}", human_size(current)),
+                    }
+                } else if let Some(peak) = usage.peak_memory_bytes {
+                    println!("      Peak memory: {}", human_size(peak));
+                }
+                if usage.memory_limit_hits > 0 {
+                    println!("      ⚠️  Reached its memory limit {} time(s)", usage.memory_limit_hits);
+                }
+                if usage.oom_kills > 0 {
+                    println!("      💥 {} process(es) OOM-killed for exceeding the memory limit", usage.oom_kills);
+                }
+                if usage.cpu_throttled > 0 {
+                    println!("      🐢 CPU-throttled in {} scheduler period(s)", usage.cpu_throttled);
+                }
+            }
+
             println!();
         }
 
+        if crashed > 0 {
+            println!("💡 Start the {} crashed service(s) again with: port-kill --restart-failed", crashed);
+        }
+
+        Ok(())
+    }
+
+    /// `--adopt`: register listening processes that belong to a configured or detected
+    /// service but weren't started by port-kill
+    pub async fn adopt_services(&self) -> Result<()> {
+        use crate::adopt::{match_processes, register, Candidate};
+        use crate::service_state::{RunState, ServiceState};
+
+        let state = ServiceState::load();
+        let mut candidates = Vec::new();
+        let config_path = std::path::Path::new(&self.args.config_file);
+        if config_path.exists() {
+            let orchestrator = crate::orchestrator::Orchestrator::load(config_path)?;
+            let mut names: Vec<&String> = orchestrator.config().services.keys().collect();
+            names.sort();
+            for name in names {
+                let config = &orchestrator.config().services[name];
+                let dir = orchestrator.working_dir(config);
+                candidates.push(Candidate {
+                    name: name.clone(),
+                    port: config.port.or_else(|| {
+                        crate::service_detector::ServiceDetector::infer_port_for_command(&config.command, &dir)
+                    }),
+                    dir,
+                    command: config.command.clone(),
+                });
+            }
+        }
+        for service in crate::service_detector::ServiceDetector::new().discover_services()? {
+            candidates.push(Candidate {
+                command: service.command_line(),
+                name: service.name,
+                port: service.inferred_port,
+                dir: service.working_directory,
+            });
+        }
+        // Services port-kill is already running need no adopting
+        candidates.retain(|c| state.run_state(&c.name, false) != RunState::Running);
+        if candidates.is_empty() {
+            println!("ℹ️  No services to adopt: nothing configured in {} or detected here", config_path.display());
+            return Ok(());
+        }
+
+        let mut ports = self.args.get_ports_to_monitor();
+        ports.extend(candidates.iter().filter_map(|c| c.port));
+        ports.sort_unstable();
+        ports.dedup();
+        let (_, mut listeners) = crate::process_monitor::get_processes_on_ports(&ports, &self.args);
+        crate::system_monitor::fill_working_directories(&mut listeners);
+        let processes: Vec<crate::types::ProcessInfo> = listeners
+            .into_values()
+            .map(|mut process| {
+                if process.command_line.is_none() {
+                    process.command_line = crate::system_monitor::process_command_line(process.pid)
+                        .map(|argv| argv.join(" "));
+                }
+                process
+            })
+            .collect();
+
+        let adoptions = match_processes(&candidates, &processes);
+        if adoptions.is_empty() {
+            println!("ℹ️  No running server matches a configured or detected service");
+            return Ok(());
+        }
+        register(&adoptions, &candidates)?;
+
+        println!("🤝 Adopted {} service(s):", adoptions.len());
+        for adoption in &adoptions {
+            println!(
+                "   {} - PID {} on port {} (matched by {})",
+                adoption.service,
+                adoption.pid,
+                adoption.port,
+                adoption.matched.join(", ")
+            );
+        }
+        println!();
+        println!("💡 port-kill --status shows them, port-kill --down stops them");
+
+        Ok(())
+    }
+
+    /// `--restart-failed`: start the services that crashed again
+    pub async fn orchestrate_restart_failed(&self) -> Result<()> {
+        use crate::orchestrator::Orchestrator;
+        use std::path::Path;
+
+        let config_path = Path::new(&self.args.config_file);
+        if !config_path.exists() {
+            println!("❌ Configuration file not found: {}", config_path.display());
+            return Ok(());
+        }
+
+        let mut orchestrator = Orchestrator::load(config_path)?;
+
+        match orchestrator.restart_failed().await {
+            Ok(restarted) if restarted.is_empty() => {
+                println!("✅ No crashed services to restart");
+            }
+            Ok(restarted) => {
+                println!("✅ Restarted {} crashed service(s): {}", restarted.len(), restarted.join(", "));
+            }
+            Err(e) => {
+                println!("❌ Failed to restart crashed services: {:#}", e);
+            }
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,323 @@
+//! `port-kill daemon`: monitoring or guarding with no terminal attached, plus installers that
+//! register it to start at login and keep running after the terminal closes: a systemd user
+//! unit on Linux, a LaunchAgent on macOS and a logon task on Windows. The flags given before
+//! `daemon` are what the service runs with:
+//!
+//! ```text
+//! port-kill --guard-mode --guard-ports 3000,8080 --auto-resolve daemon --install-service
+//! ```
+//!
+//! `--uninstall` removes the installed service along with everything else.
+
+use crate::cli::DaemonArgs;
+use crate::uninstall::{home_dir, LAUNCHD_LABEL, SERVICE_NAME};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Unit file, plist or task name of the daemon service
+pub fn service_name() -> String {
+    format!("{}-daemon", SERVICE_NAME)
+}
+
+pub fn launchd_label() -> String {
+    format!("{}.daemon", LAUNCHD_LABEL)
+}
+
+pub fn pid_file_path() -> PathBuf {
+    home_dir().join(".port-kill").join("daemon.pid")
+}
+
+/// Where the daemon logs when no `--log-file` is given
+pub fn log_file_path() -> PathBuf {
+    home_dir().join(".port-kill").join("daemon.log")
+}
+
+pub fn write_pid_file() -> Result<()> {
+    let path = pid_file_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, std::process::id().to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn remove_pid_file() {
+    let _ = std::fs::remove_file(pid_file_path());
+}
+
+/// PID of a daemon that is still running, from the pid file
+pub fn running_pid() -> Option<u32> {
+    let pid: u32 = std::fs::read_to_string(pid_file_path()).ok()?.trim().parse().ok()?;
+    // After a restart in place the pid file already holds our own PID
+    if pid == std::process::id() {
+        return None;
+    }
+    let mut system = sysinfo::System::new();
+    system.refresh_processes();
+    system.process(sysinfo::Pid::from_u32(pid)).map(|_| pid)
+}
+
+/// The arguments the service runs with: the command line up to the `daemon` subcommand,
+/// then `daemon`. Output flags that only make sense in a terminal are dropped.
+pub fn service_args(command_line: &[String]) -> Vec<String> {
+    let mut args: Vec<String> = command_line
+        .iter()
+        .skip(1)
+        .take_while(|arg| arg.as_str() != "daemon")
+        .filter(|arg| !matches!(arg.as_str(), "--verbose" | "--safe"))
+        .cloned()
+        .collect();
+    args.push("daemon".to_string());
+    args
+}
+
+/// `ExecStart` quoting: wrap arguments with spaces or quotes in double quotes
+pub(crate) fn systemd_quote(arg: &str) -> String {
+    if arg.is_empty() || arg.contains([' ', '"', '\\', '\'']) {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+pub fn systemd_unit(exe: &Path, args: &[String]) -> String {
+    let exec = std::iter::once(exe.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "[Unit]
+Description=port-kill daemon
+After=network.target
+
+[Service]
+ExecStart={}
+Restart=on-failure
+RestartSec=5
+
+[Install]
+WantedBy=default.target
+",
+        exec
+    )
+}
+
+pub(crate) fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn launchd_plist(exe: &Path, args: &[String], log: &Path) -> String {
+    let arguments: String = std::iter::once(exe.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+        .collect();
+    let log = xml_escape(&log.to_string_lossy());
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{}</string>
+    <key>StandardErrorPath</key>
+    <string>{}</string>
+</dict>
+</plist>
+"#,
+        launchd_label(),
+        arguments,
+        log,
+        log
+    )
+}
+
+/// Windows `/TR` command line
+pub(crate) fn windows_command(exe: &Path, args: &[String]) -> String {
+    std::iter::once(exe.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| if arg.contains(' ') { format!("\"{}\"", arg) } else { arg })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn run(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Where the service definition lives on this platform
+pub fn service_path() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        home_dir()
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", launchd_label()))
+    } else if cfg!(target_os = "windows") {
+        PathBuf::from(format!("Task Scheduler: {}", service_name()))
+    } else {
+        home_dir()
+            .join(".config/systemd/user")
+            .join(format!("{}.service", service_name()))
+    }
+}
+
+/// Write and register the service for the current binary with `args`, replacing any
+/// installed one, and start it. Returns where it was installed.
+pub fn install_service(args: &[String]) -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to find the port-kill binary")?;
+    let path = service_path();
+
+    if cfg!(target_os = "windows") {
+        let command = windows_command(&exe, args);
+        run(
+            "schtasks",
+            &["/Create", "/TN", &service_name(), "/TR", &command, "/SC", "ONLOGON", "/RL", "LIMITED", "/F"],
+        )?;
+        run("schtasks", &["/Run", "/TN", &service_name()])?;
+        return Ok(path);
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if cfg!(target_os = "macos") {
+        if path.exists() {
+            let _ = run("launchctl", &["unload", &path.to_string_lossy()]);
+        }
+        std::fs::write(&path, launchd_plist(&exe, args, &log_file_path()))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        run("launchctl", &["load", "-w", &path.to_string_lossy()])?;
+    } else {
+        std::fs::write(&path, systemd_unit(&exe, args))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        run("systemctl", &["--user", "daemon-reload"])?;
+        let unit = format!("{}.service", service_name());
+        run("systemctl", &["--user", "enable", &unit])?;
+        run("systemctl", &["--user", "restart", &unit])?;
+    }
+    Ok(path)
+}
+
+/// Stop and remove the daemon service. Returns false when none was installed.
+pub fn uninstall_service() -> Result<bool> {
+    if cfg!(target_os = "windows") {
+        return Ok(run("schtasks", &["/Delete", "/TN", &service_name(), "/F"]).is_ok());
+    }
+    let path = service_path();
+    if !path.exists() {
+        return Ok(false);
+    }
+    crate::uninstall::stop_service(&path);
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    if !cfg!(target_os = "macos") {
+        let _ = run("systemctl", &["--user", "daemon-reload"]);
+    }
+    Ok(true)
+}
+
+/// `daemon --install-service` / `--uninstall-service`
+pub fn manage_service(daemon: &DaemonArgs) -> Result<()> {
+    if daemon.uninstall_service {
+        if uninstall_service()? {
+            println!("🗑️  Removed the daemon service ({})", service_path().display());
+        } else {
+            println!("No daemon service is installed.");
+        }
+        return Ok(());
+    }
+    let command_line: Vec<String> = std::env::args().collect();
+    let args = service_args(&command_line);
+    let path = install_service(&args)?;
+    println!("✅ Installed the daemon service: {}", path.display());
+    println!("   Runs: port-kill {}", args.join(" "));
+    if !cfg!(target_os = "windows") {
+        println!("   Logs: {}", if cfg!(target_os = "macos") {
+            log_file_path().display().to_string()
+        } else {
+            format!("journalctl --user -u {}", service_name())
+        });
+    }
+    Ok(())
+}
+
+/// Resolves on Ctrl+C, or on SIGTERM from systemd/launchd stopping the service
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = term.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Check the daemon's own memory every [`crate::memory::CHECK_INTERVAL`] and report it for
+/// `--status`. Resolves once it is over `--max-rss`, so the daemon can restart itself.
+pub async fn watch_memory(max_rss: Option<u64>) {
+    let mut ticker = tokio::time::interval(crate::memory::CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let stats = crate::memory::check(max_rss);
+        if let Err(e) = crate::memory::write_daemon_stats(&stats) {
+            log::debug!("{:#}", e);
+        }
+        if stats.is_over_limit() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_definitions() {
+        let command_line: Vec<String> = ["port-kill", "--guard-mode", "--guard-ports", "3000,8080", "--verbose", "daemon", "--install-service"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let args = service_args(&command_line);
+        assert_eq!(args, vec!["--guard-mode", "--guard-ports", "3000,8080", "daemon"]);
+
+        let unit = systemd_unit(Path::new("/opt/port kill/port-kill"), &args);
+        assert!(unit.contains("ExecStart=\"/opt/port kill/port-kill\" --guard-mode --guard-ports 3000,8080 daemon\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+
+        let plist = launchd_plist(Path::new("/usr/local/bin/port-kill"), &args, Path::new("/tmp/daemon.log"));
+        assert!(plist.contains("<string>com.treadie.port-kill.daemon</string>"));
+        assert!(plist.contains("        <string>3000,8080</string>\n        <string>daemon</string>\n"));
+    }
+}
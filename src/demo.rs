@@ -0,0 +1,323 @@
+//! `--demo`: a fixed cast of realistic fake listeners for screenshots, talks and UI work on
+//! the tray, console and TUI. While it is on, scans return these processes instead of
+//! asking lsof/netstat, and kills only mark them dead — nothing is signalled, no hooks run
+//! and no history is written. Killed processes come back after [`RESPAWN`] so a demo can
+//! go on for as long as it needs to.
+
+use crate::types::ProcessInfo;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a killed demo process stays gone
+pub const RESPAWN: Duration = Duration::from_secs(30);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static KILLED: Mutex<Option<HashMap<i32, Instant>>> = Mutex::new(None);
+
+struct DemoProcess {
+    port: u16,
+    pid: i32,
+    name: &'static str,
+    command_line: &'static str,
+    /// Project directory under `~/code`
+    project: &'static str,
+    /// (container id, container name)
+    container: Option<(&'static str, &'static str)>,
+    /// Owner when it isn't the current user
+    user: Option<&'static str>,
+    cpu: f64,
+    memory_mb: u64,
+    uptime_secs: u64,
+    gpu_mb: Option<u64>,
+}
+
+const PROCESSES: [DemoProcess; 12] = [
+    DemoProcess {
+        port: 3000,
+        pid: 48211,
+        name: "node",
+        command_line: "node node_modules/.bin/next dev",
+        project: "storefront",
+        container: None,
+        user: None,
+        cpu: 4.2,
+        memory_mb: 384,
+        uptime_secs: 3 * 3600 + 420,
+        gpu_mb: None,
+    },
+    // A second copy of the same dev server, for --kill-duplicates
+    DemoProcess {
+        port: 3001,
+        pid: 51877,
+        name: "node",
+        command_line: "node node_modules/.bin/next dev -p 3001",
+        project: "storefront",
+        container: None,
+        user: None,
+        cpu: 3.1,
+        memory_mb: 352,
+        uptime_secs: 26 * 3600,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 3002,
+        pid: 39004,
+        name: "node",
+        command_line: "node node_modules/.bin/webpack serve --watch",
+        project: "legacy-frontend",
+        container: None,
+        user: None,
+        cpu: 78.4,
+        memory_mb: 1536,
+        uptime_secs: 4 * 86400,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 5173,
+        pid: 48390,
+        name: "node",
+        command_line: "node node_modules/.bin/vite",
+        project: "dashboard",
+        container: None,
+        user: None,
+        cpu: 1.1,
+        memory_mb: 212,
+        uptime_secs: 2 * 3600,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 8000,
+        pid: 47102,
+        name: "python3",
+        command_line: "python3 -m uvicorn app.main:app --reload --port 8000",
+        project: "api",
+        container: None,
+        user: None,
+        cpu: 2.5,
+        memory_mb: 148,
+        uptime_secs: 5 * 3600,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 8888,
+        pid: 44519,
+        name: "python3",
+        command_line: "python3 -m jupyter lab --no-browser",
+        project: "ml-notebooks",
+        container: None,
+        user: None,
+        cpu: 0.4,
+        memory_mb: 640,
+        uptime_secs: 2 * 86400,
+        gpu_mb: Some(2150),
+    },
+    DemoProcess {
+        port: 4000,
+        pid: 45733,
+        name: "ruby",
+        command_line: "ruby bin/rails server -p 4000",
+        project: "admin",
+        container: None,
+        user: None,
+        cpu: 0.9,
+        memory_mb: 264,
+        uptime_secs: 7 * 3600,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 8081,
+        pid: 43188,
+        name: "java",
+        command_line: "java -jar build/libs/payments-0.3.1.jar --server.port=8081",
+        project: "payments",
+        container: None,
+        user: None,
+        cpu: 7.8,
+        memory_mb: 896,
+        uptime_secs: 9 * 3600,
+        gpu_mb: None,
+    },
+    // The runaway process every demo needs
+    DemoProcess {
+        port: 9000,
+        pid: 50261,
+        name: "ingest-worker",
+        command_line: "./target/debug/ingest-worker --listen 0.0.0.0:9000",
+        project: "ingest",
+        container: None,
+        user: None,
+        cpu: 96.5,
+        memory_mb: 1228,
+        uptime_secs: 40 * 60,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 5432,
+        pid: 812,
+        name: "postgres",
+        command_line: "postgres -D /usr/local/var/postgresql@16",
+        project: "",
+        container: None,
+        user: Some("postgres"),
+        cpu: 0.8,
+        memory_mb: 96,
+        uptime_secs: 12 * 86400,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 6379,
+        pid: 2231,
+        name: "redis-server",
+        command_line: "redis-server 127.0.0.1:6379",
+        project: "",
+        container: None,
+        user: None,
+        cpu: 0.3,
+        memory_mb: 12,
+        uptime_secs: 12 * 86400,
+        gpu_mb: None,
+    },
+    DemoProcess {
+        port: 8080,
+        pid: 41027,
+        name: "docker-proxy",
+        command_line: "docker-proxy -proto tcp -host-port 8080 -container-port 80",
+        project: "",
+        container: Some(("3f9c2a1b7d4e", "storefront-nginx-1")),
+        user: Some("root"),
+        cpu: 0.2,
+        memory_mb: 24,
+        uptime_secs: 3 * 3600 + 600,
+        gpu_mb: None,
+    },
+];
+
+/// Turn demo mode on for the rest of the process
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn is_dead(pid: i32, now: Instant) -> bool {
+    KILLED
+        .lock()
+        .ok()
+        .and_then(|killed| killed.as_ref()?.get(&pid).copied())
+        .is_some_and(|at| now.duration_since(at) < RESPAWN)
+}
+
+fn to_process_info(demo: &DemoProcess, home: &str, user: &str, tick: u64) -> ProcessInfo {
+    // A little movement between scans so the TUI and sparklines look alive
+    let wobble = ((tick + demo.pid as u64) % 7) as f64 / 3.0 - 1.0;
+    let cpu = (demo.cpu * (1.0 + 0.12 * wobble)).clamp(0.0, 100.0);
+    let memory = demo.memory_mb * 1024 * 1024;
+    let working_directory = if demo.project.is_empty() {
+        "/".to_string()
+    } else {
+        format!("{}/code/{}", home, demo.project)
+    };
+
+    let mut info = ProcessInfo {
+        pid: demo.pid,
+        port: demo.port,
+        command: demo.name.to_string(),
+        name: demo.name.to_string(),
+        container_id: demo.container.map(|(id, _)| id.to_string()),
+        container_name: demo.container.map(|(_, name)| name.to_string()),
+        command_line: Some(demo.command_line.to_string()),
+        working_directory: Some(working_directory),
+        process_group: None,
+        project_name: None,
+        cpu_usage: Some(cpu),
+        memory_usage: Some(memory),
+        memory_percentage: Some(memory as f64 / (16.0 * 1024.0 * 1024.0 * 1024.0) * 100.0),
+        wsl_distro: None,
+        user: Some(demo.user.unwrap_or(user).to_string()),
+        uid: None,
+        started_at: Some(Utc::now() - chrono::Duration::seconds(demo.uptime_secs as i64)),
+        uptime_secs: Some(demo.uptime_secs),
+        ppid: Some(1),
+        gpu_memory: demo.gpu_mb.map(|mb| mb * 1024 * 1024),
+    };
+    info.process_group = info.determine_process_group();
+    info.project_name = info.extract_project_name();
+    info
+}
+
+/// The demo processes still alive, listening on one of `ports`
+fn alive_on(ports: &[u16]) -> Vec<ProcessInfo> {
+    let home = crate::uninstall::home_dir().to_string_lossy().to_string();
+    let user = crate::process_monitor::current_username().unwrap_or_else(|| "dev".to_string());
+    let now = Instant::now();
+    let tick = Utc::now().timestamp().unsigned_abs() / 2;
+    PROCESSES
+        .iter()
+        .filter(|demo| ports.contains(&demo.port) && !is_dead(demo.pid, now))
+        .map(|demo| to_process_info(demo, &home, &user, tick))
+        .collect()
+}
+
+/// What a scan of `ports` finds in demo mode
+pub fn processes(ports: &[u16]) -> HashMap<u16, ProcessInfo> {
+    alive_on(ports).into_iter().map(|p| (p.port, p)).collect()
+}
+
+/// Demo processes grouped by port, as returned by the multi-process scans
+pub fn processes_multi(ports: &[u16]) -> HashMap<u16, Vec<ProcessInfo>> {
+    let mut by_port: HashMap<u16, Vec<ProcessInfo>> = HashMap::new();
+    for process in alive_on(ports) {
+        by_port.entry(process.port).or_default().push(process);
+    }
+    by_port
+}
+
+/// "Kill" a demo process: it disappears from scans until it respawns
+pub fn kill(pid: i32) -> anyhow::Result<()> {
+    let now = Instant::now();
+    if !PROCESSES.iter().any(|demo| demo.pid == pid) || is_dead(pid, now) {
+        return Err(anyhow::anyhow!("No such process: {}", pid));
+    }
+    let mut killed = KILLED
+        .lock()
+        .map_err(|_| anyhow::anyhow!("demo state is poisoned"))?;
+    killed.get_or_insert_with(HashMap::new).insert(pid, now);
+    log::info!("[demo] Killed process {}", pid);
+    Ok(())
+}
+
+/// Kill every demo process on `ports`
+pub fn kill_ports(ports: &[u16]) -> anyhow::Result<()> {
+    for process in alive_on(ports) {
+        kill(process.pid)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_scan_and_kill() {
+        let scanned = processes(&[3000, 3002, 8080, 9999]);
+        assert_eq!(scanned.len(), 3);
+        let next = &scanned[&3000];
+        assert_eq!(next.process_group.as_deref(), Some("Node.js"));
+        assert_eq!(next.project_name.as_deref(), Some("storefront"));
+        assert_eq!(scanned[&8080].container_name.as_deref(), Some("storefront-nginx-1"));
+        assert!(scanned[&3002].cpu_usage.unwrap() > 50.0);
+
+        // Runs against a port no other test scans, since demo state is process-wide
+        assert!(processes(&[4000]).contains_key(&4000));
+        kill(45733).unwrap();
+        assert!(processes(&[4000]).is_empty());
+        assert!(kill(45733).is_err());
+        assert!(kill(1).is_err());
+    }
+}
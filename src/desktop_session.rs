@@ -0,0 +1,209 @@
+//! Desktop session detection for the Linux tray and clipboard. On Wayland the GTK tray only
+//! shows up through a StatusNotifier host on the session D-Bus (KDE, or GNOME with the
+//! AppIndicator extension); the old XEmbed tray icons don't exist there. Copy actions
+//! likewise need `wl-copy` on Wayland instead of `xclip`/`xsel`.
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// D-Bus name of the StatusNotifier watcher that tray hosts register
+const STATUS_NOTIFIER_WATCHER: &str = "org.kde.StatusNotifierWatcher";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionType {
+    Wayland,
+    X11,
+    /// A text console or SSH session with no display
+    Tty,
+    Unknown,
+}
+
+/// Classify a session from `XDG_SESSION_TYPE`, `WAYLAND_DISPLAY` and `DISPLAY`
+pub fn classify(
+    session_type: Option<&str>,
+    wayland_display: Option<&str>,
+    display: Option<&str>,
+) -> SessionType {
+    match session_type.map(str::to_ascii_lowercase).as_deref() {
+        Some("wayland") => return SessionType::Wayland,
+        Some("x11") => return SessionType::X11,
+        _ => {}
+    }
+    // XDG_SESSION_TYPE is missing under some launchers; the display variables still tell
+    if wayland_display.is_some_and(|d| !d.is_empty()) {
+        SessionType::Wayland
+    } else if display.is_some_and(|d| !d.is_empty()) {
+        SessionType::X11
+    } else if session_type.is_some() {
+        SessionType::Tty
+    } else {
+        SessionType::Unknown
+    }
+}
+
+/// The current session's type
+pub fn detect() -> SessionType {
+    let var = |name| std::env::var(name).ok();
+    classify(
+        var("XDG_SESSION_TYPE").as_deref(),
+        var("WAYLAND_DISPLAY").as_deref(),
+        var("DISPLAY").as_deref(),
+    )
+}
+
+/// Whether a StatusNotifier host is running on the session bus, or `None` when neither
+/// `gdbus` nor `dbus-send` is available to ask
+pub fn status_notifier_available() -> Option<bool> {
+    let gdbus = Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest",
+            "org.freedesktop.DBus",
+            "--object-path",
+            "/org/freedesktop/DBus",
+            "--method",
+            "org.freedesktop.DBus.NameHasOwner",
+            STATUS_NOTIFIER_WATCHER,
+        ])
+        .output();
+    if let Ok(output) = gdbus {
+        if output.status.success() {
+            return Some(String::from_utf8_lossy(&output.stdout).contains("true"));
+        }
+    }
+    let output = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--dest=org.freedesktop.DBus",
+            "--type=method_call",
+            "--print-reply",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus.NameHasOwner",
+            &format!("string:{}", STATUS_NOTIFIER_WATCHER),
+        ])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).contains("boolean true"))
+}
+
+/// Why the tray can't work in this session, if it can't
+pub fn tray_unavailable_reason(session: SessionType) -> Option<String> {
+    match session {
+        SessionType::Tty => Some("no graphical session (DISPLAY and WAYLAND_DISPLAY are unset)".into()),
+        SessionType::Wayland => match status_notifier_available() {
+            Some(false) => Some(
+                "Wayland session without a StatusNotifier host: on GNOME install the \
+                 AppIndicator and KStatusNotifierItem Support extension"
+                    .into(),
+            ),
+            _ => None,
+        },
+        SessionType::X11 | SessionType::Unknown => None,
+    }
+}
+
+/// Clipboard commands to try for a session, best first: (program, args)
+pub fn clipboard_commands(session: SessionType) -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbcopy", &[])];
+    }
+    if cfg!(target_os = "windows") {
+        return vec![("clip", &[])];
+    }
+    let wayland: [(&str, &[&str]); 1] = [("wl-copy", &[])];
+    let x11: [(&str, &[&str]); 2] = [
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+    match session {
+        SessionType::Wayland => wayland.into_iter().chain(x11).collect(),
+        _ => x11.into_iter().chain(wayland).collect(),
+    }
+}
+
+/// Put `text` on the clipboard with the first tool that works. Returns the tool used.
+pub fn copy_to_clipboard(text: &str) -> Result<&'static str> {
+    let session = detect();
+    for (program, args) in clipboard_commands(session) {
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .with_context(|| format!("Could not write to {}", program))?;
+        }
+        if child.wait().is_ok_and(|status| status.success()) {
+            return Ok(program);
+        }
+    }
+    anyhow::bail!(match session {
+        SessionType::Wayland => "No clipboard tool found: install wl-clipboard (wl-copy)",
+        _ => "No clipboard tool found: install xclip, xsel or wl-clipboard",
+    })
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| dir.join(program).is_file())
+    })
+}
+
+/// Session, tray host and clipboard lines for the Linux diagnostics
+pub fn print_diagnostics() {
+    let session = detect();
+    println!("\n🖥️  Desktop Session:");
+    println!("✅ Session type: {:?}", session);
+    match status_notifier_available() {
+        Some(true) => println!("✅ StatusNotifier host: running ({})", STATUS_NOTIFIER_WATCHER),
+        Some(false) if session == SessionType::Wayland => println!(
+            "❌ StatusNotifier host: not running (the tray icon can't appear on Wayland without one)"
+        ),
+        Some(false) => println!("⚠️  StatusNotifier host: not running (falling back to XEmbed tray icons)"),
+        None => println!("⚠️  StatusNotifier host: unknown (gdbus/dbus-send not found)"),
+    }
+    let tools: Vec<&str> = clipboard_commands(session)
+        .into_iter()
+        .map(|(program, _)| program)
+        .filter(|program| on_path(program))
+        .collect();
+    match tools.first() {
+        Some(tool) => println!("✅ Clipboard: {}", tool),
+        None if session == SessionType::Wayland => {
+            println!("❌ Clipboard: wl-copy not found (install wl-clipboard)")
+        }
+        None => println!("❌ Clipboard: no tool found (install xclip, xsel or wl-clipboard)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_session() {
+        assert_eq!(classify(Some("wayland"), None, None), SessionType::Wayland);
+        assert_eq!(classify(Some("x11"), Some("wayland-0"), Some(":0")), SessionType::X11);
+        // Launched without XDG_SESSION_TYPE, e.g. from a systemd user unit
+        assert_eq!(classify(None, Some("wayland-0"), Some(":0")), SessionType::Wayland);
+        assert_eq!(classify(None, None, Some(":0")), SessionType::X11);
+        assert_eq!(classify(Some("tty"), None, None), SessionType::Tty);
+        assert_eq!(classify(None, None, None), SessionType::Unknown);
+
+        if cfg!(target_os = "linux") {
+            assert_eq!(clipboard_commands(SessionType::Wayland)[0].0, "wl-copy");
+            assert_eq!(clipboard_commands(SessionType::X11)[0].0, "xclip");
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! `--report week|month`: a digest of the last week or month for posting to a team
+//! channel. It covers the kills (with the change from the period before), the top
+//! offenders, the busiest hours, the disk cache cleans reclaimed (from the audit log) and
+//! recommendations the period's history makes that aren't applied yet: processes worth
+//! ignoring, ports the guard keeps fighting over and processes that keep needing restarts.
+//!
+//! ```text
+//! port-kill-console --report week
+//! port-kill-console --report month --report-format markdown > digest.md
+//! port-kill-console --report week --json
+//! ```
+
+use crate::audit_log::{AuditAction, AuditFilter, AuditOutcome};
+use crate::cache::output::human_size;
+use crate::history_store::HistoryFilter;
+use crate::types::{PatternType, ProcessHistory};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Offenders and hours listed in a digest
+const TOP: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Week,
+    Month,
+}
+
+impl ReportPeriod {
+    pub fn duration(self) -> Duration {
+        match self {
+            ReportPeriod::Week => Duration::days(7),
+            ReportPeriod::Month => Duration::days(30),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ReportPeriod::Week => "week",
+            ReportPeriod::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Markdown,
+    Json,
+}
+
+/// A process killed repeatedly on one port during the period
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct DigestOffender {
+    pub process_name: String,
+    pub port: u16,
+    pub kills: usize,
+}
+
+/// `--report week|month --json`
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct Digest {
+    pub period: ReportPeriod,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub total_kills: usize,
+    /// Kills in the period of the same length before `since`
+    pub previous_kills: usize,
+    /// Most killed first
+    pub top_offenders: Vec<DigestOffender>,
+    /// `(hour, kills)`, busiest first; hours are UTC
+    pub busiest_hours: Vec<(u32, usize)>,
+    /// Caches deleted by `cache --clean` and scheduled cleans
+    pub caches_cleaned: usize,
+    pub reclaimed_bytes: u64,
+    pub recommendations: Vec<String>,
+}
+
+impl Digest {
+    /// The digest of the `period` up to `now`
+    pub fn build(history: &ProcessHistory, period: ReportPeriod, now: DateTime<Utc>) -> Self {
+        let since = now - period.duration();
+        let current = history.filtered(&HistoryFilter {
+            since: Some(since),
+            until: Some(now),
+            ..Default::default()
+        });
+        let previous = history.filtered(&HistoryFilter {
+            since: Some(since - period.duration()),
+            until: Some(since),
+            ..Default::default()
+        });
+
+        let top_offenders = current
+            .get_frequent_offenders(2)
+            .into_iter()
+            .take(TOP)
+            .map(|offender| DigestOffender {
+                process_name: offender.process_name,
+                port: offender.port,
+                kills: offender.kill_count,
+            })
+            .collect();
+        let mut busiest_hours: Vec<(u32, usize)> = current
+            .get_time_patterns()
+            .hour_distribution
+            .into_iter()
+            .collect();
+        busiest_hours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        busiest_hours.truncate(3);
+
+        let audit = AuditFilter {
+            since: Some(since),
+            action: Some(AuditAction::CacheClean),
+            ..Default::default()
+        };
+        let cleans: Vec<_> = crate::audit_log::read(&audit)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| entry.outcome == AuditOutcome::Success && entry.timestamp < now)
+            .collect();
+
+        Self {
+            period,
+            since,
+            until: now,
+            total_kills: current.get_statistics().total_kills,
+            previous_kills: previous.get_statistics().total_kills,
+            top_offenders,
+            busiest_hours,
+            caches_cleaned: cleans.len(),
+            reclaimed_bytes: cleans.iter().filter_map(|entry| entry.bytes).sum(),
+            recommendations: recommendations(&current),
+        }
+    }
+
+    /// "12 (up 4 from the week before)"
+    fn kills_trend(&self) -> String {
+        use std::cmp::Ordering;
+
+        let before = format!("the {} before", self.period.name());
+        let change = match self.total_kills.cmp(&self.previous_kills) {
+            Ordering::Greater => format!(
+                "up {} from {}",
+                self.total_kills - self.previous_kills,
+                before
+            ),
+            Ordering::Less => format!(
+                "down {} from {}",
+                self.previous_kills - self.total_kills,
+                before
+            ),
+            Ordering::Equal => format!("the same as {}", before),
+        };
+        format!("{} ({})", self.total_kills, change)
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Port report: {} to {}",
+            self.since.with_timezone(&chrono::Local).format("%Y-%m-%d"),
+            self.until.with_timezone(&chrono::Local).format("%Y-%m-%d")
+        )
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "📊 {}", self.title());
+        let _ = writeln!(text, "{}", "─".repeat(60));
+        let _ = writeln!(text, "Kills: {}", self.kills_trend());
+        if !self.top_offenders.is_empty() {
+            let _ = writeln!(text, "\n🔁 Top offenders:");
+            for offender in &self.top_offenders {
+                let _ = writeln!(
+                    text,
+                    "  • {} on port {}: {} kills",
+                    offender.process_name, offender.port, offender.kills
+                );
+            }
+        }
+        if !self.busiest_hours.is_empty() {
+            let _ = writeln!(text, "\n🕐 Busiest hours (UTC):");
+            for (hour, kills) in &self.busiest_hours {
+                let _ = writeln!(text, "  • {:02}:00: {} kills", hour, kills);
+            }
+        }
+        let _ = writeln!(
+            text,
+            "\n🧹 Cache cleans: {} cache(s), {} reclaimed",
+            self.caches_cleaned,
+            human_size(self.reclaimed_bytes)
+        );
+        if !self.recommendations.is_empty() {
+            let _ = writeln!(text, "\n💡 Recommendations:");
+            for recommendation in &self.recommendations {
+                let _ = writeln!(text, "  • {}", recommendation);
+            }
+        }
+        text
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "## {}\n", self.title());
+        let _ = writeln!(text, "**Kills:** {}\n", self.kills_trend());
+        if !self.top_offenders.is_empty() {
+            let _ = writeln!(text, "### Top offenders\n");
+            let _ = writeln!(text, "| Process | Port | Kills |");
+            let _ = writeln!(text, "|---------|------|-------|");
+            for offender in &self.top_offenders {
+                let _ = writeln!(
+                    text,
+                    "| `{}` | {} | {} |",
+                    offender.process_name, offender.port, offender.kills
+                );
+            }
+            text.push('\n');
+        }
+        if !self.busiest_hours.is_empty() {
+            let hours: Vec<String> = self
+                .busiest_hours
+                .iter()
+                .map(|(hour, kills)| format!("{:02}:00 ({})", hour, kills))
+                .collect();
+            let _ = writeln!(text, "**Busiest hours (UTC):** {}\n", hours.join(", "));
+        }
+        let _ = writeln!(
+            text,
+            "**Cache cleans:** {} cache(s), {} reclaimed\n",
+            self.caches_cleaned,
+            human_size(self.reclaimed_bytes)
+        );
+        if !self.recommendations.is_empty() {
+            let _ = writeln!(text, "### Recommendations\n");
+            for recommendation in &self.recommendations {
+                let _ = writeln!(text, "- {}", recommendation);
+            }
+        }
+        text
+    }
+}
+
+/// What the period's history suggests that isn't done yet
+fn recommendations(history: &ProcessHistory) -> Vec<String> {
+    let config = crate::config::load_or_default().config;
+    let suggestions = history.get_ignore_suggestions(2);
+    let mut recommendations: Vec<String> =
+        crate::ignore_list::new_suggestions(&suggestions, &config)
+            .into_iter()
+            .map(|(key, value)| {
+                format!(
+                    "Ignore {}, killed repeatedly (`--ignore add {} {}`)",
+                    value,
+                    crate::ignore_list::kind_label(key),
+                    value
+                )
+            })
+            .collect();
+    recommendations.extend(
+        history
+            .get_root_cause_analysis()
+            .patterns
+            .into_iter()
+            .filter(|pattern| {
+                matches!(
+                    pattern.pattern_type,
+                    PatternType::Automated | PatternType::AutoRestart
+                )
+            })
+            .map(|pattern| pattern.recommendation),
+    );
+    recommendations
+}
+
+/// Handle `--report week|month`
+pub fn command(history: &ProcessHistory, period: ReportPeriod, format: ReportFormat) -> Result<()> {
+    let digest = Digest::build(history, period, Utc::now());
+    match format {
+        ReportFormat::Json => println!("{}", serde_json::to_string(&digest)?),
+        ReportFormat::Markdown => print!("{}", digest.to_markdown()),
+        ReportFormat::Text => print!("{}", digest.to_text()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ProcessHistoryEntry, ProcessInfo};
+
+    #[test]
+    fn test_digest() {
+        let now = Utc::now();
+        let mut history = ProcessHistory::new(100);
+        let process = |name: &str, port: u16| ProcessInfo {
+            pid: 100,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        };
+        for (name, port, days_ago) in [
+            ("node", 3000, 1),
+            ("node", 3000, 2),
+            ("node", 3000, 3),
+            ("python", 8000, 4),
+            ("node", 3000, 10),
+        ] {
+            let mut entry = ProcessHistoryEntry::new(&process(name, port), "user".to_string());
+            entry.killed_at = now - Duration::days(days_ago);
+            history.add_entry(entry);
+        }
+
+        let digest = Digest::build(&history, ReportPeriod::Week, now);
+        assert_eq!((digest.total_kills, digest.previous_kills), (4, 1));
+        assert_eq!(
+            digest.top_offenders,
+            vec![DigestOffender {
+                process_name: "node".to_string(),
+                port: 3000,
+                kills: 3
+            }]
+        );
+        assert_eq!(digest.kills_trend(), "4 (up 3 from the week before)");
+        assert!(digest.to_markdown().contains("| `node` | 3000 | 3 |"));
+        assert!(digest.to_text().contains("node on port 3000: 3 kills"));
+
+        let month = Digest::build(&history, ReportPeriod::Month, now);
+        assert_eq!((month.total_kills, month.previous_kills), (5, 0));
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::process::Command;
+use std::time::Duration;
+
+/// A kill was refused by the OS because the process belongs to root/another user
+#[derive(Debug)]
+pub struct PermissionDenied {
+    pub pid: i32,
+}
+
+impl fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "permission denied killing PID {} (owned by root or another user); re-run with --sudo to retry with elevated privileges",
+            self.pid
+        )
+    }
+}
+
+impl std::error::Error for PermissionDenied {}
+
+/// Whether `err` is a permission failure that `--sudo` could get past
+pub fn is_permission_denied(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<PermissionDenied>().is_some()
+}
+
+/// Whether Windows refuses to let this process terminate `pid`, judged by the error code of
+/// opening it (ERROR_ACCESS_DENIED) rather than by taskkill's localized message
+#[cfg(target_os = "windows")]
+pub fn termination_denied(pid: i32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_ACCESS_DENIED};
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_TERMINATE};
+
+    let handle = unsafe { OpenProcess(PROCESS_TERMINATE, 0, pid as u32) };
+    if handle.is_null() {
+        return std::io::Error::last_os_error().raw_os_error() == Some(ERROR_ACCESS_DENIED as i32);
+    }
+    unsafe { CloseHandle(handle) };
+    false
+}
+
+/// Kill `pid` with elevated privileges: `sudo kill` on Unix (sudo asks for the password
+/// on the terminal), a UAC-elevated `taskkill` on Windows.
+#[cfg(not(target_os = "windows"))]
+pub fn kill_elevated(pid: i32) -> Result<()> {
+    let pid_str = pid.to_string();
+    println!(
+        "🔐 PID {} needs elevated privileges. Running: sudo kill -TERM {}",
+        pid, pid
+    );
+
+    let status = Command::new("sudo")
+        .args(["kill", "-TERM", &pid_str])
+        .status()
+        .context("Failed to run sudo")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("sudo kill -TERM {} failed", pid));
+    }
+
+    std::thread::sleep(Duration::from_millis(500));
+
+    let still_running = Command::new("ps")
+        .args(["-p", &pid_str])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+    if still_running {
+        println!("🔐 PID {} still running, sending SIGKILL via sudo", pid);
+        let status = Command::new("sudo")
+            .args(["kill", "-KILL", &pid_str])
+            .status()
+            .context("Failed to run sudo")?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("sudo kill -KILL {} failed", pid));
+        }
+    }
+
+    log::info!("Killed PID {} with elevated privileges", pid);
+    Ok(())
+}
+
+/// Kill `pid` with elevated privileges: `sudo kill` on Unix (sudo asks for the password
+/// on the terminal), a UAC-elevated `taskkill` on Windows.
+#[cfg(target_os = "windows")]
+pub fn kill_elevated(pid: i32) -> Result<()> {
+    println!(
+        "🔐 PID {} needs administrator rights. Approve the UAC prompt to continue...",
+        pid
+    );
+
+    let script = format!(
+        "Start-Process -FilePath taskkill -ArgumentList '/PID','{}','/F' -Verb RunAs -Wait -WindowStyle Hidden",
+        pid
+    );
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .status()
+        .context("Failed to run powershell for UAC elevation")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "Elevated taskkill for PID {} was cancelled or failed",
+            pid
+        ));
+    }
+
+    std::thread::sleep(Duration::from_millis(500));
+
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .context("Failed to run tasklist")?;
+    if String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()) {
+        return Err(anyhow::anyhow!(
+            "PID {} is still running after elevated taskkill",
+            pid
+        ));
+    }
+
+    log::info!("Killed PID {} with elevated privileges", pid);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_permission_denied() {
+        let err: anyhow::Error = PermissionDenied { pid: 42 }.into();
+        assert!(is_permission_denied(&err));
+        assert!(err.to_string().contains("--sudo"));
+
+        let other = anyhow::anyhow!("something else");
+        assert!(!is_permission_denied(&other));
+    }
+}
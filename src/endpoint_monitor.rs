@@ -71,7 +71,7 @@ impl EndpointMonitor {
             .clone();
 
         // Create HTTP client with timeout
-        let client = Client::builder()
+        let client = crate::offline::client_builder(crate::offline::Channel::Endpoint)?
             .timeout(Duration::from_secs(args.endpoint_timeout))
             .build()
             .context("Failed to create HTTP client")?;
@@ -99,7 +99,7 @@ impl EndpointMonitor {
         // Create process monitor
         let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
         let ports_to_scan = args.get_ports_to_monitor();
-        let process_monitor = ProcessMonitor::new_with_performance(
+        let mut process_monitor = ProcessMonitor::new_with_performance(
             update_sender,
             ports_to_scan,
             args.docker,
@@ -107,6 +107,7 @@ impl EndpointMonitor {
             None,
             args.performance,
         )?;
+        process_monitor.set_user_filter(args.user.clone());
 
         // Create security auditor if audit is enabled
         let security_auditor = if args.endpoint_include_audit {
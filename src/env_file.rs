@@ -0,0 +1,153 @@
+//! `.env` files for orchestrated services (`env_file` in the orchestration config), read the
+//! way docker-compose and dotenv read them:
+//!
+//! ```text
+//! # comment
+//! DATABASE_URL=postgres://localhost:5432/app
+//! export NODE_ENV=development
+//! GREETING="hello\nworld"     # double quotes: escapes and ${VAR} expand
+//! PATTERN='literal ${NOT}'    # single quotes: taken as written
+//! API_URL=http://localhost:${API_PORT}
+//! ```
+//!
+//! `${VAR}` refers to a key earlier in the file, or else to port-kill's own environment.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Read `path`, keeping the order of its keys
+pub fn load(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read env file {}", path.display()))?;
+    parse(&content).with_context(|| format!("Invalid env file {}", path.display()))
+}
+
+pub fn parse(content: &str) -> Result<Vec<(String, String)>> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .with_context(|| format!("line {}: expected KEY=VALUE", number + 1))?;
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+            anyhow::bail!("line {}: invalid variable name '{}'", number + 1, key);
+        }
+        let value = value.trim();
+        let known: HashMap<&str, &str> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        let value = if let Some(quoted) = value.strip_prefix('\'') {
+            let end = quoted
+                .find('\'')
+                .with_context(|| format!("line {}: unterminated quote", number + 1))?;
+            quoted[..end].to_string()
+        } else if let Some(quoted) = value.strip_prefix('"') {
+            let end = closing_quote(quoted)
+                .with_context(|| format!("line {}: unterminated quote", number + 1))?;
+            expand(&unescape(&quoted[..end]), &known)
+        } else {
+            // An unquoted value ends at a " #" comment
+            let value = match value.find(" #") {
+                Some(comment) => value[..comment].trim_end(),
+                None => value,
+            };
+            expand(value, &known)
+        };
+        vars.retain(|(k, _)| k != key);
+        vars.push((key.to_string(), value));
+    }
+    Ok(vars)
+}
+
+/// Index of the first `"` not escaped by a backslash
+fn closing_quote(quoted: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in quoted.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Replace `${VAR}` with earlier keys or the environment; unknown variables become empty
+fn expand(value: &str, known: &HashMap<&str, &str>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                match known.get(name) {
+                    Some(v) => out.push_str(v),
+                    None => out.push_str(&std::env::var(name).unwrap_or_default()),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse(
+            r#"
+# database
+export DB_PORT=5432
+DATABASE_URL=postgres://localhost:${DB_PORT}/app   # inline comment
+GREETING="hello\n\"world\""
+PATTERN='literal ${DB_PORT}'
+EMPTY=
+DB_PORT=6543
+"#,
+        )
+        .unwrap();
+        let vars: HashMap<String, String> = vars.into_iter().collect();
+        assert_eq!(vars["DATABASE_URL"], "postgres://localhost:5432/app");
+        assert_eq!(vars["GREETING"], "hello\n\"world\"");
+        assert_eq!(vars["PATTERN"], "literal ${DB_PORT}");
+        assert_eq!(vars["EMPTY"], "");
+        // The last assignment wins
+        assert_eq!(vars["DB_PORT"], "6543");
+
+        assert!(parse("NOT A VARIABLE").is_err());
+        assert!(parse("KEY=\"open").is_err());
+    }
+}
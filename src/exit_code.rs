@@ -0,0 +1,71 @@
+//! Documented exit codes, so CI pipelines, Makefiles and scripts can branch on the outcome.
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Success / nothing found |
+//! | 1 | General error (bad arguments, I/O, ...) |
+//! | 2 | Processes found (`--once`, `--fail-if-occupied`) |
+//! | 3 | One or more processes could not be killed |
+//! | 4 | The port scan itself failed, so "nothing found" can't be trusted |
+//! | 5 | Timed out (`--wait-free`, `--wait-open`) |
+
+use std::fmt;
+
+pub const SUCCESS: i32 = 0;
+pub const ERROR: i32 = 1;
+pub const PROCESSES_FOUND: i32 = 2;
+pub const KILL_FAILED: i32 = 3;
+pub const SCAN_FAILED: i32 = 4;
+pub const TIMED_OUT: i32 = 5;
+
+/// Error for a kill operation where some processes survived
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillFailed {
+    pub failed: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for KillFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to kill {} of {} process(es)",
+            self.failed, self.total
+        )
+    }
+}
+
+impl std::error::Error for KillFailed {}
+
+/// Exit with `code` once pending webhook notifications (e.g. about the failed kill) are out
+pub fn exit(code: i32) -> ! {
+    crate::webhook::flush(crate::webhook::FLUSH_TIMEOUT);
+    std::process::exit(code)
+}
+
+/// Exit with [`KILL_FAILED`] if a kill operation left processes behind; other errors propagate
+pub fn exit_on_kill_failure(result: anyhow::Result<()>) -> anyhow::Result<()> {
+    if let Err(ref e) = result {
+        if e.downcast_ref::<KillFailed>().is_some() {
+            eprintln!("❌ {:#}", e);
+            exit(KILL_FAILED);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_failed_is_recognisable_through_anyhow() {
+        let error = anyhow::Error::new(KillFailed {
+            failed: 1,
+            total: 3,
+        });
+        assert_eq!(error.to_string(), "Failed to kill 1 of 3 process(es)");
+        assert!(error.downcast_ref::<KillFailed>().is_some());
+        assert!(exit_on_kill_failure(Err(anyhow::anyhow!("bad config"))).is_err());
+    }
+}
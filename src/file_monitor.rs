@@ -182,6 +182,13 @@ impl FileMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            wsl_distro: None,
+                            user: None,
+                            uid: None,
+                            started_at: None,
+                            uptime_secs: None,
+                            ppid: None,
+                            gpu_memory: None,
                         });
                     }
                 }
@@ -270,6 +277,13 @@ impl FileMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        wsl_distro: None,
+                        user: None,
+                        uid: None,
+                        started_at: None,
+                        uptime_secs: None,
+                        ppid: None,
+                        gpu_memory: None,
                     });
                 }
             }
@@ -318,6 +332,13 @@ impl FileMonitor {
                                 cpu_usage: None,
                                 memory_usage: None,
                                 memory_percentage: None,
+                                wsl_distro: None,
+                                user: None,
+                                uid: None,
+                                started_at: None,
+                                uptime_secs: None,
+                                ppid: None,
+                                gpu_memory: None,
                             });
                         }
                     }
@@ -362,6 +383,13 @@ impl FileMonitor {
                                 cpu_usage: None,
                                 memory_usage: None,
                                 memory_percentage: None,
+                                wsl_distro: None,
+                                user: None,
+                                uid: None,
+                                started_at: None,
+                                uptime_secs: None,
+                                ppid: None,
+                                gpu_memory: None,
                             });
                         }
                     }
@@ -418,6 +446,13 @@ impl FileMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        wsl_distro: None,
+                        user: None,
+                        uid: None,
+                        started_at: None,
+                        uptime_secs: None,
+                        ppid: None,
+                        gpu_memory: None,
                     });
                 }
             }
@@ -0,0 +1,105 @@
+//! GPU memory per process, so ML dev servers sitting on VRAM can be spotted and freed.
+//!
+//! NVIDIA GPUs are queried through `nvidia-smi` (Linux, Windows and WSL). Metal on macOS
+//! has no per-process GPU memory accounting, so listeners there never show GPU usage.
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once `nvidia-smi` turned out to be missing, so scans stop spawning it
+static NVIDIA_SMI_MISSING: AtomicBool = AtomicBool::new(false);
+
+/// GPU memory in bytes held by each process, summed across GPUs. Empty when no
+/// supported GPU (or driver tooling) is present.
+pub fn gpu_memory_by_pid() -> HashMap<i32, u64> {
+    if NVIDIA_SMI_MISSING.load(Ordering::Relaxed) {
+        return HashMap::new();
+    }
+
+    let output = Command::new("nvidia-smi")
+        .args([
+            "--query-compute-apps=pid,used_memory",
+            "--format=csv,noheader,nounits",
+        ])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+        }
+        Ok(output) => {
+            log::debug!(
+                "nvidia-smi failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            HashMap::new()
+        }
+        Err(e) => {
+            if e.kind() == ErrorKind::NotFound {
+                NVIDIA_SMI_MISSING.store(true, Ordering::Relaxed);
+            }
+            log::debug!("nvidia-smi unavailable: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// True if GPU usage can be reported on this machine
+pub fn is_available() -> bool {
+    if NVIDIA_SMI_MISSING.load(Ordering::Relaxed) {
+        return false;
+    }
+    let available = Command::new("nvidia-smi")
+        .arg("-L")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !available {
+        NVIDIA_SMI_MISSING.store(true, Ordering::Relaxed);
+    }
+    available
+}
+
+/// Parse `pid, used_memory` lines (MiB). Rows reporting `[N/A]`, as seen under WSL and
+/// some Windows driver modes, are skipped.
+pub fn parse_nvidia_smi(output: &str) -> HashMap<i32, u64> {
+    let mut usage: HashMap<i32, u64> = HashMap::new();
+    for line in output.lines() {
+        let mut fields = line.split(',').map(str::trim);
+        let (Some(pid), Some(used_mib)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if let (Ok(pid), Ok(used_mib)) = (pid.parse::<i32>(), used_mib.parse::<u64>()) {
+            *usage.entry(pid).or_default() += used_mib * 1024 * 1024;
+        }
+    }
+    usage
+}
+
+/// Short human-readable amount, e.g. `5.2GB` or `740MB`
+pub fn format_gpu_memory(bytes: u64) -> String {
+    let mb = bytes as f64 / 1024.0 / 1024.0;
+    if mb >= 1024.0 {
+        format!("{:.1}GB", mb / 1024.0)
+    } else {
+        format!("{:.0}MB", mb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvidia_smi_sums_gpus_and_skips_unavailable() {
+        let output = "4242, 5120\n4242, 1024\n777, [N/A]\n\n1001, 300\n";
+        let usage = parse_nvidia_smi(output);
+        assert_eq!(usage.get(&4242), Some(&(6144 * 1024 * 1024)));
+        assert_eq!(usage.get(&1001), Some(&(300 * 1024 * 1024)));
+        assert!(!usage.contains_key(&777));
+        assert_eq!(format_gpu_memory(6144 * 1024 * 1024), "6.0GB");
+        assert_eq!(format_gpu_memory(300 * 1024 * 1024), "300MB");
+    }
+}
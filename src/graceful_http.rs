@@ -0,0 +1,234 @@
+//! Graceful shutdown for HTTP dev servers. Before a matching listener is signalled it is
+//! probed with a `Connection: close` request; if it speaks HTTP, the configured shutdown
+//! endpoints are tried and open client connections get time to drain. Rules live next to
+//! the hooks in `~/.port-kill/config.toml` or a project's `.portkill.toml`:
+//!
+//! ```toml
+//! [[graceful_http]]
+//! ports = [3000, 8000]                        # selector: any of these ports (empty = any port)
+//! endpoints = ["/shutdown", "/quitquitquit"]  # tried in order until one answers 2xx
+//! method = "POST"
+//! drain = 5                                   # seconds to wait for in-flight connections
+//! grace = 5                                   # seconds to wait for exit after an endpoint accepted
+//! ```
+
+use crate::hooks::PreKill;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+fn default_endpoints() -> Vec<String> {
+    vec!["/shutdown".to_string(), "/quitquitquit".to_string()]
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_drain() -> u64 {
+    5
+}
+
+fn default_grace() -> u64 {
+    5
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GracefulHttp {
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    #[serde(default = "default_endpoints")]
+    pub endpoints: Vec<String>,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default = "default_drain")]
+    pub drain: u64,
+    #[serde(default = "default_grace")]
+    pub grace: u64,
+}
+
+impl GracefulHttp {
+    pub fn matches(&self, port: u16) -> bool {
+        self.ports.is_empty() || self.ports.contains(&port)
+    }
+}
+
+/// Status code from an HTTP status line such as `HTTP/1.1 204 No Content`
+pub fn parse_status_line(line: &str) -> Option<u16> {
+    let mut parts = line.split_whitespace();
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+fn connect(port: u16) -> Option<TcpStream> {
+    [
+        SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+        SocketAddr::from((Ipv6Addr::LOCALHOST, port)),
+    ]
+    .iter()
+    .find_map(|addr| TcpStream::connect_timeout(addr, REQUEST_TIMEOUT).ok())
+}
+
+/// Send one `Connection: close` request to localhost and return the response status,
+/// or `None` if nothing answered with HTTP
+pub fn request(port: u16, method: &str, path: &str) -> Option<u16> {
+    let mut stream = connect(port)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost:{}\r\nContent-Length: 0\r\nConnection: close\r\nUser-Agent: port-kill\r\n\r\n",
+        method, path, port
+    );
+    stream.write_all(request.as_bytes()).ok()?;
+
+    let mut buf = [0u8; 512];
+    let mut response = Vec::new();
+    while !response.contains(&b'\n') && response.len() < 4096 {
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => response.extend_from_slice(&buf[..n]),
+        }
+    }
+    let text = String::from_utf8_lossy(&response);
+    parse_status_line(text.lines().next()?)
+}
+
+/// True if the listener on `port` answers HTTP
+pub fn is_http(port: u16) -> bool {
+    request(port, "HEAD", "/").is_some()
+}
+
+fn wait_for_exit(pid: i32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if !crate::hooks::is_running(pid) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    false
+}
+
+/// Wait until no client connections are left on `port`, or `timeout` passes
+fn drain(port: u16, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let Some(active) = crate::connections::active_connections_on(port) else {
+            return;
+        };
+        if Instant::now() >= deadline {
+            log::warn!(
+                "Port {} still has {} after {}s; signalling anyway",
+                port,
+                active.summary(),
+                timeout.as_secs()
+            );
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+}
+
+/// Ask the HTTP server on `port` to shut itself down, then let in-flight requests drain.
+/// Returns [`PreKill::Exited`] if the process went away on its own.
+pub fn shutdown(rules: &[GracefulHttp], pid: i32, port: Option<u16>) -> PreKill {
+    let Some(port) = port else {
+        return PreKill::Proceed;
+    };
+    let Some(rule) = rules.iter().find(|r| r.matches(port)) else {
+        return PreKill::Proceed;
+    };
+    if !is_http(port) {
+        log::debug!("Port {} doesn't answer HTTP; skipping graceful shutdown", port);
+        return PreKill::Proceed;
+    }
+
+    for endpoint in &rule.endpoints {
+        match request(port, &rule.method, endpoint) {
+            Some(status) if (200..300).contains(&status) => {
+                log::info!(
+                    "{} {} on port {} returned {}; waiting for PID {} to exit",
+                    rule.method,
+                    endpoint,
+                    port,
+                    status,
+                    pid
+                );
+                if wait_for_exit(pid, Duration::from_secs(rule.grace)) {
+                    log::info!("PID {} shut down gracefully", pid);
+                    return PreKill::Exited;
+                }
+                break;
+            }
+            Some(status) => log::debug!("{} {} on port {} returned {}", rule.method, endpoint, port, status),
+            // The server may have stopped accepting connections on its way out
+            None if !crate::hooks::is_running(pid) => return PreKill::Exited,
+            None => {}
+        }
+    }
+
+    drain(port, Duration::from_secs(rule.drain));
+    PreKill::Proceed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_parse_rules_and_status_line() {
+        let config: crate::config::ConfigLayer = toml::from_str(
+            r#"
+            [[graceful_http]]
+            ports = [3000]
+            drain = 0
+
+            [[graceful_http]]
+            endpoints = ["/admin/stop"]
+            method = "GET"
+            "#,
+        )
+        .unwrap();
+        let rules = config.graceful_http;
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].endpoints, default_endpoints());
+        assert_eq!(rules[0].method, "POST");
+        assert!(rules[0].matches(3000));
+        assert!(!rules[0].matches(8080));
+        assert!(rules[1].matches(8080));
+        assert_eq!(rules[1].grace, 5);
+
+        assert_eq!(parse_status_line("HTTP/1.1 204 No Content"), Some(204));
+        assert_eq!(parse_status_line("HTTP/1.0 404"), Some(404));
+        assert_eq!(parse_status_line("SSH-2.0-OpenSSH_9.0"), None);
+        assert_eq!(parse_status_line(""), None);
+    }
+
+    #[test]
+    fn test_request_reads_status_from_local_server() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let n = stream.read(&mut buf).unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_string();
+            stream
+                .write_all(b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            request
+        });
+
+        assert_eq!(request(port, "POST", "/shutdown"), Some(202));
+        let received = server.join().unwrap();
+        assert!(received.starts_with("POST /shutdown HTTP/1.1\r\n"));
+        assert!(received.contains("Connection: close"));
+    }
+}
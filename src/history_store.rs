@@ -0,0 +1,758 @@
+//! The kill history in SQLite (`~/.port-kill/history.db`). Each entry is a row with its
+//! time, port, process, group and project in indexed columns and the full entry as JSON,
+//! so statistics, offenders and time patterns are queries over the whole history instead
+//! of a pass over what fits in memory. Nothing is dropped unless the `[history]` table of
+//! the config files asks for it:
+//!
+//! ```toml
+//! [history]
+//! keep_days = 365                 # prune entries older than a year
+//! max_entries = 100000            # and keep at most this many
+//! max_entries_per_project = 5000  # and at most this many per project
+//!
+//! [history.projects]              # quotas for single projects, by project name
+//! infra = 500
+//! ```
+//!
+//! The retention is applied whenever the store is opened; `--history prune` applies it on
+//! demand, reports what went and compacts the database file.
+//!
+//! `--show-history`, `--show-stats`, `--show-offenders`, `--show-patterns` and
+//! `--show-suggestions` can be narrowed with a [`HistoryFilter`] (`--since`, `--until`,
+//! `--port`, `--project`, `--group`, `--limit`), which becomes the WHERE clause of the
+//! queries.
+//!
+//! The first time the store is opened, `~/.port-kill-history.json` (the format before the
+//! store) is imported; `--import-history <file>` imports another such file.
+
+use crate::cli::Args;
+use crate::types::{FrequentOffender, HistoryStatistics, ProcessHistoryEntry, TimePatterns};
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// How much history is kept, from `[history]` in the config files
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HistoryRetention {
+    /// Entries older than this many days are pruned
+    pub keep_days: Option<u32>,
+    /// Only the newest this many entries are kept
+    pub max_entries: Option<usize>,
+    /// Only the newest this many entries of each project are kept
+    pub max_entries_per_project: Option<usize>,
+    /// Quotas for single projects, overriding `max_entries_per_project`
+    pub projects: HashMap<String, usize>,
+}
+
+impl HistoryRetention {
+    /// Overlay the project file's retention: quotas are combined, the other settings replaced
+    pub fn merge(mut self, overlay: HistoryRetention) -> HistoryRetention {
+        self.keep_days = overlay.keep_days.or(self.keep_days);
+        self.max_entries = overlay.max_entries.or(self.max_entries);
+        self.max_entries_per_project = overlay
+            .max_entries_per_project
+            .or(self.max_entries_per_project);
+        self.projects.extend(overlay.projects);
+        self
+    }
+
+    /// The quota for `project`, if it has one
+    fn project_quota(&self, project: &str) -> Option<usize> {
+        self.projects
+            .get(project)
+            .copied()
+            .or(self.max_entries_per_project)
+    }
+}
+
+/// What one prune removed, by the rule that removed it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Older than `keep_days`
+    pub expired: usize,
+    /// Over a project's quota
+    pub over_project_quota: usize,
+    /// Over `max_entries`
+    pub over_max_entries: usize,
+}
+
+impl PruneReport {
+    pub fn total(&self) -> usize {
+        self.expired + self.over_project_quota + self.over_max_entries
+    }
+}
+
+/// The part of the history the history commands look at
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub port: Option<u16>,
+    pub project: Option<String>,
+    /// Process group, e.g. `Node.js`
+    pub group: Option<String>,
+    /// Only the newest this many of the entries the rest matches
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    pub fn from_args(args: &Args) -> Result<Self> {
+        let when = |when: &Option<String>| {
+            when.as_deref()
+                .map(|when| crate::timeline::parse_when(when, chrono::Local::now()))
+                .transpose()
+        };
+        Ok(Self {
+            since: when(&args.history_since)?,
+            until: when(&args.history_until)?,
+            port: args.history_port,
+            project: args.history_project.clone(),
+            group: args.history_group.clone(),
+            limit: args.history_limit,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether `entry` matches, leaving `limit` aside. Projects and groups are compared
+    /// ignoring case.
+    pub fn matches(&self, entry: &ProcessHistoryEntry) -> bool {
+        let same = |wanted: &Option<String>, value: &Option<String>| {
+            wanted.as_ref().is_none_or(|wanted| {
+                value
+                    .as_ref()
+                    .is_some_and(|value| value.eq_ignore_ascii_case(wanted))
+            })
+        };
+        self.since.is_none_or(|since| entry.killed_at >= since)
+            && self.until.is_none_or(|until| entry.killed_at < until)
+            && self.port.is_none_or(|port| entry.port == port)
+            && same(&self.project, &entry.project_name)
+            && same(&self.group, &entry.process_group)
+    }
+
+    /// The matching entries of `entries` (oldest first), the newest `limit` of them
+    pub fn apply(&self, entries: &[ProcessHistoryEntry]) -> Vec<ProcessHistoryEntry> {
+        let matching: Vec<_> = entries.iter().filter(|e| self.matches(e)).collect();
+        let skip = self
+            .limit
+            .map_or(0, |limit| matching.len().saturating_sub(limit));
+        matching.into_iter().skip(skip).cloned().collect()
+    }
+
+    /// The filter as an SQL condition on `entries`, with its parameters (`?` placeholders)
+    fn sql(&self) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(since) = self.since {
+            conditions.push("killed_at >= ?");
+            values.push(Value::Text(timestamp(since)));
+        }
+        if let Some(until) = self.until {
+            conditions.push("killed_at < ?");
+            values.push(Value::Text(timestamp(until)));
+        }
+        if let Some(port) = self.port {
+            conditions.push("port = ?");
+            values.push(Value::Integer(port as i64));
+        }
+        if let Some(project) = &self.project {
+            conditions.push("project_name = ? COLLATE NOCASE");
+            values.push(Value::Text(project.clone()));
+        }
+        if let Some(group) = &self.group {
+            conditions.push("process_group = ? COLLATE NOCASE");
+            values.push(Value::Text(group.clone()));
+        }
+        let condition = if conditions.is_empty() {
+            "1".to_string()
+        } else {
+            conditions.join(" AND ")
+        };
+        match self.limit {
+            Some(limit) => {
+                let doubled = values.iter().chain(&values).cloned().collect();
+                (
+                    format!(
+                        "{condition} AND id IN (SELECT id FROM entries WHERE {condition} \
+                         ORDER BY id DESC LIMIT {limit})"
+                    ),
+                    doubled,
+                )
+            }
+            None => (condition, values),
+        }
+    }
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    killed_at TEXT NOT NULL,
+    port INTEGER NOT NULL,
+    process_name TEXT NOT NULL,
+    process_group TEXT,
+    project_name TEXT,
+    is_kill INTEGER NOT NULL,
+    entry TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS entries_killed_at ON entries (killed_at);
+CREATE INDEX IF NOT EXISTS entries_process_port ON entries (process_name, port);
+CREATE INDEX IF NOT EXISTS entries_port ON entries (port);
+CREATE INDEX IF NOT EXISTS entries_project ON entries (project_name);
+CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+";
+
+pub struct HistoryStore {
+    conn: Connection,
+    path: PathBuf,
+}
+
+impl std::fmt::Debug for HistoryStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HistoryStore")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+pub fn default_path() -> PathBuf {
+    crate::uninstall::home_dir()
+        .join(".port-kill")
+        .join("history.db")
+}
+
+/// Times as stored: RFC 3339 in UTC with milliseconds, so text order is time order and
+/// SQLite's date functions read them
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(text: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+impl HistoryStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("Failed to set up {}", path.display()))?;
+        Ok(Self {
+            conn,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// The store in `~/.port-kill`, with the old JSON history imported the first time and
+    /// the `[history]` retention applied
+    pub fn open_default() -> Result<Self> {
+        let mut store = Self::open(&default_path())?;
+        if store.setting("json_imported")?.is_none() {
+            let json = crate::types::ProcessHistory::get_history_file_path();
+            if Path::new(&json).exists() {
+                let imported = store.import_json(Path::new(&json))?;
+                log::info!("Imported {} history entries from {}", imported, json);
+            }
+            store.set_setting("json_imported", &timestamp(Utc::now()))?;
+        }
+        if let Some(retention) = crate::config::load_or_default().config.history {
+            store.prune(&retention, Utc::now())?;
+        }
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn setting(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            [key, value],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert(&mut self, entry: &ProcessHistoryEntry) -> Result<()> {
+        insert(&self.conn, entry)
+    }
+
+    /// Add the entries of a JSON history file (a list of entries, as `~/.port-kill-history.json`
+    /// holds). Entries the store has already (same time, port and process) are skipped, so
+    /// importing a file twice adds nothing. Returns how many were added.
+    pub fn import_json(&mut self, path: &Path) -> Result<usize> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let entries: Vec<ProcessHistoryEntry> = crate::state_format::decode_list(&json)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        let tx = self.conn.transaction()?;
+        let mut added = 0;
+        for entry in &entries {
+            let known: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM entries WHERE killed_at = ?1 AND port = ?2 AND process_name = ?3",
+                    params![timestamp(entry.killed_at), entry.port, entry.process_name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if known.is_none() {
+                insert(&tx, entry)?;
+                added += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// The newest `limit` entries, oldest first
+    pub fn recent(&self, limit: usize) -> Result<Vec<ProcessHistoryEntry>> {
+        self.matching(&HistoryFilter::default(), limit)
+    }
+
+    /// The newest `limit` entries `filter` matches, oldest first
+    pub fn matching(
+        &self,
+        filter: &HistoryFilter,
+        limit: usize,
+    ) -> Result<Vec<ProcessHistoryEntry>> {
+        let (condition, mut values) = filter.sql();
+        values.push(Value::Integer(limit as i64));
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT entry FROM entries WHERE {condition} ORDER BY id DESC LIMIT ?"
+        ))?;
+        let mut entries = statement
+            .query_map(params_from_iter(values), |row| row.get::<_, String>(0))?
+            .filter_map(|json| serde_json::from_str(&json.ok()?).ok())
+            .collect::<Vec<ProcessHistoryEntry>>();
+        entries.reverse();
+        Ok(entries)
+    }
+
+    pub fn len(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        self.conn.execute("DELETE FROM entries", [])?;
+        Ok(())
+    }
+
+    /// Drop what `retention` doesn't keep: entries past `keep_days`, then those over their
+    /// project's quota, then the oldest over `max_entries`
+    pub fn prune(
+        &mut self,
+        retention: &HistoryRetention,
+        now: DateTime<Utc>,
+    ) -> Result<PruneReport> {
+        let mut report = PruneReport::default();
+        let transaction = self.conn.transaction()?;
+        if let Some(days) = retention.keep_days {
+            let cutoff = timestamp(now - chrono::Duration::days(days as i64));
+            report.expired =
+                transaction.execute("DELETE FROM entries WHERE killed_at < ?1", [cutoff])?;
+        }
+        if retention.max_entries_per_project.is_some() || !retention.projects.is_empty() {
+            let projects = transaction
+                .prepare(
+                    "SELECT DISTINCT project_name FROM entries WHERE project_name IS NOT NULL",
+                )?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for project in projects {
+                if let Some(quota) = retention.project_quota(&project) {
+                    report.over_project_quota += transaction.execute(
+                        "DELETE FROM entries WHERE project_name = ?1 AND id NOT IN
+                            (SELECT id FROM entries WHERE project_name = ?1
+                             ORDER BY id DESC LIMIT ?2)",
+                        params![project, quota as i64],
+                    )?;
+                }
+            }
+        }
+        if let Some(max_entries) = retention.max_entries {
+            report.over_max_entries = transaction.execute(
+                "DELETE FROM entries WHERE id NOT IN (SELECT id FROM entries ORDER BY id DESC LIMIT ?1)",
+                [max_entries as i64],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(report)
+    }
+
+    /// Rewrite the database file without the space pruned entries left behind
+    pub fn compact(&mut self) -> Result<()> {
+        self.conn.execute_batch("VACUUM")?;
+        Ok(())
+    }
+
+    /// Processes killed on the same port at least `min_kills` times, most killed first
+    pub fn frequent_offenders(
+        &self,
+        filter: &HistoryFilter,
+        min_kills: usize,
+    ) -> Result<Vec<FrequentOffender>> {
+        let (condition, mut values) = filter.sql();
+        values.push(Value::Integer(min_kills as i64));
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT process_name, port, COUNT(*), MIN(killed_at), MAX(killed_at),
+                    MIN(process_group), MIN(project_name)
+             FROM entries WHERE is_kill = 1 AND {condition}
+             GROUP BY process_name, port HAVING COUNT(*) >= ?
+             ORDER BY COUNT(*) DESC, process_name, port"
+        ))?;
+        let offenders = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok(FrequentOffender {
+                    process_name: row.get(0)?,
+                    port: row.get(1)?,
+                    kill_count: row.get::<_, i64>(2)? as usize,
+                    first_killed: parse_timestamp(&row.get::<_, String>(3)?)?,
+                    last_killed: parse_timestamp(&row.get::<_, String>(4)?)?,
+                    process_group: row.get(5)?,
+                    project_name: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(offenders)
+    }
+
+    /// Kills by hour of the day and day of the week (UTC, like the in-memory history)
+    pub fn time_patterns(&self, filter: &HistoryFilter) -> Result<TimePatterns> {
+        let (condition, values) = filter.sql();
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT CAST(strftime('%H', killed_at) AS INTEGER),
+                    CAST(strftime('%w', killed_at) AS INTEGER), COUNT(*)
+             FROM entries WHERE is_kill = 1 AND {condition} GROUP BY 1, 2"
+        ))?;
+        let mut hour_distribution: HashMap<u32, usize> = HashMap::new();
+        let mut day_distribution: HashMap<chrono::Weekday, usize> = HashMap::new();
+        let mut total_kills = 0;
+        let rows = statement.query_map(params_from_iter(values), |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (hour, weekday, count) = row?;
+            let count = count as usize;
+            *hour_distribution.entry(hour).or_insert(0) += count;
+            // %w counts from Sunday, chrono from Monday
+            let day = chrono::Weekday::try_from(((weekday + 6) % 7) as u8)
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            *day_distribution.entry(day).or_insert(0) += count;
+            total_kills += count;
+        }
+        Ok(TimePatterns {
+            total_kills,
+            peak_hour: peak(&hour_distribution),
+            peak_day: peak(&day_distribution),
+            hour_distribution,
+            day_distribution,
+        })
+    }
+
+    /// Totals, the most killed processes (by group where known), ports and projects, and
+    /// the span the kills cover
+    pub fn statistics(&self, filter: &HistoryFilter) -> Result<HistoryStatistics> {
+        let (condition, values) = filter.sql();
+        let (total, oldest, newest): (i64, Option<String>, Option<String>) = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*), MIN(killed_at), MAX(killed_at) FROM entries
+                 WHERE is_kill = 1 AND {condition}"
+            ),
+            params_from_iter(values),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let oldest_kill = oldest.as_deref().map(parse_timestamp).transpose()?;
+        let newest_kill = newest.as_deref().map(parse_timestamp).transpose()?;
+
+        let processes = self.counts(filter, "COALESCE(process_group, process_name)")?;
+        let ports: Vec<(u16, usize)> = self
+            .counts(filter, "port")?
+            .into_iter()
+            .filter_map(|(port, count)| Some((port.parse().ok()?, count)))
+            .collect();
+        let projects = self.counts(filter, "project_name")?;
+
+        let days_span = match (oldest_kill, newest_kill) {
+            (Some(oldest), Some(newest)) if oldest != newest => (newest - oldest).num_days() as f64,
+            _ => 1.0,
+        };
+        Ok(HistoryStatistics {
+            total_kills: total as usize,
+            unique_processes: processes.len(),
+            unique_ports: ports.len(),
+            unique_projects: projects.len(),
+            most_killed_process: processes.first().cloned(),
+            most_killed_port: ports.first().copied(),
+            most_killed_project: projects.first().cloned(),
+            top_processes: processes.into_iter().take(3).collect(),
+            top_ports: ports.into_iter().take(3).collect(),
+            top_projects: projects.into_iter().take(3).collect(),
+            average_kills_per_day: total as f64 / days_span.max(1.0),
+            oldest_kill,
+            newest_kill,
+        })
+    }
+
+    /// Kills per value of `column`, most first; rows where it is NULL aren't counted
+    fn counts(&self, filter: &HistoryFilter, column: &str) -> Result<Vec<(String, usize)>> {
+        let (condition, values) = filter.sql();
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT CAST({column} AS TEXT) AS value, COUNT(*) AS kills FROM entries
+             WHERE is_kill = 1 AND value IS NOT NULL AND {condition}
+             GROUP BY value ORDER BY kills DESC, value"
+        ))?;
+        let counts = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as usize))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(counts)
+    }
+}
+
+fn insert(conn: &Connection, entry: &ProcessHistoryEntry) -> Result<()> {
+    conn.execute(
+        "INSERT INTO entries (killed_at, port, process_name, process_group, project_name, is_kill, entry)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            timestamp(entry.killed_at),
+            entry.port,
+            entry.process_name,
+            entry.process_group,
+            entry.project_name,
+            entry.action.is_kill(),
+            serde_json::to_string(entry)?,
+        ],
+    )?;
+    Ok(())
+}
+
+fn peak<K: Copy>(distribution: &HashMap<K, usize>) -> Option<K> {
+    distribution
+        .iter()
+        .max_by_key(|(_, count)| **count)
+        .map(|(key, _)| *key)
+}
+
+/// Add `entry` to the store in `~/.port-kill`, for actions taken outside a
+/// [`ProcessMonitor`](crate::process_monitor::ProcessMonitor) (guard resolutions, restarts).
+/// Failures are logged; demo mode actions aren't recorded.
+pub fn record(entry: &ProcessHistoryEntry) {
+    if crate::demo::is_enabled() {
+        return;
+    }
+    if let Err(e) = HistoryStore::open_default().and_then(|mut store| store.insert(entry)) {
+        log::warn!(
+            "Failed to record {} in the history: {:#}",
+            entry.action.describe(),
+            e
+        );
+    }
+}
+
+/// `--history prune`: apply the `[history]` retention now, and report what it removed
+pub fn prune_command() -> Result<()> {
+    let retention = crate::config::load_or_default()
+        .config
+        .history
+        .unwrap_or_default();
+    let mut store = HistoryStore::open(&default_path())?;
+    let size = |store: &HistoryStore| std::fs::metadata(store.path()).map_or(0, |m| m.len());
+    let before = size(&store);
+    let report = store.prune(&retention, Utc::now())?;
+    store.compact()?;
+
+    if retention == HistoryRetention::default() {
+        println!(
+            "ℹ️  No [history] retention is configured (keep_days, max_entries, \
+             max_entries_per_project), so nothing is pruned"
+        );
+    } else if report.total() == 0 {
+        println!("✅ The history is within its retention; nothing to prune");
+    } else {
+        println!("🧹 Pruned {} history entries:", report.total());
+        for (count, reason) in [
+            (report.expired, "older than keep_days"),
+            (report.over_project_quota, "over a project quota"),
+            (report.over_max_entries, "over max_entries"),
+        ] {
+            if count > 0 {
+                println!("  • {} {}", count, reason);
+            }
+        }
+    }
+    println!(
+        "   {} entries left in {} ({} → {})",
+        store.len()?,
+        store.path().display(),
+        crate::cache::output::human_size(before),
+        crate::cache::output::human_size(size(&store))
+    );
+    Ok(())
+}
+
+/// `--import-history <file>`: add the entries of a JSON history file to the store
+pub fn import_command(path: &str) -> Result<()> {
+    let mut store = HistoryStore::open_default()?;
+    let imported = store.import_json(Path::new(path))?;
+    println!(
+        "📥 Imported {} history entries from {} into {}",
+        imported,
+        path,
+        store.path().display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+
+    #[test]
+    fn test_store_queries_and_pruning() {
+        let path =
+            std::env::temp_dir().join(format!("port-kill-history-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut store = HistoryStore::open(&path).unwrap();
+
+        let now = Utc::now();
+        let entry = |name: &str, port: u16, days_ago: i64| {
+            let mut entry: ProcessHistoryEntry = serde_json::from_value(serde_json::json!({
+                "pid": 1,
+                "port": port,
+                "process_name": name,
+                "process_group": null,
+                "project_name": "web",
+                "killed_at": now,
+                "command_line": null,
+                "working_directory": null,
+            }))
+            .unwrap();
+            entry.killed_at = now - chrono::Duration::days(days_ago);
+            entry
+        };
+        for (name, port, days_ago) in [("node", 3000, 10), ("node", 3000, 1), ("python", 8000, 0)] {
+            store.insert(&entry(name, port, days_ago)).unwrap();
+        }
+
+        let offenders = store
+            .frequent_offenders(&HistoryFilter::default(), 2)
+            .unwrap();
+        assert_eq!(offenders.len(), 1);
+        assert_eq!(
+            (offenders[0].process_name.as_str(), offenders[0].kill_count),
+            ("node", 2)
+        );
+
+        let stats = store.statistics(&HistoryFilter::default()).unwrap();
+        assert_eq!(stats.total_kills, 3);
+        assert_eq!(stats.most_killed_process, Some(("node".to_string(), 2)));
+        assert_eq!(stats.top_ports, vec![(3000, 2), (8000, 1)]);
+        assert_eq!(stats.most_killed_project, Some(("web".to_string(), 3)));
+
+        let patterns = store.time_patterns(&HistoryFilter::default()).unwrap();
+        assert_eq!(patterns.total_kills, 3);
+        assert_eq!(patterns.day_distribution[&now.weekday()], 1);
+        assert_eq!(patterns.hour_distribution[&now.hour()], 3);
+
+        // Filters narrow every query; --limit keeps the newest matches
+        let port_3000 = HistoryFilter {
+            port: Some(3000),
+            since: Some(now - chrono::Duration::days(5)),
+            ..Default::default()
+        };
+        assert_eq!(store.statistics(&port_3000).unwrap().total_kills, 1);
+        let newest = HistoryFilter {
+            project: Some("WEB".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            store.statistics(&newest).unwrap().top_ports,
+            vec![(3000, 1), (8000, 1)]
+        );
+        assert!(store.frequent_offenders(&newest, 2).unwrap().is_empty());
+        let names = |entries: Vec<ProcessHistoryEntry>| {
+            entries
+                .into_iter()
+                .map(|e| e.process_name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(store.matching(&newest, 10).unwrap()),
+            vec!["node", "python"]
+        );
+        assert_eq!(
+            names(newest.apply(&store.recent(10).unwrap())),
+            vec!["node", "python"]
+        );
+
+        let recent = store.recent(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[1].process_name, "python");
+
+        let retention = HistoryRetention {
+            keep_days: Some(5),
+            max_entries: Some(1),
+            ..Default::default()
+        };
+        let report = store.prune(&retention, now).unwrap();
+        assert_eq!((report.expired, report.over_max_entries), (1, 1));
+        assert_eq!(store.recent(10).unwrap()[0].process_name, "python");
+
+        // Importing skips what the store has already
+        let json = path.with_extension("json");
+        let file =
+            serde_json::to_string(&[entry("python", 8000, 0), entry("bun", 3001, 2)]).unwrap();
+        std::fs::write(&json, file).unwrap();
+        assert_eq!(store.import_json(&json).unwrap(), 1);
+        assert_eq!(store.import_json(&json).unwrap(), 0);
+        assert_eq!(store.len().unwrap(), 2);
+
+        let quota = HistoryRetention {
+            max_entries_per_project: Some(5),
+            projects: HashMap::from([("web".to_string(), 1)]),
+            ..Default::default()
+        };
+        assert_eq!(store.prune(&quota, now).unwrap().over_project_quota, 1);
+        assert_eq!(store.len().unwrap(), 1);
+        let _ = std::fs::remove_file(&json);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,335 @@
+//! Commands run around kills, so apps with a graceful shutdown endpoint get to use it
+//! before they are signalled. Hooks live in `~/.port-kill/config.toml` or a project's
+//! `.portkill.toml` (see [`crate::config`]):
+//!
+//! ```toml
+//! [[hooks]]
+//! ports = [3000]                 # selector: any of these ports (empty = any port)
+//! process = "node"               # selector: process name (optional)
+//! pre_kill = "curl -fsS -X POST localhost:3000/shutdown"
+//! post_kill = "say done"
+//! timeout = 10                   # seconds each command may run
+//! grace = 5                      # seconds to wait for the process to exit after pre_kill
+//! on_failure = "continue"        # or "abort" to leave the process alone
+//! ```
+//!
+//! Commands run through the shell with `PORTKILL_HOOK_PID`, `PORTKILL_HOOK_PORT` and
+//! `PORTKILL_HOOK_NAME` set. They are kept out of the `PORT_KILL_` namespace, whose
+//! variables stand in for flags, so a hook that runs port-kill doesn't pick them up.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailure {
+    /// Log the failure and kill the process anyway
+    #[default]
+    Continue,
+    /// Don't kill the process if its pre-kill hook fails
+    Abort,
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+fn default_grace() -> u64 {
+    5
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KillHook {
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    #[serde(default)]
+    pub process: Option<String>,
+    #[serde(default)]
+    pub pre_kill: Option<String>,
+    #[serde(default)]
+    pub post_kill: Option<String>,
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    #[serde(default = "default_grace")]
+    pub grace: u64,
+    #[serde(default)]
+    pub on_failure: HookFailure,
+}
+
+/// What `pre_kill` hooks achieved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreKill {
+    /// The process is still running and should be signalled as usual
+    Proceed,
+    /// The process shut down on its own, no signal needed
+    Exited,
+}
+
+/// How a kill that wasn't refused ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillOutcome {
+    /// The process was signalled
+    Killed,
+    /// A pre-kill hook or graceful HTTP shutdown made it exit before any signal was sent
+    Exited,
+}
+
+impl KillHook {
+    pub fn matches(&self, port: Option<u16>, name: Option<&str>) -> bool {
+        let port_matches =
+            self.ports.is_empty() || port.is_some_and(|port| self.ports.contains(&port));
+        let name_matches = match (&self.process, name) {
+            (None, _) => true,
+            (Some(wanted), Some(name)) => wanted.eq_ignore_ascii_case(name),
+            (Some(_), None) => false,
+        };
+        port_matches && name_matches
+    }
+}
+
+pub fn parse_hooks(content: &str) -> Result<Vec<KillHook>> {
+    let config: crate::config::ConfigLayer =
+        toml::from_str(content).context("Invalid hooks configuration")?;
+    Ok(config.hooks)
+}
+
+/// Hooks from the global and project config files. A broken file is logged and ignored
+/// rather than blocking every kill.
+pub fn load_hooks() -> Vec<KillHook> {
+    crate::config::load_or_default().config.hooks
+}
+
+pub(crate) fn is_running(pid: i32) -> bool {
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::Pid::from_u32(pid as u32);
+    system.refresh_process(pid);
+    // A process that exited but wasn't reaped yet lingers as a zombie
+    system
+        .process(pid)
+        .is_some_and(|p| p.status() != sysinfo::ProcessStatus::Zombie)
+}
+
+/// Run one hook command through the shell, killing it once `timeout` passes
+fn run_command(
+    command: &str,
+    pid: i32,
+    port: Option<u16>,
+    name: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    let mut child = shell
+        .env("PORTKILL_HOOK_PID", pid.to_string())
+        .env(
+            "PORTKILL_HOOK_PORT",
+            port.map(|p| p.to_string()).unwrap_or_default(),
+        )
+        .env("PORTKILL_HOOK_NAME", name)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run hook `{}`", command))?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if status.success() {
+                return Ok(());
+            }
+            anyhow::bail!("Hook `{}` failed with {}", command, status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("Hook `{}` timed out after {}s", command, timeout.as_secs());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Run the matching `pre_kill` hooks for a process about to be killed. Fails only when a
+/// hook with `on_failure = "abort"` fails.
+pub fn run_pre_kill(
+    hooks: &[KillHook],
+    pid: i32,
+    port: Option<u16>,
+    name: Option<&str>,
+) -> Result<PreKill> {
+    let resolved_name = match name {
+        Some(name) => Some(name.to_string()),
+        None if hooks.iter().any(|h| h.process.is_some()) => {
+            crate::system_monitor::process_name(pid)
+        }
+        None => None,
+    };
+    let name = resolved_name.as_deref();
+
+    for hook in hooks.iter().filter(|h| h.matches(port, name)) {
+        let Some(ref command) = hook.pre_kill else {
+            continue;
+        };
+        log::info!("Running pre-kill hook for PID {}: {}", pid, command);
+        if let Err(e) = run_command(
+            command,
+            pid,
+            port,
+            name.unwrap_or(""),
+            Duration::from_secs(hook.timeout),
+        ) {
+            if hook.on_failure == HookFailure::Abort {
+                return Err(e.context(format!("Not killing PID {}: pre-kill hook failed", pid)));
+            }
+            log::warn!("{:#}; killing PID {} anyway", e, pid);
+            continue;
+        }
+
+        // Give a graceful shutdown endpoint time to take effect
+        let deadline = Instant::now() + Duration::from_secs(hook.grace);
+        while Instant::now() < deadline {
+            if !is_running(pid) {
+                log::info!("PID {} exited after its pre-kill hook", pid);
+                return Ok(PreKill::Exited);
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    }
+    Ok(PreKill::Proceed)
+}
+
+/// Run the matching `post_kill` hooks. Failures are only logged: the process is gone either way.
+pub fn run_post_kill(hooks: &[KillHook], pid: i32, port: Option<u16>, name: Option<&str>) {
+    for hook in hooks.iter().filter(|h| h.matches(port, name)) {
+        let Some(ref command) = hook.post_kill else {
+            continue;
+        };
+        log::info!("Running post-kill hook for PID {}: {}", pid, command);
+        if let Err(e) = run_command(
+            command,
+            pid,
+            port,
+            name.unwrap_or(""),
+            Duration::from_secs(hook.timeout),
+        ) {
+            log::warn!("{:#}", e);
+        }
+    }
+}
+
+/// Kill a process with `kill`, wrapped in the configured hooks and graceful HTTP shutdown.
+/// Only a kill that actually ran is notified; every attempt is written to the audit log.
+pub fn with_kill_hooks(
+    pid: i32,
+    port: Option<u16>,
+    name: Option<&str>,
+    kill: impl FnOnce() -> Result<()>,
+) -> Result<KillOutcome> {
+    if crate::demo::is_enabled() {
+        let result = crate::demo::kill(pid);
+        crate::notify::kill_result(pid, port, name, &result);
+        return result.map(|()| KillOutcome::Killed);
+    }
+    let config = crate::config::load_or_default().config;
+    let (hooks, graceful) = (config.hooks, config.graceful_http);
+    let name = name
+        .map(str::to_string)
+        .or_else(|| crate::system_monitor::process_name(pid));
+    let notify = |result: Result<()>| {
+        crate::notify::kill_result(pid, port, name.as_deref(), &result);
+        crate::audit_log::record_kill(pid, port, name.as_deref(), &result);
+        result.map(|()| KillOutcome::Killed)
+    };
+    if hooks.is_empty() && graceful.is_empty() {
+        return notify(kill());
+    }
+    let outcome = match run_pre_kill(&hooks, pid, port, name.as_deref()) {
+        Ok(PreKill::Proceed)
+            if crate::graceful_http::shutdown(&graceful, pid, port) == PreKill::Proceed =>
+        {
+            notify(kill())?
+        }
+        Ok(_) => {
+            crate::audit_log::record_kill(pid, port, name.as_deref(), &Ok(()));
+            KillOutcome::Exited
+        }
+        Err(e) => {
+            let result = Err(e);
+            crate::audit_log::record_kill(pid, port, name.as_deref(), &result);
+            return result;
+        }
+    };
+    run_post_kill(&hooks, pid, port, name.as_deref());
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hooks_and_selectors() {
+        let hooks = parse_hooks(
+            r#"
+            [[hooks]]
+            ports = [3000, 3001]
+            pre_kill = "curl -X POST localhost:3000/shutdown"
+            on_failure = "abort"
+
+            [[hooks]]
+            process = "node"
+            post_kill = "say done"
+            timeout = 3
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(hooks.len(), 2);
+        assert_eq!(hooks[0].on_failure, HookFailure::Abort);
+        assert_eq!(hooks[0].timeout, 10);
+        assert!(hooks[0].matches(Some(3001), None));
+        assert!(!hooks[0].matches(Some(8080), Some("node")));
+        assert!(!hooks[0].matches(None, None));
+        assert!(hooks[1].matches(None, Some("Node")));
+        assert!(!hooks[1].matches(Some(3000), Some("python3")));
+        assert_eq!(hooks[1].on_failure, HookFailure::Continue);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_pre_kill_abort_and_timeout() {
+        let hooks = parse_hooks(
+            r#"
+            [[hooks]]
+            pre_kill = "exit 1"
+            on_failure = "abort"
+            "#,
+        )
+        .unwrap();
+        assert!(run_pre_kill(&hooks, i32::MAX, Some(3000), Some("node")).is_err());
+
+        let hooks = parse_hooks(
+            r#"
+            [[hooks]]
+            pre_kill = "sleep 5"
+            timeout = 0
+            grace = 0
+            "#,
+        )
+        .unwrap();
+        let started = Instant::now();
+        assert_eq!(
+            run_pre_kill(&hooks, i32::MAX, Some(3000), Some("node")).unwrap(),
+            PreKill::Proceed
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,251 @@
+//! Global hotkeys for the tray, bound in the config file (`~/.port-kill/config.toml` or the
+//! project's `.portkill.toml`):
+//!
+//! ```toml
+//! [hotkeys]
+//! "CmdOrCtrl+Shift+P" = "show-processes"
+//! "CmdOrCtrl+Shift+K" = "kill-all"
+//! "CmdOrCtrl+Alt+3" = "kill-port:3000"
+//! "CmdOrCtrl+Alt+N" = "kill-group:Node.js"
+//! ```
+//!
+//! Without a `[hotkeys]` table the [`DEFAULT_BINDINGS`] apply; an empty table turns them off.
+//! Hotkeys work on macOS, Windows and X11 (Wayland has no global hotkeys for apps).
+
+use anyhow::{Context, Result};
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Bindings used when the config has no `[hotkeys]` table
+pub const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("CmdOrCtrl+Shift+P", "show-processes"),
+    ("CmdOrCtrl+Shift+K", "kill-all"),
+];
+
+/// What a hotkey does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Kill everything on the monitored ports, like the tray's "Kill All Processes"
+    KillAll,
+    KillPort(u16),
+    /// Kill every listener in a process group, like the tray's "Kill all <group>"
+    KillGroup(String),
+    /// A notification with what is listening (a status bar menu can't be opened for you)
+    ShowProcesses,
+}
+
+impl FromStr for HotkeyAction {
+    type Err = anyhow::Error;
+
+    fn from_str(action: &str) -> Result<Self> {
+        match action.split_once(':') {
+            None if action == "kill-all" => Ok(HotkeyAction::KillAll),
+            None if action == "show-processes" => Ok(HotkeyAction::ShowProcesses),
+            Some(("kill-port", port)) => port
+                .parse()
+                .map(HotkeyAction::KillPort)
+                .with_context(|| format!("Invalid port in hotkey action '{}'", action)),
+            Some(("kill-group", group)) if !group.is_empty() => {
+                Ok(HotkeyAction::KillGroup(group.to_string()))
+            }
+            _ => anyhow::bail!(
+                "Unknown hotkey action '{}' (use kill-all, show-processes, kill-port:<port> or kill-group:<group>)",
+                action
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HotkeyAction::KillAll => write!(f, "kill-all"),
+            HotkeyAction::KillPort(port) => write!(f, "kill-port:{}", port),
+            HotkeyAction::KillGroup(group) => write!(f, "kill-group:{}", group),
+            HotkeyAction::ShowProcesses => write!(f, "show-processes"),
+        }
+    }
+}
+
+/// The bindings from the config (or the defaults), parsed. Each entry is the accelerator
+/// as written, its hotkey and its action, sorted by accelerator.
+pub fn bindings(config: Option<&HashMap<String, String>>) -> Result<Vec<(String, HotKey, HotkeyAction)>> {
+    let mut bindings: Vec<(String, String)> = match config {
+        Some(config) => config.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        None => DEFAULT_BINDINGS
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    };
+    bindings.sort();
+    bindings
+        .into_iter()
+        .map(|(accelerator, action)| {
+            let hotkey = HotKey::from_str(&accelerator)
+                .map_err(|e| anyhow::anyhow!("Invalid hotkey '{}': {}", accelerator, e))?;
+            Ok((accelerator, hotkey, action.parse()?))
+        })
+        .collect()
+}
+
+/// Registered hotkeys. Keep this alive for as long as they should work; on macOS it must
+/// be created on the main thread, on Windows on the thread running the event loop.
+pub struct Hotkeys {
+    _manager: GlobalHotKeyManager,
+    actions: HashMap<u32, HotkeyAction>,
+    accelerators: Vec<(String, HotkeyAction)>,
+}
+
+impl Hotkeys {
+    /// Register the configured bindings. Hotkeys another app already holds are skipped
+    /// with a warning.
+    pub fn register() -> Result<Self> {
+        let config = crate::config::load_or_default().config;
+        let manager = GlobalHotKeyManager::new()
+            .map_err(|e| anyhow::anyhow!("Global hotkeys are not available: {}", e))?;
+        let mut actions = HashMap::new();
+        let mut accelerators = Vec::new();
+        for (accelerator, hotkey, action) in bindings(config.hotkeys.as_ref())? {
+            if let Err(e) = manager.register(hotkey) {
+                log::warn!("Could not register hotkey {}: {}", accelerator, e);
+                continue;
+            }
+            log::info!("Hotkey {} -> {}", accelerator, action);
+            actions.insert(hotkey.id(), action.clone());
+            accelerators.push((accelerator, action));
+        }
+        Ok(Self {
+            _manager: manager,
+            actions,
+            accelerators,
+        })
+    }
+
+    /// The action of a hotkey pressed since the last call, if any
+    pub fn poll(&self) -> Option<HotkeyAction> {
+        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+            if event.state() == HotKeyState::Pressed {
+                if let Some(action) = self.actions.get(&event.id()) {
+                    return Some(action.clone());
+                }
+            }
+        }
+        None
+    }
+
+    /// The accelerator bound to `action`, for tooltips
+    pub fn accelerator_for(&self, action: &HotkeyAction) -> Option<&str> {
+        self.accelerators
+            .iter()
+            .find(|(_, bound)| bound == action)
+            .map(|(accelerator, _)| accelerator.as_str())
+    }
+}
+
+/// Register the bindings on a thread of their own and call `on_action` there for each press.
+/// For the Linux and Windows trays; on macOS hotkeys need the main thread, so the tray there
+/// uses [`Hotkeys`] from its event loop instead.
+#[cfg(not(target_os = "macos"))]
+pub fn spawn(on_action: impl Fn(HotkeyAction) + Send + 'static) {
+    std::thread::spawn(move || {
+        let hotkeys = match Hotkeys::register() {
+            Ok(hotkeys) => hotkeys,
+            Err(e) => {
+                log::warn!("{:#}", e);
+                return;
+            }
+        };
+        #[cfg(target_os = "windows")]
+        {
+            use windows_sys::Win32::UI::WindowsAndMessaging::{DispatchMessageW, GetMessageW, TranslateMessage, MSG};
+            // Hotkey presses arrive through this thread's message loop
+            let mut message: MSG = unsafe { std::mem::zeroed() };
+            while unsafe { GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) } > 0 {
+                unsafe {
+                    TranslateMessage(&message);
+                    DispatchMessageW(&message);
+                }
+                while let Some(action) = hotkeys.poll() {
+                    on_action(action);
+                }
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
+        while let Ok(event) = GlobalHotKeyEvent::receiver().recv() {
+            if event.state() == HotKeyState::Pressed {
+                if let Some(action) = hotkeys.actions.get(&event.id()) {
+                    on_action(action.clone());
+                }
+            }
+        }
+    });
+}
+
+/// Carry out `action` with the scanner and kill functions the Linux and Windows trays use
+pub fn run_action(action: &HotkeyAction, args: &crate::cli::Args) -> Result<()> {
+    use crate::process_monitor::{get_processes_on_ports, kill_all_processes, kill_group_processes, kill_single_process};
+    match action {
+        HotkeyAction::KillAll => kill_all_processes(&args.get_ports_to_monitor(), args),
+        HotkeyAction::KillPort(port) => {
+            let (_, processes) = get_processes_on_ports(&[*port], args);
+            match processes.get(port) {
+                Some(process_info) => kill_single_process(process_info.pid, args),
+                None => {
+                    log::info!("Nothing is listening on port {}", port);
+                    Ok(())
+                }
+            }
+        }
+        HotkeyAction::KillGroup(group) => kill_group_processes(group, args),
+        HotkeyAction::ShowProcesses => {
+            let (count, processes) = get_processes_on_ports(&args.get_ports_to_monitor(), args);
+            crate::notify::show(
+                &format!("{} process(es) on monitored ports", count),
+                &processes_summary(&processes),
+            );
+            Ok(())
+        }
+    }
+}
+
+/// "Port 3000: node (PID 123)" lines for the show-processes notification
+pub fn processes_summary(processes: &HashMap<u16, crate::types::ProcessInfo>) -> String {
+    if processes.is_empty() {
+        return "No processes on the monitored ports".to_string();
+    }
+    let mut lines: Vec<(u16, String)> = processes
+        .iter()
+        .map(|(port, p)| (*port, format!("Port {}: {} (PID {})", port, p.name, p.pid)))
+        .collect();
+    lines.sort();
+    lines
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings_parse() {
+        let defaults = bindings(None).unwrap();
+        assert_eq!(defaults.len(), DEFAULT_BINDINGS.len());
+
+        let mut config = HashMap::new();
+        config.insert("CmdOrCtrl+Alt+3".to_string(), "kill-port:3000".to_string());
+        config.insert("CmdOrCtrl+Alt+N".to_string(), "kill-group:Node.js".to_string());
+        let parsed = bindings(Some(&config)).unwrap();
+        assert_eq!(parsed[0].2, HotkeyAction::KillPort(3000));
+        assert_eq!(parsed[1].2, HotkeyAction::KillGroup("Node.js".to_string()));
+
+        config.insert("CmdOrCtrl+Alt+X".to_string(), "explode".to_string());
+        assert!(bindings(Some(&config)).is_err());
+        assert!(bindings(Some(&HashMap::new())).unwrap().is_empty());
+        assert!("kill-port:99999".parse::<HotkeyAction>().is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Tray icon themes and the process-count badge. The icon is a disc with the number of
+//! listening processes drawn in; what the disc looks like depends on the theme:
+//!
+//! - `color`: green, orange or red by count, with a white number
+//! - `template`: a black glyph macOS recolours for light and dark menu bars (and the
+//!   highlighted state). A ring while nothing is listening, a solid disc with the number
+//!   cut out otherwise
+//! - `light` / `dark`: the same glyph in a fixed dark or white ink, for panels that don't
+//!   recolour icons
+//! - `auto` (the default): `template` on macOS, `color` elsewhere
+//!
+//! Chosen with `--icon-theme` or `icon_theme` in the config file. The Linux tray uses named
+//! icons instead of drawn ones; [`install_linux_icons`] puts them where the tray finds them.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Width and height of the drawn icons
+pub const ICON_SIZE: u32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IconTheme {
+    #[default]
+    Auto,
+    Color,
+    Template,
+    Light,
+    Dark,
+}
+
+impl IconTheme {
+    pub const NAMES: [&'static str; 5] = ["auto", "color", "template", "light", "dark"];
+
+    /// `--icon-theme` if given, otherwise `icon_theme` from the config, otherwise `auto`
+    pub fn configured(flag: Option<&str>) -> IconTheme {
+        let configured = crate::config::load_or_default().config.icon_theme;
+        match flag.or(configured.as_deref()).map(str::parse) {
+            Some(Ok(theme)) => theme,
+            Some(Err(e)) => {
+                log::warn!("{:#}", e);
+                IconTheme::Auto
+            }
+            None => IconTheme::Auto,
+        }
+    }
+
+    /// The theme `auto` stands for on this platform
+    pub fn effective(self) -> IconTheme {
+        match self {
+            IconTheme::Auto if cfg!(target_os = "macos") => IconTheme::Template,
+            IconTheme::Auto => IconTheme::Color,
+            theme => theme,
+        }
+    }
+
+    /// Whether macOS should treat the icon as a template image
+    pub fn is_template(self) -> bool {
+        cfg!(target_os = "macos") && self.effective() == IconTheme::Template
+    }
+
+    /// The named icon the Linux tray shows for `count` processes
+    pub fn linux_icon_name(self, count: usize) -> &'static str {
+        match self.effective() {
+            IconTheme::Color => match count {
+                0 => "port-kill-green",
+                1..=9 => "port-kill-orange",
+                _ => "port-kill-red",
+            },
+            // Symbolic icons are recoloured by the panel, like macOS templates
+            _ => "port-kill-symbolic",
+        }
+    }
+}
+
+impl FromStr for IconTheme {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "auto" => Ok(IconTheme::Auto),
+            "color" | "colour" => Ok(IconTheme::Color),
+            "template" => Ok(IconTheme::Template),
+            "light" => Ok(IconTheme::Light),
+            "dark" => Ok(IconTheme::Dark),
+            _ => anyhow::bail!(
+                "Unknown icon theme '{}' (use {})",
+                name,
+                IconTheme::NAMES.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for IconTheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            IconTheme::Auto => "auto",
+            IconTheme::Color => "color",
+            IconTheme::Template => "template",
+            IconTheme::Light => "light",
+            IconTheme::Dark => "dark",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The number drawn on the icon; more than two digits don't fit
+pub fn badge_text(count: usize) -> String {
+    if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// Disc colour of the `color` theme, with the same thresholds as the Linux icons
+pub fn status_color(count: usize) -> [u8; 3] {
+    match count {
+        0 => [52, 199, 89],
+        1..=9 => [255, 149, 0],
+        _ => [255, 59, 48],
+    }
+}
+
+/// 3x5 glyphs, one row per byte with the leftmost pixel in bit 2
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        _ => [0; 5],
+    }
+}
+
+/// Which pixels of the icon belong to `text`, centred
+fn text_mask(text: &str) -> Vec<bool> {
+    let size = ICON_SIZE as usize;
+    let glyphs: Vec<[u8; 5]> = text.chars().map(glyph).collect();
+    let scale = if glyphs.len() <= 2 { 3 } else { 2 };
+    let width = glyphs.len() * 4 * scale - scale;
+    let left = size.saturating_sub(width) / 2;
+    let top = (size - 5 * scale) / 2;
+
+    let mut mask = vec![false; size * size];
+    for (i, rows) in glyphs.iter().enumerate() {
+        let glyph_left = left + i * 4 * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let x = glyph_left + column * scale + dx;
+                        let y = top + row * scale + dy;
+                        if x < size {
+                            mask[y * size + x] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    mask
+}
+
+/// RGBA pixels of the tray icon for `count` processes, [`ICON_SIZE`] square
+pub fn render(count: usize, theme: IconTheme) -> Vec<u8> {
+    let size = ICON_SIZE as usize;
+    let text = text_mask(&badge_text(count));
+    let center = (size as f32 - 1.0) / 2.0;
+    let radius = size as f32 / 2.0 - 0.5;
+    let theme = theme.effective();
+    let ink = match theme {
+        IconTheme::Dark => [255, 255, 255],
+        IconTheme::Light => [32, 32, 32],
+        // Only the alpha of a template image matters
+        _ => [0, 0, 0],
+    };
+
+    let mut rgba = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+            let in_disc = distance <= radius;
+            let in_text = text[y * size + x];
+            let pixel = if theme == IconTheme::Color {
+                match (in_disc, in_text) {
+                    (_, true) => Some([255, 255, 255]),
+                    (true, false) => Some(status_color(count)),
+                    _ => None,
+                }
+            } else {
+                let on = if count == 0 {
+                    in_text || (in_disc && distance > radius - 2.5)
+                } else {
+                    in_disc && !in_text
+                };
+                on.then_some(ink)
+            };
+            match pixel {
+                Some([r, g, b]) => rgba.extend_from_slice(&[r, g, b, 255]),
+                None => rgba.extend_from_slice(&[0, 0, 0, 0]),
+            }
+        }
+    }
+    rgba
+}
+
+/// The Linux tray's named icons, shipped in the binary
+pub const LINUX_ICONS: &[(&str, &str)] = &[
+    ("port-kill-green", include_str!("../assets/linux/port-kill-green.svg")),
+    ("port-kill-orange", include_str!("../assets/linux/port-kill-orange.svg")),
+    ("port-kill-red", include_str!("../assets/linux/port-kill-red.svg")),
+    ("port-kill-symbolic", include_str!("../assets/linux/port-kill-symbolic.svg")),
+];
+
+/// `~/.local/share/icons/hicolor/scalable/apps` (or under `$XDG_DATA_HOME`)
+pub fn linux_icon_dir() -> PathBuf {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| crate::uninstall::home_dir().join(".local").join("share"));
+    data_home
+        .join("icons")
+        .join("hicolor")
+        .join("scalable")
+        .join("apps")
+}
+
+/// Write the Linux tray icons into the user's icon theme, replacing outdated copies.
+/// Returns the directory, for the tray's icon search path.
+pub fn install_linux_icons() -> Result<PathBuf> {
+    let dir = linux_icon_dir();
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    for (name, svg) in LINUX_ICONS {
+        let path = dir.join(format!("{}.svg", name));
+        if std::fs::read_to_string(&path).is_ok_and(|existing| existing == *svg) {
+            continue;
+        }
+        std::fs::write(&path, svg).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alpha_at(rgba: &[u8], x: usize, y: usize) -> u8 {
+        rgba[(y * ICON_SIZE as usize + x) * 4 + 3]
+    }
+
+    #[test]
+    fn test_render_badge() {
+        let color = render(3, IconTheme::Color);
+        assert_eq!(color.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        // Corners are outside the disc
+        assert_eq!(alpha_at(&color, 0, 0), 0);
+        assert_eq!(&color[(2 * ICON_SIZE as usize + 16) * 4..][..3], &status_color(3));
+
+        // A template badge cuts the number out of the disc, so it differs by count
+        let template = render(3, IconTheme::Template);
+        assert_ne!(template, render(4, IconTheme::Template));
+        assert!(template.chunks(4).all(|pixel| pixel[..3] == [0, 0, 0]));
+        assert_eq!(badge_text(150), "99+");
+        assert_ne!(render(100, IconTheme::Dark), render(99, IconTheme::Dark));
+    }
+
+    #[test]
+    fn test_theme_names() {
+        for name in IconTheme::NAMES {
+            assert_eq!(name.parse::<IconTheme>().unwrap().to_string(), name);
+        }
+        assert!("neon".parse::<IconTheme>().is_err());
+        assert_eq!(IconTheme::Color.linux_icon_name(12), "port-kill-red");
+        assert_eq!(IconTheme::Dark.linux_icon_name(0), "port-kill-symbolic");
+    }
+}
@@ -0,0 +1,367 @@
+//! `--ignore add|remove|list`: the ignore lists of the global config file
+//! (`~/.port-kill/config.toml`), managed without editing it:
+//!
+//! ```text
+//! port-kill-console --ignore add process Slack,zoom
+//! port-kill-console --ignore add port 5353
+//! port-kill-console --ignore remove pattern 'Code*'
+//! port-kill-console --ignore list
+//! ```
+//!
+//! `--apply-suggestions` writes what `--show-suggestions` proposes from the kill history,
+//! after asking.
+
+use crate::config::{ConfigLayer, LayeredConfig};
+use crate::types::{IgnoreSuggestions, ProcessHistory};
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+/// The lists `--ignore` manages, as `--ignore list` labels them, with their config keys
+const KINDS: [(&str, &str); 5] = [
+    ("ports", "ignore_ports"),
+    ("processes", "ignore_processes"),
+    ("patterns", "ignore_patterns"),
+    ("regexes", "ignore_regex"),
+    ("groups", "ignore_groups"),
+];
+
+/// The config key for `port`, `processes`, ...
+fn config_key(kind: &str) -> Result<&'static str> {
+    Ok(match kind {
+        "port" | "ports" => "ignore_ports",
+        "process" | "processes" => "ignore_processes",
+        "pattern" | "patterns" => "ignore_patterns",
+        "regex" | "regexes" => "ignore_regex",
+        "group" | "groups" => "ignore_groups",
+        _ => bail!(
+            "Unknown ignore list '{}', expected one of: port, process, pattern, regex, group",
+            kind
+        ),
+    })
+}
+
+/// The `--ignore` kind for a config key: `ignore_ports` is `ports`
+pub(crate) fn kind_label(key: &str) -> &'static str {
+    KINDS
+        .iter()
+        .find(|(_, kind_key)| *kind_key == key)
+        .map_or("processes", |(kind, _)| kind)
+}
+
+/// The values of one `--ignore add|remove`, split on commas except for regexes
+fn split_values(key: &str, values: &[String]) -> Vec<String> {
+    if key == "ignore_regex" {
+        return values.to_vec();
+    }
+    values
+        .iter()
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// `value` as it is stored under `key`: ports are numbers, the rest strings
+fn toml_value(key: &str, value: &str) -> Result<toml::Value> {
+    match key {
+        "ignore_ports" => match value.parse::<u16>() {
+            Ok(port) if port != 0 => Ok(toml::Value::Integer(port as i64)),
+            _ => bail!("Invalid port '{}'", value),
+        },
+        "ignore_regex" => {
+            regex::Regex::new(value)
+                .with_context(|| format!("Invalid regular expression '{}'", value))?;
+            Ok(toml::Value::String(value.to_string()))
+        }
+        _ => Ok(toml::Value::String(value.to_string())),
+    }
+}
+
+/// The entries of the list `key` in `layer`, as text
+fn entries(layer: &ConfigLayer, key: &str) -> Vec<String> {
+    let strings = |list: &Option<Vec<String>>| list.clone().unwrap_or_default();
+    match key {
+        "ignore_ports" => layer
+            .ignore_ports
+            .iter()
+            .flatten()
+            .map(|port| port.to_string())
+            .collect(),
+        "ignore_processes" => strings(&layer.ignore_processes),
+        "ignore_patterns" => strings(&layer.ignore_patterns),
+        "ignore_regex" => strings(&layer.ignore_regex),
+        _ => strings(&layer.ignore_groups),
+    }
+}
+
+/// Add `values` to the list for `kind` in the global config file. Returns the file and the
+/// values that were new.
+pub fn add(kind: &str, values: &[String]) -> Result<(PathBuf, Vec<String>)> {
+    let key = config_key(kind)?;
+    let values = split_values(key, values);
+    if values.is_empty() {
+        bail!("Nothing to add, e.g. --ignore add {} <value>", kind);
+    }
+    let new_values = values
+        .iter()
+        .map(|value| Ok((value.clone(), toml_value(key, value)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let mut added = Vec::new();
+    let path = crate::config::update_global_list(key, |list| {
+        for (text, value) in new_values {
+            if !list.contains(&value) {
+                list.push(value);
+                added.push(text);
+            }
+        }
+    })?;
+    Ok((path, added))
+}
+
+/// Remove `values` from the list for `kind` in the global config file. Returns the file, the
+/// values removed and those that weren't in it.
+pub fn remove(kind: &str, values: &[String]) -> Result<(PathBuf, Vec<String>, Vec<String>)> {
+    let key = config_key(kind)?;
+    let values = split_values(key, values);
+    let old_values = values
+        .iter()
+        .map(|value| Ok((value.clone(), toml_value(key, value)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let (mut removed, mut missing) = (Vec::new(), Vec::new());
+    let path = crate::config::update_global_list(key, |list| {
+        for (text, value) in old_values {
+            let before = list.len();
+            list.retain(|item| *item != value);
+            if list.len() < before {
+                removed.push(text);
+            } else {
+                missing.push(text);
+            }
+        }
+    })?;
+    Ok((path, removed, missing))
+}
+
+/// `--ignore list`: the ignore lists of both config files, combined
+pub fn list(json: bool) -> Result<()> {
+    let layered = crate::config::load()?;
+    if json {
+        let lists: serde_json::Map<String, serde_json::Value> = KINDS
+            .iter()
+            .map(|(_, key)| {
+                let values = match *key {
+                    "ignore_ports" => serde_json::json!(layered.config.ignore_ports),
+                    _ => entries(&layered.config, key).into(),
+                };
+                (key.to_string(), values)
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&lists)?);
+        return Ok(());
+    }
+
+    let files: Vec<String> = [&layered.global_path, &layered.project_path]
+        .into_iter()
+        .flatten()
+        .map(|path| path.display().to_string())
+        .collect();
+    if files.is_empty() {
+        println!(
+            "🙈 No config file yet; `--ignore add` creates {}",
+            crate::config::global_config_path().display()
+        );
+        return Ok(());
+    }
+    println!("🙈 Ignore lists ({}):", files.join(", "));
+    let mut any = false;
+    for (kind, key) in KINDS {
+        let values = entries(&layered.config, key);
+        if !values.is_empty() {
+            any = true;
+            println!("  {:<11}{}", format!("{}:", kind), values.join(", "));
+        }
+    }
+    if !any {
+        println!("  (empty)");
+    }
+    Ok(())
+}
+
+/// Handle `--ignore ACTION [KIND VALUE...]`
+pub fn command(args: &[String], json: bool) -> Result<()> {
+    let (action, rest) = args
+        .split_first()
+        .context("--ignore needs add, remove or list")?;
+    match (action.as_str(), rest) {
+        ("list", []) => list(json),
+        ("add", [kind, values @ ..]) => {
+            let (path, added) = add(kind, values)?;
+            if added.is_empty() {
+                println!("ℹ️  Already ignored; {} is unchanged", path.display());
+            } else {
+                println!(
+                    "🙈 Added {} to {} in {}",
+                    added.join(", "),
+                    config_key(kind)?,
+                    path.display()
+                );
+            }
+            Ok(())
+        }
+        ("remove", [kind, values @ ..]) if !values.is_empty() => {
+            let key = config_key(kind)?;
+            let (path, removed, missing) = remove(kind, values)?;
+            if !removed.is_empty() {
+                println!(
+                    "Removed {} from {} in {}",
+                    removed.join(", "),
+                    key,
+                    path.display()
+                );
+            }
+            for value in &missing {
+                println!("ℹ️  {} is not in {} in {}", value, key, path.display());
+            }
+            if !missing.is_empty() {
+                if let Some(project) = project_source(key, &missing) {
+                    println!(
+                        "   It is set in {}; edit that file to remove it",
+                        project.display()
+                    );
+                }
+            }
+            Ok(())
+        }
+        ("list", _) => bail!("--ignore list takes no values"),
+        ("add" | "remove", _) => bail!(
+            "Usage: --ignore {} <port|process|pattern|regex|group> <value>[,<value>...]",
+            action
+        ),
+        (other, _) => bail!(
+            "Unknown --ignore action '{}', expected add, remove or list",
+            other
+        ),
+    }
+}
+
+/// The project config file, when it sets one of `values` under `key`
+fn project_source(key: &str, values: &[String]) -> Option<PathBuf> {
+    let LayeredConfig { project_path, .. } = crate::config::load().ok()?;
+    let project_path = project_path?;
+    let layer = ConfigLayer::from_file(&project_path).ok()?;
+    let set = entries(&layer, key);
+    values
+        .iter()
+        .any(|value| set.contains(value))
+        .then_some(project_path)
+}
+
+/// The suggestions not ignored yet, as (config key, value) pairs
+pub(crate) fn new_suggestions(
+    suggestions: &IgnoreSuggestions,
+    config: &ConfigLayer,
+) -> Vec<(&'static str, String)> {
+    let ports = suggestions
+        .suggested_ports
+        .iter()
+        .map(|port| ("ignore_ports", port.to_string()));
+    let processes = suggestions
+        .suggested_processes
+        .iter()
+        .map(|name| ("ignore_processes", name.clone()));
+    let groups = suggestions
+        .suggested_groups
+        .iter()
+        .map(|group| ("ignore_groups", group.clone()));
+    ports
+        .chain(processes)
+        .chain(groups)
+        .filter(|(key, value)| !entries(config, key).contains(value))
+        .collect()
+}
+
+/// `--apply-suggestions`: add what `--show-suggestions` proposes to the global config file,
+/// once confirmed
+pub fn apply_suggestions() -> Result<()> {
+    let history = ProcessHistory::load(100);
+    // The same threshold as --show-suggestions
+    let suggestions = history.get_ignore_suggestions(2);
+    let config = crate::config::load()?.config;
+    let new = new_suggestions(&suggestions, &config);
+    if new.is_empty() {
+        println!("✅ No suggestions to apply: nothing killed repeatedly is left unignored");
+        return Ok(());
+    }
+
+    let path = crate::config::global_config_path();
+    println!("💡 From the kill history, these would be ignored from now on:");
+    for (key, value) in &new {
+        println!("  • {} {}", key, value);
+    }
+    if !crate::safe_mode::confirm(&format!("Add them to {}?", path.display()))? {
+        println!("Cancelled.");
+        return Ok(());
+    }
+    for (kind, key) in KINDS {
+        let values: Vec<String> = new
+            .iter()
+            .filter(|(new_key, _)| *new_key == key)
+            .map(|(_, value)| value.clone())
+            .collect();
+        if !values.is_empty() {
+            add(kind, &values)?;
+        }
+    }
+    println!("🙈 Added {} ignore(s) to {}", new.len(), path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_values() {
+        assert_eq!(config_key("ports").unwrap(), "ignore_ports");
+        assert_eq!(config_key("process").unwrap(), "ignore_processes");
+        assert_eq!(config_key("processes").unwrap(), "ignore_processes");
+        assert_eq!(config_key("regex").unwrap(), "ignore_regex");
+        assert!(config_key("users").is_err());
+
+        assert_eq!(
+            split_values(
+                "ignore_processes",
+                &["Slack, zoom".to_string(), "node".to_string()]
+            ),
+            vec!["Slack", "zoom", "node"]
+        );
+        assert_eq!(
+            split_values("ignore_regex", &["a{1,2}".to_string()]),
+            vec!["a{1,2}"]
+        );
+        assert_eq!(
+            toml_value("ignore_ports", "5353").unwrap(),
+            toml::Value::Integer(5353)
+        );
+        assert!(toml_value("ignore_ports", "0").is_err());
+        assert!(toml_value("ignore_regex", "(").is_err());
+
+        let suggestions = IgnoreSuggestions {
+            suggested_ports: vec![5353, 7000],
+            suggested_processes: vec!["rapportd".to_string()],
+            suggested_groups: Vec::new(),
+            frequent_offenders: Vec::new(),
+        };
+        let config = ConfigLayer {
+            ignore_ports: Some(vec![5353]),
+            ..Default::default()
+        };
+        assert_eq!(
+            new_suggestions(&suggestions, &config),
+            vec![
+                ("ignore_ports", "7000".to_string()),
+                ("ignore_processes", "rapportd".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Running Jupyter / JupyterLab servers, read from the runtime files they write on start
+//! (`jpserver-<pid>.json`, or `nbserver-<pid>.json` for the classic notebook). This is the
+//! same data `jupyter server list` prints, without starting a Python interpreter.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct JupyterServer {
+    pub pid: i32,
+    pub port: u16,
+    /// Base URL of the server, e.g. `http://localhost:8888/`
+    pub url: String,
+    #[serde(default)]
+    pub token: String,
+    #[serde(default, alias = "notebook_dir")]
+    pub root_dir: Option<String>,
+    #[serde(skip)]
+    modified: Option<SystemTime>,
+}
+
+impl JupyterServer {
+    /// URL that logs straight in, like the one Jupyter prints on start
+    pub fn browser_url(&self) -> String {
+        if self.token.is_empty() {
+            self.url.clone()
+        } else {
+            format!("{}?token={}", self.url, self.token)
+        }
+    }
+}
+
+/// Candidate runtime directories, most specific first
+fn runtime_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(dir) = std::env::var("JUPYTER_RUNTIME_DIR") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Ok(dir) = std::env::var("JUPYTER_DATA_DIR") {
+        dirs.push(PathBuf::from(dir).join("runtime"));
+    }
+    if let Ok(dir) = std::env::var("XDG_DATA_HOME") {
+        dirs.push(PathBuf::from(dir).join("jupyter").join("runtime"));
+    }
+    if let Ok(appdata) = std::env::var("APPDATA") {
+        dirs.push(PathBuf::from(appdata).join("jupyter").join("runtime"));
+    }
+    if let Ok(home) = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")) {
+        let home = PathBuf::from(home);
+        dirs.push(home.join(".local/share/jupyter/runtime"));
+        dirs.push(home.join("Library/Jupyter/runtime"));
+    }
+    dirs
+}
+
+/// Servers described by the runtime files in `dir`
+pub fn servers_in(dir: &Path) -> Vec<JupyterServer> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            (name.starts_with("jpserver-") || name.starts_with("nbserver-"))
+                && name.ends_with(".json")
+        })
+        .filter_map(|e| {
+            let content = fs::read_to_string(e.path()).ok()?;
+            let mut server: JupyterServer = serde_json::from_str(&content).ok()?;
+            server.modified = e.metadata().and_then(|m| m.modified()).ok();
+            Some(server)
+        })
+        .collect()
+}
+
+/// All running servers, newest first. Files left behind by a crashed server are
+/// skipped by checking that their PID is still alive.
+pub fn list_servers() -> Vec<JupyterServer> {
+    let mut servers: Vec<JupyterServer> = runtime_dirs()
+        .iter()
+        .flat_map(|dir| servers_in(dir))
+        .collect();
+    servers.sort_by_key(|s| std::cmp::Reverse(s.modified));
+    // The same directory can be reached through more than one variable
+    let mut seen = std::collections::HashSet::new();
+    servers.retain(|s| seen.insert((s.pid, s.port)));
+
+    let mut system = sysinfo::System::new();
+    servers.retain(|s| system.refresh_process(sysinfo::Pid::from_u32(s.pid as u32)));
+    servers
+}
+
+/// The server listening on `port`, optionally also requiring its PID to match
+pub fn find_for_port(port: u16, pid: Option<i32>) -> Option<JupyterServer> {
+    list_servers()
+        .into_iter()
+        .find(|s| s.port == port && pid.is_none_or(|pid| s.pid == pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_servers_in_reads_runtime_files() {
+        let dir = std::env::temp_dir().join("port-kill-test-jupyter-runtime");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("jpserver-4242.json"),
+            r#"{"base_url": "/", "hostname": "localhost", "pid": 4242, "port": 8888,
+                "root_dir": "/home/me/ml", "token": "abc123", "url": "http://localhost:8888/"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("nbserver-99.json"),
+            r#"{"pid": 99, "port": 8890, "notebook_dir": "/srv", "token": "", "url": "http://127.0.0.1:8890/"}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("kernel-1.json"), "{}").unwrap();
+
+        let mut servers = servers_in(&dir);
+        servers.sort_by_key(|s| s.port);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(servers.len(), 2);
+        assert_eq!(
+            servers[0].browser_url(),
+            "http://localhost:8888/?token=abc123"
+        );
+        assert_eq!(servers[0].root_dir.as_deref(), Some("/home/me/ml"));
+        assert_eq!(servers[1].browser_url(), "http://127.0.0.1:8890/");
+        assert_eq!(servers[1].root_dir.as_deref(), Some("/srv"));
+    }
+}
@@ -1,21 +1,75 @@
+pub mod adopt;
+pub mod api_server;
+pub mod audit_log;
+pub mod autostart;
 pub mod cache;
 pub mod cli;
 pub mod command_line;
+pub mod completions;
+pub mod compose;
+pub mod config;
+pub mod connections;
 pub mod console_app;
+pub mod daemon;
+pub mod demo;
+pub mod desktop_session;
+pub mod digest;
+pub mod elevation;
 pub mod endpoint_monitor;
+pub mod env_file;
+pub mod exit_code;
 pub mod file_monitor;
+pub mod gpu;
+pub mod graceful_http;
+pub mod history_store;
+pub mod hooks;
+pub mod hotkeys;
+pub mod icon_theme;
+pub mod ignore_list;
+pub mod jupyter;
+pub mod logging;
+pub mod memory;
+pub mod notify;
+pub mod offline;
 pub mod orchestrator;
+pub mod output_schema;
 pub mod port_guard;
+pub mod port_spec;
+pub mod port_wait;
 pub mod preset_manager;
+pub mod priority;
+pub mod profiles;
 pub mod process_monitor;
+pub mod query;
+pub mod rate_limiter;
+pub mod remediation;
+pub mod reservations;
 pub mod restart_manager;
+pub mod safe_mode;
+pub mod scan_health;
 pub mod scripting;
+pub mod settings;
 pub mod security_audit;
 pub mod service_detector;
+pub mod service_group;
+pub mod service_logs;
+pub mod service_state;
 pub mod smart_filter;
+pub mod state_format;
+pub mod status_page;
 pub mod system_monitor;
+pub mod timeline;
+pub mod token;
+pub mod traffic;
+pub mod tui;
+pub mod tunnel;
 pub mod types;
+pub mod undo;
+pub mod uninstall;
 pub mod update_check;
+pub mod webhook;
+pub mod workspaces;
+pub mod wsl_bridge;
 
 // macOS-specific modules (only compiled on macOS)
 #[cfg(target_os = "macos")]
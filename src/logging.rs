@@ -0,0 +1,302 @@
+//! Logging for every front end. Lines go to stderr, or to `--log-file` with size-based
+//! rotation, as text or as one JSON object per line (`--log-format json`), so daemon and
+//! guard runs leave an auditable record. Log calls attach structured fields, which JSON
+//! lines carry as keys and text lines append as `key=value`:
+//!
+//! ```ignore
+//! info!(port = 3000, pid = 4242, action = "kill"; "Killed node on port 3000");
+//! ```
+//!
+//! The level comes from `RUST_LOG` as before (`info`, or `warn,port_kill::port_guard=debug`).
+
+use crate::cli::{Args, LogFormat};
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use log::kv::{Key, Value, VisitSource};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{json, Map};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Rotated files kept next to the log file (`port-kill.log.1` is the newest)
+pub const KEEP_ROTATED: usize = 5;
+
+/// Levels from a `RUST_LOG` value: a default level plus `module=level` overrides
+#[derive(Debug, Clone, PartialEq)]
+struct LevelSpec {
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LevelSpec {
+    fn parse(spec: &str) -> Self {
+        let mut default = LevelFilter::Info;
+        let mut modules = Vec::new();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = level.parse() {
+                        modules.push((module.to_string(), level));
+                    }
+                }
+                None => match directive.parse() {
+                    Ok(level) => default = level,
+                    // A bare module name turns everything on for it
+                    Err(_) => modules.push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+        // Most specific module first
+        modules.sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        Self { default, modules }
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(module, _)| {
+                target == module || target.strip_prefix(module.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// A log file that is rotated once it grows past `max_size` bytes
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    PathBuf::from(format!("{}.{}", path.display(), n))
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_size: u64) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        shift_rotated(&self.path)?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// port-kill.log → port-kill.log.1 → ... → port-kill.log.N, dropping the oldest
+fn shift_rotated(path: &Path) -> std::io::Result<()> {
+    let _ = std::fs::remove_file(rotated_path(path, KEEP_ROTATED));
+    for n in (1..KEEP_ROTATED).rev() {
+        let _ = std::fs::rename(rotated_path(path, n), rotated_path(path, n + 1));
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+}
+
+/// Rotate `path` the same way if it is over `max_size` bytes, for files other processes
+/// write to. Returns whether it was rotated.
+pub fn rotate_if_larger(path: &Path, max_size: u64) -> std::io::Result<bool> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.len() > max_size => shift_rotated(path).map(|_| true),
+        _ => Ok(false),
+    }
+}
+
+/// Collects a record's structured fields, in the order they were given
+struct Fields(Vec<(String, serde_json::Value)>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = if let Some(b) = value.to_bool() {
+            json!(b)
+        } else if let Some(n) = value.to_u64() {
+            json!(n)
+        } else if let Some(n) = value.to_i64() {
+            json!(n)
+        } else if let Some(n) = value.to_f64() {
+            json!(n)
+        } else {
+            json!(value.to_string())
+        };
+        self.0.push((key.as_str().to_string(), value));
+        Ok(())
+    }
+}
+
+/// One log line, without the trailing newline
+pub fn format_record(record: &Record, format: LogFormat) -> String {
+    let mut fields = Fields(Vec::new());
+    let _ = record.key_values().visit(&mut fields);
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    match format {
+        LogFormat::Text => {
+            let mut line = format!(
+                "[{} {:<5} {}] {}",
+                timestamp,
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            for (key, value) in &fields.0 {
+                match value {
+                    serde_json::Value::String(s) if !s.contains(' ') => line.push_str(&format!(" {}={}", key, s)),
+                    _ => line.push_str(&format!(" {}={}", key, value)),
+                }
+            }
+            line
+        }
+        LogFormat::Json => {
+            let mut object = Map::new();
+            object.insert("timestamp".into(), json!(timestamp));
+            object.insert("level".into(), json!(record.level().as_str()));
+            object.insert("target".into(), json!(record.target()));
+            object.insert("message".into(), json!(record.args().to_string()));
+            object.extend(fields.0);
+            serde_json::Value::Object(object).to_string()
+        }
+    }
+}
+
+enum Sink {
+    Stderr,
+    File(RotatingFile),
+}
+
+struct Logger {
+    levels: LevelSpec,
+    format: LogFormat,
+    sink: Mutex<Sink>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.levels.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_record(record, self.format);
+        let Ok(mut sink) = self.sink.lock() else {
+            return;
+        };
+        match &mut *sink {
+            Sink::Stderr => eprintln!("{}", line),
+            Sink::File(file) => {
+                if let Err(e) = file.write_line(&line) {
+                    eprintln!("port-kill: could not write log file: {}", e);
+                    eprintln!("{}", line);
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut sink) = self.sink.lock() {
+            if let Sink::File(file) = &mut *sink {
+                let _ = file.file.flush();
+            }
+        }
+    }
+}
+
+/// Install the logger for `--log-format`/`--log-file`, at the level in `RUST_LOG`
+pub fn init(args: &Args) -> Result<()> {
+    let levels = LevelSpec::parse(&std::env::var("RUST_LOG").unwrap_or_default());
+    let sink = match &args.log_file {
+        Some(path) => Sink::File(RotatingFile::open(Path::new(path), args.log_max_size.max(1) * 1024 * 1024)?),
+        None => Sink::Stderr,
+    };
+    let max = levels.max();
+    log::set_boxed_logger(Box::new(Logger {
+        levels,
+        format: args.log_format,
+        sink: Mutex::new(sink),
+    }))
+    .context("Logger already initialized")?;
+    log::set_max_level(max);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_and_json_fields() {
+        let levels = LevelSpec::parse("warn,port_kill::port_guard=debug");
+        assert_eq!(levels.level_for("port_kill::port_guard"), LevelFilter::Debug);
+        assert_eq!(levels.level_for("port_kill::port_guard_x"), LevelFilter::Warn);
+        assert_eq!(levels.max(), LevelFilter::Debug);
+        assert_eq!(LevelSpec::parse("").default, LevelFilter::Info);
+
+        let kvs: [(&str, Value); 3] = [
+            ("port", Value::from(3000u16)),
+            ("pid", Value::from(-1i32)),
+            ("action", Value::from("kill")),
+        ];
+        let record = Record::builder()
+            .args(format_args!("Killed node"))
+            .level(log::Level::Info)
+            .target("port_kill::process_monitor")
+            .key_values(&kvs)
+            .build();
+        let line: serde_json::Value = serde_json::from_str(&format_record(&record, LogFormat::Json)).unwrap();
+        assert_eq!(line["message"], "Killed node");
+        assert_eq!(line["port"], 3000);
+        assert_eq!(line["pid"], -1);
+        assert_eq!(line["action"], "kill");
+        assert!(format_record(&record, LogFormat::Text).ends_with("Killed node port=3000 pid=-1 action=kill"));
+    }
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = std::env::temp_dir().join(format!("port-kill-log-test-{}", std::process::id()));
+        let path = dir.join("port-kill.log");
+        let mut file = RotatingFile::open(&path, 20).unwrap();
+        for line in ["first line", "second line", "third line"] {
+            file.write_line(line).unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "third line\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "second line\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 2)).unwrap(), "first line\n");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
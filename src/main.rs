@@ -1,8 +1,6 @@
 #[cfg(target_os = "macos")]
 use anyhow::Result;
 #[cfg(target_os = "macos")]
-use clap::Parser;
-#[cfg(target_os = "macos")]
 use log::info;
 #[cfg(target_os = "macos")]
 use port_kill::cache::output::print_or_json;
@@ -10,8 +8,8 @@ use port_kill::cache::output::print_or_json;
 use port_kill::cache::{
     clean::clean_caches,
     doctor::doctor,
-    list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    list::{list_caches, print_list_table, CacheQuery},
+    restore::restore_backup,
 };
 use port_kill::update_check;
 #[cfg(target_os = "macos")]
@@ -19,8 +17,13 @@ use port_kill::{app::PortKillApp, cli::Args, console_app::ConsolePortKillApp};
 
 #[cfg(target_os = "macos")]
 fn main() -> Result<()> {
-    // Parse command-line arguments
-    let mut args = Args::parse();
+    // Webhooks about what this run did go out before it exits, however main returns
+    let _webhooks = port_kill::webhook::FlushOnExit;
+    // Parse command-line arguments (PORT_KILL_* environment variables fill in missing flags)
+    let mut args = Args::parse_with_env();
+    if args.offline {
+        port_kill::offline::enable();
+    }
 
     // Handle update check
     if args.check_updates {
@@ -42,15 +45,77 @@ fn main() -> Result<()> {
         }
     }
 
+    if let Some(output) = args.schema {
+        print!("{}", output.to_json());
+        return Ok(());
+    }
+
+    // Shell completion scripts, and the values they look up at tab time
+    if let Some(kind) = args.complete_values {
+        for value in port_kill::completions::values(kind) {
+            println!("{}", value);
+        }
+        return Ok(());
+    }
+    if let Some(port_kill::cli::CacheSubcommand::Completions(ref completions)) = args.cache {
+        if let Err(e) = port_kill::completions::run(completions.shell, completions.install) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Register or remove the `daemon` service (the flags before `daemon` are what it runs with)
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(ref daemon)) = args.cache {
+        if daemon.install_service || daemon.uninstall_service {
+            if let Err(e) = args.validate() {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
+            }
+            if let Err(e) = port_kill::daemon::manage_service(daemon) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
+    // Remove services, login items, completions and (with --purge) all state
+    if args.uninstall {
+        if let Err(e) = args.validate() {
+            eprintln!("Error: {}", e);
+            port_kill::exit_code::exit(1);
+        }
+        let report = port_kill::uninstall::uninstall(args.purge);
+        port_kill::uninstall::print_report(&report, args.purge);
+        if !report.failed.is_empty() {
+            port_kill::exit_code::exit(port_kill::exit_code::ERROR);
+        }
+        return Ok(());
+    }
+
+    // Open the tray at login, or stop doing so
+    if args.enable_autostart || args.disable_autostart {
+        if let Err(e) = port_kill::autostart::set_from_flag(args.enable_autostart) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
     // Determine if this is a quick operation that will exit early
     // Skip update check for these to avoid 1-5+ second network delays
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
+        || args.export_preset.is_some()
+        || args.import_preset.is_some()
+        || args.show_preset.is_some()
+        || args.edit_preset.is_some()
         || args.cache.is_some();
 
     // Check for updates only for long-running operations
-    if !is_quick_operation {
+    if !is_quick_operation && !args.no_update_check {
         let current_version = env!("CARGO_PKG_VERSION");
         let rt = tokio::runtime::Runtime::new()?;
         if let Ok(Some(update_info)) = rt.block_on(update_check::check_for_updates(current_version)) {
@@ -67,7 +132,7 @@ fn main() -> Result<()> {
             }
             Err(e) => {
                 eprintln!("Error: {}", e);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
         }
     }
@@ -77,7 +142,7 @@ fn main() -> Result<()> {
         // Validate arguments before building preset to catch malformed port specifications
         if let Err(e) = args.validate() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         let desc = args
             .preset_desc
@@ -87,12 +152,12 @@ fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         mgr.add_preset(preset);
         if let Err(e) = mgr.save_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         println!("✅ Saved preset '{}'.", name);
         return Ok(());
@@ -103,36 +168,113 @@ fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         match mgr.remove_preset(&name) {
             Some(_) => {
                 if let Err(e) = mgr.save_presets() {
                     eprintln!("Error: {}", e);
-                    std::process::exit(1);
+                    port_kill::exit_code::exit(1);
                 }
                 println!("🗑️  Deleted preset '{}'.", name);
             }
             None => {
                 eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Share presets through a file, a repo or a gist
+    if let Some(export) = args.export_preset.as_deref() {
+        let file = export.get(1).map(String::as_str);
+        if let Err(e) = port_kill::preset_manager::export_command(&export[0], file) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(source) = args.import_preset.as_deref() {
+        let import = port_kill::preset_manager::import_command(
+            source,
+            args.import_as.as_deref(),
+            args.overwrite,
+        );
+        if let Err(e) = tokio::runtime::Runtime::new()?.block_on(import) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Show or edit one preset
+    if let Some(name) = args.show_preset.clone() {
+        match args.show_preset(&name) {
+            Ok((definition, resolved)) => {
+                println!("{}", definition);
+                if let Some(ports) = resolved {
+                    eprintln!(
+                        "ℹ️  With the presets it extends: {}",
+                        port_kill::port_spec::describe(&ports)
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
             }
         }
         return Ok(());
     }
+    if let Some(name) = args.edit_preset.as_deref() {
+        if let Err(e) = port_kill::preset_manager::edit_command(name) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Leave safe mode for good (only announced the first time)
+    if args.trust_me && !port_kill::safe_mode::trusted_marker_path().exists() {
+        match port_kill::safe_mode::trust() {
+            Ok(path) => println!(
+                "🔓 Safe mode is off ({}): multi-kills no longer ask, system ports and processes can be killed.",
+                path.display()
+            ),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+        }
+    }
+
+    // Merge settings from ~/.port-kill/config.toml and the project's .portkill.toml
+    if let Err(e) = args.apply_layered_config() {
+        eprintln!("Error: {}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::notify::init(&args);
+    if args.demo {
+        port_kill::demo::enable();
+    }
+    if args.test_webhooks {
+        let ok = port_kill::webhook::test_all(&port_kill::notify::webhooks());
+        port_kill::exit_code::exit(if ok { 0 } else { 1 });
+    }
 
     // Apply preset if specified
     if let Some(preset_name) = args.preset.clone() {
         if let Err(e) = args.load_preset(&preset_name) {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
     }
 
     // Validate arguments
     if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        port_kill::exit_code::exit(1);
     }
 
     // Set up logging level based on log_level argument
@@ -144,28 +286,55 @@ fn main() -> Result<()> {
     };
     std::env::set_var("RUST_LOG", log_level);
 
-    // Initialize logging
-    env_logger::init();
+    // Initialize logging (the TUI keeps log lines for its own pane)
+    if args.tui {
+        port_kill::tui::init_logger();
+    } else if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {:#}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::audit_log::set_initiator(port_kill::audit_log::AuditInitiator::for_args(&args, true));
 
     info!("Starting Port Kill application...");
     info!("Monitoring: {}", args.get_port_description());
 
     // Handle cache subcommand: route to console-like behavior
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
+    if let Some(c) = args.cache.as_ref().and_then(|cmd| cmd.args()).cloned() {
+        let c = &c;
+        port_kill::cache::size::configure(c.scan_options());
+        port_kill::cache::backup::configure_retention(c.retention());
+        port_kill::cache::backup::configure_compression(c.compress_backups);
+        if c.pin.is_some() || c.unpin.is_some() {
+            if let Err(e) = port_kill::cache::rules::pin_command(c) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.watch {
+            let result = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(port_kill::cache::watch::watch(c));
+            if let Err(e) = result {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.schedule.is_some() || c.uninstall_schedule || c.schedule_log {
+            let result = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(port_kill::cache::schedule::schedule_command(c));
+            if let Err(e) = result {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
         if c.list || c.dry_run {
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
-                .block_on(list_caches(
-                    &c.lang,
-                    c.npx,
-                    c.js_pm,
-                    c.hf,
-                    c.torch,
-                    c.vercel,
-                    c.cloudflare,
-                    c.stale_days,
-                ));
+                .block_on(list_caches(&CacheQuery::from(c)));
             if c.json {
                 print_or_json(&resp, true);
             } else {
@@ -176,34 +345,44 @@ fn main() -> Result<()> {
         if c.clean {
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
-                .block_on(clean_caches(
-                    &c.lang,
-                    c.npx,
-                    c.js_pm,
-                    c.safe_delete,
-                    c.force,
-                    c.hf,
-                    c.torch,
-                    c.vercel,
-                    c.cloudflare,
-                    c.stale_days,
-                ));
+                .block_on(clean_caches(&CacheQuery::from(c)));
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
+        if c.backups {
+            let resp = port_kill::cache::backup::list_backups_response();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                port_kill::cache::backup::print_backups_table(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore_last || c.restore {
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
-                .block_on(restore_last_backup());
+                .block_on(restore_backup(
+                    c.backup.as_deref().unwrap_or("last"),
+                    &c.only,
+                ));
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
             return Ok(());
         }
         if c.doctor {
-            let report = tokio::runtime::Runtime::new().unwrap().block_on(doctor());
+            let report = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(doctor(c.fix));
             print_or_json(&report, c.json);
+            if c.fail_on.is_some_and(|level| {
+                report
+                    .worst_severity()
+                    .is_some_and(|worst| worst >= level)
+            }) {
+                port_kill::exit_code::exit(1);
+            }
             return Ok(());
         }
     }
@@ -229,6 +408,54 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.undo_last_kill {
+        port_kill::undo::undo_last_kill()?;
+        return Ok(());
+    }
+
+    if let Some(file) = args.import_history.as_deref() {
+        port_kill::history_store::import_command(file)?;
+        return Ok(());
+    }
+
+    if args.history_action.as_deref() == Some("prune") {
+        port_kill::history_store::prune_command()?;
+        return Ok(());
+    }
+
+    if let Some(spec) = args.nice.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.renice_port(&spec).await
+        })?;
+        return Ok(());
+    }
+
+    if args.show_audit {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.show_audit().await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Query(query)) = args.cache.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.run_query(&query).await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(_)) = args.cache {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(ConsolePortKillApp::run_daemon(args))?;
+        return Ok(());
+    }
+
     if let Some(port) = args.clear_restart {
         let rt = tokio::runtime::Runtime::new()?;
         rt.block_on(async {
@@ -301,6 +528,104 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.restart_failed {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.orchestrate_restart_failed().await
+        })?;
+        return Ok(());
+    }
+
+    if args.adopt {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.adopt_services().await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(service_name) = args.logs.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.show_service_logs(&service_name).await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(when) = args.at.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.show_port_map_at(&when).await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(port) = args.timeline {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.show_port_timeline(port).await
+        })?;
+        return Ok(());
+    }
+
+    if args.report.is_some() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.show_port_report().await
+        })?;
+        return Ok(());
+    }
+
+    if let Some(addr) = args.serve.clone() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.serve_api(&addr).await
+        })?;
+        return Ok(());
+    }
+
+    if args.tui {
+        args.performance = true; // CPU and memory columns
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            port_kill::tui::TuiApp::new(app.process_monitor(), app.args().clone())
+                .run()
+                .await
+        })?;
+        return Ok(());
+    }
+
+    if args.what_if {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.preset_what_if().await
+        })?;
+        return Ok(());
+    }
+
+    if args.once
+        || args.fail_if_occupied.is_some()
+        || args.wait_free.is_some()
+        || args.wait_open.is_some()
+        || args.status_page.is_some()
+    {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let app = ConsolePortKillApp::new(args)?;
+            app.run().await
+        })?;
+        return Ok(());
+    }
+
     // Create and run the application (GUI mode)
     let app = PortKillApp::new(args)?;
     app.run()?;
@@ -312,8 +637,6 @@ fn main() -> Result<()> {
 #[cfg(target_os = "windows")]
 use anyhow::Result;
 #[cfg(target_os = "windows")]
-use clap::Parser;
-#[cfg(target_os = "windows")]
 use log::info;
 #[cfg(target_os = "windows")]
 use port_kill::cache::output::print_or_json;
@@ -321,8 +644,8 @@ use port_kill::cache::output::print_or_json;
 use port_kill::cache::{
     clean::clean_caches,
     doctor::doctor,
-    list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    list::{list_caches, print_list_table, CacheQuery},
+    restore::restore_backup,
 };
 #[cfg(target_os = "windows")]
 use port_kill::{cli::Args, console_app::ConsolePortKillApp};
@@ -330,8 +653,13 @@ use port_kill::{cli::Args, console_app::ConsolePortKillApp};
 #[cfg(target_os = "windows")]
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Parse command-line arguments
-    let mut args = Args::parse();
+    // Webhooks about what this run did go out before it exits, however main returns
+    let _webhooks = port_kill::webhook::FlushOnExit;
+    // Parse command-line arguments (PORT_KILL_* environment variables fill in missing flags)
+    let mut args = Args::parse_with_env();
+    if args.offline {
+        port_kill::offline::enable();
+    }
 
     // Handle self-update
     if args.self_update {
@@ -363,15 +691,77 @@ async fn main() -> Result<()> {
         }
     }
 
+    if let Some(output) = args.schema {
+        print!("{}", output.to_json());
+        return Ok(());
+    }
+
+    // Shell completion scripts, and the values they look up at tab time
+    if let Some(kind) = args.complete_values {
+        for value in port_kill::completions::values(kind) {
+            println!("{}", value);
+        }
+        return Ok(());
+    }
+    if let Some(port_kill::cli::CacheSubcommand::Completions(ref completions)) = args.cache {
+        if let Err(e) = port_kill::completions::run(completions.shell, completions.install) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Register or remove the `daemon` service (the flags before `daemon` are what it runs with)
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(ref daemon)) = args.cache {
+        if daemon.install_service || daemon.uninstall_service {
+            if let Err(e) = args.validate() {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
+            }
+            if let Err(e) = port_kill::daemon::manage_service(daemon) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
+    // Remove services, login items, completions and (with --purge) all state
+    if args.uninstall {
+        if let Err(e) = args.validate() {
+            eprintln!("Error: {}", e);
+            port_kill::exit_code::exit(1);
+        }
+        let report = port_kill::uninstall::uninstall(args.purge);
+        port_kill::uninstall::print_report(&report, args.purge);
+        if !report.failed.is_empty() {
+            port_kill::exit_code::exit(port_kill::exit_code::ERROR);
+        }
+        return Ok(());
+    }
+
+    // Open the tray at login, or stop doing so
+    if args.enable_autostart || args.disable_autostart {
+        if let Err(e) = port_kill::autostart::set_from_flag(args.enable_autostart) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
     // Determine if this is a quick operation that will exit early
     // Skip update check for these to avoid 1-5+ second network delays
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
+        || args.export_preset.is_some()
+        || args.import_preset.is_some()
+        || args.show_preset.is_some()
+        || args.edit_preset.is_some()
         || args.cache.is_some();
 
     // Check for updates only for long-running operations
-    if !is_quick_operation {
+    if !is_quick_operation && !args.no_update_check {
         let current_version = env!("CARGO_PKG_VERSION");
         if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version).await {
             port_kill::update_check::print_update_notification(&update_info);
@@ -387,7 +777,7 @@ async fn main() -> Result<()> {
             }
             Err(e) => {
                 eprintln!("Error: {}", e);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
         }
     }
@@ -397,7 +787,7 @@ async fn main() -> Result<()> {
         // Validate arguments before building preset to catch malformed port specifications
         if let Err(e) = args.validate() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         let desc = args
             .preset_desc
@@ -407,12 +797,12 @@ async fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         mgr.add_preset(preset);
         if let Err(e) = mgr.save_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         println!("✅ Saved preset '{}'.", name);
         return Ok(());
@@ -423,36 +813,114 @@ async fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         match mgr.remove_preset(&name) {
             Some(_) => {
                 if let Err(e) = mgr.save_presets() {
                     eprintln!("Error: {}", e);
-                    std::process::exit(1);
+                    port_kill::exit_code::exit(1);
                 }
                 println!("🗑️  Deleted preset '{}'.", name);
             }
             None => {
                 eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Share presets through a file, a repo or a gist
+    if let Some(export) = args.export_preset.as_deref() {
+        let file = export.get(1).map(String::as_str);
+        if let Err(e) = port_kill::preset_manager::export_command(&export[0], file) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(source) = args.import_preset.as_deref() {
+        if let Err(e) = port_kill::preset_manager::import_command(
+            source,
+            args.import_as.as_deref(),
+            args.overwrite,
+        )
+        .await
+        {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Show or edit one preset
+    if let Some(name) = args.show_preset.clone() {
+        match args.show_preset(&name) {
+            Ok((definition, resolved)) => {
+                println!("{}", definition);
+                if let Some(ports) = resolved {
+                    eprintln!(
+                        "ℹ️  With the presets it extends: {}",
+                        port_kill::port_spec::describe(&ports)
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
             }
         }
         return Ok(());
     }
+    if let Some(name) = args.edit_preset.as_deref() {
+        if let Err(e) = port_kill::preset_manager::edit_command(name) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Leave safe mode for good (only announced the first time)
+    if args.trust_me && !port_kill::safe_mode::trusted_marker_path().exists() {
+        match port_kill::safe_mode::trust() {
+            Ok(path) => println!(
+                "🔓 Safe mode is off ({}): multi-kills no longer ask, system ports and processes can be killed.",
+                path.display()
+            ),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+        }
+    }
+
+    // Merge settings from ~/.port-kill/config.toml and the project's .portkill.toml
+    if let Err(e) = args.apply_layered_config() {
+        eprintln!("Error: {}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::notify::init(&args);
+    if args.demo {
+        port_kill::demo::enable();
+    }
+    if args.test_webhooks {
+        let ok = port_kill::webhook::test_all(&port_kill::notify::webhooks());
+        port_kill::exit_code::exit(if ok { 0 } else { 1 });
+    }
 
     // Apply preset if specified
     if let Some(preset_name) = args.preset.clone() {
         if let Err(e) = args.load_preset(&preset_name) {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
     }
 
     // Validate arguments
     if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        port_kill::exit_code::exit(1);
     }
 
     // Set up logging level based on log_level argument
@@ -464,27 +932,47 @@ async fn main() -> Result<()> {
     };
     std::env::set_var("RUST_LOG", log_level);
 
-    // Initialize logging
-    env_logger::init();
+    // Initialize logging (the TUI keeps log lines for its own pane)
+    if args.tui {
+        port_kill::tui::init_logger();
+    } else if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {:#}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::audit_log::set_initiator(port_kill::audit_log::AuditInitiator::for_args(&args, true));
 
     info!("Starting Port Kill application on Windows...");
     info!("Monitoring: {}", args.get_port_description());
 
     // Handle cache subcommand: route to console-like behavior
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
+    if let Some(c) = args.cache.as_ref().and_then(|cmd| cmd.args()).cloned() {
+        let c = &c;
+        port_kill::cache::size::configure(c.scan_options());
+        port_kill::cache::backup::configure_retention(c.retention());
+        port_kill::cache::backup::configure_compression(c.compress_backups);
+        if c.pin.is_some() || c.unpin.is_some() {
+            if let Err(e) = port_kill::cache::rules::pin_command(c) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.watch {
+            if let Err(e) = port_kill::cache::watch::watch(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.schedule.is_some() || c.uninstall_schedule || c.schedule_log {
+            if let Err(e) = port_kill::cache::schedule::schedule_command(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
         if c.list || c.dry_run {
-            let resp = list_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
-                c.stale_days,
-            )
-            .await;
+            let resp = list_caches(&CacheQuery::from(c)).await;
             if c.json {
                 print_or_json(&resp, true);
             } else {
@@ -493,33 +981,37 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.clean {
-            let resp = clean_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
-                c.safe_delete,
-                c.force,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
-                c.stale_days,
-            )
-            .await;
+            let resp = clean_caches(&CacheQuery::from(c)).await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.backups {
+            let resp = port_kill::cache::backup::list_backups_response();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                port_kill::cache::backup::print_backups_table(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore_last || c.restore {
+            let resp = restore_backup(c.backup.as_deref().unwrap_or("last"), &c.only).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix).await;
             print_or_json(&report, c.json);
+            if c.fail_on.is_some_and(|level| {
+                report
+                    .worst_severity()
+                    .is_some_and(|worst| worst >= level)
+            }) {
+                port_kill::exit_code::exit(1);
+            }
             return Ok(());
         }
     }
@@ -538,6 +1030,44 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.undo_last_kill {
+        port_kill::undo::undo_last_kill()?;
+        return Ok(());
+    }
+
+    if let Some(file) = args.import_history.as_deref() {
+        port_kill::history_store::import_command(file)?;
+        return Ok(());
+    }
+
+    if args.history_action.as_deref() == Some("prune") {
+        port_kill::history_store::prune_command()?;
+        return Ok(());
+    }
+
+    if let Some(spec) = args.nice.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.renice_port(&spec).await?;
+        return Ok(());
+    }
+
+    if args.show_audit {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_audit().await?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Query(query)) = args.cache.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.run_query(&query).await?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(_)) = args.cache {
+        ConsolePortKillApp::run_daemon(args).await?;
+        return Ok(());
+    }
+
     if let Some(port) = args.clear_restart {
         let app = ConsolePortKillApp::new(args)?;
         app.clear_restart_history(port).await?;
@@ -586,6 +1116,57 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.restart_failed {
+        let app = ConsolePortKillApp::new(args)?;
+        app.orchestrate_restart_failed().await?;
+        return Ok(());
+    }
+
+    if args.adopt {
+        let app = ConsolePortKillApp::new(args)?;
+        app.adopt_services().await?;
+        return Ok(());
+    }
+
+    if let Some(service_name) = args.logs.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_service_logs(&service_name).await?;
+        return Ok(());
+    }
+
+    if let Some(when) = args.at.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_map_at(&when).await?;
+        return Ok(());
+    }
+
+    if let Some(port) = args.timeline {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_timeline(port).await?;
+        return Ok(());
+    }
+
+    if args.report.is_some() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_report().await?;
+        return Ok(());
+    }
+
+    if let Some(addr) = args.serve.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.serve_api(&addr).await?;
+        return Ok(());
+    }
+
+    if args.tui {
+        args.performance = true; // CPU and memory columns
+        let app = ConsolePortKillApp::new(args)?;
+        port_kill::tui::TuiApp::new(app.process_monitor(), app.args().clone())
+            .run()
+            .await?;
+        return Ok(());
+    }
+
     // Create and run the console application
     let app = ConsolePortKillApp::new(args)?;
     app.run().await?;
@@ -597,8 +1178,6 @@ async fn main() -> Result<()> {
 #[cfg(target_os = "linux")]
 use anyhow::Result;
 #[cfg(target_os = "linux")]
-use clap::Parser;
-#[cfg(target_os = "linux")]
 use log::info;
 #[cfg(target_os = "linux")]
 use port_kill::cache::output::print_or_json;
@@ -606,8 +1185,8 @@ use port_kill::cache::output::print_or_json;
 use port_kill::cache::{
     clean::clean_caches,
     doctor::doctor,
-    list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    list::{list_caches, print_list_table, CacheQuery},
+    restore::restore_backup,
 };
 #[cfg(target_os = "linux")]
 use port_kill::{cli::Args, console_app::ConsolePortKillApp};
@@ -615,8 +1194,13 @@ use port_kill::{cli::Args, console_app::ConsolePortKillApp};
 #[cfg(target_os = "linux")]
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Parse command-line arguments
-    let mut args = Args::parse();
+    // Webhooks about what this run did go out before it exits, however main returns
+    let _webhooks = port_kill::webhook::FlushOnExit;
+    // Parse command-line arguments (PORT_KILL_* environment variables fill in missing flags)
+    let mut args = Args::parse_with_env();
+    if args.offline {
+        port_kill::offline::enable();
+    }
 
     // Handle self-update
     if args.self_update {
@@ -648,15 +1232,77 @@ async fn main() -> Result<()> {
         }
     }
 
+    if let Some(output) = args.schema {
+        print!("{}", output.to_json());
+        return Ok(());
+    }
+
+    // Shell completion scripts, and the values they look up at tab time
+    if let Some(kind) = args.complete_values {
+        for value in port_kill::completions::values(kind) {
+            println!("{}", value);
+        }
+        return Ok(());
+    }
+    if let Some(port_kill::cli::CacheSubcommand::Completions(ref completions)) = args.cache {
+        if let Err(e) = port_kill::completions::run(completions.shell, completions.install) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Register or remove the `daemon` service (the flags before `daemon` are what it runs with)
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(ref daemon)) = args.cache {
+        if daemon.install_service || daemon.uninstall_service {
+            if let Err(e) = args.validate() {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
+            }
+            if let Err(e) = port_kill::daemon::manage_service(daemon) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
+    // Remove services, login items, completions and (with --purge) all state
+    if args.uninstall {
+        if let Err(e) = args.validate() {
+            eprintln!("Error: {}", e);
+            port_kill::exit_code::exit(1);
+        }
+        let report = port_kill::uninstall::uninstall(args.purge);
+        port_kill::uninstall::print_report(&report, args.purge);
+        if !report.failed.is_empty() {
+            port_kill::exit_code::exit(port_kill::exit_code::ERROR);
+        }
+        return Ok(());
+    }
+
+    // Open the tray at login, or stop doing so
+    if args.enable_autostart || args.disable_autostart {
+        if let Err(e) = port_kill::autostart::set_from_flag(args.enable_autostart) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
     // Determine if this is a quick operation that will exit early
     // Skip update check for these to avoid 1-5+ second network delays
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
+        || args.export_preset.is_some()
+        || args.import_preset.is_some()
+        || args.show_preset.is_some()
+        || args.edit_preset.is_some()
         || args.cache.is_some();
 
     // Check for updates only for long-running operations
-    if !is_quick_operation {
+    if !is_quick_operation && !args.no_update_check {
         let current_version = env!("CARGO_PKG_VERSION");
         if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version).await {
             port_kill::update_check::print_update_notification(&update_info);
@@ -672,7 +1318,7 @@ async fn main() -> Result<()> {
             }
             Err(e) => {
                 eprintln!("Error: {}", e);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
         }
     }
@@ -682,7 +1328,7 @@ async fn main() -> Result<()> {
         // Validate arguments before building preset to catch malformed port specifications
         if let Err(e) = args.validate() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         let desc = args
             .preset_desc
@@ -692,12 +1338,12 @@ async fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         mgr.add_preset(preset);
         if let Err(e) = mgr.save_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         println!("✅ Saved preset '{}'.", name);
         return Ok(());
@@ -708,36 +1354,114 @@ async fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         match mgr.remove_preset(&name) {
             Some(_) => {
                 if let Err(e) = mgr.save_presets() {
                     eprintln!("Error: {}", e);
-                    std::process::exit(1);
+                    port_kill::exit_code::exit(1);
                 }
                 println!("🗑️  Deleted preset '{}'.", name);
             }
             None => {
                 eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Share presets through a file, a repo or a gist
+    if let Some(export) = args.export_preset.as_deref() {
+        let file = export.get(1).map(String::as_str);
+        if let Err(e) = port_kill::preset_manager::export_command(&export[0], file) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(source) = args.import_preset.as_deref() {
+        if let Err(e) = port_kill::preset_manager::import_command(
+            source,
+            args.import_as.as_deref(),
+            args.overwrite,
+        )
+        .await
+        {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Show or edit one preset
+    if let Some(name) = args.show_preset.clone() {
+        match args.show_preset(&name) {
+            Ok((definition, resolved)) => {
+                println!("{}", definition);
+                if let Some(ports) = resolved {
+                    eprintln!(
+                        "ℹ️  With the presets it extends: {}",
+                        port_kill::port_spec::describe(&ports)
+                    );
+                }
             }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(name) = args.edit_preset.as_deref() {
+        if let Err(e) = port_kill::preset_manager::edit_command(name) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
         }
         return Ok(());
     }
 
+    // Leave safe mode for good (only announced the first time)
+    if args.trust_me && !port_kill::safe_mode::trusted_marker_path().exists() {
+        match port_kill::safe_mode::trust() {
+            Ok(path) => println!(
+                "🔓 Safe mode is off ({}): multi-kills no longer ask, system ports and processes can be killed.",
+                path.display()
+            ),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+        }
+    }
+
+    // Merge settings from ~/.port-kill/config.toml and the project's .portkill.toml
+    if let Err(e) = args.apply_layered_config() {
+        eprintln!("Error: {}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::notify::init(&args);
+    if args.demo {
+        port_kill::demo::enable();
+    }
+    if args.test_webhooks {
+        let ok = port_kill::webhook::test_all(&port_kill::notify::webhooks());
+        port_kill::exit_code::exit(if ok { 0 } else { 1 });
+    }
+
     // Apply preset if specified
     if let Some(preset_name) = args.preset.clone() {
         if let Err(e) = args.load_preset(&preset_name) {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
     }
 
     // Validate arguments
     if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        port_kill::exit_code::exit(1);
     }
 
     // Set up logging level based on log_level argument
@@ -749,27 +1473,47 @@ async fn main() -> Result<()> {
     };
     std::env::set_var("RUST_LOG", log_level);
 
-    // Initialize logging
-    env_logger::init();
+    // Initialize logging (the TUI keeps log lines for its own pane)
+    if args.tui {
+        port_kill::tui::init_logger();
+    } else if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {:#}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::audit_log::set_initiator(port_kill::audit_log::AuditInitiator::for_args(&args, true));
 
     info!("Starting Port Kill application on Linux...");
     info!("Monitoring: {}", args.get_port_description());
 
     // Handle cache subcommand: route to console-like behavior
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
+    if let Some(c) = args.cache.as_ref().and_then(|cmd| cmd.args()).cloned() {
+        let c = &c;
+        port_kill::cache::size::configure(c.scan_options());
+        port_kill::cache::backup::configure_retention(c.retention());
+        port_kill::cache::backup::configure_compression(c.compress_backups);
+        if c.pin.is_some() || c.unpin.is_some() {
+            if let Err(e) = port_kill::cache::rules::pin_command(c) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.watch {
+            if let Err(e) = port_kill::cache::watch::watch(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.schedule.is_some() || c.uninstall_schedule || c.schedule_log {
+            if let Err(e) = port_kill::cache::schedule::schedule_command(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
         if c.list || c.dry_run {
-            let resp = list_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
-                c.stale_days,
-            )
-            .await;
+            let resp = list_caches(&CacheQuery::from(c)).await;
             if c.json {
                 print_or_json(&resp, true);
             } else {
@@ -778,33 +1522,37 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.clean {
-            let resp = clean_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
-                c.safe_delete,
-                c.force,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
-                c.stale_days,
-            )
-            .await;
+            let resp = clean_caches(&CacheQuery::from(c)).await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.backups {
+            let resp = port_kill::cache::backup::list_backups_response();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                port_kill::cache::backup::print_backups_table(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore_last || c.restore {
+            let resp = restore_backup(c.backup.as_deref().unwrap_or("last"), &c.only).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix).await;
             print_or_json(&report, c.json);
+            if c.fail_on.is_some_and(|level| {
+                report
+                    .worst_severity()
+                    .is_some_and(|worst| worst >= level)
+            }) {
+                port_kill::exit_code::exit(1);
+            }
             return Ok(());
         }
     }
@@ -823,6 +1571,44 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.undo_last_kill {
+        port_kill::undo::undo_last_kill()?;
+        return Ok(());
+    }
+
+    if let Some(file) = args.import_history.as_deref() {
+        port_kill::history_store::import_command(file)?;
+        return Ok(());
+    }
+
+    if args.history_action.as_deref() == Some("prune") {
+        port_kill::history_store::prune_command()?;
+        return Ok(());
+    }
+
+    if let Some(spec) = args.nice.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.renice_port(&spec).await?;
+        return Ok(());
+    }
+
+    if args.show_audit {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_audit().await?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Query(query)) = args.cache.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.run_query(&query).await?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(_)) = args.cache {
+        ConsolePortKillApp::run_daemon(args).await?;
+        return Ok(());
+    }
+
     if let Some(port) = args.clear_restart {
         let app = ConsolePortKillApp::new(args)?;
         app.clear_restart_history(port).await?;
@@ -871,6 +1657,57 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.restart_failed {
+        let app = ConsolePortKillApp::new(args)?;
+        app.orchestrate_restart_failed().await?;
+        return Ok(());
+    }
+
+    if args.adopt {
+        let app = ConsolePortKillApp::new(args)?;
+        app.adopt_services().await?;
+        return Ok(());
+    }
+
+    if let Some(service_name) = args.logs.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_service_logs(&service_name).await?;
+        return Ok(());
+    }
+
+    if let Some(when) = args.at.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_map_at(&when).await?;
+        return Ok(());
+    }
+
+    if let Some(port) = args.timeline {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_timeline(port).await?;
+        return Ok(());
+    }
+
+    if args.report.is_some() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_report().await?;
+        return Ok(());
+    }
+
+    if let Some(addr) = args.serve.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.serve_api(&addr).await?;
+        return Ok(());
+    }
+
+    if args.tui {
+        args.performance = true; // CPU and memory columns
+        let app = ConsolePortKillApp::new(args)?;
+        port_kill::tui::TuiApp::new(app.process_monitor(), app.args().clone())
+            .run()
+            .await?;
+        return Ok(());
+    }
+
     // Create and run the console application
     let app = ConsolePortKillApp::new(args)?;
     app.run().await?;
@@ -884,5 +1721,5 @@ fn main() {
     eprintln!("Error: This binary is only available on macOS, Windows, and Linux.");
     eprintln!("For other platforms, use the platform-specific binaries:");
     eprintln!("  - Console mode (all platforms): ./run.sh --console");
-    std::process::exit(1);
+    port_kill::exit_code::exit(1);
 }
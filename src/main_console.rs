@@ -1,24 +1,29 @@
 use anyhow::Result;
-use clap::Parser;
 use log::info;
 use port_kill::cache::output::print_or_json;
 use port_kill::cache::{
     clean::clean_caches,
     doctor::doctor,
-    list::{list_caches, print_list_table},
-    restore::restore_last_backup,
+    list::{list_caches, print_list_table, CacheQuery},
+    restore::restore_backup,
 };
 use port_kill::update_check;
 use port_kill::{
     cli::Args,
     console_app::ConsolePortKillApp,
     scripting::{load_script_file, ScriptEngine},
+    tui::TuiApp,
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Parse command-line arguments
-    let mut args = Args::parse();
+    // Webhooks about what this run did go out before it exits, however main returns
+    let _webhooks = port_kill::webhook::FlushOnExit;
+    // Parse command-line arguments (PORT_KILL_* environment variables fill in missing flags)
+    let mut args = Args::parse_with_env();
+    if args.offline {
+        port_kill::offline::enable();
+    }
 
     // Handle self-update
     if args.self_update {
@@ -50,15 +55,84 @@ async fn main() -> Result<()> {
         }
     }
 
+    if let Some(output) = args.schema {
+        print!("{}", output.to_json());
+        return Ok(());
+    }
+
+    // Shell completion scripts, and the values they look up at tab time
+    if let Some(kind) = args.complete_values {
+        for value in port_kill::completions::values(kind) {
+            println!("{}", value);
+        }
+        return Ok(());
+    }
+    if let Some(port_kill::cli::CacheSubcommand::Completions(ref completions)) = args.cache {
+        if let Err(e) = port_kill::completions::run(completions.shell, completions.install) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Register or remove the `daemon` service (the flags before `daemon` are what it runs with)
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(ref daemon)) = args.cache {
+        if daemon.install_service || daemon.uninstall_service {
+            if let Err(e) = args.validate() {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
+            }
+            if let Err(e) = port_kill::daemon::manage_service(daemon) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
+    // Remove services, login items, completions and (with --purge) all state
+    if args.uninstall {
+        if let Err(e) = args.validate() {
+            eprintln!("Error: {}", e);
+            port_kill::exit_code::exit(1);
+        }
+        let report = port_kill::uninstall::uninstall(args.purge);
+        port_kill::uninstall::print_report(&report, args.purge);
+        if !report.failed.is_empty() {
+            port_kill::exit_code::exit(port_kill::exit_code::ERROR);
+        }
+        return Ok(());
+    }
+
+    // Open the tray at login, or stop doing so
+    if args.enable_autostart || args.disable_autostart {
+        if let Err(e) = port_kill::autostart::set_from_flag(args.enable_autostart) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
     // Determine if this is a quick operation that will exit early
     // Skip update check for these to avoid 1-5+ second network delays
     let is_quick_operation = args.list_presets
         || args.save_preset.is_some()
         || args.delete_preset.is_some()
+        || args.export_preset.is_some()
+        || args.import_preset.is_some()
+        || args.show_preset.is_some()
+        || args.edit_preset.is_some()
+        || args.ignore.is_some()
+        || args.apply_suggestions
+        || args.import_history.is_some()
+        || args.history_action.is_some()
+        || args.reserve.is_some()
+        || args.unreserve.is_some()
+        || args.reservations
         || args.cache.is_some();
 
     // Check for updates only for long-running operations
-    if !is_quick_operation {
+    if !is_quick_operation && !args.no_update_check {
         let current_version = env!("CARGO_PKG_VERSION");
         if let Ok(Some(update_info)) = update_check::check_for_updates(current_version).await {
             update_check::print_update_notification(&update_info);
@@ -74,7 +148,7 @@ async fn main() -> Result<()> {
             }
             Err(e) => {
                 eprintln!("Error: {}", e);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
         }
     }
@@ -84,7 +158,7 @@ async fn main() -> Result<()> {
         // Validate arguments before building preset to catch malformed port specifications
         if let Err(e) = args.validate() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         let desc = args
             .preset_desc
@@ -94,12 +168,12 @@ async fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         mgr.add_preset(preset);
         if let Err(e) = mgr.save_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         println!("✅ Saved preset '{}'.", name);
         return Ok(());
@@ -110,53 +184,186 @@ async fn main() -> Result<()> {
         let mut mgr = port_kill::preset_manager::PresetManager::new();
         if let Err(e) = mgr.load_presets() {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
         match mgr.remove_preset(&name) {
             Some(_) => {
                 if let Err(e) = mgr.save_presets() {
                     eprintln!("Error: {}", e);
-                    std::process::exit(1);
+                    port_kill::exit_code::exit(1);
                 }
                 println!("🗑️  Deleted preset '{}'.", name);
             }
             None => {
                 eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Share presets through a file, a repo or a gist
+    if let Some(export) = args.export_preset.as_deref() {
+        let file = export.get(1).map(String::as_str);
+        if let Err(e) = port_kill::preset_manager::export_command(&export[0], file) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(source) = args.import_preset.as_deref() {
+        if let Err(e) = port_kill::preset_manager::import_command(
+            source,
+            args.import_as.as_deref(),
+            args.overwrite,
+        )
+        .await
+        {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Show or edit one preset
+    if let Some(name) = args.show_preset.clone() {
+        match args.show_preset(&name) {
+            Ok((definition, resolved)) => {
+                println!("{}", definition);
+                if let Some(ports) = resolved {
+                    eprintln!(
+                        "ℹ️  With the presets it extends: {}",
+                        port_kill::port_spec::describe(&ports)
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                port_kill::exit_code::exit(1);
             }
         }
         return Ok(());
     }
+    if let Some(name) = args.edit_preset.as_deref() {
+        if let Err(e) = port_kill::preset_manager::edit_command(name) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Ignore lists in the global config file
+    if let Some(ignore) = &args.ignore {
+        if let Err(e) = port_kill::ignore_list::command(ignore, args.json) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+    if args.apply_suggestions {
+        if let Err(e) = port_kill::ignore_list::apply_suggestions() {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Port reservations, enforced by the guard
+    if args.reserve.is_some() || args.unreserve.is_some() || args.reservations {
+        let path = std::path::PathBuf::from(args.get_reservation_file_path());
+        let result = if let Some(port) = args.reserve {
+            port_kill::reservations::reserve_command(
+                &path,
+                port,
+                args.process_name.as_deref().unwrap_or_default(),
+                args.project_name.as_deref(),
+                args.expires_in.as_deref(),
+                !args.no_renew,
+                args.on_conflict,
+            )
+        } else if let Some(port) = args.unreserve {
+            port_kill::reservations::unreserve_command(&path, port)
+        } else {
+            port_kill::reservations::list_command(&path, args.json)
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Leave safe mode for good (only announced the first time)
+    if args.trust_me && !port_kill::safe_mode::trusted_marker_path().exists() {
+        match port_kill::safe_mode::trust() {
+            Ok(path) => println!(
+                "🔓 Safe mode is off ({}): multi-kills no longer ask, system ports and processes can be killed.",
+                path.display()
+            ),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+        }
+    }
+
+    // Merge settings from ~/.port-kill/config.toml and the project's .portkill.toml
+    if let Err(e) = args.apply_layered_config() {
+        eprintln!("Error: {}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::notify::init(&args);
+    if args.demo {
+        port_kill::demo::enable();
+    }
+    if args.test_webhooks {
+        let ok = port_kill::webhook::test_all(&port_kill::notify::webhooks());
+        port_kill::exit_code::exit(if ok { 0 } else { 1 });
+    }
 
     // Apply preset if specified
     if let Some(preset_name) = args.preset.clone() {
         if let Err(e) = args.load_preset(&preset_name) {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            port_kill::exit_code::exit(1);
         }
     }
 
     // Validate arguments
     if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        port_kill::exit_code::exit(1);
     }
 
     // Handle cache subcommand
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
+    if let Some(c) = args.cache.as_ref().and_then(|cmd| cmd.args()).cloned() {
+        let c = &c;
+        port_kill::cache::size::configure(c.scan_options());
+        port_kill::cache::backup::configure_retention(c.retention());
+        port_kill::cache::backup::configure_compression(c.compress_backups);
+        if c.pin.is_some() || c.unpin.is_some() {
+            if let Err(e) = port_kill::cache::rules::pin_command(c) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.watch {
+            if let Err(e) = port_kill::cache::watch::watch(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.schedule.is_some() || c.uninstall_schedule || c.schedule_log {
+            if let Err(e) = port_kill::cache::schedule::schedule_command(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
         if c.list || c.dry_run {
-            let resp = list_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
-                c.stale_days,
-            )
-            .await;
+            let resp = list_caches(&CacheQuery::from(c)).await;
             if c.json {
                 print_or_json(&resp, true);
             } else {
@@ -165,33 +372,37 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.clean {
-            let resp = clean_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
-                c.safe_delete,
-                c.force,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
-                c.stale_days,
-            )
-            .await;
+            let resp = clean_caches(&CacheQuery::from(c)).await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.backups {
+            let resp = port_kill::cache::backup::list_backups_response();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                port_kill::cache::backup::print_backups_table(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore_last || c.restore {
+            let resp = restore_backup(c.backup.as_deref().unwrap_or("last"), &c.only).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix).await;
             print_or_json(&report, c.json);
+            if c.fail_on.is_some_and(|level| {
+                report
+                    .worst_severity()
+                    .is_some_and(|worst| worst >= level)
+            }) {
+                port_kill::exit_code::exit(1);
+            }
             return Ok(());
         }
     }
@@ -203,8 +414,14 @@ async fn main() -> Result<()> {
         std::env::set_var("RUST_LOG", "info");
     }
 
-    // Initialize logging
-    env_logger::init();
+    // Initialize logging (the TUI keeps log lines for its own pane)
+    if args.tui {
+        port_kill::tui::init_logger();
+    } else if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {:#}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::audit_log::set_initiator(port_kill::audit_log::AuditInitiator::for_args(&args, false));
 
     info!("Starting Console Port Kill application...");
     info!("Monitoring: {}", args.get_port_description());
@@ -222,6 +439,22 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(file) = args.import_history.as_deref() {
+        if let Err(e) = port_kill::history_store::import_command(file) {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.history_action.as_deref() == Some("prune") {
+        if let Err(e) = port_kill::history_store::prune_command() {
+            eprintln!("Error: {:#}", e);
+            port_kill::exit_code::exit(1);
+        }
+        return Ok(());
+    }
+
     if args.show_filters {
         let app = ConsolePortKillApp::new(args)?;
         app.display_filter_info().await?;
@@ -241,6 +474,18 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.kill_gpu_hogs {
+        let app = ConsolePortKillApp::new(args)?;
+        app.kill_gpu_hogs().await?;
+        return Ok(());
+    }
+
+    if args.kill_duplicates {
+        let app = ConsolePortKillApp::new(args)?;
+        app.kill_duplicates().await?;
+        return Ok(());
+    }
+
     if let Some(ref projects) = args.kill_project {
         let projects: Vec<String> = projects.clone();
         let app = ConsolePortKillApp::new(args)?;
@@ -260,6 +505,34 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.undo_last_kill {
+        port_kill::undo::undo_last_kill()?;
+        return Ok(());
+    }
+
+    if let Some(spec) = args.nice.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.renice_port(&spec).await?;
+        return Ok(());
+    }
+
+    if args.show_audit {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_audit().await?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Query(query)) = args.cache.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.run_query(&query).await?;
+        return Ok(());
+    }
+
+    if let Some(port_kill::cli::CacheSubcommand::Daemon(_)) = args.cache {
+        ConsolePortKillApp::run_daemon(args).await?;
+        return Ok(());
+    }
+
     if let Some(port) = args.clear_restart {
         let app = ConsolePortKillApp::new(args)?;
         app.clear_restart_history(port).await?;
@@ -308,9 +581,32 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.restart_failed {
+        let app = ConsolePortKillApp::new(args)?;
+        app.orchestrate_restart_failed().await?;
+        return Ok(());
+    }
+
+    if args.adopt {
+        let app = ConsolePortKillApp::new(args)?;
+        app.adopt_services().await?;
+        return Ok(());
+    }
+
+    if let Some(service_name) = args.logs.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_service_logs(&service_name).await?;
+        return Ok(());
+    }
+
     if args.reset {
+        let deep = args.deep;
         let app = ConsolePortKillApp::new(args)?;
-        app.reset_development_ports().await?;
+        if deep {
+            app.reset_project().await?;
+        } else {
+            app.reset_development_ports().await?;
+        }
         return Ok(());
     }
 
@@ -344,12 +640,36 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(when) = args.at.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_map_at(&when).await?;
+        return Ok(());
+    }
+
+    if let Some(port) = args.timeline {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_timeline(port).await?;
+        return Ok(());
+    }
+
+    if args.report.is_some() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_port_report().await?;
+        return Ok(());
+    }
+
     if args.audit {
         let app = ConsolePortKillApp::new(args)?;
         app.perform_security_audit().await?;
         return Ok(());
     }
 
+    if let Some(addr) = args.serve.clone() {
+        let app = ConsolePortKillApp::new(args)?;
+        app.serve_api(&addr).await?;
+        return Ok(());
+    }
+
     // Handle remote mode
     if let Some(remote_host) = args.get_remote_host() {
         let app = ConsolePortKillApp::new(args)?;
@@ -358,6 +678,10 @@ async fn main() -> Result<()> {
     }
 
     if args.guard_mode {
+        if let Some(names) = args.profile.clone() {
+            return ConsolePortKillApp::run_guard_profiles(args, &names).await;
+        }
+
         // Extract reservation parameters before moving args
         let reserve_port = args.reserve_port;
         let project_name = args.project_name.clone();
@@ -404,6 +728,14 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.tui {
+        args.performance = true; // CPU and memory columns
+        let app = ConsolePortKillApp::new(args)?;
+        let mut tui = TuiApp::new(app.process_monitor(), app.args().clone());
+        tui.run().await?;
+        return Ok(());
+    }
+
     // Create and run the console application
     let app = ConsolePortKillApp::new(args)?;
     app.run().await?;
@@ -4,8 +4,13 @@
 use port_kill::{
     cli::Args,
     console_app::ConsolePortKillApp,
+    desktop_session::{self, SessionType},
+    icon_theme::{self, IconTheme},
     types::{ProcessInfo, StatusBarInfo},
-    process_monitor::{get_processes_on_ports, kill_all_processes, kill_single_process},
+    process_monitor::{
+        get_processes_on_ports, group_counts, kill_all_processes, kill_group_processes,
+        kill_single_process,
+    },
 };
 use libappindicator::{AppIndicator, AppIndicatorStatus};
 use anyhow::Result;
@@ -20,7 +25,7 @@ use std::cell::RefCell;
 
 // GTK initialization for tray support
 use gtk::prelude::*;
-use gtk::{Menu, MenuItem, SeparatorMenuItem};
+use gtk::{CheckMenuItem, Menu, MenuItem, SeparatorMenuItem};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -43,7 +48,11 @@ async fn main() -> Result<()> {
     env::set_var("RUST_LOG", log_level);
 
     // Initialize logging
-    env_logger::init();
+    if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {:#}", e);
+        process::exit(1);
+    }
+    port_kill::audit_log::set_initiator(port_kill::audit_log::AuditInitiator::for_args(&args, true));
     
     info!("Starting Port Kill application on Linux...");
     info!("Monitoring: {}", args.get_port_description());
@@ -80,6 +89,17 @@ async fn main() -> Result<()> {
 
 async fn start_tray_mode(args: Args) -> Result<()> {
     info!("Starting Linux tray mode...");
+
+    // On Wayland the indicator is only visible through a StatusNotifier host, so fail
+    // early (and fall back to console) instead of running with an invisible icon
+    let session = desktop_session::detect();
+    info!("Desktop session: {:?}", session);
+    if let Some(reason) = desktop_session::tray_unavailable_reason(session) {
+        return Err(anyhow::anyhow!("Tray unavailable: {}", reason));
+    }
+    if session == SessionType::Wayland {
+        info!("Using the StatusNotifier (D-Bus) tray backend");
+    }
     
     // Initialize GTK before creating tray items
     if gtk::init().is_err() {
@@ -90,10 +110,17 @@ async fn start_tray_mode(args: Args) -> Result<()> {
     // Create the app indicator (tray icon)
     let indicator = Rc::new(RefCell::new(AppIndicator::new("port-kill", "port-kill")));
     indicator.borrow_mut().set_status(AppIndicatorStatus::Active);
+
+    // The icons ship in the binary; put them where the indicator looks
+    match icon_theme::install_linux_icons() {
+        Ok(dir) => indicator.borrow_mut().set_icon_theme_path(&dir.to_string_lossy()),
+        Err(e) => error!("Failed to install tray icons: {}", e),
+    }
+    let theme = IconTheme::configured(args.icon_theme.as_deref());
     
     // Set initial icon based on process count
     let (initial_count, _) = get_processes_on_ports(&args.get_ports_to_monitor(), &args);
-    update_tray_icon(&mut indicator.borrow_mut(), initial_count);
+    update_tray_icon(&mut indicator.borrow_mut(), initial_count, theme);
     
     // Create the main menu
     let mut menu = Menu::new();
@@ -128,6 +155,8 @@ async fn start_tray_mode(args: Args) -> Result<()> {
         }
     });
     menu.append(&kill_all_item);
+    append_undo_item(&menu);
+    append_settings_item(&menu);
     
     let quit_item = MenuItem::with_label("Quit");
     quit_item.connect_activate(move |_| {
@@ -144,17 +173,41 @@ async fn start_tray_mode(args: Args) -> Result<()> {
     println!("🔍 Look for the Port Kill icon in your system tray!");
     println!("💡 Features: Dynamic process menu, status display, individual process control");
     
+    // Global hotkeys (X11 only) run off the GTK thread, like the kill menu items
+    let args_for_hotkeys = args.clone();
+    port_kill::hotkeys::spawn(move |action| {
+        info!("Hotkey pressed: {}", action);
+        if let Err(e) = port_kill::hotkeys::run_action(&action, &args_for_hotkeys) {
+            error!("Hotkey {} failed: {}", action, e);
+        }
+    });
+
+    // Saved settings take effect by starting again
+    gtk::glib::timeout_add_local(Duration::from_millis(500), || {
+        if port_kill::settings::take_saved() {
+            info!("Settings changed, restarting...");
+            if let Err(e) = port_kill::memory::restart_self() {
+                error!("Failed to restart: {}", e);
+            }
+        }
+        gtk::glib::Continue(true)
+    });
+
     // Set up periodic updates using GTK timeout
     let args_clone = args.clone();
     let indicator_clone = indicator.clone();
-    gtk::glib::timeout_add_local(Duration::from_secs(5), move || {
+    let mut resource_sampler = port_kill::system_monitor::ResourceSampler::new();
+    gtk::glib::timeout_add_local(Duration::from_secs(args.scan_interval.max(1)), move || {
         // Get current processes
-        let (process_count, processes) = 
+        let (process_count, mut processes) = 
             get_processes_on_ports(&args_clone.get_ports_to_monitor(), &args_clone);
+        resource_sampler.sample(&mut processes);
+        let mut status_info = StatusBarInfo::from_process_count(process_count);
+        status_info.set_top_consumers(&processes, &resource_sampler);
         
         // Update tray icon and menu
         if let Ok(mut ind) = indicator_clone.try_borrow_mut() {
-            update_tray_icon(&mut ind, process_count);
+            update_tray_icon(&mut ind, process_count, theme);
 
             // Rebuild the menu with current processes
             let mut new_menu = Menu::new();
@@ -173,6 +226,7 @@ async fn start_tray_mode(args: Args) -> Result<()> {
             let process_root = MenuItem::with_label("Processes");
             process_root.set_submenu(Some(&process_menu));
             new_menu.append(&process_root);
+            append_resource_usage_item(&new_menu, &status_info.details);
 
             // Add another separator
             let separator2 = SeparatorMenuItem::new();
@@ -189,6 +243,9 @@ async fn start_tray_mode(args: Args) -> Result<()> {
                 }
             });
             new_menu.append(&kill_all_item);
+            append_group_kill_items(&new_menu, &args_clone, &processes);
+            append_undo_item(&new_menu);
+            append_settings_item(&new_menu);
 
             let quit_item = MenuItem::with_label("Quit");
             quit_item.connect_activate(move |_| {
@@ -205,7 +262,6 @@ async fn start_tray_mode(args: Args) -> Result<()> {
         }
         
         // Update status display
-        let status_info = StatusBarInfo::from_process_count(process_count);
         println!("🔄 Port Status: {} - {}", status_info.text, status_info.tooltip);
         
         // Print detected processes with grouping
@@ -311,6 +367,21 @@ async fn start_tray_mode(args: Args) -> Result<()> {
     Ok(())
 }
 
+/// "Kill all <group>" items, one per process group with listeners
+fn append_group_kill_items(menu: &Menu, args: &Args, processes: &HashMap<u16, ProcessInfo>) {
+    for (group, count) in group_counts(processes) {
+        let item = MenuItem::with_label(&format!("Kill all {} ({})", group, count));
+        let args_for_kill = args.clone();
+        item.connect_activate(move |_| {
+            info!("Kill all {} clicked", group);
+            if let Err(e) = kill_group_processes(&group, &args_for_kill) {
+                error!("Failed to kill {} processes: {}", group, e);
+            }
+        });
+        menu.append(&item);
+    }
+}
+
 /// Create a dynamic menu for processes
 fn create_process_menu(args: &Args, processes: &HashMap<u16, ProcessInfo>) -> Menu {
     create_process_menu_with_verbose(args, processes, false)
@@ -371,19 +442,89 @@ fn create_process_menu_with_verbose(args: &Args, processes: &HashMap<u16, Proces
         
         menu.append(&menu_item);
     }
+
+    // Copy "port pid name" lines; wl-copy on Wayland, xclip/xsel on X11
+    let listing = processes_listing(processes);
+    let copy_item = MenuItem::with_label("Copy Process List");
+    copy_item.connect_activate(move |_| match desktop_session::copy_to_clipboard(&listing) {
+        Ok(tool) => info!("Copied the process list with {}", tool),
+        Err(e) => error!("Failed to copy the process list: {}", e),
+    });
+    menu.append(&SeparatorMenuItem::new());
+    menu.append(&copy_item);
     
     menu
 }
 
-/// Update the tray icon based on process count
-fn update_tray_icon(indicator: &mut AppIndicator, process_count: usize) {
-    let icon_name = match process_count {
-        0 => "port-kill-green",      // Green for no processes
-        1..=9 => "port-kill-orange", // Orange for 1-9 processes
-        _ => "port-kill-red",        // Red for 10+ processes
+fn processes_listing(processes: &HashMap<u16, ProcessInfo>) -> String {
+    let mut sorted: Vec<_> = processes.values().collect();
+    sorted.sort_by_key(|p| p.port);
+    sorted
+        .iter()
+        .map(|p| format!("{}\t{}\t{}", p.port, p.pid, p.name))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// "Resource Usage": the status detail view with the top CPU/memory consumers. The menu is
+/// rebuilt on every scan, so the values stay live.
+fn append_resource_usage_item(menu: &Menu, details: &[String]) {
+    if details.is_empty() {
+        return;
+    }
+    let usage_menu = Menu::new();
+    for line in details {
+        let item = MenuItem::with_label(line);
+        item.set_sensitive(false);
+        usage_menu.append(&item);
+    }
+    let usage_root = MenuItem::with_label("Resource Usage");
+    usage_root.set_submenu(Some(&usage_menu));
+    menu.append(&usage_root);
+}
+
+/// "Restart Last Killed", naming the process it would start again (a dialog still asks)
+fn append_undo_item(menu: &Menu) {
+    let last_killed = port_kill::undo::last_killed().ok().flatten();
+    let label = match &last_killed {
+        Some(entry) => format!("Restart Last Killed: {} on port {}", entry.process_name, entry.port),
+        None => "Restart Last Killed".to_string(),
     };
-    
-    indicator.set_icon(icon_name);
+    let undo_item = MenuItem::with_label(&label);
+    undo_item.set_sensitive(last_killed.is_some());
+    undo_item.connect_activate(|_| {
+        if let Err(e) = port_kill::undo::undo_last_kill_from_tray() {
+            error!("Failed to restart the last killed process: {}", e);
+        }
+    });
+    menu.append(&undo_item);
+}
+
+/// "Open at Login" (checked while the login item exists) and "Settings…", which opens the
+/// Settings window in the browser
+fn append_settings_item(menu: &Menu) {
+    let open_at_login_item = CheckMenuItem::with_label("Open at Login");
+    open_at_login_item.set_active(port_kill::autostart::is_enabled());
+    open_at_login_item.connect_toggled(|item| {
+        if let Err(e) = port_kill::autostart::set_enabled(item.is_active()) {
+            error!("Failed to change Open at Login: {}", e);
+        }
+    });
+    menu.append(&open_at_login_item);
+
+    let settings_item = MenuItem::with_label("Settings…");
+    settings_item.connect_activate(|_| {
+        if let Err(e) = port_kill::settings::open() {
+            error!("Failed to open settings: {}", e);
+        }
+    });
+    menu.append(&settings_item);
+}
+
+/// Update the tray icon and its count badge (the indicator label) based on process count
+fn update_tray_icon(indicator: &mut AppIndicator, process_count: usize, theme: IconTheme) {
+    indicator.set_icon(theme.linux_icon_name(process_count));
+    indicator.set_label(&icon_theme::badge_text(process_count), "99+");
     
     // Update tooltip
     let tooltip = match process_count {
@@ -456,6 +597,8 @@ fn run_linux_diagnostics() {
         }
         _ => println!("❌ GTK+3.0: Not available (install GTK development packages)"),
     }
+
+    desktop_session::print_diagnostics();
     
     println!("");
 }
@@ -1,13 +1,14 @@
 use port_kill::{
     cli::Args,
     console_app::ConsolePortKillApp,
+    hotkeys::HotkeyAction,
     types::{ProcessInfo, StatusBarInfo},
-    process_monitor::{get_processes_on_ports, kill_all_processes},
+    process_monitor::{get_processes_on_ports, group_counts, kill_all_processes, kill_group_processes},
 };
 use port_kill::cache::{
-    list::{list_caches, print_list_table},
+    list::{list_caches, print_list_table, CacheQuery},
     clean::clean_caches,
-    restore::restore_last_backup,
+    restore::restore_backup,
     doctor::doctor,
 };
 use port_kill::cache::output::print_or_json;
@@ -21,13 +22,15 @@ use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Webhooks about what this run did go out before it exits, however main returns
+    let _webhooks = port_kill::webhook::FlushOnExit;
     // Parse command line arguments
     let args = Args::parse();
     
     // Validate arguments
     if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        port_kill::exit_code::exit(1);
     }
     
     // Set up logging level based on log_level argument
@@ -40,7 +43,11 @@ async fn main() -> Result<()> {
     std::env::set_var("RUST_LOG", log_level);
     
     // Initialize logging
-    env_logger::init();
+    if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {:#}", e);
+        port_kill::exit_code::exit(1);
+    }
+    port_kill::audit_log::set_initiator(port_kill::audit_log::AuditInitiator::for_args(&args, true));
     
     info!("Starting Port Kill application on Windows...");
     info!("Monitoring: {}", args.get_port_description());
@@ -84,8 +91,32 @@ async fn main() -> Result<()> {
     // Handle cache subcommand on Windows too (parity with console binary)
     if let Some(cache_cmd) = args.cache.clone() {
         let c = cache_cmd.args();
+        port_kill::cache::size::configure(c.scan_options());
+        port_kill::cache::backup::configure_retention(c.retention());
+        port_kill::cache::backup::configure_compression(c.compress_backups);
+        if c.pin.is_some() || c.unpin.is_some() {
+            if let Err(e) = port_kill::cache::rules::pin_command(c) {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.watch {
+            if let Err(e) = port_kill::cache::watch::watch(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
+        if c.schedule.is_some() || c.uninstall_schedule || c.schedule_log {
+            if let Err(e) = port_kill::cache::schedule::schedule_command(c).await {
+                eprintln!("Error: {:#}", e);
+                port_kill::exit_code::exit(1);
+            }
+            return Ok(());
+        }
         if c.list || c.dry_run {
-            let resp = list_caches(&c.lang, c.npx, c.js_pm, c.hf, c.torch, c.vercel, c.cloudflare, c.stale_days).await;
+            let resp = list_caches(&CacheQuery::from(c)).await;
             if c.json {
                 print_or_json(&resp, true);
             } else {
@@ -94,21 +125,37 @@ async fn main() -> Result<()> {
             return Ok(());
         }
         if c.clean {
-            let resp = clean_caches(&c.lang, c.npx, c.js_pm, c.safe_delete, c.force, c.hf, c.torch, c.vercel, c.cloudflare, c.stale_days).await;
+            let resp = clean_caches(&CacheQuery::from(c)).await;
             print_or_json(&resp, c.json);
             return Ok(());
         }
-        if c.restore_last {
-            let resp = restore_last_backup().await;
+        if c.backups {
+            let resp = port_kill::cache::backup::list_backups_response();
+            if c.json {
+                print_or_json(&resp, true);
+            } else {
+                port_kill::cache::backup::print_backups_table(&resp);
+            }
+            return Ok(());
+        }
+        if c.restore_last || c.restore {
+            let resp = restore_backup(c.backup.as_deref().unwrap_or("last"), &c.only).await;
             print_or_json(&resp, c.json);
             if resp.error.is_some() {
-                std::process::exit(1);
+                port_kill::exit_code::exit(1);
             }
             return Ok(());
         }
         if c.doctor {
-            let report = doctor().await;
+            let report = doctor(c.fix).await;
             print_or_json(&report, c.json);
+            if c.fail_on.is_some_and(|level| {
+                report
+                    .worst_severity()
+                    .is_some_and(|worst| worst >= level)
+            }) {
+                port_kill::exit_code::exit(1);
+            }
             return Ok(());
         }
     }
@@ -148,27 +195,59 @@ async fn run_windows_tray_mode(args: Args) -> Result<()> {
     // Add menu items
     let sender_clone = menu_sender.clone();
     tray.add_menu_item("Kill All Processes", move || {
-        if let Err(e) = sender_clone.send("kill_all") {
+        if let Err(e) = sender_clone.send("kill_all".to_string()) {
             error!("Failed to send kill_all event: {}", e);
         }
     }).map_err(|e| anyhow::anyhow!("Failed to add Kill All menu item: {}", e))?;
     
+    let sender_clone = menu_sender.clone();
+    tray.add_menu_item("Restart Last Killed", move || {
+        if let Err(e) = sender_clone.send("undo_last_kill".to_string()) {
+            error!("Failed to send undo_last_kill event: {}", e);
+        }
+    }).map_err(|e| anyhow::anyhow!("Failed to add Restart Last Killed menu item: {}", e))?;
+
+    let sender_clone = menu_sender.clone();
+    tray.add_menu_item("Toggle Open at Login", move || {
+        if let Err(e) = sender_clone.send("open_at_login".to_string()) {
+            error!("Failed to send open_at_login event: {}", e);
+        }
+    }).map_err(|e| anyhow::anyhow!("Failed to add Open at Login menu item: {}", e))?;
+
+    let sender_clone = menu_sender.clone();
+    tray.add_menu_item("Settings…", move || {
+        if let Err(e) = sender_clone.send("settings".to_string()) {
+            error!("Failed to send settings event: {}", e);
+        }
+    }).map_err(|e| anyhow::anyhow!("Failed to add Settings menu item: {}", e))?;
+
     let sender_clone = menu_sender.clone();
     tray.add_menu_item("Quit", move || {
-        if let Err(e) = sender_clone.send("quit") {
+        if let Err(e) = sender_clone.send("quit".to_string()) {
             error!("Failed to send quit event: {}", e);
         }
     }).map_err(|e| anyhow::anyhow!("Failed to add Quit menu item: {}", e))?;
     
+    // Global hotkeys come into the same channel as menu clicks
+    let sender_clone = menu_sender.clone();
+    port_kill::hotkeys::spawn(move |action| {
+        if let Err(e) = sender_clone.send(format!("hotkey:{}", action)) {
+            error!("Failed to send hotkey event: {}", e);
+        }
+    });
+
     // Main monitoring loop
     let mut last_check = std::time::Instant::now();
     let mut last_process_count = 0;
     let mut last_processes = HashMap::new();
+    let mut resource_sampler = port_kill::system_monitor::ResourceSampler::new();
+    // Groups that already have a "Kill all <group>" item
+    let mut group_items = std::collections::HashSet::new();
     
     loop {
         // Check for menu events
         if let Ok(event) = menu_receiver.try_recv() {
-            match event {
+            match event.as_str() {
                 "kill_all" => {
                     info!("Kill All Processes clicked");
                     let ports_to_kill = args.get_ports_to_monitor();
@@ -186,18 +265,70 @@ async fn run_windows_tray_mode(args: Args) -> Result<()> {
                         Err(e) => error!("Kill task panicked: {}", e),
                     }
                 }
+                "undo_last_kill" => {
+                    // The confirmation dialog blocks until answered
+                    let handle = tokio::task::spawn_blocking(port_kill::undo::undo_last_kill_from_tray);
+                    match handle.await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => error!("Failed to restart the last killed process: {}", e),
+                        Err(e) => error!("Restart task panicked: {}", e),
+                    }
+                }
+                "open_at_login" => {
+                    if let Err(e) = port_kill::autostart::toggle() {
+                        error!("Failed to change Open at Login: {}", e);
+                    }
+                }
+                "settings" => {
+                    if let Err(e) = port_kill::settings::open() {
+                        error!("Failed to open settings: {}", e);
+                    }
+                }
                 "quit" => {
                     info!("Quit clicked, exiting...");
                     break;
                 }
-                _ => {
-                    info!("Unknown menu event: {}", event);
-                }
+                other => match other.strip_prefix("kill_group:") {
+                    Some(group) => {
+                        info!("Kill all {} clicked", group);
+                        let group = group.to_string();
+                        let args_clone = args.clone();
+                        let handle = tokio::task::spawn_blocking(move || {
+                            kill_group_processes(&group, &args_clone)
+                        });
+                        match handle.await {
+                            Ok(Ok(())) => println!("✅ Group processes killed successfully"),
+                            Ok(Err(e)) => error!("Failed to kill group processes: {}", e),
+                            Err(e) => error!("Kill task panicked: {}", e),
+                        }
+                    }
+                    None => match other.strip_prefix("hotkey:").map(str::parse::<HotkeyAction>) {
+                        Some(Ok(action)) => {
+                            info!("Hotkey pressed: {}", action);
+                            let args_clone = args.clone();
+                            let handle = tokio::task::spawn_blocking(move || {
+                                port_kill::hotkeys::run_action(&action, &args_clone)
+                            });
+                            match handle.await {
+                                Ok(Ok(())) => {}
+                                Ok(Err(e)) => error!("Hotkey failed: {}", e),
+                                Err(e) => error!("Hotkey task panicked: {}", e),
+                            }
+                        }
+                        _ => info!("Unknown menu event: {}", event),
+                    },
+                },
             }
         }
         
-        // Check for processes every 5 seconds
-        if last_check.elapsed() >= Duration::from_secs(5) {
+        // Saved settings take effect by starting again
+        if port_kill::settings::take_saved() {
+            info!("Settings changed, restarting...");
+            port_kill::memory::restart_self()?;
+        }
+
+        // Check for processes every scan interval
+        if last_check.elapsed() >= Duration::from_secs(args.scan_interval.max(1)) {
             last_check = std::time::Instant::now();
             
             // Get process information using spawn_blocking to avoid blocking the async runtime
@@ -214,7 +345,26 @@ async fn run_windows_tray_mode(args: Args) -> Result<()> {
                     continue;
                 }
             };
-            let status_info = StatusBarInfo::from_process_count(process_count);
+            let mut status_info = StatusBarInfo::from_process_count(process_count);
+            // Sampled on a copy so CPU changes alone don't count as a changed process list
+            let mut sampled = processes.clone();
+            resource_sampler.sample(&mut sampled);
+            status_info.set_top_consumers(&sampled, &resource_sampler);
+
+            // A "Kill all <group>" item for each group seen so far (tray menus can only grow)
+            for group in group_counts(&processes).into_keys() {
+                if group_items.insert(group.clone()) {
+                    let sender_clone = menu_sender.clone();
+                    let event = format!("kill_group:{}", group);
+                    if let Err(e) = tray.add_menu_item(&format!("Kill all {}", group), move || {
+                        if let Err(e) = sender_clone.send(event.clone()) {
+                            error!("Failed to send kill_group event: {}", e);
+                        }
+                    }) {
+                        error!("Failed to add Kill all {} menu item: {}", group, e);
+                    }
+                }
+            }
             
             // Only update if processes have actually changed
             if process_count != last_process_count || processes != last_processes {
@@ -0,0 +1,254 @@
+//! Memory hygiene for the long-running modes (the tray, `daemon`, guard mode, `--serve`).
+//! A tool that hunts memory hogs shouldn't turn into one after a few weeks of uptime:
+//!
+//! - in-memory state that grows with what is seen is bounded ([`BoundedSet`], the
+//!   history's `max_entries`)
+//! - process monitors compact themselves every [`COMPACT_INTERVAL`]
+//! - every [`CHECK_INTERVAL`] port-kill samples its own resident memory; over `--max-rss`
+//!   it warns, and the daemon restarts itself
+//!
+//! The numbers are in `--status` (for a running daemon) and in the API's `GET /metrics`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often long-running modes check their own memory
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often a process monitor drops cached state and shrinks its buffers
+pub const COMPACT_INTERVAL: Duration = Duration::from_secs(600);
+
+/// What the last self-check found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryStats {
+    pub pid: u32,
+    pub rss_bytes: u64,
+    pub peak_rss_bytes: u64,
+    /// `--max-rss`, when set
+    pub max_rss_bytes: Option<u64>,
+    pub compactions: u64,
+    /// Checks that found the resident size over `--max-rss`
+    pub over_limit: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MemoryStats {
+    pub fn is_over_limit(&self) -> bool {
+        self.max_rss_bytes.is_some_and(|max| self.rss_bytes > max)
+    }
+
+    /// Still reporting: a running daemon rewrites its stats every check
+    pub fn is_live(&self) -> bool {
+        Utc::now() - self.updated_at < chrono::Duration::seconds(CHECK_INTERVAL.as_secs() as i64 * 3)
+    }
+
+    /// One line for `--status`
+    pub fn describe(&self) -> String {
+        let limit = match self.max_rss_bytes {
+            Some(max) => format!(" of {} allowed", crate::cache::output::human_size(max)),
+            None => String::new(),
+        };
+        format!(
+            "{} resident{} (peak {}), {} compaction(s)",
+            crate::cache::output::human_size(self.rss_bytes),
+            limit,
+            crate::cache::output::human_size(self.peak_rss_bytes),
+            self.compactions
+        )
+    }
+
+    /// Prometheus text format, for `GET /metrics`
+    pub fn to_prometheus(&self) -> String {
+        let mut metrics = vec![
+            ("port_kill_memory_rss_bytes", "gauge", "Resident memory of port-kill itself", self.rss_bytes),
+            ("port_kill_memory_peak_rss_bytes", "gauge", "Highest resident memory seen", self.peak_rss_bytes),
+            ("port_kill_memory_compactions_total", "counter", "Compactions of in-memory state", self.compactions),
+            ("port_kill_memory_over_limit_total", "counter", "Checks that found memory over --max-rss", self.over_limit),
+        ];
+        if let Some(max) = self.max_rss_bytes {
+            metrics.push(("port_kill_memory_max_rss_bytes", "gauge", "The --max-rss limit", max));
+        }
+        metrics
+            .into_iter()
+            .map(|(name, kind, help, value)| {
+                format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+            })
+            .collect()
+    }
+}
+
+struct Counters {
+    peak: u64,
+    compactions: u64,
+    over_limit: u64,
+}
+
+static COUNTERS: Mutex<Counters> = Mutex::new(Counters {
+    peak: 0,
+    compactions: 0,
+    over_limit: 0,
+});
+
+/// Resident memory of this process
+pub fn rss_bytes() -> Option<u64> {
+    let pid = sysinfo::Pid::from_u32(std::process::id());
+    let mut system = sysinfo::System::new();
+    system.refresh_process(pid);
+    system.process(pid).map(|process| process.memory())
+}
+
+pub fn record_compaction() {
+    if let Ok(mut counters) = COUNTERS.lock() {
+        counters.compactions += 1;
+    }
+}
+
+/// Sample this process's memory against `max_rss`, warning when it is over
+pub fn check(max_rss: Option<u64>) -> MemoryStats {
+    let rss = rss_bytes().unwrap_or(0);
+    let over = max_rss.is_some_and(|max| rss > max);
+    let (peak, compactions, over_limit) = match COUNTERS.lock() {
+        Ok(mut counters) => {
+            counters.peak = counters.peak.max(rss);
+            counters.over_limit += over as u64;
+            (counters.peak, counters.compactions, counters.over_limit)
+        }
+        Err(_) => (rss, 0, over as u64),
+    };
+    let stats = MemoryStats {
+        pid: std::process::id(),
+        rss_bytes: rss,
+        peak_rss_bytes: peak,
+        max_rss_bytes: max_rss,
+        compactions,
+        over_limit,
+        updated_at: Utc::now(),
+    };
+    if stats.is_over_limit() {
+        warn!("port-kill itself is using {}", stats.describe());
+    }
+    stats
+}
+
+/// Where a running daemon reports its memory for `--status`
+pub fn daemon_stats_path() -> PathBuf {
+    crate::uninstall::home_dir()
+        .join(".port-kill")
+        .join("daemon-memory.json")
+}
+
+pub fn write_daemon_stats(stats: &MemoryStats) -> Result<()> {
+    let path = daemon_stats_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(stats)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The running daemon's last report, if it is still running and reporting
+pub fn read_daemon_stats() -> Option<MemoryStats> {
+    let stats: MemoryStats =
+        serde_json::from_str(&std::fs::read_to_string(daemon_stats_path()).ok()?).ok()?;
+    (stats.is_live() && crate::daemon::running_pid() == Some(stats.pid)).then_some(stats)
+}
+
+/// Start this program again with the same arguments. On unix the process image is
+/// replaced, so the PID (and the daemon's pid file) stay the same.
+pub fn restart_self() -> Result<()> {
+    let exe = std::env::current_exe().context("Could not find the port-kill executable")?;
+    let mut command = std::process::Command::new(exe);
+    command.args(std::env::args_os().skip(1));
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Only returns if the exec failed
+        Err(command.exec()).context("Failed to restart port-kill")
+    }
+    #[cfg(not(unix))]
+    {
+        command.spawn().context("Failed to restart port-kill")?;
+        std::process::exit(0);
+    }
+}
+
+/// A set that keeps at most `capacity` items, forgetting the oldest first
+#[derive(Debug, Clone)]
+pub struct BoundedSet<T> {
+    capacity: usize,
+    items: HashSet<T>,
+    order: VecDeque<T>,
+}
+
+impl<T: Eq + Hash + Clone> BoundedSet<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            items: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn insert(&mut self, item: T) {
+        if self.capacity == 0 || !self.items.insert(item.clone()) {
+            return;
+        }
+        self.order.push_back(item);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.items.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.items.contains(item)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.items.shrink_to_fit();
+        self.order.shrink_to_fit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_set_forgets_oldest() {
+        let mut set = BoundedSet::new(2);
+        set.insert("a");
+        set.insert("b");
+        set.insert("a");
+        set.insert("c");
+        assert_eq!(set.len(), 2);
+        assert!(!set.contains(&"a"));
+        assert!(set.contains(&"b") && set.contains(&"c"));
+    }
+
+    #[test]
+    fn test_check_reports_limit() {
+        let stats = check(Some(1));
+        assert!(stats.rss_bytes > 0);
+        assert!(stats.is_over_limit());
+        assert!(stats.peak_rss_bytes >= stats.rss_bytes);
+        assert!(stats.to_prometheus().contains("port_kill_memory_max_rss_bytes 1\n"));
+        assert!(!check(None).is_over_limit());
+    }
+}
@@ -0,0 +1,353 @@
+//! Notifications for kills, failed kills, guard events and audit findings. Native desktop
+//! notifications are shown through notify-rust (D-Bus on Linux, Notification Center on
+//! macOS, toasts on Windows) and are off unless `--notify` is given or the config enables
+//! them; configured webhooks (see [`crate::webhook`]) get the same events:
+//!
+//! ```toml
+//! [notifications]
+//! enabled = true
+//! kill = true          # a process was killed
+//! kill_failed = true   # a kill was refused or failed
+//! conflict = true      # port guard found a conflict or an unauthorized process
+//! audit = true         # the security audit flagged a process
+//! cooldown = 60        # seconds before the same event for the same process repeats
+//! ```
+
+use crate::webhook::WebhookSettings;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_cooldown() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub kill: bool,
+    #[serde(default = "default_true")]
+    pub kill_failed: bool,
+    #[serde(default = "default_true")]
+    pub conflict: bool,
+    #[serde(default = "default_true")]
+    pub audit: bool,
+    #[serde(default = "default_cooldown")]
+    pub cooldown: u64,
+    /// `[[notifications.webhooks]]`
+    #[serde(default)]
+    pub webhooks: Vec<WebhookSettings>,
+}
+
+impl Default for NotifySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kill: true,
+            kill_failed: true,
+            conflict: true,
+            audit: true,
+            cooldown: default_cooldown(),
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotifyEvent {
+    Killed,
+    KillFailed,
+    Conflict,
+    AuditFinding,
+    /// `--test-webhooks`
+    Test,
+}
+
+impl NotifyEvent {
+    /// Name used in webhook payloads and webhook `events` filters
+    pub fn name(self) -> &'static str {
+        match self {
+            NotifyEvent::Killed => "process_killed",
+            NotifyEvent::KillFailed => "kill_failed",
+            NotifyEvent::Conflict => "port_conflict",
+            NotifyEvent::AuditFinding => "audit_finding",
+            NotifyEvent::Test => "test",
+        }
+    }
+}
+
+impl NotifySettings {
+    /// Whether `event` is shown on the desktop
+    pub fn wants(&self, event: NotifyEvent) -> bool {
+        self.enabled
+            && match event {
+                NotifyEvent::Killed => self.kill,
+                NotifyEvent::KillFailed => self.kill_failed,
+                NotifyEvent::Conflict => self.conflict,
+                NotifyEvent::AuditFinding => self.audit,
+                NotifyEvent::Test => true,
+            }
+    }
+}
+
+/// One notification, before it is handed to the desktop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub event: NotifyEvent,
+    pub title: String,
+    pub body: String,
+    /// Identifies "the same thing happening again" for the cooldown
+    pub key: String,
+    pub pid: Option<i32>,
+    pub port: Option<u16>,
+    pub process_name: Option<String>,
+}
+
+fn describe(pid: i32, port: Option<u16>, name: Option<&str>) -> String {
+    let name = name.unwrap_or("process");
+    match port {
+        Some(port) => format!("{} (PID {}) on port {}", name, pid, port),
+        None => format!("{} (PID {})", name, pid),
+    }
+}
+
+impl Message {
+    pub fn killed(pid: i32, port: Option<u16>, name: Option<&str>) -> Self {
+        Self {
+            event: NotifyEvent::Killed,
+            title: match port {
+                Some(port) => format!("Port {} freed", port),
+                None => "Process killed".to_string(),
+            },
+            body: format!("Killed {}", describe(pid, port, name)),
+            key: pid.to_string(),
+            pid: Some(pid),
+            port,
+            process_name: name.map(str::to_string),
+        }
+    }
+
+    pub fn kill_failed(pid: i32, port: Option<u16>, name: Option<&str>, error: &str) -> Self {
+        Self {
+            event: NotifyEvent::KillFailed,
+            title: "Kill failed".to_string(),
+            body: format!("Could not kill {}: {}", describe(pid, port, name), error),
+            key: pid.to_string(),
+            pid: Some(pid),
+            port,
+            process_name: name.map(str::to_string),
+        }
+    }
+
+    /// Port guard saw `name` take a port it watches
+    pub fn conflict(port: u16, pid: i32, name: &str, detail: &str) -> Self {
+        Self {
+            event: NotifyEvent::Conflict,
+            title: format!("Port {} conflict", port),
+            body: format!("{}: {}", describe(pid, Some(port), Some(name)), detail),
+            key: format!("{}:{}", port, pid),
+            pid: Some(pid),
+            port: Some(port),
+            process_name: Some(name.to_string()),
+        }
+    }
+
+    /// The security audit flagged `name` as suspicious
+    pub fn audit_finding(port: u16, pid: i32, name: &str, risk: &str, reason: &str) -> Self {
+        Self {
+            event: NotifyEvent::AuditFinding,
+            title: format!("{} risk process on port {}", risk, port),
+            body: format!("{}: {}", describe(pid, Some(port), Some(name)), reason),
+            key: format!("{}:{}", port, pid),
+            pid: Some(pid),
+            port: Some(port),
+            process_name: Some(name.to_string()),
+        }
+    }
+
+    /// Sent by `--test-webhooks`
+    pub fn test() -> Self {
+        Self {
+            event: NotifyEvent::Test,
+            title: "Port Kill test notification".to_string(),
+            body: "This webhook is set up to receive port-kill events.".to_string(),
+            key: "test".to_string(),
+            pid: None,
+            port: None,
+            process_name: None,
+        }
+    }
+
+    /// Summary for a bulk kill, instead of one notification per process
+    pub fn bulk(killed: usize, failed: usize) -> Self {
+        let (event, title) = if failed == 0 {
+            (NotifyEvent::Killed, "Ports freed".to_string())
+        } else {
+            (NotifyEvent::KillFailed, "Some kills failed".to_string())
+        };
+        Self {
+            event,
+            title,
+            body: format!("Killed {} processes, {} failed", killed, failed),
+            key: "bulk".to_string(),
+            pid: None,
+            port: None,
+            process_name: None,
+        }
+    }
+}
+
+/// Drops repeats of the same event within the cooldown
+#[derive(Debug, Default)]
+pub struct Throttle {
+    last_sent: HashMap<(NotifyEvent, String), Instant>,
+}
+
+impl Throttle {
+    pub fn allow(&mut self, message: &Message, cooldown: Duration, now: Instant) -> bool {
+        let key = (message.event, message.key.clone());
+        if let Some(last) = self.last_sent.get(&key) {
+            if now.duration_since(*last) < cooldown {
+                return false;
+            }
+        }
+        self.last_sent.retain(|_, sent| now.duration_since(*sent) < cooldown);
+        self.last_sent.insert(key, now);
+        true
+    }
+}
+
+struct State {
+    settings: NotifySettings,
+    throttle: Throttle,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Set up notifications from the config files and `--notify`. Until this is called
+/// nothing is shown.
+pub fn init(args: &crate::cli::Args) {
+    let mut settings = if args.no_config {
+        NotifySettings::default()
+    } else {
+        crate::config::load_or_default()
+            .config
+            .notifications
+            .unwrap_or_default()
+    };
+    settings.enabled |= args.notify;
+    if let Ok(mut state) = STATE.lock() {
+        *state = Some(State {
+            settings,
+            throttle: Throttle::default(),
+        });
+    }
+}
+
+/// Webhooks from the config, for `--test-webhooks`
+pub fn webhooks() -> Vec<WebhookSettings> {
+    STATE
+        .lock()
+        .ok()
+        .and_then(|state| Some(state.as_ref()?.settings.webhooks.clone()))
+        .unwrap_or_default()
+}
+
+/// Show `message` on the desktop and post it to webhooks, where its event is enabled and
+/// it isn't a repeat within the cooldown. Delivery happens on background threads so a slow
+/// notification daemon or webhook never holds up a kill.
+pub fn send(message: Message) {
+    let Ok(mut state) = STATE.lock() else {
+        return;
+    };
+    let Some(state) = state.as_mut() else {
+        return;
+    };
+    let desktop = state.settings.wants(message.event);
+    let webhook = state.settings.webhooks.iter().any(|w| w.wants(message.event));
+    if !desktop && !webhook {
+        return;
+    }
+    let cooldown = Duration::from_secs(state.settings.cooldown);
+    if !state.throttle.allow(&message, cooldown, Instant::now()) {
+        return;
+    }
+
+    crate::webhook::dispatch(&state.settings.webhooks, &message);
+    if !desktop {
+        return;
+    }
+    show(&message.title, &message.body);
+}
+
+/// Show a desktop notification now, whatever the settings: for things the user asked to
+/// see, like the show-processes hotkey
+pub fn show(title: &str, body: &str) {
+    let (title, body) = (title.to_string(), body.to_string());
+    std::thread::spawn(move || {
+        let result = notify_rust::Notification::new()
+            .appname("Port Kill")
+            .summary(&title)
+            .body(&body)
+            .show();
+        if let Err(e) = result {
+            log::debug!("Could not show notification: {}", e);
+        }
+    });
+}
+
+/// Notify about the outcome of a kill
+pub fn kill_result(pid: i32, port: Option<u16>, name: Option<&str>, result: &anyhow::Result<()>) {
+    match result {
+        Ok(()) => send(Message::killed(pid, port, name)),
+        Err(e) => send(Message::kill_failed(pid, port, name, &format!("{:#}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_from_config() {
+        let config: crate::config::ConfigLayer = toml::from_str(
+            r#"
+            [notifications]
+            enabled = true
+            kill = false
+            "#,
+        )
+        .unwrap();
+        let settings = config.notifications.unwrap();
+        assert!(!settings.wants(NotifyEvent::Killed));
+        assert!(settings.wants(NotifyEvent::KillFailed));
+        assert!(settings.wants(NotifyEvent::Conflict));
+        assert_eq!(settings.cooldown, 60);
+        assert!(!NotifySettings::default().wants(NotifyEvent::Killed));
+    }
+
+    #[test]
+    fn test_throttle_drops_repeats_within_cooldown() {
+        let mut throttle = Throttle::default();
+        let cooldown = Duration::from_secs(60);
+        let start = Instant::now();
+        let conflict = Message::conflict(3000, 42, "node", "unauthorized process");
+
+        assert!(throttle.allow(&conflict, cooldown, start));
+        assert!(!throttle.allow(&conflict, cooldown, start + Duration::from_secs(5)));
+        // A different process on the same port is news
+        let other = Message::conflict(3000, 43, "node", "unauthorized process");
+        assert!(throttle.allow(&other, cooldown, start + Duration::from_secs(5)));
+        assert!(throttle.allow(&conflict, cooldown, start + Duration::from_secs(61)));
+
+        let failed = Message::kill_failed(42, Some(3000), Some("node"), "permission denied");
+        assert_eq!(failed.body, "Could not kill node (PID 42) on port 3000: permission denied");
+    }
+}
@@ -0,0 +1,150 @@
+//! `--offline`: one switch that turns off everything that reaches beyond this machine.
+//! Every outbound path asks [`ensure_allowed`] first, and HTTP clients are only built
+//! through [`client_builder`] / [`blocking_client_builder`], so nothing can phone out
+//! without going through here. Only URLs on this machine get through while offline, through
+//! [`local_client_builder`]. `cache --doctor` lists each path and whether it is blocked.
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Everything port-kill can send over the network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// Startup and `--check-updates` release lookups on GitHub
+    UpdateCheck,
+    /// `--self-update` downloads
+    SelfUpdate,
+    /// `[[notifications.webhooks]]` deliveries
+    Webhook,
+    /// `--monitor-endpoint` reports
+    Endpoint,
+    /// `--remote` commands over ssh
+    Remote,
+    /// `--linked` stopping the far end of ssh and kubectl tunnels
+    Tunnel,
+    /// `--import-preset` from a URL
+    PresetImport,
+    /// Service `healthcheck.http` URLs that aren't on this machine
+    HealthCheck,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 8] = [
+        Channel::UpdateCheck,
+        Channel::SelfUpdate,
+        Channel::Webhook,
+        Channel::Endpoint,
+        Channel::Remote,
+        Channel::Tunnel,
+        Channel::PresetImport,
+        Channel::HealthCheck,
+    ];
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Channel::UpdateCheck => "update checks",
+            Channel::SelfUpdate => "self-update",
+            Channel::Webhook => "webhooks",
+            Channel::Endpoint => "--monitor-endpoint",
+            Channel::Remote => "--remote",
+            Channel::Tunnel => "--linked tunnel endpoints",
+            Channel::PresetImport => "--import-preset URLs",
+            Channel::HealthCheck => "service healthchecks on other hosts",
+        }
+    }
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Fails when `--offline` is set
+pub fn ensure_allowed(channel: Channel) -> Result<()> {
+    if is_enabled() {
+        anyhow::bail!("Network access ({}) is disabled by --offline", channel.describe());
+    }
+    Ok(())
+}
+
+/// An async HTTP client for `channel`, unless offline
+pub fn client_builder(channel: Channel) -> Result<reqwest::ClientBuilder> {
+    ensure_allowed(channel)?;
+    Ok(reqwest::Client::builder())
+}
+
+/// An async HTTP client for `url`: always allowed when it points at localhost or a loopback
+/// address, since that never leaves the machine, and otherwise only unless offline
+pub fn local_client_builder(channel: Channel, url: &str) -> Result<reqwest::ClientBuilder> {
+    if !is_loopback_url(url) {
+        ensure_allowed(channel)?;
+    }
+    Ok(reqwest::Client::builder())
+}
+
+/// Whether `url` names localhost or a loopback address
+pub fn is_loopback_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let host = url.host_str().unwrap_or_default();
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// A blocking HTTP client for `channel`, unless offline
+pub fn blocking_client_builder(channel: Channel) -> Result<reqwest::blocking::ClientBuilder> {
+    ensure_allowed(channel)?;
+    Ok(reqwest::blocking::Client::builder())
+}
+
+/// One line per network path for `cache --doctor`
+pub fn doctor_notes() -> Vec<String> {
+    let state = if is_enabled() { "blocked (--offline)" } else { "allowed" };
+    let mut notes = vec![format!(
+        "Offline mode: {}",
+        if is_enabled() {
+            "on, no network access"
+        } else {
+            "off (--offline blocks every network path)"
+        }
+    )];
+    notes.extend(
+        Channel::ALL
+            .iter()
+            .map(|channel| format!("Network: {} {}", channel.describe(), state)),
+    );
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_blocks_every_channel() {
+        enable();
+        for channel in Channel::ALL {
+            assert!(ensure_allowed(channel).is_err());
+        }
+        assert!(client_builder(Channel::UpdateCheck).is_err());
+        assert!(doctor_notes().iter().skip(1).all(|note| note.ends_with("blocked (--offline)")));
+
+        // Loopback URLs never leave the machine
+        for url in ["http://localhost:3000/health", "http://127.0.0.1:8080/", "http://[::1]:9000/"] {
+            assert!(local_client_builder(Channel::HealthCheck, url).is_ok(), "{}", url);
+        }
+        for url in ["https://example.com/health", "http://10.0.0.5/", "not a url"] {
+            assert!(local_client_builder(Channel::HealthCheck, url).is_err(), "{}", url);
+        }
+    }
+}
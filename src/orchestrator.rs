@@ -4,7 +4,11 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
-use tokio::time::{sleep, Duration};
+use crate::compose::ComposeService;
+use crate::service_detector::ServiceDetector;
+use crate::service_state::{RunState, ServiceState};
+use crate::service_group::{parse_size, ResourceLimits, ResourceUsage, ServiceGroup};
+use tokio::time::{sleep, Duration, Instant};
 
 /// Configuration for a single service
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,17 +28,208 @@ pub struct ServiceConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
     
+    /// `.env` file(s) to load, relative to the config file; `env` overrides them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<EnvFiles>,
+    
     /// Services this depends on (must start first)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub depends_on: Option<Vec<String>>,
     
-    /// Health check command (optional)
+    /// How to tell the service is ready; services that depend on it wait for this
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub healthcheck: Option<String>,
+    pub healthcheck: Option<HealthCheck>,
     
     /// Delay before considering service started (seconds)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub startup_delay: Option<u64>,
+    
+    /// Memory limit for the whole service, e.g. "512M" or "2G"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem_limit: Option<String>,
+    
+    /// CPU limit in cores, e.g. 1.5
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    
+    /// Restart the service when it is killed for exceeding mem_limit (`--up` keeps watching)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_on_oom: Option<bool>,
+}
+
+/// `env_file: .env` or `env_file: [.env, .env.local]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvFiles {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl EnvFiles {
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            EnvFiles::One(path) => vec![path.as_str()],
+            EnvFiles::Many(paths) => paths.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// The service's environment, later sources winning as in docker-compose: the global
+    /// `env`, then each `env_file` in order, then the service's own `env`
+    pub fn environment(
+        &self,
+        global: Option<&HashMap<String, String>>,
+        base_dir: &Path,
+    ) -> Result<HashMap<String, String>> {
+        let mut env = global.cloned().unwrap_or_default();
+        for path in self.env_file.as_ref().map(EnvFiles::paths).unwrap_or_default() {
+            env.extend(crate::env_file::load(&base_dir.join(path))?);
+        }
+        env.extend(self.env.clone().unwrap_or_default());
+        Ok(env)
+    }
+    
+    /// Limits to enforce on the service's cgroup / job object
+    pub fn resource_limits(&self) -> Result<ResourceLimits> {
+        // A job object refuses allocations over its limit rather than killing the service,
+        // so there is no OOM kill to restart after
+        if cfg!(target_os = "windows") && self.restart_on_oom.unwrap_or(false) {
+            anyhow::bail!("restart_on_oom is not supported on Windows; remove it or use mem_limit alone");
+        }
+        Ok(ResourceLimits {
+            memory_bytes: self.mem_limit.as_deref().map(parse_size).transpose()?,
+            cpus: self.cpus,
+            oom_kill_group: self.restart_on_oom.unwrap_or(false),
+        })
+    }
+}
+
+/// How `--up` tells a service is ready before starting the services that depend on it. A
+/// plain string is a command; otherwise any of `tcp`, `http` and `command` (all must pass,
+/// and with none of them the service's own port is connected to), with docker-compose
+/// style timing:
+///
+/// ```yaml
+/// healthcheck:
+///   http: http://localhost:8000/health
+///   interval: 1       # seconds between checks
+///   timeout: 5        # seconds one check may take
+///   retries: 30       # failed checks before the service counts as unhealthy
+///   start_period: 0   # seconds of failures that don't count yet
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum HealthCheck {
+    Command(String),
+    Probe(HealthProbe),
+}
+
+impl HealthCheck {
+    pub fn probe(&self) -> HealthProbe {
+        match self {
+            HealthCheck::Command(command) => HealthProbe {
+                command: Some(command.clone()),
+                ..Default::default()
+            },
+            HealthCheck::Probe(probe) => probe.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthProbe {
+    /// Port to connect to on localhost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<u16>,
+    /// URL that must answer a GET with a 2xx status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http: Option<String>,
+    /// Shell command that must exit with 0, run in the service's directory and environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<u64>,
+}
+
+impl HealthProbe {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval.unwrap_or(1))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(5))
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(30)
+    }
+
+    pub fn start_period(&self) -> Duration {
+        Duration::from_secs(self.start_period.unwrap_or(0))
+    }
+
+    /// Run the check once. `port` is the service's, connected to when nothing else is set.
+    pub async fn check(&self, port: Option<u16>, dir: &Path, env: &HashMap<String, String>) -> Result<()> {
+        let timeout = self.timeout();
+        let tcp = match (self.tcp, &self.http, &self.command) {
+            (None, None, None) => Some(port.ok_or_else(|| {
+                anyhow::anyhow!("healthcheck needs tcp, http or command when the service has no port")
+            })?),
+            _ => self.tcp,
+        };
+        if let Some(port) = tcp {
+            tokio::time::timeout(timeout, tokio::net::TcpStream::connect(("127.0.0.1", port)))
+                .await
+                .map_err(|_| anyhow::anyhow!("connecting to port {} timed out", port))?
+                .with_context(|| format!("nothing accepts connections on port {}", port))?;
+        }
+        if let Some(ref url) = self.http {
+            let response =
+                crate::offline::local_client_builder(crate::offline::Channel::HealthCheck, url)?
+                    .timeout(timeout)
+                    .build()?
+                    .get(url)
+                    .send()
+                    .await
+                    .with_context(|| format!("GET {} failed", url))?;
+            if !response.status().is_success() {
+                anyhow::bail!("GET {} returned {}", url, response.status());
+            }
+        }
+        if let Some(ref command) = self.command {
+            let mut shell = if cfg!(target_os = "windows") {
+                let mut cmd = tokio::process::Command::new("cmd");
+                cmd.args(["/C", command]);
+                cmd
+            } else {
+                let mut cmd = tokio::process::Command::new("sh");
+                cmd.args(["-c", command]);
+                cmd
+            };
+            shell
+                .current_dir(dir)
+                .envs(env)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .kill_on_drop(true);
+            let status = tokio::time::timeout(timeout, shell.status())
+                .await
+                .map_err(|_| anyhow::anyhow!("`{}` timed out", command))?
+                .with_context(|| format!("Failed to run `{}`", command))?;
+            if !status.success() {
+                anyhow::bail!("`{}` exited with {}", command, status);
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Main orchestration configuration
@@ -74,8 +269,20 @@ impl Orchestrator {
         let content = fs::read_to_string(config_path)
             .context(format!("Failed to read config file: {}", config_path.display()))?;
         
-        let config: OrchestrationConfig = serde_yaml::from_str(&content)
-            .context("Failed to parse YAML configuration")?;
+        // The services section of a .portkill.toml / .portkill.json works too
+        let is_layered_config = config_path
+            .extension()
+            .is_some_and(|ext| ext == "toml" || ext == "json");
+        let config: OrchestrationConfig = if is_layered_config {
+            let layer = crate::config::ConfigLayer::from_file(config_path)?;
+            OrchestrationConfig {
+                version: None,
+                services: layer.services,
+                env: layer.env,
+            }
+        } else {
+            serde_yaml::from_str(&content).context("Failed to parse YAML configuration")?
+        };
         
         Ok(Self {
             config,
@@ -109,6 +316,32 @@ impl Orchestrator {
         &self.config
     }
     
+    /// Directory the config's relative paths (`dir`, `env_file`) start from
+    fn base_dir(&self) -> &Path {
+        self.config_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+    }
+    
+    /// Where a service runs: its `dir`, relative to the config, or the config's directory
+    pub(crate) fn working_dir(&self, service_config: &ServiceConfig) -> PathBuf {
+        match service_config.dir {
+            Some(ref dir) => self.base_dir().join(dir),
+            None => self.base_dir().to_path_buf(),
+        }
+    }
+    
+    /// The environment a configured service runs with (see [`ServiceConfig::environment`]),
+    /// or None when there is no service by that name
+    pub fn service_environment(&self, service_name: &str) -> Result<Option<HashMap<String, String>>> {
+        self.config
+            .services
+            .get(service_name)
+            .map(|service| service.environment(self.config.env.as_ref(), self.base_dir()))
+            .transpose()
+    }
+    
     /// Start all services in dependency order
     pub async fn start_all(&mut self) -> Result<()> {
         log::info!("Starting all services...");
@@ -123,14 +356,28 @@ impl Orchestrator {
         Ok(())
     }
     
-    /// Stop all running services
+    /// Stop all running services, dependents first. Services started by an earlier
+    /// invocation are found through their cgroup / job object.
     pub async fn stop_all(&mut self) -> Result<()> {
         log::info!("Stopping all services...");
         
-        let service_names: Vec<String> = self.running_services.keys().cloned().collect();
+        let service_names = self.resolve_dependencies()?;
         
+        let state = ServiceState::load();
+        // Servers taken over with --adopt that the config doesn't list
+        for service_name in state.running_adopted() {
+            if !self.config.services.contains_key(&service_name) {
+                self.stop_service(&service_name).await?;
+            }
+        }
         for service_name in service_names.iter().rev() {
-            self.stop_service(service_name).await?;
+            let recorded = state.get(service_name).is_some_and(|r| r.stopped_at.is_none());
+            if recorded
+                || self.running_services.contains_key(service_name)
+                || ServiceGroup::new(service_name).is_active()
+            {
+                self.stop_service(service_name).await?;
+            }
         }
         
         self.running_services.clear();
@@ -170,32 +417,59 @@ impl Orchestrator {
         let program = &parts[0];
         let args = &parts[1..];
         
-        let config_parent = self.config_path.parent().unwrap_or(Path::new("."));
-        let working_dir = if let Some(ref dir) = service_config.dir {
-            config_parent.join(dir)
-        } else {
-            config_parent.to_path_buf()
-        };
+        let working_dir = self.working_dir(&service_config);
         
         let mut cmd = Command::new(program);
         cmd.args(args).current_dir(&working_dir);
         
         // Add environment variables
-        if let Some(ref global_env) = self.config.env {
-            cmd.envs(global_env);
-        }
-        if let Some(ref service_env) = service_config.env {
-            cmd.envs(service_env);
+        let env = service_config
+            .environment(self.config.env.as_ref(), self.base_dir())
+            .with_context(|| format!("Failed to load the environment of service '{}'", service_name))?;
+        cmd.envs(&env);
+        
+        let limits = service_config.resource_limits()
+            .context(format!("Invalid resource limits for service '{}'", service_name))?;
+        let group = ServiceGroup::new(service_name).with_limits(limits);
+        group.configure(&mut cmd);
+        
+        // Output goes to the service's log rather than to this terminal, so it can be read
+        // with --logs after this run has exited
+        match crate::service_logs::open_for_start(service_name, &service_config.command)
+            .and_then(|log| Ok((log.try_clone()?, log)))
+        {
+            Ok((stdout, stderr)) => {
+                cmd.stdin(std::process::Stdio::null()).stdout(stdout).stderr(stderr);
+            }
+            Err(e) => log::warn!("Output of service '{}' is not captured: {:#}", service_name, e),
         }
         
         // Spawn the process
-        let child = cmd.spawn()
+        let mut child = cmd.spawn()
             .context(format!("Failed to start service '{}': {}", service_name, service_config.command))?;
         
         let pid = child.id();
         
         log::info!("Service '{}' started with PID {}", service_name, pid);
         
+        // Track the whole process tree, not just the PID we spawned
+        if let Err(e) = group.adopt(pid) {
+            if limits.is_empty() {
+                log::debug!("Service '{}' not placed in {}: {} (falling back to its process group)", service_name, group.name(), e);
+            } else {
+                log::warn!("Resource limits for service '{}' are not enforced: {}", service_name, e);
+            }
+        }
+        if let Err(e) = group.resume(pid) {
+            let _ = child.kill();
+            return Err(e.context(format!("Failed to start service '{}'", service_name)));
+        }
+        
+        // So a later --status can tell a crash from a service that was never started
+        if let Err(e) = ServiceState::load().record_start(service_name, pid, &service_config.command) {
+            log::warn!("Service '{}' not recorded in {}: {:#}", service_name, ServiceState::state_path().display(), e);
+        }
+        
         // Wait for startup delay if specified
         if let Some(delay) = service_config.startup_delay {
             log::info!("Waiting {} seconds for service '{}' to start...", delay, service_name);
@@ -213,15 +487,88 @@ impl Orchestrator {
             },
         );
         
+        // Dependents only start once this is healthy, like compose's service_healthy
+        if let Some(ref healthcheck) = service_config.healthcheck {
+            self.wait_until_healthy(service_name, &healthcheck.probe(), service_config.port, &working_dir, &env)
+                .await?;
+        }
+        
         Ok(())
     }
     
+    /// Check `probe` every interval until it passes. Fails once `retries` checks after the
+    /// start period have failed, or when the service exits with an error first.
+    async fn wait_until_healthy(
+        &mut self,
+        service_name: &str,
+        probe: &HealthProbe,
+        port: Option<u16>,
+        dir: &Path,
+        env: &HashMap<String, String>,
+    ) -> Result<()> {
+        log::info!("Waiting for service '{}' to become healthy...", service_name);
+        let started = Instant::now();
+        let mut failures = 0;
+        loop {
+            // A service whose command failed won't become healthy. One that exited cleanly
+            // may have started something in the background (`docker compose up -d`).
+            let child = self.running_services.get_mut(service_name).and_then(|s| s.child.as_mut());
+            if let Some(status) = child.and_then(|child| child.try_wait().ok().flatten()) {
+                if !status.success() {
+                    anyhow::bail!("Service '{}' exited with {} before it became healthy", service_name, status);
+                }
+            }
+            
+            match probe.check(port, dir, env).await {
+                Ok(()) => {
+                    log::info!("Service '{}' is healthy", service_name);
+                    return Ok(());
+                }
+                Err(e) => {
+                    if started.elapsed() >= probe.start_period() {
+                        failures += 1;
+                    }
+                    if failures >= probe.retries() {
+                        anyhow::bail!("Service '{}' is unhealthy after {} failed check(s): {:#}", service_name, failures, e);
+                    }
+                    log::debug!("Service '{}' not healthy yet: {:#}", service_name, e);
+                }
+            }
+            sleep(probe.interval()).await;
+        }
+    }
+    
     /// Stop a specific service
     pub async fn stop_service(&mut self, service_name: &str) -> Result<()> {
-        let mut service = match self.running_services.remove(service_name) {
+        let group = ServiceGroup::new(service_name);
+        let tracked = self.running_services.remove(service_name);
+        let mut state = ServiceState::load();
+        let recorded = state.get(service_name).cloned();
+        if let Err(e) = state.record_stop(service_name) {
+            log::warn!("Failed to record that service '{}' stopped: {:#}", service_name, e);
+        }
+        
+        // Exact: stop everything in the service's cgroup / job object, however it forked
+        if group.is_active() {
+            let count = group.terminate(Duration::from_millis(500)).await?;
+            log::info!("Service '{}' stopped ({} process(es) in {} {})", service_name, count, group.kind(), group.name());
+            if let Some(mut child) = tracked.and_then(|s| s.child) {
+                let _ = child.wait();
+            }
+            return Ok(());
+        }
+        group.release();
+        
+        let mut service = match tracked {
             Some(s) => s,
             None => {
-                log::warn!("Service '{}' is not running", service_name);
+                // Started by an earlier run without a cgroup / job object: the recorded PID
+                // leads the service's process group. An adopted one is a lone listener.
+                match recorded.filter(|record| record.is_alive()) {
+                    Some(record) if record.adopted => Self::terminate_tree(service_name, record.pid).await,
+                    Some(record) => Self::terminate_process_group(service_name, record.pid).await,
+                    None => log::warn!("Service '{}' is not running", service_name),
+                }
                 return Ok(());
             }
         };
@@ -232,10 +579,11 @@ impl Orchestrator {
             // Try graceful shutdown first
             #[cfg(not(target_os = "windows"))]
             {
-                use nix::sys::signal::{kill, Signal};
+                use nix::sys::signal::{killpg, Signal};
                 use nix::unistd::Pid;
                 
-                if let Err(e) = kill(Pid::from_raw(service.pid as i32), Signal::SIGTERM) {
+                // The service leads its own process group, so this reaches its children too
+                if let Err(e) = killpg(Pid::from_raw(service.pid as i32), Signal::SIGTERM) {
                     log::warn!("Failed to send SIGTERM to service '{}': {}", service_name, e);
                 }
                 
@@ -262,6 +610,59 @@ impl Orchestrator {
         Ok(())
     }
     
+    async fn terminate_process_group(service_name: &str, pid: u32) {
+        log::info!("Stopping service '{}' (PID {})...", service_name, pid);
+        
+        #[cfg(not(target_os = "windows"))]
+        {
+            use nix::sys::signal::{killpg, Signal};
+            use nix::unistd::Pid;
+            
+            if let Err(e) = killpg(Pid::from_raw(pid as i32), Signal::SIGTERM) {
+                log::warn!("Failed to send SIGTERM to service '{}': {}", service_name, e);
+            }
+            sleep(Duration::from_millis(500)).await;
+            let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+        }
+        
+        #[cfg(target_os = "windows")]
+        {
+            let _ = Command::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .output();
+        }
+    }
+    
+    /// Stop an adopted server and whatever it started, children first
+    async fn terminate_tree(service_name: &str, pid: u32) {
+        log::info!("Stopping adopted service '{}' (PID {})...", service_name, pid);
+        
+        #[cfg(not(target_os = "windows"))]
+        {
+            use nix::sys::signal::{kill, Signal};
+            use nix::unistd::Pid;
+            
+            let mut pids = crate::system_monitor::descendants(pid as i32);
+            pids.push(pid as i32);
+            for &pid in &pids {
+                let _ = kill(Pid::from_raw(pid), Signal::SIGTERM);
+            }
+            sleep(Duration::from_millis(500)).await;
+            for &pid in &pids {
+                if crate::hooks::is_running(pid) {
+                    let _ = kill(Pid::from_raw(pid), Signal::SIGKILL);
+                }
+            }
+        }
+        
+        #[cfg(target_os = "windows")]
+        {
+            let _ = Command::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .output();
+        }
+    }
+    
     /// Restart a specific service
     pub async fn restart_service(&mut self, service_name: &str) -> Result<()> {
         log::info!("Restarting service '{}'...", service_name);
@@ -274,19 +675,94 @@ impl Orchestrator {
         Ok(())
     }
     
+    /// Restart services with `restart_on_oom` whose group recorded an OOM kill.
+    /// Returns the names of the services that were restarted.
+    pub async fn restart_oom_killed(&mut self) -> Result<Vec<String>> {
+        let mut restarted = Vec::new();
+        let candidates: Vec<String> = self.config.services.iter()
+            .filter(|(_, config)| config.restart_on_oom.unwrap_or(false))
+            .map(|(name, _)| name.clone())
+            .collect();
+        
+        for service_name in candidates {
+            let oom_killed = ServiceGroup::new(&service_name)
+                .usage()
+                .is_some_and(|usage| usage.oom_kills > 0);
+            if oom_killed {
+                log::warn!("Service '{}' was OOM-killed, restarting", service_name);
+                // Stopping removes the group, which resets its OOM counter
+                self.restart_service(&service_name).await?;
+                restarted.push(service_name);
+            }
+        }
+        
+        Ok(restarted)
+    }
+    
+    /// Start the services that crashed (see [`RunState::Crashed`]) again, in dependency
+    /// order. Services stopped with `--down` or never started are left alone. Returns the
+    /// names of the services that were restarted.
+    pub async fn restart_failed(&mut self) -> Result<Vec<String>> {
+        let crashed: Vec<String> = self.get_status()
+            .into_iter()
+            .filter(|status| status.state == RunState::Crashed)
+            .map(|status| status.name)
+            .collect();
+        let mut restarted = Vec::new();
+        for service_name in self.resolve_dependencies()? {
+            if crashed.contains(&service_name) {
+                log::warn!("Service '{}' crashed, restarting", service_name);
+                // Whatever it left behind in its group goes first
+                if ServiceGroup::new(&service_name).is_active() {
+                    self.stop_service(&service_name).await?;
+                }
+                self.start_service(&service_name).await?;
+                restarted.push(service_name);
+            }
+        }
+        Ok(restarted)
+    }
+    
+    /// True if any service asks to be restarted after an OOM kill (never on Windows, see
+    /// [`ServiceConfig::resource_limits`])
+    pub fn wants_oom_supervision(&self) -> bool {
+        !cfg!(target_os = "windows")
+            && self.config.services.values().any(|config| config.restart_on_oom.unwrap_or(false))
+    }
+    
     /// Get status of all services
     pub fn get_status(&self) -> Vec<ServiceStatus> {
         let mut statuses = Vec::new();
+        let service_state = ServiceState::load();
         
         for (name, config) in &self.config.services {
             let running = self.running_services.get(name);
+            let group = ServiceGroup::new(name);
+            let group_pids = group.pids();
+            let state = service_state.run_state(name, running.is_some() || !group_pids.is_empty());
+            let record = service_state.get(name);
+            // Without a configured port, the one its command or project declares
+            let port = config.port.or_else(|| {
+                ServiceDetector::infer_port_for_command(&config.command, &self.working_dir(config))
+            });
             
             statuses.push(ServiceStatus {
                 name: name.clone(),
-                running: running.is_some(),
-                pid: running.map(|s| s.pid),
-                port: config.port,
+                running: state == RunState::Running,
+                state,
+                pid: running
+                    .map(|s| s.pid)
+                    .or_else(|| group_pids.iter().min().copied())
+                    .or_else(|| record.filter(|_| state == RunState::Running).map(|r| r.pid)),
+                started_at: record.filter(|_| state == RunState::Running).map(|r| r.started_at),
+                port,
+                port_bound: port.map(|port| std::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, port)).is_err()),
                 command: config.command.clone(),
+                processes: group_pids.len(),
+                group: (!group_pids.is_empty()).then(|| format!("{} {}", group.kind(), group.name())),
+                mem_limit: config.mem_limit.clone(),
+                cpus: config.cpus,
+                usage: if group_pids.is_empty() { None } else { group.usage() },
             });
         }
         
@@ -301,7 +777,10 @@ impl Orchestrator {
         let mut in_progress = std::collections::HashSet::new();
         let mut order = Vec::new();
         
-        for service_name in self.config.services.keys() {
+        // Sorted, so services that don't depend on each other start in a stable order
+        let mut service_names: Vec<&String> = self.config.services.keys().collect();
+        service_names.sort();
+        for service_name in service_names {
             self.visit_service(service_name, &mut visited, &mut in_progress, &mut order)?;
         }
         
@@ -333,6 +812,9 @@ impl Orchestrator {
         
         if let Some(ref deps) = service.depends_on {
             for dep in deps {
+                if !self.config.services.contains_key(dep) {
+                    anyhow::bail!("Service '{}' depends on '{}', which is not configured", service_name, dep);
+                }
                 self.visit_service(dep, visited, in_progress, order)?;
             }
         }
@@ -352,9 +834,23 @@ impl Orchestrator {
 pub struct ServiceStatus {
     pub name: String,
     pub running: bool,
+    pub state: RunState,
     pub pid: Option<u32>,
+    /// When the running service was started, if this or an earlier run started it
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
     pub port: Option<u16>,
+    /// Whether something is listening on `port`
+    pub port_bound: Option<bool>,
     pub command: String,
+    /// Processes in the service's cgroup / job object (0 when not tracked)
+    pub processes: usize,
+    /// e.g. "cgroup port-kill-api"
+    pub group: Option<String>,
+    /// Configured limits, as written in the config
+    pub mem_limit: Option<String>,
+    pub cpus: Option<f64>,
+    /// Measured usage and limit violations (None when the group isn't accounted)
+    pub usage: Option<ResourceUsage>,
 }
 
 /// Create a sample configuration file
@@ -376,6 +872,7 @@ services:
     port: 3000
     dir: ./frontend
     startup_delay: 2
+    # env_file: .env          # or a list; loaded first, env below overrides it
     env:
       PORT: "3000"
   
@@ -390,11 +887,27 @@ services:
       PORT: "8000"
       DATABASE_URL: postgres://localhost:5432/myapp
   
-  # Database service
+  # Database service; backend waits until it accepts connections
   database:
     command: docker-compose up database
     port: 5432
-    startup_delay: 5
+    healthcheck:
+      tcp: 5432
+      retries: 60
+
+  # Health checks (dependents start once a check passes):
+  #   healthcheck: pg_isready              # a command that exits with 0
+  #   healthcheck:
+  #     http: http://localhost:8000/health # a GET answered with 2xx
+  #     interval: 1                        # seconds between checks
+  #     timeout: 5                         # seconds one check may take
+  #     retries: 30                        # failed checks before giving up
+  #     start_period: 0                    # seconds of failures that don't count
+
+  # Resource limits (cgroup v2 on Linux, Job Objects on Windows):
+  #   mem_limit: 2G          # memory for the whole service
+  #   cpus: 1.5              # CPU time in cores
+  #   restart_on_oom: true   # --up keeps watching and restarts it after an OOM kill
 "#;
     
     fs::write(path, sample)
@@ -403,6 +916,68 @@ services:
     Ok(())
 }
 
+/// An orchestration config running each service of a compose file with `docker-compose up
+/// --no-deps`, so `--up` starts them in `depends_on` order itself. Services that depend on
+/// a service with a published port wait until it accepts connections. Services behind a
+/// profile are left out.
+pub fn config_from_compose(compose_file_name: &str, services: &[ComposeService]) -> OrchestrationConfig {
+    let included: Vec<&ComposeService> = services.iter().filter(|s| s.profiles.is_empty()).collect();
+    let depended_on: std::collections::HashSet<&str> =
+        included.iter().flat_map(|s| s.depends_on.iter().map(String::as_str)).collect();
+    let services = included
+        .iter()
+        .map(|service| {
+            let port = service.published_ports.first().copied();
+            let depends_on: Vec<String> = service
+                .depends_on
+                .iter()
+                .filter(|name| included.iter().any(|s| &s.name == *name))
+                .cloned()
+                .collect();
+            let config = ServiceConfig {
+                command: format!("docker-compose -f {} up --no-deps {}", compose_file_name, service.name),
+                port,
+                dir: None,
+                env: None,
+                env_file: None,
+                depends_on: (!depends_on.is_empty()).then_some(depends_on),
+                healthcheck: port
+                    .filter(|_| depended_on.contains(service.name.as_str()))
+                    .map(|port| HealthCheck::Probe(HealthProbe { tcp: Some(port), ..Default::default() })),
+                startup_delay: None,
+                mem_limit: None,
+                cpus: None,
+                restart_on_oom: None,
+            };
+            (service.name.clone(), config)
+        })
+        .collect();
+    OrchestrationConfig {
+        version: Some("1".to_string()),
+        services,
+        env: None,
+    }
+}
+
+/// Write an orchestration config for the compose file at `compose_path` (see
+/// [`config_from_compose`]). Returns the number of services written.
+pub fn create_config_from_compose(path: &Path, compose_path: &Path) -> Result<usize> {
+    let services = crate::compose::load(compose_path)?;
+    let file_name = compose_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let config = config_from_compose(&file_name, &services);
+    let yaml = serde_yaml::to_string(&config).context("Failed to serialize configuration")?;
+    let header = format!(
+        "# Port Kill Orchestration Configuration, generated from {}\n# Documentation: https://github.com/treadiehq/port-kill\n\n",
+        file_name
+    );
+    fs::write(path, header + &yaml)
+        .context("Failed to write configuration")?;
+    Ok(config.services.len())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::command_line::parse_command_line;
@@ -433,8 +1008,12 @@ mod tests {
                 dir: None,
                 depends_on: Some(vec!["backend".to_string()]),
                 env: None,
+                env_file: None,
                 startup_delay: None,
                 healthcheck: None,
+                mem_limit: None,
+                cpus: None,
+                restart_on_oom: None,
             },
         );
         services.insert(
@@ -445,8 +1024,12 @@ mod tests {
                 dir: None,
                 depends_on: Some(vec!["frontend".to_string()]),
                 env: None,
+                env_file: None,
                 startup_delay: None,
                 healthcheck: None,
+                mem_limit: None,
+                cpus: None,
+                restart_on_oom: None,
             },
         );
 
@@ -466,6 +1049,140 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_start_order_and_healthchecks() {
+        let config: OrchestrationConfig = serde_yaml::from_str(
+            r#"
+services:
+  web:
+    command: npm run dev
+    depends_on: [api]
+  api:
+    command: npm start
+    port: 8000
+    depends_on: [database, cache]
+    healthcheck:
+      http: http://localhost:8000/health
+      retries: 5
+  database:
+    command: postgres
+    healthcheck: pg_isready
+  cache:
+    command: redis-server
+"#,
+        )
+        .unwrap();
+        let database = &config.services["database"];
+        assert_eq!(database.healthcheck.as_ref().unwrap().probe().command.as_deref(), Some("pg_isready"));
+        let api = config.services["api"].healthcheck.as_ref().unwrap().probe();
+        assert_eq!(api.http.as_deref(), Some("http://localhost:8000/health"));
+        assert_eq!((api.retries(), api.interval().as_secs()), (5, 1));
+
+        let mut orchestrator = Orchestrator {
+            config,
+            running_services: std::collections::HashMap::new(),
+            config_path: std::path::PathBuf::from(".port-kill.yaml"),
+        };
+        assert_eq!(orchestrator.resolve_dependencies().unwrap(), vec!["database", "cache", "api", "web"]);
+
+        orchestrator.config.services.get_mut("web").unwrap().depends_on = Some(vec!["worker".to_string()]);
+        let err = orchestrator.resolve_dependencies().unwrap_err();
+        assert!(err.to_string().contains("depends on 'worker'"));
+    }
+
+    #[test]
+    fn test_config_from_compose() {
+        let services = crate::compose::parse(
+            r#"
+services:
+  web:
+    ports: ["3000:3000"]
+    depends_on: [api, debugger]
+  api:
+    ports: ["8000:8000"]
+    depends_on: [db]
+  db:
+    ports: ["5432:5432"]
+  debugger:
+    profiles: [debug]
+"#,
+        )
+        .unwrap();
+        let config = crate::orchestrator::config_from_compose("compose.yaml", &services);
+        assert_eq!(config.services.len(), 3);
+        let web = &config.services["web"];
+        assert_eq!(web.command, "docker-compose -f compose.yaml up --no-deps web");
+        assert_eq!(web.port, Some(3000));
+        // The profiled debugger isn't part of the config
+        assert_eq!(web.depends_on, Some(vec!["api".to_string()]));
+        assert!(web.healthcheck.is_none());
+        let db = config.services["db"].healthcheck.as_ref().unwrap().probe();
+        assert_eq!(db.tcp, Some(5432));
+
+        let orchestrator = Orchestrator {
+            config,
+            running_services: std::collections::HashMap::new(),
+            config_path: std::path::PathBuf::from(".port-kill.yaml"),
+        };
+        assert_eq!(orchestrator.resolve_dependencies().unwrap(), vec!["db", "api", "web"]);
+    }
+
+    #[test]
+    fn test_service_environment_precedence() {
+        let dir = std::env::temp_dir().join(format!("port-kill-env-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".env"), "PORT=3000\nMODE=file\nSHARED=base\n").unwrap();
+        std::fs::write(dir.join(".env.local"), "SHARED=local\n").unwrap();
+        let config_path = dir.join("port-kill.yaml");
+        std::fs::write(
+            &config_path,
+            r#"
+env:
+  MODE: global
+  NODE_ENV: development
+services:
+  web:
+    command: npm run dev
+    env_file: [.env, .env.local]
+    env:
+      PORT: "4000"
+"#,
+        )
+        .unwrap();
+
+        let orchestrator = Orchestrator::load(&config_path).unwrap();
+        let env = orchestrator.service_environment("web").unwrap().unwrap();
+        assert_eq!(env["NODE_ENV"], "development");
+        assert_eq!(env["MODE"], "file");
+        assert_eq!(env["SHARED"], "local");
+        assert_eq!(env["PORT"], "4000");
+        assert!(orchestrator.service_environment("api").unwrap().is_none());
+
+        std::fs::remove_file(dir.join(".env.local")).unwrap();
+        assert!(orchestrator.service_environment("web").is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_health_probe_check() {
+        use crate::orchestrator::HealthProbe;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let dir = std::env::temp_dir();
+        let env = std::collections::HashMap::new();
+
+        // Nothing set: the service's own port
+        assert!(HealthProbe::default().check(Some(port), &dir, &env).await.is_ok());
+        assert!(HealthProbe::default().check(None, &dir, &env).await.is_err());
+        drop(listener);
+        let tcp = HealthProbe { tcp: Some(port), ..Default::default() };
+        assert!(tcp.check(None, &dir, &env).await.is_err());
+
+        let command = |command: &str| HealthProbe { command: Some(command.to_string()), ..Default::default() };
+        assert!(command("exit 0").check(None, &dir, &env).await.is_ok());
+        assert!(command("exit 1").check(None, &dir, &env).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_working_dir_relative_to_config() {
         let root_temp = std::env::temp_dir().join("port-kill-test-wd");
@@ -0,0 +1,178 @@
+//! The documented JSON output of `--json` commands. Each output has a JSON Schema generated
+//! from its Rust type with schemars; `--schema <output>` prints it and the same schemas are
+//! committed under `schemas/`. A test fails when a type drifts from its committed schema,
+//! so output changes are always deliberate. See `schemas/README.md` for the
+//! compatibility rules and ordering guarantees.
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+/// A documented JSON output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputSchema {
+    /// `--json` / `--once --json`: one process per line
+    #[value(name = "output.list")]
+    List,
+    /// `--list --json` / `--output json`: the whole scan as one object
+    #[value(name = "output.snapshot")]
+    Snapshot,
+    /// Last line of a listing when the scan failed or was incomplete
+    #[value(name = "output.scan-status")]
+    ScanStatus,
+    /// `--json` with a kill (`<ports>`, `--clear`, `--kill`): one result per process
+    #[value(name = "output.kill")]
+    Kill,
+    /// `--history --json`: one entry per line (`--output json` prints an array of them)
+    #[value(name = "output.history")]
+    History,
+    /// `--audit --json`
+    #[value(name = "output.audit")]
+    Audit,
+    /// `--show-audit --json`: one audited action per line
+    #[value(name = "output.audit-log")]
+    AuditLog,
+    /// `--at <when> --json`
+    #[value(name = "output.at")]
+    At,
+    /// `--timeline <port> --json`
+    #[value(name = "output.timeline")]
+    Timeline,
+    /// `--report week|month --json`
+    #[value(name = "output.report")]
+    Report,
+    /// `--serve`: the data of each change streamed on `/events`
+    #[value(name = "output.event")]
+    Event,
+    #[value(name = "output.cache.list")]
+    CacheList,
+    #[value(name = "output.cache.clean")]
+    CacheClean,
+    #[value(name = "output.cache.restore")]
+    CacheRestore,
+    #[value(name = "output.cache.doctor")]
+    CacheDoctor,
+    #[value(name = "output.cache.backups")]
+    CacheBackups,
+}
+
+impl OutputSchema {
+    pub const ALL: [OutputSchema; 16] = [
+        OutputSchema::List,
+        OutputSchema::Snapshot,
+        OutputSchema::ScanStatus,
+        OutputSchema::Kill,
+        OutputSchema::History,
+        OutputSchema::Audit,
+        OutputSchema::AuditLog,
+        OutputSchema::At,
+        OutputSchema::Timeline,
+        OutputSchema::Report,
+        OutputSchema::Event,
+        OutputSchema::CacheList,
+        OutputSchema::CacheClean,
+        OutputSchema::CacheRestore,
+        OutputSchema::CacheDoctor,
+        OutputSchema::CacheBackups,
+    ];
+
+    /// Name used on the command line and for the file under `schemas/`
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputSchema::List => "output.list",
+            OutputSchema::Snapshot => "output.snapshot",
+            OutputSchema::ScanStatus => "output.scan-status",
+            OutputSchema::Kill => "output.kill",
+            OutputSchema::History => "output.history",
+            OutputSchema::Audit => "output.audit",
+            OutputSchema::AuditLog => "output.audit-log",
+            OutputSchema::At => "output.at",
+            OutputSchema::Timeline => "output.timeline",
+            OutputSchema::Report => "output.report",
+            OutputSchema::Event => "output.event",
+            OutputSchema::CacheList => "output.cache.list",
+            OutputSchema::CacheClean => "output.cache.clean",
+            OutputSchema::CacheRestore => "output.cache.restore",
+            OutputSchema::CacheDoctor => "output.cache.doctor",
+            OutputSchema::CacheBackups => "output.cache.backups",
+        }
+    }
+
+    pub fn schema(self) -> RootSchema {
+        let mut schema = match self {
+            OutputSchema::List => schema_for!(crate::types::ProcessInfo),
+            OutputSchema::Snapshot => schema_for!(crate::types::ScanSnapshot),
+            OutputSchema::ScanStatus => schema_for!(crate::scan_health::ScanStatusLine),
+            OutputSchema::Kill => schema_for!(crate::types::KillResult),
+            OutputSchema::History => schema_for!(crate::types::ProcessHistoryEntry),
+            OutputSchema::Audit => schema_for!(crate::types::SecurityAuditResult),
+            OutputSchema::AuditLog => schema_for!(crate::audit_log::AuditEntry),
+            OutputSchema::At => schema_for!(crate::timeline::TimeTravelReport),
+            OutputSchema::Timeline => schema_for!(crate::timeline::PortTimeline),
+            OutputSchema::Report => schema_for!(crate::digest::Digest),
+            OutputSchema::Event => schema_for!(crate::types::PortEvent),
+            OutputSchema::CacheList => schema_for!(crate::cache::types::ListResponse),
+            OutputSchema::CacheClean => schema_for!(crate::cache::types::CleanResponse),
+            OutputSchema::CacheRestore => schema_for!(crate::cache::types::RestoreResponse),
+            OutputSchema::CacheDoctor => schema_for!(crate::cache::doctor::DoctorReport),
+            OutputSchema::CacheBackups => schema_for!(crate::cache::types::BackupsResponse),
+        };
+        schema.schema.metadata().id = Some(format!("port-kill/{}.json", self.name()));
+        schema
+    }
+
+    /// The schema as pretty-printed JSON, exactly as committed under `schemas/`
+    pub fn to_json(self) -> String {
+        let mut json = serde_json::to_string_pretty(&self.schema())
+            .expect("schemas always serialize");
+        json.push('\n');
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_committed_schemas_match_types() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        for output in OutputSchema::ALL {
+            let path = dir.join(format!("{}.json", output.name()));
+            let committed = std::fs::read_to_string(&path).unwrap_or_default();
+            assert!(
+                committed == output.to_json(),
+                "{} is out of date: if the output change is intended, run \
+                 `port-kill-console --schema {} > schemas/{}.json` and note it in schemas/README.md",
+                path.display(),
+                output.name(),
+                output.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_kill_results_match_documented_enum_values() {
+        let ok = crate::types::KillResult::new(
+            42,
+            Some(3000),
+            Some("node"),
+            &Ok(crate::hooks::KillOutcome::Killed),
+        );
+        let line = serde_json::to_value(&ok).unwrap();
+        assert_eq!(line["status"], "killed");
+        assert!(line.get("error").is_none());
+
+        let exited = crate::types::KillResult::new(
+            42,
+            Some(3000),
+            Some("node"),
+            &Ok(crate::hooks::KillOutcome::Exited),
+        );
+        assert_eq!(serde_json::to_value(&exited).unwrap()["status"], "exited");
+
+        let failed = crate::types::KillResult::new(42, None, None, &Err(anyhow::anyhow!("denied")));
+        let line = serde_json::to_value(&failed).unwrap();
+        assert_eq!(line["status"], "failed");
+        assert_eq!(line["error"], "denied");
+    }
+}
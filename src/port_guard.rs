@@ -8,25 +8,46 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration as TokioDuration};
 
+use crate::memory::BoundedSet;
 use crate::process_monitor::ProcessMonitor;
+use crate::rate_limiter::{KillDecision, KillRateLimiter};
+use crate::reservations::{SharedReservations, SHARED_FILE};
 use crate::types::{
     GuardStatus, PortConflict, PortConflictType, PortReservation, PortResolution, ProcessInfo,
 };
 use std::collections::HashSet;
 
+/// Intercepted commands remembered for the count in the guard status
+const INTERCEPTED_COMMANDS_KEPT: usize = 1000;
+
+/// Conflicts remembered as resolved, so the guard doesn't resolve them again on each pass
+const RESOLVED_CONFLICTS_KEPT: usize = 1000;
+
 /// Port Guard daemon that proactively prevents port conflicts
 pub struct PortGuardDaemon {
     watched_ports: Vec<u16>,
     reservations: Arc<Mutex<HashMap<u16, PortReservation>>>,
     reservation_file: String,
-    auto_resolve: bool,
+    /// The reservation file's modification time when it was last read or written
+    reservations_modified: Arc<Mutex<Option<std::time::SystemTime>>>,
+    /// The reservations the repository of the current directory shares, which a
+    /// reservation of the same port in the reservation file overrides
+    shared_reservations: Arc<Mutex<SharedReservations>>,
+    /// The policy for conflicts on ports whose reservation doesn't set one
+    default_policy: PortResolution,
+    /// `port:pid` of the conflicts already resolved without a kill, so each is handled once
+    resolved_conflicts: Arc<Mutex<BoundedSet<String>>>,
     auto_restart: bool,
     conflicts_resolved: Arc<Mutex<usize>>,
     is_running: Arc<Mutex<bool>>,
     process_monitor: Arc<Mutex<ProcessMonitor>>,
-    intercepted_commands: Arc<Mutex<HashSet<String>>>,
+    /// Distinct commands seen, the most recent [`INTERCEPTED_COMMANDS_KEPT`]
+    intercepted_commands: Arc<Mutex<BoundedSet<String>>>,
     process_interception_enabled: bool,
     allowed_process_name: Option<String>,
+    simulate: bool,
+    kill_limiter: Arc<Mutex<KillRateLimiter>>,
+    no_kill_if_active: bool,
 }
 
 impl PortGuardDaemon {
@@ -41,17 +62,33 @@ impl PortGuardDaemon {
             watched_ports,
             reservations: Arc::new(Mutex::new(HashMap::new())),
             reservation_file,
-            auto_resolve,
+            reservations_modified: Arc::new(Mutex::new(None)),
+            shared_reservations: Arc::new(Mutex::new(SharedReservations::default())),
+            default_policy: if auto_resolve {
+                PortResolution::KillExisting
+            } else {
+                PortResolution::NotifyUser
+            },
+            resolved_conflicts: Arc::new(Mutex::new(BoundedSet::new(RESOLVED_CONFLICTS_KEPT))),
             auto_restart: false,
             conflicts_resolved: Arc::new(Mutex::new(0)),
             is_running: Arc::new(Mutex::new(false)),
             process_monitor,
-            intercepted_commands: Arc::new(Mutex::new(HashSet::new())),
+            intercepted_commands: Arc::new(Mutex::new(BoundedSet::new(INTERCEPTED_COMMANDS_KEPT))),
             process_interception_enabled: true,
             allowed_process_name: None,
+            simulate: false,
+            kill_limiter: Arc::new(Mutex::new(KillRateLimiter::default())),
+            no_kill_if_active: false,
         }
     }
 
+    /// Set the policy for conflicts on ports without one of their own, instead of the one
+    /// `auto_resolve` picked
+    pub fn set_default_policy(&mut self, policy: PortResolution) {
+        self.default_policy = policy;
+    }
+
     /// Set an allowed process name for guard enforcement
     pub fn set_allowed_process_name(&mut self, name: String) {
         self.allowed_process_name = Some(name);
@@ -62,10 +99,26 @@ impl PortGuardDaemon {
         self.auto_restart = enabled;
     }
 
+    /// Enable or disable simulation (log intended actions without killing anything)
+    pub fn set_simulate(&mut self, enabled: bool) {
+        self.simulate = enabled;
+    }
+
+    /// Set the cooldown/rate limit applied to automated kills
+    pub fn set_kill_limiter(&mut self, limiter: KillRateLimiter) {
+        self.kill_limiter = Arc::new(Mutex::new(limiter));
+    }
+
+    /// Leave listeners alone while clients are still connected to them
+    pub fn set_no_kill_if_active(&mut self, enabled: bool) {
+        self.no_kill_if_active = enabled;
+    }
+
     /// Start the Port Guard daemon
     pub async fn start(&self) -> Result<()> {
         // Load existing reservations
         self.load_reservations().await?;
+        self.sync_shared_reservations().await?;
 
         // Mark as running
         {
@@ -77,6 +130,9 @@ impl PortGuardDaemon {
             "🛡️  Port Guard daemon started, watching ports: {:?}",
             self.watched_ports
         );
+        if self.simulate {
+            info!("🧪 Simulation mode: conflicts are logged but no processes will be killed");
+        }
 
         // Start the main monitoring loop
         self.monitor_loop().await?;
@@ -113,9 +169,12 @@ impl PortGuardDaemon {
                 }
             }
 
-            // Clean up expired reservations
-            if let Err(e) = self.cleanup_expired_reservations().await {
-                warn!("Error cleaning up expired reservations: {}", e);
+            // Follow changes to the reservation file, expire and renew reservations
+            if let Err(e) = self.sync_reservations().await {
+                warn!("Error updating port reservations: {}", e);
+            }
+            if let Err(e) = self.sync_shared_reservations().await {
+                warn!("Error reading shared port reservations: {:#}", e);
             }
 
             // Sleep for 2 seconds
@@ -163,6 +222,7 @@ impl PortGuardDaemon {
                 };
 
                 info!(
+                    port = port, pid = conflict.new_process.pid, action = "conflict";
                     "⚠️  Port conflict detected on port {}: {} vs {}",
                     port, conflict.existing_process.name, conflict.new_process.name
                 );
@@ -214,9 +274,9 @@ impl PortGuardDaemon {
             
             let mut monitor_mut = self.process_monitor.lock().await;
             if let Err(e) = monitor_mut.restart_process_on_port(port).await {
-                warn!("Failed to auto-restart process on port {}: {}", port, e);
+                warn!(port = port, action = "restart_failed"; "Failed to auto-restart process on port {}: {}", port, e);
             } else {
-                info!("✅ Successfully auto-restarted process on port {}", port);
+                info!(port = port, action = "restarted"; "✅ Successfully auto-restarted process on port {}", port);
             }
         }
         
@@ -225,35 +285,61 @@ impl PortGuardDaemon {
 
     /// Resolve a port conflict
     async fn resolve_conflict(&self, mut conflict: PortConflict) -> Result<()> {
-        if !self.auto_resolve {
-            conflict.resolution = Some(PortResolution::NotifyUser);
-            info!(
-                "🔔 Port conflict on {} - manual resolution required",
-                conflict.port
-            );
-            return Ok(());
-        }
-
-        // Auto-resolve by killing the older process
-        let older_process = if self
+        // Resolved by killing the older process, when the port's policy says so
+        let (older_process, other_process) = if self
             .is_process_older(&conflict.existing_process, &conflict.new_process)
             .await
         {
-            &conflict.existing_process
+            (&conflict.existing_process, &conflict.new_process)
         } else {
-            &conflict.new_process
+            (&conflict.new_process, &conflict.existing_process)
         };
+        let reason = format!(
+            "also bound by {} (PID {})",
+            other_process.name, other_process.pid
+        );
+        match self
+            .handle_conflict(conflict.port, older_process, &reason)
+            .await
+        {
+            Some(PortResolution::KillExisting) => {}
+            resolution => {
+                conflict.resolution = resolution;
+                return Ok(());
+            }
+        }
 
         info!(
+            port = conflict.port, pid = older_process.pid, action = "resolve";
             "🔧 Auto-resolving port conflict on {} by killing process {} (PID: {})",
             conflict.port, older_process.name, older_process.pid
         );
 
         // Kill the older process
-        if let Err(e) = self.kill_process(older_process.pid).await {
-            warn!("Failed to kill process {}: {}", older_process.pid, e);
+        let result = self.kill_process(older_process.pid).await;
+        crate::notify::kill_result(
+            older_process.pid,
+            Some(conflict.port),
+            Some(&older_process.name),
+            &result,
+        );
+        crate::audit_log::record_resolution(
+            conflict.port,
+            older_process.pid,
+            &older_process.name,
+            PortResolution::KillExisting,
+            &result,
+        );
+        if let Err(e) = result {
+            warn!(
+                port = conflict.port, pid = older_process.pid, action = "kill_failed";
+                "Failed to kill process {}: {}", older_process.pid, e
+            );
             return Err(e);
         }
+        record_guard_kill(older_process);
+
+        self.kill_limiter.lock().await.record(conflict.port);
 
         // Update conflict resolution
         conflict.resolution = Some(PortResolution::KillExisting);
@@ -264,7 +350,7 @@ impl PortGuardDaemon {
             *count += 1;
         }
 
-        info!("✅ Port conflict resolved on port {}", conflict.port);
+        info!(port = conflict.port, action = "resolved"; "✅ Port conflict resolved on port {}", conflict.port);
         Ok(())
     }
 
@@ -276,15 +362,16 @@ impl PortGuardDaemon {
         process_name: String,
     ) -> Result<()> {
         let project_name_clone = project_name.clone();
-        let reservation = PortReservation {
+        let reservation = crate::reservations::new_reservation(
             port,
             project_name,
             process_name,
-            reserved_at: Utc::now(),
-            expires_at: Some(Utc::now() + Duration::hours(24)), // 24 hour reservation
-            auto_renew: true,
-        };
+            Some(Duration::hours(crate::reservations::DEFAULT_TERM_HOURS)),
+            true,
+        );
 
+        // Keep what was reserved since the file was read
+        self.sync_reservations().await?;
         {
             let mut reservations = self.reservations.lock().await;
             reservations.insert(port, reservation);
@@ -300,6 +387,7 @@ impl PortGuardDaemon {
 
     /// Release a port reservation
     pub async fn release_port(&self, port: u16) -> Result<()> {
+        self.sync_reservations().await?;
         {
             let mut reservations = self.reservations.lock().await;
             reservations.remove(&port);
@@ -313,78 +401,99 @@ impl PortGuardDaemon {
     /// Get current guard status
     pub async fn get_status(&self) -> GuardStatus {
         let reservations = self.reservations.lock().await;
+        let shared = self.shared_reservations.lock().await;
+        let shared = shared
+            .reservations
+            .values()
+            .filter(|reservation| !reservations.contains_key(&reservation.port));
         let conflicts_resolved = *self.conflicts_resolved.lock().await;
         let is_running = *self.is_running.lock().await;
 
         GuardStatus {
             is_active: is_running,
             watched_ports: self.watched_ports.clone(),
-            active_reservations: reservations.values().cloned().collect(),
+            active_reservations: reservations.values().chain(shared).cloned().collect(),
             conflicts_resolved,
             last_activity: Some(Utc::now()),
-            auto_resolve_enabled: self.auto_resolve,
+            auto_resolve_enabled: self.default_policy == PortResolution::KillExisting,
+            rate_limited: self.kill_limiter.lock().await.is_tripped(),
         }
     }
 
     /// Load reservations from file
     async fn load_reservations(&self) -> Result<()> {
-        if !Path::new(&self.reservation_file).exists() {
-            return Ok(());
-        }
-
-        let content = fs::read_to_string(&self.reservation_file)?;
-        let reservations: HashMap<u16, PortReservation> = serde_json::from_str(&content)?;
-
-        {
-            let mut current_reservations = self.reservations.lock().await;
-            *current_reservations = reservations;
-        }
+        let path = Path::new(&self.reservation_file);
+        let reservations = crate::reservations::load(path)?;
+        let count = reservations.len();
+        *self.reservations.lock().await = reservations;
+        *self.reservations_modified.lock().await = modified_time(path);
 
-        info!(
-            "📂 Loaded {} port reservations",
-            self.reservations.lock().await.len()
-        );
+        info!("📂 Loaded {} port reservations", count);
         Ok(())
     }
 
     /// Save reservations to file
     async fn save_reservations(&self) -> Result<()> {
-        // Create directory if it doesn't exist
-        if let Some(parent) = Path::new(&self.reservation_file).parent() {
-            fs::create_dir_all(parent)?;
-        }
-
+        let path = Path::new(&self.reservation_file);
         let reservations = self.reservations.lock().await;
-        let content = serde_json::to_string_pretty(&*reservations)?;
-        fs::write(&self.reservation_file, content)?;
-
+        crate::reservations::save(path, &reservations)?;
+        *self.reservations_modified.lock().await = modified_time(path);
         Ok(())
     }
 
-    /// Clean up expired reservations
-    async fn cleanup_expired_reservations(&self) -> Result<()> {
-        let now = Utc::now();
-        let mut reservations = self.reservations.lock().await;
-        let mut to_remove = Vec::new();
-
-        for (port, reservation) in reservations.iter() {
-            if let Some(expires_at) = reservation.expires_at {
-                if now > expires_at {
-                    to_remove.push(*port);
-                }
-            }
+    /// Pick up reservations added or removed with `--reserve`/`--unreserve` since the file
+    /// was last read, then drop the expired ones and renew the auto-renewing ones
+    async fn sync_reservations(&self) -> Result<()> {
+        let modified = modified_time(Path::new(&self.reservation_file));
+        if modified != *self.reservations_modified.lock().await {
+            self.load_reservations().await?;
         }
 
-        for port in to_remove {
-            reservations.remove(&port);
-            info!("🧹 Cleaned up expired reservation for port {}", port);
+        let lifecycle = {
+            let mut reservations = self.reservations.lock().await;
+            crate::reservations::expire(&mut reservations, Utc::now())
+        };
+        if lifecycle.is_empty() {
+            return Ok(());
+        }
+        for reservation in &lifecycle.expired {
+            info!(
+                port = reservation.port, action = "reservation_expired";
+                "⌛ Reservation of port {} for '{}' expired",
+                reservation.port, reservation.project_name
+            );
+        }
+        for port in &lifecycle.renewed {
+            info!(port = *port, action = "reservation_renewed"; "🔁 Renewed the reservation of port {}", port);
         }
+        self.save_reservations().await
+    }
 
+    /// Pick up the shared reservations file of the current directory's repository when it
+    /// appears, changes or goes away
+    async fn sync_shared_reservations(&self) -> Result<()> {
+        let cwd = std::env::current_dir()?;
+        let mut shared = self.shared_reservations.lock().await;
+        if !shared.refresh(&cwd)? {
+            return Ok(());
+        }
+        match &shared.path {
+            Some(path) => info!(
+                "🤝 Loaded {} shared port reservations from {}",
+                shared.reservations.len(),
+                path.display()
+            ),
+            None => info!("🤝 No shared port reservations"),
+        }
         Ok(())
     }
 
     /// Kill a process by PID
     async fn kill_process(&self, pid: i32) -> Result<()> {
+        if crate::demo::is_enabled() {
+            return crate::demo::kill(pid);
+        }
+
         #[cfg(target_os = "macos")]
         {
             use std::process::Command;
@@ -466,19 +575,45 @@ impl PortGuardDaemon {
                     if !is_port_available(port).await {
                         info!("⚠️  Port {} is busy, attempting to resolve conflict", port);
 
-                        if self.auto_resolve {
-                            // Try to kill the conflicting process
-                            if let Err(e) = self.resolve_port_conflict(port).await {
-                                warn!("Failed to resolve port conflict: {}", e);
-                                return Err(e);
+                        match self.policy_for_port(port).await {
+                            PortResolution::KillExisting => {
+                                // Try to kill the conflicting process
+                                if let Err(e) = self.resolve_port_conflict(port).await {
+                                    warn!("Failed to resolve port conflict: {}", e);
+                                    return Err(e);
+                                }
+
+                                if !self.simulate {
+                                    info!(
+                                        "✅ Port {} conflict resolved, command can proceed",
+                                        port
+                                    );
+                                }
+                            }
+                            PortResolution::BlockNewProcess => {
+                                warn!(port = port, action = "block"; "⛔ Blocked '{}': port {} is busy", command, port);
+                                return Err(anyhow::anyhow!(
+                                    "Port {} is busy; the guard blocks new processes on it",
+                                    port
+                                ));
+                            }
+                            PortResolution::ReassignPort => {
+                                let free = find_available_port(port.saturating_add(1), 100).await;
+                                return Err(match free {
+                                    Some(free) => anyhow::anyhow!(
+                                        "Port {} is busy; use port {} instead",
+                                        port,
+                                        free
+                                    ),
+                                    None => anyhow::anyhow!("Port {} is busy", port),
+                                });
+                            }
+                            PortResolution::NotifyUser => {
+                                return Err(anyhow::anyhow!(
+                                    "Port {} is busy and auto-resolve is disabled",
+                                    port
+                                ));
                             }
-
-                            info!("✅ Port {} conflict resolved, command can proceed", port);
-                        } else {
-                            return Err(anyhow::anyhow!(
-                                "Port {} is busy and auto-resolve is disabled",
-                                port
-                            ));
                         }
                     } else {
                         info!("✅ Port {} is available, command can proceed", port);
@@ -584,12 +719,28 @@ impl PortGuardDaemon {
                     .unwrap_or(conflicting_processes[0]),
                 None => conflicting_processes[0],
             };
+            if self.simulate {
+                info!(
+                    "🧪 [simulate] Would kill conflicting process {} (PID: {}) on port {}",
+                    process_to_kill.name, process_to_kill.pid, port
+                );
+                return Ok(());
+            }
+            if !self.allow_automated_kill(port).await {
+                return Err(anyhow::anyhow!(
+                    "Automated kills on port {} are paused by the rate limiter",
+                    port
+                ));
+            }
+
             info!(
                 "🔧 Killing conflicting process {} (PID: {}) on port {}",
                 process_to_kill.name, process_to_kill.pid, port
             );
 
             self.kill_process(process_to_kill.pid).await?;
+            self.kill_limiter.lock().await.record(port);
+            record_guard_kill(process_to_kill);
 
             // Wait a moment for the process to die
             sleep(TokioDuration::from_millis(500)).await;
@@ -649,56 +800,218 @@ impl PortGuardDaemon {
 
             for process in processes {
                 if process.name != allowed_name {
-                    disallowed_processes.push((*port, process.pid, process.name.clone()));
+                    disallowed_processes.push((*port, process));
                 }
             }
         }
 
-        if disallowed_processes.is_empty() {
-            return Ok(());
-        }
-
-        for (port, pid, name) in disallowed_processes {
-            if !self.auto_resolve {
-                info!(
-                    "🔔 Unauthorized process '{}' on port {} - manual resolution required",
-                    name, port
-                );
+        for (port, process) in disallowed_processes {
+            let (pid, name) = (process.pid, process.name.as_str());
+            let reason = match self.reserved_for(port).await {
+                Some(holder) => format!("the port is reserved for {}", holder),
+                None => "not the process allowed on this port".to_string(),
+            };
+            if self.handle_conflict(port, process, &reason).await
+                != Some(PortResolution::KillExisting)
+            {
                 continue;
             }
 
             info!(
-                "🚨 Unauthorized process '{}' (PID: {}) on port {} - KILLING",
-                name, pid, port
+                port = port, pid = pid, action = "kill_unauthorized";
+                "🚨 Unauthorized process '{}' (PID: {}) on port {} ({}) - KILLING",
+                name, pid, port, reason
             );
 
-            if let Err(e) = self.kill_process(pid).await {
+            let result = self.kill_process(pid).await;
+            crate::notify::kill_result(pid, Some(port), Some(name), &result);
+            crate::audit_log::record_resolution(
+                port,
+                pid,
+                name,
+                PortResolution::KillExisting,
+                &result,
+            );
+            if let Err(e) = result {
                 warn!("Failed to kill unauthorized process {}: {}", pid, e);
+            } else {
+                self.kill_limiter.lock().await.record(port);
+                record_guard_kill(process);
             }
         }
 
         Ok(())
     }
 
+    /// The conflict policy of `port`: its reservation's (the reservation file's, then the
+    /// shared one's), or the default
+    async fn policy_for_port(&self, port: u16) -> PortResolution {
+        let reservations = self.reservations.lock().await;
+        let shared = self.shared_reservations.lock().await;
+        reservations
+            .get(&port)
+            .or_else(|| shared.reservations.get(&port))
+            .and_then(|reservation| reservation.on_conflict)
+            .unwrap_or(self.default_policy)
+    }
+
+    /// Deal with `process` being in the way on `port` as the port's policy says, recording
+    /// the resolution in the audit log. Kills are left to the caller: this returns
+    /// `KillExisting` when `process` is to be killed now. `None` when there is nothing more
+    /// to do, because this conflict was already resolved or the guard only simulates.
+    async fn handle_conflict(
+        &self,
+        port: u16,
+        process: &ProcessInfo,
+        reason: &str,
+    ) -> Option<PortResolution> {
+        let mut policy = self.policy_for_port(port).await;
+        if self.simulate {
+            info!(
+                "🧪 [simulate] Conflict on port {}: {} (PID: {}), {}: would pick {:?}",
+                port, process.name, process.pid, reason, policy
+            );
+            return None;
+        }
+        if policy == PortResolution::KillExisting {
+            if self.allow_automated_kill(port).await {
+                return Some(policy);
+            }
+            policy = PortResolution::NotifyUser;
+        }
+
+        let key = format!("{}:{}", port, process.pid);
+        {
+            let mut resolved = self.resolved_conflicts.lock().await;
+            if resolved.contains(&key) {
+                return None;
+            }
+            resolved.insert(key);
+        }
+
+        let (pid, name) = (process.pid, process.name.as_str());
+        match policy {
+            PortResolution::ReassignPort => {
+                let detail = match find_available_port(port.saturating_add(1), 100).await {
+                    Some(free) => format!("{}; move it to port {}", reason, free),
+                    None => format!("{}; no free port found nearby", reason),
+                };
+                crate::notify::send(crate::notify::Message::conflict(port, pid, name, &detail));
+                info!(
+                    port = port, pid = pid, action = "reassign";
+                    "🔀 '{}' (PID: {}) on port {}: {}",
+                    name, pid, port, detail
+                );
+            }
+            PortResolution::BlockNewProcess => {
+                warn!(
+                    port = port, pid = pid, action = "block";
+                    "⛔ '{}' (PID: {}) on port {} ({}) - blocked, logged only",
+                    name, pid, port, reason
+                );
+            }
+            _ => {
+                crate::notify::send(crate::notify::Message::conflict(port, pid, name, reason));
+                info!(
+                    port = port, pid = pid, action = "notify";
+                    "🔔 '{}' (PID: {}) on port {} ({}) - manual resolution required",
+                    name, pid, port, reason
+                );
+            }
+        }
+        crate::audit_log::record_resolution(port, pid, name, policy, &Ok(()));
+        Some(policy)
+    }
+
+    /// Ask the rate limiter whether an automated kill on `port` may proceed.
+    /// Logs why not when it is held back.
+    async fn allow_automated_kill(&self, port: u16) -> bool {
+        if self.no_kill_if_active {
+            if let Some(active) = crate::connections::active_connections_on(port) {
+                info!(
+                    "🔗 Port {} has {} - not killing (--no-kill-if-active)",
+                    port,
+                    active.summary()
+                );
+                return false;
+            }
+        }
+
+        let mut limiter = self.kill_limiter.lock().await;
+        let was_tripped = limiter.is_tripped();
+        match limiter.check(port) {
+            KillDecision::Allow => {
+                if was_tripped {
+                    info!("🚦 Automated kill rate back under the limit, resuming auto-resolution");
+                }
+                true
+            }
+            KillDecision::PortCooldown(remaining) => {
+                info!(
+                    "⏳ Port {} was auto-killed recently ({}s cooldown left) - not killing again",
+                    port,
+                    remaining.as_secs()
+                );
+                false
+            }
+            KillDecision::RateLimited => {
+                if !was_tripped {
+                    warn!(
+                        "🚦 Automated kill limit reached ({}) - switching to notify-only. Something may be restarting processes on port {}",
+                        limiter.describe(),
+                        port
+                    );
+                }
+                false
+            }
+        }
+    }
+
     /// Returns the effective set of watched ports: the initial list merged with all reserved ports.
     async fn effective_watched_ports(&self) -> HashSet<u16> {
         let mut ports: HashSet<u16> = self.watched_ports.iter().copied().collect();
         let reservations = self.reservations.lock().await;
         ports.extend(reservations.keys());
+        ports.extend(self.shared_reservations.lock().await.reservations.keys());
         ports
     }
 
-    /// Returns the allowed process name for a specific port, checking reservations first,
-    /// then falling back to the global allowed_process_name.
+    /// Returns the allowed process name for a specific port, checking reservations first
+    /// (the reservation file's, then the shared ones), then falling back to the global
+    /// allowed_process_name.
     async fn allowed_name_for_port(&self, port: u16) -> Option<String> {
         let reservations = self.reservations.lock().await;
-        if let Some(reservation) = reservations.get(&port) {
+        let shared = self.shared_reservations.lock().await;
+        if let Some(reservation) = reservations
+            .get(&port)
+            .or_else(|| shared.reservations.get(&port))
+        {
             Some(reservation.process_name.clone())
         } else {
             self.allowed_process_name.clone()
         }
     }
 
+    /// Who `port` is reserved for, for conflict reports: "'web' (node)", with the shared
+    /// reservations file when the reservation comes from one
+    async fn reserved_for(&self, port: u16) -> Option<String> {
+        if let Some(reservation) = self.reservations.lock().await.get(&port) {
+            return Some(format!(
+                "'{}' ({})",
+                reservation.project_name, reservation.process_name
+            ));
+        }
+        let shared = self.shared_reservations.lock().await;
+        let reservation = shared.reservations.get(&port)?;
+        let file = shared.path.as_deref().unwrap_or(Path::new(SHARED_FILE));
+        Some(format!(
+            "service '{}' ({}) by {}",
+            reservation.project_name,
+            reservation.process_name,
+            file.display()
+        ))
+    }
+
     /// Get intercepted commands count
     pub async fn get_intercepted_commands_count(&self) -> usize {
         let commands = self.intercepted_commands.lock().await;
@@ -711,6 +1024,20 @@ impl PortGuardDaemon {
     }
 }
 
+/// Add an automated kill to the kill history, attributed to the guard
+fn record_guard_kill(process: &ProcessInfo) {
+    let entry = crate::types::ProcessHistoryEntry::new(process, "auto".to_string())
+        .initiated_by(crate::audit_log::AuditInitiator::Guard);
+    crate::history_store::record(&entry);
+}
+
+/// When `path` was last written, if it exists
+fn modified_time(path: &Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
 /// Check if a port is available for binding
 pub async fn is_port_available(port: u16) -> bool {
     use std::net::Ipv4Addr;
@@ -732,3 +1059,145 @@ pub async fn find_available_port(start_port: u16, max_attempts: u16) -> Option<u
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(port: u16, pid: i32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        }
+    }
+
+    fn reservation(port: u16, on_conflict: Option<PortResolution>) -> PortReservation {
+        PortReservation {
+            on_conflict,
+            ..crate::reservations::new_reservation(port, "shop".into(), "node".into(), None, false)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conflict_policies() {
+        // Resolutions go to the audit log in the home directory, so this test runs itself
+        // again as a child process with a home of its own
+        let home = std::env::temp_dir().join(format!("port-kill-guard-{}", std::process::id()));
+        if std::env::var_os("PORT_KILL_TEST_CHILD").is_none() {
+            let _ = fs::remove_dir_all(&home);
+            fs::create_dir_all(&home).unwrap();
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "port_guard::tests::test_conflict_policies"])
+                .env("PORT_KILL_TEST_CHILD", "1")
+                .env("HOME", &home)
+                .stdout(std::process::Stdio::null())
+                .status()
+                .unwrap();
+            let _ = fs::remove_dir_all(&home);
+            assert!(status.success());
+            return;
+        }
+        let home = crate::uninstall::home_dir();
+
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let monitor = ProcessMonitor::new(sender, vec![], false, false).unwrap();
+        let reservation_file = home.join("reservations.json").to_string_lossy().to_string();
+        let mut guard = PortGuardDaemon::new(
+            vec![],
+            reservation_file,
+            false,
+            Arc::new(Mutex::new(monitor)),
+        );
+        guard.reservations.lock().await.extend([
+            (
+                3000,
+                reservation(3000, Some(PortResolution::BlockNewProcess)),
+            ),
+            (3001, reservation(3001, None)),
+        ]);
+        {
+            let mut shared = guard.shared_reservations.lock().await;
+            // The reservation file's reservation of a port wins over the shared one
+            shared
+                .reservations
+                .insert(3000, reservation(3000, Some(PortResolution::KillExisting)));
+            shared
+                .reservations
+                .insert(3002, reservation(3002, Some(PortResolution::ReassignPort)));
+        }
+
+        assert_eq!(
+            guard.policy_for_port(3000).await,
+            PortResolution::BlockNewProcess
+        );
+        assert_eq!(
+            guard.policy_for_port(3001).await,
+            PortResolution::NotifyUser
+        );
+        assert_eq!(
+            guard.policy_for_port(3002).await,
+            PortResolution::ReassignPort
+        );
+        assert_eq!(
+            guard.policy_for_port(4000).await,
+            PortResolution::NotifyUser
+        );
+
+        // Each conflict is resolved once; kills are left to the caller
+        let intruder = process(3000, 4242, "python3");
+        assert_eq!(
+            guard.handle_conflict(3000, &intruder, "reserved").await,
+            Some(PortResolution::BlockNewProcess)
+        );
+        assert_eq!(
+            guard.handle_conflict(3000, &intruder, "reserved").await,
+            None
+        );
+        guard.set_default_policy(PortResolution::KillExisting);
+        assert_eq!(
+            guard
+                .handle_conflict(4000, &process(4000, 4343, "ruby"), "reserved")
+                .await,
+            Some(PortResolution::KillExisting)
+        );
+        guard.set_simulate(true);
+        assert_eq!(
+            guard.handle_conflict(3001, &intruder, "reserved").await,
+            None
+        );
+
+        // One record, with the resolution spelled as before it had a short name
+        let log = fs::read_to_string(crate::audit_log::log_file_path()).unwrap();
+        let records: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["action"], "resolve");
+        assert_eq!(records[0]["resolution"], "BlockNewProcess");
+        assert_eq!(records[0]["pid"], 4242);
+        // Config files may still use the short names
+        assert_eq!(
+            serde_json::from_str::<PortResolution>("\"kill\"").unwrap(),
+            PortResolution::KillExisting
+        );
+    }
+}
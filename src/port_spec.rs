@@ -0,0 +1,146 @@
+//! Port specs, as `--ports` and preset `ports` take them: single ports (`3000`), ranges
+//! (`3000-3010`), trailing wildcards (`30**` is 3000-3099) and exclusions (`!5353`,
+//! `!3005-3007`) that take ports back out of the others. Presets store the specs as written,
+//! so the `full` preset is one `"2000-8000"` rather than 6001 numbers, and [`expand`] turns
+//! them into ports when they are used.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// One entry of a preset's `ports`. Plain ports are written as numbers, so presets without
+/// ranges stay readable by releases from before specs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PortSpec {
+    Port(u16),
+    Spec(String),
+}
+
+impl From<u16> for PortSpec {
+    fn from(port: u16) -> Self {
+        PortSpec::Port(port)
+    }
+}
+
+impl From<&str> for PortSpec {
+    fn from(spec: &str) -> Self {
+        match spec.trim().parse() {
+            Ok(port) => PortSpec::Port(port),
+            Err(_) => PortSpec::Spec(spec.trim().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for PortSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortSpec::Port(port) => write!(f, "{}", port),
+            PortSpec::Spec(spec) => f.write_str(spec),
+        }
+    }
+}
+
+/// The ports one spec covers and whether it is an exclusion; `None` when it isn't valid
+pub fn parse(spec: &str) -> Option<(bool, RangeInclusive<u16>)> {
+    let spec = spec.trim();
+    let (exclude, spec) = match spec.strip_prefix('!') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, spec),
+    };
+    let range = if spec == "*" {
+        1..=u16::MAX
+    } else if let Some((start, end)) = spec.split_once('-') {
+        start.trim().parse().ok()?..=end.trim().parse().ok()?
+    } else if spec.ends_with('*') {
+        let digits = spec.trim_end_matches('*');
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let low: u32 = spec.replace('*', "0").parse().ok()?;
+        let high: u32 = spec.replace('*', "9").parse().ok()?;
+        u16::try_from(low).ok()?..=u16::try_from(high).unwrap_or(u16::MAX)
+    } else {
+        let port = spec.parse().ok()?;
+        port..=port
+    };
+    (range.start() <= range.end()).then_some((exclude, range))
+}
+
+/// Whether `spec` takes ports out rather than adding them
+pub fn is_exclusion(spec: &str) -> bool {
+    spec.trim().starts_with('!')
+}
+
+/// The ports `specs` add, in order and without repeats, minus those they exclude. Invalid
+/// specs are skipped; `Args::validate` reports them for `--ports`.
+pub fn expand<T: ToString>(specs: &[T]) -> Vec<u16> {
+    let mut included = Vec::new();
+    let mut excluded = HashSet::new();
+    for spec in specs {
+        match parse(&spec.to_string()) {
+            Some((true, range)) => excluded.extend(range),
+            Some((false, range)) => included.extend(range),
+            None => {}
+        }
+    }
+    let mut seen = HashSet::new();
+    included.retain(|port| !excluded.contains(port) && seen.insert(*port));
+    included
+}
+
+/// `ports` sorted, with runs written as ranges: `3000-3005, 8080`
+pub fn describe(ports: &[u16]) -> String {
+    let mut sorted = ports.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut runs: Vec<(u16, u16)> = Vec::new();
+    for port in sorted {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == port => *end = port,
+            _ => runs.push((port, port)),
+        }
+    }
+    runs.iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_specs() {
+        assert_eq!(
+            expand(&["3000", "3002-3004", "3000"]),
+            vec![3000, 3002, 3003, 3004]
+        );
+        assert_eq!(expand(&["3000-3010", "!3001-3009"]), vec![3000, 3010]);
+        assert_eq!(expand(&["808*"]).len(), 10);
+        assert_eq!(parse("30**"), Some((false, 3000..=3099)));
+        assert_eq!(parse("6****"), Some((false, 60000..=65535)));
+        assert_eq!(parse("!5353"), Some((true, 5353..=5353)));
+        assert_eq!(expand(&["*", "!2-65535"]), vec![1]);
+        for invalid in ["3010-3000", "3*0", "**", "70000", "abc"] {
+            assert_eq!(parse(invalid), None, "{}", invalid);
+        }
+
+        assert_eq!(describe(&[8080, 3001, 3000, 3002, 3000]), "3000-3002, 8080");
+
+        let specs = vec![PortSpec::from(3000), PortSpec::from("2000-2001")];
+        assert_eq!(expand(&specs), vec![3000, 2000, 2001]);
+        assert_eq!(
+            serde_json::to_string(&specs).unwrap(),
+            r#"[3000,"2000-2001"]"#
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! `--wait-free` / `--wait-open`: block until ports are released or accepting connections,
+//! for "kill, then wait until the port is really gone" scripts and readiness checks.
+
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitCondition {
+    /// Nothing is listening and the port can be bound
+    Free,
+    /// Something accepts connections on localhost
+    Open,
+}
+
+impl WaitCondition {
+    pub fn describe(self) -> &'static str {
+        match self {
+            WaitCondition::Free => "free",
+            WaitCondition::Open => "open",
+        }
+    }
+
+    pub fn is_met(self, port: u16) -> bool {
+        match self {
+            WaitCondition::Free => is_free(port),
+            WaitCondition::Open => is_open(port),
+        }
+    }
+}
+
+/// True if a local client can connect, over IPv4 or IPv6 (Node binds `::1` by default)
+pub fn is_open(port: u16) -> bool {
+    let timeout = Duration::from_millis(250);
+    [
+        SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+        SocketAddr::from((Ipv6Addr::LOCALHOST, port)),
+    ]
+    .iter()
+    .any(|addr| TcpStream::connect_timeout(addr, timeout).is_ok())
+}
+
+/// True if a server could bind the port on all interfaces right now. Where binding
+/// isn't allowed (privileged ports) fall back to "nobody answers".
+pub fn is_free(port: u16) -> bool {
+    for addr in [
+        SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)),
+        SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)),
+    ] {
+        match TcpListener::bind(addr) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::AddrInUse => return false,
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => return !is_open(port),
+            // e.g. IPv6 disabled
+            Err(_) => {}
+        }
+    }
+    true
+}
+
+/// Poll until every port meets `condition`. Returns the ports still waiting when
+/// `timeout` ran out (empty on success).
+pub async fn wait_for_ports(
+    ports: &[u16],
+    condition: WaitCondition,
+    timeout: Option<Duration>,
+) -> Vec<u16> {
+    let started = Instant::now();
+    let mut pending: Vec<u16> = ports.to_vec();
+    loop {
+        pending.retain(|&port| !condition.is_met(port));
+        if pending.is_empty() {
+            return pending;
+        }
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            return pending;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_ports_tracks_a_listener() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert!(is_open(port));
+        assert!(!is_free(port));
+        assert!(wait_for_ports(&[port], WaitCondition::Open, None)
+            .await
+            .is_empty());
+        assert_eq!(
+            wait_for_ports(&[port], WaitCondition::Free, Some(Duration::ZERO)).await,
+            vec![port]
+        );
+
+        drop(listener);
+        assert!(
+            wait_for_ports(&[port], WaitCondition::Free, Some(Duration::from_secs(5)))
+                .await
+                .is_empty()
+        );
+    }
+}
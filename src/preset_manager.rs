@@ -1,5 +1,8 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use crate::port_spec::PortSpec;
+use crate::state_format::{decode_record, split_list_field, StateMeta, Versioned};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
@@ -11,8 +14,10 @@ pub struct PortPreset {
     pub name: String,
     /// Description of what this preset is for
     pub description: String,
-    /// List of ports to monitor
-    pub ports: Vec<u16>,
+    /// Ports to monitor: single ports, ranges, wildcards and exclusions (see
+    /// [`crate::port_spec`]), expanded when the preset is used
+    #[serde(default)]
+    pub ports: Vec<PortSpec>,
     /// Ports to ignore (comma-separated)
     pub ignore_ports: Option<Vec<u16>>,
     /// Process names to ignore (comma-separated)
@@ -24,24 +29,85 @@ pub struct PortPreset {
     /// Only show processes from specific groups
     pub only_groups: Option<Vec<String>>,
     /// Enable smart filtering
+    #[serde(default)]
     pub smart_filter: bool,
     /// Enable Docker container monitoring
+    #[serde(default)]
     pub docker: bool,
     /// Show process IDs
+    #[serde(default)]
     pub show_pid: bool,
     /// Enable performance metrics
+    #[serde(default)]
     pub performance: bool,
     /// Show project context
+    #[serde(default)]
     pub show_context: bool,
+    /// Presets whose ports, ignore lists and flags this one adds to, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<String>,
+    /// Where an imported preset came from: the file or URL and the day it was imported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<String>,
+    /// Schema version and fields from newer releases
+    #[serde(flatten)]
+    pub meta: StateMeta,
+}
+
+impl Versioned for PortPreset {
+    const KIND: &'static str = "preset";
+
+    fn upgrade(record: &mut Map<String, Value>, from: u32) {
+        if from == 0 {
+            split_list_field(record, "ignore_ports", true);
+            for field in ["ignore_processes", "ignore_patterns", "ignore_groups", "only_groups"] {
+                split_list_field(record, field, false);
+            }
+        }
+    }
 }
 
 impl PortPreset {
+    /// The ports the preset's specs come to
+    pub fn port_numbers(&self) -> Vec<u16> {
+        crate::port_spec::expand(&self.ports)
+    }
+
+    /// Add `other`'s ports, ignore lists and flags to this preset's
+    fn merge_from(&mut self, other: &PortPreset) {
+        fn combine<T: Clone + PartialEq>(list: &mut Option<Vec<T>>, other: &Option<Vec<T>>) {
+            if let Some(other) = other {
+                let list = list.get_or_insert_with(Vec::new);
+                for item in other {
+                    if !list.contains(item) {
+                        list.push(item.clone());
+                    }
+                }
+            }
+        }
+        for spec in &other.ports {
+            if !self.ports.contains(spec) {
+                self.ports.push(spec.clone());
+            }
+        }
+        combine(&mut self.ignore_ports, &other.ignore_ports);
+        combine(&mut self.ignore_processes, &other.ignore_processes);
+        combine(&mut self.ignore_patterns, &other.ignore_patterns);
+        combine(&mut self.ignore_groups, &other.ignore_groups);
+        combine(&mut self.only_groups, &other.only_groups);
+        self.smart_filter |= other.smart_filter;
+        self.docker |= other.docker;
+        self.show_pid |= other.show_pid;
+        self.performance |= other.performance;
+        self.show_context |= other.show_context;
+    }
+
     /// Create a new preset
     pub fn new(name: String, description: String, ports: Vec<u16>) -> Self {
         Self {
             name,
             description,
-            ports,
+            ports: ports.into_iter().map(PortSpec::from).collect(),
             ignore_ports: None,
             ignore_processes: None,
             ignore_patterns: None,
@@ -52,6 +118,9 @@ impl PortPreset {
             show_pid: false,
             performance: false,
             show_context: false,
+            extends: Vec::new(),
+            provenance: None,
+            meta: StateMeta::default(),
         }
     }
 
@@ -68,7 +137,7 @@ impl PortPreset {
         Self {
             name,
             description,
-            ports,
+            ports: ports.into_iter().map(PortSpec::from).collect(),
             ignore_ports,
             ignore_processes,
             ignore_patterns,
@@ -79,6 +148,9 @@ impl PortPreset {
             show_pid: false,
             performance: false,
             show_context: false,
+            extends: Vec::new(),
+            provenance: None,
+            meta: StateMeta::default(),
         }
     }
 
@@ -92,7 +164,7 @@ impl PortPreset {
         Self {
             name,
             description,
-            ports,
+            ports: ports.into_iter().map(PortSpec::from).collect(),
             ignore_ports: None,
             ignore_processes: None,
             ignore_patterns: None,
@@ -103,6 +175,9 @@ impl PortPreset {
             show_pid: false,
             performance: false,
             show_context: false,
+            extends: Vec::new(),
+            provenance: None,
+            meta: StateMeta::default(),
         }
     }
 }
@@ -138,7 +213,8 @@ impl PresetManager {
         // Then try to load user presets from file
         if Path::new(&self.config_path).exists() {
             let content = fs::read_to_string(&self.config_path)?;
-            let user_presets: HashMap<String, PortPreset> = serde_json::from_str(&content)?;
+            let user_presets: HashMap<String, PortPreset> =
+                crate::state_format::decode_map(&content)?;
 
             // Merge user presets (they override defaults)
             for (name, preset) in user_presets {
@@ -259,12 +335,13 @@ impl PresetManager {
         add_default("python", python_preset);
 
         // Full range preset - comprehensive monitoring
-        let full_preset = PortPreset::with_smart_filter(
+        let mut full_preset = PortPreset::with_smart_filter(
             "full".to_string(),
             "Comprehensive port monitoring (2000-8000 with smart filtering)".to_string(),
-            (2000..=8000).collect(),
+            Vec::new(),
             true, // Enable smart filtering
         );
+        full_preset.ports = vec![PortSpec::from("2000-8000")];
         add_default("full", full_preset);
 
         // Minimal preset - just the essentials
@@ -290,6 +367,7 @@ impl PresetManager {
             && left.show_pid == right.show_pid
             && left.performance == right.performance
             && left.show_context == right.show_context
+            && left.extends == right.extends
     }
 
     /// Get a preset by name
@@ -297,6 +375,44 @@ impl PresetManager {
         self.presets.get(name)
     }
 
+    /// A preset with everything it `extends` merged in: ports and ignore lists are combined,
+    /// parents first, and a flag is on when any of them turns it on. Parents are resolved
+    /// when asked for, so one that changes (a built-in after an upgrade, say) carries over.
+    pub fn resolve_preset(&self, name: &str) -> Result<PortPreset> {
+        self.resolve_with(name, &mut Vec::new())
+    }
+
+    fn resolve_with(&self, name: &str, chain: &mut Vec<String>) -> Result<PortPreset> {
+        if chain.iter().any(|seen| seen == name) {
+            chain.push(name.to_string());
+            bail!("Preset inheritance loops: {}", chain.join(" -> "));
+        }
+        let preset = match self.presets.get(name) {
+            Some(preset) => preset,
+            None => match chain.last() {
+                Some(child) => bail!("Preset '{}' extends unknown preset '{}'", child, name),
+                None => bail!("Preset '{}' not found", name),
+            },
+        };
+        if preset.extends.is_empty() {
+            return Ok(preset.clone());
+        }
+
+        chain.push(name.to_string());
+        let mut resolved =
+            PortPreset::new(preset.name.clone(), preset.description.clone(), Vec::new());
+        for parent in &preset.extends {
+            let parent = self.resolve_with(parent, chain)?;
+            resolved.merge_from(&parent);
+        }
+        chain.pop();
+        resolved.merge_from(preset);
+        resolved.extends = preset.extends.clone();
+        resolved.provenance = preset.provenance.clone();
+        resolved.meta = preset.meta.clone();
+        Ok(resolved)
+    }
+
     /// Get all preset names
     pub fn get_preset_names(&self) -> Vec<String> {
         self.presets.keys().cloned().collect()
@@ -324,7 +440,11 @@ impl PresetManager {
 
         for name in preset_names {
             if let Some(preset) = self.presets.get(name) {
+                let preset = &self.resolve_preset(name).unwrap_or_else(|_| preset.clone());
                 output.push_str(&format!("• {}: {}\n", name, preset.description));
+                if !preset.extends.is_empty() {
+                    output.push_str(&format!("  Extends: {}\n", preset.extends.join(", ")));
+                }
                 output.push_str(&format!(
                     "  Ports: {}\n",
                     preset
@@ -361,6 +481,10 @@ impl PresetManager {
                     output.push_str("  Smart filtering: enabled\n");
                 }
 
+                if let Some(ref provenance) = preset.provenance {
+                    output.push_str(&format!("  Imported from: {}\n", provenance));
+                }
+
                 output.push('\n');
             }
         }
@@ -372,6 +496,351 @@ impl PresetManager {
     pub fn get_config_path(&self) -> &str {
         &self.config_path
     }
+
+    /// Replace preset `name` with the JSON `content` (`--edit-preset`). The name in it may
+    /// differ, which renames the preset. The ports must be valid specs that come to at least
+    /// one port and everything it extends must exist, else nothing changes. Returns the
+    /// saved name, or `None` when the content matches the preset as it was.
+    pub fn replace_preset(&mut self, name: &str, content: &str) -> Result<Option<String>> {
+        let edited: PortPreset = decode_record(serde_json::from_str(content)?)?;
+        if self
+            .presets
+            .get(name)
+            .is_some_and(|current| Self::presets_match(current, &edited))
+        {
+            return Ok(None);
+        }
+        for spec in &edited.ports {
+            match crate::port_spec::parse(&spec.to_string()) {
+                Some((false, range)) if *range.start() == 0 => bail!("Port 0 is not valid"),
+                Some(_) => {}
+                None => bail!("Invalid port specification: '{}'", spec),
+            }
+        }
+        if edited.name != name && self.presets.contains_key(&edited.name) {
+            bail!(
+                "Can't rename to '{}': that preset already exists",
+                edited.name
+            );
+        }
+
+        let new_name = edited.name.clone();
+        let previous = self.presets.insert(new_name.clone(), edited);
+        let checked = self.resolve_preset(&new_name).and_then(|preset| {
+            if preset.port_numbers().is_empty() {
+                bail!("Preset '{}' has no ports to monitor", new_name);
+            }
+            Ok(())
+        });
+        if let Err(e) = checked {
+            match previous {
+                Some(previous) => self.presets.insert(new_name, previous),
+                None => self.presets.remove(&new_name),
+            };
+            return Err(e);
+        }
+        if new_name != name {
+            self.presets.remove(name);
+        }
+        Ok(Some(new_name))
+    }
+
+    /// `--export-preset`: the preset as JSON, or TOML when `file` ends in `.toml`, for
+    /// committing to a repo or a gist. The provenance stays behind; whoever imports the
+    /// file records their own.
+    pub fn export_preset(&self, name: &str, file: Option<&Path>) -> Result<String> {
+        let mut preset = self
+            .get_preset(name)
+            .with_context(|| format!("Preset '{}' not found", name))?
+            .clone();
+        preset.provenance = None;
+        if file.is_some_and(is_toml_path) {
+            Ok(toml::to_string(&preset)?)
+        } else {
+            Ok(serde_json::to_string_pretty(&preset)?)
+        }
+    }
+
+    /// `--import-preset`: add the presets in `content`, read from `source`. A name that is
+    /// taken by a different preset is an error unless `rename` (one preset only) gives it
+    /// another or `overwrite` is set; nothing is added when any name clashes. Returns the
+    /// imported presets with whether each replaced one, leaving out those already present.
+    pub fn import_presets(
+        &mut self,
+        content: &str,
+        source: &str,
+        rename: Option<&str>,
+        overwrite: bool,
+    ) -> Result<Vec<(PortPreset, bool)>> {
+        let mut presets = parse_presets(content, is_toml_path(Path::new(source)))
+            .with_context(|| format!("Invalid preset file {}", source))?;
+        if let Some(rename) = rename {
+            if presets.len() != 1 {
+                bail!(
+                    "{} has {} presets; --import-as renames a single one",
+                    source,
+                    presets.len()
+                );
+            }
+            presets[0].name = rename.to_string();
+        }
+
+        let mut imported = Vec::new();
+        let mut clashes = Vec::new();
+        for preset in presets {
+            match self.presets.get(&preset.name) {
+                Some(existing) if Self::presets_match(existing, &preset) => continue,
+                Some(_) if !overwrite => clashes.push(format!("'{}'", preset.name)),
+                existing => {
+                    let replaced = existing.is_some();
+                    imported.push((preset, replaced));
+                }
+            }
+        }
+        if !clashes.is_empty() {
+            bail!(
+                "Preset {} already exists; use --import-as <name> to import under another name, or --overwrite to replace it",
+                clashes.join(", ")
+            );
+        }
+
+        let provenance = format!("{} ({})", source, chrono::Local::now().format("%Y-%m-%d"));
+        for (preset, _) in &mut imported {
+            preset.provenance = Some(provenance.clone());
+            self.presets.insert(preset.name.clone(), preset.clone());
+        }
+        Ok(imported)
+    }
+}
+
+/// What `--preset` takes to pick a preset for the project being worked in
+pub const AUTO_PRESET: &str = "auto";
+
+/// Built-in presets for the kinds of project a file marks, first match per kind
+const PROJECT_MARKERS: [(&str, &str); 4] = [
+    ("package.json", "node"),
+    ("manage.py", "python"),
+    ("pyproject.toml", "python"),
+    ("requirements.txt", "python"),
+];
+
+/// `--preset auto`: a preset for the project at `root`, with the ports of the services
+/// [`crate::service_detector::ServiceDetector`] finds there (npm scripts, compose services,
+/// Django, Cargo binaries, ...), extending the built-ins its files point to: `node`, or
+/// `react` when package.json uses React, and `python`. `None` when nothing there looks
+/// like a project.
+pub fn detect_project_preset(root: &Path) -> Option<PortPreset> {
+    let services = crate::service_detector::ServiceDetector::with_paths(vec![root.to_path_buf()])
+        .discover_services()
+        .unwrap_or_default();
+    let mut ports: Vec<u16> = Vec::new();
+    for port in services.iter().filter_map(|service| service.inferred_port) {
+        if !ports.contains(&port) {
+            ports.push(port);
+        }
+    }
+
+    let mut extends: Vec<String> = Vec::new();
+    for (marker, preset) in PROJECT_MARKERS {
+        if !root.join(marker).exists() {
+            continue;
+        }
+        let preset = match preset {
+            "node" if uses_react(&root.join(marker)) => "react",
+            preset => preset,
+        };
+        if !extends.iter().any(|name| name == preset) {
+            extends.push(preset.to_string());
+        }
+    }
+    if ports.is_empty() && extends.is_empty() {
+        return None;
+    }
+
+    let mut found: Vec<String> = services
+        .iter()
+        .map(|service| service.name.clone())
+        .collect();
+    found.extend(extends.iter().cloned());
+    let mut preset = PortPreset::new(
+        AUTO_PRESET.to_string(),
+        format!("Detected in {}: {}", root.display(), found.join(", ")),
+        ports,
+    );
+    preset.extends = extends;
+    Some(preset)
+}
+
+fn uses_react(package_json: &Path) -> bool {
+    let Some(package) = fs::read_to_string(package_json)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+    else {
+        return false;
+    };
+    ["dependencies", "devDependencies"].iter().any(|section| {
+        package[section]
+            .as_object()
+            .is_some_and(|deps| deps.contains_key("react"))
+    })
+}
+
+/// Handle `--export-preset NAME [FILE]`
+pub fn export_command(name: &str, file: Option<&str>) -> Result<()> {
+    let mut manager = PresetManager::new();
+    manager.load_presets()?;
+    let content = manager.export_preset(name, file.map(Path::new))?;
+    match file {
+        Some(file) => {
+            fs::write(file, &content).with_context(|| format!("Failed to write {}", file))?;
+            println!(
+                "📤 Exported preset '{}' to {}; share it and import with --import-preset {}",
+                name, file, file
+            );
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
+
+/// Handle `--edit-preset NAME`: open the preset in `$VISUAL` / `$EDITOR` as JSON, check it
+/// when the editor exits and save it, offering another go when it doesn't check out
+pub fn edit_command(name: &str) -> Result<()> {
+    if crate::config::load_or_default()
+        .config
+        .presets
+        .contains_key(name)
+    {
+        bail!(
+            "Preset '{}' is defined in a config file; edit it there",
+            name
+        );
+    }
+    let mut manager = PresetManager::new();
+    manager.load_presets()?;
+    let preset = manager
+        .get_preset(name)
+        .with_context(|| format!("Preset '{}' not found", name))?;
+
+    let path = std::env::temp_dir().join(format!(
+        "port-kill-preset-{}-{}.json",
+        name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_"),
+        std::process::id()
+    ));
+    fs::write(&path, serde_json::to_string_pretty(preset)?)?;
+    let result = loop {
+        if let Err(e) = open_editor(&path) {
+            break Err(e);
+        }
+        let content = fs::read_to_string(&path)?;
+        match manager.replace_preset(name, &content) {
+            Ok(None) => {
+                println!("No changes to preset '{}'.", name);
+                break Ok(());
+            }
+            Ok(Some(saved)) => {
+                manager.save_presets()?;
+                println!("✅ Saved preset '{}'.", saved);
+                break Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                if !crate::safe_mode::confirm("Edit it again? (no discards the changes)")? {
+                    println!("Preset '{}' left as it was.", name);
+                    break Ok(());
+                }
+            }
+        }
+    };
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/// Run the user's editor on `path` and wait for it; `$EDITOR` may carry arguments, e.g.
+/// `code --wait`
+fn open_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().context("$EDITOR is empty")?;
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run editor '{}'", editor))?;
+    if !status.success() {
+        bail!("Editor '{}' exited with {}", editor, status);
+    }
+    Ok(())
+}
+
+/// Handle `--import-preset FILE|URL` with `--import-as` / `--overwrite`
+pub async fn import_command(source: &str, rename: Option<&str>, overwrite: bool) -> Result<()> {
+    let content = read_preset_source(source).await?;
+    let source = match Path::new(source).canonicalize() {
+        Ok(path) if !source.contains("://") => path.to_string_lossy().to_string(),
+        _ => source.to_string(),
+    };
+    let mut manager = PresetManager::new();
+    manager.load_presets()?;
+    let imported = manager.import_presets(&content, &source, rename, overwrite)?;
+    if imported.is_empty() {
+        println!("Presets from {} are already up to date.", source);
+        return Ok(());
+    }
+    manager.save_presets()?;
+    for (preset, replaced) in imported {
+        println!(
+            "📥 {} preset '{}' from {}",
+            if replaced { "Replaced" } else { "Imported" },
+            preset.name,
+            source
+        );
+    }
+    Ok(())
+}
+
+fn is_toml_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+/// The presets in an exported file: one preset, or a table of them keyed by name (how
+/// `presets.json` stores them). TOML is read when `toml` is set or the content isn't JSON.
+fn parse_presets(content: &str, toml: bool) -> Result<Vec<PortPreset>> {
+    let value: Value = if toml || !content.trim_start().starts_with('{') {
+        toml::from_str(content)?
+    } else {
+        serde_json::from_str(content)?
+    };
+    let Value::Object(map) = value else {
+        bail!("expected a preset or a table of presets");
+    };
+    if map.contains_key("ports") {
+        return Ok(vec![decode_record(Value::Object(map))?]);
+    }
+    map.into_iter()
+        .map(|(name, mut record)| {
+            if let Value::Object(fields) = &mut record {
+                fields.entry("name").or_insert(Value::from(name));
+            }
+            decode_record(record)
+        })
+        .collect()
+}
+
+/// The text of a preset file, or of a URL (`https://...`, e.g. a gist's raw link)
+pub async fn read_preset_source(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let client = crate::offline::client_builder(crate::offline::Channel::PresetImport)?
+            .timeout(std::time::Duration::from_secs(15))
+            .build()?;
+        let response = client.get(source).send().await?.error_for_status()?;
+        Ok(response.text().await?)
+    } else {
+        fs::read_to_string(source).with_context(|| format!("Failed to read {}", source))
+    }
 }
 
 impl Default for PresetManager {
@@ -395,7 +864,7 @@ mod tests {
 
         assert_eq!(preset.name, "test");
         assert_eq!(preset.description, "Test preset");
-        assert_eq!(preset.ports, vec![3000, 8080]);
+        assert_eq!(preset.port_numbers(), vec![3000, 8080]);
     }
 
     #[test]
@@ -428,6 +897,158 @@ mod tests {
         assert!(names.contains(&"system".to_string()));
     }
 
+    #[test]
+    fn test_resolve_extended_preset() {
+        let mut manager = PresetManager::new();
+        manager.load_default_presets();
+        let mut team = PortPreset::with_ignores(
+            "team".to_string(),
+            "Team".to_string(),
+            vec![4000, 3000],
+            None,
+            Some(vec!["Slack".to_string()]),
+            None,
+            None,
+        );
+        team.extends = vec!["dev".to_string(), "database".to_string()];
+        manager.add_preset(team);
+
+        let team = manager.resolve_preset("team").unwrap();
+        let ports = team.port_numbers();
+        assert_eq!(&ports[..2], &[3000, 3001]);
+        assert!(ports.contains(&5432));
+        assert_eq!(ports.last(), Some(&4000));
+        assert_eq!(ports.iter().filter(|p| **p == 3000).count(), 1);
+        let ignored = team.ignore_processes.unwrap();
+        assert!(ignored.contains(&"Chrome".to_string()) && ignored.contains(&"Slack".to_string()));
+
+        let mut looped = PortPreset::new("a".to_string(), String::new(), vec![]);
+        looped.extends = vec!["team".to_string(), "a".to_string()];
+        manager.add_preset(looped);
+        let err = manager.resolve_preset("a").unwrap_err().to_string();
+        assert!(err.contains("a -> a"), "{}", err);
+        let mut orphan = PortPreset::new("b".to_string(), String::new(), vec![]);
+        orphan.extends = vec!["missing".to_string()];
+        manager.add_preset(orphan);
+        assert!(manager.resolve_preset("b").is_err());
+    }
+
+    #[test]
+    fn test_detect_project_preset() {
+        let root = std::env::temp_dir().join(format!("port-kill-auto-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        assert!(detect_project_preset(&root).is_none());
+
+        fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"dev": "vite --port 5173"}, "dependencies": {"react": "^18"}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("manage.py"), "").unwrap();
+        let preset = detect_project_preset(&root).unwrap();
+        assert_eq!(preset.name, AUTO_PRESET);
+        assert_eq!(preset.extends, vec!["react", "python"]);
+        assert!(preset.port_numbers().contains(&5173));
+        assert!(preset.port_numbers().contains(&8000));
+
+        let mut manager = PresetManager::new();
+        manager.load_default_presets();
+        manager.add_preset(preset);
+        let resolved = manager.resolve_preset(AUTO_PRESET).unwrap().port_numbers();
+        assert!(resolved.contains(&3000) && resolved.contains(&5000));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_replace_preset() {
+        let mut manager = PresetManager::new();
+        manager.load_default_presets();
+        let mut edited = manager.get_preset("react").unwrap().clone();
+        let json = |preset: &PortPreset| serde_json::to_string(preset).unwrap();
+        assert_eq!(
+            manager.replace_preset("react", &json(&edited)).unwrap(),
+            None
+        );
+
+        edited.ports = vec!["3000-3002".into(), "!3001".into()];
+        assert_eq!(
+            manager.replace_preset("react", &json(&edited)).unwrap(),
+            Some("react".to_string())
+        );
+        assert_eq!(
+            manager.get_preset("react").unwrap().port_numbers(),
+            vec![3000, 3002]
+        );
+
+        // Invalid edits leave the preset alone
+        let mut broken = edited.clone();
+        broken.ports = vec!["30x0".into()];
+        assert!(manager.replace_preset("react", &json(&broken)).is_err());
+        broken.ports = vec![];
+        broken.extends = vec!["missing".to_string()];
+        assert!(manager.replace_preset("react", &json(&broken)).is_err());
+        assert!(manager.replace_preset("react", "{not json").is_err());
+        assert_eq!(manager.get_preset("react").unwrap().ports, edited.ports);
+
+        edited.name = "my-react".to_string();
+        manager.replace_preset("react", &json(&edited)).unwrap();
+        assert!(manager.get_preset("my-react").is_some());
+        assert!(manager.get_preset("react").is_none());
+        edited.name = "node".to_string();
+        assert!(manager.replace_preset("my-react", &json(&edited)).is_err());
+    }
+
+    #[test]
+    fn test_export_and_import_presets() {
+        let mut source = PresetManager::new();
+        source.load_default_presets();
+        let mut team = PortPreset::new("team".to_string(), "Team".to_string(), vec![3000]);
+        team.ignore_processes = Some(vec!["Chrome".to_string()]);
+        source.add_preset(team);
+        let toml = source
+            .export_preset("team", Some(Path::new("team.toml")))
+            .unwrap();
+        assert!(toml.contains("ports = [3000]"));
+
+        let mut manager = PresetManager::new();
+        manager.load_default_presets();
+        let imported = manager
+            .import_presets(&toml, "team.toml", None, false)
+            .unwrap();
+        assert_eq!(imported.len(), 1);
+        let team = manager.get_preset("team").unwrap();
+        assert_eq!(team.ignore_processes, Some(vec!["Chrome".to_string()]));
+        assert!(team
+            .provenance
+            .as_deref()
+            .unwrap()
+            .starts_with("team.toml ("));
+        // The same preset again is a no-op
+        assert!(manager
+            .import_presets(&toml, "team.toml", None, false)
+            .unwrap()
+            .is_empty());
+
+        // A table of presets, as presets.json holds them, clashing with a built-in
+        let json = r#"{"dev": {"description": "Our dev", "ports": [4000]}}"#;
+        assert!(manager.import_presets(json, "x.json", None, false).is_err());
+        manager
+            .import_presets(json, "x.json", Some("our-dev"), false)
+            .unwrap();
+        assert_eq!(
+            manager.get_preset("our-dev").unwrap().port_numbers(),
+            vec![4000]
+        );
+        assert_eq!(manager.get_preset("dev").unwrap().port_numbers()[0], 3000);
+        let imported = manager.import_presets(json, "x.json", None, true).unwrap();
+        assert!(imported[0].1);
+        assert_eq!(
+            manager.get_preset("dev").unwrap().port_numbers(),
+            vec![4000]
+        );
+    }
+
     #[test]
     fn test_save_overrides_default_preset() {
         let test_dir = std::env::temp_dir().join(format!(
@@ -463,7 +1084,7 @@ mod tests {
         reloaded.load_presets().expect("Failed to load presets");
 
         let loaded_dev = reloaded.get_preset("dev").expect("Missing dev preset");
-        assert_eq!(loaded_dev.ports, vec![9999, 9998]);
+        assert_eq!(loaded_dev.port_numbers(), vec![9999, 9998]);
         assert_eq!(loaded_dev.description, "My custom dev preset");
 
         let _ = fs::remove_dir_all(&test_dir);
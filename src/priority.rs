@@ -0,0 +1,124 @@
+//! Renice actions: keep a heavy dev process (a webpack watcher, a cargo build server)
+//! running but lower its scheduling priority so it stops starving the rest of the machine.
+//! `--nice <port> <adjustment>` and the tray's "Deprioritize" items use this; each renice
+//! is recorded in the kill history as a non-destructive action.
+
+use crate::types::ProcessInfo;
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Adjustment applied by the tray's "Deprioritize" items
+pub const DEPRIORITIZE: i32 = 10;
+
+/// CPU usage above which a listener is offered for deprioritizing in the tray
+const HEAVY_CPU: f64 = 25.0;
+
+/// Tools that routinely peg a core while they watch and rebuild
+const HEAVY_TOOLS: &[&str] = &[
+    "webpack", "cargo", "rustc", "rust-analyzer", "tsc", "gradle", "bazel", "esbuild", "next",
+    "nuxt", "ng serve", "jest", "vitest",
+];
+
+/// `+10`, `10` or `-5`; nice values only go from -20 to 19
+pub fn parse_adjustment(input: &str) -> Result<i32> {
+    let value: i32 = input
+        .trim()
+        .trim_start_matches('+')
+        .parse()
+        .with_context(|| format!("Invalid priority adjustment '{}' (expected e.g. +10 or -5)", input))?;
+    if !(-39..=39).contains(&value) || value == 0 {
+        anyhow::bail!("Priority adjustment must be between -39 and +39, and not 0");
+    }
+    Ok(value)
+}
+
+/// Whether the tray should offer to deprioritize this listener
+pub fn is_heavy(process: &ProcessInfo) -> bool {
+    if process.cpu_usage.is_some_and(|cpu| cpu >= HEAVY_CPU) {
+        return true;
+    }
+    let command = process.command_line.as_deref().unwrap_or(&process.name);
+    HEAVY_TOOLS.iter().any(|tool| command.contains(tool))
+}
+
+/// The process's current nice value
+#[cfg(not(target_os = "windows"))]
+pub fn current_nice(pid: i32) -> Option<i32> {
+    let output = Command::new("ps")
+        .args(["-o", "ni=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(target_os = "windows")]
+pub fn current_nice(_pid: i32) -> Option<i32> {
+    None
+}
+
+/// Shift the process's priority by `adjustment` (positive is nicer). Returns the new nice
+/// value where the platform has one.
+#[cfg(not(target_os = "windows"))]
+pub fn renice(pid: i32, adjustment: i32) -> Result<Option<i32>> {
+    let current = current_nice(pid).with_context(|| format!("No such process: {}", pid))?;
+    let nice = (current + adjustment).clamp(-20, 19);
+    // An absolute value reads the same to BSD and util-linux renice
+    let output = Command::new("renice")
+        .args([&nice.to_string(), "-p", &pid.to_string()])
+        .output()
+        .context("Could not run renice")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if adjustment < 0 && stderr.to_lowercase().contains("permission") {
+            anyhow::bail!("Raising a process's priority needs root: retry with --sudo");
+        }
+        anyhow::bail!("renice failed: {}", stderr.trim());
+    }
+    Ok(Some(nice))
+}
+
+#[cfg(target_os = "windows")]
+pub fn renice(pid: i32, adjustment: i32) -> Result<Option<i32>> {
+    let class = match adjustment {
+        15.. => "Idle",
+        1.. => "BelowNormal",
+        -9..=-1 => "AboveNormal",
+        _ => "High",
+    };
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!("(Get-Process -Id {}).PriorityClass = '{}'", pid, class),
+        ])
+        .output()
+        .context("Could not run powershell")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Setting priority failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(None)
+}
+
+/// "+10" / "-5"
+pub fn format_adjustment(adjustment: i32) -> String {
+    format!("{:+}", adjustment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_adjustment() {
+        assert_eq!(parse_adjustment("+10").unwrap(), 10);
+        assert_eq!(parse_adjustment("10").unwrap(), 10);
+        assert_eq!(parse_adjustment("-5").unwrap(), -5);
+        assert!(parse_adjustment("0").is_err());
+        assert!(parse_adjustment("+40").is_err());
+        assert!(parse_adjustment("lots").is_err());
+        assert_eq!(format_adjustment(10), "+10");
+    }
+}
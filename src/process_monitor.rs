@@ -1,3 +1,5 @@
+use crate::elevation::{self, PermissionDenied};
+use crate::hooks::KillOutcome;
 use crate::restart_manager::RestartManager;
 use crate::smart_filter::{FilterStats, SmartFilter};
 use crate::system_monitor::SystemMonitor;
@@ -27,6 +29,21 @@ pub struct ProcessMonitor {
     system_monitor: SystemMonitor,
     performance_enabled: bool,
     restart_manager: RestartManager,
+    user_filter: Option<String>,
+    allow_other_users: bool,
+    elevate: bool,
+    /// Whether a kill may stop to ask on the terminal, see [`Self::set_confirm_on_terminal`]
+    confirm_on_terminal: bool,
+    no_kill_if_active: bool,
+    /// Sampling window when only listeners with traffic may be killed (--only-active)
+    only_active: Option<Duration>,
+    /// Last traffic sample for --only-active, reused for a few seconds across bulk kills
+    traffic: Option<(std::time::Instant, std::collections::BTreeMap<u16, crate::traffic::PortTraffic>)>,
+    protected: Vec<String>,
+    /// Safe mode: listeners on system ports are listed but not killed
+    safe_mode: bool,
+    /// When cached state was last dropped, see [`Self::compact`]
+    last_compaction: std::time::Instant,
 }
 
 impl ProcessMonitor {
@@ -42,12 +59,21 @@ impl ProcessMonitor {
             ports_to_monitor,
             docker_enabled,
             verbose,
-            history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
-                .unwrap_or_else(|_| ProcessHistory::new(100)),
+            history: ProcessHistory::load(100),
             smart_filter: None,
             system_monitor: SystemMonitor::new(),
             performance_enabled: false,
             restart_manager: RestartManager::new().unwrap_or_default(),
+            user_filter: None,
+            allow_other_users: false,
+            elevate: false,
+            confirm_on_terminal: false,
+            no_kill_if_active: false,
+            only_active: None,
+            traffic: None,
+            protected: Vec::new(),
+            safe_mode: false,
+            last_compaction: std::time::Instant::now(),
         })
     }
 
@@ -64,12 +90,21 @@ impl ProcessMonitor {
             ports_to_monitor,
             docker_enabled,
             verbose,
-            history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
-                .unwrap_or_else(|_| ProcessHistory::new(100)),
+            history: ProcessHistory::load(100),
             smart_filter: Some(smart_filter),
             system_monitor: SystemMonitor::new(),
             performance_enabled: false,
             restart_manager: RestartManager::new().unwrap_or_default(),
+            user_filter: None,
+            allow_other_users: false,
+            elevate: false,
+            confirm_on_terminal: false,
+            no_kill_if_active: false,
+            only_active: None,
+            traffic: None,
+            protected: Vec::new(),
+            safe_mode: false,
+            last_compaction: std::time::Instant::now(),
         })
     }
 
@@ -87,15 +122,71 @@ impl ProcessMonitor {
             ports_to_monitor,
             docker_enabled,
             verbose,
-            history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
-                .unwrap_or_else(|_| ProcessHistory::new(100)),
+            history: ProcessHistory::load(100),
             smart_filter,
             system_monitor: SystemMonitor::new(),
             performance_enabled,
             restart_manager: RestartManager::new().unwrap_or_default(),
+            user_filter: None,
+            allow_other_users: false,
+            elevate: false,
+            confirm_on_terminal: false,
+            no_kill_if_active: false,
+            only_active: None,
+            traffic: None,
+            protected: Vec::new(),
+            safe_mode: false,
+            last_compaction: std::time::Instant::now(),
         })
     }
 
+    /// Only report processes owned by this user
+    pub fn set_user_filter(&mut self, user: Option<String>) {
+        self.user_filter = user;
+    }
+
+    /// Allow killing processes that belong to other users (--force)
+    pub fn set_allow_other_users(&mut self, allowed: bool) {
+        self.allow_other_users = allowed;
+    }
+
+    /// Retry kills refused with a permission error through sudo/UAC (--sudo)
+    pub fn set_elevate(&mut self, elevate: bool) {
+        self.elevate = elevate;
+    }
+
+    /// Let `--sudo` kills of other users' processes ask on the terminal. Only the console
+    /// commands turn this on: the TUI, the port guard and `--serve` share a monitor that
+    /// has no terminal to ask on, so those kills are refused instead.
+    pub fn set_confirm_on_terminal(&mut self, enabled: bool) {
+        self.confirm_on_terminal = enabled;
+    }
+
+    /// Refuse to kill listeners that still have clients connected (--no-kill-if-active)
+    pub fn set_no_kill_if_active(&mut self, enabled: bool) {
+        self.no_kill_if_active = enabled;
+    }
+
+    /// Only kill listeners that saw traffic during a sample of `window` (--only-active)
+    pub fn set_only_active(&mut self, window: Option<Duration>) {
+        self.only_active = window;
+    }
+
+    /// Process names that are never killed (--protect, `protected` in the config files)
+    pub fn set_protected(&mut self, protected: Vec<String>) {
+        self.protected = protected;
+    }
+
+    /// Refuse to kill listeners on system ports, as safe mode does
+    pub fn set_safe_mode(&mut self, enabled: bool) {
+        self.safe_mode = enabled;
+    }
+
+    /// Sample CPU and memory during scans, as with --performance
+    pub fn set_performance(&mut self, enabled: bool) {
+        self.performance_enabled = enabled;
+    }
+
     pub fn get_process_start_time(&mut self, pid: i32) -> Option<u64> {
         self.system_monitor.get_process_start_time(pid)
     }
@@ -125,6 +216,7 @@ impl ProcessMonitor {
             let old_processes = self.current_processes.clone();
             match self.scan_processes().await {
                 Ok(processes) => {
+                    crate::timeline::record(&processes);
                     if old_processes != processes {
                         let update = ProcessUpdate::new(processes.clone());
                         info!("Process update: {} processes found", update.count);
@@ -143,7 +235,25 @@ impl ProcessMonitor {
         }
     }
 
+    /// Drop cached state and give back memory from buffers that grew: the sysinfo process
+    /// table (command lines and environments of every process on the machine), a stale
+    /// traffic sample, and history beyond its limit. Long-running scans call this every
+    /// [`crate::memory::COMPACT_INTERVAL`].
+    pub fn compact(&mut self) {
+        self.system_monitor = SystemMonitor::new();
+        self.traffic = None;
+        self.history.compact();
+        self.current_processes.shrink_to_fit();
+        crate::timeline::compact();
+        self.last_compaction = std::time::Instant::now();
+        crate::memory::record_compaction();
+        log::debug!("Compacted process monitor state");
+    }
+
     pub async fn scan_processes(&mut self) -> Result<HashMap<u16, ProcessInfo>> {
+        if self.last_compaction.elapsed() >= crate::memory::COMPACT_INTERVAL {
+            self.compact();
+        }
         // Use the optimized batch scanning approach instead of iterating one by one
         let args = crate::cli::Args {
             start_port: 2000,
@@ -152,16 +262,27 @@ impl ProcessMonitor {
             ignore_ports: None,
             ignore_processes: None,
             ignore_patterns: None,
+            ignore_regex: None,
+            ignore_case: false,
             ignore_groups: None,
             smart_filter: false,
             only_groups: None,
+            only_processes: None,
+            only_ports: None,
+            port_rules: Default::default(),
+            conditional_filters: Vec::new(),
             console: false,
             verbose: false, // Set to false to avoid infinite recursion in get_processes_on_ports
             docker: self.docker_enabled,
             show_pid: false,
             log_level: crate::cli::LogLevel::Info,
+            log_format: crate::cli::LogFormat::Text,
+            log_file: None,
+            log_max_size: 10,
             show_history: false,
             clear_history: false,
+            import_history: None,
+            history_action: None,
             show_filters: false,
             performance: self.performance_enabled,
             show_context: false,
@@ -174,25 +295,50 @@ impl ProcessMonitor {
             show_tree: false,
             json: false,
             reset: false,
+            deep: false,
             show_offenders: false,
             show_patterns: false,
             show_suggestions: false,
+            apply_suggestions: false,
+            ignore: None,
             show_stats: false,
+            history_since: None,
+            history_until: None,
+            history_port: None,
+            history_project: None,
+            history_group: None,
+            history_limit: None,
             show_root_cause: false,
+            apply: false,
             guard_mode: false,
             guard_ports: "3000,3001,3002,8000,8080,9000".to_string(),
             auto_resolve: false,
+            on_conflict: None,
             reservation_file: "~/.port-kill/reservations.json".to_string(),
             intercept_commands: false,
             reserve_port: None,
             project_name: None,
             process_name: None,
+            reserve: None,
+            expires_in: None,
+            no_renew: false,
+            unreserve: None,
+            reservations: false,
             audit: false,
             security_mode: false,
             suspicious_ports: "8444,4444,9999,14444,5555,6666,7777".to_string(),
             baseline_file: None,
             suspicious_only: false,
             remote: None,
+            linked: false,
+            nice: None,
+            profile: None,
+            show_audit: false,
+            audit_since: None,
+            audit_action: None,
+            audit_initiator: None,
+            audit_port: None,
+            audit_failed: false,
             monitor_endpoint: None,
             send_interval: 30,
             scan_interval: 2,
@@ -219,9 +365,37 @@ impl ProcessMonitor {
             save_preset: None,
             preset_desc: None,
             delete_preset: None,
+            export_preset: None,
+            import_preset: None,
+            import_as: None,
+            overwrite: false,
+            show_preset: None,
+            edit_preset: None,
             check_updates: false,
+            no_update_check: false,
+            demo: false,
+            test_webhooks: false,
+            adopt: false,
+            restart_failed: false,
+            logs: None,
+            follow: false,
+            supervise: false,
+            supervise_max_restarts: 5,
+            undo_last_kill: false,
+            enable_autostart: false,
+            disable_autostart: false,
+            icon_theme: None,
+            max_rss: None,
+            offline: false,
+            trust_me: false,
+            safe_mode: false,
+            notify: false,
+            uninstall: false,
+            purge: false,
             self_update: false,
             cache: None,
+            complete_values: None,
+            schema: None,
             detect: false,
             start: None,
             guard_auto_restart: false,
@@ -231,11 +405,47 @@ impl ProcessMonitor {
             status: false,
             config_file: ".port-kill.yaml".to_string(),
             init_config: false,
+            simulate: false,
+            wsl: false,
+            auto_kill_cooldown: 30,
+            max_auto_kills: 10,
+            auto_kill_window: 600,
+            user: self.user_filter.clone(),
+            force: false,
+            sudo: false,
+            what_if: false,
+            report: None,
+            report_format: crate::digest::ReportFormat::Text,
+            at: None,
+            timeline: None,
+            hours: 24,
+            connections: false,
+            traffic: false,
+            traffic_window: 2,
+            no_kill_if_active: false,
+            only_active: false,
+            tui: false,
+            set_terminal_title: false,
+            status_page: None,
+            serve: None,
+            serve_token: None,
+            output: crate::cli::OutputFormat::Table,
+            once: false,
+            fail_if_occupied: None,
+            kill_gpu_hogs: false,
+            wait_free: None,
+            wait_open: None,
+            timeout: None,
+            kill_duplicates: false,
+            protect: None,
+            no_config: false,
         };
         
         let (_count, mut processes) = get_processes_on_ports(&self.ports_to_monitor, &args);
+        // Demo processes come fully populated and have no real PID to enrich from
+        let live = !crate::demo::is_enabled();
 
-        if self.verbose {
+        if self.verbose && live {
             #[cfg(not(target_os = "windows"))]
             {
                 for process_info in processes.values_mut() {
@@ -249,6 +459,10 @@ impl ProcessMonitor {
             #[cfg(target_os = "windows")]
             {
                 for process_info in processes.values_mut() {
+                    // WSL PIDs live in the Linux VM, not in the Windows process table
+                    if process_info.wsl_distro.is_some() {
+                        continue;
+                    }
                     let (command_line, working_directory) =
                         self.get_process_verbose_info_windows(process_info.pid).await;
                     process_info.command_line = command_line;
@@ -258,11 +472,14 @@ impl ProcessMonitor {
         }
 
         // Refresh system information for performance metrics
-        if self.performance_enabled {
+        if self.performance_enabled && live {
             self.system_monitor.refresh();
             
             // Add performance metrics to each process
             for process_info in processes.values_mut() {
+                if process_info.wsl_distro.is_some() {
+                    continue;
+                }
                 if let Some(cpu_usage) =
                     self.system_monitor.get_process_cpu_usage(process_info.pid)
                 {
@@ -282,6 +499,14 @@ impl ProcessMonitor {
             self.system_monitor.cleanup_old_processes();
         }
 
+        // GPU memory for ML dev servers (performance/verbose output and the TUI)
+        if (self.performance_enabled || self.verbose) && live {
+            let gpu_usage = crate::gpu::gpu_memory_by_pid();
+            for process_info in processes.values_mut() {
+                process_info.gpu_memory = gpu_usage.get(&process_info.pid).copied();
+            }
+        }
+
         // Apply smart filtering if enabled
         if let Some(ref filter) = self.smart_filter {
             filter.filter_processes(&mut processes);
@@ -304,16 +529,27 @@ impl ProcessMonitor {
             ignore_ports: None,
             ignore_processes: None,
             ignore_patterns: None,
+            ignore_regex: None,
+            ignore_case: false,
             ignore_groups: None,
             smart_filter: false,
             only_groups: None,
+            only_processes: None,
+            only_ports: None,
+            port_rules: Default::default(),
+            conditional_filters: Vec::new(),
             console: false,
             verbose: false,
             docker: self.docker_enabled,
             show_pid: false,
             log_level: crate::cli::LogLevel::Info,
+            log_format: crate::cli::LogFormat::Text,
+            log_file: None,
+            log_max_size: 10,
             show_history: false,
             clear_history: false,
+            import_history: None,
+            history_action: None,
             show_filters: false,
             performance: false,
             show_context: false,
@@ -326,25 +562,50 @@ impl ProcessMonitor {
             show_tree: false,
             json: false,
             reset: false,
+            deep: false,
             show_offenders: false,
             show_patterns: false,
             show_suggestions: false,
+            apply_suggestions: false,
+            ignore: None,
             show_stats: false,
+            history_since: None,
+            history_until: None,
+            history_port: None,
+            history_project: None,
+            history_group: None,
+            history_limit: None,
             show_root_cause: false,
+            apply: false,
             guard_mode: false,
             guard_ports: "3000,3001,3002,8000,8080,9000".to_string(),
             auto_resolve: false,
+            on_conflict: None,
             reservation_file: "~/.port-kill/reservations.json".to_string(),
             intercept_commands: false,
             reserve_port: None,
             project_name: None,
             process_name: None,
+            reserve: None,
+            expires_in: None,
+            no_renew: false,
+            unreserve: None,
+            reservations: false,
             audit: false,
             security_mode: false,
             suspicious_ports: "8444,4444,9999,14444,5555,6666,7777".to_string(),
             baseline_file: None,
             suspicious_only: false,
             remote: None,
+            linked: false,
+            nice: None,
+            profile: None,
+            show_audit: false,
+            audit_since: None,
+            audit_action: None,
+            audit_initiator: None,
+            audit_port: None,
+            audit_failed: false,
             monitor_endpoint: None,
             send_interval: 30,
             scan_interval: 2,
@@ -371,9 +632,37 @@ impl ProcessMonitor {
             save_preset: None,
             preset_desc: None,
             delete_preset: None,
+            export_preset: None,
+            import_preset: None,
+            import_as: None,
+            overwrite: false,
+            show_preset: None,
+            edit_preset: None,
             check_updates: false,
+            no_update_check: false,
+            demo: false,
+            test_webhooks: false,
+            adopt: false,
+            restart_failed: false,
+            logs: None,
+            follow: false,
+            supervise: false,
+            supervise_max_restarts: 5,
+            undo_last_kill: false,
+            enable_autostart: false,
+            disable_autostart: false,
+            icon_theme: None,
+            max_rss: None,
+            offline: false,
+            trust_me: false,
+            safe_mode: false,
+            notify: false,
+            uninstall: false,
+            purge: false,
             self_update: false,
             cache: None,
+            complete_values: None,
+            schema: None,
             detect: false,
             start: None,
             guard_auto_restart: false,
@@ -383,6 +672,40 @@ impl ProcessMonitor {
             status: false,
             config_file: ".port-kill.yaml".to_string(),
             init_config: false,
+            simulate: false,
+            wsl: false,
+            auto_kill_cooldown: 30,
+            max_auto_kills: 10,
+            auto_kill_window: 600,
+            user: self.user_filter.clone(),
+            force: false,
+            sudo: false,
+            what_if: false,
+            report: None,
+            report_format: crate::digest::ReportFormat::Text,
+            at: None,
+            timeline: None,
+            hours: 24,
+            connections: false,
+            traffic: false,
+            traffic_window: 2,
+            no_kill_if_active: false,
+            only_active: false,
+            tui: false,
+            set_terminal_title: false,
+            status_page: None,
+            serve: None,
+            serve_token: None,
+            output: crate::cli::OutputFormat::Table,
+            once: false,
+            fail_if_occupied: None,
+            kill_gpu_hogs: false,
+            wait_free: None,
+            wait_open: None,
+            timeout: None,
+            kill_duplicates: false,
+            protect: None,
+            no_config: false,
         };
         get_processes_on_ports_multi(&self.ports_to_monitor, &args)
     }
@@ -507,6 +830,13 @@ impl ProcessMonitor {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
         };
 
         // Determine process group and project name
@@ -558,6 +888,13 @@ impl ProcessMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            wsl_distro: None,
+                            user: None,
+                            uid: None,
+                            started_at: None,
+                            uptime_secs: None,
+                            ppid: None,
+                            gpu_memory: None,
                         };
 
                         // Determine process group and project name
@@ -589,6 +926,13 @@ impl ProcessMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        wsl_distro: None,
+                        user: None,
+                        uid: None,
+                        started_at: None,
+                        uptime_secs: None,
+                        ppid: None,
+                        gpu_memory: None,
                     };
 
                     // Determine process group and project name
@@ -631,6 +975,13 @@ impl ProcessMonitor {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
         };
 
         // Determine process group and project name
@@ -925,7 +1276,7 @@ impl ProcessMonitor {
         }
     }
 
-    pub async fn kill_process(&mut self, pid: i32) -> Result<()> {
+    pub async fn kill_process(&mut self, pid: i32) -> Result<KillOutcome> {
         self.kill_process_with_context(pid, "user", true).await
     }
 
@@ -934,8 +1285,30 @@ impl ProcessMonitor {
         pid: i32,
         context: &str,
         add_to_history: bool,
-    ) -> Result<()> {
-        info!("Attempting to kill process {}", pid);
+    ) -> Result<KillOutcome> {
+        let target = self
+            .current_processes
+            .values()
+            .find(|p| p.pid == pid)
+            .map(|p| (p.port, p.name.clone()));
+        let result = self.kill_and_track(pid, context, add_to_history).await;
+        crate::audit_log::record_kill(
+            pid,
+            target.as_ref().map(|(port, _)| *port),
+            target.as_ref().map(|(_, name)| name.as_str()),
+            &result,
+        );
+        result
+    }
+
+    /// Kill with hooks, notifications, restart info and history; refusals are errors
+    async fn kill_and_track(
+        &mut self,
+        pid: i32,
+        context: &str,
+        add_to_history: bool,
+    ) -> Result<KillOutcome> {
+        info!(pid = pid, action = "kill", context = context; "Attempting to kill process {}", pid);
 
         // Find the process info before killing it
         let process_info = self
@@ -944,12 +1317,70 @@ impl ProcessMonitor {
             .find(|p| p.pid == pid)
             .cloned();
 
+        // Demo processes only pretend to die: no hooks, restart info or history
+        if crate::demo::is_enabled() {
+            let result = crate::demo::kill(pid);
+            if context != "bulk" {
+                let port = process_info.as_ref().map(|p| p.port);
+                let name = process_info.as_ref().map(|p| p.name.as_str());
+                crate::notify::kill_result(pid, port, name, &result);
+            }
+            return result.map(|()| KillOutcome::Killed);
+        }
+
+        if !self.allow_other_users {
+            let owner = match &process_info {
+                Some(p) if p.wsl_distro.is_some() => None,
+                Some(p) if p.user.is_some() => p.user.clone(),
+                _ => get_process_owner(pid),
+            };
+            ensure_kill_confirmed(
+                pid,
+                owner.as_deref(),
+                false,
+                self.elevate && self.confirm_on_terminal,
+            )?;
+        }
+        ensure_not_protected(
+            pid,
+            process_info.as_ref().map(|p| p.name.as_str()),
+            &self.protected,
+        )?;
+        if let Some(port) = process_info.as_ref().map(|p| p.port) {
+            crate::safe_mode::ensure_not_system_port(port, self.safe_mode)?;
+        }
+
+        if self.no_kill_if_active {
+            if let Some(port) = process_info.as_ref().map(|p| p.port) {
+                if let Some(active) = crate::connections::active_connections_on(port) {
+                    return Err(anyhow::anyhow!(
+                        "Port {} still has {}; not killing PID {} (--no-kill-if-active)",
+                        port,
+                        active.summary(),
+                        pid
+                    ));
+                }
+            }
+        }
+
+        if let (Some(window), Some(port)) = (self.only_active, process_info.as_ref().map(|p| p.port)) {
+            let traffic = self.port_traffic(port, window);
+            if !traffic.is_active() {
+                return Err(anyhow::anyhow!(
+                    "Port {} had no traffic in the last {}s; not killing PID {} (--only-active)",
+                    port,
+                    window.as_secs(),
+                    pid
+                ));
+            }
+        }
+
         // Save to restart manager — fetch verbose info on demand if not already available
         if let Some(ref proc_info) = process_info {
             let mut cmd_line = proc_info.command_line.clone();
             let mut work_dir = proc_info.working_directory.clone();
 
-            if cmd_line.is_none() || work_dir.is_none() {
+            if (cmd_line.is_none() || work_dir.is_none()) && proc_info.wsl_distro.is_none() {
                 #[cfg(not(target_os = "windows"))]
                 {
                     let (cl, wd) = self.get_process_verbose_info(proc_info.pid).await;
@@ -975,6 +1406,50 @@ impl ProcessMonitor {
             }
         }
 
+        // Configured hooks, then HTTP shutdown endpoints, get the first chance to shut the
+        // process down gracefully
+        let config = crate::config::load_or_default().config;
+        let hooks = config.hooks;
+        let port = process_info.as_ref().map(|p| p.port);
+        let name = process_info.as_ref().map(|p| p.name.clone());
+        let outcome = if crate::hooks::run_pre_kill(&hooks, pid, port, name.as_deref())? == crate::hooks::PreKill::Proceed
+            && crate::graceful_http::shutdown(&config.graceful_http, pid, port) == crate::hooks::PreKill::Proceed
+        {
+            let result = self.signal_process(pid, process_info.as_ref()).await;
+            // Bulk kills send one summary instead
+            if context != "bulk" {
+                crate::notify::kill_result(pid, port, name.as_deref(), &result);
+            }
+            result?;
+            KillOutcome::Killed
+        } else {
+            info!("PID {} exited before it was signalled", pid);
+            KillOutcome::Exited
+        };
+        crate::hooks::run_post_kill(&hooks, pid, port, name.as_deref());
+
+        // Add to history if we found the process info and add_to_history is true
+        if add_to_history {
+            if let Some(process_info) = process_info {
+                let history_entry = ProcessHistoryEntry::new(&process_info, context.to_string());
+                self.history.add_entry(history_entry);
+                info!("Added process {} to history", pid);
+
+                // Save history to file
+                if let Err(e) = self.history.persist() {
+                    warn!("Failed to save history to file: {}", e);
+                }
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Stop the container or signal the process (SIGTERM, then SIGKILL / taskkill)
+    async fn signal_process(&mut self, pid: i32, process_info: Option<&ProcessInfo>) -> Result<()> {
+        #[cfg(not(target_os = "windows"))]
+        let _ = process_info;
+
         #[cfg(not(target_os = "windows"))]
         {
             // Check if this is a Docker container process (Unix-like systems only)
@@ -991,21 +1466,32 @@ impl ProcessMonitor {
 
         #[cfg(target_os = "windows")]
         {
-            // Windows: Use taskkill
-            let output = Command::new("taskkill")
-                .args(&["/PID", &pid.to_string(), "/F"])
-                .output()
-                .context("Failed to execute taskkill command")?;
-
-            if output.status.success() {
-                info!("Successfully killed process {} on Windows", pid);
+            let wsl_distro = process_info.and_then(|p| p.wsl_distro.clone());
+            if let Some(distro) = wsl_distro {
+                // Linux-side process inside WSL2: kill it through wsl.exe
+                crate::wsl_bridge::kill_wsl_process(&distro, pid)?;
+            } else if elevation::termination_denied(pid) {
+                if !self.elevate {
+                    return Err(PermissionDenied { pid }.into());
+                }
+                elevation::kill_elevated(pid)?;
             } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                error!("Failed to kill process {} on Windows: {}", pid, stderr);
-                return Err(anyhow::anyhow!(
-                    "Failed to kill process on Windows: {}",
-                    stderr
-                ));
+                // Windows: Use taskkill
+                let output = Command::new("taskkill")
+                    .args(&["/PID", &pid.to_string(), "/F"])
+                    .output()
+                    .context("Failed to execute taskkill command")?;
+
+                if output.status.success() {
+                    info!(pid = pid, action = "killed"; "Successfully killed process {} on Windows", pid);
+                } else {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    error!(pid = pid, action = "kill_failed"; "Failed to kill process {} on Windows: {}", pid, stderr);
+                    return Err(anyhow::anyhow!(
+                        "Failed to kill process on Windows: {}",
+                        stderr
+                    ));
+                }
             }
         }
 
@@ -1014,7 +1500,7 @@ impl ProcessMonitor {
             // Unix-like systems: Use SIGTERM then SIGKILL
             match kill(Pid::from_raw(pid), Signal::SIGTERM) {
                 Ok(_) => {
-                    info!("Sent SIGTERM to process {}", pid);
+                    info!(pid = pid, action = "sigterm"; "Sent SIGTERM to process {}", pid);
 
                     // Wait a bit and check if process is still alive
                     sleep(Duration::from_millis(500)).await;
@@ -1029,41 +1515,30 @@ impl ProcessMonitor {
                         // Send SIGKILL if process is still alive
                         match kill(Pid::from_raw(pid), Signal::SIGKILL) {
                             Ok(_) => {
-                                info!("Sent SIGKILL to process {}", pid);
+                                info!(pid = pid, action = "sigkill"; "Sent SIGKILL to process {}", pid);
                             }
                             Err(e) => {
-                                error!("Failed to send SIGKILL to process {}: {}", pid, e);
+                                error!(pid = pid, action = "kill_failed"; "Failed to send SIGKILL to process {}: {}", pid, e);
                                 return Err(anyhow::anyhow!("Failed to kill process: {}", e));
                             }
                         }
                     } else {
-                        info!("Process {} terminated successfully with SIGTERM", pid);
+                        info!(pid = pid, action = "killed"; "Process {} terminated successfully with SIGTERM", pid);
                     }
                 }
+                Err(nix::errno::Errno::EPERM) => {
+                    if !self.elevate {
+                        return Err(PermissionDenied { pid }.into());
+                    }
+                    elevation::kill_elevated(pid)?;
+                }
                 Err(e) => {
-                    error!("Failed to send SIGTERM to process {}: {}", pid, e);
+                    error!(pid = pid, action = "kill_failed"; "Failed to send SIGTERM to process {}: {}", pid, e);
                     return Err(anyhow::anyhow!("Failed to kill process: {}", e));
                 }
             }
         }
 
-        // Add to history if we found the process info and add_to_history is true
-        if add_to_history {
-            if let Some(process_info) = process_info {
-                let history_entry = ProcessHistoryEntry::new(&process_info, context.to_string());
-                self.history.add_entry(history_entry);
-                info!("Added process {} to history", pid);
-
-                // Save history to file
-                if let Err(e) = self
-                    .history
-                    .save_to_file(&ProcessHistory::get_history_file_path())
-                {
-                    warn!("Failed to save history to file: {}", e);
-                }
-            }
-        }
-
         Ok(())
     }
 
@@ -1109,39 +1584,58 @@ impl ProcessMonitor {
         info!("Killing all monitored processes");
 
         let processes = self.scan_processes().await?;
+        let total = processes.len();
+        let mut killed = 0;
         let mut errors = Vec::new();
 
         for (port, process_info) in processes {
+            if let Err(e) = ensure_not_protected(process_info.pid, Some(&process_info.name), &self.protected)
+                .and_then(|()| crate::safe_mode::ensure_not_system_port(port, self.safe_mode))
+            {
+                info!("Skipping port {}: {}", port, e);
+                continue;
+            }
             info!(
                 "Killing process on port {} (PID: {})",
                 port, process_info.pid
             );
 
             // Add to history before killing
-            let history_entry = ProcessHistoryEntry::new(&process_info, "bulk".to_string());
-            self.history.add_entry(history_entry);
+            if !crate::demo::is_enabled() {
+                let history_entry = ProcessHistoryEntry::new(&process_info, "bulk".to_string());
+                self.history.add_entry(history_entry);
+            }
 
-            if let Err(e) = self
+            match self
                 .kill_process_with_context(process_info.pid, "bulk", false)
                 .await
             {
-                errors.push(format!("Port {} (PID {}): {}", port, process_info.pid, e));
+                Ok(_) => killed += 1,
+                Err(e) => errors.push(format!("Port {} (PID {}): {}", port, process_info.pid, e)),
             }
         }
 
+        if killed + errors.len() > 0 {
+            crate::notify::send(crate::notify::Message::bulk(killed, errors.len()));
+        }
+
         if !errors.is_empty() {
             let error_msg = errors.join("; ");
             return Err(anyhow::anyhow!(
                 "Some processes failed to kill: {}",
                 error_msg
-            ));
+            )
+            .context(crate::exit_code::KillFailed {
+                failed: errors.len(),
+                total,
+            }));
         }
 
         // Save history to file after killing all processes
-        if let Err(e) = self
-            .history
-            .save_to_file(&ProcessHistory::get_history_file_path())
-        {
+        if crate::demo::is_enabled() {
+            return Ok(());
+        }
+        if let Err(e) = self.history.persist() {
             warn!("Failed to save history to file: {}", e);
         }
 
@@ -1159,6 +1653,47 @@ impl ProcessMonitor {
         }
     }
 
+    /// Shift a process's priority and record it in the history. Returns the new nice value
+    /// where the platform has one.
+    pub fn renice_process(&mut self, process_info: &ProcessInfo, adjustment: i32) -> Result<Option<i32>> {
+        if crate::demo::is_enabled() {
+            log::info!(port = process_info.port, pid = process_info.pid, action = "renice", adjustment = adjustment; "[demo] Reniced process {} by {}", process_info.pid, adjustment);
+            return Ok(Some(adjustment.clamp(-20, 19)));
+        }
+        let nice = crate::priority::renice(process_info.pid, adjustment)?;
+        info!(
+            port = process_info.port, pid = process_info.pid, action = "renice", adjustment = adjustment;
+            "Reniced {} (PID {}) on port {} by {}",
+            process_info.name, process_info.pid, process_info.port, adjustment
+        );
+        self.history
+            .add_entry(ProcessHistoryEntry::reniced(process_info, adjustment, nice));
+        if let Err(e) = self.history.persist() {
+            warn!("Failed to save history to file: {}", e);
+        }
+        Ok(nice)
+    }
+
+    /// Traffic on `port`, sampling every current listener at once so a bulk kill only waits
+    /// for one window
+    fn port_traffic(&mut self, port: u16, window: Duration) -> crate::traffic::PortTraffic {
+        let fresh = self
+            .traffic
+            .as_ref()
+            .is_some_and(|(at, sample)| at.elapsed() < Duration::from_secs(10) && sample.contains_key(&port));
+        if !fresh {
+            let mut ports: Vec<u16> = self.current_processes.keys().copied().collect();
+            if !ports.contains(&port) {
+                ports.push(port);
+            }
+            self.traffic = Some((std::time::Instant::now(), crate::traffic::measure(&ports, window)));
+        }
+        self.traffic
+            .as_ref()
+            .and_then(|(_, sample)| sample.get(&port).cloned())
+            .unwrap_or_default()
+    }
+
     pub fn get_history(&self) -> &ProcessHistory {
         &self.history
     }
@@ -1170,10 +1705,7 @@ impl ProcessMonitor {
     pub fn clear_history(&mut self) {
         self.history.clear();
         // Save empty history to file
-        if let Err(e) = self
-            .history
-            .save_to_file(&ProcessHistory::get_history_file_path())
-        {
+        if let Err(e) = self.history.persist() {
             warn!("Failed to save cleared history to file: {}", e);
         }
     }
@@ -1196,29 +1728,35 @@ impl ProcessMonitor {
 
     /// Restart a process on a specific port
     pub async fn restart_process_on_port(&mut self, port: u16) -> Result<()> {
-        info!("Attempting to restart process on port {}", port);
+        info!(port = port, action = "restart"; "Attempting to restart process on port {}", port);
 
         // First, kill any existing process on the port
-        if let Some(process_info) = self.current_processes.get(&port).cloned() {
-            self.kill_process_with_context(process_info.pid, "restart", true).await?;
-            
-            // Wait a moment for the port to be released
-            tokio::time::sleep(Duration::from_millis(1000)).await;
-        }
+        self.free_port_for_restart(port).await?;
 
         // Now restart using saved command
         match self.restart_manager.restart_port(port) {
             Ok(pid) => {
-                info!("Successfully restarted process on port {} with PID {}", port, pid);
+                info!(port = port, pid = pid, action = "restarted"; "Successfully restarted process on port {} with PID {}", port, pid);
                 Ok(())
             }
             Err(e) => {
-                error!("Failed to restart process on port {}: {}", port, e);
+                error!(port = port, action = "restart_failed"; "Failed to restart process on port {}: {}", port, e);
                 Err(e)
             }
         }
     }
 
+    /// Kill whatever is on `port` before it is restarted and give the port time to be released
+    pub async fn free_port_for_restart(&mut self, port: u16) -> Result<()> {
+        if let Some(process_info) = self.current_processes.get(&port).cloned() {
+            self.kill_process_with_context(process_info.pid, "restart", true).await?;
+            
+            // Wait a moment for the port to be released
+            tokio::time::sleep(Duration::from_millis(1000)).await;
+        }
+        Ok(())
+    }
+
     /// Get restart manager reference
     pub fn get_restart_manager(&self) -> &RestartManager {
         &self.restart_manager
@@ -1298,6 +1836,11 @@ pub fn get_processes_on_ports(
     usize,
     std::collections::HashMap<u16, crate::types::ProcessInfo>,
 ) {
+    if crate::demo::is_enabled() {
+        let processes = demo_processes(ports, args);
+        return (processes.len(), processes);
+    }
+
     // If verbose mode is enabled, use ProcessMonitor to get detailed information
     if args.verbose {
         use crossbeam_channel::bounded;
@@ -1306,6 +1849,7 @@ pub fn get_processes_on_ports(
         if let Ok(mut process_monitor) =
             ProcessMonitor::new(update_sender, ports.to_vec(), args.docker, args.verbose)
         {
+            process_monitor.set_user_filter(args.user.clone());
             // Use block_in_place to avoid runtime conflicts when already in a tokio runtime
             match tokio::task::block_in_place(|| {
                 tokio::runtime::Handle::current().block_on(process_monitor.scan_processes())
@@ -1352,6 +1896,7 @@ fn get_processes_on_ports_unix(
 ) {
     const MAX_PORTS_PER_LSOF: usize = 100;
     const LARGE_RANGE_THRESHOLD: usize = 200; // If more than 200 ports, use optimized scanning
+    crate::scan_health::begin_scan();
     
     let mut processes = std::collections::HashMap::new();
     let ports_filter: HashSet<u16> = ports.iter().copied().collect();
@@ -1369,6 +1914,7 @@ fn get_processes_on_ports_unix(
         ];
 
         let output = std::process::Command::new("lsof").args(&lsof_args).output();
+        crate::scan_health::report(crate::scan_health::check_command("lsof", &output));
 
         match output {
             Ok(output) => {
@@ -1402,6 +1948,7 @@ fn get_processes_on_ports_unix(
             }
 
             let output = std::process::Command::new("lsof").args(&lsof_args).output();
+            crate::scan_health::report(crate::scan_health::check_command("lsof", &output));
 
             match output {
                 Ok(output) => {
@@ -1433,9 +1980,28 @@ fn get_processes_on_ports_unix(
         }
     }
 
+    apply_user_filter(&mut processes, args.user.as_deref());
+    crate::system_monitor::fill_process_origins(&mut processes);
     (processes.len(), processes)
 }
 
+/// `--demo`: the fake processes on `ports`, with the same ignore and user filters a real
+/// scan applies
+fn demo_processes(
+    ports: &[u16],
+    args: &crate::cli::Args,
+) -> HashMap<u16, crate::types::ProcessInfo> {
+    crate::scan_health::begin_scan();
+    let ignore_ports = args.get_ignore_ports_set();
+    let ignore_processes = args.get_ignore_processes_set();
+    let mut processes = crate::demo::processes(ports);
+    processes.retain(|port, process| {
+        !ignore_ports.contains(port) && !ignore_processes.contains(&process.name)
+    });
+    apply_user_filter(&mut processes, args.user.as_deref());
+    processes
+}
+
 /// Returns all processes per port (multiple processes can share a port). Used by Port Guard for conflict detection.
 pub fn get_processes_on_ports_multi(
     ports: &[u16],
@@ -1444,6 +2010,13 @@ pub fn get_processes_on_ports_multi(
     if ports.is_empty() {
         return HashMap::new();
     }
+    if crate::demo::is_enabled() {
+        let ignore_ports = args.get_ignore_ports_set();
+        return crate::demo::processes_multi(ports)
+            .into_iter()
+            .filter(|(port, _)| !ignore_ports.contains(port))
+            .collect();
+    }
     #[cfg(target_os = "windows")]
     {
         get_processes_on_ports_windows_multi(ports, args)
@@ -1509,6 +2082,7 @@ fn get_processes_on_ports_unix_multi(
             }
         }
     }
+    apply_user_filter_multi(&mut processes, args.user.as_deref());
     processes
 }
 
@@ -1537,6 +2111,19 @@ fn get_processes_on_ports_windows_multi(
             );
         }
     }
+
+    let relayed = processes
+        .values()
+        .flatten()
+        .any(|p| crate::wsl_bridge::is_wsl_host_process(&p.name));
+    if args.wsl || relayed {
+        for process_info in wsl_processes(&ports_filter, &ignore_ports, &ignore_processes) {
+            let entry = processes.entry(process_info.port).or_insert_with(Vec::new);
+            entry.retain(|p| !crate::wsl_bridge::is_wsl_host_process(&p.name));
+            entry.push(process_info);
+        }
+    }
+    apply_user_filter_multi(&mut processes, args.user.as_deref());
     processes
 }
 
@@ -1554,9 +2141,11 @@ fn get_processes_on_ports_windows(
     let ignore_processes = args.get_ignore_processes_set();
 
     // On Windows, use netstat to find all listening TCP ports
+    crate::scan_health::begin_scan();
     let output = std::process::Command::new("netstat")
         .args(&["-ano", "-p", "TCP"])
         .output();
+    crate::scan_health::report(crate::scan_health::check_command("netstat", &output));
 
     match output {
         Ok(output) => {
@@ -1576,9 +2165,39 @@ fn get_processes_on_ports_windows(
         }
     }
 
+    let relayed = processes
+        .values()
+        .any(|p| crate::wsl_bridge::is_wsl_host_process(&p.name));
+    if args.wsl || relayed {
+        for process_info in wsl_processes(&ports_filter, &ignore_ports, &ignore_processes) {
+            let replace = processes
+                .get(&process_info.port)
+                .map(|existing| crate::wsl_bridge::is_wsl_host_process(&existing.name))
+                .unwrap_or(true);
+            if replace {
+                processes.insert(process_info.port, process_info);
+            }
+        }
+    }
+
+    apply_user_filter(&mut processes, args.user.as_deref());
+    crate::system_monitor::fill_process_origins(&mut processes);
     (processes.len(), processes)
 }
 
+/// Listeners inside running WSL2 distros, with the user's ignore lists applied
+#[cfg(target_os = "windows")]
+fn wsl_processes(
+    ports_filter: &HashSet<u16>,
+    ignore_ports: &HashSet<u16>,
+    ignore_processes: &HashSet<String>,
+) -> Vec<crate::types::ProcessInfo> {
+    crate::wsl_bridge::get_wsl_processes_on_ports(ports_filter)
+        .into_iter()
+        .filter(|p| !ignore_ports.contains(&p.port) && !ignore_processes.contains(&p.name))
+        .collect()
+}
+
 #[cfg(target_os = "windows")]
 fn parse_netstat_output(
     stdout: &str,
@@ -1663,6 +2282,13 @@ fn parse_netstat_output(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
         };
 
         process_info.process_group = process_info.determine_process_group();
@@ -1765,6 +2391,13 @@ fn parse_lsof_output(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            wsl_distro: None,
+            user: Some(parts[2].to_string()),
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
         };
 
         process_info.process_group = process_info.determine_process_group();
@@ -1820,6 +2453,13 @@ pub(crate) fn parse_lsof_output_multi(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            wsl_distro: None,
+            user: Some(parts[2].to_string()),
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
         };
         process_info.process_group = process_info.determine_process_group();
         process_info.project_name = process_info.extract_project_name();
@@ -1880,6 +2520,13 @@ fn parse_netstat_output_multi(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
         };
         process_info.process_group = process_info.determine_process_group();
         process_info.project_name = process_info.extract_project_name();
@@ -1889,6 +2536,9 @@ fn parse_netstat_output_multi(
 
 #[cfg(target_os = "windows")]
 pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<()> {
+    if crate::demo::is_enabled() {
+        return kill_demo_processes(ports, args);
+    }
     use std::collections::HashSet;
 
     let port_list = ports
@@ -1924,6 +2574,17 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let mut pids_to_kill = Vec::new();
+    let mut wsl_relayed = false;
+    // netstat already lists ESTABLISHED sockets alongside the listeners
+    let active_ports = if args.no_kill_if_active {
+        crate::connections::parse_netstat_established(&stdout, &ports_filter)
+    } else {
+        Default::default()
+    };
+    let traffic = match args.only_active_window() {
+        Some(window) => crate::traffic::measure(ports, window),
+        None => Default::default(),
+    };
 
     for line in stdout.lines() {
         if !line.contains("LISTENING") {
@@ -1953,9 +2614,89 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
             );
             continue;
         }
-        if !pids_to_kill.contains(&pid) {
-            pids_to_kill.push(pid);
+        // Killing the WSL relay only breaks forwarding; the real server is inside the distro
+        if crate::wsl_bridge::is_wsl_host_process(&process_name) {
+            wsl_relayed = true;
+            continue;
+        }
+        if let Some(active) = active_ports.get(&port) {
+            log::warn!(
+                "Skipping {} on port {}: {} (--no-kill-if-active)",
+                process_name,
+                port,
+                active.summary()
+            );
+            continue;
+        }
+        if args.only_active && !traffic.get(&port).is_some_and(|t| t.is_active()) {
+            log::warn!(
+                "Skipping {} on port {}: no traffic in the last {}s (--only-active)",
+                process_name,
+                port,
+                args.traffic_window
+            );
+            continue;
+        }
+        if args.user.is_some() || !args.force {
+            let owner = get_process_owner(pid);
+            if let (Some(user), Some(owner)) = (&args.user, &owner) {
+                if !crate::types::same_user(owner, user) {
+                    continue;
+                }
+            }
+            if let Err(e) = ensure_kill_confirmed(pid, owner.as_deref(), args.force, args.sudo) {
+                log::warn!("Skipping {} on port {}: {}", process_name, port, e);
+                continue;
+            }
         }
+        if let Err(e) = ensure_not_protected(pid, Some(&process_name), &args.get_protected_processes())
+            .and_then(|()| crate::safe_mode::ensure_not_system_port(port, args.safe_mode))
+        {
+            log::info!("Skipping port {}: {}", port, e);
+            continue;
+        }
+        if !pids_to_kill.iter().any(|(p, _, _)| *p == pid) {
+            pids_to_kill.push((pid, port, process_name));
+        }
+    }
+
+    let mut killed_wsl = 0usize;
+    let mut failed_wsl = 0usize;
+    if args.wsl || wsl_relayed {
+        for process_info in wsl_processes(&ports_filter, &ignore_ports, &ignore_processes) {
+            let distro = process_info.wsl_distro.clone().unwrap_or_default();
+            let result = crate::wsl_bridge::kill_wsl_process(&distro, process_info.pid);
+            crate::audit_log::record_kill(
+                process_info.pid,
+                Some(process_info.port),
+                Some(&process_info.name),
+                &result,
+            );
+            print_kill_result(
+                args,
+                process_info.pid,
+                Some(process_info.port),
+                Some(&process_info.name),
+                &result,
+            );
+            match result {
+                Ok(_) => killed_wsl += 1,
+                Err(e) => {
+                    failed_wsl += 1;
+                    log::error!(
+                        "Failed to kill process {} in WSL distro {}: {}",
+                        process_info.pid,
+                        distro,
+                        e
+                    )
+                }
+            }
+        }
+    }
+
+    if pids_to_kill.is_empty() && killed_wsl + failed_wsl > 0 {
+        log::info!("Finished killing all processes");
+        return kill_outcome(failed_wsl, killed_wsl + failed_wsl);
     }
 
     if pids_to_kill.is_empty() {
@@ -1965,20 +2706,30 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
 
     log::info!("Found {} processes to kill", pids_to_kill.len());
 
-    for pid in pids_to_kill {
-        log::info!("Attempting to kill process PID: {}", pid);
-        match kill_process(pid) {
-            Ok(_) => log::info!("Successfully killed process PID: {}", pid),
-            Err(e) => log::error!("Failed to kill process {}: {}", pid, e),
+    let total = pids_to_kill.len() + killed_wsl + failed_wsl;
+    let mut failed = failed_wsl;
+    for (pid, port, name) in pids_to_kill {
+        log::info!(port = port, pid = pid, action = "kill"; "Attempting to kill process PID: {}", pid);
+        let result = kill_process_escalating(pid, Some(port), args);
+        print_kill_result(args, pid, Some(port), Some(&name), &result);
+        match result {
+            Ok(_) => log::info!(port = port, pid = pid, action = "killed"; "Successfully killed process PID: {}", pid),
+            Err(e) => {
+                failed += 1;
+                log::error!(port = port, pid = pid, action = "kill_failed"; "Failed to kill process {}: {}", pid, e)
+            }
         }
     }
 
     log::info!("Finished killing all processes");
-    Ok(())
+    kill_outcome(failed, total)
 }
 
 #[cfg(not(target_os = "windows"))]
 pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<()> {
+    if crate::demo::is_enabled() {
+        return kill_demo_processes(ports, args);
+    }
     // Build port range string for lsof
     let port_list = ports
         .iter()
@@ -2013,6 +2764,16 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
     // Get ignore sets for efficient lookup
     let ignore_ports = args.get_ignore_ports_set();
     let ignore_processes = args.get_ignore_processes_set();
+    let protected = args.get_protected_processes();
+    let active_ports = if args.no_kill_if_active {
+        crate::connections::get_established_connections(ports)
+    } else {
+        Default::default()
+    };
+    let traffic = match args.only_active_window() {
+        Some(window) => crate::traffic::measure(ports, window),
+        None => Default::default(),
+    };
 
     let mut pids_to_kill = Vec::new();
 
@@ -2024,13 +2785,48 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
                 parts[8].split(':').last().unwrap_or("0").parse::<u16>(),
             ) {
                 let name = parts[0].to_string();
+                let owner = parts[2];
+
+                if let Some(ref user) = args.user {
+                    if !crate::types::same_user(owner, user) {
+                        continue;
+                    }
+                }
+                if let Err(e) = ensure_kill_confirmed(pid, Some(owner), args.force, args.sudo) {
+                    log::warn!("Skipping {} on port {}: {}", name, port, e);
+                    continue;
+                }
+                if let Err(e) = ensure_not_protected(pid, Some(&name), &protected)
+                    .and_then(|()| crate::safe_mode::ensure_not_system_port(port, args.safe_mode))
+                {
+                    log::info!("Skipping port {}: {}", port, e);
+                    continue;
+                }
+                if let Some(active) = active_ports.get(&port) {
+                    log::warn!(
+                        "Skipping {} on port {}: {} (--no-kill-if-active)",
+                        name,
+                        port,
+                        active.summary()
+                    );
+                    continue;
+                }
+                if args.only_active && !traffic.get(&port).is_some_and(|t| t.is_active()) {
+                    log::warn!(
+                        "Skipping {} on port {}: no traffic in the last {}s (--only-active)",
+                        name,
+                        port,
+                        args.traffic_window
+                    );
+                    continue;
+                }
 
                 // Check if this process should be ignored
                 let should_ignore =
                     ignore_ports.contains(&port) || ignore_processes.contains(&name);
 
-                if !should_ignore && !pids_to_kill.contains(&pid) {
-                    pids_to_kill.push(pid);
+                if !should_ignore && !pids_to_kill.iter().any(|(p, _, _)| *p == pid) {
+                    pids_to_kill.push((pid, port, name));
                 } else if should_ignore {
                     log::info!("Ignoring process {} (PID {}) on port {} during kill operation (ignored by user configuration)", name, pid, port);
                 }
@@ -2048,29 +2844,81 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
         pids_to_kill.len()
     );
 
-    for pid in pids_to_kill {
-        log::info!("Attempting to kill process PID: {}", pid);
-        match kill_process(pid) {
-            Ok(_) => log::info!("Successfully killed process PID: {}", pid),
-            Err(e) => log::error!("Failed to kill process {}: {}", pid, e),
+    let total = pids_to_kill.len();
+    let mut failed = 0;
+    for (pid, port, name) in pids_to_kill {
+        log::info!(port = port, pid = pid, action = "kill"; "Attempting to kill process PID: {}", pid);
+        let result = kill_process_escalating(pid, Some(port), args);
+        print_kill_result(args, pid, Some(port), Some(&name), &result);
+        match result {
+            Ok(_) => log::info!(port = port, pid = pid, action = "killed"; "Successfully killed process PID: {}", pid),
+            Err(e) => {
+                failed += 1;
+                log::error!(port = port, pid = pid, action = "kill_failed"; "Failed to kill process {}: {}", pid, e)
+            }
         }
     }
 
     log::info!("Finished killing all processes");
-    Ok(())
+    kill_outcome(failed, total)
+}
+
+/// With `--json`, print the outcome of one kill as a [`KillResult`](crate::types::KillResult) line
+fn print_kill_result(
+    args: &crate::cli::Args,
+    pid: i32,
+    port: Option<u16>,
+    name: Option<&str>,
+    result: &anyhow::Result<KillOutcome>,
+) {
+    if args.json {
+        let line = crate::types::KillResult::new(pid, port, name, result);
+        if let Ok(json) = serde_json::to_string(&line) {
+            println!("{}", json);
+        }
+    }
+}
+
+/// Turn a kill tally into `Ok` or a [`KillFailed`](crate::exit_code::KillFailed) error (exit code 3)
+pub(crate) fn kill_outcome(failed: usize, total: usize) -> anyhow::Result<()> {
+    if failed > 0 {
+        Err(crate::exit_code::KillFailed { failed, total }.into())
+    } else {
+        Ok(())
+    }
 }
 
 #[cfg(target_os = "windows")]
-pub fn kill_single_process(pid: i32, _args: &crate::cli::Args) -> anyhow::Result<()> {
-    log::info!("Killing single process PID: {}", pid);
+pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<()> {
+    log::info!(pid = pid, action = "kill"; "Killing single process PID: {}", pid);
+    if crate::demo::is_enabled() {
+        let result = kill_process_escalating(pid, None, args);
+        print_kill_result(args, pid, None, None, &result);
+        return result
+            .map(|_| ())
+            .map_err(|e| e.context(crate::exit_code::KillFailed { failed: 1, total: 1 }));
+    }
     // On Windows, simplified version - just kill the process
     // Process filtering is done at a higher level
-    kill_process(pid)
+    ensure_kill_confirmed(pid, get_process_owner(pid).as_deref(), args.force, args.sudo)?;
+    ensure_not_protected(pid, None, &args.get_protected_processes())?;
+    let result = kill_process_escalating(pid, None, args);
+    print_kill_result(args, pid, None, None, &result);
+    result
+        .map(|_| ())
+        .map_err(|e| e.context(crate::exit_code::KillFailed { failed: 1, total: 1 }))
 }
 
 #[cfg(not(target_os = "windows"))]
 pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<()> {
-    log::info!("Killing single process PID: {}", pid);
+    log::info!(pid = pid, action = "kill"; "Killing single process PID: {}", pid);
+    if crate::demo::is_enabled() {
+        let result = kill_process_escalating(pid, None, args);
+        print_kill_result(args, pid, None, None, &result);
+        return result
+            .map(|_| ())
+            .map_err(|e| e.context(crate::exit_code::KillFailed { failed: 1, total: 1 }));
+    }
 
     // Check if this process should be ignored
     let ignore_ports = args.get_ignore_ports_set();
@@ -2160,7 +3008,248 @@ pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<
     }
 
     // Process is not ignored, proceed with killing
-    kill_process(pid)
+    ensure_kill_confirmed(pid, get_process_owner(pid).as_deref(), args.force, args.sudo)?;
+    ensure_not_protected(pid, None, &args.get_protected_processes())?;
+    let result = kill_process_escalating(pid, None, args);
+    print_kill_result(args, pid, None, None, &result);
+    result
+        .map(|_| ())
+        .map_err(|e| e.context(crate::exit_code::KillFailed { failed: 1, total: 1 }))
+}
+
+/// Processes whose group is one of `groups`, by port. Shared by `--kill-group` and the
+/// trays' "Kill all <group>" items.
+pub fn processes_in_groups<'a>(
+    processes: &'a HashMap<u16, ProcessInfo>,
+    groups: &[String],
+) -> Vec<&'a ProcessInfo> {
+    let mut members: Vec<&ProcessInfo> = processes
+        .values()
+        .filter(|p| p.process_group.as_ref().is_some_and(|g| groups.contains(g)))
+        .collect();
+    members.sort_by_key(|p| p.port);
+    members
+}
+
+/// How many listeners each process group has, for the trays' "Kill all <group>" items
+pub fn group_counts(processes: &HashMap<u16, ProcessInfo>) -> std::collections::BTreeMap<String, usize> {
+    let mut counts = std::collections::BTreeMap::new();
+    for group in processes.values().filter_map(|p| p.process_group.clone()) {
+        *counts.entry(group).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Tray "Kill all <group>": kill every listener in `group` on the monitored ports. Ignore
+/// and protect lists apply as for single kills.
+pub fn kill_group_processes(group: &str, args: &crate::cli::Args) -> anyhow::Result<()> {
+    let (_, processes) = get_processes_on_ports(&args.get_ports_to_monitor(), args);
+    let members = processes_in_groups(&processes, &[group.to_string()]);
+    log::info!("Killing {} {} process(es)", members.len(), group);
+    let failed = members
+        .iter()
+        .filter(|p| {
+            crate::safe_mode::ensure_not_system_port(p.port, args.safe_mode)
+                .inspect_err(|e| log::info!("Skipping {}: {}", p.name, e))
+                .and_then(|()| kill_single_process(p.pid, args))
+                .is_err()
+        })
+        .count();
+    kill_outcome(failed, members.len())
+}
+
+/// Drop processes not owned by the `--user` filter, looking up owners the scanner didn't report
+fn apply_user_filter(processes: &mut HashMap<u16, crate::types::ProcessInfo>, user: Option<&str>) {
+    let user = match user {
+        Some(u) => u,
+        None => return,
+    };
+    for process_info in processes.values_mut() {
+        if process_info.user.is_none() && process_info.wsl_distro.is_none() {
+            process_info.user = get_process_owner(process_info.pid);
+        }
+    }
+    processes.retain(|_, p| p.is_owned_by(user));
+}
+
+fn apply_user_filter_multi(
+    processes: &mut HashMap<u16, Vec<crate::types::ProcessInfo>>,
+    user: Option<&str>,
+) {
+    let user = match user {
+        Some(u) => u,
+        None => return,
+    };
+    for list in processes.values_mut() {
+        for process_info in list.iter_mut() {
+            if process_info.user.is_none() && process_info.wsl_distro.is_none() {
+                process_info.user = get_process_owner(process_info.pid);
+            }
+        }
+        list.retain(|p| p.is_owned_by(user));
+    }
+    processes.retain(|_, list| !list.is_empty());
+}
+
+/// Name of the user running port-kill
+pub fn current_username() -> Option<String> {
+    static CURRENT_USER: std::sync::OnceLock<Option<String>> = std::sync::OnceLock::new();
+    CURRENT_USER.get_or_init(lookup_current_username).clone()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn lookup_current_username() -> Option<String> {
+    let output = std::process::Command::new("id").arg("-un").output().ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !name.is_empty() {
+        Some(name)
+    } else {
+        std::env::var("USER").ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn lookup_current_username() -> Option<String> {
+    std::env::var("USERNAME").ok()
+}
+
+/// Owning user of a process, if it can be determined
+#[cfg(not(target_os = "windows"))]
+pub fn get_process_owner(pid: i32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "user=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let owner = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !owner.is_empty() {
+        Some(owner)
+    } else {
+        None
+    }
+}
+
+/// Owning user of a process, if it can be determined
+#[cfg(target_os = "windows")]
+pub fn get_process_owner(pid: i32) -> Option<String> {
+    let output = std::process::Command::new("tasklist")
+        .args(["/V", "/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // CSV: "Image","PID","Session","Session#","Mem Usage","Status","User Name",...
+    // Mem Usage contains a thousands separator, so split on the quoted delimiter
+    let line = stdout.lines().next()?;
+    let fields: Vec<&str> = line.trim().trim_matches('"').split("\",\"").collect();
+    let owner = fields.get(6)?.trim();
+    if owner.is_empty() || owner == "N/A" {
+        None
+    } else {
+        Some(owner.to_string())
+    }
+}
+
+/// Refuse to kill a process owned by someone else unless `force` is set.
+/// Unknown owners are allowed, and root may kill anything.
+pub fn ensure_kill_allowed(pid: i32, owner: Option<&str>, force: bool) -> anyhow::Result<()> {
+    if force {
+        return Ok(());
+    }
+    let (owner, current) = match (owner, current_username()) {
+        (Some(owner), Some(current)) => (owner, current),
+        _ => return Ok(()),
+    };
+    if current == "root" || crate::types::same_user(owner, &current) {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "PID {} belongs to user '{}' (you are '{}'); use --force to kill other users' processes",
+        pid,
+        owner,
+        current
+    ))
+}
+
+/// [`ensure_kill_allowed`] for kills that may run elevated: `--sudo` gets a kill past the
+/// OS but doesn't speak for other users, so each of their processes still needs a yes on
+/// the terminal. `--force` kills them without asking. Callers that can't ask pass
+/// `sudo = false` and get the refusal.
+pub fn ensure_kill_confirmed(
+    pid: i32,
+    owner: Option<&str>,
+    force: bool,
+    sudo: bool,
+) -> anyhow::Result<()> {
+    match ensure_kill_allowed(pid, owner, force) {
+        Err(e) if sudo => {
+            let prompt = format!(
+                "🔐 PID {} belongs to user '{}'. Kill it with elevated privileges?",
+                pid,
+                owner.unwrap_or_default()
+            );
+            if crate::safe_mode::confirm(&prompt).unwrap_or(false) {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+        result => result,
+    }
+}
+
+/// Refuse to kill a process whose name is on the protected list. The name is looked up
+/// when the caller doesn't know it.
+pub fn ensure_not_protected(pid: i32, name: Option<&str>, protected: &[String]) -> anyhow::Result<()> {
+    if protected.is_empty() {
+        return Ok(());
+    }
+    let Some(name) = name
+        .map(str::to_string)
+        .or_else(|| crate::system_monitor::process_name(pid))
+    else {
+        return Ok(());
+    };
+    if protected.iter().any(|p| p.eq_ignore_ascii_case(&name)) {
+        return Err(anyhow::anyhow!(
+            "{} (PID {}) is protected; remove it from --protect / `protected` to kill it",
+            name,
+            pid
+        ));
+    }
+    Ok(())
+}
+
+/// `--demo` counterpart of [`kill_all_processes`]
+fn kill_demo_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<()> {
+    let processes = demo_processes(ports, args);
+    let total = processes.len();
+    let mut failed = 0;
+    let mut processes: Vec<_> = processes.into_values().collect();
+    processes.sort_by_key(|p| (p.port, p.pid));
+    for process in processes {
+        let result = kill_process_escalating(process.pid, Some(process.port), args);
+        print_kill_result(args, process.pid, Some(process.port), Some(&process.name), &result);
+        if result.is_err() {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow::anyhow!("Failed to kill {} of {} processes", failed, total)
+            .context(crate::exit_code::KillFailed { failed, total }));
+    }
+    Ok(())
+}
+
+/// Kill a process, retrying through sudo/UAC when it is refused and `--sudo` was given.
+/// Configured pre/post-kill hooks run around it.
+fn kill_process_escalating(
+    pid: i32,
+    port: Option<u16>,
+    args: &crate::cli::Args,
+) -> anyhow::Result<KillOutcome> {
+    crate::hooks::with_kill_hooks(pid, port, None, || match kill_process(pid) {
+        Err(e) if args.sudo && elevation::is_permission_denied(&e) => elevation::kill_elevated(pid),
+        result => result,
+    })
 }
 
 fn kill_process(pid: i32) -> anyhow::Result<()> {
@@ -2173,7 +3262,8 @@ fn kill_process(pid: i32) -> anyhow::Result<()> {
 
         // First try SIGTERM (graceful termination)
         match kill(Pid::from_raw(pid), Signal::SIGTERM) {
-            Ok(_) => log::info!("SIGTERM sent to PID: {}", pid),
+            Ok(_) => log::info!(pid = pid, action = "sigterm"; "SIGTERM sent to PID: {}", pid),
+            Err(nix::errno::Errno::EPERM) => return Err(PermissionDenied { pid }.into()),
             Err(e) => {
                 // Don't fail immediately, just log the error and continue
                 log::warn!(
@@ -2198,7 +3288,7 @@ fn kill_process(pid: i32) -> anyhow::Result<()> {
             // Process still running, send SIGKILL
             log::info!("Process {} still running, sending SIGKILL", pid);
             match kill(Pid::from_raw(pid), Signal::SIGKILL) {
-                Ok(_) => log::info!("SIGKILL sent to PID: {}", pid),
+                Ok(_) => log::info!(pid = pid, action = "sigkill"; "SIGKILL sent to PID: {}", pid),
                 Err(e) => {
                     // Log error but don't fail the entire operation
                     log::warn!(
@@ -2218,6 +3308,9 @@ fn kill_process(pid: i32) -> anyhow::Result<()> {
         use std::process::Command;
 
         log::info!("Killing process PID: {} on Windows", pid);
+        if elevation::termination_denied(pid) {
+            return Err(PermissionDenied { pid }.into());
+        }
 
         // Use taskkill to terminate the process
         let output = Command::new("taskkill")
@@ -2270,4 +3363,28 @@ python   5678 user   23u  IPv6 0x5678      0t0  TCP *:3000 (LISTEN)";
         assert!(names.contains(&"node"));
         assert!(names.contains(&"python"));
     }
+
+    #[test]
+    fn test_group_counts_and_members() {
+        let stdout = "COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 user   22u  IPv4 0x1234      0t0  TCP *:3001 (LISTEN)
+node     1235 user   22u  IPv4 0x1235      0t0  TCP *:3000 (LISTEN)
+python   5678 user   23u  IPv6 0x5678      0t0  TCP *:8000 (LISTEN)";
+        let ports_filter: HashSet<u16> = [3000, 3001, 8000].into_iter().collect();
+        let mut scanned: HashMap<u16, Vec<crate::types::ProcessInfo>> = HashMap::new();
+        parse_lsof_output_multi(stdout, &ports_filter, &HashSet::new(), &HashSet::new(), &mut scanned);
+        let processes: HashMap<u16, ProcessInfo> = scanned
+            .into_iter()
+            .map(|(port, mut list)| (port, list.remove(0)))
+            .collect();
+
+        let counts = group_counts(&processes);
+        assert_eq!(counts.get("Node.js"), Some(&2));
+        assert_eq!(counts.get("Python"), Some(&1));
+        let ports: Vec<u16> = processes_in_groups(&processes, &["Node.js".to_string()])
+            .iter()
+            .map(|p| p.port)
+            .collect();
+        assert_eq!(ports, vec![3000, 3001]);
+    }
 }
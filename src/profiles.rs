@@ -0,0 +1,294 @@
+//! Named guard profiles: several sets of ports, each with its own filters and policy,
+//! guarded side by side by one `--guard-mode --profile ...` process instead of one flat
+//! configuration per process:
+//!
+//! ```toml
+//! [profiles.work]
+//! ports = ["3000-3999"]
+//! policy = "auto-kill"                # resolve conflicts by killing the older process
+//! ignore_processes = ["Code Helper"]
+//!
+//! [profiles.infra]
+//! ports = ["5432", "6379"]
+//! policy = "protect"                  # watch only, never kill
+//! ```
+//!
+//! Each running profile writes its status to `~/.port-kill/profiles/<name>.json`, which
+//! `--status` and the tray read to show the profiles separately.
+
+use crate::cli::Args;
+use crate::config::ConfigLayer;
+use crate::types::{GuardStatus, ProcessInfo};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// What a profile's guard does about conflicts on its ports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProfilePolicy {
+    /// Kill the older process (`--auto-resolve`)
+    AutoKill,
+    /// Report conflicts, kill nothing
+    #[default]
+    Notify,
+    /// Watch only: no conflict handling, and the guard never kills
+    Protect,
+}
+
+impl ProfilePolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            ProfilePolicy::AutoKill => "auto-kill",
+            ProfilePolicy::Notify => "notify",
+            ProfilePolicy::Protect => "protect",
+        }
+    }
+}
+
+/// A `[profiles.<name>]` table
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ConfigProfile {
+    pub description: Option<String>,
+    /// Ports and ranges, e.g. `"3000"` or `"3000-3999"`
+    pub ports: Vec<String>,
+    pub policy: ProfilePolicy,
+    pub ignore_ports: Option<Vec<u16>>,
+    pub ignore_processes: Option<Vec<String>>,
+    pub ignore_patterns: Option<Vec<String>>,
+    pub ignore_groups: Option<Vec<String>>,
+    pub only_groups: Option<Vec<String>>,
+    /// Process names this profile never kills, on top of the global `protected`
+    pub protected: Option<Vec<String>>,
+}
+
+/// A profile selected to run, with its ports resolved
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub config: ConfigProfile,
+    pub ports: Vec<u16>,
+}
+
+pub(crate) fn parse_ports(specs: &[String]) -> Result<Vec<u16>> {
+    let mut ports = Vec::new();
+    for spec in specs {
+        let spec = spec.trim();
+        match spec.split_once('-') {
+            Some((start, end)) => {
+                let start: u16 = start.trim().parse().with_context(|| format!("Invalid port range '{}'", spec))?;
+                let end: u16 = end.trim().parse().with_context(|| format!("Invalid port range '{}'", spec))?;
+                if start > end {
+                    anyhow::bail!("Invalid port range '{}'", spec);
+                }
+                ports.extend(start..=end);
+            }
+            None => ports.push(spec.parse().with_context(|| format!("Invalid port '{}'", spec))?),
+        }
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    Ok(ports)
+}
+
+/// The profiles named on the command line (`all` for every configured one). A port may
+/// only belong to one running profile.
+pub fn select(config: &ConfigLayer, names: &[String]) -> Result<Vec<Profile>> {
+    if config.profiles.is_empty() {
+        anyhow::bail!("No profiles configured; add [profiles.<name>] tables to ~/.port-kill/config.toml");
+    }
+    let mut names: Vec<String> = if names.iter().any(|n| n == "all") {
+        config.profiles.keys().cloned().collect()
+    } else {
+        names.to_vec()
+    };
+    names.sort();
+    names.dedup();
+
+    let mut profiles = Vec::new();
+    let mut owner: HashMap<u16, &str> = HashMap::new();
+    for name in &names {
+        let config = config.profiles.get(name).with_context(|| {
+            let mut known: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+            known.sort();
+            format!("Unknown profile '{}' (configured: {})", name, known.join(", "))
+        })?;
+        let ports = parse_ports(&config.ports).with_context(|| format!("Profile '{}'", name))?;
+        if ports.is_empty() {
+            anyhow::bail!("Profile '{}' has no ports", name);
+        }
+        for port in &ports {
+            if let Some(other) = owner.insert(*port, name) {
+                anyhow::bail!("Port {} is in both the '{}' and '{}' profiles", port, other, name);
+            }
+        }
+        profiles.push(Profile {
+            name: name.clone(),
+            config: config.clone(),
+            ports,
+        });
+    }
+    Ok(profiles)
+}
+
+/// The configured profile a port belongs to, for grouping listeners by profile
+pub fn profile_of(config: &ConfigLayer, port: u16) -> Option<(String, ProfilePolicy)> {
+    let mut names: Vec<&String> = config.profiles.keys().collect();
+    names.sort();
+    names.into_iter().find_map(|name| {
+        let profile = &config.profiles[name];
+        parse_ports(&profile.ports)
+            .ok()?
+            .contains(&port)
+            .then(|| (name.clone(), profile.policy))
+    })
+}
+
+impl Profile {
+    /// `base` narrowed to this profile: its ports, filters and policy
+    pub fn args(&self, base: &Args) -> Args {
+        fn extend<T: Clone + PartialEq>(target: &mut Option<Vec<T>>, extra: &Option<Vec<T>>) {
+            if let Some(extra) = extra {
+                let list = target.get_or_insert_with(Vec::new);
+                for item in extra {
+                    if !list.contains(item) {
+                        list.push(item.clone());
+                    }
+                }
+            }
+        }
+
+        let mut args = base.clone();
+        let ports: Vec<String> = self.ports.iter().map(u16::to_string).collect();
+        args.guard_mode = true;
+        args.guard_ports = ports.join(",");
+        args.ports = Some(ports);
+        args.auto_resolve = self.config.policy == ProfilePolicy::AutoKill;
+        // The profile's policy is the default for its ports
+        args.on_conflict = None;
+        args.guard_auto_restart = args.guard_auto_restart && self.config.policy != ProfilePolicy::Protect;
+        extend(&mut args.ignore_ports, &self.config.ignore_ports);
+        extend(&mut args.ignore_processes, &self.config.ignore_processes);
+        extend(&mut args.ignore_patterns, &self.config.ignore_patterns);
+        extend(&mut args.ignore_groups, &self.config.ignore_groups);
+        extend(&mut args.only_groups, &self.config.only_groups);
+        extend(&mut args.protect, &self.config.protected);
+        args
+    }
+
+    /// "3000-3999" or "5432, 6379"
+    pub fn describe_ports(&self) -> String {
+        self.config.ports.join(", ")
+    }
+}
+
+/// What a running profile last reported, in `~/.port-kill/profiles/<name>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileStatus {
+    pub name: String,
+    pub policy: ProfilePolicy,
+    pub ports: String,
+    pub pid: u32,
+    pub updated_at: DateTime<Utc>,
+    pub guard: GuardStatus,
+    /// (port, pid, name) of each listener on the profile's ports
+    pub listeners: Vec<(u16, i32, String)>,
+}
+
+impl ProfileStatus {
+    pub fn new(profile: &Profile, guard: GuardStatus, processes: &HashMap<u16, ProcessInfo>) -> Self {
+        let mut listeners: Vec<(u16, i32, String)> = processes
+            .values()
+            .map(|p| (p.port, p.pid, p.name.clone()))
+            .collect();
+        listeners.sort();
+        Self {
+            name: profile.name.clone(),
+            policy: profile.config.policy,
+            ports: profile.describe_ports(),
+            pid: std::process::id(),
+            updated_at: Utc::now(),
+            guard,
+            listeners,
+        }
+    }
+
+    /// Still reporting: the status is rewritten every few seconds while the guard runs
+    pub fn is_live(&self) -> bool {
+        Utc::now() - self.updated_at < chrono::Duration::seconds(30)
+    }
+}
+
+fn status_dir() -> PathBuf {
+    crate::uninstall::home_dir().join(".port-kill").join("profiles")
+}
+
+pub fn write_status(status: &ProfileStatus) -> Result<()> {
+    let dir = status_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.json", status.name));
+    std::fs::write(&path, serde_json::to_string_pretty(status)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn remove_status(name: &str) {
+    let _ = std::fs::remove_file(status_dir().join(format!("{}.json", name)));
+}
+
+/// Every profile status on disk, by name
+pub fn read_statuses() -> Vec<ProfileStatus> {
+    let Ok(entries) = std::fs::read_dir(status_dir()) else {
+        return Vec::new();
+    };
+    let mut statuses: Vec<ProfileStatus> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| serde_json::from_str(&std::fs::read_to_string(entry.path()).ok()?).ok())
+        .collect();
+    statuses.sort_by(|a, b| a.name.cmp(&b.name));
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_select_profiles_and_narrow_args() {
+        let config: ConfigLayer = toml::from_str(
+            r#"
+            [profiles.work]
+            ports = ["3000-3999"]
+            policy = "auto-kill"
+            ignore_processes = ["Code Helper"]
+
+            [profiles.infra]
+            ports = ["5432", "6379"]
+            policy = "protect"
+
+            [profiles.overlap]
+            ports = ["3000"]
+            "#,
+        )
+        .unwrap();
+
+        let profiles = select(&config, &["work".into(), "infra".into()]).unwrap();
+        assert_eq!(profiles[0].name, "infra");
+        assert_eq!(profiles[1].ports.len(), 1000);
+        assert!(select(&config, &["all".into()]).is_err()); // overlap shares port 3000
+        assert!(select(&config, &["nope".into()]).is_err());
+        assert_eq!(profile_of(&config, 6379).unwrap().1, ProfilePolicy::Protect);
+
+        let base = Args::parse_from(["port-kill", "--guard-auto-restart"]);
+        let infra = profiles[0].args(&base);
+        assert_eq!(infra.get_guard_ports(), vec![5432, 6379]);
+        assert!(!infra.auto_resolve && !infra.guard_auto_restart);
+        let work = profiles[1].args(&base);
+        assert!(work.auto_resolve);
+        assert_eq!(work.ignore_processes, Some(vec!["Code Helper".to_string()]));
+    }
+}
@@ -0,0 +1,398 @@
+//! A small filter language over scanned processes and history entries, used by
+//! `port-kill query`:
+//!
+//! ```text
+//! group=Node.js and cpu>50 and project!=infra
+//! (port=3000-3999 or name~vite) and not user=root
+//! name=*webpack* and uptime>3600
+//! ```
+//!
+//! Comparisons are `=`, `!=`, `>`, `>=`, `<`, `<=` and `~` (contains). String matches are
+//! case-insensitive and `=` accepts `*`/`?` wildcards; numeric `=` accepts a range like
+//! `3000-3999`. A comparison on a field the process doesn't have (no project, no CPU
+//! sample) is false, except `!=`, which is true.
+
+use crate::types::{ProcessHistoryEntry, ProcessInfo};
+use anyhow::Result;
+
+/// Fields a query can compare, with what they mean
+pub const FIELDS: &[(&str, &str)] = &[
+    ("port", "listening port"),
+    ("pid", "process ID"),
+    ("ppid", "parent process ID"),
+    ("name", "process name"),
+    ("command", "full command line"),
+    ("group", "process group, e.g. Node.js"),
+    ("project", "project name"),
+    ("dir", "working directory"),
+    ("user", "owning user"),
+    ("uid", "owning user ID (Unix)"),
+    ("container", "Docker container name"),
+    ("cpu", "CPU usage in percent"),
+    ("mem", "memory in MB"),
+    ("gpu", "GPU memory in MB"),
+    ("uptime", "seconds running"),
+    ("killed_by", "history only: user, bulk, auto, ..."),
+    ("initiator", "history only: cli, tui, guard, ..."),
+    ("host", "history only: the machine it happened on"),
+];
+
+/// A field's value on one process or history entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Number(f64),
+    Text(String),
+}
+
+/// Something a query can be evaluated against
+pub trait Queryable {
+    fn field(&self, name: &str) -> Option<FieldValue>;
+}
+
+fn text(value: &Option<String>) -> Option<FieldValue> {
+    value.clone().map(FieldValue::Text)
+}
+
+fn mb(bytes: Option<u64>) -> Option<FieldValue> {
+    bytes.map(|b| FieldValue::Number(b as f64 / 1024.0 / 1024.0))
+}
+
+impl Queryable for ProcessInfo {
+    fn field(&self, name: &str) -> Option<FieldValue> {
+        match name {
+            "port" => Some(FieldValue::Number(self.port as f64)),
+            "pid" => Some(FieldValue::Number(self.pid as f64)),
+            "ppid" => self.ppid.map(|p| FieldValue::Number(p as f64)),
+            "name" => Some(FieldValue::Text(self.name.clone())),
+            "command" => text(&self.command_line).or(Some(FieldValue::Text(self.command.clone()))),
+            "group" => text(&self.process_group),
+            "project" => text(&self.project_name),
+            "dir" => text(&self.working_directory),
+            "user" => text(&self.user),
+            "uid" => self.uid.map(|u| FieldValue::Number(u as f64)),
+            "container" => text(&self.container_name),
+            "cpu" => self.cpu_usage.map(FieldValue::Number),
+            "mem" => mb(self.memory_usage),
+            "gpu" => mb(self.gpu_memory),
+            "uptime" => self.uptime_secs.map(|s| FieldValue::Number(s as f64)),
+            _ => None,
+        }
+    }
+}
+
+impl Queryable for ProcessHistoryEntry {
+    fn field(&self, name: &str) -> Option<FieldValue> {
+        match name {
+            "port" => Some(FieldValue::Number(self.port as f64)),
+            "pid" => Some(FieldValue::Number(self.pid as f64)),
+            "name" => Some(FieldValue::Text(self.process_name.clone())),
+            "command" => text(&self.command_line),
+            "group" => text(&self.process_group),
+            "project" => text(&self.project_name),
+            "dir" => text(&self.working_directory),
+            "killed_by" => Some(FieldValue::Text(self.killed_by.clone())),
+            "initiator" => self.initiator_name().map(FieldValue::Text),
+            "host" => text(&self.hostname),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Compare { field: String, op: Op, value: String },
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '=' | '!' | '>' | '<' | '~' => {
+                chars.next();
+                let eq = chars.peek() == Some(&'=');
+                let op = match (c, eq) {
+                    ('=', _) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    ('>', true) => Op::Ge,
+                    ('>', false) => Op::Gt,
+                    ('<', true) => Op::Le,
+                    ('<', false) => Op::Lt,
+                    ('~', _) => Op::Contains,
+                    _ => anyhow::bail!("Expected '!=' in query"),
+                };
+                if eq && c != '=' && c != '~' {
+                    chars.next();
+                } else if c == '=' && eq {
+                    chars.next(); // accept `==`
+                }
+                tokens.push(Token::Op(op));
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some(other) => word.push(other),
+                        None => anyhow::bail!("Unterminated quote in query"),
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()=!<>~\"'".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Query> {
+        let mut left = self.and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            left = Query::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Query> {
+        let mut left = self.not()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            left = Query::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Query> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Query::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Query> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let query = self.or()?;
+                if self.tokens.get(self.pos) != Some(&Token::Close) {
+                    anyhow::bail!("Missing ')' in query");
+                }
+                self.pos += 1;
+                Ok(query)
+            }
+            Some(Token::Word(field)) => {
+                let field = field.to_lowercase();
+                if !FIELDS.iter().any(|(name, _)| *name == field) {
+                    let names: Vec<&str> = FIELDS.iter().map(|(name, _)| *name).collect();
+                    anyhow::bail!("Unknown field '{}' (fields: {})", field, names.join(", "));
+                }
+                let Some(Token::Op(op)) = self.tokens.get(self.pos + 1).cloned() else {
+                    anyhow::bail!("Expected a comparison after '{}', e.g. {}=value", field, field);
+                };
+                let Some(Token::Word(value)) = self.tokens.get(self.pos + 2).cloned() else {
+                    anyhow::bail!("Expected a value after '{}'", field);
+                };
+                self.pos += 3;
+                Ok(Query::Compare { field, op, value })
+            }
+            Some(token) => anyhow::bail!("Unexpected {:?} in query", token),
+            None => anyhow::bail!("Query ended early"),
+        }
+    }
+}
+
+/// Case-insensitive `*`/`?` wildcard match
+fn wildcard(pattern: &str, text: &str) -> bool {
+    fn matches(p: &[char], t: &[char]) -> bool {
+        match p.split_first() {
+            None => t.is_empty(),
+            Some(('*', rest)) => (0..=t.len()).any(|i| matches(rest, &t[i..])),
+            Some(('?', rest)) => !t.is_empty() && matches(rest, &t[1..]),
+            Some((c, rest)) => t.first() == Some(c) && matches(rest, &t[1..]),
+        }
+    }
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    matches(&p, &t)
+}
+
+fn compare(actual: Option<FieldValue>, op: Op, expected: &str) -> bool {
+    let Some(actual) = actual else {
+        return op == Op::Ne;
+    };
+    match actual {
+        FieldValue::Number(n) => {
+            if op == Op::Eq || op == Op::Ne {
+                let equal = match expected.split_once('-').filter(|(start, _)| !start.is_empty()) {
+                    Some((start, end)) => match (start.parse::<f64>(), end.parse::<f64>()) {
+                        (Ok(start), Ok(end)) => (start..=end).contains(&n),
+                        _ => false,
+                    },
+                    None => expected.parse::<f64>().is_ok_and(|e| e == n),
+                };
+                return equal == (op == Op::Eq);
+            }
+            let Ok(expected) = expected.parse::<f64>() else {
+                return false;
+            };
+            match op {
+                Op::Gt => n > expected,
+                Op::Ge => n >= expected,
+                Op::Lt => n < expected,
+                Op::Le => n <= expected,
+                Op::Contains => n.to_string().contains(&expected.to_string()),
+                Op::Eq | Op::Ne => unreachable!(),
+            }
+        }
+        FieldValue::Text(s) => match op {
+            Op::Eq => wildcard(expected, &s),
+            Op::Ne => !wildcard(expected, &s),
+            Op::Contains => s.to_lowercase().contains(&expected.to_lowercase()),
+            Op::Gt => s.to_lowercase() > expected.to_lowercase(),
+            Op::Ge => s.to_lowercase() >= expected.to_lowercase(),
+            Op::Lt => s.to_lowercase() < expected.to_lowercase(),
+            Op::Le => s.to_lowercase() <= expected.to_lowercase(),
+        },
+    }
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        if parser.tokens.is_empty() {
+            anyhow::bail!("Empty query");
+        }
+        let query = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            anyhow::bail!("Unexpected {:?} in query (join conditions with 'and' / 'or')", token);
+        }
+        Ok(query)
+    }
+
+    pub fn matches(&self, item: &impl Queryable) -> bool {
+        match self {
+            Query::Compare { field, op, value } => compare(item.field(field), *op, value),
+            Query::And(a, b) => a.matches(item) && b.matches(item),
+            Query::Or(a, b) => a.matches(item) || b.matches(item),
+            Query::Not(q) => !q.matches(item),
+        }
+    }
+
+    /// Whether the query needs CPU or memory samples from the scan
+    pub fn needs_metrics(&self) -> bool {
+        match self {
+            Query::Compare { field, .. } => matches!(field.as_str(), "cpu" | "mem"),
+            Query::And(a, b) | Query::Or(a, b) => a.needs_metrics() || b.needs_metrics(),
+            Query::Not(q) => q.needs_metrics(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(port: u16, name: &str, group: &str, cpu: f64, project: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            pid: port as i32,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: Some(group.to_string()),
+            project_name: project.map(str::to_string),
+            cpu_usage: Some(cpu),
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_match_queries() {
+        let vite = process(5173, "node", "Node.js", 72.0, Some("web"));
+        let infra = process(3000, "node", "Node.js", 90.0, Some("infra"));
+        let db = process(5432, "postgres", "Database", 3.0, None);
+
+        let query = Query::parse("group=node.js and cpu>50 and project!=infra").unwrap();
+        assert!(query.matches(&vite));
+        assert!(!query.matches(&infra));
+        assert!(!query.matches(&db));
+        assert!(query.needs_metrics());
+
+        let query = Query::parse("(port=3000-3999 or name~gres) and not project=web").unwrap();
+        assert!(query.matches(&infra) && query.matches(&db) && !query.matches(&vite));
+        assert!(Query::parse("name='post*'").unwrap().matches(&db));
+        // A missing field only satisfies !=
+        assert!(Query::parse("project!=web").unwrap().matches(&db));
+        assert!(!Query::parse("project=*").unwrap().matches(&db));
+
+        assert!(Query::parse("colour=red").is_err());
+        assert!(Query::parse("cpu>50 port=3000").is_err());
+        assert!(Query::parse("(cpu>50").is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::cli::Args;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Outcome of asking the limiter whether an automated kill may go ahead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillDecision {
+    Allow,
+    /// The port was auto-killed recently; holds the remaining cooldown
+    PortCooldown(Duration),
+    /// The global budget of automated kills for the current window is used up
+    RateLimited,
+}
+
+/// Per-port cooldown and global rate limit for automated kills (guard mode, script guards).
+/// Once the global limit is hit, callers fall back to notify-only until the window drains,
+/// so a bad rule can't fight a process supervisor in a tight loop.
+#[derive(Debug, Clone)]
+pub struct KillRateLimiter {
+    port_cooldown: Duration,
+    max_kills: usize,
+    window: Duration,
+    last_kill: HashMap<u16, Instant>,
+    recent_kills: VecDeque<Instant>,
+    tripped: bool,
+}
+
+impl KillRateLimiter {
+    /// A zero cooldown or a `max_kills` of 0 disables that particular limit
+    pub fn new(port_cooldown: Duration, max_kills: usize, window: Duration) -> Self {
+        Self {
+            port_cooldown,
+            max_kills,
+            window,
+            last_kill: HashMap::new(),
+            recent_kills: VecDeque::new(),
+            tripped: false,
+        }
+    }
+
+    pub fn from_args(args: &Args) -> Self {
+        Self::new(
+            Duration::from_secs(args.auto_kill_cooldown),
+            args.max_auto_kills,
+            Duration::from_secs(args.auto_kill_window),
+        )
+    }
+
+    /// Check whether an automated kill on `port` is allowed right now
+    pub fn check(&mut self, port: u16) -> KillDecision {
+        self.check_at(port, Instant::now())
+    }
+
+    /// Record that an automated kill on `port` happened
+    pub fn record(&mut self, port: u16) {
+        self.record_at(port, Instant::now());
+    }
+
+    /// True while the global limit is exhausted and automated kills are notify-only
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /// Human-readable description of the configured limits
+    pub fn describe(&self) -> String {
+        format!(
+            "max {} automated kills per {}s, {}s cooldown per port",
+            self.max_kills,
+            self.window.as_secs(),
+            self.port_cooldown.as_secs()
+        )
+    }
+
+    fn check_at(&mut self, port: u16, now: Instant) -> KillDecision {
+        self.prune(now);
+
+        if self.max_kills > 0 && self.recent_kills.len() >= self.max_kills {
+            self.tripped = true;
+            return KillDecision::RateLimited;
+        }
+        self.tripped = false;
+
+        if let Some(last) = self.last_kill.get(&port) {
+            let elapsed = now.saturating_duration_since(*last);
+            if elapsed < self.port_cooldown {
+                return KillDecision::PortCooldown(self.port_cooldown - elapsed);
+            }
+        }
+
+        KillDecision::Allow
+    }
+
+    fn record_at(&mut self, port: u16, now: Instant) {
+        self.last_kill.insert(port, now);
+        self.recent_kills.push_back(now);
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some(oldest) = self.recent_kills.front() {
+            if now.saturating_duration_since(*oldest) >= self.window {
+                self.recent_kills.pop_front();
+            } else {
+                break;
+            }
+        }
+        let cooldown = self.port_cooldown;
+        self.last_kill
+            .retain(|_, last| now.saturating_duration_since(*last) < cooldown);
+    }
+}
+
+impl Default for KillRateLimiter {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), 10, Duration::from_secs(600))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_cooldown() {
+        let mut limiter =
+            KillRateLimiter::new(Duration::from_secs(30), 10, Duration::from_secs(600));
+        let start = Instant::now();
+
+        assert_eq!(limiter.check_at(3000, start), KillDecision::Allow);
+        limiter.record_at(3000, start);
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(
+            limiter.check_at(3000, later),
+            KillDecision::PortCooldown(Duration::from_secs(20))
+        );
+        // Other ports are unaffected
+        assert_eq!(limiter.check_at(3001, later), KillDecision::Allow);
+
+        let after_cooldown = start + Duration::from_secs(31);
+        assert_eq!(limiter.check_at(3000, after_cooldown), KillDecision::Allow);
+    }
+
+    #[test]
+    fn test_global_limit_trips_and_recovers() {
+        let mut limiter = KillRateLimiter::new(Duration::ZERO, 3, Duration::from_secs(60));
+        let start = Instant::now();
+
+        for i in 0..3 {
+            let now = start + Duration::from_secs(i);
+            assert_eq!(limiter.check_at(3000 + i as u16, now), KillDecision::Allow);
+            limiter.record_at(3000 + i as u16, now);
+        }
+
+        let now = start + Duration::from_secs(5);
+        assert_eq!(limiter.check_at(4000, now), KillDecision::RateLimited);
+        assert!(limiter.is_tripped());
+
+        let drained = start + Duration::from_secs(61);
+        assert_eq!(limiter.check_at(4000, drained), KillDecision::Allow);
+        assert!(!limiter.is_tripped());
+    }
+
+    #[test]
+    fn test_zero_disables_limits() {
+        let mut limiter = KillRateLimiter::new(Duration::ZERO, 0, Duration::from_secs(60));
+        let now = Instant::now();
+        for _ in 0..50 {
+            assert_eq!(limiter.check_at(3000, now), KillDecision::Allow);
+            limiter.record_at(3000, now);
+        }
+    }
+}
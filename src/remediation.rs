@@ -0,0 +1,328 @@
+//! `--show-root-cause --apply` (or `--analyze --apply`): carry out what the root cause
+//! analysis recommends, one confirmation at a time:
+//!
+//! - processes killed over and over, or that restart as soon as they are killed, are added
+//!   to the ignore lists of the global config file (as `--ignore add` would)
+//! - ports several processes collide on are saved as a preset, to watch them together
+//! - a port the guard keeps killing the same process on is reserved for that process's
+//!   project, so the guard leaves it alone
+//!
+//! Nothing is changed without a "y".
+
+use crate::config::ConfigLayer;
+use crate::preset_manager::{PortPreset, PresetManager};
+use crate::types::{ConflictType, ProcessHistory};
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+
+/// The name of the preset of colliding ports; `-2`, `-3`... when it is taken
+const CONFLICTS_PRESET: &str = "conflicts";
+
+/// One recommendation that can be applied
+#[derive(Debug, Clone, PartialEq)]
+pub enum Remedy {
+    /// Add `value` to the config list `key`, e.g. `ignore_processes`
+    Ignore {
+        key: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// Save `ports` as a user preset
+    Preset { name: String, ports: Vec<u16> },
+    /// Reserve `port` for `project` with the port guard
+    Reserve {
+        port: u16,
+        project: String,
+        process: String,
+        guard_kills: usize,
+    },
+}
+
+impl Remedy {
+    pub fn describe(&self) -> String {
+        match self {
+            Remedy::Ignore { key, value, reason } => {
+                format!("Add {} to {} ({})", value, key, reason)
+            }
+            Remedy::Preset { name, ports } => format!(
+                "Save ports {} as preset '{}' (several processes collide on them)",
+                crate::port_spec::describe(ports),
+                name
+            ),
+            Remedy::Reserve {
+                port,
+                project,
+                process,
+                guard_kills,
+            } => format!(
+                "Reserve port {} for project '{}' (the guard killed {} there {} times)",
+                port, project, process, guard_kills
+            ),
+        }
+    }
+
+    /// Carry it out; returns what was done
+    pub fn apply(&self, reservation_file: &str) -> Result<String> {
+        match self {
+            Remedy::Ignore { key, value, .. } => {
+                let kind = crate::ignore_list::kind_label(key);
+                let (path, _) = crate::ignore_list::add(kind, std::slice::from_ref(value))?;
+                Ok(format!(
+                    "🙈 Added {} to {} in {}",
+                    value,
+                    key,
+                    path.display()
+                ))
+            }
+            Remedy::Preset { name, ports } => {
+                let mut manager = PresetManager::new();
+                manager.load_presets()?;
+                manager.add_preset(PortPreset::new(
+                    name.clone(),
+                    "Ports with colliding processes, from --show-root-cause".to_string(),
+                    ports.clone(),
+                ));
+                manager.save_presets()?;
+                Ok(format!(
+                    "✅ Saved preset '{}'; watch it with --preset {}",
+                    name, name
+                ))
+            }
+            Remedy::Reserve {
+                port,
+                project,
+                process,
+                ..
+            } => {
+                crate::reservations::reserve(
+                    std::path::Path::new(reservation_file),
+                    crate::reservations::new_reservation(
+                        *port,
+                        project.clone(),
+                        process.clone(),
+                        Some(chrono::Duration::hours(
+                            crate::reservations::DEFAULT_TERM_HOURS,
+                        )),
+                        true,
+                    ),
+                )?;
+                Ok(format!(
+                    "🔒 Reserved port {} for '{}' in {}",
+                    port, project, reservation_file
+                ))
+            }
+        }
+    }
+}
+
+/// What the analysis of `history` recommends that isn't done yet, given the current
+/// `config` and the names of the existing `presets`
+pub fn remedies(history: &ProcessHistory, config: &ConfigLayer, presets: &[String]) -> Vec<Remedy> {
+    let analysis = history.get_root_cause_analysis();
+    let mut remedies: Vec<Remedy> = Vec::new();
+
+    let suggestions = history.get_ignore_suggestions(2);
+    for (key, value) in crate::ignore_list::new_suggestions(&suggestions, config) {
+        remedies.push(Remedy::Ignore {
+            key,
+            value,
+            reason: "killed repeatedly".to_string(),
+        });
+    }
+    let restarting = analysis
+        .conflicts
+        .iter()
+        .filter(|conflict| matches!(conflict.conflict_type, ConflictType::AutoRestart))
+        .flat_map(|conflict| conflict.conflicting_processes.iter());
+    for name in restarting {
+        let ignored = config.ignore_processes.iter().flatten().any(|p| p == name);
+        let listed = remedies.iter().any(|remedy| {
+            matches!(remedy, Remedy::Ignore { key: "ignore_processes", value, .. } if value == name)
+        });
+        if !ignored && !listed {
+            remedies.push(Remedy::Ignore {
+                key: "ignore_processes",
+                value: name.clone(),
+                reason: "it restarts as soon as it is killed".to_string(),
+            });
+        }
+    }
+
+    let mut colliding: Vec<u16> = analysis
+        .conflicts
+        .iter()
+        .filter(|conflict| matches!(conflict.conflict_type, ConflictType::PortCollision))
+        .map(|conflict| conflict.port)
+        .collect();
+    if !colliding.is_empty() {
+        colliding.sort_unstable();
+        let name = (1..)
+            .map(|n| match n {
+                1 => CONFLICTS_PRESET.to_string(),
+                n => format!("{}-{}", CONFLICTS_PRESET, n),
+            })
+            .find(|name| !presets.contains(name))
+            .unwrap_or_default();
+        remedies.push(Remedy::Preset {
+            name,
+            ports: colliding,
+        });
+    }
+
+    remedies.extend(reservations(history));
+    remedies
+}
+
+/// A reservation for each process the guard killed at least twice on one port, for the
+/// project it was most often part of
+fn reservations(history: &ProcessHistory) -> Vec<Remedy> {
+    // The projects of each guard kill, by port and process
+    let mut guard_kills: BTreeMap<(u16, &str), Vec<Option<&str>>> = BTreeMap::new();
+    for entry in history.kills() {
+        if entry.initiator == Some(crate::audit_log::AuditInitiator::Guard) {
+            guard_kills
+                .entry((entry.port, entry.process_name.as_str()))
+                .or_default()
+                .push(entry.project_name.as_deref());
+        }
+    }
+    guard_kills
+        .into_iter()
+        .filter(|(_, kills)| kills.len() >= 2)
+        .map(|((port, process), kills)| {
+            let mut projects: HashMap<&str, usize> = HashMap::new();
+            for project in kills.iter().flatten() {
+                *projects.entry(project).or_insert(0) += 1;
+            }
+            let project = projects
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+                .map_or(process, |(project, _)| project);
+            Remedy::Reserve {
+                port,
+                project: project.to_string(),
+                process: process.to_string(),
+                guard_kills: kills.len(),
+            }
+        })
+        .collect()
+}
+
+/// Offer each remedy for `history` in turn and apply the confirmed ones
+pub fn command(history: &ProcessHistory, reservation_file: &str) -> Result<()> {
+    let config = crate::config::load()?.config;
+    let mut manager = PresetManager::new();
+    manager.load_presets()?;
+    let remedies = remedies(history, &config, &manager.get_preset_names());
+    if remedies.is_empty() {
+        println!("✅ Nothing to apply: the recommendations are already in place");
+        return Ok(());
+    }
+
+    println!("🔧 {} recommendation(s) can be applied:", remedies.len());
+    let mut applied = 0;
+    for (i, remedy) in remedies.iter().enumerate() {
+        println!();
+        if !crate::safe_mode::confirm(&format!("{}. {}?", i + 1, remedy.describe()))? {
+            println!("   Skipped.");
+            continue;
+        }
+        match remedy.apply(reservation_file) {
+            Ok(done) => {
+                println!("   {}", done);
+                applied += 1;
+            }
+            Err(e) => println!("   ❌ {:#}", e),
+        }
+    }
+    println!();
+    println!(
+        "Applied {} of {} recommendation(s).",
+        applied,
+        remedies.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit_log::AuditInitiator;
+    use crate::types::{ProcessHistoryEntry, ProcessInfo};
+    use chrono::{Duration, Utc};
+
+    fn process(name: &str, port: u16, project: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            pid: 100,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: project.map(str::to_string),
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        }
+    }
+
+    #[test]
+    fn test_remedies() {
+        let mut history = ProcessHistory::new(100);
+        // Days apart, so nothing looks like an auto-restart
+        let kills = [
+            ("node", 3000, Some("web"), false, 1),
+            ("python", 3000, None, false, 2),
+            ("vite", 5173, Some("docs"), true, 3),
+            ("vite", 5173, Some("docs"), true, 4),
+        ];
+        for (name, port, project, guard, days_ago) in kills {
+            let mut entry = ProcessHistoryEntry::new(&process(name, port, project), "user".into());
+            if guard {
+                entry = entry.initiated_by(AuditInitiator::Guard);
+            }
+            entry.killed_at = Utc::now() - Duration::days(days_ago);
+            history.add_entry(entry);
+        }
+
+        let config = ConfigLayer {
+            ignore_ports: Some(vec![5173]),
+            ..Default::default()
+        };
+        let remedies = remedies(&history, &config, &["conflicts".to_string()]);
+        assert_eq!(
+            remedies,
+            vec![
+                Remedy::Ignore {
+                    key: "ignore_processes",
+                    value: "vite".to_string(),
+                    reason: "killed repeatedly".to_string()
+                },
+                Remedy::Preset {
+                    name: "conflicts-2".to_string(),
+                    ports: vec![3000]
+                },
+                Remedy::Reserve {
+                    port: 5173,
+                    project: "docs".to_string(),
+                    process: "vite".to_string(),
+                    guard_kills: 2
+                },
+            ]
+        );
+        assert_eq!(
+            remedies[2].describe(),
+            "Reserve port 5173 for project 'docs' (the guard killed vite there 2 times)"
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! Port reservations: a port kept for one project's process, which the guard
+//! (`--guard-mode`) enforces by treating anything else that binds it as a conflict.
+//! They live in `~/.port-kill/reservations.json` (`--reservation-file`) and are managed
+//! without a guard running:
+//!
+//! ```text
+//! port-kill-console --reserve 3000 --process-name node --project-name web
+//! port-kill-console --reserve 5432 --process-name postgres --expires-in never
+//! port-kill-console --unreserve 3000
+//! port-kill-console --reservations
+//! ```
+//!
+//! A running guard re-reads the file when it changes. Each pass it drops expired
+//! reservations and extends auto-renewing ones (the default) by another term, so those
+//! last as long as the guard keeps running.
+//!
+//! A repository can commit a [`SHARED_FILE`] saying which ports belong to which of its
+//! services, so everyone who runs the guard in it enforces the same reservations:
+//!
+//! ```toml
+//! [services.web]
+//! port = 3000
+//! process = "node"
+//!
+//! [services.api]
+//! ports = [8080, 8081]
+//! process = "java"
+//! on_conflict = "notify"              # kill, notify, reassign or block
+//! ```
+//!
+//! Those don't expire, and a reservation of the same port in the reservation file takes
+//! precedence on that machine.
+
+use crate::types::{PortReservation, PortResolution};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The reservations file a repository commits, found by walking up from the current directory
+pub const SHARED_FILE: &str = ".portkill-reservations.toml";
+
+/// How long a reservation lasts unless `--expires-in` says otherwise
+pub const DEFAULT_TERM_HOURS: i64 = 24;
+
+/// The reservations in `path`, none when it doesn't exist yet
+pub fn load(path: &Path) -> Result<HashMap<u16, PortReservation>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+    crate::state_format::decode_map(&content)
+        .with_context(|| format!("Could not parse {}", path.display()))
+}
+
+/// Replace the reservations in `path`
+pub fn save(path: &Path, reservations: &HashMap<u16, PortReservation>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(reservations)?)?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// A reservation of `port` starting now; `term` None never expires
+pub fn new_reservation(
+    port: u16,
+    project_name: String,
+    process_name: String,
+    term: Option<Duration>,
+    auto_renew: bool,
+) -> PortReservation {
+    let now = Utc::now();
+    PortReservation {
+        port,
+        project_name,
+        process_name,
+        reserved_at: now,
+        expires_at: term.map(|term| now + term),
+        auto_renew,
+        renewed_at: None,
+        on_conflict: None,
+        meta: Default::default(),
+    }
+}
+
+/// Add `reservation` to `path`, replacing any for the same port; returns the one replaced
+pub fn reserve(path: &Path, reservation: PortReservation) -> Result<Option<PortReservation>> {
+    let mut reservations = load(path)?;
+    let replaced = reservations.insert(reservation.port, reservation);
+    save(path, &reservations)?;
+    Ok(replaced)
+}
+
+/// Remove the reservation of `port` from `path`
+pub fn unreserve(path: &Path, port: u16) -> Result<Option<PortReservation>> {
+    let mut reservations = load(path)?;
+    let removed = reservations.remove(&port);
+    if removed.is_some() {
+        save(path, &reservations)?;
+    }
+    Ok(removed)
+}
+
+/// What [`expire`] changed, by port
+#[derive(Debug, Default)]
+pub struct Lifecycle {
+    pub expired: Vec<PortReservation>,
+    pub renewed: Vec<u16>,
+}
+
+impl Lifecycle {
+    pub fn is_empty(&self) -> bool {
+        self.expired.is_empty() && self.renewed.is_empty()
+    }
+}
+
+/// The length of one term of `reservation`: from when it was made, or last renewed, to
+/// when it expires
+fn term(reservation: &PortReservation) -> Option<Duration> {
+    let start = reservation.renewed_at.unwrap_or(reservation.reserved_at);
+    reservation.expires_at.map(|expires_at| expires_at - start)
+}
+
+/// Drop the reservations expired at `now` and extend the auto-renewing ones by another term
+pub fn expire(reservations: &mut HashMap<u16, PortReservation>, now: DateTime<Utc>) -> Lifecycle {
+    let mut lifecycle = Lifecycle::default();
+    let mut ports: Vec<u16> = reservations.keys().copied().collect();
+    ports.sort_unstable();
+    for port in ports {
+        let reservation = reservations.get_mut(&port).expect("listed above");
+        let Some(expires_at) = reservation.expires_at else {
+            continue;
+        };
+        if expires_at > now {
+            continue;
+        }
+        match term(reservation).filter(|term| *term > Duration::zero()) {
+            Some(term) if reservation.auto_renew => {
+                reservation.expires_at = Some(now + term);
+                reservation.renewed_at = Some(now);
+                lifecycle.renewed.push(port);
+            }
+            _ => lifecycle.expired.extend(reservations.remove(&port)),
+        }
+    }
+    lifecycle
+}
+
+/// Parse `--expires-in`: `12h`, `7d`, `30 minutes`, or `never`
+pub fn parse_term(text: &str) -> Result<Option<Duration>> {
+    if text.trim().eq_ignore_ascii_case("never") {
+        return Ok(None);
+    }
+    match crate::timeline::parse_ago(text) {
+        Some(term) if term > Duration::zero() => Ok(Some(term)),
+        _ => bail!(
+            "Invalid --expires-in '{}': use a duration such as 12h, 7d or 30m, or never",
+            text
+        ),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SharedFile {
+    #[serde(default)]
+    services: BTreeMap<String, SharedService>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SharedService {
+    port: Option<u16>,
+    #[serde(default)]
+    ports: Vec<u16>,
+    process: String,
+    on_conflict: Option<PortResolution>,
+}
+
+/// The nearest [`SHARED_FILE`] in `start` or one of its parents
+pub fn find_shared(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(SHARED_FILE))
+        .find(|path| path.is_file())
+}
+
+/// The reservations a [`SHARED_FILE`] declares, one per port of each service
+pub fn parse_shared(content: &str) -> Result<HashMap<u16, PortReservation>> {
+    let file: SharedFile = toml::from_str(content)?;
+    let mut reservations: HashMap<u16, PortReservation> = HashMap::new();
+    for (service, declared) in file.services {
+        let ports: Vec<u16> = declared.port.into_iter().chain(declared.ports).collect();
+        if ports.is_empty() {
+            bail!("Service '{}' has no port", service);
+        }
+        for port in ports {
+            if port == 0 {
+                bail!("Service '{}' has an invalid port 0", service);
+            }
+            if let Some(other) = reservations.get(&port) {
+                bail!(
+                    "Port {} is reserved by both '{}' and '{}'",
+                    port,
+                    other.project_name,
+                    service
+                );
+            }
+            let mut reservation =
+                new_reservation(port, service.clone(), declared.process.clone(), None, false);
+            reservation.on_conflict = declared.on_conflict;
+            reservations.insert(port, reservation);
+        }
+    }
+    Ok(reservations)
+}
+
+/// A repository's [`SHARED_FILE`], as last read
+#[derive(Debug, Default)]
+pub struct SharedReservations {
+    pub path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    pub reservations: HashMap<u16, PortReservation>,
+}
+
+impl SharedReservations {
+    /// Find the file for `dir` and read it if it changed since the last time. Returns
+    /// whether the reservations changed; they are kept as they were if the file is invalid.
+    pub fn refresh(&mut self, dir: &Path) -> Result<bool> {
+        let path = find_shared(dir);
+        let modified = path
+            .as_ref()
+            .and_then(|path| fs::metadata(path).and_then(|m| m.modified()).ok());
+        if path == self.path && modified == self.modified {
+            return Ok(false);
+        }
+        self.path = path;
+        self.modified = modified;
+        let reservations = match &self.path {
+            Some(path) => {
+                let content = fs::read_to_string(path)
+                    .with_context(|| format!("Could not read {}", path.display()))?;
+                parse_shared(&content).with_context(|| format!("Invalid {}", path.display()))?
+            }
+            None => HashMap::new(),
+        };
+        self.reservations = reservations;
+        Ok(true)
+    }
+
+    /// Load the file for the current directory
+    pub fn load() -> Result<Self> {
+        let mut shared = Self::default();
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        shared.refresh(&cwd)?;
+        Ok(shared)
+    }
+}
+
+/// "in 5h 12m", "never", "expired"
+fn describe_expiry(reservation: &PortReservation, now: DateTime<Utc>) -> String {
+    match reservation.expires_at {
+        None => "never".to_string(),
+        Some(expires_at) if expires_at <= now => "expired".to_string(),
+        Some(expires_at) => format!(
+            "in {}",
+            crate::types::format_uptime((expires_at - now).num_seconds() as u64)
+        ),
+    }
+}
+
+/// ", on conflict: notify", or nothing for the guard's default
+fn describe_policy(on_conflict: Option<PortResolution>) -> String {
+    on_conflict
+        .map(|policy| format!(", on conflict: {}", policy.label()))
+        .unwrap_or_default()
+}
+
+/// Handle `--reserve PORT`
+pub fn reserve_command(
+    path: &Path,
+    port: u16,
+    process_name: &str,
+    project_name: Option<&str>,
+    expires_in: Option<&str>,
+    auto_renew: bool,
+    on_conflict: Option<PortResolution>,
+) -> Result<()> {
+    let term = match expires_in {
+        Some(text) => parse_term(text)?,
+        None => Some(Duration::hours(DEFAULT_TERM_HOURS)),
+    };
+    let project_name = project_name.unwrap_or(process_name).to_string();
+    let mut reservation = new_reservation(
+        port,
+        project_name.clone(),
+        process_name.to_string(),
+        term,
+        auto_renew && term.is_some(),
+    );
+    reservation.on_conflict = on_conflict;
+    let expiry = describe_expiry(&reservation, reservation.reserved_at);
+    let replaced = reserve(path, reservation)?;
+    println!(
+        "🔒 Reserved port {} for {} ({}), expires {}{}",
+        port,
+        project_name,
+        process_name,
+        expiry,
+        describe_policy(on_conflict)
+    );
+    if let Some(old) = replaced {
+        println!(
+            "   Replaces the reservation for {} ({})",
+            old.project_name, old.process_name
+        );
+    }
+    Ok(())
+}
+
+/// Handle `--unreserve PORT`
+pub fn unreserve_command(path: &Path, port: u16) -> Result<()> {
+    match unreserve(path, port)? {
+        Some(reservation) => println!(
+            "🔓 Released port {} (was reserved for {})",
+            port, reservation.project_name
+        ),
+        None => println!("ℹ️  Port {} is not reserved", port),
+    }
+    Ok(())
+}
+
+/// Handle `--reservations`
+pub fn list_command(path: &Path, json: bool) -> Result<()> {
+    let mut reservations: Vec<PortReservation> = load(path)?.into_values().collect();
+    reservations.sort_by_key(|reservation| reservation.port);
+    let shared = SharedReservations::load()?;
+    let mut shared_list: Vec<&PortReservation> = shared.reservations.values().collect();
+    shared_list.sort_by_key(|reservation| reservation.port);
+    if json {
+        let overridden: Vec<u16> = reservations.iter().map(|r| r.port).collect();
+        reservations.extend(
+            shared_list
+                .into_iter()
+                .filter(|reservation| !overridden.contains(&reservation.port))
+                .cloned(),
+        );
+        println!("{}", serde_json::to_string(&reservations)?);
+        return Ok(());
+    }
+    if let Some(shared_path) = &shared.path {
+        println!("🤝 Shared by the repository ({}):", shared_path.display());
+        for reservation in &shared_list {
+            let overridden = reservations.iter().any(|r| r.port == reservation.port);
+            println!(
+                "  • {:<5} {} ({}){}{}",
+                reservation.port,
+                reservation.project_name,
+                reservation.process_name,
+                describe_policy(reservation.on_conflict),
+                if overridden { ", overridden below" } else { "" }
+            );
+        }
+        println!();
+    }
+    if reservations.is_empty() {
+        println!("🔒 No port reservations ({})", path.display());
+        return Ok(());
+    }
+    let now = Utc::now();
+    println!("🔒 Port reservations ({}):", path.display());
+    for reservation in &reservations {
+        println!(
+            "  • {:<5} {} ({}), expires {}{}{}",
+            reservation.port,
+            reservation.project_name,
+            reservation.process_name,
+            describe_expiry(reservation, now),
+            if reservation.auto_renew {
+                ", renewed while the guard runs"
+            } else {
+                ""
+            },
+            describe_policy(reservation.on_conflict)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expire_and_renew() {
+        let now = Utc::now();
+        let reservation = |port: u16, term: Option<i64>, auto_renew: bool| {
+            let mut reservation = new_reservation(
+                port,
+                "web".to_string(),
+                "node".to_string(),
+                term.map(Duration::hours),
+                auto_renew,
+            );
+            reservation.reserved_at = now - Duration::hours(30);
+            reservation.expires_at = term.map(|hours| now - Duration::hours(30 - hours));
+            (port, reservation)
+        };
+        let mut reservations: HashMap<u16, PortReservation> = [
+            reservation(3000, Some(24), false),
+            reservation(3001, Some(24), true),
+            reservation(3002, Some(48), false),
+            reservation(3003, None, false),
+        ]
+        .into_iter()
+        .collect();
+
+        let lifecycle = expire(&mut reservations, now);
+        assert_eq!(lifecycle.expired.len(), 1);
+        assert_eq!(lifecycle.expired[0].port, 3000);
+        assert_eq!(lifecycle.renewed, vec![3001]);
+        let renewed = &reservations[&3001];
+        assert_eq!(renewed.expires_at, Some(now + Duration::hours(24)));
+        assert_eq!(renewed.renewed_at, Some(now));
+        assert_eq!(term(renewed), Some(Duration::hours(24)));
+        let mut ports: Vec<u16> = reservations.keys().copied().collect();
+        ports.sort_unstable();
+        assert_eq!(ports, vec![3001, 3002, 3003]);
+        assert!(expire(&mut reservations, now).is_empty());
+
+        assert_eq!(parse_term("7d").unwrap(), Some(Duration::days(7)));
+        assert_eq!(parse_term("never").unwrap(), None);
+        assert!(parse_term("0h").is_err());
+        assert!(parse_term("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_shared() {
+        let reservations = parse_shared(
+            r#"
+            [services.web]
+            port = 3000
+            process = "node"
+
+            [services.api]
+            ports = [8080, 8081]
+            process = "java"
+            on_conflict = "block"
+            "#,
+        )
+        .unwrap();
+        let mut ports: Vec<u16> = reservations.keys().copied().collect();
+        ports.sort_unstable();
+        assert_eq!(ports, vec![3000, 8080, 8081]);
+        assert_eq!(reservations[&8081].project_name, "api");
+        assert_eq!(reservations[&8081].process_name, "java");
+        assert_eq!(reservations[&3000].expires_at, None);
+        assert_eq!(reservations[&3000].on_conflict, None);
+        assert_eq!(
+            reservations[&8080].on_conflict,
+            Some(PortResolution::BlockNewProcess)
+        );
+
+        let twice = "[services.a]\nport = 3000\nprocess = \"x\"\n\n\
+                     [services.b]\nport = 3000\nprocess = \"y\"\n";
+        let error = parse_shared(twice).unwrap_err().to_string();
+        assert_eq!(error, "Port 3000 is reserved by both 'a' and 'b'");
+        assert!(parse_shared("[services.a]\nprocess = \"x\"\n").is_err());
+        assert!(parse_shared("[services.a]\nport = 1\n").is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::state_format::{StateMeta, Versioned};
 use crate::types::ProcessHistoryEntry;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -6,14 +7,97 @@ use std::fs;
 use std::path::PathBuf;
 use std::process::{Child, Command};
 use std::thread;
+use std::time::{Duration, Instant};
+
+/// Events kept per port in the restart history
+pub const MAX_EVENTS: usize = 20;
+
+/// A supervised process that ran this long before dying was healthy; its restart budget
+/// starts over
+pub const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// Something that happened to a restarted process, kept in its restart history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestartEvent {
+    pub at: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub kind: RestartEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RestartEventKind {
+    /// Started with the saved command
+    Started { pid: u32 },
+    /// A supervised process exited; `code` is absent when a signal ended it
+    Exited { pid: u32, code: Option<i32> },
+    /// `--supervise` stopped restarting after this many restarts
+    GaveUp { restarts: u32 },
+}
+
+impl std::fmt::Display for RestartEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RestartEventKind::Started { pid } => write!(f, "started (PID {})", pid),
+            RestartEventKind::Exited { pid, code: Some(code) } => {
+                write!(f, "PID {} exited with code {}", pid, code)
+            }
+            RestartEventKind::Exited { pid, code: None } => {
+                write!(f, "PID {} was killed by a signal", pid)
+            }
+            RestartEventKind::GaveUp { restarts } => {
+                write!(f, "gave up after {} restart(s)", restarts)
+            }
+        }
+    }
+}
+
+/// `--supervise`: how often a dying process is started again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupervisePolicy {
+    pub max_restarts: u32,
+    /// Wait before the first restart, doubled for each one after it
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for SupervisePolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl SupervisePolicy {
+    /// Wait before restart number `attempt` (0-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RestartInfo {
     pub port: u16,
     pub command: Vec<String>,
     pub working_directory: String,
+    #[serde(default)]
     pub env_vars: HashMap<String, String>,
+    #[serde(default)]
     pub last_restarted: chrono::DateTime<chrono::Utc>,
+    /// The most recent [`MAX_EVENTS`] starts, exits and give-ups, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<RestartEvent>,
+    #[serde(flatten)]
+    pub meta: StateMeta,
+}
+
+impl Versioned for RestartInfo {
+    const KIND: &'static str = "restart info";
 }
 
 pub struct RestartManager {
@@ -56,7 +140,15 @@ impl RestartManager {
         let command_parts = crate::command_line::parse_command_line(command_line);
 
         // Get current environment variables (filter to common dev vars)
-        let env_vars = Self::get_relevant_env_vars();
+        let mut env_vars = Self::get_relevant_env_vars();
+
+        // Jupyter generates a fresh token on every start; hand the old one back so
+        // open browser tabs and saved URLs keep working after a restart
+        if let Some(server) = crate::jupyter::find_for_port(port, None) {
+            if !server.token.is_empty() {
+                env_vars.insert("JUPYTER_TOKEN".to_string(), server.token);
+            }
+        }
 
         let restart_info = RestartInfo {
             port,
@@ -64,6 +156,13 @@ impl RestartManager {
             working_directory: working_directory.to_string(),
             env_vars,
             last_restarted: chrono::Utc::now(),
+            // Keep what happened to earlier runs on this port
+            events: self
+                .restart_info
+                .remove(&port)
+                .map(|info| info.events)
+                .unwrap_or_default(),
+            meta: StateMeta::default(),
         };
 
         self.restart_info.insert(port, restart_info);
@@ -87,6 +186,64 @@ impl RestartManager {
     /// Returns the PID of the spawned process. A background reaper thread
     /// ensures the child is waited on so it doesn't become a zombie.
     pub fn restart_port(&mut self, port: u16) -> Result<u32> {
+        let mut child = self.spawn_port(port)?;
+        let pid = child.id();
+
+        // Spawn a background thread to reap the child when it exits, preventing zombies.
+        // Without this, the child would remain in the process table as a defunct/zombie
+        // entry until port-kill itself exits — problematic in long-running modes like --guard.
+        thread::spawn(move || {
+            let _ = child.wait();
+        });
+
+        Ok(pid)
+    }
+
+    /// `--restart <port> --supervise`: start the port's saved command and start it again
+    /// each time it dies, waiting longer before every restart, until it exits cleanly or
+    /// `policy.max_restarts` is used up. Blocks for as long as it supervises.
+    pub fn supervise(&mut self, port: u16, policy: &SupervisePolicy) -> Result<()> {
+        let mut child = self.spawn_port(port)?;
+        let mut restarts = 0;
+        let mut attempt = 0;
+        loop {
+            let started = Instant::now();
+            let pid = child.id();
+            let status = child.wait().context("Failed to wait for the supervised process")?;
+            self.record_event(port, RestartEventKind::Exited { pid, code: status.code() });
+            if status.success() {
+                println!("✅ Port {}: PID {} exited cleanly, no longer supervising", port, pid);
+                return Ok(());
+            }
+            if started.elapsed() >= STABLE_AFTER {
+                attempt = 0;
+            }
+            if attempt >= policy.max_restarts {
+                self.record_event(port, RestartEventKind::GaveUp { restarts });
+                anyhow::bail!(
+                    "Port {} kept dying; gave up after {} restart(s)",
+                    port,
+                    restarts
+                );
+            }
+            let delay = policy.backoff(attempt);
+            println!(
+                "⚠️  Port {}: {}, restarting in {}s ({}/{})",
+                port,
+                RestartEventKind::Exited { pid, code: status.code() },
+                delay.as_secs_f32(),
+                attempt + 1,
+                policy.max_restarts
+            );
+            thread::sleep(delay);
+            child = self.spawn_port(port)?;
+            restarts += 1;
+            attempt += 1;
+        }
+    }
+
+    /// Start the port's saved command, recording it; the caller waits on the child
+    fn spawn_port(&mut self, port: u16) -> Result<Child> {
         let restart_info = self
             .restart_info
             .get(&port)
@@ -98,22 +255,39 @@ impl RestartManager {
             restart_info.command
         );
 
-        let mut child = self.execute_restart(restart_info)?;
+        let command = restart_info.command.join(" ");
+        let child = match self.execute_restart(restart_info) {
+            Ok(child) => child,
+            Err(e) => {
+                crate::audit_log::record_restart(port, &command, &Err(anyhow::anyhow!("{:#}", e)));
+                return Err(e);
+            }
+        };
         let pid = child.id();
-
-        // Spawn a background thread to reap the child when it exits, preventing zombies.
-        // Without this, the child would remain in the process table as a defunct/zombie
-        // entry until port-kill itself exits — problematic in long-running modes like --guard.
-        thread::spawn(move || {
-            let _ = child.wait();
-        });
+        crate::audit_log::record_restart(port, &command, &Ok(pid));
+        crate::history_store::record(&ProcessHistoryEntry::restarted(restart_info, pid));
 
         if let Some(info) = self.restart_info.get_mut(&port) {
             info.last_restarted = chrono::Utc::now();
         }
-        let _ = self.save();
+        self.record_event(port, RestartEventKind::Started { pid });
 
-        Ok(pid)
+        Ok(child)
+    }
+
+    /// Add an event to the port's restart history and save it
+    fn record_event(&mut self, port: u16, kind: RestartEventKind) {
+        if let Some(info) = self.restart_info.get_mut(&port) {
+            info.events.push(RestartEvent {
+                at: chrono::Utc::now(),
+                kind,
+            });
+            let excess = info.events.len().saturating_sub(MAX_EVENTS);
+            info.events.drain(..excess);
+        }
+        if let Err(e) = self.save() {
+            log::warn!("Failed to save restart history: {:#}", e);
+        }
     }
 
     /// Get restart info for a port
@@ -212,8 +386,8 @@ impl RestartManager {
     fn load(&mut self) -> Result<()> {
         let json = fs::read_to_string(&self.restart_history_path)
             .context("Failed to read restart history file")?;
-        self.restart_info =
-            serde_json::from_str(&json).context("Failed to parse restart history file")?;
+        self.restart_info = crate::state_format::decode_map(&json)
+            .context("Failed to parse restart history file")?;
         Ok(())
     }
 
@@ -243,6 +417,7 @@ impl Default for RestartManager {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::command_line::parse_command_line;
 
     #[test]
@@ -252,6 +427,29 @@ mod tests {
         assert_eq!(parts, vec!["npm", "run", "dev", "--port", "3000"]);
     }
 
+    #[test]
+    fn test_supervise_backoff() {
+        let policy = SupervisePolicy::default();
+        let waits: Vec<u64> = (0..7).map(|attempt| policy.backoff(attempt).as_secs()).collect();
+        assert_eq!(waits, vec![1, 2, 4, 8, 16, 30, 30]);
+        assert_eq!(policy.backoff(u32::MAX), policy.max_backoff);
+    }
+
+    #[test]
+    fn test_restart_events_serialize() {
+        let event = RestartEvent {
+            at: chrono::DateTime::UNIX_EPOCH,
+            kind: RestartEventKind::Exited { pid: 42, code: None },
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            r#"{"at":"1970-01-01T00:00:00Z","event":"exited","pid":42,"code":null}"#
+        );
+        assert_eq!(serde_json::from_str::<RestartEvent>(&json).unwrap(), event);
+        assert_eq!(event.kind.to_string(), "PID 42 was killed by a signal");
+    }
+
     #[test]
     fn test_parse_command_line_with_quotes() {
         let cmd = r#"node "my script.js" --arg "value with spaces""#;
@@ -0,0 +1,109 @@
+//! Safe mode: the default until a user opts out, so a `--ports 2000-8000 --kill-all` pasted
+//! from a blog post can't take half the machine down. While it is on:
+//!
+//! - killing more than one process asks for confirmation first
+//! - listeners on system ports (below 1024) and system processes (launchd, ControlCenter,
+//!   systemd, svchost.exe, ...) are still listed but never killed
+//! - `--kill-all` over a wide range only prints what it would kill
+//!
+//! `port-kill --trust-me` turns it off for good; `safe_mode = false` (or `true`) in
+//! `~/.port-kill/config.toml` overrides either way.
+
+use crate::uninstall::home_dir;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Ports below this are system ports, never killed in safe mode
+pub const SYSTEM_PORTS_END: u16 = 1024;
+
+/// `--kill-all` over more ports than this is a dry run in safe mode
+pub const WIDE_RANGE_PORTS: usize = 100;
+
+/// Processes that listen on dev-looking ports but belong to the OS (macOS AirPlay
+/// receivers sit on 5000 and 7000, for one)
+pub const SYSTEM_PROCESSES: &[&str] = &[
+    "launchd",
+    "ControlCenter",
+    "ControlCe",
+    "rapportd",
+    "mDNSResponder",
+    "sharingd",
+    "systemd",
+    "systemd-resolved",
+    "sshd",
+    "cupsd",
+    "avahi-daemon",
+    "System",
+    "svchost.exe",
+    "lsass.exe",
+    "services.exe",
+    "wininit.exe",
+    "spoolsv.exe",
+];
+
+/// Refuse to kill the listener on a system port while safe mode is on
+pub fn ensure_not_system_port(port: u16, safe_mode: bool) -> Result<()> {
+    if safe_mode && port > 0 && port < SYSTEM_PORTS_END {
+        anyhow::bail!(
+            "port {} is a system port; safe mode doesn't kill its listener (--trust-me turns safe mode off)",
+            port
+        );
+    }
+    Ok(())
+}
+
+/// Written by `--trust-me`
+pub fn trusted_marker_path() -> PathBuf {
+    home_dir().join(".port-kill").join("trusted")
+}
+
+/// Whether safe mode is on: the config setting if there is one, otherwise until `--trust-me`
+pub fn is_active(config_setting: Option<bool>) -> bool {
+    config_setting.unwrap_or_else(|| !trusted_marker_path().exists())
+}
+
+/// `--trust-me`: leave safe mode on this machine. Returns the marker file written.
+pub fn trust() -> Result<PathBuf> {
+    let path = trusted_marker_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, chrono::Utc::now().to_rfc3339())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Ask `prompt` with y/N on stdin. Without an answer (stdin closed, as in CI) it's a no.
+pub fn confirm(prompt: &str) -> Result<bool> {
+    println!("{} y/N", prompt);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim(), "y" | "Y" | "yes" | "YES"))
+}
+
+/// Printed instead of killing when `--kill-all` covers a wide range in safe mode
+pub fn print_wide_range_banner(port_count: usize) {
+    println!("🛟 Safe mode: --kill-all over {} ports is a dry run. Nothing was killed.", port_count);
+    println!(
+        "   Narrow it to {} ports or fewer, or run `port-kill --trust-me` to turn safe mode off.",
+        WIDE_RANGE_PORTS
+    );
+}
+
+/// One line after a safe-mode cancellation, pointing at the way out
+pub fn print_cancelled() {
+    println!("Cancelled. Safe mode asks before killing several processes; `port-kill --trust-me` turns it off.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_setting_wins() {
+        assert!(is_active(Some(true)));
+        assert!(!is_active(Some(false)));
+    }
+}
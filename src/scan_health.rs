@@ -0,0 +1,263 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::process::{Command, Output};
+use std::sync::Mutex;
+
+/// Whether the last port scan can be trusted. An empty result only means
+/// "no listeners" when the status is `Ok`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanStatus {
+    #[default]
+    Ok,
+    /// The scanner ran but could not see everything (e.g. permission denied)
+    Degraded,
+    /// The scanner could not run at all (e.g. lsof missing)
+    Failed,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanHealth {
+    pub status: ScanStatus,
+    pub reasons: Vec<String>,
+}
+
+impl ScanHealth {
+    pub fn degraded(reason: impl Into<String>) -> Self {
+        Self {
+            status: ScanStatus::Degraded,
+            reasons: vec![reason.into()],
+        }
+    }
+
+    pub fn failed(reason: impl Into<String>) -> Self {
+        Self {
+            status: ScanStatus::Failed,
+            reasons: vec![reason.into()],
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == ScanStatus::Ok
+    }
+
+    /// Combine two results; the worse status wins and reasons are kept once
+    pub fn merge(&mut self, other: ScanHealth) {
+        self.status = self.status.max(other.status);
+        for reason in other.reasons {
+            if !self.reasons.contains(&reason) {
+                self.reasons.push(reason);
+            }
+        }
+    }
+
+    /// One-line explanation for console and tray output, None when the scan was fine
+    pub fn describe(&self) -> Option<String> {
+        match self.status {
+            ScanStatus::Ok => None,
+            ScanStatus::Degraded => Some(format!(
+                "⚠️  Scan incomplete: {} (run `port-kill-console cache --doctor` for help)",
+                self.reasons.join("; ")
+            )),
+            ScanStatus::Failed => Some(format!(
+                "❌ Port scan failed: {} (run `port-kill-console cache --doctor` for help)",
+                self.reasons.join("; ")
+            )),
+        }
+    }
+}
+
+/// Last line of every `--json` listing, `ok` included, so an empty scan can be told from a
+/// failed one; it has no `pid`/`port`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScanStatusLine {
+    pub scan_status: ScanStatus,
+    pub reasons: Vec<String>,
+}
+
+impl From<&ScanHealth> for ScanStatusLine {
+    fn from(health: &ScanHealth) -> Self {
+        Self {
+            scan_status: health.status,
+            reasons: health.reasons.clone(),
+        }
+    }
+}
+
+static LAST_SCAN: Mutex<Option<ScanHealth>> = Mutex::new(None);
+
+/// Start tracking a new scan
+pub fn begin_scan() {
+    if let Ok(mut last) = LAST_SCAN.lock() {
+        *last = Some(ScanHealth::default());
+    }
+}
+
+/// Fold the outcome of one scanner invocation into the current scan
+pub fn report(health: ScanHealth) {
+    if health.is_ok() {
+        return;
+    }
+    if let Ok(mut last) = LAST_SCAN.lock() {
+        last.get_or_insert_with(ScanHealth::default).merge(health);
+    }
+}
+
+/// Health of the most recent scan (Ok if nothing has been scanned yet)
+pub fn last_scan() -> ScanHealth {
+    LAST_SCAN
+        .lock()
+        .ok()
+        .and_then(|last| last.clone())
+        .unwrap_or_default()
+}
+
+/// Classify the result of running a scanner such as lsof or netstat.
+/// lsof exits 1 with empty stderr when nothing is listening, which is not an error.
+pub fn check_command(tool: &str, result: &std::io::Result<Output>) -> ScanHealth {
+    let output = match result {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return ScanHealth::failed(format!("`{}` is not installed or not on PATH", tool))
+        }
+        Err(e) => return ScanHealth::failed(format!("could not run `{}`: {}", tool, e)),
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lower = stderr.to_lowercase();
+    if lower.contains("permission denied")
+        || lower.contains("operation not permitted")
+        || lower.contains("access is denied")
+    {
+        return ScanHealth::degraded(format!(
+            "`{}` was denied access to some processes; run with sudo/as Administrator to see every listener",
+            tool
+        ));
+    }
+
+    if !output.status.success() {
+        // lsof prints harmless "WARNING: can't stat()" lines for fuse mounts and the like
+        let error_line = stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.contains("WARNING"));
+        if let Some(line) = error_line {
+            return ScanHealth::degraded(format!("`{}` reported an error: {}", tool, line));
+        }
+    }
+
+    ScanHealth::default()
+}
+
+/// True if `tool` can be spawned at all
+fn tool_available(tool: &str, probe_arg: &str) -> bool {
+    !matches!(
+        Command::new(tool).arg(probe_arg).output(),
+        Err(ref e) if e.kind() == ErrorKind::NotFound
+    )
+}
+
+/// Port-scanning checks for `cache --doctor`: is the scanner installed, can it see
+/// other users' listeners, and which alternatives exist if it is missing.
+pub fn diagnose(notes: &mut Vec<String>, warnings: &mut Vec<String>, errors: &mut Vec<String>) {
+    #[cfg(target_os = "windows")]
+    let (scanner, probe, scan_args): (&str, &str, &[&str]) =
+        ("netstat", "-?", &["-ano", "-p", "TCP"]);
+    #[cfg(not(target_os = "windows"))]
+    let (scanner, probe, scan_args): (&str, &str, &[&str]) =
+        ("lsof", "-v", &["-nP", "-iTCP", "-sTCP:LISTEN"]);
+
+    if !tool_available(scanner, probe) {
+        errors.push(format!(
+            "Port scanning needs `{}`, which was not found - port-kill will report scans as failed",
+            scanner
+        ));
+        #[cfg(not(target_os = "windows"))]
+        {
+            let alternatives: Vec<&str> = [("ss", "-V"), ("netstat", "--version"), ("fuser", "-V")]
+                .iter()
+                .filter(|(tool, probe)| tool_available(tool, probe))
+                .map(|(tool, _)| *tool)
+                .collect();
+            if !alternatives.is_empty() {
+                notes.push(format!(
+                    "Available socket tools: {} (useful for manual checks; port-kill itself needs lsof)",
+                    alternatives.join(", ")
+                ));
+            }
+            errors.push(
+                "Install lsof: Debian/Ubuntu `sudo apt install lsof`, Fedora `sudo dnf install lsof`, Arch `sudo pacman -S lsof`, Alpine `apk add lsof`"
+                    .to_string(),
+            );
+        }
+        return;
+    }
+
+    let health = check_command(scanner, &Command::new(scanner).args(scan_args).output());
+    match health.status {
+        ScanStatus::Ok => notes.push(format!("Port scanning with `{}` works", scanner)),
+        _ => warnings.extend(health.reasons),
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::process_monitor::current_username().as_deref() != Some("root") {
+            notes.push(
+                "Not running as root: lsof only sees your own processes' sockets, so listeners owned by other users or root may be missing. Use `sudo` for a complete view."
+                    .to_string(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn output(code: i32, stderr: &str) -> std::io::Result<Output> {
+        use std::os::unix::process::ExitStatusExt;
+        Ok(Output {
+            status: std::process::ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_command() {
+        // No listeners: lsof exits 1 without complaint
+        assert!(check_command("lsof", &output(1, "")).is_ok());
+        assert!(check_command(
+            "lsof",
+            &output(1, "lsof: WARNING: can't stat() fuse file system")
+        )
+        .is_ok());
+
+        let denied = check_command(
+            "lsof",
+            &output(1, "lsof: can't open /proc/1/fd: Permission denied"),
+        );
+        assert_eq!(denied.status, ScanStatus::Degraded);
+
+        let missing = check_command(
+            "lsof",
+            &Err(std::io::Error::new(ErrorKind::NotFound, "not found")),
+        );
+        assert_eq!(missing.status, ScanStatus::Failed);
+    }
+
+    #[test]
+    fn test_merge_keeps_worst_status() {
+        let mut health = ScanHealth::degraded("partial");
+        health.merge(ScanHealth::failed("missing"));
+        health.merge(ScanHealth::degraded("partial"));
+        assert_eq!(health.status, ScanStatus::Failed);
+        assert_eq!(
+            health.reasons,
+            vec!["partial".to_string(), "missing".to_string()]
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use crate::{
-    cli::Args, file_monitor::FileMonitor, process_monitor::ProcessMonitor, types::ProcessInfo,
+    cli::Args,
+    file_monitor::FileMonitor,
+    process_monitor::ProcessMonitor,
+    rate_limiter::{KillDecision, KillRateLimiter},
+    types::ProcessInfo,
 };
 use anyhow::Result;
 use std::collections::HashMap;
@@ -548,6 +552,7 @@ impl ScriptEngine {
             .collect();
         let port_guards = self.port_guards.clone();
         let port_handlers = Arc::clone(&self.port_handlers);
+        let mut kill_limiter = KillRateLimiter::from_args(&self.args);
 
         tokio::spawn(async move {
             let mut last_processes: HashMap<u16, ProcessInfo> = HashMap::new();
@@ -588,22 +593,33 @@ impl ScriptEngine {
                                     if let Some(guard_config) = port_guards.get(&port) {
                                         match guard_config {
                                             GuardConfig::KillAll => {
-                                                println!("🚨 Unauthorized process on port {}: {} (PID: {}) - KILLING", 
-                                                         port, process_info.name, process_info.pid);
-                                                if let Err(e) =
-                                                    monitor.kill_process(process_info.pid).await
-                                                {
-                                                    println!(
-                                                        "❌ Failed to kill process {}: {}",
-                                                        process_info.pid, e
-                                                    );
+                                                if !script_kill_allowed(&mut kill_limiter, port) {
+                                                    println!("🔔 Unauthorized process on port {}: {} (PID: {}) - not killed",
+                                                             port, process_info.name, process_info.pid);
                                                 } else {
-                                                    println!("✅ Successfully killed unauthorized process {} on port {}", 
-                                                             process_info.pid, port);
+                                                    println!("🚨 Unauthorized process on port {}: {} (PID: {}) - KILLING", 
+                                                             port, process_info.name, process_info.pid);
+                                                    if let Err(e) =
+                                                        monitor.kill_process(process_info.pid).await
+                                                    {
+                                                        println!(
+                                                            "❌ Failed to kill process {}: {}",
+                                                            process_info.pid, e
+                                                        );
+                                                    } else {
+                                                        kill_limiter.record(port);
+                                                        println!("✅ Successfully killed unauthorized process {} on port {}", 
+                                                                 process_info.pid, port);
+                                                    }
                                                 }
                                             }
                                             GuardConfig::AllowOnly(allowed_name) => {
-                                                if process_info.name != *allowed_name {
+                                                if process_info.name != *allowed_name
+                                                    && !script_kill_allowed(&mut kill_limiter, port)
+                                                {
+                                                    println!("🔔 Unauthorized process '{}' on port {} (PID: {}) - not killed",
+                                                             process_info.name, port, process_info.pid);
+                                                } else if process_info.name != *allowed_name {
                                                     println!("🚨 Unauthorized process '{}' on port {}: {} (PID: {}) - KILLING", 
                                                              process_info.name, port, process_info.name, process_info.pid);
                                                     if let Err(e) =
@@ -614,6 +630,7 @@ impl ScriptEngine {
                                                             process_info.pid, e
                                                         );
                                                     } else {
+                                                        kill_limiter.record(port);
                                                         println!("✅ Successfully killed unauthorized process {} on port {}", 
                                                                  process_info.pid, port);
                                                     }
@@ -668,6 +685,32 @@ impl ScriptEngine {
     }
 }
 
+/// Check the automated-kill limiter for a script guard, printing why a kill is held back
+fn script_kill_allowed(limiter: &mut KillRateLimiter, port: u16) -> bool {
+    let was_tripped = limiter.is_tripped();
+    match limiter.check(port) {
+        KillDecision::Allow => true,
+        KillDecision::PortCooldown(remaining) => {
+            println!(
+                "⏳ Port {} was auto-killed recently ({}s cooldown left)",
+                port,
+                remaining.as_secs()
+            );
+            false
+        }
+        KillDecision::RateLimited => {
+            if !was_tripped {
+                println!(
+                    "🚦 Automated kill limit reached ({}) - switching to notify-only. Is something restarting processes on port {}?",
+                    limiter.describe(),
+                    port
+                );
+            }
+            false
+        }
+    }
+}
+
 /// Load script from file
 pub fn load_script_file(file_path: &str) -> Result<String> {
     fs::read_to_string(file_path)
@@ -698,16 +741,27 @@ mod tests {
                 ignore_ports: None,
                 ignore_processes: None,
                 ignore_patterns: None,
+                ignore_regex: None,
+                ignore_case: false,
                 ignore_groups: None,
                 smart_filter: false,
                 only_groups: None,
+                only_processes: None,
+                only_ports: None,
+                port_rules: Default::default(),
+                conditional_filters: Vec::new(),
                 console: false,
                 verbose: false,
                 docker: false,
                 show_pid: false,
                 log_level: crate::cli::LogLevel::Info,
+                log_format: crate::cli::LogFormat::Text,
+                log_file: None,
+                log_max_size: 10,
                 show_history: false,
                 clear_history: false,
+                import_history: None,
+                history_action: None,
                 show_filters: false,
                 performance: false,
                 show_context: false,
@@ -720,25 +774,50 @@ mod tests {
                 show_tree: false,
                 json: false,
                 reset: false,
+                deep: false,
                 show_offenders: false,
                 show_patterns: false,
                 show_suggestions: false,
+                apply_suggestions: false,
+                ignore: None,
                 show_stats: false,
+                history_since: None,
+                history_until: None,
+                history_port: None,
+                history_project: None,
+                history_group: None,
+                history_limit: None,
                 show_root_cause: false,
+                apply: false,
                 guard_mode: false,
                 guard_ports: "3000,3001,3002,8000,8080,9000".to_string(),
                 auto_resolve: false,
+                on_conflict: None,
                 reservation_file: "~/.port-kill/reservations.json".to_string(),
                 intercept_commands: false,
                 reserve_port: None,
                 project_name: None,
                 process_name: None,
+                reserve: None,
+                expires_in: None,
+                no_renew: false,
+                unreserve: None,
+                reservations: false,
                 audit: false,
                 security_mode: false,
                 suspicious_ports: "8444,4444,9999,14444,5555,6666,7777".to_string(),
                 baseline_file: None,
                 suspicious_only: false,
                 remote: None,
+                linked: false,
+                nice: None,
+                profile: None,
+                show_audit: false,
+                audit_since: None,
+                audit_action: None,
+                audit_initiator: None,
+                audit_port: None,
+                audit_failed: false,
                 monitor_endpoint: None,
                 send_interval: 30,
                 scan_interval: 2,
@@ -764,9 +843,37 @@ mod tests {
                 save_preset: None,
                 preset_desc: None,
                 delete_preset: None,
+                export_preset: None,
+                import_preset: None,
+                import_as: None,
+                overwrite: false,
+                show_preset: None,
+                edit_preset: None,
                 check_updates: false,
+                no_update_check: false,
+                demo: false,
+                test_webhooks: false,
+                adopt: false,
+                restart_failed: false,
+                logs: None,
+                follow: false,
+                supervise: false,
+                supervise_max_restarts: 5,
+                undo_last_kill: false,
+                enable_autostart: false,
+                disable_autostart: false,
+                icon_theme: None,
+                max_rss: None,
+                offline: false,
+                trust_me: false,
+                safe_mode: false,
+                notify: false,
+                uninstall: false,
+                purge: false,
                 self_update: false,
                 cache: None,
+                complete_values: None,
+                schema: None,
                 detect: false,
                 start: None,
                 guard_auto_restart: false,
@@ -776,6 +883,40 @@ mod tests {
                 status: false,
                 config_file: ".port-kill.yaml".to_string(),
                 init_config: false,
+                simulate: false,
+                wsl: false,
+                auto_kill_cooldown: 30,
+                max_auto_kills: 10,
+                auto_kill_window: 600,
+                user: None,
+                force: false,
+                sudo: false,
+                what_if: false,
+                report: None,
+                report_format: crate::digest::ReportFormat::Text,
+                at: None,
+                timeline: None,
+                hours: 24,
+                connections: false,
+                traffic: false,
+                traffic_window: 2,
+                no_kill_if_active: false,
+                only_active: false,
+                tui: false,
+                set_terminal_title: false,
+                status_page: None,
+                serve: None,
+                serve_token: None,
+                output: crate::cli::OutputFormat::Table,
+                once: false,
+                fail_if_occupied: None,
+                kill_gpu_hogs: false,
+                wait_free: None,
+                wait_open: None,
+                timeout: None,
+                kill_duplicates: false,
+                protect: None,
+                no_config: false,
             },
         );
 
@@ -809,16 +950,27 @@ mod tests {
                 ignore_ports: None,
                 ignore_processes: None,
                 ignore_patterns: None,
+                ignore_regex: None,
+                ignore_case: false,
                 ignore_groups: None,
                 smart_filter: false,
                 only_groups: None,
+                only_processes: None,
+                only_ports: None,
+                port_rules: Default::default(),
+                conditional_filters: Vec::new(),
                 console: false,
                 verbose: false,
                 docker: false,
                 show_pid: false,
                 log_level: crate::cli::LogLevel::Info,
+                log_format: crate::cli::LogFormat::Text,
+                log_file: None,
+                log_max_size: 10,
                 show_history: false,
                 clear_history: false,
+                import_history: None,
+                history_action: None,
                 show_filters: false,
                 performance: false,
                 show_context: false,
@@ -831,25 +983,50 @@ mod tests {
                 show_tree: false,
                 json: false,
                 reset: false,
+                deep: false,
                 show_offenders: false,
                 show_patterns: false,
                 show_suggestions: false,
+                apply_suggestions: false,
+                ignore: None,
                 show_stats: false,
+                history_since: None,
+                history_until: None,
+                history_port: None,
+                history_project: None,
+                history_group: None,
+                history_limit: None,
                 show_root_cause: false,
+                apply: false,
                 guard_mode: false,
                 guard_ports: "3000,3001,3002,8000,8080,9000".to_string(),
                 auto_resolve: false,
+                on_conflict: None,
                 reservation_file: "~/.port-kill/reservations.json".to_string(),
                 intercept_commands: false,
                 reserve_port: None,
                 project_name: None,
                 process_name: None,
+                reserve: None,
+                expires_in: None,
+                no_renew: false,
+                unreserve: None,
+                reservations: false,
                 audit: false,
                 security_mode: false,
                 suspicious_ports: "8444,4444,9999,14444,5555,6666,7777".to_string(),
                 baseline_file: None,
                 suspicious_only: false,
                 remote: None,
+                linked: false,
+                nice: None,
+                profile: None,
+                show_audit: false,
+                audit_since: None,
+                audit_action: None,
+                audit_initiator: None,
+                audit_port: None,
+                audit_failed: false,
                 monitor_endpoint: None,
                 send_interval: 30,
                 scan_interval: 2,
@@ -875,9 +1052,37 @@ mod tests {
                 save_preset: None,
                 preset_desc: None,
                 delete_preset: None,
+                export_preset: None,
+                import_preset: None,
+                import_as: None,
+                overwrite: false,
+                show_preset: None,
+                edit_preset: None,
                 check_updates: false,
+                no_update_check: false,
+                demo: false,
+                test_webhooks: false,
+                adopt: false,
+                restart_failed: false,
+                logs: None,
+                follow: false,
+                supervise: false,
+                supervise_max_restarts: 5,
+                undo_last_kill: false,
+                enable_autostart: false,
+                disable_autostart: false,
+                icon_theme: None,
+                max_rss: None,
+                offline: false,
+                trust_me: false,
+                safe_mode: false,
+                notify: false,
+                uninstall: false,
+                purge: false,
                 self_update: false,
                 cache: None,
+                complete_values: None,
+                schema: None,
                 detect: false,
                 start: None,
                 guard_auto_restart: false,
@@ -887,6 +1092,40 @@ mod tests {
                 status: false,
                 config_file: ".port-kill.yaml".to_string(),
                 init_config: false,
+                simulate: false,
+                wsl: false,
+                auto_kill_cooldown: 30,
+                max_auto_kills: 10,
+                auto_kill_window: 600,
+                user: None,
+                force: false,
+                sudo: false,
+                what_if: false,
+                report: None,
+                report_format: crate::digest::ReportFormat::Text,
+                at: None,
+                timeline: None,
+                hours: 24,
+                connections: false,
+                traffic: false,
+                traffic_window: 2,
+                no_kill_if_active: false,
+                only_active: false,
+                tui: false,
+                set_terminal_title: false,
+                status_page: None,
+                serve: None,
+                serve_token: None,
+                output: crate::cli::OutputFormat::Table,
+                once: false,
+                fail_if_occupied: None,
+                kill_gpu_hogs: false,
+                wait_free: None,
+                wait_open: None,
+                timeout: None,
+                kill_duplicates: false,
+                protect: None,
+                no_config: false,
             },
         );
 
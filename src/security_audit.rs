@@ -31,6 +31,27 @@ impl SecurityAuditor {
         }
     }
 
+    /// Where the most recent audit is kept for the status page
+    pub fn last_audit_file_path() -> String {
+        let home_dir = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        format!("{}/.port-kill-last-audit.json", home_dir)
+    }
+
+    pub fn save_last_audit(result: &SecurityAuditResult) -> Result<()> {
+        fs::write(
+            Self::last_audit_file_path(),
+            serde_json::to_string_pretty(result)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn load_last_audit() -> Option<SecurityAuditResult> {
+        let content = fs::read_to_string(Self::last_audit_file_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
     /// Perform comprehensive security audit
     pub async fn perform_audit(
         &self,
@@ -72,6 +93,10 @@ impl SecurityAuditor {
             }
         }
 
+        // Port order keeps the report stable between runs
+        suspicious_processes.sort_by_key(|p| p.port);
+        approved_processes.sort_by_key(|p| p.port);
+
         // Generate security recommendations
         recommendations.extend(self.generate_recommendations(&suspicious_processes));
 
@@ -415,6 +440,10 @@ impl SecurityAuditor {
             }
         }
 
+        new_processes.sort_by_key(|p| (p.port, p.pid));
+        removed_processes.sort_by_key(|p| (p.port, p.pid));
+        changed_processes.sort_by_key(|c| c.port);
+
         Ok(BaselineComparison {
             baseline_file: baseline_path.to_string(),
             new_processes,
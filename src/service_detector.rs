@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
@@ -13,6 +14,9 @@ pub enum ServiceType {
     DockerCompose {
         service_name: String,
         compose_file_path: PathBuf,
+        /// Enabled when starting, so the service and dependencies in them can start
+        #[serde(default)]
+        profiles: Vec<String>,
     },
     Procfile {
         process_name: String,
@@ -21,6 +25,27 @@ pub enum ServiceType {
     PythonApp {
         script_path: PathBuf,
     },
+    /// `go run <package>`, or `air` when the module has an .air.toml
+    GoApp {
+        package: String,
+        module_dir: PathBuf,
+        air_config: Option<PathBuf>,
+    },
+    CargoBin {
+        package: String,
+        binary: String,
+        manifest_path: PathBuf,
+    },
+    /// `bootRun` with Gradle, `spring-boot:run` with Maven; the wrappers when present
+    SpringBoot {
+        build_file_path: PathBuf,
+    },
+    RailsApp {
+        app_dir: PathBuf,
+    },
+    LaravelApp {
+        artisan_path: PathBuf,
+    },
     Custom {
         command: Vec<String>,
         working_dir: PathBuf,
@@ -36,40 +61,137 @@ pub struct DiscoveredService {
     pub description: String,
 }
 
+impl DiscoveredService {
+    /// The command `--start` runs, as a shell would show it
+    pub fn command_line(&self) -> String {
+        match &self.service_type {
+            ServiceType::NpmScript { script_name, .. } => format!("npm run {}", script_name),
+            ServiceType::DockerCompose { service_name, .. } => format!("docker-compose up {}", service_name),
+            ServiceType::Procfile {
+                process_name,
+                procfile_path,
+            } => fs::read_to_string(procfile_path)
+                .ok()
+                .and_then(|content| {
+                    content
+                        .lines()
+                        .filter_map(ServiceDetector::parse_procfile_line)
+                        .find(|(name, _)| name == process_name)
+                        .map(|(_, command)| command)
+                })
+                .unwrap_or_default(),
+            ServiceType::PythonApp { script_path } => {
+                let script = script_path.file_name().unwrap_or_default().to_string_lossy();
+                if script == "manage.py" {
+                    format!("python {} runserver", script)
+                } else {
+                    format!("python {}", script)
+                }
+            }
+            ServiceType::GoApp {
+                package,
+                air_config,
+                ..
+            } => match air_config {
+                Some(_) => "air".to_string(),
+                None => format!("go run {}", package),
+            },
+            ServiceType::CargoBin { binary, .. } => format!("cargo run --bin {}", binary),
+            ServiceType::SpringBoot { build_file_path } => {
+                if build_file_path.extension().is_some_and(|ext| ext == "xml") {
+                    "mvn spring-boot:run".to_string()
+                } else {
+                    "gradle bootRun".to_string()
+                }
+            }
+            ServiceType::RailsApp { .. } => "bin/rails server".to_string(),
+            ServiceType::LaravelApp { .. } => "php artisan serve".to_string(),
+            ServiceType::Custom { command, .. } => command.join(" "),
+        }
+    }
+}
+
 pub struct ServiceDetector {
     search_paths: Vec<PathBuf>,
+    /// Added to the environment of every service started
+    env: HashMap<String, String>,
 }
 
 impl ServiceDetector {
     pub fn new() -> Self {
         Self {
             search_paths: vec![PathBuf::from(".")],
+            env: HashMap::new(),
         }
     }
 
     pub fn with_paths(paths: Vec<PathBuf>) -> Self {
         Self {
             search_paths: paths,
+            env: HashMap::new(),
         }
     }
 
-    /// Discover all services in the search paths
+    /// Start services with these variables set, e.g. from the orchestration config's `env`
+    /// and `env_file`
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Discover all services in the search paths, and in the members of any workspace they
+    /// are the root of. Services in a member are named after its directory, e.g.
+    /// `apps/web/npm:dev`.
     pub fn discover_services(&self) -> Result<Vec<DiscoveredService>> {
         let mut services = Vec::new();
 
         for search_path in &self.search_paths {
-            // Search for package.json (Node.js/npm)
-            services.extend(self.discover_npm_services(search_path)?);
+            services.extend(self.discover_in(search_path)?);
+
+            for member in crate::workspaces::member_dirs(search_path) {
+                let prefix = crate::workspaces::relative_path(search_path, &member).unwrap_or_default();
+                match self.discover_in(&member) {
+                    Ok(found) => services.extend(found.into_iter().map(|mut service| {
+                        service.name = format!("{}/{}", prefix, service.name);
+                        service
+                    })),
+                    // One broken package.json shouldn't hide the rest of the monorepo
+                    Err(e) => log::warn!("Skipping workspace member {}: {:#}", prefix, e),
+                }
+            }
+        }
 
-            // Search for docker-compose.yml
-            services.extend(self.discover_docker_services(search_path)?);
+        Ok(services)
+    }
 
-            // Search for Procfile
-            services.extend(self.discover_procfile_services(search_path)?);
+    fn discover_in(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
 
-            // Search for Python apps
-            services.extend(self.discover_python_services(search_path)?);
-        }
+        // Search for package.json (Node.js/npm)
+        services.extend(self.discover_npm_services(search_path)?);
+
+        // Search for docker-compose.yml
+        services.extend(self.discover_docker_services(search_path)?);
+
+        // Search for Procfile
+        services.extend(self.discover_procfile_services(search_path)?);
+
+        // Search for Python apps
+        services.extend(self.discover_python_services(search_path)?);
+
+        // Search for Go modules
+        services.extend(self.discover_go_services(search_path)?);
+
+        // Search for Cargo binaries
+        services.extend(self.discover_cargo_services(search_path)?);
+
+        // Search for Spring Boot, Rails and Laravel apps
+        services.extend(self.discover_spring_boot_services(search_path)?);
+        services.extend(self.discover_rails_services(search_path)?);
+        services.extend(self.discover_laravel_services(search_path)?);
+
+        // Search for Nx project targets
+        services.extend(self.discover_nx_targets(search_path)?);
 
         Ok(services)
     }
@@ -89,7 +211,8 @@ impl ServiceDetector {
             ServiceType::DockerCompose {
                 service_name,
                 compose_file_path,
-            } => self.start_docker_service(service_name, compose_file_path),
+                profiles,
+            } => self.start_docker_service(service_name, compose_file_path, profiles),
 
             ServiceType::Procfile {
                 process_name,
@@ -98,6 +221,24 @@ impl ServiceDetector {
 
             ServiceType::PythonApp { script_path } => self.start_python_app(script_path),
 
+            ServiceType::GoApp {
+                package,
+                module_dir,
+                air_config,
+            } => self.start_go_app(package, module_dir, air_config.as_deref()),
+
+            ServiceType::CargoBin {
+                package,
+                binary,
+                manifest_path,
+            } => self.start_cargo_bin(package, binary, manifest_path),
+
+            ServiceType::SpringBoot { build_file_path } => self.start_spring_boot(build_file_path),
+
+            ServiceType::RailsApp { app_dir } => self.start_rails_app(app_dir),
+
+            ServiceType::LaravelApp { artisan_path } => self.start_laravel_app(artisan_path),
+
             ServiceType::Custom {
                 command,
                 working_dir,
@@ -106,6 +247,11 @@ impl ServiceDetector {
         
         let pid = child.id();
 
+        // Keep whatever the service forks in one cgroup / job object named after it
+        if let Err(e) = crate::service_group::ServiceGroup::new(&service.name).adopt(pid) {
+            log::debug!("Service '{}' not placed in a service group: {}", service.name, e);
+        }
+
         // Spawn a background thread to reap the child when it exits,
         // preventing zombie process accumulation
         std::thread::spawn(move || {
@@ -133,13 +279,14 @@ impl ServiceDetector {
 
         // Extract scripts
         if let Some(scripts) = package_json.get("scripts").and_then(|s| s.as_object()) {
-            for (script_name, _script_value) in scripts {
+            for (script_name, script_value) in scripts {
                 // Skip test and build scripts
                 if script_name.contains("test") || script_name.contains("build") {
                     continue;
                 }
 
-                let inferred_port = Self::infer_port_from_script_name(script_name);
+                let script = script_value.as_str().unwrap_or_default();
+                let inferred_port = Self::infer_npm_port(script_name, script, search_path);
                 
                 services.push(DiscoveredService {
                     name: format!("npm:{}", script_name),
@@ -159,33 +306,27 @@ impl ServiceDetector {
 
     fn discover_docker_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
         let mut services = Vec::new();
-        
-        let compose_files = ["docker-compose.yml", "docker-compose.yaml", "compose.yml"];
-        
-        for compose_file_name in &compose_files {
-            let compose_path = search_path.join(compose_file_name);
-            
-            if !compose_path.exists() {
-                continue;
-            }
 
-            let content = fs::read_to_string(&compose_path)
-                .context("Failed to read docker-compose file")?;
-
-            // Simple YAML parsing for service names (proper YAML parser would be better)
-            for line in content.lines() {
-                if let Some(service_name) = Self::extract_docker_service_name(line) {
-                    services.push(DiscoveredService {
-                        name: format!("docker:{}", service_name),
-                        service_type: ServiceType::DockerCompose {
-                            service_name: service_name.clone(),
-                            compose_file_path: compose_path.clone(),
-                        },
-                        working_directory: search_path.to_path_buf(),
-                        inferred_port: None,
-                        description: format!("Docker Compose service: {}", service_name),
-                    });
+        for compose_path in crate::compose::find(search_path) {
+            for compose_service in crate::compose::load(&compose_path)? {
+                let mut description = format!("Docker Compose service: {}", compose_service.name);
+                if !compose_service.profiles.is_empty() {
+                    description.push_str(&format!(" (profiles: {})", compose_service.profiles.join(", ")));
+                }
+                if !compose_service.depends_on.is_empty() {
+                    description.push_str(&format!(", depends on {}", compose_service.depends_on.join(", ")));
                 }
+                services.push(DiscoveredService {
+                    name: format!("docker:{}", compose_service.name),
+                    service_type: ServiceType::DockerCompose {
+                        service_name: compose_service.name.clone(),
+                        compose_file_path: compose_path.clone(),
+                        profiles: compose_service.profiles.clone(),
+                    },
+                    working_directory: search_path.to_path_buf(),
+                    inferred_port: compose_service.published_ports.first().copied(),
+                    description,
+                });
             }
         }
 
@@ -203,19 +344,19 @@ impl ServiceDetector {
         let content = fs::read_to_string(&procfile_path)
             .context("Failed to read Procfile")?;
 
-        for line in content.lines() {
-            if let Some((process_name, _command)) = Self::parse_procfile_line(line) {
-                services.push(DiscoveredService {
-                    name: format!("procfile:{}", process_name),
-                    service_type: ServiceType::Procfile {
-                        process_name: process_name.clone(),
-                        procfile_path: procfile_path.clone(),
-                    },
-                    working_directory: search_path.to_path_buf(),
-                    inferred_port: None,
-                    description: format!("Procfile process: {}", process_name),
-                });
-            }
+        let processes: Vec<(String, String)> = content.lines().filter_map(Self::parse_procfile_line).collect();
+        for (index, (process_name, command)) in processes.into_iter().enumerate() {
+            let inferred_port = Self::procfile_port(&command, index, search_path);
+            services.push(DiscoveredService {
+                name: format!("procfile:{}", process_name),
+                service_type: ServiceType::Procfile {
+                    process_name: process_name.clone(),
+                    procfile_path: procfile_path.clone(),
+                },
+                working_directory: search_path.to_path_buf(),
+                inferred_port,
+                description: format!("Procfile process: {}", process_name),
+            });
         }
 
         Ok(services)
@@ -234,7 +375,8 @@ impl ServiceDetector {
                 let inferred_port = if *py_file == "manage.py" {
                     Some(8000) // Django default
                 } else {
-                    Some(5000) // Flask default
+                    // Apps commonly read PORT; otherwise the Flask default
+                    Self::env_file_port(search_path).or(Some(5000))
                 };
 
                 services.push(DiscoveredService {
@@ -252,6 +394,313 @@ impl ServiceDetector {
         Ok(services)
     }
 
+    fn discover_cargo_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
+        let cargo_toml_path = search_path.join("Cargo.toml");
+
+        if !cargo_toml_path.exists() {
+            return Ok(services);
+        }
+
+        let content = fs::read_to_string(&cargo_toml_path)
+            .context("Failed to read Cargo.toml")?;
+        let cargo_toml: toml::Value = content.parse()
+            .context("Failed to parse Cargo.toml")?;
+
+        // A workspace root without a package of its own has nothing to run
+        let Some(package_name) = cargo_toml
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+        else {
+            return Ok(services);
+        };
+
+        // Explicit [[bin]] targets, then the ones cargo finds by itself
+        let mut binaries: Vec<String> = cargo_toml
+            .get("bin")
+            .and_then(|b| b.as_array())
+            .map(|bins| {
+                bins.iter()
+                    .filter_map(|bin| bin.get("name")?.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let autobins = cargo_toml
+            .get("package")
+            .and_then(|p| p.get("autobins"))
+            .and_then(|a| a.as_bool())
+            .unwrap_or(true);
+        if autobins {
+            if search_path.join("src/main.rs").exists() {
+                binaries.push(package_name.to_string());
+            }
+            if let Ok(entries) = fs::read_dir(search_path.join("src/bin")) {
+                let mut found: Vec<String> = entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let path = entry.path();
+                        if path.extension().is_some_and(|ext| ext == "rs") {
+                            path.file_stem().map(|stem| stem.to_string_lossy().into_owned())
+                        } else if path.join("main.rs").exists() {
+                            path.file_name().map(|name| name.to_string_lossy().into_owned())
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                found.sort();
+                binaries.extend(found);
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        binaries.retain(|binary| seen.insert(binary.clone()));
+
+        for binary in binaries {
+            services.push(DiscoveredService {
+                name: format!("cargo:{}", binary),
+                service_type: ServiceType::CargoBin {
+                    package: package_name.to_string(),
+                    binary: binary.clone(),
+                    manifest_path: cargo_toml_path.clone(),
+                },
+                working_directory: search_path.to_path_buf(),
+                // Servers commonly read PORT
+                inferred_port: Self::env_file_port(search_path),
+                description: format!("cargo run --bin {} (Rust)", binary),
+            });
+        }
+
+        Ok(services)
+    }
+
+    fn discover_go_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
+        let go_mod_path = search_path.join("go.mod");
+
+        if !go_mod_path.exists() {
+            return Ok(services);
+        }
+
+        let content = fs::read_to_string(&go_mod_path)
+            .context("Failed to read go.mod")?;
+        let module = content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))
+            .map(|m| m.trim().trim_matches('"'))
+            .unwrap_or("app");
+        let module_name = module.rsplit('/').next().unwrap_or(module);
+        let inferred_port = Self::env_file_port(search_path);
+
+        // air rebuilds and restarts the whole module on changes
+        let air_config = search_path.join(".air.toml");
+        if air_config.exists() {
+            services.push(DiscoveredService {
+                name: format!("go:{}:air", module_name),
+                service_type: ServiceType::GoApp {
+                    package: ".".to_string(),
+                    module_dir: search_path.to_path_buf(),
+                    air_config: Some(air_config),
+                },
+                working_directory: search_path.to_path_buf(),
+                inferred_port,
+                description: format!("air live reload for {} (Go)", module),
+            });
+        }
+
+        // A main package at the root, and the cmd/<name> layout
+        let mut packages = Vec::new();
+        if Self::is_go_main_package(search_path) {
+            packages.push((module_name.to_string(), ".".to_string()));
+        }
+        if let Ok(entries) = fs::read_dir(search_path.join("cmd")) {
+            let mut commands: Vec<String> = entries
+                .flatten()
+                .filter(|entry| Self::is_go_main_package(&entry.path()))
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect();
+            commands.sort();
+            packages.extend(commands.into_iter().map(|name| (name.clone(), format!("./cmd/{}", name))));
+        }
+
+        for (name, package) in packages {
+            services.push(DiscoveredService {
+                name: format!("go:{}", name),
+                service_type: ServiceType::GoApp {
+                    package: package.clone(),
+                    module_dir: search_path.to_path_buf(),
+                    air_config: None,
+                },
+                working_directory: search_path.to_path_buf(),
+                inferred_port,
+                description: format!("go run {} (Go)", package),
+            });
+        }
+
+        Ok(services)
+    }
+
+    fn is_go_main_package(dir: &Path) -> bool {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return false;
+        };
+        entries.flatten().any(|entry| {
+            let path = entry.path();
+            path.extension().is_some_and(|ext| ext == "go")
+                && !path.to_string_lossy().ends_with("_test.go")
+                && fs::read_to_string(&path).is_ok_and(|content| {
+                    content.lines().any(|line| line.trim() == "package main")
+                })
+        })
+    }
+
+    fn discover_spring_boot_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
+
+        let build_files = ["build.gradle.kts", "build.gradle", "pom.xml"];
+        let Some(build_file_path) = build_files
+            .iter()
+            .map(|name| search_path.join(name))
+            .find(|path| path.exists())
+        else {
+            return Ok(services);
+        };
+
+        let content = fs::read_to_string(&build_file_path)
+            .context("Failed to read the Gradle/Maven build file")?;
+        if !content.contains("org.springframework.boot") && !content.contains("spring-boot") {
+            return Ok(services);
+        }
+
+        let gradle = build_file_path.extension().is_some_and(|ext| ext != "xml");
+        let task = if gradle { "gradle bootRun" } else { "mvn spring-boot:run" };
+        services.push(DiscoveredService {
+            name: if gradle { "gradle:bootRun".to_string() } else { "maven:spring-boot:run".to_string() },
+            service_type: ServiceType::SpringBoot {
+                build_file_path: build_file_path.clone(),
+            },
+            working_directory: search_path.to_path_buf(),
+            inferred_port: Self::spring_boot_port(search_path).or(Some(8080)),
+            description: format!("{} (Spring Boot)", task),
+        });
+
+        Ok(services)
+    }
+
+    /// `server.port` from application.properties or application.yml
+    fn spring_boot_port(dir: &Path) -> Option<u16> {
+        let resources = dir.join("src/main/resources");
+        let properties = fs::read_to_string(resources.join("application.properties")).ok();
+        let from_properties = properties.as_deref().and_then(|content| {
+            content.lines().find_map(|line| {
+                let (key, value) = line.split_once('=').or_else(|| line.split_once(':'))?;
+                (key.trim() == "server.port").then(|| value.trim().parse().ok())?
+            })
+        });
+        from_properties.or_else(|| {
+            ["application.yml", "application.yaml"].iter().find_map(|name| {
+                let content = fs::read_to_string(resources.join(name)).ok()?;
+                // Only the first document of a multi-profile file
+                let yaml: serde_yaml::Value = serde_yaml::Deserializer::from_str(&content)
+                    .next()
+                    .and_then(|doc| serde_yaml::Value::deserialize(doc).ok())?;
+                let port = yaml.get("server")?.get("port")?;
+                port.as_u64()
+                    .and_then(|p| u16::try_from(p).ok())
+                    .or_else(|| port.as_str()?.parse().ok())
+            })
+        })
+    }
+
+    fn discover_rails_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
+
+        if !search_path.join("bin/rails").exists() || !search_path.join("config/application.rb").exists() {
+            return Ok(services);
+        }
+
+        services.push(DiscoveredService {
+            name: "rails:server".to_string(),
+            service_type: ServiceType::RailsApp {
+                app_dir: search_path.to_path_buf(),
+            },
+            working_directory: search_path.to_path_buf(),
+            // Puma's generated config reads PORT, defaulting to 3000
+            inferred_port: Self::env_file_port(search_path).or(Some(3000)),
+            description: "bin/rails server (Ruby on Rails)".to_string(),
+        });
+
+        Ok(services)
+    }
+
+    fn discover_laravel_services(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
+        let artisan_path = search_path.join("artisan");
+
+        if !artisan_path.exists() || !search_path.join("composer.json").exists() {
+            return Ok(services);
+        }
+
+        // artisan serve reads SERVER_PORT
+        let inferred_port = Self::env_file_value(search_path, "SERVER_PORT").and_then(|port| port.parse().ok());
+        services.push(DiscoveredService {
+            name: "laravel:serve".to_string(),
+            service_type: ServiceType::LaravelApp { artisan_path },
+            working_directory: search_path.to_path_buf(),
+            inferred_port: inferred_port.or(Some(8000)),
+            description: "php artisan serve (Laravel)".to_string(),
+        });
+
+        Ok(services)
+    }
+
+    fn discover_nx_targets(&self, search_path: &Path) -> Result<Vec<DiscoveredService>> {
+        let mut services = Vec::new();
+        let project_json_path = search_path.join("project.json");
+
+        if !project_json_path.exists() {
+            return Ok(services);
+        }
+
+        let content = fs::read_to_string(&project_json_path)
+            .context("Failed to read project.json")?;
+        let project: serde_json::Value = serde_json::from_str(&content)
+            .context("Failed to parse project.json")?;
+
+        let Some(project_name) = project.get("name").and_then(|n| n.as_str()) else {
+            return Ok(services);
+        };
+
+        // Only the targets that run something long-lived
+        for target in ["serve", "dev", "start", "preview"] {
+            let Some(options) = project.get("targets").and_then(|t| t.get(target)) else {
+                continue;
+            };
+            let inferred_port = options
+                .pointer("/options/port")
+                .and_then(|p| p.as_u64())
+                .and_then(|p| u16::try_from(p).ok());
+
+            services.push(DiscoveredService {
+                name: format!("nx:{}:{}", project_name, target),
+                service_type: ServiceType::Custom {
+                    command: vec![
+                        "npx".to_string(),
+                        "nx".to_string(),
+                        "run".to_string(),
+                        format!("{}:{}", project_name, target),
+                    ],
+                    working_dir: search_path.to_path_buf(),
+                },
+                working_directory: search_path.to_path_buf(),
+                inferred_port,
+                description: format!("nx run {}:{} (Nx)", project_name, target),
+            });
+        }
+
+        Ok(services)
+    }
+
     // Private methods for starting services
 
     fn start_npm_script(&self, script_name: &str, package_json_path: &Path) -> Result<Child> {
@@ -261,17 +710,24 @@ impl ServiceDetector {
             .arg("run")
             .arg(script_name)
             .current_dir(working_dir)
+            .envs(&self.env)
             .spawn()
             .context(format!("Failed to start npm script: {}", script_name))
     }
 
-    fn start_docker_service(&self, service_name: &str, compose_file_path: &Path) -> Result<Child> {
+    fn start_docker_service(&self, service_name: &str, compose_file_path: &Path, profiles: &[String]) -> Result<Child> {
         let working_dir = compose_file_path.parent().unwrap_or(Path::new("."));
 
-        Command::new("docker-compose")
+        let mut command = Command::new("docker-compose");
+        command.arg("-f").arg(compose_file_path);
+        for profile in profiles {
+            command.arg("--profile").arg(profile);
+        }
+        command
             .arg("up")
             .arg(service_name)
             .current_dir(working_dir)
+            .envs(&self.env)
             .spawn()
             .context(format!("Failed to start docker service: {}", service_name))
     }
@@ -307,10 +763,93 @@ impl ServiceDetector {
         Command::new(command[0])
             .args(&command[1..])
             .current_dir(working_dir)
+            .envs(&self.env)
             .spawn()
             .context(format!("Failed to start Python app: {}", script_name))
     }
 
+    fn start_go_app(&self, package: &str, module_dir: &Path, air_config: Option<&Path>) -> Result<Child> {
+        let mut command = match air_config {
+            Some(config) => {
+                let mut command = Command::new("air");
+                command.arg("-c").arg(config);
+                command
+            }
+            None => {
+                let mut command = Command::new("go");
+                command.arg("run").arg(package);
+                command
+            }
+        };
+
+        command
+            .current_dir(module_dir)
+            .envs(&self.env)
+            .spawn()
+            .context(format!("Failed to start Go package: {}", package))
+    }
+
+    fn start_cargo_bin(&self, package: &str, binary: &str, manifest_path: &Path) -> Result<Child> {
+        let working_dir = manifest_path.parent().unwrap_or(Path::new("."));
+
+        Command::new("cargo")
+            .arg("run")
+            .arg("--manifest-path")
+            .arg(manifest_path)
+            .args(["--package", package, "--bin", binary])
+            .current_dir(working_dir)
+            .envs(&self.env)
+            .spawn()
+            .context(format!("Failed to start cargo binary: {}", binary))
+    }
+
+    fn start_spring_boot(&self, build_file_path: &Path) -> Result<Child> {
+        let working_dir = build_file_path.parent().unwrap_or(Path::new("."));
+        let gradle = build_file_path.extension().is_some_and(|ext| ext != "xml");
+
+        // The project's wrapper pins the build tool version; fall back to one on PATH
+        let (wrapper, tool, task) = if gradle {
+            ("gradlew", "gradle", "bootRun")
+        } else {
+            ("mvnw", "mvn", "spring-boot:run")
+        };
+        let wrapper = if cfg!(target_os = "windows") {
+            working_dir.join(format!("{}.bat", wrapper))
+        } else {
+            working_dir.join(wrapper)
+        };
+        let program = if wrapper.exists() { wrapper } else { PathBuf::from(tool) };
+
+        Command::new(program)
+            .arg(task)
+            .current_dir(working_dir)
+            .envs(&self.env)
+            .spawn()
+            .context(format!("Failed to start Spring Boot app with {}", tool))
+    }
+
+    fn start_rails_app(&self, app_dir: &Path) -> Result<Child> {
+        Command::new("ruby")
+            .arg("bin/rails")
+            .arg("server")
+            .current_dir(app_dir)
+            .envs(&self.env)
+            .spawn()
+            .context("Failed to start Rails server")
+    }
+
+    fn start_laravel_app(&self, artisan_path: &Path) -> Result<Child> {
+        let working_dir = artisan_path.parent().unwrap_or(Path::new("."));
+
+        Command::new("php")
+            .arg("artisan")
+            .arg("serve")
+            .current_dir(working_dir)
+            .envs(&self.env)
+            .spawn()
+            .context("Failed to start Laravel app")
+    }
+
     fn start_custom_command(&self, command: &[String], working_dir: &Path) -> Result<Child> {
         if command.is_empty() {
             return Err(anyhow::anyhow!("Empty command"));
@@ -319,6 +858,7 @@ impl ServiceDetector {
         Command::new(&command[0])
             .args(&command[1..])
             .current_dir(working_dir)
+            .envs(&self.env)
             .spawn()
             .context("Failed to start custom command")
     }
@@ -330,6 +870,7 @@ impl ServiceDetector {
                 .arg("/C")
                 .arg(command)
                 .current_dir(working_dir)
+                .envs(&self.env)
                 .spawn()
                 .context("Failed to execute shell command")
         }
@@ -340,6 +881,7 @@ impl ServiceDetector {
                 .arg("-c")
                 .arg(command)
                 .current_dir(working_dir)
+                .envs(&self.env)
                 .spawn()
                 .context("Failed to execute shell command")
         }
@@ -358,35 +900,150 @@ impl ServiceDetector {
         }
     }
 
-    fn extract_docker_service_name(line: &str) -> Option<String> {
-        // Extract service names from docker-compose.yml by checking indentation
-        // Service names appear exactly 2 spaces indented under "services:"
-        // Format: "  service_name:" (2 spaces, then name, then colon)
-        // Properties like ports:, environment:, volumes: have 4+ spaces
-
-        // Check for exactly 2 spaces of indentation (service level)
-        // This filters out top-level keys (0 spaces) and properties (4+ spaces)
-        if !line.starts_with("  ") || line.starts_with("   ") {
-            return None;
+    /// The port `command`, run in `dir`, most likely listens on: a port given on the command
+    /// line, else one declared in the project's framework config, `.env` or package.json
+    /// script. Used for orchestrated services that don't set `port`.
+    pub fn infer_port_for_command(command: &str, dir: &Path) -> Option<u16> {
+        let words = crate::command_line::parse_command_line(command);
+        if let Some(port) = Self::port_from_words(&words) {
+            return Some(port);
         }
+        // npm run dev / yarn dev / pnpm dev: look at the script itself
+        let script_name = match words.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            ["npm" | "pnpm" | "yarn" | "bun", "run", name, ..] => Some(*name),
+            ["npm" | "pnpm", "start", ..] => Some("start"),
+            ["yarn" | "pnpm", name, ..] => Some(*name),
+            _ => None,
+        };
+        if let Some(script_name) = script_name {
+            if let Some(script) = Self::package_script(dir, script_name) {
+                return Self::infer_npm_port(script_name, &script, dir);
+            }
+        }
+        Self::framework_port(&words, dir)
+    }
 
-        let trimmed = line.trim();
-        
-        // Skip the services: key itself and comments
-        if trimmed.starts_with("services:") || trimmed.starts_with('#') {
-            return None;
+    fn infer_npm_port(script_name: &str, script: &str, dir: &Path) -> Option<u16> {
+        let words = crate::command_line::parse_command_line(script);
+        Self::port_from_words(&words)
+            .or_else(|| Self::framework_port(&words, dir))
+            // A server script guessed from its name still reads PORT from .env
+            .or_else(|| {
+                Self::infer_port_from_script_name(script_name)
+                    .map(|guess| Self::env_file_port(dir).unwrap_or(guess))
+            })
+    }
+
+    fn package_script(dir: &Path, script_name: &str) -> Option<String> {
+        let content = fs::read_to_string(dir.join("package.json")).ok()?;
+        let package_json: serde_json::Value = serde_json::from_str(&content).ok()?;
+        package_json
+            .get("scripts")?
+            .get(script_name)?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    /// A port passed on the command line: `--port 3000`, `--port=3000`, `-p 3000`,
+    /// `PORT=3000 node server.js`, `--bind 0.0.0.0:8000` or `runserver 0.0.0.0:8000`
+    fn port_from_words(words: &[String]) -> Option<u16> {
+        fn port_value(value: &str) -> Option<u16> {
+            // The last part of host:port
+            value.rsplit(':').next()?.parse().ok()
         }
-        
-        if trimmed.ends_with(':') {
-            let name = trimmed.trim_end_matches(':').trim();
-            if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
-                return Some(name.to_string());
+        for (i, word) in words.iter().enumerate() {
+            let next = words.get(i + 1).map(String::as_str);
+            let port = match word.as_str() {
+                "--port" | "-p" | "--bind" | "-b" => next.and_then(port_value),
+                "runserver" => next.filter(|n| !n.starts_with('-')).and_then(port_value),
+                _ => word
+                    .strip_prefix("--port=")
+                    .or_else(|| word.strip_prefix("--bind="))
+                    .or_else(|| word.strip_prefix("PORT="))
+                    .and_then(port_value),
+            };
+            if port.is_some() {
+                return port;
             }
         }
-        
         None
     }
 
+    /// Dev servers that declare their port in their own config, with their defaults
+    fn framework_port(words: &[String], dir: &Path) -> Option<u16> {
+        let runs = |tool: &str| {
+            words
+                .iter()
+                .any(|w| w == tool || w.ends_with(&format!("/{}", tool)))
+        };
+        let has = |word: &str| words.iter().any(|w| w == word);
+        if runs("vite") {
+            let default = if has("preview") { 4173 } else { 5173 };
+            Self::config_file_port(dir, &["vite.config.ts", "vite.config.js", "vite.config.mts", "vite.config.mjs"])
+                .or(Some(default))
+        } else if runs("ng") && has("serve") {
+            Self::angular_port(dir).or(Some(4200))
+        } else if runs("next") || runs("react-scripts") || runs("nuxt") {
+            Self::env_file_port(dir)
+                .or_else(|| Self::config_file_port(dir, &["next.config.js", "next.config.mjs", "next.config.ts"]))
+                .or(Some(3000))
+        } else {
+            None
+        }
+    }
+
+    /// The first `port: N` in a JavaScript/TypeScript config such as vite.config.ts
+    fn config_file_port(dir: &Path, names: &[&str]) -> Option<u16> {
+        let pattern = regex::Regex::new(r"\bport\s*:\s*(\d{1,5})\b").ok()?;
+        names.iter().find_map(|name| {
+            let content = fs::read_to_string(dir.join(name)).ok()?;
+            pattern.captures(&content)?[1].parse().ok()
+        })
+    }
+
+    /// `serve.options.port` of the first project in angular.json that sets one
+    fn angular_port(dir: &Path) -> Option<u16> {
+        let content = fs::read_to_string(dir.join("angular.json")).ok()?;
+        let angular: serde_json::Value = serde_json::from_str(&content).ok()?;
+        angular.get("projects")?.as_object()?.values().find_map(|project| {
+            let port = project.pointer("/architect/serve/options/port")?.as_u64()?;
+            u16::try_from(port).ok()
+        })
+    }
+
+    /// PORT from .env.local or .env
+    fn env_file_port(dir: &Path) -> Option<u16> {
+        Self::env_file_value(dir, "PORT")?.parse().ok()
+    }
+
+    fn env_file_value(dir: &Path, key: &str) -> Option<String> {
+        [".env.local", ".env"].iter().find_map(|name| {
+            let path = dir.join(name);
+            if !path.is_file() {
+                return None;
+            }
+            crate::env_file::load(&path)
+                .ok()?
+                .into_iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value)
+        })
+    }
+
+    /// A Procfile process's port. Processes that use `$PORT` get foreman's: the base port
+    /// (PORT from .env, else 5000) plus 100 for each process listed before them.
+    fn procfile_port(command: &str, index: usize, dir: &Path) -> Option<u16> {
+        let words = crate::command_line::parse_command_line(command);
+        if let Some(port) = Self::port_from_words(&words) {
+            return Some(port);
+        }
+        if command.contains("$PORT") || command.contains("${PORT}") {
+            let base = Self::env_file_port(dir).unwrap_or(5000);
+            return u16::try_from(index * 100).ok().and_then(|offset| base.checked_add(offset));
+        }
+        Self::framework_port(&words, dir)
+    }
+
     fn parse_procfile_line(line: &str) -> Option<(String, String)> {
         // Format: "process_name: command"
         let trimmed = line.trim();
@@ -424,17 +1081,116 @@ mod tests {
         assert_eq!(result, Some(("web".to_string(), "npm start".to_string())));
     }
 
-    #[test]
-    fn test_extract_docker_service_name() {
-        let line = "  web:";
-        let result = ServiceDetector::extract_docker_service_name(line);
-        assert_eq!(result, Some("web".to_string()));
-    }
-
     #[test]
     fn test_infer_port() {
         assert_eq!(ServiceDetector::infer_port_from_script_name("dev"), Some(3000));
         assert_eq!(ServiceDetector::infer_port_from_script_name("dev:api"), Some(8000));
     }
+
+    #[test]
+    fn test_port_from_command() {
+        let port = |command: &str| {
+            ServiceDetector::port_from_words(&crate::command_line::parse_command_line(command))
+        };
+        assert_eq!(port("next dev -p 4000"), Some(4000));
+        assert_eq!(port("vite --port=5174"), Some(5174));
+        assert_eq!(port("PORT=8081 node server.js"), Some(8081));
+        assert_eq!(port("gunicorn app:app --bind 0.0.0.0:8001"), Some(8001));
+        assert_eq!(port("python manage.py runserver 0.0.0.0:8002"), Some(8002));
+        assert_eq!(port("tsc -p tsconfig.json"), None);
+    }
+
+    #[test]
+    fn test_declared_ports() {
+        let dir = std::env::temp_dir().join(format!("port-kill-declared-ports-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"scripts": {"dev": "vite", "start": "node server.js", "web": "next start", "ng": "ng serve"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("vite.config.ts"), "export default { server: { port: 5180 } }").unwrap();
+        fs::write(dir.join(".env"), "PORT=3100\n").unwrap();
+        fs::write(dir.join("Procfile"), "web: bundle exec puma -p $PORT\nworker: node worker.js -p $PORT\n").unwrap();
+        fs::write(
+            dir.join("angular.json"),
+            r#"{"projects": {"app": {"architect": {"serve": {"options": {"port": 4300}}}}}}"#,
+        )
+        .unwrap();
+
+        let services = ServiceDetector::with_paths(vec![dir.clone()]).discover_services().unwrap();
+        let port = |name: &str| services.iter().find(|s| s.name == name).unwrap().inferred_port;
+        assert_eq!(port("npm:dev"), Some(5180));
+        // Guessed from the name, with PORT from .env
+        assert_eq!(port("npm:start"), Some(3100));
+        assert_eq!(port("npm:web"), Some(3100));
+        assert_eq!(port("npm:ng"), Some(4300));
+        // foreman's ports, from the base PORT
+        assert_eq!(port("procfile:web"), Some(3100));
+        assert_eq!(port("procfile:worker"), Some(3200));
+
+        assert_eq!(ServiceDetector::infer_port_for_command("npm run dev", &dir), Some(5180));
+        assert_eq!(ServiceDetector::infer_port_for_command("yarn ng", &dir), Some(4300));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_other_runtimes() {
+        let root = std::env::temp_dir().join(format!("port-kill-runtimes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("go/go.mod", "module github.com/acme/shop\n\ngo 1.22\n");
+        write("go/.air.toml", "");
+        write("go/cmd/api/main.go", "package main\n\nfunc main() {}\n");
+        write("go/internal/db/db.go", "package db\n");
+        write("spring/build.gradle.kts", "plugins { id(\"org.springframework.boot\") version \"3.3.0\" }");
+        write("spring/src/main/resources/application.yml", "server:\n  port: 9090\n---\nserver:\n  port: 1\n");
+        write("rails/bin/rails", "");
+        write("rails/config/application.rb", "");
+        write("laravel/artisan", "");
+        write("laravel/composer.json", "{}");
+        write("laravel/.env", "SERVER_PORT=8800\n");
+
+        let detect = |dir: &str| ServiceDetector::with_paths(vec![root.join(dir)]).discover_services().unwrap();
+        let go: Vec<String> = detect("go").into_iter().map(|s| s.name).collect();
+        assert_eq!(go, vec!["go:shop:air", "go:api"]);
+        let spring = detect("spring");
+        assert_eq!(spring[0].name, "gradle:bootRun");
+        assert_eq!(spring[0].inferred_port, Some(9090));
+        assert_eq!(detect("rails")[0].inferred_port, Some(3000));
+        assert_eq!(detect("laravel")[0].inferred_port, Some(8800));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_workspace_members() {
+        let root = std::env::temp_dir().join(format!("port-kill-monorepo-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("apps/web")).unwrap();
+        fs::create_dir_all(root.join("crates/server/src")).unwrap();
+        fs::write(root.join("pnpm-workspace.yaml"), "packages:\n  - 'apps/*'\n").unwrap();
+        fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        fs::write(root.join("apps/web/package.json"), r#"{"scripts": {"dev": "next dev"}}"#).unwrap();
+        fs::write(
+            root.join("apps/web/project.json"),
+            r#"{"name": "web", "targets": {"serve": {"options": {"port": 4201}}, "lint": {}}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("crates/server/Cargo.toml"), "[package]\nname = \"server\"\n").unwrap();
+        fs::write(root.join("crates/server/src/main.rs"), "fn main() {}").unwrap();
+
+        let services = ServiceDetector::with_paths(vec![root.clone()]).discover_services().unwrap();
+        let mut names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["apps/web/npm:dev", "apps/web/nx:web:serve", "crates/server/cargo:server"]);
+        let nx = services.iter().find(|s| s.name == "apps/web/nx:web:serve").unwrap();
+        assert_eq!(nx.inferred_port, Some(4201));
+        assert_eq!(nx.working_directory, root.join("apps/web"));
+        let _ = fs::remove_dir_all(&root);
+    }
 }
 
@@ -0,0 +1,842 @@
+//! OS-level grouping for services started by port-kill (`--up`, `--start`).
+//!
+//! Each service is placed in a container named after it: a cgroup v2 on Linux and a Job
+//! Object on Windows. Everything the service forks stays in the container, so identifying
+//! its processes and stopping all of them no longer depends on matching ports or PIDs.
+//! A cgroup's path is recorded when it is created and a job is opened by name, so a later
+//! `--down` finds the group from any terminal. Where neither is available the service is
+//! started in its own process group instead.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::process::Command;
+use std::time::Duration;
+
+/// Container for one service's process tree
+#[derive(Debug, Clone)]
+pub struct ServiceGroup {
+    name: String,
+    limits: ResourceLimits,
+}
+
+/// Limits enforced on a whole service group, mirroring docker-compose `mem_limit` / `cpus`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub memory_bytes: Option<u64>,
+    /// CPU time in cores, e.g. 0.5 for half a core
+    pub cpus: Option<f64>,
+    /// On OOM kill the whole group rather than one process, so a restart starts clean
+    pub oom_kill_group: bool,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.memory_bytes.is_none() && self.cpus.is_none()
+    }
+}
+
+/// Usage and limit violations of a running group, as far as the platform reports them
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResourceUsage {
+    pub memory_bytes: Option<u64>,
+    pub peak_memory_bytes: Option<u64>,
+    pub memory_limit: Option<u64>,
+    /// Times the group ran into its memory limit
+    pub memory_limit_hits: u64,
+    /// Processes the kernel killed for exceeding the memory limit
+    pub oom_kills: u64,
+    /// Scheduler periods in which the group was throttled by its CPU limit
+    pub cpu_throttled: u64,
+}
+
+/// Parse a docker-style size such as `512M`, `2G`, `1.5gb` or a plain byte count
+pub fn parse_size(text: &str) -> Result<u64> {
+    let lower = text.trim().to_lowercase();
+    let number_end = lower
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(number_end);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid size '{}'", text))?;
+    let multiplier: u64 = match unit.trim().trim_end_matches('b').trim_end_matches('i') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => anyhow::bail!("Invalid size '{}' (use e.g. 512M or 2G)", text),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Make a service name safe for a cgroup directory or kernel object name
+fn sanitize(service_name: &str) -> String {
+    service_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl ServiceGroup {
+    pub fn new(service_name: &str) -> Self {
+        Self {
+            name: format!("port-kill-{}", sanitize(service_name)),
+            limits: ResourceLimits::default(),
+        }
+    }
+
+    /// Limits to enforce when a process is adopted
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Name of the cgroup / Job Object, e.g. `port-kill-api`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Human-readable kind of container used on this platform
+    pub fn kind(&self) -> &'static str {
+        #[cfg(target_os = "linux")]
+        if cgroup::find(&self.name).is_some() {
+            return "cgroup";
+        }
+        if cfg!(target_os = "windows") {
+            "job object"
+        } else {
+            "process group"
+        }
+    }
+
+    /// Prepare a command before it is spawned. On Unix the service becomes the leader
+    /// of its own process group so the fallback can signal the whole tree. On Windows it
+    /// is created suspended, so it is in its job (and under its limits) before it runs;
+    /// [`resume`](Self::resume) starts it.
+    pub fn configure(&self, cmd: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(windows_sys::Win32::System::Threading::CREATE_SUSPENDED);
+        }
+        #[cfg(not(any(unix, target_os = "windows")))]
+        {
+            let _ = cmd;
+        }
+    }
+
+    /// Let a service spawned with [`configure`](Self::configure) run, whether or not
+    /// [`adopt`](Self::adopt) succeeded. Only Windows services wait for this.
+    pub fn resume(&self, pid: u32) -> Result<()> {
+        #[cfg(target_os = "windows")]
+        {
+            job::resume(pid)
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = pid;
+            Ok(())
+        }
+    }
+
+    /// Move a freshly spawned service into its container, applying any limits first
+    pub fn adopt(&self, pid: u32) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            cgroup::adopt(&self.name, pid, &self.limits)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            job::adopt(&self.name, pid, &self.limits)
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            let _ = pid;
+            Err(anyhow::anyhow!(
+                "no cgroups or job objects on this platform; using a process group"
+            ))
+        }
+    }
+
+    /// PIDs currently inside the container (empty if it doesn't exist)
+    pub fn pids(&self) -> Vec<u32> {
+        #[cfg(target_os = "linux")]
+        {
+            cgroup::pids(&self.name)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            job::pids(&self.name)
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            Vec::new()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.pids().is_empty()
+    }
+
+    /// Remove an emptied container so a restarted service starts with fresh counters
+    pub fn release(&self) {
+        #[cfg(target_os = "linux")]
+        cgroup::remove(&self.name);
+    }
+
+    /// Resource usage of the container, None when it doesn't exist or has no accounting
+    pub fn usage(&self) -> Option<ResourceUsage> {
+        #[cfg(target_os = "linux")]
+        {
+            cgroup::usage(&self.name)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            job::usage(&self.name)
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            None
+        }
+    }
+
+    /// Stop every process in the container: SIGTERM first, SIGKILL whatever is left
+    /// after `grace`. Returns how many processes were found.
+    pub async fn terminate(&self, grace: Duration) -> Result<usize> {
+        let pids = self.pids();
+        if pids.is_empty() {
+            return Ok(0);
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            cgroup::signal_all(&pids, nix::sys::signal::Signal::SIGTERM);
+            let deadline = std::time::Instant::now() + grace;
+            while std::time::Instant::now() < deadline && self.is_active() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            cgroup::kill_all(&self.name)?;
+            cgroup::remove(&self.name);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            // Job Objects have no graceful stop; give console apps a chance via taskkill first
+            for pid in &pids {
+                let _ = Command::new("taskkill")
+                    .args(["/PID", &pid.to_string()])
+                    .output();
+            }
+            tokio::time::sleep(grace).await;
+            job::terminate(&self.name)?;
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            let _ = grace;
+        }
+
+        Ok(pids.len())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod cgroup {
+    use super::{ResourceLimits, ResourceUsage};
+    use anyhow::{Context, Result};
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+    fn is_cgroup2() -> bool {
+        Path::new(CGROUP_ROOT).join("cgroup.controllers").exists()
+    }
+
+    /// Our own cgroup, from the `0::/path` line of /proc/self/cgroup
+    fn own_cgroup() -> Option<PathBuf> {
+        let content = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let path = content.lines().find_map(|l| l.strip_prefix("0::"))?;
+        Some(Path::new(CGROUP_ROOT).join(path.trim_start_matches('/')))
+    }
+
+    /// Groups' directories by name, as recorded when they were created, so a later `--down`
+    /// finds them even when run from a different terminal (and therefore a different cgroup)
+    fn registry_path() -> PathBuf {
+        crate::uninstall::home_dir()
+            .join(".port-kill")
+            .join("cgroups.json")
+    }
+
+    fn load_registry(path: &Path) -> HashMap<String, PathBuf> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_registry(path: &Path, registry: &HashMap<String, PathBuf>) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(registry)?)?;
+        Ok(())
+    }
+
+    /// Record (or with None, forget) where group `name` lives
+    fn record(name: &str, dir: Option<&Path>) {
+        let path = registry_path();
+        let mut registry = load_registry(&path);
+        match dir {
+            Some(dir) => registry.insert(name.to_string(), dir.to_path_buf()),
+            None => registry.remove(name),
+        };
+        if let Err(e) = save_registry(&path, &registry) {
+            log::warn!(
+                "Could not record cgroup {} in {}: {:#}",
+                name,
+                path.display(),
+                e
+            );
+        }
+        if let Ok(mut known) = KNOWN.lock() {
+            let known = known.get_or_insert_with(HashMap::new);
+            match dir {
+                Some(dir) => known.insert(name.to_string(), dir.to_path_buf()),
+                None => known.remove(name),
+            };
+        }
+    }
+
+    /// Whether `dir` is a live cgroup for group `name`, not just any directory called that
+    fn is_group_dir(dir: &Path, name: &str) -> bool {
+        dir.starts_with(CGROUP_ROOT)
+            && dir.file_name().is_some_and(|file| file == name)
+            && dir.join("cgroup.procs").exists()
+    }
+
+    /// Where a new group goes: the root when privileged, else next to or under our own
+    /// (delegated) cgroup
+    fn candidates() -> Vec<PathBuf> {
+        let mut candidates = vec![PathBuf::from(CGROUP_ROOT)];
+        if let Some(own) = own_cgroup() {
+            if let Some(parent) = own.parent() {
+                candidates.push(parent.to_path_buf());
+            }
+            candidates.push(own);
+        }
+        candidates
+    }
+
+    /// Directories found already, so the stop loop doesn't read the registry every time
+    static KNOWN: Mutex<Option<HashMap<String, PathBuf>>> = Mutex::new(None);
+
+    /// The existing group `name`: where it was recorded, else where this process would
+    /// have created it
+    pub fn find(name: &str) -> Option<PathBuf> {
+        if !is_cgroup2() {
+            return None;
+        }
+        let mut known = KNOWN.lock().ok()?;
+        let known = known.get_or_insert_with(HashMap::new);
+        if let Some(dir) = known.get(name).filter(|dir| is_group_dir(dir, name)) {
+            return Some(dir.clone());
+        }
+        let dir = load_registry(&registry_path())
+            .remove(name)
+            .into_iter()
+            .chain(candidates().into_iter().map(|base| base.join(name)))
+            .find(|dir| is_group_dir(dir, name))?;
+        known.insert(name.to_string(), dir.clone());
+        Some(dir)
+    }
+
+    /// Read a single number from a cgroup interface file (`max` reads as None)
+    fn read_number(path: &Path) -> Option<u64> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    /// Read a `key value` counter from a flat-keyed file such as memory.events
+    fn read_counter(path: &Path, key: &str) -> u64 {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| {
+                content.lines().find_map(|line| {
+                    let (k, v) = line.split_once(' ')?;
+                    if k == key {
+                        v.trim().parse().ok()
+                    } else {
+                        None
+                    }
+                })
+            })
+            .unwrap_or(0)
+    }
+
+    /// Write limits into the group. The parent must delegate the memory/cpu controllers,
+    /// which we try to enable if they aren't already.
+    fn apply_limits(dir: &Path, limits: &ResourceLimits) -> Result<()> {
+        if limits.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = dir.parent() {
+            let _ = fs::write(parent.join("cgroup.subtree_control"), "+memory +cpu");
+        }
+        if let Some(bytes) = limits.memory_bytes {
+            fs::write(dir.join("memory.max"), bytes.to_string())
+                .with_context(|| format!("memory controller not available in {}", dir.display()))?;
+            if limits.oom_kill_group {
+                let _ = fs::write(dir.join("memory.oom.group"), "1");
+            }
+        }
+        if let Some(cpus) = limits.cpus {
+            const PERIOD: u64 = 100_000;
+            let quota = ((cpus * PERIOD as f64) as u64).max(1_000);
+            fs::write(dir.join("cpu.max"), format!("{} {}", quota, PERIOD))
+                .with_context(|| format!("cpu controller not available in {}", dir.display()))?;
+        }
+        Ok(())
+    }
+
+    pub fn usage(name: &str) -> Option<ResourceUsage> {
+        let dir = find(name)?;
+        let memory_events = dir.join("memory.events");
+        Some(ResourceUsage {
+            memory_bytes: read_number(&dir.join("memory.current")),
+            peak_memory_bytes: read_number(&dir.join("memory.peak")),
+            memory_limit: read_number(&dir.join("memory.max")),
+            memory_limit_hits: read_counter(&memory_events, "max"),
+            oom_kills: read_counter(&memory_events, "oom_kill"),
+            cpu_throttled: read_counter(&dir.join("cpu.stat"), "nr_throttled"),
+        })
+    }
+
+    /// Create the group (root-level when privileged, else under our own delegated cgroup) and move `pid` in
+    pub fn adopt(name: &str, pid: u32, limits: &ResourceLimits) -> Result<()> {
+        if !is_cgroup2() {
+            anyhow::bail!("cgroup v2 is not mounted at {}", CGROUP_ROOT);
+        }
+
+        let dir = match find(name) {
+            Some(dir) => dir,
+            None => {
+                let dir = candidates()
+                    .into_iter()
+                    .map(|base| base.join(name))
+                    .find(|dir| fs::create_dir(dir).is_ok())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("no writable cgroup found (cgroup delegation not enabled?)")
+                    })?;
+                record(name, Some(&dir));
+                dir
+            }
+        };
+
+        apply_limits(&dir, limits)?;
+        fs::write(dir.join("cgroup.procs"), pid.to_string())
+            .with_context(|| format!("Failed to move PID {} into {}", pid, dir.display()))?;
+        log::info!("Placed PID {} in cgroup {}", pid, dir.display());
+        Ok(())
+    }
+
+    pub fn pids(name: &str) -> Vec<u32> {
+        let Some(dir) = find(name) else {
+            return Vec::new();
+        };
+        // cgroup.procs only lists direct members; include nested cgroups the service created
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name() == "cgroup.procs")
+            .filter_map(|e| fs::read_to_string(e.path()).ok())
+            .flat_map(|content| {
+                content
+                    .lines()
+                    .filter_map(|l| l.trim().parse().ok())
+                    .collect::<Vec<u32>>()
+            })
+            .collect()
+    }
+
+    pub fn signal_all(pids: &[u32], signal: Signal) {
+        for pid in pids {
+            let _ = kill(Pid::from_raw(*pid as i32), signal);
+        }
+    }
+
+    /// Kill everything left, using cgroup.kill (Linux 5.14+) when available
+    pub fn kill_all(name: &str) -> Result<()> {
+        let Some(dir) = find(name) else {
+            return Ok(());
+        };
+        if fs::write(dir.join("cgroup.kill"), "1").is_err() {
+            signal_all(&pids(name), Signal::SIGKILL);
+        }
+        Ok(())
+    }
+
+    /// Remove the (now empty) group; the kernel refuses while processes are still exiting
+    pub fn remove(name: &str) {
+        if let Some(dir) = find(name) {
+            for _ in 0..10 {
+                if fs::remove_dir(&dir).is_ok() {
+                    record(name, None);
+                    return;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            log::warn!("Could not remove cgroup {}", dir.display());
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_group_registry() {
+            let path = std::env::temp_dir()
+                .join(format!("port-kill-cgroups-{}", std::process::id()))
+                .join("cgroups.json");
+            assert!(load_registry(&path).is_empty());
+            let dir = Path::new(CGROUP_ROOT).join("user.slice/port-kill-api");
+            let registry = HashMap::from([("port-kill-api".to_string(), dir.clone())]);
+            save_registry(&path, &registry).unwrap();
+            assert_eq!(load_registry(&path).get("port-kill-api"), Some(&dir));
+            let _ = fs::remove_dir_all(path.parent().unwrap());
+
+            // Only a cgroup under the hierarchy with the group's own name counts
+            assert!(!is_group_dir(
+                &std::env::temp_dir().join("port-kill-api"),
+                "port-kill-api"
+            ));
+            assert!(!is_group_dir(Path::new(CGROUP_ROOT), "port-kill-api"));
+            if is_cgroup2() {
+                assert!(is_group_dir(Path::new(CGROUP_ROOT), "cgroup"));
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod job {
+    use super::{ResourceLimits, ResourceUsage};
+    use anyhow::Result;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, DuplicateHandle, DUPLICATE_SAME_ACCESS, HANDLE, INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectBasicProcessIdList,
+        JobObjectCpuRateControlInformation, JobObjectExtendedLimitInformation, OpenJobObjectW,
+        QueryInformationJobObject, SetInformationJobObject, TerminateJobObject,
+        JOBOBJECT_BASIC_PROCESS_ID_LIST, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_ALL_ACCESS,
+        JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+        JOB_OBJECT_LIMIT_JOB_MEMORY,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentProcess, OpenProcess, OpenThread, ResumeThread, PROCESS_DUP_HANDLE,
+        PROCESS_SET_QUOTA, PROCESS_TERMINATE, THREAD_SUSPEND_RESUME,
+    };
+
+    const MAX_PIDS: usize = 1024;
+
+    /// Job handles stay open for the life of this process: a named job loses its name
+    /// once the last handle closes, even while its processes keep running. [`adopt`] also
+    /// leaves a handle in the service, which keeps the name after this process exits.
+    static JOBS: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+
+    fn wide(name: &str) -> Vec<u16> {
+        format!("Local\\{}", name)
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect()
+    }
+
+    /// The job `name`: ours, one an earlier run created (opened by name), or with `create`
+    /// a new one
+    fn job_handle(name: &str, create: bool) -> Option<HANDLE> {
+        let mut jobs = JOBS.lock().ok()?;
+        let jobs = jobs.get_or_insert_with(HashMap::new);
+        if let Some(handle) = jobs.get(name) {
+            return Some(*handle as HANDLE);
+        }
+        let name_w = wide(name);
+        let mut handle = unsafe { OpenJobObjectW(JOB_OBJECT_ALL_ACCESS, 0, name_w.as_ptr()) };
+        if handle.is_null() && create {
+            handle = unsafe { CreateJobObjectW(std::ptr::null(), name_w.as_ptr()) };
+        }
+        if handle.is_null() {
+            return None;
+        }
+        jobs.insert(name.to_string(), handle as usize);
+        Some(handle)
+    }
+
+    fn apply_limits(job: HANDLE, name: &str, limits: &ResourceLimits) -> Result<()> {
+        unsafe {
+            if let Some(bytes) = limits.memory_bytes {
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+                info.JobMemoryLimit = bytes as usize;
+                if SetInformationJobObject(
+                    job,
+                    JobObjectExtendedLimitInformation,
+                    (&info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION).cast(),
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                ) == 0
+                {
+                    anyhow::bail!(
+                        "Failed to set memory limit on job object {}: {}",
+                        name,
+                        std::io::Error::last_os_error()
+                    );
+                }
+            }
+            if let Some(cpus) = limits.cpus {
+                // CpuRate is in 1/100ths of a percent of the whole machine
+                let cores = std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1) as f64;
+                let rate = ((cpus / cores) * 10_000.0).clamp(1.0, 10_000.0) as u32;
+                let mut info: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = std::mem::zeroed();
+                info.ControlFlags =
+                    JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+                info.Anonymous.CpuRate = rate;
+                if SetInformationJobObject(
+                    job,
+                    JobObjectCpuRateControlInformation,
+                    (&info as *const JOBOBJECT_CPU_RATE_CONTROL_INFORMATION).cast(),
+                    std::mem::size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>() as u32,
+                ) == 0
+                {
+                    anyhow::bail!(
+                        "Failed to set CPU limit on job object {}: {}",
+                        name,
+                        std::io::Error::last_os_error()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Job Objects only track peak memory; a job that reached its limit had allocations refused
+    pub fn usage(name: &str) -> Option<ResourceUsage> {
+        let job = job_handle(name, false)?;
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        let ok = unsafe {
+            QueryInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                (&mut info as *mut JOBOBJECT_EXTENDED_LIMIT_INFORMATION).cast(),
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                std::ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return None;
+        }
+        let limited = info.BasicLimitInformation.LimitFlags & JOB_OBJECT_LIMIT_JOB_MEMORY != 0;
+        let peak = info.PeakJobMemoryUsed as u64;
+        let limit = limited.then_some(info.JobMemoryLimit as u64);
+        Some(ResourceUsage {
+            peak_memory_bytes: Some(peak),
+            memory_limit: limit,
+            memory_limit_hits: limit.map_or(0, |limit| u64::from(peak >= limit)),
+            ..Default::default()
+        })
+    }
+
+    pub fn adopt(name: &str, pid: u32, limits: &ResourceLimits) -> Result<()> {
+        let job = job_handle(name, true)
+            .ok_or_else(|| anyhow::anyhow!("Failed to create job object {}", name))?;
+        apply_limits(job, name, limits)?;
+        unsafe {
+            let process = OpenProcess(
+                PROCESS_SET_QUOTA | PROCESS_TERMINATE | PROCESS_DUP_HANDLE,
+                0,
+                pid,
+            );
+            if process.is_null() {
+                anyhow::bail!(
+                    "Failed to open PID {}: {}",
+                    pid,
+                    std::io::Error::last_os_error()
+                );
+            }
+            let assigned = AssignProcessToJobObject(job, process);
+            // A handle in the service keeps the job's name while the service runs, so
+            // `--down` from another terminal can open it after this process has exited
+            let mut kept: HANDLE = std::ptr::null_mut();
+            if assigned != 0
+                && DuplicateHandle(
+                    GetCurrentProcess(),
+                    job,
+                    process,
+                    &mut kept,
+                    0,
+                    0,
+                    DUPLICATE_SAME_ACCESS,
+                ) == 0
+            {
+                log::debug!(
+                    "Job object {} is only reachable while this process runs: {}",
+                    name,
+                    std::io::Error::last_os_error()
+                );
+            }
+            CloseHandle(process);
+            if assigned == 0 {
+                anyhow::bail!(
+                    "Failed to assign PID {} to job object {}: {}",
+                    pid,
+                    name,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+        log::info!("Placed PID {} in job object {}", pid, name);
+        Ok(())
+    }
+
+    /// Resume the threads of a process created with `CREATE_SUSPENDED`. std doesn't keep
+    /// the main thread's handle, so the threads are found in a snapshot.
+    pub fn resume(pid: u32) -> Result<()> {
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                anyhow::bail!(
+                    "Failed to list the threads of PID {}: {}",
+                    pid,
+                    std::io::Error::last_os_error()
+                );
+            }
+            let mut entry: THREADENTRY32 = std::mem::zeroed();
+            entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+            let mut resumed = 0;
+            let mut more = Thread32First(snapshot, &mut entry) != 0;
+            while more {
+                if entry.th32OwnerProcessID == pid {
+                    let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                    if !thread.is_null() {
+                        if ResumeThread(thread) != u32::MAX {
+                            resumed += 1;
+                        }
+                        CloseHandle(thread);
+                    }
+                }
+                more = Thread32Next(snapshot, &mut entry) != 0;
+            }
+            CloseHandle(snapshot);
+            if resumed == 0 {
+                anyhow::bail!(
+                    "Failed to resume PID {}: {}",
+                    pid,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn pids(name: &str) -> Vec<u32> {
+        let Some(job) = job_handle(name, false) else {
+            return Vec::new();
+        };
+        let id_offset = std::mem::offset_of!(JOBOBJECT_BASIC_PROCESS_ID_LIST, ProcessIdList)
+            / std::mem::size_of::<usize>();
+        let mut buffer = vec![0usize; id_offset + MAX_PIDS];
+        let ok = unsafe {
+            QueryInformationJobObject(
+                job,
+                JobObjectBasicProcessIdList,
+                buffer.as_mut_ptr().cast(),
+                (buffer.len() * std::mem::size_of::<usize>()) as u32,
+                std::ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Vec::new();
+        }
+        let list = unsafe { &*(buffer.as_ptr() as *const JOBOBJECT_BASIC_PROCESS_ID_LIST) };
+        let count = (list.NumberOfProcessIdsInList as usize).min(MAX_PIDS);
+        buffer[id_offset..id_offset + count]
+            .iter()
+            .map(|pid| *pid as u32)
+            .collect()
+    }
+
+    pub fn terminate(name: &str) -> Result<()> {
+        if let Some(job) = job_handle(name, false) {
+            if unsafe { TerminateJobObject(job, 1) } == 0 {
+                anyhow::bail!(
+                    "Failed to terminate job object {}: {}",
+                    name,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_name_is_sanitized() {
+        assert_eq!(ServiceGroup::new("api").name(), "port-kill-api");
+        assert_eq!(
+            ServiceGroup::new("web/app v2").name(),
+            "port-kill-web_app_v2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_group_is_empty() {
+        let group = ServiceGroup::new(&format!("no-such-service-{}", std::process::id()));
+        assert!(group.pids().is_empty());
+        assert!(!group.is_active());
+        assert_eq!(group.terminate(Duration::from_millis(10)).await.unwrap(), 0);
+        assert_eq!(
+            group.kind(),
+            if cfg!(windows) {
+                "job object"
+            } else {
+                "process group"
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("512M").unwrap(), 512 * 1024 * 1024);
+        assert_eq!(parse_size("2G").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert_eq!(parse_size("1.5gb").unwrap(), 1536 * 1024 * 1024);
+        assert_eq!(parse_size("64KiB").unwrap(), 64 * 1024);
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("2X").is_err());
+    }
+}
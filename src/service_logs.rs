@@ -0,0 +1,110 @@
+//! Output of orchestrated services. `--up` and `--restart-service` point each service's
+//! stdout and stderr at `~/.port-kill/logs/<service>.log`, so the output outlives the
+//! `port-kill` run that started it; the file is rotated like `--log-file` when a start finds
+//! it over [`MAX_SIZE`]. `--logs <service>` prints the end of it and `--follow` keeps
+//! printing what the service writes.
+
+use crate::uninstall::home_dir;
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A service's log is rotated at the next start once it is this large
+pub const MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Lines `--logs` prints before following
+pub const TAIL_LINES: usize = 100;
+
+pub fn logs_dir() -> PathBuf {
+    home_dir().join(".port-kill").join("logs")
+}
+
+/// `~/.port-kill/logs/<service>.log`, with characters that don't belong in a file name
+/// replaced
+pub fn log_path(service_name: &str) -> PathBuf {
+    let file_name: String = service_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    logs_dir().join(format!("{}.log", file_name))
+}
+
+/// Open the service's log for a new start: rotated if it is too large, with a line marking
+/// the start. Returns the file to hand to the service as stdout and stderr.
+pub fn open_for_start(service_name: &str, command: &str) -> Result<File> {
+    let path = log_path(service_name);
+    std::fs::create_dir_all(logs_dir()).context("Failed to create the service logs directory")?;
+    crate::logging::rotate_if_larger(&path, MAX_SIZE)
+        .with_context(|| format!("Failed to rotate {}", path.display()))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(
+        file,
+        "==> {} started {}: {}",
+        service_name,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        command
+    )?;
+    Ok(file)
+}
+
+/// The last `count` lines of the file
+pub fn last_lines(path: &Path, count: usize) -> Result<Vec<String>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut lines = std::collections::VecDeque::with_capacity(count + 1);
+    for line in BufReader::new(file).lines() {
+        // Services can write anything; don't stop at a line that isn't UTF-8
+        let line = line.unwrap_or_else(|_| "<binary output>".to_string());
+        lines.push_back(line);
+        if lines.len() > count {
+            lines.pop_front();
+        }
+    }
+    Ok(lines.into())
+}
+
+/// Copy what is appended to `path` to `out` until interrupted, starting at its end. Starts
+/// over from the top when the file is rotated or truncated.
+pub fn follow(path: &Path, out: &mut impl Write) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut position = file.seek(SeekFrom::End(0))?;
+    let mut buffer = Vec::new();
+    loop {
+        let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if len < position {
+            // Rotated away (or truncated): read the new file from the start
+            file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+            position = 0;
+        }
+        buffer.clear();
+        file.seek(SeekFrom::Start(position))?;
+        position += file.read_to_end(&mut buffer)? as u64;
+        if !buffer.is_empty() {
+            out.write_all(&buffer)?;
+            out.flush()?;
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_path_and_last_lines() {
+        assert!(log_path("api").ends_with(".port-kill/logs/api.log"));
+        assert!(log_path("../web server").ends_with("logs/.._web_server.log"));
+
+        let path = std::env::temp_dir().join(format!("port-kill-service-log-{}", std::process::id()));
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        assert_eq!(last_lines(&path, 2).unwrap(), vec!["two", "three"]);
+        assert_eq!(last_lines(&path, 10).unwrap().len(), 3);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,196 @@
+//! Services `--up` and `--restart-service` started, and servers taken over with `--adopt`,
+//! kept in `~/.port-kill/services.json`. A later `--status` uses it to tell a service that
+//! crashed from one that was stopped with `--down` or never started, to show uptime, and
+//! `--restart-failed` to start only the crashed ones again.
+
+use crate::uninstall::home_dir;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceRecord {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub command: String,
+    /// Set by `--down` / `--restart-service`; a service that isn't running without it crashed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<DateTime<Utc>>,
+    /// Started outside port-kill and taken over with `--adopt`: `pid` is the listener, not
+    /// the leader of a process group
+    #[serde(default)]
+    pub adopted: bool,
+}
+
+impl ServiceRecord {
+    /// Whether the process that was started is still running. A PID reused by a process
+    /// started later doesn't count.
+    pub fn is_alive(&self) -> bool {
+        let mut system = sysinfo::System::new();
+        let pid = sysinfo::Pid::from_u32(self.pid);
+        system.refresh_process(pid);
+        system.process(pid).is_some_and(|process| {
+            process.status() != sysinfo::ProcessStatus::Zombie
+                && process.start_time() as i64 <= self.started_at.timestamp() + 2
+        })
+    }
+}
+
+/// What `--status` says about a configured service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Running,
+    /// Started and not stopped, but gone
+    Crashed,
+    Stopped,
+    NotStarted,
+}
+
+impl std::fmt::Display for RunState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RunState::Running => "RUNNING",
+            RunState::Crashed => "CRASHED",
+            RunState::Stopped => "STOPPED",
+            RunState::NotStarted => "NOT STARTED",
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ServiceState {
+    path: PathBuf,
+    records: BTreeMap<String, ServiceRecord>,
+}
+
+impl ServiceState {
+    pub fn state_path() -> PathBuf {
+        home_dir().join(".port-kill").join("services.json")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::state_path())
+    }
+
+    /// A missing or unreadable file is an empty state
+    pub fn load_from(path: &Path) -> Self {
+        let records = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: path.to_path_buf(),
+            records,
+        }
+    }
+
+    pub fn get(&self, service_name: &str) -> Option<&ServiceRecord> {
+        self.records.get(service_name)
+    }
+
+    pub fn record_start(&mut self, service_name: &str, pid: u32, command: &str) -> Result<()> {
+        self.records.insert(
+            service_name.to_string(),
+            ServiceRecord {
+                pid,
+                started_at: Utc::now(),
+                command: command.to_string(),
+                stopped_at: None,
+                adopted: false,
+            },
+        );
+        self.save()
+    }
+
+    pub fn record_adopted(
+        &mut self,
+        service_name: &str,
+        pid: u32,
+        command: &str,
+        started_at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.records.insert(
+            service_name.to_string(),
+            ServiceRecord {
+                pid,
+                started_at,
+                command: command.to_string(),
+                stopped_at: None,
+                adopted: true,
+            },
+        );
+        self.save()
+    }
+
+    /// Adopted services that are still running, for `--down` to stop even when they aren't
+    /// in the orchestration config
+    pub fn running_adopted(&self) -> Vec<String> {
+        self.records
+            .iter()
+            .filter(|(_, record)| record.adopted && record.stopped_at.is_none() && record.is_alive())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn record_stop(&mut self, service_name: &str) -> Result<()> {
+        match self.records.get_mut(service_name) {
+            Some(record) if record.stopped_at.is_none() => {
+                record.stopped_at = Some(Utc::now());
+                self.save()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The state of a service, given whether it was found running some other way (a child
+    /// of this run, or processes in its cgroup / job object)
+    pub fn run_state(&self, service_name: &str, running: bool) -> RunState {
+        match self.get(service_name) {
+            _ if running => RunState::Running,
+            Some(record) if record.is_alive() => RunState::Running,
+            Some(record) if record.stopped_at.is_none() => RunState::Crashed,
+            Some(_) => RunState::Stopped,
+            None => RunState::NotStarted,
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(&self.records)?;
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_states() {
+        let path = std::env::temp_dir().join(format!("port-kill-services-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut state = ServiceState::load_from(&path);
+        assert_eq!(state.run_state("api", false), RunState::NotStarted);
+
+        // This test process is alive, and was started before the record
+        state.record_start("api", std::process::id(), "npm start").unwrap();
+        assert_eq!(state.run_state("api", false), RunState::Running);
+
+        // A PID that doesn't exist: started, never stopped, gone
+        state.record_start("worker", u32::MAX - 1, "node worker.js").unwrap();
+        assert_eq!(state.run_state("worker", false), RunState::Crashed);
+        assert_eq!(state.run_state("worker", true), RunState::Running);
+
+        state.record_stop("worker").unwrap();
+        let state = ServiceState::load_from(&path);
+        assert_eq!(state.run_state("worker", false), RunState::Stopped);
+        assert_eq!(state.get("api").unwrap().command, "npm start");
+        let _ = std::fs::remove_file(&path);
+    }
+}
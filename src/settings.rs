@@ -0,0 +1,525 @@
+//! The tray's Settings window: a native form for the ports, ignore lists, Docker and scan
+//! interval in `~/.port-kill/config.toml` plus the login item ([`crate::autostart`]); the
+//! tray restarts itself to pick the changes up.
+//!
+//! The tray has no window toolkit of its own, so like [`crate::undo::confirm_dialog`] the
+//! window is drawn by what each platform ships with: an AppKit panel through JavaScript for
+//! Automation on macOS, a Windows Forms dialog through PowerShell on Windows and a zenity
+//! form on Linux. Each prints the answers as `key=value` lines.
+
+use crate::config::{global_config_path, ConfigLayer};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Scan interval shown when neither the config nor `--scan-interval` sets one
+pub const DEFAULT_SCAN_INTERVAL: u64 = 2;
+
+/// What the Settings window edits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Ports and ranges, e.g. `3000` or `8000-8010`; empty means the default 2000-9000
+    pub ports: Vec<String>,
+    pub ignore_ports: Vec<u16>,
+    pub ignore_processes: Vec<String>,
+    pub docker: bool,
+    pub scan_interval: u64,
+    pub start_at_login: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ports: Vec::new(),
+            ignore_ports: Vec::new(),
+            ignore_processes: Vec::new(),
+            docker: false,
+            scan_interval: DEFAULT_SCAN_INTERVAL,
+            start_at_login: false,
+        }
+    }
+}
+
+impl Settings {
+    /// The global config file's settings (a project's `.portkill.toml` isn't edited here)
+    pub fn load() -> Result<Self> {
+        let path = global_config_path();
+        let config = if path.is_file() {
+            ConfigLayer::from_file(&path)?
+        } else {
+            ConfigLayer::default()
+        };
+        Ok(Self {
+            ports: config.ports.unwrap_or_default(),
+            ignore_ports: config.ignore_ports.unwrap_or_default(),
+            ignore_processes: config.ignore_processes.unwrap_or_default(),
+            docker: config.docker.unwrap_or(false),
+            scan_interval: config.scan_interval.unwrap_or(DEFAULT_SCAN_INTERVAL),
+            start_at_login: crate::autostart::is_enabled(),
+        })
+    }
+
+    /// The window's fields, as [`Self::from_form`] reads them back
+    pub fn to_form(&self) -> HashMap<String, String> {
+        let join = |items: Vec<String>| items.join(", ");
+        let mut form = HashMap::from([
+            ("ports".to_string(), join(self.ports.clone())),
+            (
+                "ignore_ports".to_string(),
+                join(self.ignore_ports.iter().map(u16::to_string).collect()),
+            ),
+            (
+                "ignore_processes".to_string(),
+                join(self.ignore_processes.clone()),
+            ),
+            ("scan_interval".to_string(), self.scan_interval.to_string()),
+        ]);
+        for (key, on) in [
+            ("docker", self.docker),
+            ("start_at_login", self.start_at_login),
+        ] {
+            if on {
+                form.insert(key.to_string(), "on".to_string());
+            }
+        }
+        form
+    }
+
+    /// Read the window's answers. Unchecked checkboxes are left out.
+    pub fn from_form(form: &HashMap<String, String>) -> Result<Self> {
+        let field = |name: &str| form.get(name).map(String::as_str).unwrap_or("");
+        let ports = split_list(field("ports"));
+        crate::profiles::parse_ports(&ports)?;
+        let ignore_ports = split_list(field("ignore_ports"))
+            .iter()
+            .map(|port| {
+                port.parse()
+                    .with_context(|| format!("Invalid port '{}' to ignore", port))
+            })
+            .collect::<Result<_>>()?;
+        let scan_interval: u64 = field("scan_interval")
+            .trim()
+            .parse()
+            .context("The scan interval must be a whole number of seconds")?;
+        if scan_interval == 0 {
+            anyhow::bail!("The scan interval must be at least 1 second");
+        }
+        Ok(Self {
+            ports,
+            ignore_ports,
+            ignore_processes: split_list(field("ignore_processes")),
+            docker: form.contains_key("docker"),
+            scan_interval,
+            start_at_login: form.contains_key("start_at_login"),
+        })
+    }
+
+    /// Write to the global config and update the login item. Returns the config file.
+    pub fn save(&self) -> Result<PathBuf> {
+        fn list<T: Into<toml::Value> + Clone>(items: &[T]) -> Option<toml::Value> {
+            (!items.is_empty())
+                .then(|| toml::Value::Array(items.iter().cloned().map(Into::into).collect()))
+        }
+        let ignore_ports: Vec<i64> = self.ignore_ports.iter().map(|&port| port as i64).collect();
+        let path = crate::config::set_global_values(vec![
+            ("ports", list(&self.ports)),
+            ("ignore_ports", list(&ignore_ports)),
+            ("ignore_processes", list(&self.ignore_processes)),
+            ("docker", self.docker.then_some(toml::Value::Boolean(true))),
+            (
+                "scan_interval",
+                (self.scan_interval != DEFAULT_SCAN_INTERVAL)
+                    .then_some(toml::Value::Integer(self.scan_interval as i64)),
+            ),
+        ])?;
+        crate::autostart::set_enabled(self.start_at_login)?;
+        Ok(path)
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Text fields in the window, in order
+const FIELDS: [(&str, &str); 4] = [
+    ("ports", "Ports to monitor (e.g. 3000, 5173, 8000-8010)"),
+    ("ignore_ports", "Ignored ports"),
+    (
+        "ignore_processes",
+        "Ignored processes (e.g. Chrome, Code Helper)",
+    ),
+    ("scan_interval", "Scan interval in seconds"),
+];
+
+/// Checkboxes in the window, in order
+const CHECKBOXES: [(&str, &str); 2] = [
+    ("docker", "Include Docker containers"),
+    ("start_at_login", "Open Port Kill at login"),
+];
+
+/// Set by a save; the tray restarts when it sees it
+static SAVED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the window is showing, so a second click doesn't open another
+static OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Whether settings were saved since the last call
+pub fn take_saved() -> bool {
+    SAVED.swap(false, Ordering::Relaxed)
+}
+
+/// Show the Settings window, unless it is already open. It runs on its own thread so the
+/// tray keeps working meanwhile.
+pub fn open() -> Result<()> {
+    if OPEN.swap(true, Ordering::Relaxed) {
+        return Ok(());
+    }
+    std::thread::Builder::new()
+        .name("settings".to_string())
+        .spawn(|| {
+            if let Err(e) = run_window() {
+                log::error!("Settings window failed: {:#}", e);
+            }
+            OPEN.store(false, Ordering::Relaxed);
+        })
+        .context("Could not open the Settings window")?;
+    Ok(())
+}
+
+/// Ask until the answers save or the window is cancelled. Answers that don't save are shown
+/// again with the reason.
+fn run_window() -> Result<()> {
+    let mut form = Settings::load()?.to_form();
+    let mut notice = None;
+    loop {
+        let Some(answers) = ask(&form, notice.as_deref())? else {
+            return Ok(());
+        };
+        match Settings::from_form(&answers).and_then(|settings| settings.save()) {
+            Ok(path) => {
+                log::info!("Settings saved to {}", path.display());
+                SAVED.store(true, Ordering::Relaxed);
+                return Ok(());
+            }
+            Err(e) => {
+                notice = Some(format!("Not saved: {:#}", e));
+                form = answers;
+            }
+        }
+    }
+}
+
+/// The text above the fields: `notice` (why the last answers weren't saved), the flags that
+/// override these settings and where they are saved
+fn describe(notice: Option<&str>) -> String {
+    let mut lines: Vec<String> = notice.map(String::from).into_iter().collect();
+    // Flags the tray was started with win over the config file
+    let flags: Vec<String> = std::env::args().skip(1).collect();
+    if !flags.is_empty() {
+        lines.push(format!(
+            "Port Kill was started with {}; those flags take precedence over these settings.",
+            flags.join(" ")
+        ));
+    }
+    lines.push(format!("Saved to {}", global_config_path().display()));
+    lines.join("\n\n")
+}
+
+/// Show the window filled in from `form`. `None` when it was cancelled.
+fn ask(
+    form: &HashMap<String, String>,
+    notice: Option<&str>,
+) -> Result<Option<HashMap<String, String>>> {
+    let mut current = serde_json::Map::new();
+    for (key, _) in FIELDS {
+        current.insert(
+            key.into(),
+            form.get(key).cloned().unwrap_or_default().into(),
+        );
+    }
+    for (key, _) in CHECKBOXES {
+        current.insert(key.into(), form.contains_key(key).into());
+    }
+    current.insert("notice".into(), describe(notice).into());
+    let current = serde_json::Value::Object(current).to_string();
+    let fields = serde_json::to_string(&FIELDS)?;
+    let checkboxes = serde_json::to_string(&CHECKBOXES)?;
+
+    let output = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .args(["-l", "JavaScript", "-e", MACOS_WINDOW])
+            .args([current, fields, checkboxes])
+            .output()
+            .context("Could not run osascript for the Settings window")?
+    } else if cfg!(target_os = "windows") {
+        Command::new("powershell")
+            .args(["-NoProfile", "-STA", "-Command", WINDOWS_WINDOW])
+            .env("PORTKILL_SETTINGS", current)
+            .env("PORTKILL_SETTINGS_FIELDS", fields)
+            .env("PORTKILL_SETTINGS_CHECKBOXES", checkboxes)
+            .output()
+            .context("Could not run powershell for the Settings window")?
+    } else {
+        let output = zenity_command(form, notice)
+            .output()
+            .context("The Settings window needs zenity")?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Ok(Some(zenity_answers(&stdout, form)));
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(parse_answers(&stdout)))
+}
+
+/// `key=value` lines as printed by the macOS and Windows windows
+fn parse_answers(stdout: &str) -> HashMap<String, String> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// An AppKit alert with the fields as its accessory view; arguments are the current values,
+/// the fields and the checkboxes as JSON
+const MACOS_WINDOW: &str = r#"
+ObjC.import('Cocoa');
+function run(argv) {
+    const current = JSON.parse(argv[0]), fields = JSON.parse(argv[1]), checkboxes = JSON.parse(argv[2]);
+    const width = 380, height = fields.length * 48 + checkboxes.length * 24;
+    const view = $.NSView.alloc.initWithFrame($.NSMakeRect(0, 0, width, height));
+    const inputs = {}, boxes = {};
+    let y = height;
+    for (const [key, label] of fields) {
+        const text = $.NSTextField.labelWithString(label);
+        text.frame = $.NSMakeRect(0, y -= 20, width, 17);
+        view.addSubview(text);
+        const input = $.NSTextField.alloc.initWithFrame($.NSMakeRect(0, y -= 26, width, 22));
+        input.stringValue = current[key];
+        view.addSubview(input);
+        inputs[key] = input;
+        y -= 2;
+    }
+    for (const [key, label] of checkboxes) {
+        const box = $.NSButton.checkboxWithTitleTargetAction(label, null, null);
+        box.frame = $.NSMakeRect(0, y -= 24, width, 20);
+        box.state = current[key] ? 1 : 0;
+        view.addSubview(box);
+        boxes[key] = box;
+    }
+    const alert = $.NSAlert.alloc.init;
+    alert.messageText = 'Port Kill Settings';
+    alert.informativeText = current.notice;
+    alert.accessoryView = view;
+    alert.addButtonWithTitle('Save');
+    alert.addButtonWithTitle('Cancel');
+    alert.window.initialFirstResponder = inputs[fields[0][0]];
+    $.NSApplication.sharedApplication.activateIgnoringOtherApps(true);
+    // NSAlertFirstButtonReturn
+    if (alert.runModal != 1000) {
+        return '';
+    }
+    const lines = fields.map(([key]) => key + '=' + inputs[key].stringValue.js);
+    for (const [key] of checkboxes) {
+        if (boxes[key].state == 1) {
+            lines.push(key + '=on');
+        }
+    }
+    return lines.join('\n');
+}
+"#;
+
+/// A Windows Forms dialog; the current values, fields and checkboxes come as JSON in
+/// `PORTKILL_SETTINGS*` environment variables
+const WINDOWS_WINDOW: &str = r#"
+Add-Type -AssemblyName System.Windows.Forms, System.Drawing
+[Console]::OutputEncoding = [Text.Encoding]::UTF8
+$current = $env:PORTKILL_SETTINGS | ConvertFrom-Json
+$form = New-Object System.Windows.Forms.Form
+$form.Text = 'Port Kill Settings'
+$form.FormBorderStyle = 'FixedDialog'
+$form.MaximizeBox = $false
+$form.MinimizeBox = $false
+$form.StartPosition = 'CenterScreen'
+$form.TopMost = $true
+$note = New-Object System.Windows.Forms.Label
+$note.Text = $current.notice
+$note.Location = New-Object System.Drawing.Point(12, 12)
+$note.MaximumSize = New-Object System.Drawing.Size(356, 0)
+$note.AutoSize = $true
+$form.Controls.Add($note)
+$y = 20 + $note.GetPreferredSize((New-Object System.Drawing.Size(356, 0))).Height
+$inputs = @{}
+foreach ($field in ($env:PORTKILL_SETTINGS_FIELDS | ConvertFrom-Json)) {
+    $label = New-Object System.Windows.Forms.Label
+    $label.Text = $field[1]
+    $label.Location = New-Object System.Drawing.Point(12, $y)
+    $label.AutoSize = $true
+    $form.Controls.Add($label)
+    $box = New-Object System.Windows.Forms.TextBox
+    $box.Text = $current.($field[0])
+    $box.Location = New-Object System.Drawing.Point(12, ($y + 20))
+    $box.Width = 356
+    $form.Controls.Add($box)
+    $inputs[$field[0]] = $box
+    $y += 50
+}
+$checks = @{}
+foreach ($checkbox in ($env:PORTKILL_SETTINGS_CHECKBOXES | ConvertFrom-Json)) {
+    $check = New-Object System.Windows.Forms.CheckBox
+    $check.Text = $checkbox[1]
+    $check.Checked = [bool]$current.($checkbox[0])
+    $check.Location = New-Object System.Drawing.Point(12, $y)
+    $check.AutoSize = $true
+    $form.Controls.Add($check)
+    $checks[$checkbox[0]] = $check
+    $y += 26
+}
+$save = New-Object System.Windows.Forms.Button
+$save.Text = 'Save'
+$save.DialogResult = 'OK'
+$save.Location = New-Object System.Drawing.Point(212, ($y + 10))
+$form.Controls.Add($save)
+$form.AcceptButton = $save
+$cancel = New-Object System.Windows.Forms.Button
+$cancel.Text = 'Cancel'
+$cancel.DialogResult = 'Cancel'
+$cancel.Location = New-Object System.Drawing.Point(293, ($y + 10))
+$form.Controls.Add($cancel)
+$form.CancelButton = $cancel
+$form.ClientSize = New-Object System.Drawing.Size(380, ($y + 46))
+if ($form.ShowDialog() -ne 'OK') { exit 1 }
+foreach ($key in $inputs.Keys) { "$key=$($inputs[$key].Text)" }
+foreach ($key in $checks.Keys) { if ($checks[$key].Checked) { "$key=on" } }
+"#;
+
+/// zenity forms can't be filled in, so the current values are listed above the fields and
+/// a blank answer keeps its value
+fn zenity_command(form: &HashMap<String, String>, notice: Option<&str>) -> Command {
+    let value = |key: &str| form.get(key).map(String::as_str).unwrap_or("");
+    let mut text = vec![
+        describe(notice),
+        "Leave a field blank to keep it, or enter - to clear a list.".to_string(),
+    ];
+    for (key, label) in FIELDS {
+        text.push(format!("{}: {}", label, value(key)));
+    }
+    for (key, label) in CHECKBOXES {
+        text.push(format!(
+            "{}: {}",
+            label,
+            if form.contains_key(key) { "Yes" } else { "No" }
+        ));
+    }
+    let text = text
+        .join("\n")
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+
+    let mut command = Command::new("zenity");
+    command
+        .args(["--forms", "--title=Port Kill Settings", "--separator=\n"])
+        .arg(format!("--text={}", text));
+    for (_, label) in FIELDS {
+        command.arg(format!("--add-entry={}", label));
+    }
+    for (_, label) in CHECKBOXES {
+        command
+            .arg(format!("--add-combo={}", label))
+            .arg("--combo-values=Yes|No");
+    }
+    command
+}
+
+/// Read zenity's answers, one line per field, keeping `form`'s value where one is blank
+fn zenity_answers(stdout: &str, form: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut answers = stdout.lines().map(str::trim);
+    let mut result = HashMap::new();
+    for (key, _) in FIELDS {
+        let value = match answers.next().unwrap_or("") {
+            "" => form.get(key).cloned().unwrap_or_default(),
+            "-" => String::new(),
+            answer => answer.to_string(),
+        };
+        result.insert(key.to_string(), value);
+    }
+    for (key, _) in CHECKBOXES {
+        let on = match answers.next().unwrap_or("") {
+            "" => form.contains_key(key),
+            answer => answer == "Yes",
+        };
+        if on {
+            result.insert(key.to_string(), "on".to_string());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_form() {
+        let mut form = HashMap::new();
+        form.insert("ports".to_string(), "3000, 8000-8010,".to_string());
+        form.insert("ignore_ports".to_string(), "5000".to_string());
+        form.insert(
+            "ignore_processes".to_string(),
+            "Chrome, Code Helper".to_string(),
+        );
+        form.insert("scan_interval".to_string(), "5".to_string());
+        form.insert("docker".to_string(), "on".to_string());
+        let settings = Settings::from_form(&form).unwrap();
+        assert_eq!(settings.ports, vec!["3000", "8000-8010"]);
+        assert_eq!(settings.ignore_ports, vec![5000]);
+        assert_eq!(settings.ignore_processes, vec!["Chrome", "Code Helper"]);
+        assert!(settings.docker && !settings.start_at_login);
+        assert_eq!(Settings::from_form(&settings.to_form()).unwrap(), settings);
+
+        form.insert("ports".to_string(), "3000-abc".to_string());
+        assert!(Settings::from_form(&form).is_err());
+        form.insert("ports".to_string(), "3000".to_string());
+        form.insert("scan_interval".to_string(), "0".to_string());
+        assert!(Settings::from_form(&form).is_err());
+    }
+
+    #[test]
+    fn test_window_answers() {
+        let answers =
+            parse_answers("ports=3000, 5173\nignore_ports=\nscan_interval=5\ndocker=on\n");
+        assert_eq!(answers["ports"], "3000, 5173");
+        assert_eq!(answers["ignore_ports"], "");
+        assert!(answers.contains_key("docker") && !answers.contains_key("start_at_login"));
+
+        // zenity: blank keeps, "-" clears, combos say Yes or No
+        let current = Settings {
+            ports: vec!["3000".to_string()],
+            ignore_processes: vec!["Chrome".to_string()],
+            docker: true,
+            ..Settings::default()
+        }
+        .to_form();
+        let answers = zenity_answers("\n5000\n-\n\n\nYes\n", &current);
+        let settings = Settings::from_form(&answers).unwrap();
+        assert_eq!(settings.ports, vec!["3000"]);
+        assert_eq!(settings.ignore_ports, vec![5000]);
+        assert!(settings.ignore_processes.is_empty());
+        assert_eq!(settings.scan_interval, DEFAULT_SCAN_INTERVAL);
+        assert!(settings.docker && settings.start_at_login);
+    }
+}
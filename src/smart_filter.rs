@@ -1,14 +1,222 @@
 use crate::types::ProcessInfo;
 use anyhow::Result;
-use regex::Regex;
-use std::collections::HashSet;
+use chrono::{NaiveDateTime, NaiveTime, Weekday};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 pub struct SmartFilter {
     ignore_ports: HashSet<u16>,
     ignore_processes: HashSet<String>,
-    ignore_patterns: Vec<Regex>,
+    /// Compiled patterns alongside the wildcard text they came from
+    ignore_patterns: Vec<(String, Regex)>,
+    /// `--ignore-regex`: unanchored, matched against the full command line too
+    ignore_regexes: Vec<(String, Regex)>,
+    /// `--ignore-case`: applies to every pattern, wildcard or regex
+    ignore_case: bool,
     ignore_groups: HashSet<String>,
     only_groups: Option<HashSet<String>>,
+    /// Allowlist mode: only these processes (names or wildcards) are shown
+    only_processes: Option<Vec<(String, Regex)>>,
+    /// Allowlist mode: only these ports are shown
+    only_ports: Option<HashSet<u16>>,
+    port_rules: Vec<CompiledPortRule>,
+    conditionals: Vec<CompiledConditional>,
+    /// The `--smart-filter` ports, processes and groups, for conditionals that turn it on
+    smart_defaults: (HashSet<u16>, HashSet<String>, HashSet<String>),
+    /// The project `outside_project` conditionals compare working directories with
+    project_root: Option<PathBuf>,
+}
+
+/// Filtering for some ports only, from `[port_rules."<ports>"]` in the config, e.g. to hide
+/// nginx on 8080 without hiding it everywhere:
+///
+/// ```toml
+/// [port_rules."8080"]
+/// ignore_processes = ["nginx"]
+///
+/// [port_rules."3000-3010"]
+/// only_processes = ["node", "bun*"]
+/// ```
+///
+/// The key takes port specs like `--ports`. Rules add to the global lists, never lift them.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PortRule {
+    /// Names or wildcard patterns hidden on these ports
+    pub ignore_processes: Vec<String>,
+    /// When set, only processes matching one of these are shown on these ports
+    pub only_processes: Option<Vec<String>>,
+}
+
+/// A filter that only applies while its conditions hold, from `[[conditional_filters]]` in
+/// the config:
+///
+/// ```toml
+/// [[conditional_filters]]              # chat apps stay out of the way during work hours
+/// hours = "09:00-18:00"
+/// days = ["mon", "tue", "wed", "thu", "fri"]
+/// ignore_processes = ["Slack*", "zoom*"]
+///
+/// [[conditional_filters]]              # smart-filter only when the list gets long
+/// more_than = 20
+/// smart_filter = true
+///
+/// [[conditional_filters]]
+/// outside_project = true
+/// ```
+///
+/// Every condition given has to hold; one without conditions always applies.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ConditionalFilter {
+    /// Local time window, e.g. `"09:00-18:00"`; `"22:00-06:00"` runs past midnight
+    pub hours: Option<String>,
+    /// Days the filter applies on (`mon` ... `sun`); every day when empty
+    pub days: Vec<String>,
+    /// Only when a scan finds more than this many processes
+    pub more_than: Option<usize>,
+    /// Names or wildcard patterns hidden while the filter applies
+    pub ignore_processes: Vec<String>,
+    /// Apply the `--smart-filter` defaults while the filter applies
+    pub smart_filter: bool,
+    /// Hide processes whose working directory is outside the current project
+    pub outside_project: bool,
+}
+
+/// `"09:00-18:00"` as its start and end
+fn parse_hours(hours: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = hours.split_once('-')?;
+    Some((
+        crate::timeline::parse_clock(start)?,
+        crate::timeline::parse_clock(end)?,
+    ))
+}
+
+struct CompiledConditional {
+    description: String,
+    hours: Option<(NaiveTime, NaiveTime)>,
+    days: Vec<Weekday>,
+    more_than: Option<usize>,
+    ignore_processes: Vec<(String, Regex)>,
+    smart_filter: bool,
+    outside_project: bool,
+}
+
+impl CompiledConditional {
+    fn compile(filter: &ConditionalFilter, ignore_case: bool) -> Result<Self> {
+        let hours = match filter.hours.as_deref() {
+            Some(hours) => Some(parse_hours(hours).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid hours '{}' in [[conditional_filters]], expected e.g. 09:00-18:00",
+                    hours
+                )
+            })?),
+            None => None,
+        };
+        let days = filter
+            .days
+            .iter()
+            .map(|day| {
+                day.parse::<Weekday>().map_err(|_| {
+                    anyhow::anyhow!("Invalid day '{}' in [[conditional_filters]]", day)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut conditions = Vec::new();
+        if let Some(hours) = &filter.hours {
+            conditions.push(hours.clone());
+        }
+        if !filter.days.is_empty() {
+            conditions.push(filter.days.join(","));
+        }
+        if let Some(more_than) = filter.more_than {
+            conditions.push(format!("more than {} processes", more_than));
+        }
+        let description = if conditions.is_empty() {
+            "conditional filter".to_string()
+        } else {
+            format!("conditional filter ({})", conditions.join(", "))
+        };
+
+        Ok(Self {
+            description,
+            hours,
+            days,
+            more_than: filter.more_than,
+            ignore_processes: compile_patterns(&filter.ignore_processes, ignore_case)?,
+            smart_filter: filter.smart_filter,
+            outside_project: filter.outside_project,
+        })
+    }
+
+    fn applies(&self, now: NaiveDateTime, process_count: usize) -> bool {
+        use chrono::Datelike;
+        let in_hours = self.hours.is_none_or(|(start, end)| {
+            let time = now.time();
+            if start <= end {
+                start <= time && time < end
+            } else {
+                time >= start || time < end
+            }
+        });
+        in_hours
+            && (self.days.is_empty() || self.days.contains(&now.weekday()))
+            && self
+                .more_than
+                .is_none_or(|more_than| process_count > more_than)
+    }
+}
+
+struct CompiledPortRule {
+    spec: String,
+    ports: HashSet<u16>,
+    ignore_processes: Vec<(String, Regex)>,
+    only_processes: Option<Vec<(String, Regex)>>,
+}
+
+/// A wildcard pattern (`*`, `?`) as an anchored regex
+fn wildcard_regex(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    // First escape all regex metacharacters to treat them as literals
+    let escaped = regex::escape(pattern);
+    // Then replace our escaped wildcards with regex equivalents
+    // regex::escape() converts * to \* and ? to \?, so we replace those
+    let regex_pattern = escaped.replace(r"\*", ".*").replace(r"\?", ".");
+    Ok(RegexBuilder::new(&format!("^{}$", regex_pattern))
+        .case_insensitive(ignore_case)
+        .build()?)
+}
+
+fn compile_patterns(patterns: &[String], ignore_case: bool) -> Result<Vec<(String, Regex)>> {
+    patterns
+        .iter()
+        .map(|pattern| Ok((pattern.clone(), wildcard_regex(pattern, ignore_case)?)))
+        .collect()
+}
+
+fn compile_regexes(patterns: &[String], ignore_case: bool) -> Result<Vec<(String, Regex)>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+                .map_err(|e| anyhow::anyhow!("Invalid --ignore-regex '{}': {}", pattern, e))?;
+            Ok((pattern.clone(), regex))
+        })
+        .collect()
+}
+
+/// The first pattern matching the process's name or command
+fn matching<'a>(patterns: &'a [(String, Regex)], process_info: &ProcessInfo) -> Option<&'a str> {
+    patterns
+        .iter()
+        .find(|(_, regex)| {
+            regex.is_match(&process_info.name) || regex.is_match(&process_info.command)
+        })
+        .map(|(source, _)| source.as_str())
 }
 
 impl SmartFilter {
@@ -19,69 +227,273 @@ impl SmartFilter {
         ignore_groups: HashSet<String>,
         only_groups: Option<HashSet<String>>,
     ) -> Result<Self> {
-        let mut compiled_patterns = Vec::new();
-
-        if let Some(patterns) = ignore_patterns {
-            for pattern in patterns {
-                // Convert wildcard pattern to regex
-                // First escape all regex metacharacters to treat them as literals
-                let escaped = regex::escape(&pattern);
-                // Then replace our escaped wildcards with regex equivalents
-                // regex::escape() converts * to \* and ? to \?, so we replace those
-                let regex_pattern = escaped.replace(r"\*", ".*").replace(r"\?", ".");
-                let regex = Regex::new(&format!("^{}$", regex_pattern))?;
-                compiled_patterns.push(regex);
-            }
-        }
+        let compiled_patterns = compile_patterns(&ignore_patterns.unwrap_or_default(), false)?;
 
         Ok(Self {
             ignore_ports,
             ignore_processes,
             ignore_patterns: compiled_patterns,
+            ignore_regexes: Vec::new(),
+            ignore_case: false,
             ignore_groups,
             only_groups,
+            only_processes: None,
+            only_ports: None,
+            port_rules: Vec::new(),
+            conditionals: Vec::new(),
+            smart_defaults: Default::default(),
+            project_root: None,
         })
     }
 
+    /// Allowlist mode (`--only-processes`, `--only-ports`): hide everything else
+    pub fn with_allowlist(
+        mut self,
+        only_processes: Option<Vec<String>>,
+        only_ports: Option<HashSet<u16>>,
+    ) -> Result<Self> {
+        self.only_processes = only_processes
+            .map(|patterns| compile_patterns(&patterns, self.ignore_case))
+            .transpose()?;
+        self.only_ports = only_ports;
+        Ok(self)
+    }
+
+    /// Rules for some ports, keyed by port spec (see [`PortRule`])
+    pub fn with_port_rules(mut self, rules: &HashMap<String, PortRule>) -> Result<Self> {
+        let mut specs: Vec<&String> = rules.keys().collect();
+        specs.sort();
+        for spec in specs {
+            let rule = &rules[spec];
+            let specs: Vec<&str> = spec.split(',').collect();
+            let ports: HashSet<u16> = crate::port_spec::expand(&specs).into_iter().collect();
+            if ports.is_empty() {
+                anyhow::bail!("Invalid ports '{}' in [port_rules]", spec);
+            }
+            self.port_rules.push(CompiledPortRule {
+                spec: spec.clone(),
+                ports,
+                ignore_processes: compile_patterns(&rule.ignore_processes, self.ignore_case)?,
+                only_processes: rule
+                    .only_processes
+                    .as_deref()
+                    .map(|patterns| compile_patterns(patterns, self.ignore_case))
+                    .transpose()?,
+            });
+        }
+        Ok(self)
+    }
+
+    /// `--ignore-regex`: hide processes whose name, command or command line matches one of
+    /// these regular expressions
+    pub fn with_ignore_regex(mut self, regexes: Option<Vec<String>>) -> Result<Self> {
+        self.ignore_regexes = compile_regexes(&regexes.unwrap_or_default(), self.ignore_case)?;
+        Ok(self)
+    }
+
+    /// `--ignore-case`: match every pattern, those given before and after, case-insensitively
+    pub fn with_ignore_case(mut self, ignore_case: bool) -> Result<Self> {
+        self.ignore_case = ignore_case;
+        let recompile = |patterns: &mut Vec<(String, Regex)>| -> Result<()> {
+            for (_, regex) in patterns.iter_mut() {
+                *regex = RegexBuilder::new(regex.as_str())
+                    .case_insensitive(ignore_case)
+                    .build()?;
+            }
+            Ok(())
+        };
+        recompile(&mut self.ignore_patterns)?;
+        recompile(&mut self.ignore_regexes)?;
+        if let Some(only_processes) = self.only_processes.as_mut() {
+            recompile(only_processes)?;
+        }
+        for rule in &mut self.port_rules {
+            recompile(&mut rule.ignore_processes)?;
+            if let Some(only_processes) = rule.only_processes.as_mut() {
+                recompile(only_processes)?;
+            }
+        }
+        for conditional in &mut self.conditionals {
+            recompile(&mut conditional.ignore_processes)?;
+        }
+        Ok(self)
+    }
+
+    /// Filters that apply only at some times, on busy scans or to processes outside the
+    /// project (see [`ConditionalFilter`]). `smart_defaults` are what the ones with
+    /// `smart_filter = true` hide.
+    pub fn with_conditional_filters(
+        mut self,
+        filters: &[ConditionalFilter],
+        smart_defaults: (HashSet<u16>, HashSet<String>, HashSet<String>),
+    ) -> Result<Self> {
+        for filter in filters {
+            self.conditionals
+                .push(CompiledConditional::compile(filter, self.ignore_case)?);
+        }
+        if filters.iter().any(|filter| filter.smart_filter) {
+            self.smart_defaults = smart_defaults;
+        }
+        if filters.iter().any(|filter| filter.outside_project) {
+            self.project_root = Some(crate::cache::project::project_root(""));
+        }
+        Ok(self)
+    }
+
     pub fn should_ignore_process(&self, process_info: &ProcessInfo) -> bool {
+        self.ignore_reason(process_info).is_some()
+    }
+
+    /// Explain which rule hides this process, or None if it stays visible. Conditional
+    /// filters that depend on the number of processes never apply here; see
+    /// [`Self::ignore_reason_in_scan`].
+    pub fn ignore_reason(&self, process_info: &ProcessInfo) -> Option<String> {
+        self.ignore_reason_in_scan(process_info, 0)
+    }
+
+    /// Like [`Self::ignore_reason`], for a process out of a scan that found `process_count`
+    pub fn ignore_reason_in_scan(
+        &self,
+        process_info: &ProcessInfo,
+        process_count: usize,
+    ) -> Option<String> {
         // Check port ignore list
         if self.ignore_ports.contains(&process_info.port) {
-            return true;
+            return Some(format!("port {} is in the ignore list", process_info.port));
         }
 
         // Check process name ignore list
         if self.ignore_processes.contains(&process_info.name) {
-            return true;
+            return Some(format!("process '{}' is in the ignore list", process_info.name));
         }
 
         // Check pattern matching against both name and command
-        for pattern in &self.ignore_patterns {
-            if pattern.is_match(&process_info.name) || pattern.is_match(&process_info.command) {
-                return true;
+        if let Some(source) = matching(&self.ignore_patterns, process_info) {
+            return Some(format!("matches ignore pattern '{}'", source));
+        }
+        if let Some((source, _)) = self.ignore_regexes.iter().find(|(_, regex)| {
+            regex.is_match(&process_info.name)
+                || regex.is_match(&process_info.command)
+                || process_info
+                    .command_line
+                    .as_deref()
+                    .is_some_and(|command_line| regex.is_match(command_line))
+        }) {
+            return Some(format!("matches ignore regex '{}'", source));
+        }
+
+        // Allowlists
+        if let Some(ref only_ports) = self.only_ports {
+            if !only_ports.contains(&process_info.port) {
+                return Some(format!("port {} is not in only-ports", process_info.port));
+            }
+        }
+        if let Some(ref only_processes) = self.only_processes {
+            if matching(only_processes, process_info).is_none() {
+                return Some(format!(
+                    "process '{}' is not in only-processes",
+                    process_info.name
+                ));
+            }
+        }
+
+        // Rules for this port
+        for rule in self
+            .port_rules
+            .iter()
+            .filter(|rule| rule.ports.contains(&process_info.port))
+        {
+            if let Some(source) = matching(&rule.ignore_processes, process_info) {
+                return Some(format!(
+                    "port rule '{}' ignores '{}' on port {}",
+                    rule.spec, source, process_info.port
+                ));
+            }
+            if let Some(ref only_processes) = rule.only_processes {
+                if matching(only_processes, process_info).is_none() {
+                    return Some(format!(
+                        "port rule '{}' only shows {} on port {}",
+                        rule.spec,
+                        only_processes
+                            .iter()
+                            .map(|(source, _)| source.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        process_info.port
+                    ));
+                }
+            }
+        }
+
+        // Filters that apply right now
+        let now = chrono::Local::now().naive_local();
+        for conditional in self
+            .conditionals
+            .iter()
+            .filter(|conditional| conditional.applies(now, process_count))
+        {
+            if let Some(reason) = self.conditional_reason(conditional, process_info) {
+                return Some(format!("{}: {}", conditional.description, reason));
             }
         }
 
         // Check only_groups filter (if specified, only show these groups)
         if let Some(ref only_groups) = self.only_groups {
             match process_info.process_group.as_ref() {
-                Some(group) if only_groups.contains(group) => return false,
-                Some(_) => return true,
-                None => return true,
+                Some(group) if only_groups.contains(group) => return None,
+                Some(group) => return Some(format!("group '{}' is not in only-groups", group)),
+                None => return Some("has no group and only-groups is set".to_string()),
             }
         }
 
         // Check group ignore list
         if let Some(ref group) = process_info.process_group {
             if self.ignore_groups.contains(group) {
-                return true;
+                return Some(format!("group '{}' is in the ignore list", group));
             }
         }
 
-        false
+        None
+    }
+
+    fn conditional_reason(
+        &self,
+        conditional: &CompiledConditional,
+        process_info: &ProcessInfo,
+    ) -> Option<String> {
+        if let Some(source) = matching(&conditional.ignore_processes, process_info) {
+            return Some(format!("matches '{}'", source));
+        }
+        if conditional.smart_filter {
+            let (ports, processes, groups) = &self.smart_defaults;
+            if ports.contains(&process_info.port) {
+                return Some(format!("smart filter ignores port {}", process_info.port));
+            }
+            if processes.contains(&process_info.name) {
+                return Some(format!("smart filter ignores '{}'", process_info.name));
+            }
+            if let Some(group) = process_info.process_group.as_ref() {
+                if groups.contains(group) {
+                    return Some(format!("smart filter ignores group '{}'", group));
+                }
+            }
+        }
+        if conditional.outside_project {
+            if let (Some(root), Some(cwd)) = (&self.project_root, &process_info.working_directory) {
+                if !std::path::Path::new(cwd).starts_with(root) {
+                    return Some(format!("{} is outside {}", cwd, root.display()));
+                }
+            }
+        }
+        None
     }
 
     pub fn filter_processes(&self, processes: &mut std::collections::HashMap<u16, ProcessInfo>) {
-        processes.retain(|_, process_info| !self.should_ignore_process(process_info));
+        let process_count = processes.len();
+        processes.retain(|_, process_info| {
+            self.ignore_reason_in_scan(process_info, process_count)
+                .is_none()
+        });
     }
 
     pub fn get_filter_stats(&self) -> FilterStats {
@@ -89,8 +501,13 @@ impl SmartFilter {
             ignore_ports_count: self.ignore_ports.len(),
             ignore_processes_count: self.ignore_processes.len(),
             ignore_patterns_count: self.ignore_patterns.len(),
+            ignore_regex_count: self.ignore_regexes.len(),
             ignore_groups_count: self.ignore_groups.len(),
             only_groups_count: self.only_groups.as_ref().map_or(0, |g| g.len()),
+            only_processes_count: self.only_processes.as_ref().map_or(0, |p| p.len()),
+            only_ports_count: self.only_ports.as_ref().map_or(0, |p| p.len()),
+            port_rules_count: self.port_rules.len(),
+            conditional_filters_count: self.conditionals.len(),
         }
     }
 }
@@ -100,8 +517,13 @@ pub struct FilterStats {
     pub ignore_ports_count: usize,
     pub ignore_processes_count: usize,
     pub ignore_patterns_count: usize,
+    pub ignore_regex_count: usize,
     pub ignore_groups_count: usize,
     pub only_groups_count: usize,
+    pub only_processes_count: usize,
+    pub only_ports_count: usize,
+    pub port_rules_count: usize,
+    pub conditional_filters_count: usize,
 }
 
 impl FilterStats {
@@ -109,8 +531,13 @@ impl FilterStats {
         self.ignore_ports_count > 0
             || self.ignore_processes_count > 0
             || self.ignore_patterns_count > 0
+            || self.ignore_regex_count > 0
             || self.ignore_groups_count > 0
             || self.only_groups_count > 0
+            || self.only_processes_count > 0
+            || self.only_ports_count > 0
+            || self.port_rules_count > 0
+            || self.conditional_filters_count > 0
     }
 
     pub fn get_description(&self) -> String {
@@ -125,12 +552,30 @@ impl FilterStats {
         if self.ignore_patterns_count > 0 {
             parts.push(format!("{} patterns", self.ignore_patterns_count));
         }
+        if self.ignore_regex_count > 0 {
+            parts.push(format!("{} regexes", self.ignore_regex_count));
+        }
         if self.ignore_groups_count > 0 {
             parts.push(format!("{} groups", self.ignore_groups_count));
         }
         if self.only_groups_count > 0 {
             parts.push(format!("{} only-groups", self.only_groups_count));
         }
+        if self.only_processes_count > 0 {
+            parts.push(format!("{} only-processes", self.only_processes_count));
+        }
+        if self.only_ports_count > 0 {
+            parts.push(format!("{} only-ports", self.only_ports_count));
+        }
+        if self.port_rules_count > 0 {
+            parts.push(format!("{} port rules", self.port_rules_count));
+        }
+        if self.conditional_filters_count > 0 {
+            parts.push(format!(
+                "{} conditional filters",
+                self.conditional_filters_count
+            ));
+        }
 
         if parts.is_empty() {
             "no filters".to_string()
@@ -173,6 +618,13 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: None,
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             },
         );
 
@@ -192,6 +644,13 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: None,
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             },
         );
 
@@ -211,6 +670,13 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: None,
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             },
         );
 
@@ -249,6 +715,13 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: None,
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             },
         );
 
@@ -268,6 +741,13 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: None,
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             },
         );
 
@@ -306,6 +786,13 @@ mod tests {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                wsl_distro: None,
+                user: None,
+                uid: None,
+                started_at: None,
+                uptime_secs: None,
+                ppid: None,
+                gpu_memory: None,
             },
         );
 
@@ -314,4 +801,188 @@ mod tests {
         assert_eq!(processes.len(), 1);
         assert!(processes.contains_key(&5432));
     }
+
+    #[test]
+    fn test_allowlist_and_port_rules() {
+        let process = |port: u16, name: &str| ProcessInfo {
+            pid: port as i32,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        };
+        let rules: HashMap<String, PortRule> = toml::from_str(
+            "[\"8080\"]\nignore_processes = [\"nginx\"]\n[\"3000-3010\"]\nonly_processes = [\"node\", \"bun*\"]\n",
+        )
+        .unwrap();
+        let filter = SmartFilter::new(HashSet::new(), HashSet::new(), None, HashSet::new(), None)
+            .unwrap()
+            .with_port_rules(&rules)
+            .unwrap();
+
+        // nginx is only hidden on 8080
+        assert!(filter.should_ignore_process(&process(8080, "nginx")));
+        assert!(!filter.should_ignore_process(&process(8081, "nginx")));
+        assert!(!filter.should_ignore_process(&process(8080, "node")));
+        // On 3000-3010 only node and bun are shown
+        assert!(!filter.should_ignore_process(&process(3005, "bun-dev")));
+        assert!(filter
+            .ignore_reason(&process(3005, "python"))
+            .unwrap()
+            .starts_with("port rule '3000-3010'"));
+
+        let filter = filter
+            .with_allowlist(
+                Some(vec!["node".to_string()]),
+                Some([3000, 8080].into_iter().collect()),
+            )
+            .unwrap();
+        assert!(!filter.should_ignore_process(&process(3000, "node")));
+        assert!(filter.should_ignore_process(&process(4000, "node")));
+        assert!(filter.should_ignore_process(&process(8080, "python")));
+        assert_eq!(filter.get_filter_stats().port_rules_count, 2);
+    }
+
+    #[test]
+    fn test_ignore_regex_and_case() {
+        let process = |name: &str, command_line: &str| ProcessInfo {
+            pid: 1,
+            port: 3000,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: Some(command_line.to_string()),
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        };
+        let filter = SmartFilter::new(
+            HashSet::new(),
+            HashSet::new(),
+            Some(vec!["Chrome*".to_string()]),
+            HashSet::new(),
+            None,
+        )
+        .unwrap()
+        .with_ignore_regex(Some(vec![r"--inspect(=\d+)?$".to_string()]))
+        .unwrap();
+
+        // The regex is unanchored and also sees the command line
+        assert_eq!(
+            filter
+                .ignore_reason(&process("node", "node server.js --inspect=9229"))
+                .as_deref(),
+            Some(r"matches ignore regex '--inspect(=\d+)?$'")
+        );
+        assert!(!filter.should_ignore_process(&process("node", "node server.js")));
+        assert!(!filter.should_ignore_process(&process("chrome", "chrome")));
+
+        let filter = filter.with_ignore_case(true).unwrap();
+        assert!(filter.should_ignore_process(&process("chrome", "chrome")));
+        assert!(filter.should_ignore_process(&process("node", "node --INSPECT")));
+        assert_eq!(filter.get_filter_stats().ignore_regex_count, 1);
+
+        let empty = SmartFilter::new(HashSet::new(), HashSet::new(), None, HashSet::new(), None);
+        assert!(empty
+            .unwrap()
+            .with_ignore_regex(Some(vec!["(".to_string()]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_conditional_filters() {
+        let process = |port: u16, name: &str, cwd: &str| ProcessInfo {
+            pid: port as i32,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: Some(cwd.to_string()),
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        };
+        let filters: HashMap<String, Vec<ConditionalFilter>> = toml::from_str(
+            "[[f]]\nhours = \"22:00-06:00\"\ndays = [\"sat\", \"sun\"]\n\
+             [[f]]\nmore_than = 2\nsmart_filter = true\n\
+             [[f]]\noutside_project = true\n",
+        )
+        .unwrap();
+        let filters = &filters["f"];
+
+        // Saturday 2026-10-17: inside the window late at night and early in the morning
+        let at = |time: &str| {
+            NaiveDateTime::parse_from_str(&format!("2026-10-17 {}", time), "%Y-%m-%d %H:%M")
+                .unwrap()
+        };
+        let night = CompiledConditional::compile(&filters[0], false).unwrap();
+        assert!(night.applies(at("23:30"), 0));
+        assert!(night.applies(at("05:59"), 0));
+        assert!(!night.applies(at("12:00"), 0));
+        assert!(!night.applies(at("23:30") + chrono::Duration::days(2), 0));
+        assert!(CompiledConditional::compile(
+            &ConditionalFilter {
+                hours: Some("9-".to_string()),
+                ..Default::default()
+            },
+            false
+        )
+        .is_err());
+
+        let filter = SmartFilter::new(HashSet::new(), HashSet::new(), None, HashSet::new(), None)
+            .unwrap()
+            .with_conditional_filters(&filters[1..], ([22].into(), HashSet::new(), HashSet::new()))
+            .unwrap();
+        let project = crate::cache::project::project_root("");
+        let inside = project.join("web").to_string_lossy().to_string();
+        let mut processes: HashMap<u16, ProcessInfo> = [
+            (22, process(22, "sshd", &inside)),
+            (3000, process(3000, "node", &inside)),
+        ]
+        .into();
+        // Two processes aren't more than two: sshd stays
+        filter.filter_processes(&mut processes);
+        assert_eq!(processes.len(), 2);
+
+        processes.insert(3001, process(3001, "node", "/somewhere/else"));
+        filter.filter_processes(&mut processes);
+        assert_eq!(processes.keys().collect::<Vec<_>>(), vec![&3000]);
+    }
 }
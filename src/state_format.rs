@@ -0,0 +1,201 @@
+//! Versioning for the JSON state port-kill keeps between runs: user presets, kill history,
+//! port reservations and restart info.
+//!
+//! The files keep the shape older releases read (a map or list of records); each record
+//! carries its own `schema_version` instead. Loading goes through [`decode_record`]:
+//!
+//! - records without `schema_version` come from releases before versioning (version 0) and
+//!   are upgraded with [`Versioned::upgrade`]; fields they never had take serde defaults
+//! - records from a newer release are read as-is; fields this release doesn't know are kept
+//!   in [`StateMeta::unknown`] and written back untouched, along with their version
+//!
+//! Schema changes must stay additive so that path keeps working: a newer release may add
+//! fields, but keeps writing every field an older one requires.
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Schema version written with every record
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Version bookkeeping flattened into every persisted record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StateMeta {
+    /// 0 for records written before versioning
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Fields written by a newer release, kept so saving doesn't drop them
+    #[serde(flatten)]
+    pub unknown: Map<String, Value>,
+}
+
+impl Default for StateMeta {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            unknown: Map::new(),
+        }
+    }
+}
+
+/// A persisted record type
+pub trait Versioned: DeserializeOwned {
+    /// What the record is called in error messages
+    const KIND: &'static str;
+
+    /// Rewrite a record written with schema `from` into the current layout, before it is
+    /// deserialized. Missing fields don't need handling here; they have serde defaults.
+    fn upgrade(_record: &mut Map<String, Value>, _from: u32) {}
+}
+
+/// Turn a comma-separated string (how some early releases stored lists) into an array
+pub fn split_list_field(record: &mut Map<String, Value>, field: &str, numeric: bool) {
+    let Some(Value::String(list)) = record.get(field) else {
+        return;
+    };
+    let items: Vec<Value> = list
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| {
+            if numeric {
+                item.parse::<u64>().ok().map(Value::from)
+            } else {
+                Some(Value::from(item))
+            }
+        })
+        .collect();
+    record.insert(field.to_string(), Value::Array(items));
+}
+
+/// Deserialize one record, upgrading it first if it predates [`SCHEMA_VERSION`]
+pub fn decode_record<T: Versioned>(mut value: Value) -> Result<T> {
+    if let Value::Object(record) = &mut value {
+        let version = record
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32;
+        if version < SCHEMA_VERSION {
+            T::upgrade(record, version);
+            record.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+        } else if version > SCHEMA_VERSION {
+            log::debug!(
+                "{} written by a newer port-kill (schema {}); keeping fields this version doesn't know",
+                T::KIND,
+                version
+            );
+        }
+    }
+    serde_json::from_value(value).with_context(|| format!("Invalid {}", T::KIND))
+}
+
+/// Load a file holding a JSON list of records
+pub fn decode_list<T: Versioned>(content: &str) -> Result<Vec<T>> {
+    let values: Vec<Value> = serde_json::from_str(content)?;
+    values.into_iter().map(decode_record).collect()
+}
+
+/// Load a file holding a JSON map of records
+pub fn decode_map<K, T>(content: &str) -> Result<HashMap<K, T>>
+where
+    K: DeserializeOwned + Eq + Hash,
+    T: Versioned,
+{
+    let values: HashMap<K, Value> = serde_json::from_str(content)?;
+    values
+        .into_iter()
+        .map(|(key, value)| Ok((key, decode_record(value)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preset_manager::PortPreset;
+    use crate::restart_manager::RestartInfo;
+    use crate::types::{PortReservation, ProcessHistoryEntry};
+
+    #[test]
+    fn test_reads_records_from_before_versioning() {
+        // Presets from early releases: lists as comma-separated strings, no filter flags
+        let presets: HashMap<String, PortPreset> = decode_map(
+            r#"{"web": {"name": "web", "description": "Web", "ports": [3000],
+                        "ignore_ports": "5432, 6379", "ignore_processes": "postgres"}}"#,
+        )
+        .unwrap();
+        let web = &presets["web"];
+        assert_eq!(web.ignore_ports, Some(vec![5432, 6379]));
+        assert_eq!(web.ignore_processes, Some(vec!["postgres".to_string()]));
+        assert!(!web.smart_filter);
+        assert_eq!(web.meta.schema_version, SCHEMA_VERSION);
+
+        // History before command lines were recorded
+        let history: Vec<ProcessHistoryEntry> = decode_list(
+            r#"[{"pid": 42, "port": 3000, "process_name": "node",
+                 "killed_at": "2025-01-02T03:04:05Z"}]"#,
+        )
+        .unwrap();
+        assert_eq!(history[0].killed_by, "user");
+        assert_eq!(history[0].command_line, None);
+
+        let reservations: HashMap<u16, PortReservation> = decode_map(
+            r#"{"3000": {"port": 3000, "project_name": "app", "process_name": "node",
+                         "reserved_at": "2025-01-02T03:04:05Z"}}"#,
+        )
+        .unwrap();
+        assert!(!reservations[&3000].auto_renew);
+
+        let restart: HashMap<u16, RestartInfo> = decode_map(
+            r#"{"8000": {"port": 8000, "command": ["python", "-m", "http.server"],
+                         "working_directory": "/srv"}}"#,
+        )
+        .unwrap();
+        assert!(restart[&8000].env_vars.is_empty());
+    }
+
+    #[test]
+    fn test_keeps_fields_from_newer_releases() {
+        let written_by_newer = r#"[{"schema_version": 7, "pid": 42, "port": 3000,
+            "process_name": "node", "killed_at": "2025-01-02T03:04:05Z", "killed_by": "auto",
+            "signal": "SIGTERM", "tags": ["ci"]}]"#;
+        let history: Vec<ProcessHistoryEntry> = decode_list(written_by_newer).unwrap();
+        assert_eq!(history[0].meta.schema_version, 7);
+        assert_eq!(history[0].meta.unknown["signal"], "SIGTERM");
+
+        let saved: Value = serde_json::to_value(&history).unwrap();
+        assert_eq!(saved[0]["schema_version"], 7);
+        assert_eq!(saved[0]["tags"][0], "ci");
+        assert_eq!(saved[0]["killed_by"], "auto");
+    }
+
+    #[test]
+    fn test_older_readers_accept_current_records() {
+        // The preset layout older releases deserialize, which ignores unknown fields
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct OlderPreset {
+            name: String,
+            description: String,
+            ports: Vec<u16>,
+            ignore_ports: Option<Vec<u16>>,
+            smart_filter: bool,
+        }
+
+        let mut presets = HashMap::new();
+        presets.insert(
+            "api".to_string(),
+            PortPreset::new("api".to_string(), "API".to_string(), vec![8080]),
+        );
+        let content = serde_json::to_string(&presets).unwrap();
+        let older: HashMap<String, OlderPreset> = serde_json::from_str(&content).unwrap();
+        assert_eq!(older["api"].ports, vec![8080]);
+
+        let reloaded: HashMap<String, PortPreset> = decode_map(&content).unwrap();
+        assert_eq!(reloaded["api"].meta, StateMeta::default());
+    }
+}
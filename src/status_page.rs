@@ -0,0 +1,212 @@
+use crate::cache::types::ListSummary;
+use crate::scan_health::ScanHealth;
+use crate::types::{format_uptime, ProcessInfo, SecurityAuditResult};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Snapshot rendered by `--status-page`: a static, self-contained HTML page that
+/// can be dropped onto a wiki or opened straight from disk
+pub struct StatusPage {
+    pub generated_at: DateTime<Utc>,
+    pub port_description: String,
+    pub processes: Vec<ProcessInfo>,
+    pub scan_health: ScanHealth,
+    pub cache_summary: Option<ListSummary>,
+    pub last_audit: Option<SecurityAuditResult>,
+    /// Seconds between browser reloads when the page is rewritten continuously
+    pub auto_refresh: Option<u64>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= 1024.0 * MB {
+        format!("{:.1} GB", bytes as f64 / 1024.0 / MB)
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB)
+    }
+}
+
+impl StatusPage {
+    pub fn render_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        if let Some(secs) = self.auto_refresh {
+            html.push_str(&format!(
+                "<meta http-equiv=\"refresh\" content=\"{}\">\n",
+                secs
+            ));
+        }
+        html.push_str("<title>Port Kill status</title>\n<style>\n");
+        html.push_str(
+            "body{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;margin:2rem;color:#222}\n\
+             table{border-collapse:collapse;margin-bottom:1.5rem}\n\
+             th,td{border:1px solid #ddd;padding:.35rem .7rem;text-align:left}\n\
+             th{background:#f4f4f4}\n\
+             .muted{color:#777}.warn{color:#b36b00}.bad{color:#b00020}\n",
+        );
+        html.push_str("</style>\n</head>\n<body>\n<h1>Port Kill status</h1>\n");
+        html.push_str(&format!(
+            "<p class=\"muted\">Generated {} &middot; monitoring {}</p>\n",
+            self.generated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            escape(&self.port_description)
+        ));
+
+        if let Some(message) = self.scan_health.describe() {
+            html.push_str(&format!("<p class=\"bad\">{}</p>\n", escape(&message)));
+        }
+
+        // Ports and processes
+        let mut processes: Vec<&ProcessInfo> = self.processes.iter().collect();
+        processes.sort_by_key(|p| p.port);
+        html.push_str(&format!("<h2>Ports in use ({})</h2>\n", processes.len()));
+        if processes.is_empty() {
+            html.push_str("<p class=\"muted\">No processes detected</p>\n");
+        } else {
+            html.push_str("<table>\n<tr><th>Port</th><th>PID</th><th>Process</th><th>Project</th><th>User</th><th>Uptime</th></tr>\n");
+            for p in &processes {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    p.port,
+                    p.pid,
+                    escape(&p.get_display_name()),
+                    escape(p.project_name.as_deref().unwrap_or("")),
+                    escape(p.user.as_deref().unwrap_or("")),
+                    p.uptime_secs.map(format_uptime).unwrap_or_default()
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        // Projects
+        let mut projects: BTreeMap<&str, Vec<u16>> = BTreeMap::new();
+        for p in &processes {
+            if let Some(ref project) = p.project_name {
+                projects.entry(project).or_default().push(p.port);
+            }
+        }
+        if !projects.is_empty() {
+            html.push_str("<h2>Projects</h2>\n<ul>\n");
+            for (project, ports) in &projects {
+                let ports: Vec<String> = ports.iter().map(|p| p.to_string()).collect();
+                html.push_str(&format!(
+                    "<li>{} &mdash; ports {}</li>\n",
+                    escape(project),
+                    ports.join(", ")
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        // Caches
+        html.push_str("<h2>Caches</h2>\n");
+        match &self.cache_summary {
+            Some(summary) => html.push_str(&format!(
+                "<p>{} cache(s), {} total, {} stale</p>\n",
+                summary.count,
+                format_bytes(summary.total_size_bytes),
+                summary.stale_count
+            )),
+            None => html.push_str("<p class=\"muted\">Cache stats unavailable</p>\n"),
+        }
+
+        // Last audit
+        html.push_str("<h2>Last security audit</h2>\n");
+        match &self.last_audit {
+            Some(audit) => {
+                html.push_str(&format!(
+                    "<p>{} &middot; score {:.0}/100 &middot; {} suspicious, {} approved</p>\n",
+                    audit.audit_timestamp.format("%Y-%m-%d %H:%M UTC"),
+                    audit.security_score,
+                    audit.suspicious_processes.len(),
+                    audit.approved_processes.len()
+                ));
+                if !audit.suspicious_processes.is_empty() {
+                    html.push_str("<ul class=\"warn\">\n");
+                    for suspicious in &audit.suspicious_processes {
+                        html.push_str(&format!(
+                            "<li>Port {}: {} ({:?}, {:?} risk)</li>\n",
+                            suspicious.port,
+                            escape(&suspicious.process_info.name),
+                            suspicious.suspicion_reason,
+                            suspicious.risk_level
+                        ));
+                    }
+                    html.push_str("</ul>\n");
+                }
+            }
+            None => html.push_str(
+                "<p class=\"muted\">No audit recorded yet (run <code>port-kill-console --audit</code>)</p>\n",
+            ),
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Write the page, replacing the file in one step so viewers never see a partial page
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("html.tmp");
+        fs::write(&tmp, self.render_html())
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html_escapes_and_groups_projects() {
+        let process = ProcessInfo {
+            pid: 42,
+            port: 3000,
+            command: "node".to_string(),
+            name: "<node>".to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: Some("web & api".to_string()),
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            user: None,
+            uid: None,
+            wsl_distro: None,
+            started_at: None,
+            uptime_secs: Some(90),
+            ppid: None,
+            gpu_memory: None,
+        };
+        let page = StatusPage {
+            generated_at: Utc::now(),
+            port_description: "ports 3000".to_string(),
+            processes: vec![process],
+            scan_health: ScanHealth::degraded("lsof was denied access"),
+            cache_summary: None,
+            last_audit: None,
+            auto_refresh: Some(5),
+        };
+
+        let html = page.render_html();
+        assert!(html.contains("http-equiv=\"refresh\" content=\"5\""));
+        assert!(html.contains("&lt;node&gt;"));
+        assert!(!html.contains("<node>"));
+        assert!(html.contains("web &amp; api &mdash; ports 3000"));
+        assert!(html.contains("Scan incomplete"));
+        assert!(html.contains("No audit recorded yet"));
+    }
+}
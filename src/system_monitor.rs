@@ -1,5 +1,7 @@
-use std::collections::HashMap;
-use sysinfo::{Pid, System};
+use crate::types::ProcessInfo;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessRefreshKind, System, UpdateKind};
 
 pub struct SystemMonitor {
     system: System,
@@ -97,6 +99,256 @@ impl SystemMonitor {
     }
 }
 
+/// Readings kept per process for its sparkline
+pub const SPARKLINE_SAMPLES: usize = 8;
+
+/// CPU and memory of the monitored processes, sampled on every scan. Only their PIDs are
+/// refreshed; the `System` is kept between scans because CPU usage needs two readings.
+pub struct ResourceSampler {
+    system: System,
+    cpu_history: HashMap<i32, VecDeque<f64>>,
+}
+
+impl Default for ResourceSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceSampler {
+    pub fn new() -> Self {
+        Self {
+            system: System::new(),
+            cpu_history: HashMap::new(),
+        }
+    }
+
+    /// Fill in CPU and memory usage of `processes`, remembering CPU for the sparklines
+    pub fn sample(&mut self, processes: &mut HashMap<u16, ProcessInfo>) {
+        self.system.refresh_memory();
+        let total_memory = self.system.total_memory();
+        for process_info in processes.values_mut() {
+            let pid = Pid::from_u32(process_info.pid as u32);
+            let refresh = ProcessRefreshKind::new().with_cpu().with_memory();
+            if !self.system.refresh_process_specifics(pid, refresh) {
+                continue;
+            }
+            let Some(process) = self.system.process(pid) else {
+                continue;
+            };
+            let cpu = process.cpu_usage() as f64;
+            process_info.cpu_usage = Some(cpu);
+            process_info.memory_usage = Some(process.memory());
+            if total_memory > 0 {
+                process_info.memory_percentage =
+                    Some(process.memory() as f64 / total_memory as f64 * 100.0);
+            }
+            let history = self.cpu_history.entry(process_info.pid).or_default();
+            history.push_back(cpu);
+            if history.len() > SPARKLINE_SAMPLES {
+                history.pop_front();
+            }
+        }
+        // Forget processes that are gone, so the map stays as small as the port list
+        let live: std::collections::HashSet<i32> = processes.values().map(|p| p.pid).collect();
+        self.cpu_history.retain(|pid, _| live.contains(pid));
+        if self.system.processes().len() > live.len() * 4 + 32 {
+            // Costs one CPU baseline
+            self.system = System::new();
+        }
+    }
+
+    /// Recent CPU readings of `pid` as a sparkline, oldest first
+    pub fn cpu_sparkline(&self, pid: i32) -> String {
+        self.cpu_history
+            .get(&pid)
+            .map(|history| sparkline(&history.iter().copied().collect::<Vec<_>>()))
+            .unwrap_or_default()
+    }
+}
+
+/// `▁▂▅█`-style bars for `values`, scaled to the largest (or to 5%, so idle noise stays low)
+pub fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().fold(5.0, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            let level = (value.max(0.0) / max * (BARS.len() - 1) as f64).round() as usize;
+            BARS[level.min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
+/// Fill in start time, uptime, parent PID and (on Unix) the owner's user ID for scanned
+/// processes. Only the listed PIDs are refreshed, so this is cheap enough to run on every scan.
+pub fn fill_process_origins(processes: &mut HashMap<u16, ProcessInfo>) {
+    let mut system = System::new();
+    let now = chrono::Utc::now();
+    for process_info in processes.values_mut() {
+        // WSL PIDs live in the Linux VM, not in the Windows process table
+        if process_info.wsl_distro.is_some() {
+            continue;
+        }
+        let pid = Pid::from_u32(process_info.pid as u32);
+        system.refresh_process_specifics(
+            pid,
+            ProcessRefreshKind::new().with_user(UpdateKind::OnlyIfNotSet),
+        );
+        if let Some(process) = system.process(pid) {
+            let ppid = process.parent().map(|parent| parent.as_u32() as i32);
+            process_info.set_origin(process.start_time(), ppid, now);
+            #[cfg(unix)]
+            {
+                process_info.uid = process.user_id().map(|uid| **uid);
+            }
+        }
+    }
+}
+
+/// Name of a running process, if it still exists
+pub fn process_name(pid: i32) -> Option<String> {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid as u32);
+    system.refresh_process(pid);
+    system.process(pid).map(|process| process.name().to_string())
+}
+
+/// Arguments a running process was started with, argv[0] first
+pub fn process_command_line(pid: i32) -> Option<Vec<String>> {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid as u32);
+    system.refresh_process_specifics(pid, ProcessRefreshKind::new().with_cmd(UpdateKind::OnlyIfNotSet));
+    system
+        .process(pid)
+        .map(|process| process.cmd().to_vec())
+        .filter(|cmd| !cmd.is_empty())
+}
+
+/// PID and arguments of every running process whose name is one of `names`
+pub fn command_lines_of(names: &[&str]) -> Vec<(i32, Vec<String>)> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessRefreshKind::new().with_cmd(UpdateKind::OnlyIfNotSet));
+    system
+        .processes()
+        .iter()
+        .filter(|(_, process)| {
+            let name = process.name().trim_end_matches(".exe");
+            names.contains(&name)
+        })
+        .map(|(pid, process)| (pid.as_u32() as i32, process.cmd().to_vec()))
+        .collect()
+}
+
+/// Every descendant of `pid`, deepest first, so a process tree can be killed bottom-up
+pub fn descendants(pid: i32) -> Vec<i32> {
+    let mut system = System::new();
+    system.refresh_processes();
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (child, process) in system.processes() {
+        if let Some(parent) = process.parent() {
+            children
+                .entry(parent.as_u32() as i32)
+                .or_default()
+                .push(child.as_u32() as i32);
+        }
+    }
+
+    // Parents come before their children here; reversed, children come first
+    let mut order = Vec::new();
+    let mut stack = vec![pid];
+    while let Some(current) = stack.pop() {
+        for &child in children.get(&current).into_iter().flatten() {
+            if child != pid && !order.contains(&child) {
+                order.push(child);
+                stack.push(child);
+            }
+        }
+    }
+    order.reverse();
+    order
+}
+
+/// PID and start time (Unix seconds) of a process
+type ProcessKey = (i32, Option<i64>);
+
+/// Working directories looked up so far, by PID and start time so a reused PID isn't taken
+/// for the process that had it before. The tray and console rescan every few seconds, and a
+/// server's directory doesn't change in between.
+static WORKING_DIRECTORIES: Mutex<Option<HashMap<ProcessKey, Option<String>>>> = Mutex::new(None);
+const MAX_CACHED_WORKING_DIRECTORIES: usize = 4096;
+
+/// Fill in the working directory (and the project derived from it) where the scan left it
+/// empty, as it does outside verbose mode.
+pub fn fill_working_directories(processes: &mut HashMap<u16, ProcessInfo>) {
+    fill_working_directories_where(processes, |_| true);
+}
+
+/// [`fill_working_directories`] for duplicate-instance detection: only processes that
+/// share their command with another PID can be duplicates, and most scans have none, so
+/// usually nothing is looked up.
+pub fn fill_duplicate_candidates(processes: &mut HashMap<u16, ProcessInfo>) {
+    let mut pids_by_command: HashMap<String, HashSet<i32>> = HashMap::new();
+    for process_info in processes.values() {
+        pids_by_command
+            .entry(crate::types::instance_command(process_info))
+            .or_default()
+            .insert(process_info.pid);
+    }
+    fill_working_directories_where(processes, |process_info| {
+        pids_by_command
+            .get(&crate::types::instance_command(process_info))
+            .is_some_and(|pids| pids.len() > 1)
+    });
+}
+
+fn fill_working_directories_where(
+    processes: &mut HashMap<u16, ProcessInfo>,
+    wanted: impl Fn(&ProcessInfo) -> bool,
+) {
+    let mut cache = WORKING_DIRECTORIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if cache.len() > MAX_CACHED_WORKING_DIRECTORIES {
+        cache.clear();
+    }
+    let mut system = System::new();
+    for process_info in processes.values_mut() {
+        if process_info.working_directory.is_some()
+            || process_info.wsl_distro.is_some()
+            || !wanted(process_info)
+        {
+            continue;
+        }
+        let key = (
+            process_info.pid,
+            process_info.started_at.map(|started| started.timestamp()),
+        );
+        let cwd = cache
+            .entry(key)
+            .or_insert_with(|| {
+                let pid = Pid::from_u32(process_info.pid as u32);
+                system.refresh_process_specifics(
+                    pid,
+                    ProcessRefreshKind::new().with_cwd(UpdateKind::OnlyIfNotSet),
+                );
+                system
+                    .process(pid)
+                    .and_then(|process| process.cwd())
+                    .filter(|cwd| !cwd.as_os_str().is_empty())
+                    .map(|cwd| cwd.to_string_lossy().to_string())
+            })
+            .clone();
+        if let Some(cwd) = cwd {
+            process_info.working_directory = Some(cwd);
+            if process_info.project_name.is_none() {
+                process_info.project_name = process_info.extract_project_name();
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SystemInfo {
     pub total_memory: u64,
@@ -167,4 +419,102 @@ mod tests {
         assert_eq!(system_info.format_memory(1048576), "1.0 MB");
         assert_eq!(system_info.format_memory(1073741824), "1.0 GB");
     }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0]), "▁▅█");
+        // Below 5% everything stays low
+        assert_eq!(sparkline(&[0.0, 1.0, 0.5]), "▁▂▂");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_descendants_of_current_process() {
+        let mut child = std::process::Command::new("sleep").arg("5").spawn().unwrap();
+        let found = descendants(std::process::id() as i32);
+        let _ = child.kill();
+        let _ = child.wait();
+        assert!(found.contains(&(child.id() as i32)));
+    }
+
+    fn process(pid: i32, port: u16, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_origins_include_the_owner_uid() {
+        use std::os::unix::fs::MetadataExt;
+
+        // A file this process creates is owned by its user
+        let file = std::env::temp_dir().join(format!("port-kill-uid-{}", std::process::id()));
+        std::fs::write(&file, "").unwrap();
+        let own_uid = std::fs::metadata(&file).unwrap().uid();
+        let _ = std::fs::remove_file(&file);
+
+        let mut processes =
+            HashMap::from([(3000, process(std::process::id() as i32, 3000, "test"))]);
+        fill_process_origins(&mut processes);
+        assert_eq!(processes[&3000].uid, Some(own_uid));
+        assert!(processes[&3000].started_at.is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_duplicate_candidates_get_a_working_directory() {
+        let own_pid = std::process::id() as i32;
+        let parent_pid = std::os::unix::process::parent_id() as i32;
+        let own_dir = std::env::current_dir().unwrap();
+
+        // Every command is unique: nothing can be a duplicate, nothing is looked up
+        let mut processes = HashMap::from([
+            (3000, process(own_pid, 3000, "server")),
+            (3001, process(parent_pid, 3001, "runner")),
+        ]);
+        fill_duplicate_candidates(&mut processes);
+        assert!(processes.values().all(|p| p.working_directory.is_none()));
+
+        // Two PIDs with the same command are looked up, the unrelated one isn't
+        let mut processes = HashMap::from([
+            (3000, process(own_pid, 3000, "server")),
+            (3001, process(parent_pid, 3001, "server")),
+            (3002, process(own_pid, 3002, "other")),
+        ]);
+        fill_duplicate_candidates(&mut processes);
+        assert_eq!(
+            processes[&3000].working_directory.as_deref(),
+            Some(own_dir.to_string_lossy().as_ref())
+        );
+        assert!(processes[&3002].working_directory.is_none());
+
+        // The full fill reuses what was looked up
+        let mut processes = HashMap::from([(3002, process(own_pid, 3002, "other"))]);
+        fill_working_directories(&mut processes);
+        assert_eq!(
+            processes[&3002].working_directory.as_deref(),
+            Some(own_dir.to_string_lossy().as_ref())
+        );
+    }
 }
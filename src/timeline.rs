@@ -0,0 +1,815 @@
+//! The port timeline: long-running modes (tray, console monitoring, TUI) record when
+//! listeners appear and disappear in `~/.port-kill/timeline.jsonl`, so `--at` can rebuild
+//! the port map at a past moment ("what was holding 3000 at 3pm yesterday?").
+//!
+//! Each running port-kill writes its own session. A session starts with a snapshot of every
+//! listener, then records opens and closes as they happen, another snapshot every few
+//! hours, and a heartbeat when nothing else was written for a while, which is how gaps
+//! (port-kill not running) are told apart from "nothing changed". Records older than
+//! [`RETENTION_DAYS`] are dropped when a session starts, and daily while one keeps running.
+
+use crate::types::ProcessInfo;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Days of timeline kept
+pub const RETENTION_DAYS: i64 = 7;
+/// A heartbeat is written when a session has been quiet this long
+const HEARTBEAT_MINUTES: i64 = 5;
+/// A full snapshot is written this often, so a port map can be rebuilt from recent records
+const SNAPSHOT_HOURS: i64 = 6;
+
+/// One listener, as recorded in the timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Listener {
+    pub port: u16,
+    pub pid: i32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+}
+
+impl From<&ProcessInfo> for Listener {
+    fn from(process: &ProcessInfo) -> Self {
+        Self {
+            port: process.port,
+            pid: process.pid,
+            name: process.name.clone(),
+            command_line: process.command_line.clone(),
+            project_name: process.project_name.clone(),
+            container_name: process.container_name.clone(),
+            user: process.user.clone(),
+            uid: process.uid,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Change {
+    /// Every listener at this moment
+    Snapshot { listeners: Vec<Listener> },
+    Open { listener: Listener },
+    Close { port: u16, pid: i32 },
+    /// Still recording, nothing changed
+    Alive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub at: DateTime<Utc>,
+    pub session: String,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+/// Turns successive scans into timeline records for one session
+#[derive(Debug)]
+pub struct Recorder {
+    session: String,
+    listeners: BTreeMap<(u16, i32), Listener>,
+    last_written: Option<DateTime<Utc>>,
+    last_snapshot: Option<DateTime<Utc>>,
+    /// Sessions prune the file when they start and then daily, see [`compact`]
+    pruned_at: DateTime<Utc>,
+}
+
+impl Recorder {
+    pub fn new(session: String) -> Self {
+        Self {
+            session,
+            listeners: BTreeMap::new(),
+            last_written: None,
+            last_snapshot: None,
+            pruned_at: Utc::now(),
+        }
+    }
+
+    /// Records for a scan taken at `now`
+    pub fn observe(&mut self, processes: &HashMap<u16, ProcessInfo>, now: DateTime<Utc>) -> Vec<Record> {
+        let current: BTreeMap<(u16, i32), Listener> = processes
+            .values()
+            .map(|p| ((p.port, p.pid), Listener::from(p)))
+            .collect();
+
+        let due_snapshot = self
+            .last_snapshot
+            .is_none_or(|at| now - at >= Duration::hours(SNAPSHOT_HOURS));
+        let mut changes = Vec::new();
+        if due_snapshot {
+            changes.push(Change::Snapshot {
+                listeners: current.values().cloned().collect(),
+            });
+            self.last_snapshot = Some(now);
+        } else {
+            for (&(port, pid), _) in self.listeners.iter().filter(|(key, _)| !current.contains_key(key)) {
+                changes.push(Change::Close { port, pid });
+            }
+            for (_, listener) in current.iter().filter(|(key, _)| !self.listeners.contains_key(key)) {
+                changes.push(Change::Open {
+                    listener: listener.clone(),
+                });
+            }
+        }
+        if changes.is_empty()
+            && self
+                .last_written
+                .is_some_and(|at| now - at >= Duration::minutes(HEARTBEAT_MINUTES))
+        {
+            changes.push(Change::Alive);
+        }
+        if !changes.is_empty() {
+            self.last_written = Some(now);
+        }
+        self.listeners = current;
+
+        changes
+            .into_iter()
+            .map(|change| Record {
+                at: now,
+                session: self.session.clone(),
+                change,
+            })
+            .collect()
+    }
+}
+
+pub fn timeline_path() -> PathBuf {
+    crate::uninstall::home_dir()
+        .join(".port-kill")
+        .join("timeline.jsonl")
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// Record a scan from a long-running mode. Failed or partial scans are skipped, since
+/// listeners missing from them didn't actually go away.
+pub fn record(processes: &HashMap<u16, ProcessInfo>) {
+    if crate::demo::is_enabled()
+        || crate::scan_health::last_scan().status != crate::scan_health::ScanStatus::Ok
+    {
+        return;
+    }
+    let Ok(mut recorder) = RECORDER.lock() else {
+        return;
+    };
+    let path = timeline_path();
+    let recorder = recorder.get_or_insert_with(|| {
+        if let Err(e) = prune(&path, Utc::now() - Duration::days(RETENTION_DAYS)) {
+            log::debug!("Could not prune {}: {}", path.display(), e);
+        }
+        Recorder::new(format!("{}-{}", std::process::id(), Utc::now().timestamp()))
+    });
+    let records = recorder.observe(processes, Utc::now());
+    if records.is_empty() {
+        return;
+    }
+    if let Err(e) = append(&path, &records) {
+        log::debug!("Could not write {}: {}", path.display(), e);
+    }
+}
+
+/// Prune the timeline when this session last did so over a day ago, so a daemon that runs
+/// for weeks doesn't keep growing it. Sessions that don't record leave it alone.
+pub fn compact() {
+    let Ok(mut recorder) = RECORDER.lock() else {
+        return;
+    };
+    let Some(recorder) = recorder.as_mut() else {
+        return;
+    };
+    let now = Utc::now();
+    if now - recorder.pruned_at < Duration::days(1) {
+        return;
+    }
+    recorder.pruned_at = now;
+    let path = timeline_path();
+    if let Err(e) = prune(&path, now - Duration::days(RETENTION_DAYS)) {
+        log::debug!("Could not prune {}: {}", path.display(), e);
+    }
+}
+
+fn append(path: &PathBuf, records: &[Record]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Drop what is only needed for moments before `cutoff`: sessions that ended before it,
+/// and in the others everything before their last snapshot taken by then
+fn prune(path: &PathBuf, cutoff: DateTime<Utc>) -> Result<()> {
+    let records = match load(path) {
+        Ok(records) => records,
+        Err(_) => return Ok(()),
+    };
+    if records.first().is_none_or(|first| first.at >= cutoff) {
+        return Ok(());
+    }
+    let mut last_record: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    let mut keep_from: HashMap<&str, usize> = HashMap::new();
+    for (i, record) in records.iter().enumerate() {
+        last_record.insert(&record.session, record.at);
+        if record.at <= cutoff && matches!(record.change, Change::Snapshot { .. }) {
+            keep_from.insert(&record.session, i);
+        }
+    }
+    let kept: Vec<Record> = records
+        .iter()
+        .enumerate()
+        .filter(|(i, record)| {
+            let session = record.session.as_str();
+            last_record[session] >= cutoff && keep_from.get(session).is_none_or(|from| i >= from)
+        })
+        .map(|(_, record)| record.clone())
+        .collect();
+    let tmp = path.with_extension("jsonl.tmp");
+    let _ = fs::remove_file(&tmp);
+    append(&tmp, &kept)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Every readable record, oldest first
+pub fn load(path: &PathBuf) -> Result<Vec<Record>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+    let mut records: Vec<Record> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    records.sort_by_key(|record| record.at);
+    Ok(records)
+}
+
+/// The port map as recorded at some moment
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastPortMap {
+    pub listeners: Vec<Listener>,
+    /// Last record the map is built from
+    pub recorded_at: DateTime<Utc>,
+    /// false when port-kill wasn't recording at that moment; the map is then the last one
+    /// recorded before it
+    pub covered: bool,
+}
+
+/// Rebuild the port map at `at` from `records` (oldest first)
+pub fn port_map_at(records: &[Record], at: DateTime<Utc>) -> Option<PastPortMap> {
+    // Sessions that had started by then: their last record up to `at`, and their last record
+    let mut sessions: BTreeMap<&str, (DateTime<Utc>, DateTime<Utc>)> = BTreeMap::new();
+    for record in records {
+        if let Some(entry) = sessions.get_mut(record.session.as_str()) {
+            if record.at <= at {
+                entry.0 = record.at;
+            }
+            entry.1 = record.at;
+        } else if record.at <= at {
+            sessions.insert(&record.session, (record.at, record.at));
+        }
+    }
+    let started: Vec<_> = sessions.into_iter().collect();
+    let slack = Duration::minutes(HEARTBEAT_MINUTES * 2);
+    // A session covers `at` if it kept recording past it, or its last record is recent enough
+    let covering = |(_, (before, last)): &(&str, (DateTime<Utc>, DateTime<Utc>))| {
+        *last >= at || at - *before <= slack
+    };
+    let (session, covered) = match started.iter().filter(|s| covering(s)).max_by_key(|s| s.1 .0) {
+        Some(s) => (s.0, true),
+        None => (started.iter().max_by_key(|s| s.1 .0)?.0, false),
+    };
+
+    let mut listeners: BTreeMap<(u16, i32), Listener> = BTreeMap::new();
+    let mut recorded_at = None;
+    for record in records.iter().filter(|r| r.session == session && r.at <= at) {
+        match &record.change {
+            Change::Snapshot { listeners: all } => {
+                listeners = all.iter().map(|l| ((l.port, l.pid), l.clone())).collect();
+            }
+            Change::Open { listener } => {
+                listeners.insert((listener.port, listener.pid), listener.clone());
+            }
+            Change::Close { port, pid } => {
+                listeners.remove(&(*port, *pid));
+            }
+            Change::Alive => {}
+        }
+        recorded_at = Some(record.at);
+    }
+    Some(PastPortMap {
+        listeners: listeners.into_values().collect(),
+        recorded_at: recorded_at?,
+        covered,
+    })
+}
+
+/// The port map at a past moment (`--at`), compared with a scan taken now
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TimeTravelReport {
+    /// The moment asked about
+    pub at: DateTime<Utc>,
+    /// Time of the last timeline record the past port map is built from
+    pub recorded_at: DateTime<Utc>,
+    /// false when port-kill wasn't recording at `at`; the map is then the last one recorded
+    /// before it
+    pub covered: bool,
+    /// Listeners at `at`, by port then PID
+    pub then: Vec<Listener>,
+    /// Listening then, gone now
+    pub gone: Vec<Listener>,
+    /// The same process still listening on the same port
+    pub still_running: Vec<Listener>,
+    /// Listening now, not then
+    pub new: Vec<Listener>,
+}
+
+impl TimeTravelReport {
+    pub fn new(at: DateTime<Utc>, past: PastPortMap, now: &HashMap<u16, ProcessInfo>) -> Self {
+        let mut current: Vec<Listener> = now.values().map(Listener::from).collect();
+        current.sort_by_key(|l| (l.port, l.pid));
+        let same = |a: &Listener, b: &Listener| a.port == b.port && a.pid == b.pid;
+        let gone = past
+            .listeners
+            .iter()
+            .filter(|then| !current.iter().any(|now| same(then, now)))
+            .cloned()
+            .collect();
+        let still_running = past
+            .listeners
+            .iter()
+            .filter(|then| current.iter().any(|now| same(then, now)))
+            .cloned()
+            .collect();
+        let new = current
+            .into_iter()
+            .filter(|now| !past.listeners.iter().any(|then| same(then, now)))
+            .collect();
+        Self {
+            at,
+            recorded_at: past.recorded_at,
+            covered: past.covered,
+            then: past.listeners,
+            gone,
+            still_running,
+            new,
+        }
+    }
+}
+
+/// What a port was doing during a span of its timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Occupancy {
+    /// port-kill wasn't recording
+    Unknown,
+    Free,
+    Occupied,
+}
+
+/// A stretch of time a port stayed in one state
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct OccupancySpan {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub occupancy: Occupancy,
+    /// What held the port; empty unless it was occupied
+    pub listeners: Vec<Listener>,
+}
+
+impl OccupancySpan {
+    /// "node (PID 1234, web)", or the state
+    pub fn describe(&self) -> String {
+        match self.occupancy {
+            Occupancy::Unknown => "not recorded".to_string(),
+            Occupancy::Free => "free".to_string(),
+            Occupancy::Occupied => self
+                .listeners
+                .iter()
+                .map(|l| match &l.project_name {
+                    Some(project) => format!("{} (PID {}, {})", l.name, l.pid, project),
+                    None => format!("{} (PID {})", l.name, l.pid),
+                })
+                .collect::<Vec<_>>()
+                .join(" + "),
+        }
+    }
+}
+
+/// When one port was occupied and by what (`--timeline PORT`)
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PortTimeline {
+    pub port: u16,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Oldest first, back to back from `since` to `until`
+    pub spans: Vec<OccupancySpan>,
+}
+
+/// The state of `port` at `at`, from `records` (oldest first)
+fn occupancy_at(records: &[Record], port: u16, at: DateTime<Utc>) -> (Occupancy, Vec<Listener>) {
+    match port_map_at(records, at) {
+        Some(map) if map.covered => {
+            let listeners: Vec<Listener> =
+                map.listeners.into_iter().filter(|l| l.port == port).collect();
+            if listeners.is_empty() {
+                (Occupancy::Free, listeners)
+            } else {
+                (Occupancy::Occupied, listeners)
+            }
+        }
+        _ => (Occupancy::Unknown, Vec::new()),
+    }
+}
+
+/// The timeline of `port` between `since` and `until`, from `records` (oldest first)
+pub fn port_timeline(
+    records: &[Record],
+    port: u16,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> PortTimeline {
+    // The state can only change where a session starts or stops covering the time, or
+    // records something about the port
+    let slack = Duration::minutes(HEARTBEAT_MINUTES * 2);
+    let mut last_record: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    let mut changes = vec![since];
+    for record in records {
+        last_record.insert(&record.session, record.at);
+        let touches_port = match &record.change {
+            Change::Snapshot { .. } => true,
+            Change::Open { listener } => listener.port == port,
+            Change::Close { port: closed, .. } => *closed == port,
+            Change::Alive => false,
+        };
+        if touches_port {
+            changes.push(record.at);
+        }
+    }
+    changes.extend(
+        last_record
+            .values()
+            .map(|last| *last + slack + Duration::seconds(1)),
+    );
+    changes.retain(|at| *at >= since && *at < until);
+    changes.sort();
+    changes.dedup();
+
+    let mut spans: Vec<OccupancySpan> = Vec::new();
+    for at in changes {
+        let (occupancy, listeners) = occupancy_at(records, port, at);
+        if let Some(last) = spans.last_mut() {
+            if last.occupancy == occupancy && last.listeners == listeners {
+                continue;
+            }
+            last.to = at;
+        }
+        spans.push(OccupancySpan {
+            from: at,
+            to: until,
+            occupancy,
+            listeners,
+        });
+    }
+    PortTimeline {
+        port,
+        since,
+        until,
+        spans,
+    }
+}
+
+/// The timeline of `port` over the last `hours`, from the timeline file
+pub fn recent_port_timeline(port: u16, hours: u32) -> Result<PortTimeline> {
+    let path = timeline_path();
+    let records = if path.exists() { load(&path)? } else { Vec::new() };
+    let until = Utc::now();
+    Ok(port_timeline(&records, port, until - Duration::hours(i64::from(hours)), until))
+}
+
+impl PortTimeline {
+    /// The timeline as an ASCII chart `width` columns wide: one letter per process that held
+    /// the port, `·` where it was free and blank where nothing was recorded, above a time
+    /// axis and a legend
+    pub fn chart(&self, width: usize) -> Vec<String> {
+        let width = width.max(12);
+        let step = (self.until - self.since) / width as i32;
+        let mut occupants: Vec<(&str, i32)> = Vec::new();
+        let mut bar = String::new();
+        for column in 0..width {
+            let start = self.since + step * column as i32;
+            let end = start + step;
+            let overlap = |span: &&OccupancySpan| {
+                (span.to.min(end) - span.from.max(start)).max(Duration::zero())
+            };
+            let span = self
+                .spans
+                .iter()
+                .filter(|span| overlap(span) > Duration::zero())
+                .max_by_key(overlap);
+            bar.push(match span {
+                Some(span) if span.occupancy == Occupancy::Occupied => {
+                    let occupant = (span.listeners[0].name.as_str(), span.listeners[0].pid);
+                    let index = occupants
+                        .iter()
+                        .position(|o| *o == occupant)
+                        .unwrap_or_else(|| {
+                            occupants.push(occupant);
+                            occupants.len() - 1
+                        });
+                    occupant_symbol(index)
+                }
+                Some(span) if span.occupancy == Occupancy::Free => '·',
+                _ => ' ',
+            });
+        }
+
+        // A time label every 12 columns
+        let format = if self.until - self.since > Duration::hours(24) {
+            "%m-%d %H:%M"
+        } else {
+            "%H:%M"
+        };
+        let mut axis = String::new();
+        for column in (0..width).step_by(12) {
+            let label = (self.since + step * column as i32)
+                .with_timezone(&Local)
+                .format(format)
+                .to_string();
+            let at = column + 1;
+            if axis.chars().count() < at && at + label.len() <= width + 2 {
+                axis.push_str(&" ".repeat(at - axis.chars().count()));
+                axis.push_str(&label);
+            }
+        }
+
+        let mut legend: Vec<String> = occupants
+            .iter()
+            .enumerate()
+            .map(|(i, (name, pid))| format!("{} {} (PID {})", occupant_symbol(i), name, pid))
+            .collect();
+        legend.push("· free".to_string());
+        if bar.contains(' ') {
+            legend.push("(blank) not recorded".to_string());
+        }
+        vec![format!("|{}|", bar), axis, legend.join("   ")]
+    }
+}
+
+/// The chart letter of the `index`th process on a port
+fn occupant_symbol(index: usize) -> char {
+    (b'A'..=b'Z').map(char::from).nth(index).unwrap_or('#')
+}
+
+pub(crate) fn parse_clock(text: &str) -> Option<NaiveTime> {
+    let text = text.trim();
+    let (text, pm) = if let Some(t) = text.strip_suffix("pm") {
+        (t.trim(), Some(true))
+    } else if let Some(t) = text.strip_suffix("am") {
+        (t.trim(), Some(false))
+    } else {
+        (text, None)
+    };
+    let mut parts = text.split(':');
+    let mut hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let second: u32 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(true) if hour != 12 => hour += 12,
+        Some(false) if hour == 12 => hour = 0,
+        _ => {}
+    }
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+pub(crate) fn parse_ago(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let amount: i64 = text[..split].parse().ok()?;
+    match text[split..].trim() {
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(Duration::seconds(amount)),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(Duration::minutes(amount)),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(Duration::hours(amount)),
+        "d" | "day" | "days" => Some(Duration::days(amount)),
+        _ => None,
+    }
+}
+
+/// Parse an `--at` moment: `15:00`, `3pm`, `yesterday 15:00`, `2025-06-01 09:30`,
+/// `2h ago`, or RFC 3339. A bare time of day in the future means yesterday.
+pub fn parse_when(input: &str, now: DateTime<Local>) -> Result<DateTime<Utc>> {
+    let text = input.trim().to_lowercase();
+    let invalid = || {
+        anyhow::anyhow!(
+            "Can't read '{}' as a time; try '15:00', 'yesterday 3pm', '2h ago' or '2025-06-01 09:30'",
+            input
+        )
+    };
+    if text == "now" {
+        return Ok(now.with_timezone(&Utc));
+    }
+    if let Some(ago) = text.strip_suffix("ago") {
+        return Ok((now - parse_ago(ago).ok_or_else(invalid)?).with_timezone(&Utc));
+    }
+    if let Ok(at) = DateTime::parse_from_rfc3339(input.trim()) {
+        return Ok(at.with_timezone(&Utc));
+    }
+
+    let (date, time_text, bare_time) = if let Some(rest) = text.strip_prefix("yesterday") {
+        (now.date_naive() - Duration::days(1), rest.trim(), false)
+    } else if let Some(rest) = text.strip_prefix("today") {
+        (now.date_naive(), rest.trim(), false)
+    } else if let Some((date, rest)) = text
+        .split_once([' ', 't'])
+        .and_then(|(d, rest)| Some((NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()?, rest)))
+    {
+        (date, rest.trim(), false)
+    } else if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        (date, "", false)
+    } else {
+        (now.date_naive(), text.as_str(), true)
+    };
+    let time = if time_text.is_empty() {
+        if text.starts_with("yesterday") {
+            now.time()
+        } else {
+            NaiveTime::MIN
+        }
+    } else {
+        parse_clock(time_text).ok_or_else(invalid)?
+    };
+
+    let local = |date: NaiveDate| {
+        Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .ok_or_else(invalid)
+    };
+    let mut at = local(date)?;
+    if bare_time && at > now {
+        at = local(date - Duration::days(1))?;
+    }
+    Ok(at.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(port: u16, pid: i32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        }
+    }
+
+    fn scan(processes: &[ProcessInfo]) -> HashMap<u16, ProcessInfo> {
+        processes.iter().map(|p| (p.port, p.clone())).collect()
+    }
+
+    #[test]
+    fn test_rebuilds_port_map_at_a_past_moment() {
+        let start = Utc.with_ymd_and_hms(2025, 6, 1, 14, 0, 0).unwrap();
+        let minutes = |m: i64| start + Duration::minutes(m);
+        let mut recorder = Recorder::new("a".to_string());
+        let mut records = Vec::new();
+        records.extend(recorder.observe(&scan(&[process(3000, 10, "node")]), start));
+        // Nothing changes for a while: no records until the heartbeat
+        assert!(recorder.observe(&scan(&[process(3000, 10, "node")]), minutes(1)).is_empty());
+        records.extend(recorder.observe(&scan(&[process(3000, 10, "node")]), minutes(6)));
+        assert_eq!(records.last().unwrap().change, Change::Alive);
+        records.extend(recorder.observe(
+            &scan(&[process(3000, 10, "node"), process(8080, 20, "java")]),
+            minutes(30),
+        ));
+        records.extend(recorder.observe(&scan(&[process(8080, 20, "java")]), minutes(90)));
+
+        // Round trip through the file format
+        let lines: Vec<String> = records.iter().map(|r| serde_json::to_string(r).unwrap()).collect();
+        let records: Vec<Record> = lines.iter().map(|l| serde_json::from_str(l).unwrap()).collect();
+
+        let at_60 = port_map_at(&records, minutes(60)).unwrap();
+        assert!(at_60.covered);
+        assert_eq!(at_60.recorded_at, minutes(30));
+        let ports: Vec<u16> = at_60.listeners.iter().map(|l| l.port).collect();
+        assert_eq!(ports, vec![3000, 8080]);
+
+        assert!(port_map_at(&records, minutes(-10)).is_none());
+        // Long after the last record: the recording had stopped
+        let later = port_map_at(&records, minutes(600)).unwrap();
+        assert!(!later.covered);
+        assert_eq!(later.listeners.len(), 1);
+
+        let report = TimeTravelReport::new(
+            minutes(60),
+            at_60,
+            &scan(&[process(8080, 20, "java"), process(5173, 30, "node")]),
+        );
+        assert_eq!(report.gone[0].port, 3000);
+        assert_eq!(report.still_running[0].port, 8080);
+        assert_eq!(report.new[0].port, 5173);
+    }
+
+    #[test]
+    fn test_port_timeline() {
+        let start = Utc.with_ymd_and_hms(2025, 6, 1, 14, 0, 0).unwrap();
+        let minutes = |m: i64| start + Duration::minutes(m);
+        let mut recorder = Recorder::new("a".to_string());
+        let mut records = Vec::new();
+        let node = [process(3000, 10, "node")];
+        let python = [process(3000, 20, "python")];
+        for (m, scan_at) in [(0, &node[..]), (30, &[][..]), (60, &python[..])] {
+            records.extend(recorder.observe(&scan(scan_at), minutes(m)));
+        }
+        // Heartbeats until 2h in, then nothing
+        for m in (65..=120).step_by(5) {
+            records.extend(recorder.observe(&scan(&python), minutes(m)));
+        }
+
+        let timeline = port_timeline(&records, 3000, minutes(-60), minutes(180));
+        let states: Vec<(i64, Occupancy, String)> = timeline
+            .spans
+            .iter()
+            .map(|s| ((s.from - start).num_minutes(), s.occupancy, s.describe()))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (-60, Occupancy::Unknown, "not recorded".to_string()),
+                (0, Occupancy::Occupied, "node (PID 10)".to_string()),
+                (30, Occupancy::Free, "free".to_string()),
+                (60, Occupancy::Occupied, "python (PID 20)".to_string()),
+                (130, Occupancy::Unknown, "not recorded".to_string()),
+            ]
+        );
+        assert_eq!(timeline.spans.last().unwrap().to, minutes(180));
+
+        let chart = timeline.chart(24);
+        assert_eq!(chart[0], "|      AAA···BBBBBBB     |");
+        assert!(chart[2].starts_with("A node (PID 10)   B python (PID 20)   · free"));
+    }
+
+    #[test]
+    fn test_parse_when() {
+        let now = Local.with_ymd_and_hms(2025, 6, 2, 10, 30, 0).unwrap();
+        let local = |d: u32, h: u32, m: u32| {
+            Local.with_ymd_and_hms(2025, 6, d, h, m, 0).unwrap().with_timezone(&Utc)
+        };
+        assert_eq!(parse_when("yesterday 15:00", now).unwrap(), local(1, 15, 0));
+        assert_eq!(parse_when("Yesterday 3pm", now).unwrap(), local(1, 15, 0));
+        assert_eq!(parse_when("9:15", now).unwrap(), local(2, 9, 15));
+        // Later today hasn't happened yet, so it means yesterday
+        assert_eq!(parse_when("15:00", now).unwrap(), local(1, 15, 0));
+        assert_eq!(parse_when("2025-06-01 12:00am", now).unwrap(), local(1, 0, 0));
+        assert_eq!(parse_when("2h ago", now).unwrap(), local(2, 8, 30));
+        assert_eq!(parse_when("90 minutes ago", now).unwrap(), local(2, 9, 0));
+        assert_eq!(
+            parse_when("2025-06-01T12:00:00Z", now).unwrap(),
+            Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()
+        );
+        assert!(parse_when("last tuesday", now).is_err());
+        assert!(parse_when("25:00", now).is_err());
+    }
+}
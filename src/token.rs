@@ -0,0 +1,25 @@
+//! Secrets for local servers: the `--serve` bearer token and the Settings page path.
+//! They come from the OS random number generator, so another local user or page can't
+//! guess them from the time or the process ID.
+
+use anyhow::{anyhow, Result};
+
+/// 128 random bits from the OS, as 32 hex digits
+pub fn random_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| anyhow!("Could not generate a random token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_hex_and_differ() {
+        let (a, b) = (random_token().unwrap(), random_token().unwrap());
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+}
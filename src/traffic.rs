@@ -0,0 +1,288 @@
+//! Rough per-port traffic: bytes per second and new connections per second on each
+//! monitored listener, from two snapshots of its sockets a few seconds apart. On Linux the
+//! snapshots come from `ss -ti` and include TIME-WAIT sockets, so short requests that open and
+//! close between snapshots are still counted; macOS uses `nettop`. Elsewhere (or when those
+//! tools are missing) only open connections are seen and there are no byte counts. Used by
+//! `--traffic` and `--only-active`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::process::Command;
+use std::time::Duration;
+
+/// One connected socket whose local side is a monitored port
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketSample {
+    pub port: u16,
+    pub peer: String,
+    /// ESTABLISHED, as opposed to closing (TIME-WAIT and friends)
+    pub open: bool,
+    /// Cumulative bytes received/sent, where the platform exposes them
+    pub bytes_in: Option<u64>,
+    pub bytes_out: Option<u64>,
+}
+
+/// Traffic on one monitored port over a sampling window
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PortTraffic {
+    pub port: u16,
+    /// Connections open at the end of the window
+    pub connections: usize,
+    /// Connections opened per second during the window
+    pub new_connections_per_sec: f64,
+    /// Bytes received per second; absent when socket byte counters aren't available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_in_per_sec: Option<f64>,
+    /// Bytes sent per second; absent when socket byte counters aren't available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_out_per_sec: Option<f64>,
+    /// Length of the sampling window in seconds
+    pub window_secs: f64,
+}
+
+impl PortTraffic {
+    /// Something is hitting the port: new connections or bytes moving. Without byte
+    /// counters, any open connection counts.
+    pub fn is_active(&self) -> bool {
+        match (self.bytes_in_per_sec, self.bytes_out_per_sec) {
+            (Some(bytes_in), Some(bytes_out)) => {
+                self.new_connections_per_sec > 0.0 || bytes_in > 0.0 || bytes_out > 0.0
+            }
+            _ => self.new_connections_per_sec > 0.0 || self.connections > 0,
+        }
+    }
+
+    /// "12.3 KB/s in, 1.0 MB/s out, 0.5 new conn/s, 3 open"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let (Some(bytes_in), Some(bytes_out)) = (self.bytes_in_per_sec, self.bytes_out_per_sec) {
+            parts.push(format!("{} in", format_rate(bytes_in)));
+            parts.push(format!("{} out", format_rate(bytes_out)));
+        }
+        parts.push(format!("{:.1} new conn/s", self.new_connections_per_sec));
+        parts.push(format!("{} open", self.connections));
+        parts.join(", ")
+    }
+}
+
+/// "512 B/s", "12.3 KB/s", "1.0 MB/s"
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    if bytes_per_sec >= 1024.0 * 1024.0 {
+        format!("{:.1} MB/s", bytes_per_sec / 1024.0 / 1024.0)
+    } else if bytes_per_sec >= 1024.0 {
+        format!("{:.1} KB/s", bytes_per_sec / 1024.0)
+    } else {
+        format!("{:.0} B/s", bytes_per_sec)
+    }
+}
+
+/// Port of "host:port", "[v6]:port" or nettop's "host.port"
+fn port_of(address: &str) -> Option<u16> {
+    address.rsplit([':', '.']).next()?.parse().ok()
+}
+
+fn counter(info: &str, key: &str) -> Option<u64> {
+    info.split_whitespace()
+        .find_map(|field| field.strip_prefix(key)?.strip_prefix(':')?.parse().ok())
+}
+
+/// Parse `ss -tinH state connected`: a socket line (State Recv-Q Send-Q Local Peer) followed
+/// by an indented line of TCP info with `bytes_received:` and `bytes_acked:`
+pub fn parse_ss(stdout: &str, ports: &HashSet<u16>) -> Vec<SocketSample> {
+    let mut samples = Vec::new();
+    let mut current: Option<SocketSample> = None;
+    for line in stdout.lines() {
+        if line.starts_with(char::is_whitespace) {
+            if let Some(sample) = current.as_mut() {
+                sample.bytes_in = counter(line, "bytes_received").or(Some(0));
+                sample.bytes_out = counter(line, "bytes_acked").or(counter(line, "bytes_sent")).or(Some(0));
+            }
+            continue;
+        }
+        samples.extend(current.take());
+        let mut parts: Vec<&str> = line.split_whitespace().collect();
+        // The State column is left out when ss is asked for a single state
+        let state = match parts.first() {
+            Some(first) if first.parse::<u64>().is_err() => parts.remove(0),
+            _ => "ESTAB",
+        };
+        if parts.len() < 4 {
+            continue;
+        }
+        if let Some(port) = port_of(parts[2]).filter(|p| ports.contains(p)) {
+            current = Some(SocketSample {
+                port,
+                peer: parts[3].to_string(),
+                open: state == "ESTAB",
+                bytes_in: None,
+                bytes_out: None,
+            });
+        }
+    }
+    samples.extend(current);
+    samples
+}
+
+/// Parse `nettop -m tcp -L 1 -n -x -J bytes_in,bytes_out`: CSV with process rows and
+/// connection rows like `tcp4 127.0.0.1:3000<->127.0.0.1:54321,1200,4800,`
+pub fn parse_nettop(stdout: &str, ports: &HashSet<u16>) -> Vec<SocketSample> {
+    let mut samples = Vec::new();
+    for line in stdout.lines() {
+        let fields: Vec<&str> = line.split(',').collect();
+        // Rows may start with a time column
+        let Some(at) = fields.iter().position(|f| f.contains("<->")) else {
+            continue;
+        };
+        let Some((local, peer)) = fields[at].split_once("<->") else {
+            continue;
+        };
+        let local = local.split_whitespace().last().unwrap_or(local);
+        let counter = |offset: usize| fields.get(at + offset).and_then(|f| f.trim().parse().ok());
+        if let Some(port) = port_of(local).filter(|p| ports.contains(p)) {
+            samples.push(SocketSample {
+                port,
+                peer: peer.trim().to_string(),
+                open: true,
+                bytes_in: counter(1),
+                bytes_out: counter(2),
+            });
+        }
+    }
+    samples
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// ESTABLISHED sockets on `ports` right now
+pub fn sample_sockets(ports: &HashSet<u16>) -> Vec<SocketSample> {
+    if cfg!(target_os = "linux") {
+        if let Some(stdout) = command_stdout("ss", &["-tinH", "state", "connected"]) {
+            return parse_ss(&stdout, ports);
+        }
+    } else if cfg!(target_os = "macos") {
+        if let Some(stdout) = command_stdout("nettop", &["-m", "tcp", "-L", "1", "-n", "-x", "-J", "bytes_in,bytes_out"]) {
+            return parse_nettop(&stdout, ports);
+        }
+    }
+
+    // No byte counters: fall back to the connection list
+    let ports: Vec<u16> = ports.iter().copied().collect();
+    crate::connections::get_established_connections(&ports)
+        .into_values()
+        .flat_map(|connections| {
+            let port = connections.port;
+            connections.peers.into_iter().map(move |peer| SocketSample {
+                port,
+                peer,
+                open: true,
+                bytes_in: None,
+                bytes_out: None,
+            })
+        })
+        .collect()
+}
+
+/// Traffic per port between two snapshots taken `window` apart. Every port in `ports` gets
+/// an entry, idle ones included.
+pub fn compare(
+    ports: &[u16],
+    before: &[SocketSample],
+    after: &[SocketSample],
+    window: Duration,
+) -> BTreeMap<u16, PortTraffic> {
+    let secs = window.as_secs_f64().max(0.001);
+    let counters = before.iter().chain(after).all(|s| s.bytes_in.is_some() && s.bytes_out.is_some());
+    let earlier: HashMap<(u16, &str), &SocketSample> =
+        before.iter().map(|s| ((s.port, s.peer.as_str()), s)).collect();
+
+    let mut traffic: BTreeMap<u16, (usize, usize, u64, u64)> = ports.iter().map(|p| (*p, Default::default())).collect();
+    for sample in after {
+        let (open, new, bytes_in, bytes_out) = traffic.entry(sample.port).or_default();
+        *open += usize::from(sample.open);
+        let (base_in, base_out) = match earlier.get(&(sample.port, sample.peer.as_str())) {
+            Some(previous) => (previous.bytes_in.unwrap_or(0), previous.bytes_out.unwrap_or(0)),
+            None => {
+                *new += 1;
+                (0, 0)
+            }
+        };
+        *bytes_in += sample.bytes_in.unwrap_or(0).saturating_sub(base_in);
+        *bytes_out += sample.bytes_out.unwrap_or(0).saturating_sub(base_out);
+    }
+
+    traffic
+        .into_iter()
+        .map(|(port, (open, new, bytes_in, bytes_out))| {
+            let traffic = PortTraffic {
+                port,
+                connections: open,
+                new_connections_per_sec: new as f64 / secs,
+                bytes_in_per_sec: counters.then_some(bytes_in as f64 / secs),
+                bytes_out_per_sec: counters.then_some(bytes_out as f64 / secs),
+                window_secs: secs,
+            };
+            (port, traffic)
+        })
+        .collect()
+}
+
+/// Sample the sockets on `ports` twice, `window` apart (blocks for `window`)
+pub fn measure(ports: &[u16], window: Duration) -> BTreeMap<u16, PortTraffic> {
+    let set: HashSet<u16> = ports.iter().copied().collect();
+    if set.is_empty() {
+        return BTreeMap::new();
+    }
+    let before = sample_sockets(&set);
+    std::thread::sleep(window);
+    let after = sample_sockets(&set);
+    compare(ports, &before, &after, window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ss_and_compare_samples() {
+        let ports: HashSet<u16> = [3000, 8000].into_iter().collect();
+        let before = parse_ss(
+            "0      0      127.0.0.1:3000 127.0.0.1:54321
+\t cubic wscale:7,7 rto:204 bytes_sent:100 bytes_acked:100 bytes_received:50 segs_out:4
+0      0      127.0.0.1:54321 127.0.0.1:3000
+\t cubic wscale:7,7 rto:204 bytes_acked:50 bytes_received:100 segs_out:4",
+            &ports,
+        );
+        assert_eq!(before.len(), 1);
+        assert_eq!(before[0].bytes_in, Some(50));
+
+        let after = parse_ss(
+            "ESTAB 0      0      127.0.0.1:3000 127.0.0.1:54321
+\t cubic bytes_acked:2148 bytes_received:1074
+TIME-WAIT 0      0      [::1]:3000 [::1]:60000
+\t cubic bytes_acked:1000 bytes_received:0",
+            &ports,
+        );
+        let traffic = compare(&[3000, 8000], &before, &after, Duration::from_secs(2));
+        let busy = &traffic[&3000];
+        assert_eq!(busy.connections, 1);
+        assert_eq!(busy.new_connections_per_sec, 0.5);
+        assert_eq!(busy.bytes_in_per_sec, Some(512.0));
+        assert_eq!(busy.bytes_out_per_sec, Some(1524.0));
+        assert!(busy.is_active());
+        assert!(!traffic[&8000].is_active());
+        assert_eq!(format_rate(1536.0), "1.5 KB/s");
+
+        let nettop = parse_nettop(
+            "time,,bytes_in,bytes_out,\n12:00:01.5,node.1234,10,20,\n12:00:01.5,tcp4 127.0.0.1:3000<->127.0.0.1:54321,1200,4800,",
+            &ports,
+        );
+        assert_eq!(nettop.len(), 1);
+        assert_eq!((nettop[0].bytes_in, nettop[0].bytes_out), (Some(1200), Some(4800)));
+    }
+}
@@ -1,27 +1,112 @@
-use crate::types::{ProcessInfo, StatusBarInfo};
+#[cfg(target_os = "macos")]
+use crate::icon_theme::{self, IconTheme, ICON_SIZE};
+use crate::profiles::ProfilePolicy;
+use crate::types::{find_duplicate_instances, ProcessInfo, StatusBarInfo};
 use anyhow::Result;
 use crossbeam_channel::Sender;
 use log::debug;
 use std::collections::HashMap;
 #[cfg(target_os = "macos")]
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu},
     Icon,
 };
 
+/// What a tray menu item does. Its menu ID is the action's text form (`kill:3000`), so
+/// events map straight back to an action and the port it applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrayAction {
+    KillAll,
+    KillDuplicates,
+    /// Kill every listener in a process group ("Node.js", "Docker", ...)
+    KillGroup(String),
+    /// Open the Settings window
+    Settings,
+    /// Turn the login item on or off
+    OpenAtLogin,
+    /// Start the most recently killed process again
+    UndoLastKill,
+    Quit,
+    Kill(u16),
+    /// Kill the process and everything it started
+    KillTree(u16),
+    /// Kill and start again with the saved command
+    Restart(u16),
+    /// Add the process name to `ignore_processes` in the global config
+    Ignore(u16),
+    CopyPid(u16),
+    OpenDirectory(u16),
+    Deprioritize(u16),
+}
+
+impl TrayAction {
+    pub fn parse(id: &str) -> Option<Self> {
+        match id {
+            "kill_all" => return Some(TrayAction::KillAll),
+            "kill_duplicates" => return Some(TrayAction::KillDuplicates),
+            "settings" => return Some(TrayAction::Settings),
+            "open_at_login" => return Some(TrayAction::OpenAtLogin),
+            "undo_last_kill" => return Some(TrayAction::UndoLastKill),
+            "quit" => return Some(TrayAction::Quit),
+            _ => {}
+        }
+        let (action, port) = id.split_once(':')?;
+        if action == "kill_group" {
+            return Some(TrayAction::KillGroup(port.to_string()));
+        }
+        let port: u16 = port.parse().ok()?;
+        Some(match action {
+            "kill" => TrayAction::Kill(port),
+            "kill_tree" => TrayAction::KillTree(port),
+            "restart" => TrayAction::Restart(port),
+            "ignore" => TrayAction::Ignore(port),
+            "copy_pid" => TrayAction::CopyPid(port),
+            "open_dir" => TrayAction::OpenDirectory(port),
+            "deprioritize" => TrayAction::Deprioritize(port),
+            _ => return None,
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn menu_id(&self) -> MenuId {
+        MenuId(self.to_string())
+    }
+}
+
+impl std::fmt::Display for TrayAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrayAction::KillAll => write!(f, "kill_all"),
+            TrayAction::KillDuplicates => write!(f, "kill_duplicates"),
+            TrayAction::KillGroup(group) => write!(f, "kill_group:{}", group),
+            TrayAction::Settings => write!(f, "settings"),
+            TrayAction::OpenAtLogin => write!(f, "open_at_login"),
+            TrayAction::UndoLastKill => write!(f, "undo_last_kill"),
+            TrayAction::Quit => write!(f, "quit"),
+            TrayAction::Kill(port) => write!(f, "kill:{}", port),
+            TrayAction::KillTree(port) => write!(f, "kill_tree:{}", port),
+            TrayAction::Restart(port) => write!(f, "restart:{}", port),
+            TrayAction::Ignore(port) => write!(f, "ignore:{}", port),
+            TrayAction::CopyPid(port) => write!(f, "copy_pid:{}", port),
+            TrayAction::OpenDirectory(port) => write!(f, "open_dir:{}", port),
+            TrayAction::Deprioritize(port) => write!(f, "deprioritize:{}", port),
+        }
+    }
+}
+
 #[cfg(target_os = "macos")]
 #[derive(Clone)]
 pub struct TrayMenu {
     pub icon: Icon,
+    pub theme: IconTheme,
     current_processes: HashMap<u16, ProcessInfo>,
     show_pid: bool,
 }
 
 #[cfg(target_os = "macos")]
 impl TrayMenu {
-    pub fn new(_menu_sender: Sender<MenuEvent>) -> Result<Self> {
-        // Create a simple icon (we'll use a text-based approach for now)
-        let icon = Self::create_icon("0")?;
+    pub fn new(_menu_sender: Sender<MenuEvent>, theme: IconTheme) -> Result<Self> {
+        let icon = Self::create_icon(0, theme)?;
 
         // Set up menu event handling
         let sender_clone = _menu_sender.clone();
@@ -31,6 +116,7 @@ impl TrayMenu {
 
         Ok(Self {
             icon,
+            theme,
             current_processes: HashMap::new(),
             show_pid: false,
         })
@@ -51,23 +137,26 @@ impl TrayMenu {
     }
 
     pub fn get_current_menu(&self) -> Result<Menu> {
-        let (menu, _) = Self::create_menu(&self.current_processes, self.show_pid)?;
-        Ok(menu)
+        Self::create_menu(&self.current_processes, self.show_pid)
     }
 
     pub fn update_status(&mut self, status_info: &StatusBarInfo) -> Result<()> {
         debug!("Updating status bar: {}", status_info.text);
 
-        // Update icon with new status text
-        self.icon = Self::create_icon(&status_info.text)?;
+        // Update icon with the count from the status text
+        let count = status_info
+            .text
+            .chars()
+            .filter(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0);
+        self.icon = Self::create_icon(count, self.theme)?;
 
         Ok(())
     }
 
-    pub fn create_menu(
-        processes: &HashMap<u16, ProcessInfo>,
-        show_pid: bool,
-    ) -> Result<(Menu, HashMap<String, u16>)> {
+    pub fn create_menu(processes: &HashMap<u16, ProcessInfo>, show_pid: bool) -> Result<Menu> {
         Self::create_menu_with_verbose(processes, show_pid, false)
     }
 
@@ -75,23 +164,75 @@ impl TrayMenu {
         processes: &HashMap<u16, ProcessInfo>,
         show_pid: bool,
         verbose: bool,
-    ) -> Result<(Menu, HashMap<String, u16>)> {
+    ) -> Result<Menu> {
         let menu = Menu::new();
-        let mut menu_id_to_port = HashMap::new();
 
         // Add "Kill All Processes" item
-        let kill_all_item = MenuItem::with_id(MenuId("kill_all".into()), "Kill All Processes", true, None);
+        let kill_all_item = MenuItem::with_id(TrayAction::KillAll.menu_id(), "Kill All Processes", true, None);
         menu.append(&kill_all_item)?;
 
+        // One "Kill all <group>" item per process group that is listening
+        for (group, count) in crate::process_monitor::group_counts(processes) {
+            let item = MenuItem::with_id(
+                TrayAction::KillGroup(group.clone()).menu_id(),
+                format!("Kill all {} ({})", group, count),
+                true,
+                None,
+            );
+            menu.append(&item)?;
+        }
+
+        // Warn about projects running more than once and offer to clean them up
+        let mut processes_with_dirs = processes.clone();
+        crate::system_monitor::fill_duplicate_candidates(&mut processes_with_dirs);
+        let duplicates = find_duplicate_instances(&processes_with_dirs);
+        if !duplicates.is_empty() {
+            for duplicate in &duplicates {
+                let warning = MenuItem::new(
+                    format!("⚠️ Duplicate instance: {}", duplicate.describe()),
+                    false,
+                    None,
+                );
+                menu.append(&warning)?;
+            }
+            let kill_duplicates_item = MenuItem::with_id(
+                TrayAction::KillDuplicates.menu_id(),
+                "Keep Newest, Kill Older",
+                true,
+                None,
+            );
+            menu.append(&kill_duplicates_item)?;
+        }
+
         // Add separator
         let separator = PredefinedMenuItem::separator();
         menu.append(&separator)?;
 
-        // Add individual process items
-        for (port, process_info) in processes {
+        // Restart is only offered for ports with a saved command
+        let restart_manager = crate::restart_manager::RestartManager::new().ok();
+
+        // Add a submenu per process, under a header per guard profile when the config
+        // defines profiles
+        let config = crate::config::load_or_default().config;
+        let mut entries: Vec<(Option<(String, ProfilePolicy)>, &u16, &ProcessInfo)> = processes_with_dirs
+            .iter()
+            .map(|(port, process_info)| (crate::profiles::profile_of(&config, *port), port, process_info))
+            .collect();
+        // Profiles first by name, then ports outside any profile
+        entries.sort_by(|a, b| (a.0.is_none(), &a.0, a.1).cmp(&(b.0.is_none(), &b.0, b.1)));
+        let mut current_group = None;
+        for (profile, port, process_info) in entries {
+            if !config.profiles.is_empty() && current_group.as_ref() != Some(&profile) {
+                let header = match &profile {
+                    Some((name, policy)) => format!("🛡️ {} ({})", name, policy.label()),
+                    None => "Other ports".to_string(),
+                };
+                menu.append(&MenuItem::new(header, false, None))?;
+                current_group = Some(profile.clone());
+            }
             let menu_text = if verbose {
                 // Verbose mode: show command line and working directory
-                let mut parts = vec![format!("Kill: Port {}: {}", port, process_info.name)];
+                let mut parts = vec![format!("Port {}: {}", port, process_info.name)];
 
                 if let Some(ref cmd_line) = process_info.command_line {
                     parts.push(format!("({})", cmd_line));
@@ -115,26 +256,59 @@ impl TrayMenu {
             } else if let (Some(_container_id), Some(container_name)) =
                 (&process_info.container_id, &process_info.container_name)
             {
-                format!(
-                    "Kill: Port {}: {} [Docker: {}]",
-                    port, process_info.name, container_name
-                )
+                format!("Port {}: {} [Docker: {}]", port, process_info.name, container_name)
             } else if show_pid {
-                format!(
-                    "Kill: Port {}: {} (PID {})",
-                    port, process_info.name, process_info.pid
-                )
+                format!("Port {}: {} (PID {})", port, process_info.name, process_info.pid)
             } else {
-                format!("Kill: Port {}: {}", port, process_info.name)
+                format!("Port {}: {}", port, process_info.name)
             };
 
-            // Create menu item for each process
-            let process_item = MenuItem::new(&menu_text, true, None);
-            let process_id = process_item.id();
-            menu.append(&process_item)?;
+            let can_restart = restart_manager
+                .as_ref()
+                .is_some_and(|manager| manager.can_restart(*port));
+            let submenu = Submenu::new(&menu_text, true);
+            for (action, label, enabled) in [
+                (TrayAction::Kill(*port), "Kill".to_string(), true),
+                (TrayAction::KillTree(*port), "Kill Tree".to_string(), true),
+                (TrayAction::Restart(*port), "Restart".to_string(), can_restart),
+                (
+                    TrayAction::Ignore(*port),
+                    format!("Add '{}' to Ignore List", process_info.name),
+                    true,
+                ),
+                (
+                    TrayAction::CopyPid(*port),
+                    format!("Copy PID ({})", process_info.pid),
+                    true,
+                ),
+                (
+                    TrayAction::OpenDirectory(*port),
+                    "Open Working Directory".to_string(),
+                    process_info.working_directory.is_some(),
+                ),
+            ] {
+                submenu.append(&MenuItem::with_id(action.menu_id(), label, enabled, None))?;
+            }
+            menu.append(&submenu)?;
+        }
 
-            // Store the mapping from menu ID to port
-            menu_id_to_port.insert(process_id.0.clone(), *port);
+        // Heavy processes can be slowed down instead of killed
+        let mut heavy: Vec<&ProcessInfo> = processes
+            .values()
+            .filter(|process| crate::priority::is_heavy(process))
+            .collect();
+        heavy.sort_by_key(|process| process.port);
+        if !heavy.is_empty() {
+            menu.append(&PredefinedMenuItem::separator())?;
+            for process in heavy {
+                let item = MenuItem::with_id(
+                    TrayAction::Deprioritize(process.port).menu_id(),
+                    format!("Deprioritize: Port {}: {}", process.port, process.name),
+                    true,
+                    None,
+                );
+                menu.append(&item)?;
+            }
         }
 
         // Add another separator if there are processes
@@ -143,11 +317,31 @@ impl TrayMenu {
             menu.append(&separator)?;
         }
 
+        // Undo for the last kill, named so the click is informed (a dialog still asks)
+        let last_killed = crate::undo::last_killed().ok().flatten();
+        let undo_label = match &last_killed {
+            Some(entry) => format!("Restart Last Killed: {} on port {}", entry.process_name, entry.port),
+            None => "Restart Last Killed".to_string(),
+        };
+        let undo_item = MenuItem::with_id(TrayAction::UndoLastKill.menu_id(), undo_label, last_killed.is_some(), None);
+        menu.append(&undo_item)?;
+
+        let open_at_login_item = CheckMenuItem::with_id(
+            TrayAction::OpenAtLogin.menu_id(),
+            "Open at Login",
+            true,
+            crate::autostart::is_enabled(),
+            None,
+        );
+        menu.append(&open_at_login_item)?;
+        let settings_item = MenuItem::with_id(TrayAction::Settings.menu_id(), "Settings…", true, None);
+        menu.append(&settings_item)?;
+
         // Add "Quit" item
-        let quit_item = MenuItem::with_id(MenuId("quit".into()), "Quit", true, None);
+        let quit_item = MenuItem::with_id(TrayAction::Quit.menu_id(), "Quit", true, None);
         menu.append(&quit_item)?;
 
-        Ok((menu, menu_id_to_port))
+        Ok(menu)
     }
 
     fn short_display_path(dir: &str) -> String {
@@ -159,56 +353,37 @@ impl TrayMenu {
         }
     }
 
-    pub fn create_icon(text: &str) -> Result<Icon> {
-        // Create a simple but visible icon for the status bar
-        let icon_data = Self::generate_visible_icon(text);
-
-        // Try different sizes for better compatibility
-        match Icon::from_rgba(icon_data.clone(), 16, 16) {
-            Ok(icon) => Ok(icon),
-            Err(_) => {
-                // Fallback to 32x32
-                Icon::from_rgba(icon_data, 32, 32)
-                    .map_err(|e| anyhow::anyhow!("Failed to create icon: {}", e))
-            }
-        }
+    /// The status bar icon: a badge with the process count, drawn in `theme`
+    pub fn create_icon(count: usize, theme: IconTheme) -> Result<Icon> {
+        Icon::from_rgba(icon_theme::render(count, theme), ICON_SIZE, ICON_SIZE)
+            .map_err(|e| anyhow::anyhow!("Failed to create icon: {}", e))
     }
+}
 
-    fn generate_visible_icon(text: &str) -> Vec<u8> {
-        // Create a much larger, highly visible 32x32 RGBA icon for the status bar
-        let mut icon_data = Vec::new();
-
-        for y in 0..32 {
-            for x in 0..32 {
-                // Create a very simple, highly visible icon
-                let _is_edge = x < 2 || x > 29 || y < 2 || y > 29;
-                let _is_center = x >= 14 && x <= 17 && y >= 14 && y <= 17;
-
-                // Create a number display area in the center
-                let is_number_area = x >= 12 && x <= 19 && y >= 12 && y <= 19;
-
-                let (r, g, b, a) = if is_number_area {
-                    // Parse the number from text (remove any non-numeric characters)
-                    let number = text.chars().filter(|c| c.is_numeric()).collect::<String>();
-                    let num = number.parse::<u32>().unwrap_or(0);
-
-                    if num == 0 {
-                        (0, 255, 0, 255) // Bright green when no processes
-                    } else if num <= 9 {
-                        // For 1-9 processes, use orange
-                        (255, 165, 0, 255) // Orange for 1-9 processes
-                    } else {
-                        // For 10+ processes, use red to indicate many processes
-                        (255, 0, 0, 255) // Red for 10+ processes
-                    }
-                } else {
-                    (255, 255, 255, 255) // Clean white background
-                };
-
-                icon_data.extend_from_slice(&[r, g, b, a]);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tray_action_ids_round_trip() {
+        for action in [
+            TrayAction::KillAll,
+            TrayAction::KillGroup("Web Server".to_string()),
+            TrayAction::Settings,
+            TrayAction::OpenAtLogin,
+            TrayAction::UndoLastKill,
+            TrayAction::Quit,
+            TrayAction::Kill(3000),
+            TrayAction::KillTree(5173),
+            TrayAction::Restart(8080),
+            TrayAction::Ignore(3000),
+            TrayAction::CopyPid(3000),
+            TrayAction::OpenDirectory(3000),
+            TrayAction::Deprioritize(9000),
+        ] {
+            assert_eq!(TrayAction::parse(&action.to_string()), Some(action));
         }
-
-        icon_data
+        assert_eq!(TrayAction::parse("kill:notaport"), None);
+        assert_eq!(TrayAction::parse("42"), None);
     }
 }
@@ -0,0 +1,598 @@
+use crate::{
+    cli::Args,
+    hooks::KillOutcome,
+    process_monitor::ProcessMonitor,
+    types::{format_uptime, ProcessInfo},
+};
+use anyhow::Result;
+use log::{error, info, LevelFilter, Log, Metadata, Record};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, List, ListItem, Paragraph, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_LOG_LINES: usize = 200;
+
+static LOG_LINES: StdMutex<VecDeque<String>> = StdMutex::new(VecDeque::new());
+
+/// Logger that keeps recent records in memory for the log pane instead of
+/// writing to stderr underneath the TUI
+struct TuiLogger {
+    level: LevelFilter,
+}
+
+impl Log for TuiLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Ok(mut lines) = LOG_LINES.lock() {
+            lines.push_back(format!(
+                "{} {:<5} {}",
+                chrono::Local::now().format("%H:%M:%S"),
+                record.level(),
+                record.args()
+            ));
+            while lines.len() > MAX_LOG_LINES {
+                lines.pop_front();
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Use in place of `logging::init()` when running `--tui`. Honours the level in RUST_LOG.
+pub fn init_logger() {
+    let level = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(LevelFilter::Info);
+    if log::set_boxed_logger(Box::new(TuiLogger { level })).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+fn recent_log_lines(limit: usize) -> Vec<String> {
+    LOG_LINES
+        .lock()
+        .map(|lines| lines.iter().rev().take(limit).cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Column the process table is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Port,
+    Name,
+    Project,
+    Cpu,
+    Memory,
+    Gpu,
+}
+
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            SortColumn::Port => SortColumn::Name,
+            SortColumn::Name => SortColumn::Project,
+            SortColumn::Project => SortColumn::Cpu,
+            SortColumn::Cpu => SortColumn::Memory,
+            SortColumn::Memory => SortColumn::Gpu,
+            SortColumn::Gpu => SortColumn::Port,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SortColumn::Port => "port",
+            SortColumn::Name => "name",
+            SortColumn::Project => "project",
+            SortColumn::Cpu => "cpu",
+            SortColumn::Memory => "memory",
+            SortColumn::Gpu => "gpu",
+        }
+    }
+}
+
+/// Table contents and the user's sort/filter/ignore choices, kept apart from
+/// the terminal so it can be tested
+pub struct ProcessTable {
+    processes: Vec<ProcessInfo>,
+    pub sort: SortColumn,
+    pub descending: bool,
+    pub filter: String,
+    ignored_ports: HashSet<u16>,
+}
+
+impl ProcessTable {
+    pub fn new(ignored_ports: HashSet<u16>) -> Self {
+        Self {
+            processes: Vec::new(),
+            sort: SortColumn::Port,
+            descending: false,
+            filter: String::new(),
+            ignored_ports,
+        }
+    }
+
+    pub fn set_processes(&mut self, processes: impl IntoIterator<Item = ProcessInfo>) {
+        self.processes = processes.into_iter().collect();
+    }
+
+    /// Cycle to the next sort column; resource columns sort busiest first
+    pub fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        self.descending = matches!(
+            self.sort,
+            SortColumn::Cpu | SortColumn::Memory | SortColumn::Gpu
+        );
+    }
+
+    pub fn ignore(&mut self, port: u16) {
+        self.ignored_ports.insert(port);
+    }
+
+    fn matches_filter(&self, process_info: &ProcessInfo) -> bool {
+        if self.filter.is_empty() {
+            return true;
+        }
+        let needle = self.filter.to_lowercase();
+        process_info.port.to_string().contains(&needle)
+            || process_info.name.to_lowercase().contains(&needle)
+            || process_info.command.to_lowercase().contains(&needle)
+            || process_info
+                .project_name
+                .as_ref()
+                .is_some_and(|p| p.to_lowercase().contains(&needle))
+    }
+
+    /// Rows to display: filtered, minus ignored ports, in the current sort order
+    pub fn visible(&self) -> Vec<&ProcessInfo> {
+        let mut rows: Vec<&ProcessInfo> = self
+            .processes
+            .iter()
+            .filter(|p| !self.ignored_ports.contains(&p.port) && self.matches_filter(p))
+            .collect();
+        rows.sort_by(|a, b| {
+            let ordering = match self.sort {
+                SortColumn::Port => a.port.cmp(&b.port),
+                SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SortColumn::Project => a.project_name.cmp(&b.project_name),
+                SortColumn::Cpu => a
+                    .cpu_usage
+                    .unwrap_or(0.0)
+                    .total_cmp(&b.cpu_usage.unwrap_or(0.0)),
+                SortColumn::Memory => a.memory_usage.cmp(&b.memory_usage),
+                SortColumn::Gpu => a.gpu_memory.cmp(&b.gpu_memory),
+            };
+            let ordering = ordering.then(a.port.cmp(&b.port));
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        rows
+    }
+}
+
+/// Interactive terminal UI (`--tui`): a live process table with kill/restart/ignore
+/// keybindings, plus history and log panes
+pub struct TuiApp {
+    process_monitor: Arc<Mutex<ProcessMonitor>>,
+    args: Args,
+    table: ProcessTable,
+    table_state: TableState,
+    editing_filter: bool,
+    /// The process `x`/`d` asked to kill, until `y` confirms or another key cancels
+    pending_kill: Option<(u16, i32, String)>,
+    status: String,
+    /// The selected port's last day, shown instead of the kill history after `t`
+    timeline: Option<crate::timeline::PortTimeline>,
+}
+
+impl TuiApp {
+    pub fn new(process_monitor: Arc<Mutex<ProcessMonitor>>, args: Args) -> Self {
+        let table = ProcessTable::new(args.get_ignore_ports_set());
+        Self {
+            process_monitor,
+            args,
+            table,
+            table_state: TableState::default().with_selected(Some(0)),
+            editing_filter: false,
+            pending_kill: None,
+            status: "Press ? for keys".to_string(),
+            timeline: None,
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        let mut terminal = ratatui::try_init()?;
+        let result = self.event_loop(&mut terminal).await;
+        ratatui::restore();
+        result
+    }
+
+    async fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let mut last_refresh: Option<Instant> = None;
+
+        loop {
+            if last_refresh.is_none_or(|t| t.elapsed() >= REFRESH_INTERVAL) {
+                self.refresh().await;
+                last_refresh = Some(Instant::now());
+            }
+
+            let history = self.history_lines().await;
+            terminal.draw(|frame| self.draw(frame, &history))?;
+
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.editing_filter {
+                match key.code {
+                    KeyCode::Enter => self.editing_filter = false,
+                    KeyCode::Esc => {
+                        self.editing_filter = false;
+                        self.table.filter.clear();
+                    }
+                    KeyCode::Backspace => {
+                        self.table.filter.pop();
+                    }
+                    KeyCode::Char(c) => self.table.filter.push(c),
+                    _ => {}
+                }
+                self.table_state.select(Some(0));
+                continue;
+            }
+
+            if let Some((port, pid, name)) = self.pending_kill.take() {
+                if key.code == KeyCode::Char('y') {
+                    self.kill(port, pid, &name).await;
+                    last_refresh = None;
+                } else {
+                    self.status = "Kill cancelled".to_string();
+                }
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.table_state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.table_state.select_previous(),
+                KeyCode::Char('s') => self.table.cycle_sort(),
+                KeyCode::Char('S') => self.table.descending = !self.table.descending,
+                KeyCode::Char('/') => self.editing_filter = true,
+                KeyCode::Char('x') | KeyCode::Char('d') => {
+                    if let Some((port, pid, name)) = self.selected() {
+                        self.status = format!("Kill {} (PID {}) on port {}? y/n", name, pid, port);
+                        self.pending_kill = Some((port, pid, name));
+                    }
+                }
+                KeyCode::Char('r') => {
+                    self.restart_selected().await;
+                    last_refresh = None;
+                }
+                KeyCode::Char('i') => self.ignore_selected(),
+                KeyCode::Char('y') => self.copy_selected(),
+                KeyCode::Char('t') => self.toggle_timeline(),
+                KeyCode::Char('?') => {
+                    self.status = "↑/↓ j/k select • s sort • S reverse • / filter • x/d kill • r restart • i ignore • y copy command • t port timeline • q quit".to_string();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn refresh(&mut self) {
+        let mut monitor = self.process_monitor.lock().await;
+        match monitor.scan_processes().await {
+            Ok(processes) => {
+                crate::timeline::record(&processes);
+                let ignore_processes = self.args.get_ignore_processes_set();
+                self.table.set_processes(
+                    processes
+                        .into_values()
+                        .filter(|p| !ignore_processes.contains(&p.name)),
+                );
+            }
+            Err(e) => error!("Scan failed: {}", e),
+        }
+        drop(monitor);
+        if let Some(message) = crate::scan_health::last_scan().describe() {
+            self.status = message;
+        }
+        if let Some(port) = self.timeline.as_ref().map(|timeline| timeline.port) {
+            self.load_timeline(port);
+        }
+    }
+
+    /// Chart the selected port's last 24 hours in place of the kill history, or go back
+    fn toggle_timeline(&mut self) {
+        if self.timeline.take().is_some() {
+            return;
+        }
+        if let Some((port, _, _)) = self.selected() {
+            self.load_timeline(port);
+        }
+    }
+
+    fn load_timeline(&mut self, port: u16) {
+        match crate::timeline::recent_port_timeline(port, 24) {
+            Ok(timeline) => self.timeline = Some(timeline),
+            Err(e) => self.status = format!("❌ {}", e),
+        }
+    }
+
+    fn selected(&self) -> Option<(u16, i32, String)> {
+        let rows = self.table.visible();
+        let index = self.table_state.selected()?.min(rows.len().checked_sub(1)?);
+        rows.get(index)
+            .map(|p| (p.port, p.pid, p.get_display_name()))
+    }
+
+    async fn kill(&mut self, port: u16, pid: i32, name: &str) {
+        let mut monitor = self.process_monitor.lock().await;
+        self.status = match monitor.kill_process_with_context(pid, "tui", true).await {
+            Ok(KillOutcome::Killed) => {
+                info!("Killed {} (PID {}) on port {}", name, pid, port);
+                format!("✅ Killed {} on port {}", name, port)
+            }
+            Ok(KillOutcome::Exited) => format!("✅ {} on port {} shut down gracefully", name, port),
+            Err(e) => {
+                error!("Failed to kill PID {} on port {}: {}", pid, port, e);
+                format!("❌ {}", e)
+            }
+        };
+    }
+
+    async fn restart_selected(&mut self) {
+        let Some((port, _, name)) = self.selected() else {
+            return;
+        };
+        let mut monitor = self.process_monitor.lock().await;
+        self.status = match monitor.restart_process_on_port(port).await {
+            Ok(()) => format!("🔄 Restarted {} on port {}", name, port),
+            Err(e) => format!("❌ Restart failed: {}", e),
+        };
+    }
+
+    /// Copy the selected process's command line, to start it again elsewhere
+    fn copy_selected(&mut self) {
+        let rows = self.table.visible();
+        let Some(process) = self
+            .table_state
+            .selected()
+            .and_then(|index| rows.get(index.min(rows.len().saturating_sub(1))))
+        else {
+            return;
+        };
+        let text = process.command_line.as_deref().unwrap_or(&process.command).to_string();
+        let port = process.port;
+        self.status = match crate::desktop_session::copy_to_clipboard(&text) {
+            Ok(tool) => format!("📋 Copied the command on port {} ({})", port, tool),
+            Err(e) => format!("❌ {}", e),
+        };
+    }
+
+    fn ignore_selected(&mut self) {
+        if let Some((port, _, name)) = self.selected() {
+            self.table.ignore(port);
+            info!("Ignoring port {} ({}) for this session", port, name);
+            self.status = format!("🙈 Ignoring port {} for this session", port);
+        }
+    }
+
+    async fn history_lines(&self) -> Vec<String> {
+        let monitor = self.process_monitor.lock().await;
+        monitor
+            .get_recent_history(50)
+            .iter()
+            .rev()
+            .map(|entry| {
+                format!(
+                    "{} :{} {} ({})",
+                    entry
+                        .killed_at
+                        .with_timezone(&chrono::Local)
+                        .format("%m-%d %H:%M"),
+                    entry.port,
+                    entry.process_name,
+                    if entry.action.is_kill() {
+                        entry.killed_by.clone()
+                    } else {
+                        entry.action.describe()
+                    }
+                )
+            })
+            .collect()
+    }
+
+    fn draw(&mut self, frame: &mut Frame, history: &[String]) {
+        let [table_area, panes_area, status_area] = Layout::vertical([
+            Constraint::Min(8),
+            Constraint::Length(10),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [history_area, log_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(panes_area);
+
+        let rows: Vec<Row> = self
+            .table
+            .visible()
+            .into_iter()
+            .map(|p| {
+                Row::new(vec![
+                    p.port.to_string(),
+                    p.pid.to_string(),
+                    p.get_display_name(),
+                    p.project_name.clone().unwrap_or_default(),
+                    p.cpu_usage
+                        .map(|cpu| format!("{:.1}%", cpu))
+                        .unwrap_or_default(),
+                    p.memory_usage
+                        .map(|bytes| format!("{:.1}MB", bytes as f64 / 1024.0 / 1024.0))
+                        .unwrap_or_default(),
+                    p.gpu_memory
+                        .map(crate::gpu::format_gpu_memory)
+                        .unwrap_or_default(),
+                    p.uptime_secs.map(format_uptime).unwrap_or_default(),
+                ])
+            })
+            .collect();
+        let count = rows.len();
+
+        let mut title = format!(
+            " Port Kill — {} process(es) • sort: {}{} ",
+            count,
+            self.table.sort.title(),
+            if self.table.descending {
+                " ↓"
+            } else {
+                " ↑"
+            }
+        );
+        if self.editing_filter || !self.table.filter.is_empty() {
+            title.push_str(&format!(
+                "• filter: {}{} ",
+                self.table.filter,
+                if self.editing_filter { "▏" } else { "" }
+            ));
+        }
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(6),
+                Constraint::Length(8),
+                Constraint::Fill(2),
+                Constraint::Fill(1),
+                Constraint::Length(7),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(8),
+            ],
+        )
+        .header(
+            Row::new([
+                "PORT", "PID", "NAME", "PROJECT", "CPU", "MEM", "GPU", "UPTIME",
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title(title))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+        frame.render_stateful_widget(table, table_area, &mut self.table_state);
+
+        let (history_title, history_items): (String, Vec<ListItem>) = match &self.timeline {
+            Some(timeline) => (
+                format!(" Port {}, last 24h (t: back) ", timeline.port),
+                timeline
+                    .chart(history_area.width.saturating_sub(4) as usize)
+                    .into_iter()
+                    .map(ListItem::new)
+                    .collect(),
+            ),
+            None => (
+                " Kill history ".to_string(),
+                history.iter().map(|l| ListItem::new(l.as_str())).collect(),
+            ),
+        };
+        frame.render_widget(
+            List::new(history_items).block(Block::bordered().title(history_title)),
+            history_area,
+        );
+
+        let log_items: Vec<ListItem> = recent_log_lines(log_area.height as usize)
+            .into_iter()
+            .map(ListItem::new)
+            .collect();
+        frame.render_widget(
+            List::new(log_items).block(Block::bordered().title(" Log ")),
+            log_area,
+        );
+
+        frame.render_widget(
+            Paragraph::new(Line::from(self.status.as_str())),
+            status_area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(port: u16, name: &str, cpu: f64) -> ProcessInfo {
+        ProcessInfo {
+            pid: port as i32,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: Some(cpu),
+            memory_usage: None,
+            memory_percentage: None,
+            user: None,
+            uid: None,
+            wsl_distro: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        }
+    }
+
+    #[test]
+    fn test_process_table_sort_filter_ignore() {
+        let mut table = ProcessTable::new(HashSet::new());
+        table.set_processes(vec![
+            process(8080, "python", 5.0),
+            process(3000, "node", 40.0),
+            process(5432, "postgres", 1.0),
+        ]);
+
+        let ports = |t: &ProcessTable| t.visible().iter().map(|p| p.port).collect::<Vec<_>>();
+        assert_eq!(ports(&table), vec![3000, 5432, 8080]);
+
+        // Port -> Name -> Project -> Cpu (descending)
+        table.cycle_sort();
+        table.cycle_sort();
+        table.cycle_sort();
+        assert_eq!(table.sort, SortColumn::Cpu);
+        assert_eq!(ports(&table), vec![3000, 8080, 5432]);
+
+        table.filter = "PO".to_string();
+        assert_eq!(ports(&table), vec![5432]);
+
+        table.filter.clear();
+        table.ignore(3000);
+        assert_eq!(ports(&table), vec![8080, 5432]);
+    }
+}
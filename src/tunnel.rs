@@ -0,0 +1,447 @@
+//! Tunnel awareness: recognise `ssh -L` forwards and `kubectl port-forward` sessions among
+//! local listeners, so a kill can reach the other end too (`--linked`). Killing a local
+//! tunnel then also stops the process it forwards to, over ssh or through kubectl, and
+//! killing ports on a `--remote` host also closes the local ssh tunnels pointing at them.
+
+use crate::command_line::parse_command_line;
+use crate::types::ProcessInfo;
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Where a tunnel's traffic ends up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `ssh -L local:target_host:target_port destination`
+    Ssh {
+        destination: String,
+        /// Connection options to reuse (`-p`, `-i`, `-J`, `-o`, ...)
+        options: Vec<String>,
+        /// Host the ssh server forwards to; the endpoint is on the server itself only when
+        /// this is a loopback name
+        target_host: String,
+    },
+    /// `kubectl port-forward resource local:target_port`
+    Kubernetes {
+        /// `pod/api-5d9f`, `deployment/api`, `svc/api`, or a bare pod name
+        resource: String,
+        /// `-n`, `--context` and `--kubeconfig`, to reach the same cluster
+        flags: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tunnel {
+    pub pid: i32,
+    pub local_port: u16,
+    pub target_port: u16,
+    pub endpoint: Endpoint,
+}
+
+fn is_loopback(host: &str) -> bool {
+    matches!(
+        host.trim_matches(|c| c == '[' || c == ']'),
+        "localhost" | "127.0.0.1" | "::1" | "0.0.0.0"
+    )
+}
+
+fn is_pod(kind: &str) -> bool {
+    matches!(kind, "pod" | "pods" | "po")
+}
+
+/// Resource kinds `kubectl scale` stops
+fn is_workload(kind: &str) -> bool {
+    matches!(
+        kind,
+        "deployment"
+            | "deployments"
+            | "deploy"
+            | "statefulset"
+            | "statefulsets"
+            | "sts"
+            | "replicaset"
+            | "replicasets"
+            | "rs"
+    )
+}
+
+/// The host part of an ssh destination (`user@host`, `ssh://user@host:22`)
+fn ssh_host(destination: &str) -> &str {
+    let rest = destination.strip_prefix("ssh://").unwrap_or(destination);
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+    if destination.starts_with("ssh://") {
+        rest.rsplit_once(':').map_or(rest, |(host, _)| host)
+    } else {
+        rest
+    }
+}
+
+/// Split `[bind:]port:host:hostport`, keeping bracketed IPv6 addresses whole
+fn split_forward(spec: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_brackets = false;
+    for (i, c) in spec.char_indices() {
+        match c {
+            '[' => in_brackets = true,
+            ']' => in_brackets = false,
+            ':' if !in_brackets => {
+                parts.push(&spec[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&spec[start..]);
+    parts
+}
+
+fn parse_ssh(pid: i32, args: &[String]) -> Vec<Tunnel> {
+    // ssh options that take a value
+    const WITH_VALUE: &str = "BbcDEeFIiJLlmOoPpQRSWw";
+    // Options not worth repeating when running a command on the destination
+    const TUNNEL_ONLY: &str = "LRDNfTgW";
+
+    let mut forwards = Vec::new();
+    let mut options = Vec::new();
+    let mut destination = None;
+    let mut i = 1;
+    while i < args.len() {
+        let arg = &args[i];
+        if destination.is_some() {
+            break; // the remote command follows
+        }
+        if let Some(flags) = arg.strip_prefix('-').filter(|f| !f.is_empty()) {
+            // Flags may be combined (-fNL 3000:localhost:3000)
+            for (pos, flag) in flags.char_indices() {
+                if WITH_VALUE.contains(flag) {
+                    let inline = &flags[pos + flag.len_utf8()..];
+                    let value = if inline.is_empty() {
+                        i += 1;
+                        args.get(i).cloned().unwrap_or_default()
+                    } else {
+                        inline.to_string()
+                    };
+                    if flag == 'L' {
+                        forwards.push(value);
+                    } else if !TUNNEL_ONLY.contains(flag) {
+                        options.push(format!("-{}", flag));
+                        options.push(value);
+                    }
+                    break;
+                } else if !TUNNEL_ONLY.contains(flag) {
+                    options.push(format!("-{}", flag));
+                }
+            }
+        } else {
+            destination = Some(arg.clone());
+        }
+        i += 1;
+    }
+    let Some(destination) = destination else {
+        return Vec::new();
+    };
+
+    forwards
+        .iter()
+        .filter_map(|spec| {
+            let parts = split_forward(spec);
+            let (local, host, port) = match parts.as_slice() {
+                [local, host, port] | [_, local, host, port] => (local, host, port),
+                _ => return None, // Unix socket forwards
+            };
+            Some(Tunnel {
+                pid,
+                local_port: local.parse().ok()?,
+                target_port: port.parse().ok()?,
+                endpoint: Endpoint::Ssh {
+                    destination: destination.clone(),
+                    options: options.clone(),
+                    target_host: host.trim_matches(|c| c == '[' || c == ']').to_string(),
+                },
+            })
+        })
+        .collect()
+}
+
+fn parse_kubectl(pid: i32, args: &[String]) -> Vec<Tunnel> {
+    let Some(start) = args.iter().position(|a| a == "port-forward") else {
+        return Vec::new();
+    };
+    let mut flags = Vec::new();
+    let mut positional = Vec::new();
+    let mut i = 1;
+    while i < args.len() {
+        let arg = &args[i];
+        if i == start {
+            i += 1;
+            continue;
+        }
+        if let Some((name, value)) = arg.split_once('=').filter(|(n, _)| n.starts_with("--")) {
+            if matches!(name, "--namespace" | "--context" | "--kubeconfig") {
+                flags.push(format!("{}={}", name, value));
+            }
+        } else if matches!(arg.as_str(), "-n" | "--namespace" | "--context" | "--kubeconfig") {
+            if let Some(value) = args.get(i + 1) {
+                flags.push(arg.clone());
+                flags.push(value.clone());
+            }
+            i += 1;
+        } else if matches!(arg.as_str(), "--address" | "--pod-running-timeout") {
+            i += 1;
+        } else if !arg.starts_with('-') && i > start {
+            positional.push(arg.clone());
+        }
+        i += 1;
+    }
+    let Some((resource, ports)) = positional.split_first() else {
+        return Vec::new();
+    };
+    ports
+        .iter()
+        .filter_map(|spec| {
+            let (local, remote) = spec.split_once(':').unwrap_or((spec, spec));
+            let target_port: u16 = remote.parse().ok()?;
+            Some(Tunnel {
+                pid,
+                // `:80` picks a random local port, which only the listener scan knows
+                local_port: if local.is_empty() { 0 } else { local.parse().ok()? },
+                target_port,
+                endpoint: Endpoint::Kubernetes {
+                    resource: resource.clone(),
+                    flags: flags.clone(),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Tunnels set up by the process started with `args`
+pub fn parse(pid: i32, args: &[String]) -> Vec<Tunnel> {
+    let program = args
+        .first()
+        .map(|p| p.rsplit(['/', '\\']).next().unwrap_or(p).trim_end_matches(".exe"))
+        .unwrap_or_default();
+    match program {
+        "ssh" => parse_ssh(pid, args),
+        "kubectl" | "oc" => parse_kubectl(pid, args),
+        _ => Vec::new(),
+    }
+}
+
+/// The tunnel behind a listener, if it is one
+pub fn tunnel_for(process: &ProcessInfo) -> Option<Tunnel> {
+    let args = match &process.command_line {
+        Some(command_line) => parse_command_line(command_line),
+        None => crate::system_monitor::process_command_line(process.pid)?,
+    };
+    let tunnels = parse(process.pid, &args);
+    let kubectl_random_port = tunnels.len() == 1 && tunnels[0].local_port == 0;
+    tunnels
+        .into_iter()
+        .find(|t| t.local_port == process.port || kubectl_random_port)
+        .map(|mut t| {
+            t.local_port = process.port;
+            t
+        })
+}
+
+/// Every tunnel a process sets up, for kills by PID
+pub fn tunnels_of_pid(pid: i32) -> Vec<Tunnel> {
+    crate::system_monitor::process_command_line(pid)
+        .map(|args| parse(pid, &args))
+        .unwrap_or_default()
+}
+
+/// Local ssh tunnels forwarding to `port` on `remote` itself
+pub fn local_tunnels_to(remote: &str, port: u16) -> Vec<Tunnel> {
+    crate::system_monitor::command_lines_of(&["ssh"])
+        .into_iter()
+        .flat_map(|(pid, args)| parse(pid, &args))
+        .filter(|tunnel| {
+            tunnel.target_port == port
+                && matches!(&tunnel.endpoint, Endpoint::Ssh { destination, target_host, .. }
+                    if ssh_host(destination) == ssh_host(remote) && is_loopback(target_host))
+        })
+        .collect()
+}
+
+impl Tunnel {
+    /// e.g. "ssh dev@box → localhost:5432" or "kubectl deployment/api → port 80"
+    pub fn describe(&self) -> String {
+        match &self.endpoint {
+            Endpoint::Ssh {
+                destination,
+                target_host,
+                ..
+            } => format!("ssh {} → {}:{}", destination, target_host, self.target_port),
+            Endpoint::Kubernetes { resource, flags } => {
+                let mut text = format!("kubectl {} → port {}", resource, self.target_port);
+                if !flags.is_empty() {
+                    text.push_str(&format!(" ({})", flags.join(" ")));
+                }
+                text
+            }
+        }
+    }
+
+    /// The command that stops the remote endpoint
+    pub fn stop_command(&self) -> Result<Vec<String>> {
+        match &self.endpoint {
+            Endpoint::Ssh {
+                destination,
+                options,
+                target_host,
+            } => {
+                if !is_loopback(target_host) {
+                    anyhow::bail!(
+                        "{}:{} is only reached through {}; stop it on {} itself",
+                        target_host,
+                        self.target_port,
+                        destination,
+                        target_host
+                    );
+                }
+                let port = self.target_port;
+                let script = format!(
+                    "if command -v lsof >/dev/null 2>&1; then pids=$(lsof -t -iTCP:{port} -sTCP:LISTEN); \
+                     else pids=$(fuser {port}/tcp 2>/dev/null); fi; \
+                     [ -n \"$pids\" ] || {{ echo \"nothing is listening on port {port}\" >&2; exit 3; }}; \
+                     kill $pids && echo $pids"
+                );
+                let mut command = vec!["ssh".to_string(), "-o".to_string(), "BatchMode=yes".to_string()];
+                command.extend(options.iter().cloned());
+                command.push(destination.clone());
+                command.push(script);
+                Ok(command)
+            }
+            Endpoint::Kubernetes { resource, flags } => {
+                // Without --context the forward used whatever context was current when it
+                // started, which may not be the current one now
+                if !flags
+                    .iter()
+                    .any(|f| f == "--context" || f.starts_with("--context="))
+                {
+                    anyhow::bail!(
+                        "the port-forward to {} has no --context, so port-kill can't tell which \
+                         cluster it reached; restart it with --context to use --linked",
+                        resource
+                    );
+                }
+                let (kind, name) = resource.split_once('/').unwrap_or(("pod", resource));
+                let mut command = vec!["kubectl".to_string()];
+                command.extend(flags.iter().cloned());
+                if is_pod(kind) {
+                    command.extend(["delete".into(), "pod".into(), name.to_string(), "--wait=false".into()]);
+                } else if is_workload(kind) {
+                    command.extend(["scale".into(), resource.clone(), "--replicas=0".into()]);
+                } else {
+                    anyhow::bail!(
+                        "{} can't be stopped directly; stop the workload behind it",
+                        resource
+                    );
+                }
+                Ok(command)
+            }
+        }
+    }
+
+    /// The workload stopping the remote end would scale to zero, taking down every replica
+    /// rather than the one pod the tunnel reached
+    pub fn scaled_workload(&self) -> Option<&str> {
+        match &self.endpoint {
+            Endpoint::Kubernetes { resource, .. } => resource
+                .split_once('/')
+                .filter(|(kind, _)| is_workload(kind))
+                .map(|_| resource.as_str()),
+            Endpoint::Ssh { .. } => None,
+        }
+    }
+
+    /// Stop the process the tunnel forwards to
+    pub fn stop_remote(&self) -> Result<()> {
+        crate::offline::ensure_allowed(crate::offline::Channel::Tunnel)?;
+        let command = self.stop_command()?;
+        let output = Command::new(&command[0])
+            .args(&command[1..])
+            .output()
+            .with_context(|| format!("Could not run {}", command[0]))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "{} failed: {}",
+                command[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command_line: &str) -> Vec<String> {
+        parse_command_line(command_line)
+    }
+
+    #[test]
+    fn test_parse_ssh_forwards() {
+        let tunnels = parse(
+            7,
+            &args("/usr/bin/ssh -fN -p 2222 -L 5432:localhost:5432 -L127.0.0.1:8080:db.internal:80 dev@box"),
+        );
+        assert_eq!(tunnels.len(), 2);
+        assert_eq!(tunnels[0].local_port, 5432);
+        assert_eq!(tunnels[1].local_port, 8080);
+        assert_eq!(tunnels[1].target_port, 80);
+        assert_eq!(
+            tunnels[0].stop_command().unwrap()[..5],
+            ["ssh", "-o", "BatchMode=yes", "-p", "2222"]
+        );
+        assert_eq!(tunnels[0].stop_command().unwrap()[5], "dev@box");
+        // The endpoint lives beyond the ssh server
+        assert!(tunnels[1].stop_command().is_err());
+        assert_eq!(ssh_host("ssh://dev@box:2222"), "box");
+        assert!(parse(7, &args("ssh dev@box")).is_empty());
+    }
+
+    #[test]
+    fn test_parse_kubectl_port_forward() {
+        let tunnels = parse(
+            9,
+            &args("kubectl -n shop --context prod port-forward deployment/api 8080:80 9090"),
+        );
+        assert_eq!(tunnels.len(), 2);
+        assert_eq!(tunnels[1].local_port, 9090);
+        assert_eq!(
+            tunnels[0].stop_command().unwrap(),
+            [
+                "kubectl",
+                "-n",
+                "shop",
+                "--context",
+                "prod",
+                "scale",
+                "deployment/api",
+                "--replicas=0"
+            ]
+        );
+        assert_eq!(tunnels[0].scaled_workload(), Some("deployment/api"));
+        // The context current now may not be the one the forward started in
+        let implicit = parse(9, &args("kubectl -n shop port-forward deployment/api 8080:80"));
+        assert!(implicit[0].stop_command().is_err());
+
+        let pod = parse(9, &args("kubectl port-forward --context=dev api-5d9f :80"));
+        assert_eq!(pod[0].local_port, 0);
+        assert_eq!(
+            pod[0].stop_command().unwrap(),
+            ["kubectl", "--context=dev", "delete", "pod", "api-5d9f", "--wait=false"]
+        );
+        assert_eq!(pod[0].scaled_workload(), None);
+        assert!(parse(9, &args("kubectl port-forward --context=dev svc/api 80"))
+            .first()
+            .unwrap()
+            .stop_command()
+            .is_err());
+    }
+}
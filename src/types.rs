@@ -1,10 +1,16 @@
+use crate::audit_log::AuditInitiator;
+use crate::history_store::{HistoryFilter, HistoryStore};
+use crate::state_format::{StateMeta, Versioned};
 use chrono::{DateTime, Datelike, Timelike, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// A process listening on a monitored port
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct ProcessInfo {
     pub pid: i32,
     pub port: u16,
@@ -19,11 +25,26 @@ pub struct ProcessInfo {
     pub cpu_usage: Option<f64>,        // NEW: CPU usage percentage
     pub memory_usage: Option<u64>,     // NEW: Memory usage in bytes
     pub memory_percentage: Option<f64>, // NEW: Memory usage percentage
+    #[serde(default)]
+    pub wsl_distro: Option<String>, // WSL distro the process lives in (Windows only)
+    #[serde(default)]
+    pub user: Option<String>, // Owning user name
+    #[serde(default)]
+    pub uid: Option<u32>, // Owning user ID (Unix only)
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>, // When the process started
+    #[serde(default)]
+    pub uptime_secs: Option<u64>, // Seconds the process had been running at scan time
+    #[serde(default)]
+    pub ppid: Option<i32>, // Parent process ID
+    #[serde(default)]
+    pub gpu_memory: Option<u64>, // GPU memory held by the process in bytes (NVIDIA only)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ProcessInfo;
+    use super::{PortEvent, ProcessInfo};
+    use std::collections::HashMap;
 
     fn process_with_dir(dir: &str) -> ProcessInfo {
         ProcessInfo {
@@ -40,6 +61,13 @@ mod tests {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
         }
     }
 
@@ -70,12 +98,245 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_owned_by() {
+        let mut process = process_with_dir("/tmp");
+        assert!(!process.is_owned_by("alice"));
+
+        process.user = Some("CORP\\Alice".to_string());
+        assert!(process.is_owned_by("alice"));
+        assert!(!process.is_owned_by("bob"));
+
+        process.user = Some("verylong+".to_string());
+        assert!(process.is_owned_by("verylongusername"));
+
+        assert_eq!(process.describe_owner().as_deref(), Some("verylong+"));
+        process.uid = Some(501);
+        assert_eq!(
+            process.describe_owner().as_deref(),
+            Some("verylong+ (uid 501)")
+        );
+        process.user = None;
+        assert_eq!(process.describe_owner().as_deref(), Some("uid 501"));
+    }
+
+    #[test]
+    fn test_set_origin_and_format_uptime() {
+        use super::format_uptime;
+        use chrono::{DateTime, Utc};
+
+        let mut process = process_with_dir("/tmp");
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000 + 3_900, 0).unwrap();
+        process.set_origin(1_700_000_000, Some(42), now);
+
+        assert_eq!(process.uptime_secs, Some(3_900));
+        assert_eq!(process.ppid, Some(42));
+        assert_eq!(format_uptime(3_900), "1h 5m");
+        assert_eq!(format_uptime(59), "59s");
+        assert_eq!(format_uptime(2 * 86_400 + 4 * 3_600), "2d 4h");
+    }
+
+    #[test]
+    fn test_find_duplicate_instances_keeps_newest() {
+        use super::find_duplicate_instances;
+        use chrono::DateTime;
+        use std::collections::HashMap;
+
+        let now = DateTime::from_timestamp(1_700_010_000, 0).unwrap();
+        let mut processes = HashMap::new();
+        for (pid, port, started, ppid) in [
+            (100, 3000, 1_700_000_000, None),
+            (200, 3001, 1_700_005_000, None),
+            // A worker of the newer instance, not a separate instance
+            (201, 24678, 1_700_005_001, Some(200)),
+        ] {
+            let mut process = process_with_dir("/work/web");
+            process.pid = pid;
+            process.port = port;
+            process.command_line = Some("npm run dev".to_string());
+            process.set_origin(started, ppid, now);
+            processes.insert(port, process);
+        }
+        // Same directory, different command: not a duplicate
+        let mut api = process_with_dir("/work/web");
+        api.pid = 300;
+        api.port = 8080;
+        api.command_line = Some("python api.py".to_string());
+        processes.insert(8080, api);
+
+        let duplicates = find_duplicate_instances(&processes);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].newest().pid, 200);
+        let older: Vec<i32> = duplicates[0].older().iter().map(|p| p.pid).collect();
+        assert_eq!(older, vec![100]);
+        assert_eq!(duplicates[0].ports, vec![3000, 3001, 24678]);
+    }
+
+    #[test]
+    fn test_port_heat_report() {
+        use super::{ProcessHistory, ProcessHistoryEntry};
+
+        let mut history = ProcessHistory::new(100);
+        for port in [3000, 3000, 3000, 3001, 8080] {
+            let mut process = process_with_dir("/tmp");
+            process.port = port;
+            history.add_entry(ProcessHistoryEntry::new(&process, "user".to_string()));
+        }
+
+        let report = history.get_port_heat_report(&[3000, 3001, 3002], Some("react".to_string()));
+        assert_eq!(report.total_kills, 4);
+        assert_eq!(report.kills_outside_ports, 1);
+        assert_eq!(report.port_kills, vec![(3000, 3), (3001, 1)]);
+        assert_eq!(report.cold_ports, vec![3002]);
+        assert_eq!(report.offenders.len(), 1);
+        assert_eq!(report.offenders[0].port, 3000);
+    }
+
+    #[test]
+    fn test_terminal_title_sequence() {
+        let mut status = super::StatusBarInfo::from_process_count(2);
+        status.tooltip.push_str("\x07\nrogue");
+        assert_eq!(
+            status.terminal_title_sequence(),
+            "\x1b]0;Port Kill: 2 - 2 development process(es) runningrogue\x07"
+        );
+    }
+
+    #[test]
+    fn test_set_top_consumers() {
+        let mut processes = HashMap::new();
+        for (port, cpu, memory) in [(3000, 2.0, 10), (3001, 40.0, 5), (3002, 2.0, 900), (3003, 0.0, 1)] {
+            let mut process = process_with_dir("/tmp");
+            process.port = port;
+            process.name = format!("app{}", port);
+            process.cpu_usage = Some(cpu);
+            process.memory_usage = Some(memory * 1024 * 1024);
+            processes.insert(port, process);
+        }
+        let mut unsampled = process_with_dir("/tmp");
+        unsampled.port = 3004;
+        processes.insert(3004, unsampled);
+
+        let mut status = super::StatusBarInfo::from_process_count(processes.len());
+        status.set_top_consumers(&processes, &crate::system_monitor::ResourceSampler::new());
+        assert_eq!(status.details.len(), 4);
+        assert!(status.details[0].starts_with("app3001 :3001  CPU 40.0%"));
+        assert!(status.details[1].starts_with("app3002 :3002"));
+        assert!(status.details[1].ends_with("RAM 900.00 MB"));
+        assert!(status.tooltip.contains("\nTop CPU/memory:\napp3001"));
+    }
+
+    #[test]
+    fn test_to_csv_row_quotes_fields() {
+        use chrono::{DateTime, Utc};
+
+        let mut process = process_with_dir("/tmp");
+        process.command = "node server.js --name \"a,b\"".to_string();
+        process.project_name = Some("web".to_string());
+        let timestamp = DateTime::<Utc>::from_timestamp(0, 0).unwrap();
+
+        assert_eq!(
+            process.to_csv_row(&timestamp),
+            "1970-01-01T00:00:00Z,3000,1,test,\"node server.js --name \"\"a,b\"\"\",,web,,,,,,"
+        );
+        assert_eq!(
+            process.to_csv_row(&timestamp).split(',').count(),
+            ProcessInfo::CSV_HEADER.split(',').count() + 1 // the quoted comma
+        );
+    }
+
+    #[test]
+    fn test_history_statistics_rows() {
+        use super::{ProcessHistory, ProcessHistoryEntry};
+
+        let mut history = ProcessHistory::new(100);
+        for port in [3000, 3000, 8080] {
+            let mut process = process_with_dir("/tmp");
+            process.port = port;
+            history.add_entry(ProcessHistoryEntry::new(&process, "user".to_string()));
+        }
+        // Renices are history, but not kills
+        history.add_entry(ProcessHistoryEntry::reniced(&process_with_dir("/tmp"), 10, Some(10)));
+        assert_eq!(history.len(), 4);
+
+        let rows = history.get_statistics().rows();
+        assert_eq!(rows[0].to_csv_row(), "total_kills,,3");
+        assert!(rows.iter().any(|r| r.to_csv_row() == "top_port,3000,2"));
+        assert_eq!(
+            serde_json::to_string(&rows[0]).unwrap(),
+            r#"{"metric":"total_kills","value":3}"#
+        );
+    }
+
+    #[test]
+    fn test_automated_actions_in_root_cause() {
+        use super::{HistoryAction, PatternType, ProcessHistory, ProcessHistoryEntry};
+        use crate::audit_log::AuditInitiator;
+
+        let mut history = ProcessHistory::new(100);
+        let mut process = process_with_dir("/tmp");
+        process.port = 3000;
+        for _ in 0..2 {
+            history.add_entry(
+                ProcessHistoryEntry::new(&process, "auto".to_string())
+                    .initiated_by(AuditInitiator::Guard),
+            );
+        }
+        let mut entry = ProcessHistoryEntry::new(&process, "user".to_string());
+        entry.hostname = Some("devbox".to_string());
+        assert!(entry.describe_initiator().starts_with("user ("));
+        assert!(entry.describe_initiator().ends_with(" on devbox)"));
+        history.add_entry(entry);
+        for _ in 0..3 {
+            let mut restart = ProcessHistoryEntry::new(&process, "restart".to_string());
+            restart.action = HistoryAction::Restart;
+            history.add_entry(restart);
+        }
+
+        // Restarts aren't kills
+        assert_eq!(history.get_statistics().total_kills, 3);
+        let patterns = history.get_root_cause_analysis().patterns;
+        let automated: Vec<_> = patterns
+            .iter()
+            .filter(|p| matches!(p.pattern_type, PatternType::Automated))
+            .collect();
+        assert_eq!(automated.len(), 1);
+        assert!(automated[0].description.contains("2 times"));
+        assert!(patterns
+            .iter()
+            .any(|p| p.description == "Port 3000 was restarted 3 times"));
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     fn test_extract_project_name_ignores_windows_usernames() {
         let process = process_with_dir(r"C:\Users\Alice");
         assert_eq!(process.extract_project_name(), None);
     }
+
+    #[test]
+    fn test_port_events_between_scans() {
+        let on = |port: u16, pid: i32| ProcessInfo {
+            port,
+            pid,
+            ..process_with_dir("/tmp")
+        };
+        let previous = HashMap::from([(3000, on(3000, 1)), (5173, on(5173, 2)), (8000, on(8000, 3))]);
+        let current = HashMap::from([(3000, on(3000, 1)), (5173, on(5173, 9)), (9000, on(9000, 4))]);
+        let events: Vec<(&str, u16)> = PortEvent::between(&previous, &current)
+            .iter()
+            .map(|change| match change {
+                PortEvent::Appeared { process } => (change.name(), process.port),
+                PortEvent::Disappeared { port, .. } => (change.name(), *port),
+                PortEvent::Killed { .. } => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [("disappeared", 5173), ("disappeared", 8000), ("appeared", 5173), ("appeared", 9000)]
+        );
+        assert!(PortEvent::between(&current, &current).is_empty());
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -98,10 +359,67 @@ impl ProcessUpdate {
     }
 }
 
+/// A change between two scans; `--serve` streams these on `/events`
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PortEvent {
+    /// A listener that wasn't there in the previous scan
+    Appeared { process: Box<ProcessInfo> },
+    /// A listener from the previous scan that is gone
+    Disappeared { port: u16, pid: i32, name: String },
+    /// A kill made through the API
+    Killed { result: KillResult },
+}
+
+impl PortEvent {
+    /// What went away, then what appeared, each by port
+    pub fn between(
+        previous: &HashMap<u16, ProcessInfo>,
+        current: &HashMap<u16, ProcessInfo>,
+    ) -> Vec<PortEvent> {
+        let same = |a: &ProcessInfo, b: &ProcessInfo| a.pid == b.pid;
+        let mut gone: Vec<&ProcessInfo> = previous
+            .values()
+            .filter(|old| current.get(&old.port).is_none_or(|new| !same(old, new)))
+            .collect();
+        let mut new: Vec<&ProcessInfo> = current
+            .values()
+            .filter(|new| previous.get(&new.port).is_none_or(|old| !same(old, new)))
+            .collect();
+        gone.sort_by_key(|p| p.port);
+        new.sort_by_key(|p| p.port);
+
+        gone.into_iter()
+            .map(|p| PortEvent::Disappeared {
+                port: p.port,
+                pid: p.pid,
+                name: p.name.clone(),
+            })
+            .chain(new.into_iter().map(|p| PortEvent::Appeared {
+                process: Box::new(p.clone()),
+            }))
+            .collect()
+    }
+
+    /// The `event` tag, also used as the SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            PortEvent::Appeared { .. } => "appeared",
+            PortEvent::Disappeared { .. } => "disappeared",
+            PortEvent::Killed { .. } => "killed",
+        }
+    }
+}
+
+/// Processes listed in the tray's status detail view
+pub const TOP_CONSUMERS: usize = 5;
+
 #[derive(Debug, Clone)]
 pub struct StatusBarInfo {
     pub text: String,
     pub tooltip: String,
+    /// The status detail view: the top CPU/memory consumers, one line each
+    pub details: Vec<String>,
 }
 
 impl StatusBarInfo {
@@ -114,7 +432,11 @@ impl StatusBarInfo {
             format!("{} development process(es) running", count)
         };
 
-        Self { text, tooltip }
+        Self {
+            text,
+            tooltip,
+            details: Vec::new(),
+        }
     }
 
     pub fn from_processes_with_status(
@@ -126,6 +448,7 @@ impl StatusBarInfo {
             return Self {
                 text: "0".to_string(),
                 tooltip: "No development processes running".to_string(),
+                details: Vec::new(),
             };
         }
 
@@ -203,13 +526,177 @@ impl StatusBarInfo {
             tooltip_parts.push(format!("{} Docker containers", docker_count));
         }
 
-        let tooltip = tooltip_parts.join(" | ");
+        let mut info = Self {
+            text,
+            tooltip: tooltip_parts.join(" | "),
+            details: Vec::new(),
+        };
+        info.append_longest_running(processes);
+        info
+    }
 
-        Self { text, tooltip }
+    /// Add the longest-running process to the tooltip so stale servers stand out
+    pub fn append_longest_running(
+        &mut self,
+        processes: &std::collections::HashMap<u16, ProcessInfo>,
+    ) {
+        let oldest = processes
+            .values()
+            .filter_map(|p| p.uptime_secs.map(|secs| (p, secs)))
+            .max_by_key(|(_, secs)| *secs);
+        if let Some((process_info, secs)) = oldest {
+            self.tooltip.push_str(&format!(
+                " | Longest running: {} :{} (up {})",
+                process_info.get_short_name(),
+                process_info.port,
+                format_uptime(secs)
+            ));
+        }
+    }
+
+    /// Fill the status detail view with the [`TOP_CONSUMERS`] processes using the most CPU
+    /// (then memory), with a CPU sparkline each, and add them to the tooltip
+    pub fn set_top_consumers(
+        &mut self,
+        processes: &HashMap<u16, ProcessInfo>,
+        sampler: &crate::system_monitor::ResourceSampler,
+    ) {
+        let mut sampled: Vec<&ProcessInfo> = processes
+            .values()
+            .filter(|p| p.cpu_usage.is_some() || p.memory_usage.is_some())
+            .collect();
+        sampled.sort_by(|a, b| {
+            b.cpu_usage
+                .unwrap_or(0.0)
+                .total_cmp(&a.cpu_usage.unwrap_or(0.0))
+                .then(b.memory_usage.cmp(&a.memory_usage))
+                .then(a.port.cmp(&b.port))
+        });
+        self.details = sampled
+            .into_iter()
+            .take(TOP_CONSUMERS)
+            .map(|p| {
+                format!(
+                    "{} :{}  CPU {:.1}% {}  RAM {}",
+                    p.get_short_name(),
+                    p.port,
+                    p.cpu_usage.unwrap_or(0.0),
+                    sampler.cpu_sparkline(p.pid),
+                    p.memory_usage
+                        .map(crate::cache::output::human_size)
+                        .unwrap_or_else(|| "?".to_string())
+                )
+            })
+            .collect();
+        if !self.details.is_empty() {
+            self.tooltip.push_str("\nTop CPU/memory:\n");
+            self.tooltip.push_str(&self.details.join("\n"));
+        }
+    }
+
+    /// OSC 0 escape sequence that sets the terminal window/tab title to this status
+    pub fn terminal_title_sequence(&self) -> String {
+        let title: String = format!("Port Kill: {} - {}", self.text, self.tooltip)
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect();
+        format!("\x1b]0;{}\x07", title)
+    }
+
+    /// Surface a failed or incomplete scan so an empty list isn't mistaken for "nothing running"
+    pub fn apply_scan_health(&mut self, health: &crate::scan_health::ScanHealth) {
+        if let Some(message) = health.describe() {
+            if self.text == "0" {
+                self.tooltip = message;
+            } else {
+                self.tooltip.push_str(&format!(" | {}", message));
+            }
+        }
+    }
+}
+
+/// One scan in machine-readable form (`--output json`)
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ScanSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub count: usize,
+    pub scan_status: crate::scan_health::ScanStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+    pub processes: Vec<ProcessInfo>,
+}
+
+impl ScanSnapshot {
+    /// Snapshot of a scan, ordered by port
+    pub fn new(
+        processes: &HashMap<u16, ProcessInfo>,
+        health: crate::scan_health::ScanHealth,
+    ) -> Self {
+        let mut processes: Vec<ProcessInfo> = processes.values().cloned().collect();
+        processes.sort_by_key(|p| (p.port, p.pid));
+        Self {
+            timestamp: Utc::now(),
+            count: processes.len(),
+            scan_status: health.status,
+            reasons: health.reasons,
+            processes,
+        }
+    }
+
+    /// One JSON object per process with the scan timestamp folded in (`--output ndjson`)
+    pub fn ndjson_lines(&self) -> serde_json::Result<Vec<String>> {
+        self.processes
+            .iter()
+            .map(|process_info| {
+                let mut value = serde_json::to_value(process_info)?;
+                if let serde_json::Value::Object(ref mut map) = value {
+                    map.insert(
+                        "timestamp".to_string(),
+                        serde_json::to_value(self.timestamp)?,
+                    );
+                }
+                serde_json::to_string(&value)
+            })
+            .collect()
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or newline
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
     }
 }
 
 impl ProcessInfo {
+    pub const CSV_HEADER: &'static str =
+        "timestamp,port,pid,name,command,user,project,group,container,cpu_percent,memory_bytes,uptime_secs,uid";
+
+    /// One CSV row matching `CSV_HEADER`
+    pub fn to_csv_row(&self, timestamp: &DateTime<Utc>) -> String {
+        let optional = |value: &Option<String>| csv_field(value.as_deref().unwrap_or(""));
+        [
+            timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            self.port.to_string(),
+            self.pid.to_string(),
+            csv_field(&self.name),
+            csv_field(&self.command),
+            optional(&self.user),
+            optional(&self.project_name),
+            optional(&self.process_group),
+            optional(&self.container_name),
+            self.cpu_usage
+                .map(|c| format!("{:.1}", c))
+                .unwrap_or_default(),
+            self.memory_usage.map(|m| m.to_string()).unwrap_or_default(),
+            self.uptime_secs.map(|u| u.to_string()).unwrap_or_default(),
+            self.uid.map(|u| u.to_string()).unwrap_or_default(),
+        ]
+        .join(",")
+    }
+
     /// Determine the process group based on the command and name
     pub fn determine_process_group(&self) -> Option<String> {
         let name_lower = self.name.to_lowercase();
@@ -349,6 +836,33 @@ impl ProcessInfo {
         display_parts.join(" ")
     }
 
+    /// Owner for display, e.g. `alice (uid 501)`
+    pub fn describe_owner(&self) -> Option<String> {
+        match (&self.user, self.uid) {
+            (Some(user), Some(uid)) => Some(format!("{} (uid {})", user, uid)),
+            (Some(user), None) => Some(user.clone()),
+            (None, Some(uid)) => Some(format!("uid {}", uid)),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether this process belongs to `user`. Unknown owners match nobody.
+    pub fn is_owned_by(&self, user: &str) -> bool {
+        self.user
+            .as_deref()
+            .map(|owner| same_user(owner, user))
+            .unwrap_or(false)
+    }
+
+    /// Record start time (Unix seconds) and parent PID, deriving uptime relative to `now`
+    pub fn set_origin(&mut self, start_time: u64, ppid: Option<i32>, now: DateTime<Utc>) {
+        if let Some(started_at) = DateTime::from_timestamp(start_time as i64, 0) {
+            self.started_at = Some(started_at);
+            self.uptime_secs = Some((now - started_at).num_seconds().max(0) as u64);
+        }
+        self.ppid = ppid;
+    }
+
     /// Get a short, clean process name for status display
     pub fn get_short_name(&self) -> String {
         // Extract just the executable name without path
@@ -395,11 +909,182 @@ impl ProcessInfo {
             parts.push(format!("[Docker: {}]", container_name));
         }
 
+        if let Some(ref distro) = self.wsl_distro {
+            parts.push(format!("[WSL: {}]", distro));
+        }
+
         parts.join(" ")
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Compact uptime such as "45s", "12m", "3h 5m" or "2d 4h"
+pub fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Compare user names as reported by lsof/ps/tasklist. Ignores a Windows `DOMAIN\` prefix and
+/// case, and treats a trailing `+` (ps truncation of long names) as a prefix match.
+pub fn same_user(a: &str, b: &str) -> bool {
+    fn normalize(name: &str) -> String {
+        name.rsplit('\\')
+            .next()
+            .unwrap_or(name)
+            .trim()
+            .to_lowercase()
+    }
+    let (a, b) = (normalize(a), normalize(b));
+    if let Some(prefix) = a.strip_suffix('+') {
+        return b.starts_with(prefix);
+    }
+    if let Some(prefix) = b.strip_suffix('+') {
+        return a.starts_with(prefix);
+    }
+    a == b
+}
+
+/// Several processes started with the same command from the same directory, each bound to
+/// its own port - typically a second `npm run dev` left behind after a terminal mishap
+#[derive(Debug, Clone)]
+pub struct DuplicateInstances {
+    pub working_directory: String,
+    /// One entry per PID, newest first
+    pub instances: Vec<ProcessInfo>,
+    /// Every port held by the instances, sorted
+    pub ports: Vec<u16>,
+}
+
+impl DuplicateInstances {
+    pub fn newest(&self) -> &ProcessInfo {
+        &self.instances[0]
+    }
+
+    /// Instances that "keep newest, kill older" would kill
+    pub fn older(&self) -> &[ProcessInfo] {
+        &self.instances[1..]
+    }
+
+    pub fn describe(&self) -> String {
+        let newest = self.newest();
+        let command = newest
+            .command_line
+            .clone()
+            .unwrap_or_else(|| newest.get_short_name());
+        let ports: Vec<String> = self.ports.iter().map(|p| p.to_string()).collect();
+        format!(
+            "{} x{} in {} on ports {}",
+            command,
+            self.instances.len(),
+            self.working_directory,
+            ports.join(", ")
+        )
+    }
+}
+
+/// What instances of the same project have in common besides their working directory
+pub(crate) fn instance_command(process: &ProcessInfo) -> String {
+    process
+        .command_line
+        .clone()
+        .unwrap_or_else(|| process.name.clone())
+}
+
+/// Find projects running more than once. Processes are matched on working directory and
+/// command line (or name), so nothing is reported without a known working directory.
+/// Children of another instance in the group (worker processes sharing the parent's
+/// project) don't count as separate instances.
+pub fn find_duplicate_instances(processes: &HashMap<u16, ProcessInfo>) -> Vec<DuplicateInstances> {
+    let mut groups: HashMap<(String, String), Vec<&ProcessInfo>> = HashMap::new();
+    for process in processes.values() {
+        if let Some(ref dir) = process.working_directory {
+            groups
+                .entry((dir.clone(), instance_command(process)))
+                .or_default()
+                .push(process);
+        }
+    }
+
+    let mut duplicates: Vec<DuplicateInstances> = groups
+        .into_iter()
+        .filter_map(|((working_directory, _), members)| {
+            let pids: std::collections::HashSet<i32> = members.iter().map(|p| p.pid).collect();
+            let mut ports: Vec<u16> = members.iter().map(|p| p.port).collect();
+            ports.sort_unstable();
+
+            let mut instances: Vec<ProcessInfo> = Vec::new();
+            for process in members {
+                let is_child = process.ppid.is_some_and(|ppid| pids.contains(&ppid));
+                if !is_child && !instances.iter().any(|i| i.pid == process.pid) {
+                    instances.push(process.clone());
+                }
+            }
+            if instances.len() < 2 {
+                return None;
+            }
+            instances.sort_by_key(|p| std::cmp::Reverse((p.started_at, p.pid)));
+            Some(DuplicateInstances {
+                working_directory,
+                instances,
+                ports,
+            })
+        })
+        .collect();
+    duplicates.sort_by(|a, b| a.working_directory.cmp(&b.working_directory));
+    duplicates
+}
+
+/// `exited`: the process shut down during its pre-kill hooks or graceful HTTP shutdown, so
+/// it was never signalled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum KillStatus {
+    Killed,
+    Exited,
+    Failed,
+}
+
+/// Outcome of one kill; kill commands print one per process with `--json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct KillResult {
+    pub pid: i32,
+    pub port: Option<u16>,
+    pub name: Option<String>,
+    pub status: KillStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl KillResult {
+    pub fn new(
+        pid: i32,
+        port: Option<u16>,
+        name: Option<&str>,
+        result: &anyhow::Result<crate::hooks::KillOutcome>,
+    ) -> Self {
+        Self {
+            pid,
+            port,
+            name: name.map(str::to_string),
+            status: match result {
+                Ok(crate::hooks::KillOutcome::Killed) => KillStatus::Killed,
+                Ok(crate::hooks::KillOutcome::Exited) => KillStatus::Exited,
+                Err(_) => KillStatus::Failed,
+            },
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        }
+    }
+}
+
+/// One killed process, as kept in the history store (see [`crate::history_store`])
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProcessHistoryEntry {
     pub pid: i32,
     pub port: u16,
@@ -407,9 +1092,65 @@ pub struct ProcessHistoryEntry {
     pub process_group: Option<String>,
     pub project_name: Option<String>,
     pub killed_at: DateTime<Utc>,
+    #[serde(default = "default_killed_by")]
     pub killed_by: String, // "user", "bulk", "auto"
     pub command_line: Option<String>,
     pub working_directory: Option<String>,
+    /// Absent for kills
+    #[serde(default, skip_serializing_if = "HistoryAction::is_kill")]
+    pub action: HistoryAction,
+    /// The front end it came from (cli, tray, tui, script, api, guard); absent in entries
+    /// recorded before it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initiator: Option<AuditInitiator>,
+    /// The machine it happened on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(flatten)]
+    pub meta: StateMeta,
+}
+
+/// What a history entry records; everything but a kill leaves the process running
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HistoryAction {
+    #[default]
+    Kill,
+    /// `--nice` / "Deprioritize": priority shifted by `adjustment`, to `nice` where known
+    Renice { adjustment: i32, nice: Option<i32> },
+    /// The port's saved command was started again (`pid` is the new process)
+    Restart,
+}
+
+impl HistoryAction {
+    pub fn is_kill(&self) -> bool {
+        *self == HistoryAction::Kill
+    }
+
+    /// "kill", "renice +10"
+    pub fn describe(&self) -> String {
+        match self {
+            HistoryAction::Kill => "kill".to_string(),
+            HistoryAction::Renice { adjustment, .. } => {
+                format!("renice {}", crate::priority::format_adjustment(*adjustment))
+            }
+            HistoryAction::Restart => "restart".to_string(),
+        }
+    }
+}
+
+/// This machine's name, looked up once
+fn hostname() -> Option<String> {
+    static HOSTNAME: std::sync::OnceLock<Option<String>> = std::sync::OnceLock::new();
+    HOSTNAME.get_or_init(sysinfo::System::host_name).clone()
+}
+
+fn default_killed_by() -> String {
+    "user".to_string()
+}
+
+impl Versioned for ProcessHistoryEntry {
+    const KIND: &'static str = "history entry";
 }
 
 impl ProcessHistoryEntry {
@@ -424,6 +1165,72 @@ impl ProcessHistoryEntry {
             killed_by,
             command_line: process_info.command_line.clone(),
             working_directory: process_info.working_directory.clone(),
+            action: HistoryAction::Kill,
+            initiator: Some(crate::audit_log::initiator()),
+            hostname: hostname(),
+            meta: StateMeta::default(),
+        }
+    }
+
+    /// The same entry, attributed to `initiator` instead of this process's front end
+    pub fn initiated_by(self, initiator: AuditInitiator) -> Self {
+        Self {
+            initiator: Some(initiator),
+            ..self
+        }
+    }
+
+    /// A restart of `port`'s saved command, now running as `pid`
+    pub fn restarted(info: &crate::restart_manager::RestartInfo, pid: u32) -> Self {
+        let process_name = info
+            .command
+            .first()
+            .map(|program| {
+                std::path::Path::new(program)
+                    .file_name()
+                    .map_or(program.clone(), |name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+        Self {
+            pid: pid as i32,
+            port: info.port,
+            process_name,
+            process_group: None,
+            project_name: None,
+            killed_at: Utc::now(),
+            killed_by: "restart".to_string(),
+            command_line: Some(info.command.join(" ")),
+            working_directory: Some(info.working_directory.clone()),
+            action: HistoryAction::Restart,
+            initiator: Some(crate::audit_log::initiator()),
+            hostname: hostname(),
+            meta: StateMeta::default(),
+        }
+    }
+
+    /// "user", or with the initiator and host where they were recorded: "auto (guard on
+    /// build-box)"
+    pub fn describe_initiator(&self) -> String {
+        match (self.initiator_name(), &self.hostname) {
+            (Some(initiator), Some(host)) => {
+                format!("{} ({} on {})", self.killed_by, initiator, host)
+            }
+            (Some(initiator), None) => format!("{} ({})", self.killed_by, initiator),
+            (None, _) => self.killed_by.clone(),
+        }
+    }
+
+    /// "cli", "guard", ...
+    pub fn initiator_name(&self) -> Option<String> {
+        self.initiator
+            .map(|initiator| format!("{:?}", initiator).to_lowercase())
+    }
+
+    /// A renice of `process_info`; it is still running
+    pub fn reniced(process_info: &ProcessInfo, adjustment: i32, nice: Option<i32>) -> Self {
+        Self {
+            action: HistoryAction::Renice { adjustment, nice },
+            ..Self::new(process_info, "user".to_string())
         }
     }
 
@@ -440,6 +1247,30 @@ impl ProcessHistoryEntry {
             self.process_name.clone()
         }
     }
+
+    pub const CSV_HEADER: &'static str =
+        "killed_at,port,pid,process,group,project,killed_by,command_line,working_directory,action,initiator,hostname";
+
+    /// One CSV row matching `CSV_HEADER`
+    pub fn to_csv_row(&self) -> String {
+        let optional = |value: &Option<String>| csv_field(value.as_deref().unwrap_or(""));
+        [
+            self.killed_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            self.port.to_string(),
+            self.pid.to_string(),
+            csv_field(&self.process_name),
+            optional(&self.process_group),
+            optional(&self.project_name),
+            csv_field(&self.killed_by),
+            optional(&self.command_line),
+            optional(&self.working_directory),
+            csv_field(&self.action.describe()),
+            self.initiator_name().unwrap_or_default(),
+            optional(&self.hostname),
+        ]
+        .join(",")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -487,6 +1318,98 @@ pub struct HistoryStatistics {
     pub newest_kill: Option<DateTime<Utc>>,
 }
 
+/// One `metric,key,value` row of `--show-stats` for CSV/NDJSON export
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatisticRow {
+    pub metric: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub value: serde_json::Value,
+}
+
+impl StatisticRow {
+    pub const CSV_HEADER: &'static str = "metric,key,value";
+
+    fn new(metric: &'static str, key: Option<String>, value: impl Into<serde_json::Value>) -> Self {
+        Self {
+            metric,
+            key,
+            value: value.into(),
+        }
+    }
+
+    pub fn to_csv_row(&self) -> String {
+        let value = match self.value {
+            serde_json::Value::String(ref s) => s.clone(),
+            ref other => other.to_string(),
+        };
+        [
+            self.metric.to_string(),
+            csv_field(self.key.as_deref().unwrap_or("")),
+            csv_field(&value),
+        ]
+        .join(",")
+    }
+}
+
+impl HistoryStatistics {
+    /// Flatten the statistics into rows: totals first, then the top lists
+    pub fn rows(&self) -> Vec<StatisticRow> {
+        let timestamp = |t: &DateTime<Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let mut rows = vec![
+            StatisticRow::new("total_kills", None, self.total_kills),
+            StatisticRow::new("unique_processes", None, self.unique_processes),
+            StatisticRow::new("unique_ports", None, self.unique_ports),
+            StatisticRow::new("unique_projects", None, self.unique_projects),
+            StatisticRow::new(
+                "average_kills_per_day",
+                None,
+                (self.average_kills_per_day * 100.0).round() / 100.0,
+            ),
+        ];
+        if let Some(oldest) = &self.oldest_kill {
+            rows.push(StatisticRow::new("oldest_kill", None, timestamp(oldest)));
+        }
+        if let Some(newest) = &self.newest_kill {
+            rows.push(StatisticRow::new("newest_kill", None, timestamp(newest)));
+        }
+        for (name, count) in &self.top_processes {
+            rows.push(StatisticRow::new("top_process", Some(name.clone()), *count));
+        }
+        for (port, count) in &self.top_ports {
+            rows.push(StatisticRow::new(
+                "top_port",
+                Some(port.to_string()),
+                *count,
+            ));
+        }
+        for (project, count) in &self.top_projects {
+            rows.push(StatisticRow::new(
+                "top_project",
+                Some(project.clone()),
+                *count,
+            ));
+        }
+        rows
+    }
+}
+
+/// Kill history restricted to one set of ports (usually a preset's), used by --report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortHeatReport {
+    pub preset: Option<String>,
+    pub ports_watched: usize,
+    pub total_kills: usize,
+    /// Kills recorded on ports the preset does not cover
+    pub kills_outside_ports: usize,
+    /// (port, kills) for every watched port that saw at least one kill, hottest first
+    pub port_kills: Vec<(u16, usize)>,
+    /// Watched ports with no kills at all
+    pub cold_ports: Vec<u16>,
+    pub offenders: Vec<FrequentOffender>,
+    pub conflicts: Vec<ProcessConflict>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessConflict {
     pub port: u16,
@@ -531,6 +1454,8 @@ pub enum PatternType {
     ResourceIntensive,
     TimeBased,
     ProjectRelated,
+    /// The guard or port-kill itself acted, not the user
+    Automated,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -575,8 +1500,23 @@ pub struct PortReservation {
     pub project_name: String,
     pub process_name: String,
     pub reserved_at: DateTime<Utc>,
+    /// None for a reservation that doesn't expire
     pub expires_at: Option<DateTime<Utc>>,
+    /// Extended by another term when it expires while the guard runs
+    #[serde(default)]
     pub auto_renew: bool,
+    /// When the guard last extended it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renewed_at: Option<DateTime<Utc>>,
+    /// What the guard does about conflicts on the port, instead of its default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_conflict: Option<PortResolution>,
+    #[serde(flatten)]
+    pub meta: StateMeta,
+}
+
+impl Versioned for PortReservation {
+    const KIND: &'static str = "port reservation";
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -595,14 +1535,42 @@ pub enum PortConflictType {
     ResourceContention,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What the guard does about a conflict on a port: the policy of a reservation
+/// (`--on-conflict`), or of every port without one. JSON keeps the variant names; config
+/// files may use the short ones `--on-conflict` takes.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
 pub enum PortResolution {
+    /// Kill the intruder (for two allowed processes, the older one)
+    #[serde(alias = "kill")]
+    #[value(name = "kill")]
     KillExisting,
+    /// Notify with the nearest free port to move the intruder to
+    #[serde(alias = "reassign")]
+    #[value(name = "reassign")]
     ReassignPort,
+    /// Log the intruder without notifying, and refuse intercepted commands for the port
+    #[serde(alias = "block")]
+    #[value(name = "block")]
     BlockNewProcess,
+    /// Notify, kill nothing
+    #[serde(alias = "notify")]
+    #[value(name = "notify")]
     NotifyUser,
 }
 
+impl PortResolution {
+    pub fn label(self) -> &'static str {
+        match self {
+            PortResolution::KillExisting => "kill",
+            PortResolution::ReassignPort => "reassign",
+            PortResolution::BlockNewProcess => "block",
+            PortResolution::NotifyUser => "notify",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuardStatus {
     pub is_active: bool,
@@ -611,9 +1579,12 @@ pub struct GuardStatus {
     pub conflicts_resolved: usize,
     pub last_activity: Option<DateTime<Utc>>,
     pub auto_resolve_enabled: bool,
+    #[serde(default)]
+    pub rate_limited: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Result of `--audit`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SecurityAuditResult {
     pub audit_timestamp: DateTime<Utc>,
     pub total_ports_scanned: usize,
@@ -624,7 +1595,7 @@ pub struct SecurityAuditResult {
     pub baseline_comparison: Option<BaselineComparison>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SuspiciousProcess {
     pub port: u16,
     pub process_info: ProcessInfo,
@@ -636,7 +1607,7 @@ pub struct SuspiciousProcess {
     pub first_seen: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum SuspicionReason {
     SuspiciousPort,
     UnknownBinary,
@@ -646,7 +1617,7 @@ pub enum SuspicionReason {
     ProcessAnomaly,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -654,7 +1625,7 @@ pub enum RiskLevel {
     Critical,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ApprovedProcess {
     pub port: u16,
     pub process_info: ProcessInfo,
@@ -663,7 +1634,7 @@ pub struct ApprovedProcess {
     pub binary_hash: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ServiceType {
     WebServer,
     Database,
@@ -673,7 +1644,7 @@ pub enum ServiceType {
     Custom,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SecurityRecommendation {
     pub title: String,
     pub description: String,
@@ -682,7 +1653,7 @@ pub struct SecurityRecommendation {
     pub affected_processes: Vec<u16>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BaselineComparison {
     pub baseline_file: String,
     pub new_processes: Vec<ProcessInfo>,
@@ -690,7 +1661,7 @@ pub struct BaselineComparison {
     pub changed_processes: Vec<ProcessChange>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProcessChange {
     pub port: u16,
     pub old_process: ProcessInfo,
@@ -698,7 +1669,7 @@ pub struct ProcessChange {
     pub change_type: ProcessChangeType,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ProcessChangeType {
     BinaryChanged,
     LocationChanged,
@@ -706,10 +1677,16 @@ pub enum ProcessChangeType {
     UserChanged,
 }
 
+/// The kill history. `entries` holds the most recent `max_entries`; when the history was
+/// [loaded](Self::load) from the SQLite store, new entries are written to it as they are
+/// added and statistics, offenders and time patterns cover everything in it (or what
+/// [`filtered`](Self::filtered) narrowed it to).
 #[derive(Debug, Clone)]
 pub struct ProcessHistory {
     entries: Vec<ProcessHistoryEntry>,
     max_entries: usize,
+    store: Option<Arc<Mutex<HistoryStore>>>,
+    filter: HistoryFilter,
 }
 
 impl ProcessHistory {
@@ -717,10 +1694,77 @@ impl ProcessHistory {
         Self {
             entries: Vec::new(),
             max_entries,
+            store: None,
+            filter: HistoryFilter::default(),
+        }
+    }
+
+    /// The most recent `max_entries` of the history store, or of the JSON history file
+    /// when the store can't be opened
+    pub fn load(max_entries: usize) -> Self {
+        let store = match HistoryStore::open_default() {
+            Ok(store) => store,
+            Err(e) => {
+                log::warn!("Using the JSON history file: {:#}", e);
+                return Self::load_from_file(&Self::get_history_file_path(), max_entries)
+                    .unwrap_or_else(|_| Self::new(max_entries));
+            }
+        };
+        let entries = store.recent(max_entries).unwrap_or_else(|e| {
+            log::warn!("Failed to read {}: {:#}", store.path().display(), e);
+            Vec::new()
+        });
+        Self {
+            entries,
+            max_entries,
+            store: Some(Arc::new(Mutex::new(store))),
+            filter: HistoryFilter::default(),
+        }
+    }
+
+    /// The part of this history `filter` matches. Entries are read from the store when
+    /// there is one, and its statistics stay queries over everything that matches; without
+    /// a store, the entries in memory are filtered.
+    pub fn filtered(&self, filter: &HistoryFilter) -> ProcessHistory {
+        if filter.is_empty() {
+            return self.clone();
+        }
+        let limit = filter.limit.unwrap_or(self.max_entries);
+        let entries = self
+            .query(|store| store.matching(filter, limit))
+            .unwrap_or_else(|| filter.apply(&self.entries));
+        Self {
+            entries,
+            max_entries: self.max_entries,
+            store: self.store.clone(),
+            filter: filter.clone(),
+        }
+    }
+
+    /// Write the history where it was loaded from. The store already has every entry, so
+    /// this only writes the JSON file, for a history not backed by the store.
+    pub fn persist(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.store.is_some() {
+            return Ok(());
         }
+        self.save_to_file(&Self::get_history_file_path())
+    }
+
+    /// Run `query` against the store, or None for a history that isn't backed by one (or
+    /// when the query fails, which is logged)
+    fn query<T>(&self, query: impl FnOnce(&HistoryStore) -> anyhow::Result<T>) -> Option<T> {
+        let store = self.store.as_ref()?.lock().ok()?;
+        query(&store)
+            .map_err(|e| log::warn!("History query failed: {:#}", e))
+            .ok()
     }
 
     pub fn add_entry(&mut self, entry: ProcessHistoryEntry) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.lock().unwrap().insert(&entry) {
+                log::warn!("Failed to store history entry: {:#}", e);
+            }
+        }
         self.entries.push(entry);
 
         // Keep only the most recent entries
@@ -729,6 +1773,31 @@ impl ProcessHistory {
         }
     }
 
+    /// Trim to `max_entries` (a history file can hold more) and release spare capacity
+    pub fn compact(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.max_entries);
+        self.entries.drain(..excess);
+        self.entries.shrink_to_fit();
+    }
+
+    /// All entries, oldest first
+    pub fn get_entries(&self) -> &[ProcessHistoryEntry] {
+        &self.entries
+    }
+
+    /// Entries that killed their process; statistics and suggestions only count these
+    pub fn kills(&self) -> impl Iterator<Item = &ProcessHistoryEntry> {
+        self.entries.iter().filter(|entry| entry.action.is_kill())
+    }
+
+    /// The most recent kill that recorded a command and working directory, so it can be
+    /// started again
+    pub fn last_restartable_kill(&self) -> Option<&ProcessHistoryEntry> {
+        self.entries.iter().rev().find(|entry| {
+            entry.action.is_kill() && entry.command_line.is_some() && entry.working_directory.is_some()
+        })
+    }
+
     pub fn get_recent_entries(&self, limit: usize) -> &[ProcessHistoryEntry] {
         let start = if self.entries.len() > limit {
             self.entries.len() - limit
@@ -754,6 +1823,11 @@ impl ProcessHistory {
 
     pub fn clear(&mut self) {
         self.entries.clear();
+        if let Some(store) = &self.store {
+            if let Err(e) = store.lock().unwrap().clear() {
+                log::warn!("Failed to clear the history store: {:#}", e);
+            }
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -776,10 +1850,12 @@ impl ProcessHistory {
     ) -> Result<Self, Box<dyn std::error::Error>> {
         if Path::new(file_path).exists() {
             let json = fs::read_to_string(file_path)?;
-            let entries: Vec<ProcessHistoryEntry> = serde_json::from_str(&json)?;
+            let entries: Vec<ProcessHistoryEntry> = crate::state_format::decode_list(&json)?;
             Ok(Self {
                 entries,
                 max_entries,
+                store: None,
+                filter: HistoryFilter::default(),
             })
         } else {
             Ok(Self::new(max_entries))
@@ -795,12 +1871,17 @@ impl ProcessHistory {
 
     /// Get frequent offenders - processes that have been killed multiple times
     pub fn get_frequent_offenders(&self, min_kills: usize) -> Vec<FrequentOffender> {
+        if let Some(offenders) =
+            self.query(|store| store.frequent_offenders(&self.filter, min_kills))
+        {
+            return offenders;
+        }
         use std::collections::HashMap;
 
         let mut process_counts: HashMap<String, Vec<&ProcessHistoryEntry>> = HashMap::new();
 
         // Group entries by process name and port
-        for entry in &self.entries {
+        for entry in self.kills() {
             let key = format!("{}:{}", entry.process_name, entry.port);
             process_counts
                 .entry(key)
@@ -835,12 +1916,15 @@ impl ProcessHistory {
 
     /// Get time-based patterns - when processes are most commonly killed
     pub fn get_time_patterns(&self) -> TimePatterns {
+        if let Some(patterns) = self.query(|store| store.time_patterns(&self.filter)) {
+            return patterns;
+        }
         use std::collections::HashMap;
 
         let mut hour_counts: HashMap<u32, usize> = HashMap::new();
         let mut day_counts: HashMap<chrono::Weekday, usize> = HashMap::new();
 
-        for entry in &self.entries {
+        for entry in self.kills() {
             let hour = entry.killed_at.hour();
             *hour_counts.entry(hour).or_insert(0) += 1;
 
@@ -860,7 +1944,7 @@ impl ProcessHistory {
             .map(|(day, _)| *day);
 
         TimePatterns {
-            total_kills: self.entries.len(),
+            total_kills: self.kills().count(),
             peak_hour,
             peak_day,
             hour_distribution: hour_counts,
@@ -913,7 +1997,10 @@ impl ProcessHistory {
 
     /// Get statistics about the history
     pub fn get_statistics(&self) -> HistoryStatistics {
-        if self.entries.is_empty() {
+        if let Some(statistics) = self.query(|store| store.statistics(&self.filter)) {
+            return statistics;
+        }
+        let Some(first) = self.kills().next() else {
             return HistoryStatistics {
                 total_kills: 0,
                 unique_processes: 0,
@@ -929,7 +2016,7 @@ impl ProcessHistory {
                 oldest_kill: None,
                 newest_kill: None,
             };
-        }
+        };
 
         use std::collections::HashMap;
 
@@ -937,10 +2024,10 @@ impl ProcessHistory {
         let mut port_counts: HashMap<u16, usize> = HashMap::new();
         let mut project_counts: HashMap<String, usize> = HashMap::new();
 
-        let mut oldest_kill = self.entries[0].killed_at;
-        let mut newest_kill = self.entries[0].killed_at;
+        let mut oldest_kill = first.killed_at;
+        let mut newest_kill = first.killed_at;
 
-        for entry in &self.entries {
+        for entry in self.kills() {
             // Use process_group if available, otherwise fall back to process_name
             let process_key = if let Some(ref group) = entry.process_group {
                 group.clone()
@@ -1000,10 +2087,10 @@ impl ProcessHistory {
         } else {
             1.0
         };
-        let average_kills_per_day = self.entries.len() as f64 / days_span.max(1.0);
+        let average_kills_per_day = self.kills().count() as f64 / days_span.max(1.0);
 
         HistoryStatistics {
-            total_kills: self.entries.len(),
+            total_kills: self.kills().count(),
             unique_processes: total_unique_processes,
             unique_ports: total_unique_ports,
             unique_projects: total_unique_projects,
@@ -1019,6 +2106,53 @@ impl ProcessHistory {
         }
     }
 
+    /// A copy of this history containing only entries on the given ports
+    pub fn filter_by_ports(&self, ports: &std::collections::HashSet<u16>) -> ProcessHistory {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .filter(|entry| ports.contains(&entry.port))
+                .cloned()
+                .collect(),
+            max_entries: self.max_entries,
+            store: None,
+            filter: HistoryFilter::default(),
+        }
+    }
+
+    /// Kill counts, repeat offenders and conflicts for a set of ports
+    pub fn get_port_heat_report(&self, ports: &[u16], preset: Option<String>) -> PortHeatReport {
+        let port_set: std::collections::HashSet<u16> = ports.iter().copied().collect();
+        let scoped = self.filter_by_ports(&port_set);
+
+        let mut counts: HashMap<u16, usize> = HashMap::new();
+        for entry in scoped.kills() {
+            *counts.entry(entry.port).or_insert(0) += 1;
+        }
+
+        let mut port_kills: Vec<(u16, usize)> = counts.iter().map(|(p, c)| (*p, *c)).collect();
+        port_kills.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut cold_ports: Vec<u16> = port_set
+            .iter()
+            .filter(|p| !counts.contains_key(p))
+            .copied()
+            .collect();
+        cold_ports.sort();
+
+        PortHeatReport {
+            preset,
+            ports_watched: port_set.len(),
+            total_kills: scoped.len(),
+            kills_outside_ports: self.len() - scoped.len(),
+            port_kills,
+            cold_ports,
+            offenders: scoped.get_frequent_offenders(2),
+            conflicts: scoped.get_root_cause_analysis().conflicts,
+        }
+    }
+
     /// Perform smart root cause analysis on the process history
     pub fn get_root_cause_analysis(&self) -> RootCauseAnalysis {
         let mut conflicts = Vec::new();
@@ -1032,6 +2166,7 @@ impl ProcessHistory {
         // Analyze workflow patterns
         patterns.extend(self.analyze_development_patterns());
         patterns.extend(self.analyze_time_patterns());
+        patterns.extend(self.analyze_automated_actions());
 
         // Generate smart recommendations
         recommendations.extend(self.generate_process_management_recommendations());
@@ -1057,7 +2192,7 @@ impl ProcessHistory {
 
         // Group processes by port
         let mut port_processes: HashMap<u16, Vec<&ProcessHistoryEntry>> = HashMap::new();
-        for entry in &self.entries {
+        for entry in self.kills() {
             port_processes
                 .entry(entry.port)
                 .or_insert_with(Vec::new)
@@ -1101,7 +2236,7 @@ impl ProcessHistory {
 
         // Group by process name and port
         let mut process_groups: HashMap<String, Vec<&ProcessHistoryEntry>> = HashMap::new();
-        for entry in &self.entries {
+        for entry in self.kills() {
             let key = format!("{}:{}", entry.process_name, entry.port);
             process_groups
                 .entry(key)
@@ -1150,7 +2285,7 @@ impl ProcessHistory {
         // Look for hot reload patterns (same process killed multiple times in short intervals)
         let mut process_groups: std::collections::HashMap<String, Vec<&ProcessHistoryEntry>> =
             std::collections::HashMap::new();
-        for entry in &self.entries {
+        for entry in self.kills() {
             process_groups
                 .entry(entry.process_name.clone())
                 .or_insert_with(Vec::new)
@@ -1191,14 +2326,14 @@ impl ProcessHistory {
     fn analyze_time_patterns(&self) -> Vec<WorkflowPattern> {
         let mut patterns = Vec::new();
 
-        if self.entries.len() < 5 {
+        if self.kills().count() < 5 {
             return patterns;
         }
 
         // Group kills by hour of day
         let mut hourly_kills: std::collections::HashMap<u32, usize> =
             std::collections::HashMap::new();
-        for entry in &self.entries {
+        for entry in self.kills() {
             let hour = entry.killed_at.hour();
             *hourly_kills.entry(hour).or_insert(0) += 1;
         }
@@ -1220,6 +2355,60 @@ impl ProcessHistory {
         patterns
     }
 
+    /// Repeated guard kills and restarts: a port two projects keep fighting over, or a
+    /// process that doesn't stay up
+    fn analyze_automated_actions(&self) -> Vec<WorkflowPattern> {
+        let mut guard_kills: HashMap<(&str, u16), usize> = HashMap::new();
+        let mut restarts: HashMap<u16, usize> = HashMap::new();
+        for entry in &self.entries {
+            match entry.action {
+                HistoryAction::Kill if entry.initiator == Some(AuditInitiator::Guard) => {
+                    *guard_kills
+                        .entry((entry.process_name.as_str(), entry.port))
+                        .or_insert(0) += 1;
+                }
+                HistoryAction::Restart => *restarts.entry(entry.port).or_insert(0) += 1,
+                _ => {}
+            }
+        }
+
+        let mut patterns = Vec::new();
+        let mut guard_kills: Vec<_> = guard_kills.into_iter().filter(|(_, n)| *n >= 2).collect();
+        guard_kills.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for ((name, port), count) in guard_kills {
+            patterns.push(WorkflowPattern {
+                pattern_type: PatternType::Automated,
+                description: format!(
+                    "The port guard killed '{}' on port {} {} times",
+                    name, port, count
+                ),
+                affected_processes: vec![name.to_string()],
+                frequency: format!("{} automated kills", count),
+                recommendation: format!(
+                    "Something keeps starting '{}' on a guarded port; move it to another port or reserve {} for it.",
+                    name, port
+                ),
+                confidence: 0.9,
+            });
+        }
+        let mut restarts: Vec<_> = restarts.into_iter().filter(|(_, n)| *n >= 3).collect();
+        restarts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (port, count) in restarts {
+            patterns.push(WorkflowPattern {
+                pattern_type: PatternType::AutoRestart,
+                description: format!("Port {} was restarted {} times", port, count),
+                affected_processes: vec![format!("port {}", port)],
+                frequency: format!("{} restarts", count),
+                recommendation: format!(
+                    "The process on port {} keeps needing restarts; check its logs, or run it with --restart {} --supervise.",
+                    port, port
+                ),
+                confidence: 0.8,
+            });
+        }
+        patterns
+    }
+
     /// Generate process management recommendations
     fn generate_process_management_recommendations(&self) -> Vec<SmartRecommendation> {
         let mut recommendations = Vec::new();
@@ -1290,7 +2479,7 @@ impl ProcessHistory {
         patterns: &[WorkflowPattern],
         recommendations: &[SmartRecommendation],
     ) -> String {
-        let total_kills = self.entries.len();
+        let total_kills = self.kills().count();
         let conflict_count = conflicts.len();
         let pattern_count = patterns.len();
         let recommendation_count = recommendations.len();
@@ -0,0 +1,163 @@
+//! Undo a kill: start the most recently killed process again with the command line and
+//! working directory its history entry recorded. `--undo-last-kill` asks on the terminal
+//! first, the tray's "Restart last killed" item asks in a dialog. The restart goes through
+//! [`RestartManager`], so it shows up in `--show-restart-history` and the audit log.
+
+use crate::restart_manager::RestartManager;
+use crate::types::{ProcessHistory, ProcessHistoryEntry};
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// The kill `--undo-last-kill` would undo, from the kill history
+pub fn last_killed() -> Result<Option<ProcessHistoryEntry>> {
+    let history = ProcessHistory::load(100);
+    Ok(history.last_restartable_kill().cloned())
+}
+
+/// "Restart node (PID 123) on port 3000, killed 5m ago?" with the command and directory
+pub fn describe(entry: &ProcessHistoryEntry) -> String {
+    format!(
+        "Restart {} (PID {}) on port {}, killed {}?\n\nCommand: {}\nDirectory: {}",
+        entry.get_display_name(),
+        entry.pid,
+        entry.port,
+        crate::cache::output::human_since(Some(entry.killed_at)),
+        entry.command_line.as_deref().unwrap_or_default(),
+        entry.working_directory.as_deref().unwrap_or_default()
+    )
+}
+
+/// Start `entry` again. Refuses when something is listening on its port already, which
+/// usually means it was restarted some other way. Returns the new PID.
+pub fn restart(entry: &ProcessHistoryEntry) -> Result<u32> {
+    if std::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, entry.port)).is_err() {
+        anyhow::bail!("Port {} is in use again; not starting a second copy", entry.port);
+    }
+    let mut manager = RestartManager::new()?;
+    manager.save_from_history_entry(entry)?;
+    manager.restart_port(entry.port)
+}
+
+/// `--undo-last-kill`
+pub fn undo_last_kill() -> Result<()> {
+    let Some(entry) = last_killed()? else {
+        println!("ℹ️  No killed process with a recorded command to restart");
+        println!("💡 Tip: kills record the command line in verbose mode (-v)");
+        return Ok(());
+    };
+    if !crate::safe_mode::confirm(&describe(&entry))? {
+        println!("Cancelled.");
+        return Ok(());
+    }
+    let pid = restart(&entry)?;
+    println!("✅ Restarted {} on port {} (PID {})", entry.process_name, entry.port, pid);
+    Ok(())
+}
+
+/// Tray "Restart last killed": the same, confirmed in a dialog and reported in a
+/// notification
+pub fn undo_last_kill_from_tray() -> Result<()> {
+    let Some(entry) = last_killed()? else {
+        crate::notify::show("Nothing to restart", "No killed process with a recorded command");
+        return Ok(());
+    };
+    if !confirm_dialog(&describe(&entry)) {
+        return Ok(());
+    }
+    match restart(&entry) {
+        Ok(pid) => {
+            crate::notify::show(
+                "Process restarted",
+                &format!("{} on port {} (PID {})", entry.process_name, entry.port, pid),
+            );
+            Ok(())
+        }
+        Err(e) => {
+            crate::notify::show("Restart failed", &format!("{:#}", e));
+            Err(e)
+        }
+    }
+}
+
+/// A Restart/Cancel dialog: AppleScript on macOS, a PowerShell message box on Windows and
+/// zenity (or kdialog) on Linux. Without a way to ask, the answer is no.
+pub fn confirm_dialog(message: &str) -> bool {
+    let output = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display dialog \"{}\" with title \"Port Kill\" buttons {{\"Cancel\", \"Restart\"}} default button \"Restart\"",
+            message.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        Command::new("osascript").args(["-e", &script]).output()
+    } else if cfg!(target_os = "windows") {
+        let script = format!(
+            "Add-Type -AssemblyName PresentationFramework; [System.Windows.MessageBox]::Show('{}', 'Port Kill', 'OKCancel')",
+            message.replace('\'', "''")
+        );
+        return Command::new("powershell")
+            .args(["-NoProfile", "-Command", &script])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "OK");
+    } else {
+        Command::new("zenity")
+            .args(["--question", "--title=Port Kill", "--ok-label=Restart", "--no-markup"])
+            .arg(format!("--text={}", message))
+            .output()
+            .or_else(|_| {
+                Command::new("kdialog")
+                    .args(["--title", "Port Kill", "--yesno", message])
+                    .output()
+            })
+    };
+    match output.context("No dialog available to confirm the restart") {
+        Ok(output) => output.status.success(),
+        Err(e) => {
+            log::warn!("{:#}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ProcessInfo;
+
+    #[test]
+    fn test_last_restartable_kill() {
+        let mut process = ProcessInfo {
+            pid: 42,
+            port: 3000,
+            command: "node".to_string(),
+            name: "node".to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: Some("node server.js".to_string()),
+            working_directory: Some("/srv/app".to_string()),
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: None,
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        };
+        let mut history = ProcessHistory::new(10);
+        history.add_entry(ProcessHistoryEntry::new(&process, "user".to_string()));
+        // Later kills without a command, and renices, can't be undone
+        history.add_entry(ProcessHistoryEntry::reniced(&process, 10, None));
+        process.port = 8080;
+        process.command_line = None;
+        history.add_entry(ProcessHistoryEntry::new(&process, "user".to_string()));
+
+        let entry = history.last_restartable_kill().unwrap();
+        assert_eq!(entry.port, 3000);
+        let prompt = describe(entry);
+        assert!(prompt.starts_with("Restart node (PID 42) on port 3000, killed just now?"));
+        assert!(prompt.ends_with("Command: node server.js\nDirectory: /srv/app"));
+    }
+}
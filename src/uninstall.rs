@@ -0,0 +1,385 @@
+//! `--uninstall`: remove what port-kill installed outside its own binary — service units,
+//! login items and shell completions — and, with `--purge`, every state file and
+//! directory it writes. Each path is printed as it is removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// launchd label prefix for the macOS agents port-kill installs
+pub const LAUNCHD_LABEL: &str = "com.treadie.port-kill";
+/// Name prefix of systemd units, autostart entries and Windows tasks
+pub const SERVICE_NAME: &str = "port-kill";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// launchd agent/daemon or systemd unit; stopped before it is removed
+    Service,
+    /// Autostart entry that launches the tray app at login
+    LoginItem,
+    Completion,
+    /// Config, history, presets, backups (only with `--purge`)
+    State,
+}
+
+impl ArtifactKind {
+    pub fn describe(self) -> &'static str {
+        match self {
+            ArtifactKind::Service => "service",
+            ArtifactKind::LoginItem => "login item",
+            ArtifactKind::Completion => "shell completion",
+            ArtifactKind::State => "state",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Default)]
+pub struct UninstallReport {
+    pub removed: Vec<Artifact>,
+    pub failed: Vec<(Artifact, String)>,
+}
+
+pub fn home_dir() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home)
+}
+
+/// Files in `dir` whose name starts with `prefix` and ends with one of `suffixes`
+fn matching_files(dir: &Path, prefix: &str, suffixes: &[&str]) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(prefix) && suffixes.iter().any(|s| name.ends_with(s))
+                })
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Shell completion files, per shell, as written by `completions <shell> --install`
+pub fn completion_paths(home: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for bin in ["port-kill", "port-kill-console"] {
+        paths.push(home.join(".local/share/bash-completion/completions").join(bin));
+        paths.push(home.join(".zfunc").join(format!("_{}", bin)));
+        paths.push(home.join(".config/fish/completions").join(format!("{}.fish", bin)));
+    }
+    paths
+}
+
+/// Everything port-kill may have installed that exists on this machine
+pub fn installed_artifacts(home: &Path) -> Vec<Artifact> {
+    let mut artifacts = Vec::new();
+    let mut add = |kind, paths: Vec<PathBuf>| {
+        artifacts.extend(paths.into_iter().map(|path| Artifact { kind, path }));
+    };
+
+    // macOS launchd agents (per user) and daemons (system-wide)
+    add(
+        ArtifactKind::Service,
+        matching_files(&home.join("Library/LaunchAgents"), LAUNCHD_LABEL, &[".plist"]),
+    );
+    add(
+        ArtifactKind::Service,
+        matching_files(Path::new("/Library/LaunchDaemons"), LAUNCHD_LABEL, &[".plist"]),
+    );
+    // systemd user and system units
+    add(
+        ArtifactKind::Service,
+        matching_files(
+            &home.join(".config/systemd/user"),
+            SERVICE_NAME,
+            &[".service", ".timer"],
+        ),
+    );
+    add(
+        ArtifactKind::Service,
+        matching_files(
+            Path::new("/etc/systemd/system"),
+            SERVICE_NAME,
+            &[".service", ".timer"],
+        ),
+    );
+    // XDG autostart (Linux desktops)
+    add(
+        ArtifactKind::LoginItem,
+        matching_files(&home.join(".config/autostart"), SERVICE_NAME, &[".desktop"]),
+    );
+    add(
+        ArtifactKind::Completion,
+        completion_paths(home)
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect(),
+    );
+    artifacts
+}
+
+/// State files and directories, removed only with `--purge`
+pub fn state_artifacts(home: &Path) -> Vec<Artifact> {
+    [
+        // config.toml, presets, kill history, reservations, restart history, update-check stamp
+        home.join(".port-kill"),
+        home.join(".port-kill-history.json"),
+        home.join(".port-kill-last-audit.json"),
+        // Cache backups made by `cache --clean`
+        home.join(".cachekill-backup"),
+    ]
+    .into_iter()
+    .filter(|path| path.exists())
+    .map(|path| Artifact {
+        kind: ArtifactKind::State,
+        path,
+    })
+    .collect()
+}
+
+fn run_quietly(program: &str, args: &[&str]) {
+    let _ = Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Stop and unregister a service before its file goes away
+pub(crate) fn stop_service(path: &Path) {
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+        return;
+    };
+    if file_name.ends_with(".plist") {
+        run_quietly("launchctl", &["unload", "-w", &path.to_string_lossy()]);
+    } else if path.starts_with("/etc/systemd") {
+        run_quietly("systemctl", &["disable", "--now", file_name]);
+    } else {
+        run_quietly("systemctl", &["--user", "disable", "--now", file_name]);
+    }
+}
+
+/// Remove Windows login items and scheduled tasks, which aren't files
+#[cfg(target_os = "windows")]
+fn remove_windows_entries(report: &mut UninstallReport) {
+    let run_key = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+    let removed = Command::new("reg")
+        .args(["delete", run_key, "/v", SERVICE_NAME, "/f"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success());
+    if removed {
+        report.removed.push(Artifact {
+            kind: ArtifactKind::LoginItem,
+            path: PathBuf::from(format!(r"{}\{}", run_key, SERVICE_NAME)),
+        });
+    }
+    for task in [
+        SERVICE_NAME.to_string(),
+        crate::daemon::service_name(),
+        crate::cache::schedule::schedule_name(),
+    ] {
+        let removed = Command::new("schtasks")
+            .args(["/Delete", "/TN", &task, "/F"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success());
+        if removed {
+            report.removed.push(Artifact {
+                kind: ArtifactKind::Service,
+                path: PathBuf::from(format!("Task Scheduler: {}", task)),
+            });
+        }
+    }
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Remove the given artifacts, recording what went and what couldn't
+pub fn remove_artifacts(artifacts: Vec<Artifact>, report: &mut UninstallReport) {
+    for artifact in artifacts {
+        match remove_path(&artifact.path) {
+            Ok(()) => report.removed.push(artifact),
+            Err(e) => report.failed.push((artifact, e.to_string())),
+        }
+    }
+}
+
+/// Whether the report removed system (`/etc/systemd`) and user systemd units, in that
+/// order, so the matching systemd instance reloads its unit files
+fn removed_systemd_units(report: &UninstallReport) -> (bool, bool) {
+    let mut system = false;
+    let mut user = false;
+    for artifact in &report.removed {
+        let path = &artifact.path;
+        if artifact.kind != ArtifactKind::Service
+            || !path
+                .extension()
+                .is_some_and(|e| e == "service" || e == "timer")
+        {
+            continue;
+        }
+        if path.starts_with("/etc/systemd") {
+            system = true;
+        } else {
+            user = true;
+        }
+    }
+    (system, user)
+}
+
+/// Remove installed services, login items and completions; with `purge`, all state too
+pub fn uninstall(purge: bool) -> UninstallReport {
+    let home = home_dir();
+    let mut report = UninstallReport::default();
+    #[cfg(target_os = "windows")]
+    remove_windows_entries(&mut report);
+
+    let installed = installed_artifacts(&home);
+    let services: Vec<&Path> = installed
+        .iter()
+        .filter(|a| a.kind == ArtifactKind::Service)
+        .map(|a| a.path.as_path())
+        .collect();
+    for path in &services {
+        stop_service(path);
+    }
+    remove_artifacts(installed, &mut report);
+    let (system_units, user_units) = removed_systemd_units(&report);
+    if user_units {
+        run_quietly("systemctl", &["--user", "daemon-reload"]);
+    }
+    if system_units {
+        run_quietly("systemctl", &["daemon-reload"]);
+    }
+
+    if purge {
+        remove_artifacts(state_artifacts(&home), &mut report);
+    }
+    report
+}
+
+pub fn print_report(report: &UninstallReport, purge: bool) {
+    if report.removed.is_empty() && report.failed.is_empty() {
+        println!("Nothing to remove.");
+    }
+    for artifact in &report.removed {
+        println!(
+            "🗑️  Removed {}: {}",
+            artifact.kind.describe(),
+            artifact.path.display()
+        );
+    }
+    for (artifact, error) in &report.failed {
+        eprintln!(
+            "⚠️  Could not remove {} {}: {}",
+            artifact.kind.describe(),
+            artifact.path.display(),
+            error
+        );
+    }
+    if !purge {
+        let state = state_artifacts(&home_dir());
+        if !state.is_empty() {
+            println!("Kept state (use --uninstall --purge to remove it too):");
+            for artifact in state {
+                println!("   {}", artifact.path.display());
+            }
+        }
+    }
+    if let Ok(exe) = std::env::current_exe() {
+        println!(
+            "The port-kill binary itself was left in place: {}",
+            exe.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_and_removes_installed_artifacts() {
+        let home = std::env::temp_dir().join("port-kill-test-uninstall");
+        let _ = fs::remove_dir_all(&home);
+        let units = home.join(".config/systemd/user");
+        fs::create_dir_all(&units).unwrap();
+        fs::write(units.join("port-kill.service"), "[Unit]").unwrap();
+        fs::write(units.join("other.service"), "[Unit]").unwrap();
+        let fish = home.join(".config/fish/completions");
+        fs::create_dir_all(&fish).unwrap();
+        fs::write(fish.join("port-kill.fish"), "complete").unwrap();
+        fs::create_dir_all(home.join(".port-kill")).unwrap();
+        fs::write(home.join(".port-kill-history.json"), "[]").unwrap();
+
+        let installed = installed_artifacts(&home);
+        let kinds: Vec<ArtifactKind> = installed.iter().map(|a| a.kind).collect();
+        assert!(kinds.contains(&ArtifactKind::Service));
+        assert!(kinds.contains(&ArtifactKind::Completion));
+        assert!(installed.iter().all(|a| !a.path.ends_with("other.service")));
+        assert_eq!(state_artifacts(&home).len(), 2);
+
+        let mut report = UninstallReport::default();
+        remove_artifacts(installed, &mut report);
+        remove_artifacts(state_artifacts(&home), &mut report);
+        let leftover = units.join("other.service").exists();
+        let gone = !units.join("port-kill.service").exists() && !home.join(".port-kill").exists();
+        let _ = fs::remove_dir_all(&home);
+
+        assert!(report.failed.is_empty());
+        assert!(leftover);
+        assert!(gone);
+    }
+
+    #[test]
+    fn test_reloads_the_systemd_instance_that_lost_units() {
+        let artifact = |kind, path: &str| Artifact {
+            kind,
+            path: PathBuf::from(path),
+        };
+        let mut report = UninstallReport::default();
+        report.removed.push(artifact(
+            ArtifactKind::Service,
+            "/home/me/Library/LaunchAgents/com.treadie.port-kill.plist",
+        ));
+        report.removed.push(artifact(
+            ArtifactKind::Completion,
+            "/home/me/.config/fish/completions/port-kill.fish",
+        ));
+        assert_eq!(removed_systemd_units(&report), (false, false));
+
+        report.removed.push(artifact(
+            ArtifactKind::Service,
+            "/etc/systemd/system/port-kill-daemon.service",
+        ));
+        assert_eq!(removed_systemd_units(&report), (true, false));
+
+        report.removed.push(artifact(
+            ArtifactKind::Service,
+            "/home/me/.config/systemd/user/port-kill-cache.timer",
+        ));
+        assert_eq!(removed_systemd_units(&report), (true, true));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::offline::{self, Channel};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -55,7 +56,7 @@ pub async fn check_for_updates(current_version: &str) -> Result<Option<UpdateInf
 }
 
 async fn fetch_latest_release() -> Result<GitHubRelease> {
-    let client = reqwest::Client::new();
+    let client = offline::client_builder(Channel::UpdateCheck)?.build()?;
     let response = client
         .get(GITHUB_API_URL)
         .header("User-Agent", "port-kill-update-checker")
@@ -153,7 +154,7 @@ pub async fn self_update() -> Result<()> {
 
     // Download the new binary
     println!("📥 Downloading latest version...");
-    let client = reqwest::Client::new();
+    let client = offline::client_builder(Channel::SelfUpdate)?.build()?;
     let response = client
         .get(&download_url)
         .header(reqwest::header::USER_AGENT, "port-kill-updater")
@@ -237,7 +238,7 @@ async fn get_platform_download_url() -> Result<String> {
     };
 
     // Get the latest release info to construct the download URL
-    let client = reqwest::Client::new();
+    let client = offline::client_builder(Channel::SelfUpdate)?.build()?;
     let response = client
         .get(GITHUB_API_URL)
         .header(reqwest::header::USER_AGENT, "port-kill-updater")
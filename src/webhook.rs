@@ -0,0 +1,401 @@
+//! Webhook delivery for notification events: kills, failed kills, port guard conflicts and
+//! security audit findings are POSTed as JSON to every configured webhook. The payload is
+//! either port-kill's own structured event or shaped for Slack, Discord or Teams incoming
+//! webhooks:
+//!
+//! ```toml
+//! [[notifications.webhooks]]
+//! url = "https://hooks.slack.com/services/T000/B000/XXXX"
+//! format = "slack"                          # json (default), slack, discord, teams
+//! events = ["kill_failed", "port_conflict"]  # default: every event
+//! retries = 3
+//! timeout = 10                              # seconds per attempt
+//! ```
+//!
+//! Webhooks fire whenever they are configured (except with `--offline`); `enabled` and
+//! `--notify` only control desktop notifications. `--test-webhooks` sends a test event to
+//! each one.
+
+use crate::notify::{Message, NotifyEvent};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Deliveries still running, so a one-shot command can wait for them before exiting
+static PENDING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// [`WebhookEvent`] as-is
+    #[default]
+    Json,
+    Slack,
+    Discord,
+    Teams,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookSettings {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Event names (see [`NotifyEvent::name`]) to send; all of them when unset
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    /// Attempts after the first one fails
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+}
+
+impl WebhookSettings {
+    pub fn wants(&self, event: NotifyEvent) -> bool {
+        self.events
+            .as_ref()
+            .is_none_or(|events| events.iter().any(|e| e == event.name()))
+    }
+
+    /// The URL without its path, which for Slack and Discord holds the secret
+    pub fn display_url(&self) -> String {
+        match self.url.split_once("://") {
+            Some((scheme, rest)) => {
+                let host = rest.split('/').next().unwrap_or(rest);
+                format!("{}://{}/…", scheme, host)
+            }
+            None => "<invalid url>".to_string(),
+        }
+    }
+}
+
+/// The structured event posted with `format = "json"`
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub event: &'static str,
+    pub title: String,
+    pub message: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_name: Option<String>,
+}
+
+impl WebhookEvent {
+    pub fn from_message(message: &Message) -> Self {
+        Self {
+            event: message.event.name(),
+            title: message.title.clone(),
+            message: message.body.clone(),
+            timestamp: chrono::Utc::now(),
+            host: sysinfo::System::host_name(),
+            pid: message.pid,
+            port: message.port,
+            process_name: message.process_name.clone(),
+        }
+    }
+}
+
+/// The request body for a webhook in `format`
+pub fn payload(format: WebhookFormat, event: &WebhookEvent) -> Value {
+    let host = event.host.as_deref().unwrap_or("unknown host");
+    match format {
+        WebhookFormat::Json => serde_json::to_value(event).unwrap_or(Value::Null),
+        WebhookFormat::Slack => json!({
+            "text": format!("*{}*\n{}", event.title, event.message),
+            "blocks": [
+                {
+                    "type": "section",
+                    "text": {
+                        "type": "mrkdwn",
+                        "text": format!("*{}*\n{}", event.title, event.message)
+                    }
+                },
+                {
+                    "type": "context",
+                    "elements": [
+                        { "type": "mrkdwn", "text": format!("port-kill on `{}` · {}", host, event.event) }
+                    ]
+                }
+            ]
+        }),
+        WebhookFormat::Discord => json!({
+            "username": "Port Kill",
+            "embeds": [
+                {
+                    "title": event.title,
+                    "description": event.message,
+                    "timestamp": event.timestamp.to_rfc3339(),
+                    "footer": { "text": format!("{} · {}", host, event.event) }
+                }
+            ]
+        }),
+        WebhookFormat::Teams => json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "summary": event.title,
+            "title": event.title,
+            "text": event.message,
+            "sections": [
+                { "facts": [
+                    { "name": "Host", "value": host },
+                    { "name": "Event", "value": event.event }
+                ] }
+            ]
+        }),
+    }
+}
+
+/// POST `body` to the webhook, retrying with backoff. Blocks, so call it off the async
+/// runtime.
+pub fn deliver(webhook: &WebhookSettings, body: &Value) -> Result<()> {
+    let client = crate::offline::blocking_client_builder(crate::offline::Channel::Webhook)?
+        .timeout(Duration::from_secs(webhook.timeout))
+        .user_agent(concat!("port-kill/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let mut attempt = 0;
+    loop {
+        let result = client
+            .post(&webhook.url)
+            .json(body)
+            .send()
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => return Ok(()),
+            Err(e) if attempt < webhook.retries => {
+                attempt += 1;
+                let delay = Duration::from_secs(1 << (attempt - 1).min(5));
+                log::debug!(
+                    "Webhook {} failed ({}), retrying in {}s",
+                    webhook.display_url(),
+                    describe_error(&e),
+                    delay.as_secs()
+                );
+                std::thread::sleep(delay);
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "{} (after {} attempts)",
+                    describe_error(&e),
+                    webhook.retries + 1
+                ))
+            }
+        }
+    }
+}
+
+/// reqwest errors repeat the URL, which for Slack and Discord is the secret
+fn describe_error(error: &reqwest::Error) -> String {
+    if let Some(status) = error.status() {
+        return format!("HTTP {}", status);
+    }
+    if error.is_timeout() {
+        return "timed out".to_string();
+    }
+    let mut cause: &dyn std::error::Error = error;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+/// Send `message` to every webhook that wants it, in the background
+pub fn dispatch(webhooks: &[WebhookSettings], message: &Message) {
+    let targets: Vec<WebhookSettings> = webhooks
+        .iter()
+        .filter(|webhook| webhook.wants(message.event))
+        .cloned()
+        .collect();
+    // Offline: drop events quietly rather than warn about every one
+    if targets.is_empty() || crate::offline::is_enabled() {
+        return;
+    }
+    let event = WebhookEvent::from_message(message);
+    let handle = std::thread::spawn(move || {
+        for webhook in targets {
+            if let Err(e) = deliver(&webhook, &payload(webhook.format, &event)) {
+                log::warn!("Webhook {} failed: {:#}", webhook.display_url(), e);
+            }
+        }
+    });
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.retain(|handle| !handle.is_finished());
+        pending.push(handle);
+    }
+}
+
+/// How long an exiting command waits for webhook deliveries still in flight
+pub const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait up to `timeout` for webhook deliveries still in flight. Called before one-shot
+/// commands exit, since the process exiting would drop them.
+pub fn flush(timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    let pending = match PENDING.lock() {
+        Ok(mut pending) => std::mem::take(&mut *pending),
+        Err(_) => return,
+    };
+    for handle in pending {
+        while !handle.is_finished() {
+            if Instant::now() >= deadline {
+                log::debug!("Gave up waiting for webhook deliveries");
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let _ = handle.join();
+    }
+}
+
+/// Flushes webhooks when dropped. Held for the whole of `main`, so every return from it
+/// waits for them; [`crate::exit_code::exit`] covers exiting early.
+pub struct FlushOnExit;
+
+impl Drop for FlushOnExit {
+    fn drop(&mut self) {
+        flush(FLUSH_TIMEOUT);
+    }
+}
+
+/// `--test-webhooks`: send a test event to each configured webhook and report the
+/// outcome. Returns whether all of them accepted it.
+pub fn test_all(webhooks: &[WebhookSettings]) -> bool {
+    if webhooks.is_empty() {
+        eprintln!("No webhooks configured; add [[notifications.webhooks]] to ~/.port-kill/config.toml");
+        return false;
+    }
+    let message = Message::test();
+    let event = WebhookEvent::from_message(&message);
+    let handles: Vec<_> = webhooks
+        .iter()
+        .cloned()
+        .map(|webhook| {
+            let body = payload(webhook.format, &event);
+            std::thread::spawn(move || {
+                let result = deliver(&webhook, &body);
+                (webhook, result)
+            })
+        })
+        .collect();
+
+    let mut ok = true;
+    for handle in handles {
+        let Ok((webhook, result)) = handle.join() else {
+            ok = false;
+            continue;
+        };
+        match result {
+            Ok(()) => println!("✅ {} ({:?})", webhook.display_url(), webhook.format),
+            Err(e) => {
+                ok = false;
+                println!("❌ {} ({:?}): {:#}", webhook.display_url(), webhook.format, e);
+            }
+        }
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_settings_and_payloads() {
+        let config: crate::config::ConfigLayer = toml::from_str(
+            r#"
+            [[notifications.webhooks]]
+            url = "https://hooks.slack.com/services/T000/B000/secret"
+            format = "slack"
+            events = ["kill_failed", "port_conflict"]
+
+            [[notifications.webhooks]]
+            url = "https://example.com/port-kill"
+            "#,
+        )
+        .unwrap();
+        let webhooks = config.notifications.unwrap().webhooks;
+        assert_eq!(webhooks[0].format, WebhookFormat::Slack);
+        assert!(!webhooks[0].wants(NotifyEvent::Killed));
+        assert!(webhooks[0].wants(NotifyEvent::Conflict));
+        assert_eq!(webhooks[0].display_url(), "https://hooks.slack.com/…");
+        assert_eq!(webhooks[1].format, WebhookFormat::Json);
+        assert_eq!(webhooks[1].retries, 3);
+        assert!(webhooks[1].wants(NotifyEvent::AuditFinding));
+
+        let event = WebhookEvent::from_message(&Message::killed(42, Some(3000), Some("node")));
+        let body = payload(WebhookFormat::Json, &event);
+        assert_eq!(body["event"], "process_killed");
+        assert_eq!(body["port"], 3000);
+        assert_eq!(body["process_name"], "node");
+        assert_eq!(payload(WebhookFormat::Slack, &event)["text"], "*Port 3000 freed*\nKilled node (PID 42) on port 3000");
+        assert_eq!(payload(WebhookFormat::Discord, &event)["embeds"][0]["title"], "Port 3000 freed");
+        assert_eq!(payload(WebhookFormat::Teams, &event)["@type"], "MessageCard");
+    }
+
+    #[test]
+    fn test_dispatched_webhooks_are_delivered_before_exit() {
+        use std::io::{Read, Write};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        // Other tests turn on --offline, which drops webhooks, for the whole process; this
+        // one runs itself again as a child process without it
+        if std::env::var_os("PORT_KILL_TEST_CHILD").is_none() {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "webhook::tests::test_dispatched_webhooks_are_delivered_before_exit"])
+                .env("PORT_KILL_TEST_CHILD", "1")
+                .stdout(std::process::Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success());
+            return;
+        }
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Arc::new(AtomicBool::new(false));
+        let server = {
+            let received = received.clone();
+            std::thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                while !String::from_utf8_lossy(&request).contains("process_killed") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                received.store(true, Ordering::SeqCst);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                );
+            })
+        };
+
+        let webhook: WebhookSettings =
+            serde_json::from_value(json!({ "url": url, "retries": 0, "timeout": 5 })).unwrap();
+        dispatch(&[webhook], &Message::killed(42, Some(3000), Some("node")));
+        // What `main` returning does
+        drop(FlushOnExit);
+        assert!(received.load(Ordering::SeqCst));
+        server.join().unwrap();
+    }
+}
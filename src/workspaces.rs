@@ -0,0 +1,236 @@
+//! Monorepo members, so `--detect` at the root of a monorepo finds the services in
+//! `apps/*` and `packages/*` too. Members come from what the repo declares:
+//!
+//! - `workspaces` in package.json (npm, yarn) and `packages` in pnpm-workspace.yaml or
+//!   lerna.json
+//! - `members` / `exclude` of `[workspace]` in Cargo.toml
+//! - every directory with a project.json when nx.json is present
+//! - `apps/*` and `packages/*` when turbo.json is present and nothing else is declared
+//!
+//! Patterns are matched at most [`MAX_DEPTH`] directories down, skipping `node_modules`,
+//! `target`, hidden directories and whatever the root `.gitignore` lists.
+
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How far below the root members are looked for
+pub const MAX_DEPTH: usize = 5;
+
+/// Files that make a directory worth running the service detectors in
+const PROJECT_FILES: [&str; 12] = [
+    "package.json",
+    "Cargo.toml",
+    "project.json",
+    "Procfile",
+    "docker-compose.yml",
+    "compose.yml",
+    "go.mod",
+    "build.gradle",
+    "build.gradle.kts",
+    "pom.xml",
+    "Gemfile",
+    "artisan",
+];
+
+/// Member directories of the workspace rooted at `root`, sorted, without `root` itself.
+/// Empty when `root` isn't a workspace root.
+pub fn member_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut patterns = declared_patterns(root);
+    let nx = root.join("nx.json").is_file();
+    if patterns.is_empty() && !nx && root.join("turbo.json").is_file() {
+        patterns = vec!["apps/*".to_string(), "packages/*".to_string()];
+    }
+    if patterns.is_empty() && !nx {
+        return Vec::new();
+    }
+
+    let (excludes, includes): (Vec<&String>, Vec<&String>) = patterns.iter().partition(|p| p.starts_with('!'));
+    let includes: Vec<Regex> = includes.iter().filter_map(|p| glob_regex(p)).collect();
+    let excludes: Vec<Regex> = excludes.iter().filter_map(|p| glob_regex(&p[1..])).collect();
+    let gitignore = Gitignore::load(root);
+
+    let mut members = BTreeSet::new();
+    let walker = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(MAX_DEPTH)
+        .into_iter()
+        .filter_entry(|entry| entry.file_type().is_dir() && !gitignore.skips(root, entry.path()));
+    for entry in walker.flatten() {
+        let Some(relative) = relative_path(root, entry.path()) else {
+            continue;
+        };
+        let declared = includes.iter().any(|re| re.is_match(&relative))
+            || (nx && entry.path().join("project.json").is_file());
+        if declared
+            && !excludes.iter().any(|re| re.is_match(&relative))
+            && PROJECT_FILES.iter().any(|file| entry.path().join(file).is_file())
+        {
+            members.insert(entry.path().to_path_buf());
+        }
+    }
+    members.into_iter().collect()
+}
+
+/// `root`-relative path with `/` separators, for matching patterns and naming services
+pub fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Member patterns from package.json, pnpm-workspace.yaml, lerna.json and Cargo.toml
+fn declared_patterns(root: &Path) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let strings = |value: Option<&serde_json::Value>| -> Vec<String> {
+        value
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    };
+
+    if let Some(package) = read_json(&root.join("package.json")) {
+        // An array, or yarn's { "packages": [...] }
+        let workspaces = package.get("workspaces");
+        patterns.extend(strings(workspaces));
+        patterns.extend(strings(workspaces.and_then(|w| w.get("packages"))));
+    }
+    if let Some(lerna) = read_json(&root.join("lerna.json")) {
+        patterns.extend(strings(lerna.get("packages")));
+    }
+    if let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        if let Ok(pnpm) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
+            let packages = pnpm.get("packages").and_then(|p| p.as_sequence());
+            patterns.extend(packages.into_iter().flatten().filter_map(|p| p.as_str().map(str::to_string)));
+        }
+    }
+    if let Ok(content) = fs::read_to_string(root.join("Cargo.toml")) {
+        if let Ok(cargo) = content.parse::<toml::Value>() {
+            if let Some(workspace) = cargo.get("workspace") {
+                let list = |key: &str| -> Vec<String> {
+                    workspace
+                        .get(key)
+                        .and_then(|v| v.as_array())
+                        .map(|items| items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect())
+                        .unwrap_or_default()
+                };
+                patterns.extend(list("members"));
+                patterns.extend(list("exclude").into_iter().map(|p| format!("!{}", p)));
+            }
+        }
+    }
+    patterns
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Anchored regex for a glob: `*` stays within a directory, `**` crosses them
+pub(crate) fn glob_regex(pattern: &str) -> Option<Regex> {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // "**/" also matches no directory at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+/// The parts of a root `.gitignore` that matter for finding directories: plain and
+/// wildcard names, anchored with a leading `/` or not. Negations are ignored.
+struct Gitignore {
+    /// (anchored to the root, pattern)
+    patterns: Vec<(bool, Regex)>,
+}
+
+impl Gitignore {
+    fn load(root: &Path) -> Self {
+        let content = fs::read_to_string(root.join(".gitignore")).unwrap_or_default();
+        Self::parse(&content)
+    }
+
+    fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+            .filter_map(|line| {
+                let line = line.trim_end_matches('/');
+                let anchored = line.starts_with('/') || line.contains('/');
+                Some((anchored, glob_regex(line.trim_start_matches('/'))?))
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether the walk should not go into `dir`
+    fn skips(&self, root: &Path, dir: &Path) -> bool {
+        let name = dir.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        if name.starts_with('.') || name == "node_modules" || name == "target" {
+            return true;
+        }
+        let Some(relative) = relative_path(root, dir) else {
+            return false;
+        };
+        self.patterns.iter().any(|(anchored, re)| {
+            if *anchored {
+                re.is_match(&relative)
+            } else {
+                re.is_match(&name)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_dirs() {
+        let root = std::env::temp_dir().join(format!("port-kill-workspace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["apps/web", "apps/docs", "packages/ui", "packages/legacy", "tools/gen", "out/app"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("package.json"), "{}").unwrap();
+        }
+        fs::create_dir_all(root.join("apps/web/node_modules/dep")).unwrap();
+        fs::write(root.join("apps/web/node_modules/dep/package.json"), "{}").unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"workspaces": ["apps/*", "packages/**", "out/*", "!packages/legacy"]}"#,
+        )
+        .unwrap();
+        fs::write(root.join(".gitignore"), "# build output\n/out/\n").unwrap();
+
+        let members: Vec<String> = member_dirs(&root)
+            .iter()
+            .filter_map(|dir| relative_path(&root, dir))
+            .collect();
+        assert_eq!(members, vec!["apps/docs", "apps/web", "packages/ui"]);
+
+        // Not a workspace root
+        assert!(member_dirs(&root.join("apps/web")).is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
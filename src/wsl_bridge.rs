@@ -0,0 +1,249 @@
+use crate::types::ProcessInfo;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::process::Command;
+
+/// Windows-side processes that own ports forwarded out of a WSL2 VM
+const WSL_HOST_PROCESSES: &[&str] = &[
+    "wslhost.exe",
+    "wslrelay.exe",
+    "vmmem",
+    "vmmem.exe",
+    "vmmemwsl",
+];
+
+/// Returns true if the Windows process is a WSL2 relay/VM host rather than the real server
+pub fn is_wsl_host_process(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    WSL_HOST_PROCESSES.iter().any(|p| lower == *p)
+}
+
+/// `wsl.exe` writes UTF-16LE for its own messages but UTF-8 for commands run inside a distro
+pub fn decode_wsl_output(bytes: &[u8]) -> String {
+    let looks_utf16 = bytes.len() >= 2 && bytes.iter().skip(1).step_by(2).all(|b| *b == 0);
+    if looks_utf16 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).to_string()
+    }
+}
+
+/// Parse the output of `wsl.exe -l -q` into distro names
+pub fn parse_distro_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|l| l.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}' || c == '\0'))
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect()
+}
+
+/// Parse `ss -ltnp` output from inside a distro into ProcessInfo entries
+pub fn parse_ss_output(stdout: &str, distro: &str, ports_filter: &HashSet<u16>) -> Vec<ProcessInfo> {
+    let mut processes = Vec::new();
+
+    for line in stdout.lines() {
+        // Example: LISTEN 0 511 0.0.0.0:3000 0.0.0.0:* users:(("node",pid=1234,fd=20))
+        if !line.starts_with("LISTEN") {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 6 {
+            continue;
+        }
+
+        let port = match parts[3].rsplit(':').next().and_then(|p| p.parse::<u16>().ok()) {
+            Some(p) => p,
+            None => continue,
+        };
+        if !ports_filter.is_empty() && !ports_filter.contains(&port) {
+            continue;
+        }
+
+        let users = parts[5..].join(" ");
+        let name = users
+            .split('"')
+            .nth(1)
+            .unwrap_or("unknown")
+            .to_string();
+        let pid = match users
+            .split("pid=")
+            .nth(1)
+            .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+            .and_then(|p| p.parse::<i32>().ok())
+        {
+            Some(p) => p,
+            None => continue,
+        };
+
+        // The same socket is often reported for both IPv4 and IPv6
+        if processes
+            .iter()
+            .any(|p: &ProcessInfo| p.port == port && p.pid == pid)
+        {
+            continue;
+        }
+
+        let mut process_info = ProcessInfo {
+            pid,
+            port,
+            command: name.clone(),
+            name,
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            wsl_distro: Some(distro.to_string()),
+            user: None,
+            uid: None,
+            started_at: None,
+            uptime_secs: None,
+            ppid: None,
+            gpu_memory: None,
+        };
+        process_info.process_group = process_info.determine_process_group();
+
+        processes.push(process_info);
+    }
+
+    processes
+}
+
+/// List running WSL distros (stopped distros are not started)
+pub fn list_running_distros() -> Vec<String> {
+    match Command::new("wsl.exe").args(["-l", "-q", "--running"]).output() {
+        Ok(output) if output.status.success() => {
+            parse_distro_list(&decode_wsl_output(&output.stdout))
+        }
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            log::debug!("wsl.exe not available: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Enumerate listeners inside every running WSL distro
+pub fn get_wsl_processes_on_ports(ports_filter: &HashSet<u16>) -> Vec<ProcessInfo> {
+    let mut processes = Vec::new();
+
+    for distro in list_running_distros() {
+        let output = Command::new("wsl.exe")
+            .args(["-d", &distro, "-e", "ss", "-ltnp"])
+            .output();
+
+        match output {
+            Ok(output) if output.status.success() => {
+                let stdout = decode_wsl_output(&output.stdout);
+                processes.extend(parse_ss_output(&stdout, &distro, ports_filter));
+            }
+            Ok(output) => {
+                log::warn!(
+                    "Failed to list listeners in WSL distro {}: {}",
+                    distro,
+                    decode_wsl_output(&output.stderr).trim()
+                );
+            }
+            Err(e) => {
+                log::warn!("Failed to run wsl.exe for distro {}: {}", distro, e);
+            }
+        }
+    }
+
+    processes
+}
+
+/// Kill a Linux-side PID inside a WSL distro (SIGTERM, then SIGKILL if still alive)
+pub fn kill_wsl_process(distro: &str, pid: i32) -> Result<()> {
+    let pid_str = pid.to_string();
+    let status = Command::new("wsl.exe")
+        .args(["-d", distro, "-e", "kill", "-TERM", &pid_str])
+        .status()
+        .context("Failed to execute wsl.exe")?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "Failed to send SIGTERM to PID {} in WSL distro {}",
+            pid,
+            distro
+        ));
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    // `kill -0` succeeds only while the process is still alive
+    let alive = Command::new("wsl.exe")
+        .args(["-d", distro, "-e", "kill", "-0", &pid_str])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+
+    if alive {
+        log::warn!(
+            "Process {} in WSL distro {} still running after SIGTERM, sending SIGKILL",
+            pid,
+            distro
+        );
+        let status = Command::new("wsl.exe")
+            .args(["-d", distro, "-e", "kill", "-KILL", &pid_str])
+            .status()
+            .context("Failed to execute wsl.exe")?;
+        if !status.success() {
+            return Err(anyhow::anyhow!(
+                "Failed to kill PID {} in WSL distro {}",
+                pid,
+                distro
+            ));
+        }
+    }
+
+    log::info!("Killed process {} in WSL distro {}", pid, distro);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf16_distro_list() {
+        let raw: Vec<u8> = "Ubuntu\r\nDebian\r\n"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        let distros = parse_distro_list(&decode_wsl_output(&raw));
+        assert_eq!(distros, vec!["Ubuntu".to_string(), "Debian".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_ss_output() {
+        let stdout = "State  Recv-Q Send-Q Local Address:Port Peer Address:Port Process\n\
+LISTEN 0      511          0.0.0.0:3000      0.0.0.0:*    users:((\"node\",pid=1234,fd=20))\n\
+LISTEN 0      511             [::]:3000         [::]:*    users:((\"node\",pid=1234,fd=21))\n\
+LISTEN 0      128        127.0.0.1:5432      0.0.0.0:*    users:((\"postgres\",pid=99,fd=5))\n";
+        let filter: HashSet<u16> = [3000].into_iter().collect();
+        let processes = parse_ss_output(stdout, "Ubuntu", &filter);
+
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].pid, 1234);
+        assert_eq!(processes[0].name, "node");
+        assert_eq!(processes[0].wsl_distro.as_deref(), Some("Ubuntu"));
+    }
+
+    #[test]
+    fn test_is_wsl_host_process() {
+        assert!(is_wsl_host_process("wslrelay.exe"));
+        assert!(is_wsl_host_process("VmmemWSL"));
+        assert!(!is_wsl_host_process("node.exe"));
+    }
+}